@@ -0,0 +1,239 @@
+// Criterion suite: encode, decode and syndrome throughput for RS over GF(256)
+// Run with `cargo bench` (or `cargo bench -- decode` for one group). Every benchmark reports
+// bytes per second of the data it consumes: messages for encode, codewords for everything else.
+// ECC_MODEL_KERNEL=scalar (or avx2, ...) pins the SIMD kernel being measured, except in the
+// kernels group, which runs every kernel the CPU supports.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use _rs::gf256::simd::{self, Kernel};
+use _rs::gf256::GF;
+use _rs::rs::sim::Rng;
+use _rs::rs::split::cached_split_tables;
+use _rs::rs::{
+    berlekamp_massey, berlekamp_massey_into, cached_generator, calc_syndromes, chien_roots_into, chien_search_into, decode_batch_screen_with, encode_batch_soa,
+    encode_blocks_into, encode_scalar, encode_with, forney_into, forney_roots_into, ForneyBuffers, RsCodec, RsDecoder,
+};
+#[cfg(feature = "parallel")]
+use _rs::rs::{decode_batch, encode_batch};
+
+const N: usize = 255;
+const NSYMS: [usize; 4] = [4, 16, 32, 64];
+/// Blocks per iteration of the batch benchmarks
+const BATCH: usize = 1024;
+
+fn random_bytes(rng: &mut Rng, len: usize) -> Vec<u8> {
+    (0..len).map(|_| rng.next_u64() as u8).collect()
+}
+
+/// A codeword of RS(N, N - nsym) with `errors` random symbol errors
+fn received(codec: &RsCodec, rng: &mut Rng, errors: usize) -> Vec<u8> {
+    let mut codeword = codec.encode(&random_bytes(rng, codec.k()));
+    for pos in rng.distinct(errors, N) {
+        codeword[pos] ^= rng.nonzero_byte();
+    }
+    codeword
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("encode/{}", simd::active().name()));
+    let mut rng = Rng::new(1);
+    for nsym in NSYMS {
+        let codec = RsCodec::new(nsym, N).unwrap();
+        let message = random_bytes(&mut rng, codec.k());
+        group.throughput(Throughput::Bytes(codec.k() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(nsym), &message, |b, m| b.iter(|| codec.encode(black_box(m))));
+    }
+    group.finish();
+}
+
+fn bench_syndromes(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("syndromes/{}", simd::active().name()));
+    let mut rng = Rng::new(2);
+    for nsym in NSYMS {
+        let codeword = received(&RsCodec::new(nsym, N).unwrap(), &mut rng, 0);
+        let mut out = vec![0u8; nsym];
+        group.throughput(Throughput::Bytes(N as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(nsym), &codeword, |b, cw| b.iter(|| simd::syndromes(black_box(cw), &mut out)));
+    }
+    group.finish();
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("decode/{}", simd::active().name()));
+    let mut rng = Rng::new(3);
+    group.throughput(Throughput::Bytes(N as u64));
+    for nsym in NSYMS {
+        let codec = RsCodec::new(nsym, N).unwrap();
+        let mut decoder = RsDecoder::new(nsym, N);
+        let t = codec.max_errors();
+        for (label, errors) in [("clean", 0), ("half_t", t / 2), ("t", t)] {
+            let codeword = received(&codec, &mut rng, errors);
+            assert!(decoder.decode_with(&codeword).is_ok());
+            group.bench_with_input(BenchmarkId::new(label, nsym), &codeword, |b, cw| {
+                b.iter(|| decoder.decode_with(black_box(cw)).map(|(m, _)| m.len()))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Decode time at t errors as nsym grows to 254 (RS(255,1)), to show how the decoder scales
+/// in the redundancy: syndromes are O(n * nsym), BM and Chien/Forney O(nsym^2) at t errors
+fn bench_decode_vs_nsym(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("decode_vs_nsym/{}", simd::active().name()));
+    let mut rng = Rng::new(5);
+    group.throughput(Throughput::Bytes(N as u64));
+    for nsym in [2, 16, 64, 128, 192, 254] {
+        let codec = RsCodec::new(nsym, N).unwrap();
+        let mut decoder = RsDecoder::new(nsym, N);
+        let codeword = received(&codec, &mut rng, codec.max_errors());
+        assert!(decoder.decode_with(&codeword).is_ok());
+        group.bench_with_input(BenchmarkId::new("t", nsym), &codeword, |b, cw| b.iter(|| decoder.decode_with(black_box(cw)).map(|(m, _)| m.len())));
+        // Erasures only, the fountain-style use: nsym of the n symbols lost and flagged
+        let mut erased = codec.encode(&random_bytes(&mut rng, codec.k()));
+        let erasures = rng.distinct(nsym, N);
+        for &pos in &erasures {
+            erased[pos] = 0;
+        }
+        group.bench_with_input(BenchmarkId::new("erasures", nsym), &erased, |b, cw| b.iter(|| codec.decode_errata(black_box(cw), &erasures).map(|(m, _)| m.len())));
+    }
+    group.finish();
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("batch/{}", simd::active().name()));
+    let mut rng = Rng::new(4);
+    for nsym in [16, 32] {
+        let codec = RsCodec::new(nsym, N).unwrap();
+        let k = codec.k();
+        let messages = random_bytes(&mut rng, BATCH * k);
+        let mut out = vec![0u8; BATCH * N];
+        group.throughput(Throughput::Bytes((BATCH * k) as u64));
+        group.bench_function(BenchmarkId::new("encode_blocks", nsym), |b| b.iter(|| encode_blocks_into(black_box(&messages), &mut out, nsym, N, false)));
+        group.bench_function(BenchmarkId::new("encode_soa", nsym), |b| b.iter(|| encode_batch_soa(black_box(&messages), k, nsym, &mut out)));
+
+        // Scrubbing: one block in a hundred carries t/2 errors
+        let blocks: Vec<u8> = (0..BATCH).flat_map(|i| received(&codec, &mut rng, if i % 100 == 0 { nsym / 4 } else { 0 })).collect();
+        group.throughput(Throughput::Bytes(blocks.len() as u64));
+        group.bench_function(BenchmarkId::new("decode_screen", nsym), |b| b.iter(|| decode_batch_screen_with(black_box(&blocks), N, nsym, false)));
+    }
+    // A short code, RS(64,60), where the per-block setup the SoA layout amortizes weighs most
+    let (nsym, n) = (4, 64);
+    let messages = random_bytes(&mut rng, BATCH * (n - nsym));
+    let mut out = vec![0u8; BATCH * n];
+    group.throughput(Throughput::Bytes(messages.len() as u64));
+    group.bench_function("encode_blocks/rs64", |b| b.iter(|| encode_blocks_into(black_box(&messages), &mut out, nsym, n, false)));
+    group.bench_function("encode_soa/rs64", |b| b.iter(|| encode_batch_soa(black_box(&messages), n - nsym, nsym, &mut out)));
+    group.finish();
+}
+
+/// Every kernel this CPU supports, scalar included, side by side: RS(255,223) encode and
+/// syndromes, and a 64 KiB slice multiply
+fn bench_kernels(c: &mut Criterion) {
+    let mut group = c.benchmark_group("kernels");
+    let mut rng = Rng::new(7);
+    let nsym = 32;
+    let generator = cached_generator(nsym);
+    let message = random_bytes(&mut rng, N - nsym);
+    let codeword = received(&RsCodec::new(nsym, N).unwrap(), &mut rng, 0);
+    let src = random_bytes(&mut rng, 1 << 16);
+    let mut dst = vec![0u8; 1 << 16];
+    let mut syndromes = vec![0u8; nsym];
+    for kernel in std::iter::once(Kernel::Scalar).chain(simd::COMPILED.iter().copied()).filter(|k| k.supported()) {
+        group.throughput(Throughput::Bytes(message.len() as u64));
+        group.bench_with_input(BenchmarkId::new("encode", kernel.name()), &message, |b, m| b.iter(|| encode_with(kernel, black_box(m), nsym, generator)));
+        group.throughput(Throughput::Bytes(N as u64));
+        group.bench_with_input(BenchmarkId::new("syndromes", kernel.name()), &codeword, |b, cw| b.iter(|| simd::syndromes_with(kernel, black_box(cw), &mut syndromes)));
+        group.throughput(Throughput::Bytes(src.len() as u64));
+        group.bench_function(BenchmarkId::new("mul_add_slice", kernel.name()), |b| b.iter(|| simd::mul_add_slice_with(kernel, &mut dst, black_box(&src), 0x8e)));
+    }
+    group.finish();
+}
+
+/// RS(255,223) encode with the portable split tables against the byte-at-a-time scalar loop
+fn bench_split_encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("split_encode");
+    let mut rng = Rng::new(8);
+    let nsym = 32;
+    let message = random_bytes(&mut rng, N - nsym);
+    let (generator, tables) = (cached_generator(nsym), cached_split_tables(nsym));
+    group.throughput(Throughput::Bytes(message.len() as u64));
+    group.bench_with_input("scalar", &message, |b, m| b.iter(|| encode_scalar(black_box(m), nsym, generator)));
+    group.bench_with_input("split_tables", &message, |b, m| b.iter(|| tables.encode(black_box(m))));
+    group.finish();
+}
+
+/// Berlekamp-Massey on random (full-weight) syndromes, fresh vectors per call against buffers
+/// reused across calls
+fn bench_berlekamp_massey(c: &mut Criterion) {
+    let mut group = c.benchmark_group("berlekamp_massey");
+    let mut rng = Rng::new(9);
+    for nsym in [16, 32, 64] {
+        let syndromes = random_bytes(&mut rng, nsym);
+        let (mut sigma, mut prev) = (Vec::new(), Vec::new());
+        group.bench_with_input(BenchmarkId::new("allocating", nsym), &syndromes, |b, s| b.iter(|| berlekamp_massey(black_box(s))));
+        group.bench_with_input(BenchmarkId::new("buffers", nsym), &syndromes, |b, s| {
+            b.iter(|| berlekamp_massey_into(&GF, black_box(s), &mut sigma, &mut prev))
+        });
+    }
+    group.finish();
+}
+
+/// Chien search and Forney on a t-error block: positions into the full-Omega forney_into against
+/// roots into forney_roots_into
+fn bench_chien_forney(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chien_forney");
+    let mut rng = Rng::new(10);
+    for nsym in [16, 32, 64] {
+        let codeword = received(&RsCodec::new(nsym, N).unwrap(), &mut rng, nsym / 2);
+        let syndromes = calc_syndromes(&codeword, nsym);
+        let sigma = berlekamp_massey(&syndromes);
+        let (mut positions, mut roots, mut buffers) = (Vec::new(), Vec::new(), ForneyBuffers::default());
+        group.bench_function(BenchmarkId::new("positions", nsym), |b| {
+            b.iter(|| {
+                chien_search_into(black_box(&sigma), N, &mut positions);
+                forney_into(&syndromes, &sigma, &positions, N, &mut buffers);
+            })
+        });
+        group.bench_function(BenchmarkId::new("roots", nsym), |b| {
+            b.iter(|| {
+                chien_roots_into(black_box(&sigma), N, &mut roots);
+                forney_roots_into(&syndromes, &sigma, &roots, &mut buffers);
+            })
+        });
+    }
+    group.finish();
+}
+
+/// encode_batch then decode_batch of RS(255,223) blocks in rayon pools of 1 to 8 threads
+#[cfg(feature = "parallel")]
+fn bench_batch_scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("batch_scaling/{}", simd::active().name()));
+    let mut rng = Rng::new(6);
+    let (nsym, k) = (32, N - 32);
+    let data = random_bytes(&mut rng, BATCH * k);
+    let messages: Vec<&[u8]> = data.chunks(k).collect();
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    for threads in [1, 2, 4, 8] {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        group.bench_function(BenchmarkId::new("encode_decode", threads), |b| {
+            b.iter(|| {
+                pool.install(|| {
+                    let codewords = encode_batch(black_box(&messages), nsym, N);
+                    let refs: Vec<&[u8]> = codewords.iter().map(|c| c.as_slice()).collect();
+                    decode_batch(&refs, nsym).len()
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "parallel"))]
+fn bench_batch_scaling(_: &mut Criterion) {}
+
+criterion_group!(benches, bench_encode, bench_syndromes, bench_kernels, bench_split_encode, bench_decode, bench_berlekamp_massey, bench_chien_forney, bench_decode_vs_nsym, bench_batch, bench_batch_scaling);
+criterion_main!(benches);
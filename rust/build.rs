@@ -0,0 +1,27 @@
+// With the `capi` feature, generate ecc_rs.h from src/ffi.rs into OUT_DIR (cbindgen.toml has the
+// naming and prologue) and tell tests/capi.rs where it is, so it can check that the committed
+// include/ecc_rs.h is current, and which target it builds the C test for. Builds never write
+// to the source tree unless asked: ECC_RS_WRITE_HEADER=1 also refreshes include/ecc_rs.h.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "capi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        println!("cargo:rerun-if-env-changed=ECC_RS_WRITE_HEADER");
+        println!("cargo:rustc-env=ECC_RS_TARGET={}", std::env::var("TARGET").unwrap());
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let header = format!("{}/ecc_rs.h", std::env::var("OUT_DIR").unwrap());
+        let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", dir)).expect("reading cbindgen.toml");
+        let bindings = cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(format!("{}/src/ffi.rs", dir))
+            .generate()
+            .expect("generating ecc_rs.h");
+        bindings.write_to_file(&header);
+        println!("cargo:rustc-env=ECC_RS_HEADER={}", header);
+        if std::env::var_os("ECC_RS_WRITE_HEADER").is_some_and(|v| v == "1") {
+            bindings.write_to_file(format!("{}/include/ecc_rs.h", dir));
+        }
+    }
+}
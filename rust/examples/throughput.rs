@@ -0,0 +1,46 @@
+// One-line GB/s summary for RS(255,223) on this machine, for quick comparisons between hosts
+// cargo run --release --example throughput [-- seconds-per-measurement]
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use _rs::gf256::simd;
+use _rs::rs::sim::Rng;
+use _rs::rs::{RsCodec, RsDecoder};
+
+/// GB/s for `bytes` per call of f, running f for about `budget`
+fn rate(budget: Duration, bytes: usize, mut f: impl FnMut()) -> f64 {
+    let (start, mut calls) = (Instant::now(), 0u64);
+    while start.elapsed() < budget {
+        for _ in 0..64 {
+            f();
+        }
+        calls += 64;
+    }
+    (calls * bytes as u64) as f64 / start.elapsed().as_secs_f64() / 1e9
+}
+
+fn main() {
+    let seconds: f64 = std::env::args().nth(1).and_then(|s| s.parse().ok()).unwrap_or(0.5);
+    let budget = Duration::from_secs_f64(seconds);
+    let (nsym, n) = (32, 255);
+    let codec = RsCodec::new(nsym, n).unwrap();
+    let mut decoder = RsDecoder::new(nsym, n);
+    let mut rng = Rng::new(1);
+    let message: Vec<u8> = (0..codec.k()).map(|_| rng.next_u64() as u8).collect();
+    let clean = codec.encode(&message);
+    let mut dirty = clean.clone();
+    for pos in rng.distinct(codec.max_errors(), n) {
+        dirty[pos] ^= rng.nonzero_byte();
+    }
+    let mut syndromes = vec![0u8; nsym];
+
+    let encode = rate(budget, codec.k(), || drop(black_box(codec.encode(black_box(&message)))));
+    let check = rate(budget, n, || simd::syndromes(black_box(&clean), &mut syndromes));
+    let decode_clean = rate(budget, n, || assert!(decoder.decode_with(black_box(&clean)).is_ok()));
+    let decode_t = rate(budget, n, || assert!(decoder.decode_with(black_box(&dirty)).is_ok()));
+    println!(
+        "RS({},{}) kernel={}: encode {:.3} GB/s, syndromes {:.3} GB/s, decode clean {:.3} GB/s, decode {} errors {:.3} GB/s",
+        n, codec.k(), simd::active().name(), encode, check, decode_clean, codec.max_errors(), decode_t
+    );
+}
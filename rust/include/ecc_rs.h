@@ -0,0 +1,102 @@
+/* C API of ecc_model_rs; see src/ffi.rs. Generated by cbindgen, do not edit. */
+
+#ifndef ECC_RS_H
+#define ECC_RS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Result of every ecc_rs_* call; the values are stable across releases
+ */
+enum EccRsStatus
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  ECC_RS_STATUS_OK = 0,
+  /**
+   * (nsym, nsize) is not a GF(256) RS code: 1 <= nsym < nsize <= 255 is required
+   */
+  ECC_RS_STATUS_INVALID_PARAMETERS = -1,
+  /**
+   * A required pointer was NULL
+   */
+  ECC_RS_STATUS_NULL_POINTER = -2,
+  /**
+   * The message is not nsize - nsym bytes or the codeword is not nsize bytes
+   */
+  ECC_RS_STATUS_LENGTH_MISMATCH = -3,
+  /**
+   * An output buffer's capacity is too small for the result
+   */
+  ECC_RS_STATUS_BUFFER_TOO_SMALL = -4,
+  /**
+   * More errors than the code can correct; the outputs are not written
+   */
+  ECC_RS_STATUS_UNCORRECTABLE = -5,
+  /**
+   * A bug inside the library; the message says where
+   */
+  ECC_RS_STATUS_PANIC = -6,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum EccRsStatus EccRsStatus;
+#else
+typedef int32_t EccRsStatus;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Encode a k = nsize - nsym byte message into the nsize-byte codeword [message | parity]
+ * Writes nsize bytes to out, whose capacity out_cap must be at least nsize.
+ *
+ * # Safety
+ * msg must point to msg_len readable bytes and out to out_cap writable bytes that do not
+ * overlap msg.
+ */
+int32_t ecc_rs_encode(size_t nsym,
+                      size_t nsize,
+                      const uint8_t *msg,
+                      size_t msg_len,
+                      uint8_t *out,
+                      size_t out_cap);
+
+/**
+ * Decode an nsize-byte codeword, correcting up to nsym / 2 symbol errors
+ * On ECC_RS_STATUS_OK the k-byte message is written to msg_out (capacity msg_cap >= k), the
+ * corrected codeword positions, ascending, to positions_out, and their count to *num_positions.
+ * positions_cap of nsym / 2 always suffices; a smaller one fails with
+ * ECC_RS_STATUS_BUFFER_TOO_SMALL only when more positions than that were corrected. On any
+ * failure no output is written.
+ *
+ * # Safety
+ * codeword must point to codeword_len readable bytes, msg_out to msg_cap writable bytes,
+ * positions_out to positions_cap writable entries and num_positions to one, none overlapping.
+ */
+int32_t ecc_rs_decode(size_t nsym,
+                      size_t nsize,
+                      const uint8_t *codeword,
+                      size_t codeword_len,
+                      uint8_t *msg_out,
+                      size_t msg_cap,
+                      size_t *positions_out,
+                      size_t positions_cap,
+                      size_t *num_positions);
+
+/**
+ * NUL-terminated description of the last failed ecc_rs_* call on this thread, or "" if it
+ * succeeded; owned by the library and valid until the next ecc_rs_* call on this thread
+ */
+const char *ecc_rs_last_error_message(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ECC_RS_H */
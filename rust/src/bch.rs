@@ -0,0 +1,251 @@
+// Binary BCH codes over GF(2^m), m <= 13
+// Systematic encoding on bit vectors: codeword = [data bits | parity bits], one bit per u8 (0 or 1)
+// Position mapping matches rs.rs: position 0 = highest power of x (x^(n-1) coefficient)
+// Narrow-sense code: generator roots are alpha^1 .. alpha^(2t)
+
+use crate::gf2m::Gf2m;
+use crate::rs::{berlekamp_massey_in, RsError};
+
+/// Largest field supported for BCH (tables for m = 13 are 8191 entries)
+pub const MAX_M: u32 = 13;
+
+/// A BCH(n, k, t) code, possibly shortened (n < 2^m - 1)
+pub struct Bch {
+    field: Gf2m,
+    n: usize,
+    k: usize,
+    t: usize,
+    generator: Vec<u8>, // generator[i] = coefficient of x^i in GF(2)
+}
+
+impl Bch {
+    /// Build a t-error-correcting BCH code of length n over GF(2^m)
+    /// Returns None if m is unsupported, n exceeds 2^m - 1, or the generator leaves no data bits
+    pub fn new(m: u32, n: usize, t: usize) -> Option<Self> {
+        if m > MAX_M || t == 0 {
+            return None;
+        }
+        let field = Gf2m::with_default_poly(m)?;
+        if n > field.order() {
+            return None;
+        }
+        let generator = build_generator(&field, t);
+        let parity_bits = generator.len() - 1;
+        if parity_bits >= n {
+            return None;
+        }
+        Some(Self { field, n, k: n - parity_bits, t, generator })
+    }
+
+    /// BCH(255, 239, 2) over GF(2^8)
+    pub fn bch_255_239_2() -> Self {
+        Self::new(8, 255, 2).expect("BCH(255,239,2) preset")
+    }
+
+    /// BCH(1023, 983, 4) over GF(2^10)
+    pub fn bch_1023_983_4() -> Self {
+        Self::new(10, 1023, 4).expect("BCH(1023,983,4) preset")
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn t(&self) -> usize {
+        self.t
+    }
+
+    /// Generator polynomial over GF(2), generator[i] = coefficient of x^i
+    pub fn generator(&self) -> &[u8] {
+        &self.generator
+    }
+
+    /// Systematic encode of k message bits into n codeword bits
+    pub fn encode(&self, message: &[u8]) -> Vec<u8> {
+        assert_eq!(message.len(), self.k, "BCH message must be k={} bits", self.k);
+        let r = self.n - self.k;
+        let mut codeword = vec![0u8; self.n];
+        codeword[..self.k].copy_from_slice(message);
+
+        // Binary long division: message(x) * x^r mod g(x)
+        for i in 0..self.k {
+            if codeword[i] & 1 != 0 {
+                for j in 0..=r {
+                    codeword[i + j] ^= self.generator[r - j];
+                }
+            }
+        }
+
+        codeword[..self.k].copy_from_slice(message);
+        codeword
+    }
+
+    /// Syndromes S_j = r(alpha^j) for j = 1..2t
+    pub fn syndromes(&self, codeword: &[u8]) -> Vec<u16> {
+        let n = codeword.len();
+        let mut syndromes = vec![0u16; 2 * self.t];
+        for (idx, _) in codeword.iter().enumerate().filter(|(_, &bit)| bit & 1 != 0) {
+            let power = n - 1 - idx;
+            for (j, s) in syndromes.iter_mut().enumerate() {
+                *s ^= self.field.exp((j + 1) * power);
+            }
+        }
+        syndromes
+    }
+
+    /// Decode n codeword bits, returning the k message bits and the corrected bit positions
+    pub fn decode(&self, codeword: &[u8]) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+        let n = codeword.len();
+        if n != self.n {
+            return Err(RsError::CodewordTooShort);
+        }
+
+        let syndromes = self.syndromes(codeword);
+        if syndromes.iter().all(|&s| s == 0) {
+            return Ok((codeword[..self.k].to_vec(), vec![]));
+        }
+
+        let sigma = berlekamp_massey_in(&self.field, &syndromes);
+        let num_errors = sigma.len() - 1;
+        if num_errors == 0 {
+            return Err(RsError::TrivialLocator);
+        }
+        if num_errors > self.t {
+            return Err(RsError::TooManyErrors);
+        }
+
+        // Chien search over bit positions; magnitudes are always 1 so no Forney step
+        let order = self.field.order();
+        let positions: Vec<usize> = (0..n)
+            .filter(|&pos| {
+                let x_inv = self.field.exp(order - (n - 1 - pos) % order);
+                sigma.iter().rev().fold(0u16, |acc, &coef| self.field.mul(acc, x_inv) ^ coef) == 0
+            })
+            .collect();
+        if positions.len() != num_errors {
+            return Err(RsError::ChienSearchFailed);
+        }
+
+        let mut corrected = codeword.to_vec();
+        for &pos in &positions {
+            corrected[pos] ^= 1;
+        }
+        if self.syndromes(&corrected).iter().any(|&s| s != 0) {
+            return Err(RsError::VerificationFailed);
+        }
+
+        Ok((corrected[..self.k].to_vec(), positions))
+    }
+}
+
+/// Generator = LCM of the minimal polynomials of alpha^1 .. alpha^(2t)
+/// Each cyclotomic coset {i, 2i, 4i, ...} mod 2^m - 1 contributes its minimal polynomial once
+fn build_generator(field: &Gf2m, t: usize) -> Vec<u8> {
+    let order = field.order();
+    let mut covered = vec![false; order];
+    let mut generator = vec![1u8];
+
+    for i in 1..=2 * t {
+        let i = i % order;
+        if covered[i] {
+            continue;
+        }
+        // Minimal polynomial prod_{c in coset} (x + alpha^c), computed over GF(2^m)
+        let mut min_poly = vec![1u16];
+        let mut c = i;
+        loop {
+            covered[c] = true;
+            let root = field.exp(c);
+            let mut next = vec![0u16; min_poly.len() + 1];
+            for (d, &coef) in min_poly.iter().enumerate() {
+                next[d] ^= field.mul(coef, root);
+                next[d + 1] ^= coef;
+            }
+            min_poly = next;
+            c = (2 * c) % order;
+            if c == i {
+                break;
+            }
+        }
+        debug_assert!(min_poly.iter().all(|&coef| coef <= 1), "minimal polynomial must be binary");
+
+        // Multiply into the generator over GF(2)
+        let mut product = vec![0u8; generator.len() + min_poly.len() - 1];
+        for (a, &ga) in generator.iter().enumerate() {
+            for (b, &mb) in min_poly.iter().enumerate() {
+                product[a + b] ^= ga & (mb as u8);
+            }
+        }
+        generator = product;
+    }
+    generator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    fn random_bits(len: usize, rng: &mut Rng) -> Vec<u8> {
+        (0..len).map(|_| (rng.next_u64() & 1) as u8).collect()
+    }
+
+    #[test]
+    fn test_preset_dimensions() {
+        let a = Bch::bch_255_239_2();
+        assert_eq!((a.n(), a.k(), a.t()), (255, 239, 2));
+        let b = Bch::bch_1023_983_4();
+        assert_eq!((b.n(), b.k(), b.t()), (1023, 983, 4));
+        // Hamming(7,4) is the t = 1 BCH code over GF(8): g(x) = x^3 + x + 1
+        let hamming = Bch::new(3, 7, 1).unwrap();
+        assert_eq!(hamming.generator(), &[1, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_round_trip_up_to_t_errors() {
+        let mut rng = Rng::new(0x1234_5678_9abc_def0);
+        for code in [Bch::bch_255_239_2(), Bch::bch_1023_983_4(), Bch::new(13, 500, 3).unwrap()] {
+            for trial in 0..20 {
+                let message = random_bits(code.k(), &mut rng);
+                let mut codeword = code.encode(&message);
+                assert!(code.syndromes(&codeword).iter().all(|&s| s == 0));
+
+                let errors = rng.distinct(trial % (code.t() + 1), code.n());
+                for &pos in &errors {
+                    codeword[pos] ^= 1;
+                }
+                let (decoded, positions) = code.decode(&codeword).unwrap();
+                assert_eq!(decoded, message);
+                assert_eq!(positions, errors);
+            }
+        }
+    }
+
+    #[test]
+    fn test_beyond_t_not_decoded_to_original() {
+        let mut rng = Rng::new(0x0bad_cafe);
+        let code = Bch::bch_255_239_2();
+        for _ in 0..50 {
+            let message = random_bits(code.k(), &mut rng);
+            let mut codeword = code.encode(&message);
+            for pos in rng.distinct(code.t() + 1, code.n()) {
+                codeword[pos] ^= 1;
+            }
+            // t+1 errors are either detected or miscorrected to another codeword, never repaired
+            if let Ok((decoded, _)) = code.decode(&codeword) {
+                assert_ne!(decoded, message);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_parameters() {
+        assert!(Bch::new(14, 1000, 2).is_none());
+        assert!(Bch::new(8, 256, 2).is_none());
+        assert!(Bch::new(4, 15, 8).is_none());
+    }
+}
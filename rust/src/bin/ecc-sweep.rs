@@ -0,0 +1,301 @@
+// ecc-sweep: Monte Carlo reliability over a parameter grid, as a CSV for pandas (`sim` feature)
+// Every combination of n, nsym, interleave depth and channel point runs rs::sim::simulate_interleaved
+// with the same trial count and seed (common random numbers, so neighbouring points differ by
+// the parameter and not by luck), each point's trials spread over rayon's pool and, with the
+// `parallel` feature, the points as well. Rows are written in grid order and flushed one by one;
+// rerunning with the same output file skips the points already in it, so a long sweep that was
+// stopped picks up where it left off (a torn last line is dropped first). Outcome columns count
+// codewords: clean, ce (corrected), due (detected, uncorrected) and sdc (miscorrected).
+// Exit status: 0 on success, 2 for usage, spec and I/O errors.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+#[cfg(feature = "parallel")]
+use std::sync::Mutex;
+use std::time::Instant;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use _rs::rs::sim::{simulate_interleaved, ChannelModel, SimStats};
+use _rs::rs::RsCodec;
+
+const USAGE: &str = "usage:
+  ecc-sweep [--spec FILE] [--n LIST] [--nsym LIST] [--depth LIST] [--ser LIST] [--ber LIST]
+            [--burst LIST] [--errors LIST] [--trials N] [--seed N] [--quiet] OUT.csv
+LISTs are comma-separated. A spec file holds the same keys TOML-style, one per line:
+  n = [255, 128]
+  ser = [1e-3, 1e-2]   # symbol error rates; ber = bit error rates, burst = lengths,
+                       # errors = exact symbol error counts
+  trials = 100000
+Options on the command line override the spec. depth defaults to [1], trials to 10000, seed to 0.";
+
+const HEADER: &str = "n,nsym,depth,channel,param,trials,seed,codewords,clean,ce,due,sdc";
+
+/// The grid, each key a list of values as written
+#[derive(Debug, Default)]
+struct Spec {
+    values: BTreeMap<String, Vec<String>>,
+}
+
+const KEYS: [&str; 9] = ["n", "nsym", "depth", "ser", "ber", "burst", "errors", "trials", "seed"];
+
+impl Spec {
+    fn set(&mut self, key: &str, list: &str) -> Result<(), String> {
+        if !KEYS.contains(&key) {
+            return Err(format!("unknown key {:?}", key));
+        }
+        let values: Vec<String> = list.split(',').map(|v| v.trim().to_owned()).filter(|v| !v.is_empty()).collect();
+        if values.is_empty() {
+            return Err(format!("{} needs at least one value", key));
+        }
+        self.values.insert(key.to_owned(), values);
+        Ok(())
+    }
+
+    /// `key = value` and `key = [a, b]` lines, with # comments
+    fn read_toml(&mut self, text: &str) -> Result<(), String> {
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| format!("line {}: expected key = value", number + 1))?;
+            let value = value.trim();
+            let list = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')).unwrap_or(value);
+            self.set(key.trim(), list).map_err(|e| format!("line {}: {}", number + 1, e))?;
+        }
+        Ok(())
+    }
+
+    fn list<T: std::str::FromStr + Clone>(&self, key: &str, default: &[T]) -> Result<Vec<T>, String> {
+        match self.values.get(key) {
+            None => Ok(default.to_vec()),
+            Some(values) => values.iter().map(|v| v.parse().map_err(|_| format!("{}: cannot parse {:?}", key, v))).collect(),
+        }
+    }
+
+    fn single<T: std::str::FromStr + Clone>(&self, key: &str, default: T) -> Result<T, String> {
+        match self.list(key, &[default])?.as_slice() {
+            [value] => Ok(value.clone()),
+            _ => Err(format!("{} takes one value", key)),
+        }
+    }
+}
+
+struct Args {
+    spec: Spec,
+    quiet: bool,
+    out: PathBuf,
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let (mut file_spec, mut cli) = (Spec::default(), Spec::default());
+    let (mut quiet, mut out) = (false, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--quiet" => quiet = true,
+            "--spec" => {
+                let path = args.next().ok_or("--spec needs a value")?;
+                let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+                file_spec.read_toml(&text).map_err(|e| format!("{}: {}", path, e))?;
+            }
+            _ if arg.starts_with("--") => {
+                if !KEYS.contains(&&arg[2..]) {
+                    return Err(format!("unknown option {}", arg));
+                }
+                let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+                cli.set(&arg[2..], &value)?;
+            }
+            _ if out.is_none() => out = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument {:?}", arg)),
+        }
+    }
+    file_spec.values.extend(cli.values);
+    Ok(Args { spec: file_spec, quiet, out: out.ok_or("missing output path")? })
+}
+
+/// One grid point
+#[derive(Debug, Clone, PartialEq)]
+struct Point {
+    n: usize,
+    nsym: usize,
+    depth: usize,
+    channel: ChannelModel,
+    trials: u64,
+    seed: u64,
+}
+
+impl Point {
+    /// The parameter columns, which identify the point in an existing file
+    fn key(&self) -> String {
+        let (name, param) = match self.channel {
+            ChannelModel::SymbolErrorRate(p) => ("ser", p.to_string()),
+            ChannelModel::BitErrorRate(p) => ("ber", p.to_string()),
+            ChannelModel::Burst(len) => ("burst", len.to_string()),
+            ChannelModel::SymbolErrors(count) => ("errors", count.to_string()),
+            _ => unreachable!("not a sweep channel"),
+        };
+        format!("{},{},{},{},{},{},{}", self.n, self.nsym, self.depth, name, param, self.trials, self.seed)
+    }
+
+    fn row(&self, stats: &SimStats) -> String {
+        format!("{},{},{},{},{},{}", self.key(), stats.trials, stats.clean, stats.corrected, stats.detected, stats.miscorrected)
+    }
+
+    fn run(&self) -> SimStats {
+        let codec = RsCodec::new(self.nsym, self.n).expect("validated by grid");
+        simulate_interleaved(&codec, self.depth, &self.channel, self.trials, self.seed).stats
+    }
+}
+
+/// Every point of the spec, n outermost and the channel points innermost
+fn grid(spec: &Spec) -> Result<Vec<Point>, String> {
+    let ns: Vec<usize> = spec.list("n", &[])?;
+    let nsyms: Vec<usize> = spec.list("nsym", &[])?;
+    let depths: Vec<usize> = spec.list("depth", &[1])?;
+    let trials = spec.single("trials", 10_000u64)?;
+    let seed = spec.single("seed", 0u64)?;
+    let mut channels: Vec<ChannelModel> = vec![];
+    channels.extend(spec.list("ser", &[])?.into_iter().map(ChannelModel::SymbolErrorRate));
+    channels.extend(spec.list("ber", &[])?.into_iter().map(ChannelModel::BitErrorRate));
+    channels.extend(spec.list("burst", &[])?.into_iter().map(ChannelModel::Burst));
+    channels.extend(spec.list("errors", &[])?.into_iter().map(ChannelModel::SymbolErrors));
+    if ns.is_empty() || nsyms.is_empty() || channels.is_empty() {
+        return Err("the grid needs n, nsym and at least one of ser, ber, burst, errors".into());
+    }
+    for channel in &channels {
+        if let ChannelModel::SymbolErrorRate(p) | ChannelModel::BitErrorRate(p) = channel {
+            if !(0.0..=1.0).contains(p) {
+                return Err(format!("error rate {} is not a probability", p));
+            }
+        }
+    }
+    let mut points = vec![];
+    for &n in &ns {
+        for &nsym in &nsyms {
+            RsCodec::new(nsym, n).map_err(|e| format!("n={} nsym={}: {}", n, nsym, e))?;
+            for &depth in &depths {
+                if depth == 0 {
+                    return Err("depth must be at least 1".into());
+                }
+                for channel in &channels {
+                    if let ChannelModel::Burst(len) | ChannelModel::SymbolErrors(len) = *channel {
+                        if len > n * depth {
+                            return Err(format!("{} symbols do not fit in n={} depth={}", len, n, depth));
+                        }
+                    }
+                }
+                points.extend(channels.iter().map(|channel| Point { n, nsym, depth, channel: channel.clone(), trials, seed }));
+            }
+        }
+    }
+    Ok(points)
+}
+
+/// Keys of the rows already in `path`, which is trimmed to its last complete line; creates it
+/// with the header if it does not exist
+fn resume(path: &PathBuf) -> Result<HashSet<String>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    let complete = &text[..text.rfind('\n').map_or(0, |i| i + 1)];
+    let mut lines = complete.lines();
+    match lines.next() {
+        None => fs::write(path, format!("{}\n", HEADER)).map_err(|e| format!("{}: {}", path.display(), e))?,
+        Some(HEADER) => {
+            if complete.len() < text.len() {
+                fs::write(path, complete).map_err(|e| format!("{}: {}", path.display(), e))?;
+            }
+        }
+        Some(other) => return Err(format!("{} has header {:?}, not this tool's; not appending to it", path.display(), other)),
+    }
+    Ok(lines.map(|line| line.splitn(8, ',').take(7).collect::<Vec<_>>().join(",")).collect())
+}
+
+/// Rows finished out of order, held until the ones before them are written
+struct Writer {
+    file: File,
+    next: usize,
+    pending: BTreeMap<usize, String>,
+    done: usize,
+    error: Option<io::Error>,
+}
+
+impl Writer {
+    fn finish(&mut self, index: usize, row: String) {
+        self.done += 1;
+        self.pending.insert(index, row);
+        while let Some(row) = self.pending.remove(&self.next) {
+            self.next += 1;
+            if self.error.is_none() {
+                if let Err(e) = writeln!(self.file, "{}", row).and_then(|_| self.file.flush()) {
+                    self.error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let points = grid(&args.spec)?;
+    let existing = resume(&args.out)?;
+    let todo: Vec<Point> = points.iter().filter(|p| !existing.contains(&p.key())).cloned().collect();
+    if !args.quiet {
+        eprintln!("ecc-sweep: {} points, {} already in {}, {} to run", points.len(), points.len() - todo.len(), args.out.display(), todo.len());
+    }
+    let file = OpenOptions::new().append(true).open(&args.out).map_err(|e| format!("{}: {}", args.out.display(), e))?;
+    let mut writer = Writer { file, next: 0, pending: BTreeMap::new(), done: 0, error: None };
+    let total = todo.len();
+    let run_point = |writer: &mut Writer, index: usize, point: &Point, stats: SimStats, started: Instant| {
+        writer.finish(index, point.row(&stats));
+        if !args.quiet {
+            eprintln!(
+                "[{}/{}] {}: {} codewords, ce {} due {} sdc {} ({:.2} s)",
+                writer.done, total, point.key(), stats.trials, stats.corrected, stats.detected, stats.miscorrected, started.elapsed().as_secs_f64()
+            );
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    {
+        let writer = Mutex::new(&mut writer);
+        todo.par_iter().enumerate().for_each(|(index, point)| {
+            let started = Instant::now();
+            let stats = point.run();
+            run_point(&mut writer.lock().unwrap(), index, point, stats, started);
+        });
+    }
+    #[cfg(not(feature = "parallel"))]
+    for (index, point) in todo.iter().enumerate() {
+        let started = Instant::now();
+        let stats = point.run();
+        run_point(&mut writer, index, point, stats, started);
+    }
+    match writer.error {
+        Some(e) => Err(format!("{}: {}", args.out.display(), e)),
+        None => Ok(()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("ecc-sweep: {}\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("ecc-sweep: {}", message);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,166 @@
+// rs-tool: encode, decode, verify and repair ECRS files from the shell (`cli` feature)
+// A thin front end over rs::file, which streams one codeword or interleave group at a time, so
+// memory stays bounded by the code size however large the file. Encoded files are the
+// rs::container format ecc_model._rs.encode_file writes; its decode_file and repair_file read
+// them, and vice versa. Exit status: 0 on success, 1 if decode or repair left a block
+// uncorrected or verify found a dirty block or a damaged header copy, 2 for usage and I/O errors.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use _rs::rs::{decode_file, encode_file_interleaved, repair_file, verify_file, FileError};
+
+const USAGE: &str = "usage:
+  rs-tool encode [--nsym N] [--nsize N] [--interleave D] [--json] IN OUT
+  rs-tool decode [--json] IN OUT
+  rs-tool verify [--json] IN
+  rs-tool repair [--json] FILE";
+
+struct Args {
+    command: String,
+    nsym: usize,
+    nsize: usize,
+    interleave: usize,
+    json: bool,
+    paths: Vec<PathBuf>,
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let command = args.next().ok_or("missing command")?;
+    let mut parsed = Args { command, nsym: 32, nsize: 255, interleave: 1, json: false, paths: vec![] };
+    let mut code_options = vec![];
+    while let Some(arg) = args.next() {
+        let slot = match arg.as_str() {
+            "--json" => {
+                parsed.json = true;
+                continue;
+            }
+            "--nsym" => &mut parsed.nsym,
+            "--nsize" => &mut parsed.nsize,
+            "--interleave" => &mut parsed.interleave,
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => {
+                parsed.paths.push(arg.into());
+                continue;
+            }
+        };
+        let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+        *slot = value.parse().map_err(|_| format!("{} needs a non-negative integer, got {:?}", arg, value))?;
+        code_options.push(arg);
+    }
+    let paths = match parsed.command.as_str() {
+        "encode" | "decode" => 2,
+        "verify" | "repair" => 1,
+        other => return Err(format!("unknown command {:?}", other)),
+    };
+    if parsed.command != "encode" {
+        if let Some(option) = code_options.first() {
+            return Err(format!("{} applies only to encode; {} reads the parameters from the header", option, parsed.command));
+        }
+    }
+    if parsed.paths.len() != paths {
+        return Err(format!("{} takes {} path(s), got {}", parsed.command, paths, parsed.paths.len()));
+    }
+    Ok(parsed)
+}
+
+fn list(values: &[u64]) -> String {
+    values.iter().map(u64::to_string).collect::<Vec<_>>().join(",")
+}
+
+/// Print the summary and return the exit status
+fn run(args: &Args) -> Result<u8, FileError> {
+    let paths = &args.paths;
+    match args.command.as_str() {
+        "encode" => {
+            let header = encode_file_interleaved(&paths[0], &paths[1], args.nsym, args.nsize, args.interleave, None, 1)?;
+            if args.json {
+                println!(
+                    "{{\"command\":\"encode\",\"nsym\":{},\"nsize\":{},\"interleave\":{},\"length\":{},\"blocks\":{}}}",
+                    header.nsym, header.nsize, header.depth, header.original_len, header.blocks()
+                );
+            } else {
+                println!(
+                    "encoded {} bytes into {} blocks of RS({},{}), interleave {}",
+                    header.original_len, header.blocks(), header.nsize, header.nsize - header.nsym, header.depth
+                );
+            }
+            Ok(0)
+        }
+        "decode" => {
+            let report = decode_file(&paths[0], &paths[1], None, 1)?;
+            if args.json {
+                println!(
+                    "{{\"command\":\"decode\",\"length\":{},\"blocks\":{},\"corrected_blocks\":{},\"corrected_symbols\":{},\"failed_offsets\":[{}]}}",
+                    report.original_len, report.blocks, report.corrected_blocks, report.corrected_symbols, list(&report.failed_offsets)
+                );
+            } else {
+                println!(
+                    "decoded {} bytes from {} blocks: {} corrected ({} symbols), {} unrecoverable",
+                    report.original_len, report.blocks, report.corrected_blocks, report.corrected_symbols, report.failed_offsets.len()
+                );
+                if !report.failed_offsets.is_empty() {
+                    println!("unrecoverable blocks, written uncorrected, at data offsets {}", list(&report.failed_offsets));
+                }
+            }
+            Ok(u8::from(!report.failed_offsets.is_empty()))
+        }
+        "verify" => {
+            let report = verify_file(&paths[0])?;
+            let intact = report.header.is_intact();
+            if args.json {
+                println!(
+                    "{{\"command\":\"verify\",\"blocks\":{},\"dirty_blocks\":[{}],\"header_intact\":{}}}",
+                    report.blocks, list(&report.dirty), intact
+                );
+            } else {
+                if report.dirty.is_empty() {
+                    println!("{} blocks, all clean", report.blocks);
+                } else {
+                    println!("{} blocks, {} with nonzero syndromes: {}", report.blocks, report.dirty.len(), list(&report.dirty));
+                }
+                if !intact {
+                    let trailer = report.header.trailer.map_or_else(|| "absent".to_owned(), |t| t.to_string());
+                    println!("header copies need repair: leading {}, trailer {}", report.header.leading, trailer);
+                }
+            }
+            Ok(u8::from(!report.dirty.is_empty() || !intact))
+        }
+        _ => {
+            let report = repair_file(&paths[0], None, None)?;
+            let repaired: Vec<u64> = report.repaired.iter().map(|&(block, _)| block).collect();
+            if args.json {
+                println!(
+                    "{{\"command\":\"repair\",\"blocks\":{},\"repaired_blocks\":[{}],\"unrecoverable_blocks\":[{}],\"headers_rewritten\":{}}}",
+                    report.blocks, list(&repaired), list(&report.unrecoverable), report.headers_rewritten
+                );
+            } else {
+                println!(
+                    "{} blocks: {} repaired, {} unrecoverable, {} header copies rewritten",
+                    report.blocks, repaired.len(), report.unrecoverable.len(), report.headers_rewritten
+                );
+                if !report.unrecoverable.is_empty() {
+                    println!("unrecoverable blocks, left as they were: {}", list(&report.unrecoverable));
+                }
+            }
+            Ok(u8::from(!report.unrecoverable.is_empty()))
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("rs-tool: {}\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(status) => ExitCode::from(status),
+        Err(e) => {
+            eprintln!("rs-tool: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
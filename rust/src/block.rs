@@ -0,0 +1,338 @@
+// BlockCode: one interface over the code families, for harnesses that compare them
+// Sizes are in bits so binary codes fit. Blocks travel as bytes, LSB first: block bit i is bit
+// i % 8 of byte i / 8, the unused top bits of a final partial byte are zero. That is the bit
+// numbering secded, golay and odecc already use for their words (bit i of the integer), and for
+// RS and BCH it is byte i = symbol i and bit i = BCH position i. Every implementation is
+// systematic with the data in block bits 0..k_bits, which is what the provided extract_data
+// assumes; a LowestFirst RsCodec, whose data ends the block reversed, overrides it.
+// rs::sim::simulate_code runs the Monte Carlo engine over any &dyn BlockCode.
+
+use crate::bch::Bch;
+use crate::golay;
+use crate::odecc::{OdeccMatrix, OdeccResult};
+use crate::rs::{RsCodec, SymbolOrder};
+use crate::secded::{HMatrix, SecdedResult};
+
+/// What decode_block did to the received block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeOutcome {
+    /// Nothing to correct
+    Clean,
+    /// Corrected `symbols` symbols (bits, for a binary code) by flipping `bits` bits in all
+    Corrected { symbols: usize, bits: usize },
+    /// Error detected and not corrected; the block is left as received
+    Detected,
+}
+
+/// A systematic block code over bit-granular blocks (layout at the top of this file)
+pub trait BlockCode: Sync {
+    /// Short description for reports, e.g. "rs(255,223)"
+    fn name(&self) -> String;
+    fn n_bits(&self) -> usize;
+    fn k_bits(&self) -> usize;
+
+    /// Encode ceil(k_bits / 8) data bytes into ceil(n_bits / 8) bytes of `out`; panics on other lengths
+    fn encode_block(&self, data: &[u8], out: &mut [u8]);
+
+    /// Correct a ceil(n_bits / 8)-byte block in place
+    fn decode_block(&self, received: &mut [u8]) -> DecodeOutcome;
+
+    /// Copy the data bits 0..k_bits of a block into ceil(k_bits / 8) bytes
+    fn extract_data(&self, block: &[u8], data: &mut [u8]) {
+        let k = self.k_bits();
+        data.copy_from_slice(&block[..k.div_ceil(8)]);
+        if !k.is_multiple_of(8) {
+            data[k / 8] &= (1 << (k % 8)) - 1;
+        }
+    }
+}
+
+fn check_lengths(code: &dyn BlockCode, data: &[u8], out: &[u8]) {
+    assert_eq!(data.len(), code.k_bits().div_ceil(8), "data must be ceil(k_bits / 8) bytes");
+    assert_eq!(out.len(), code.n_bits().div_ceil(8), "block must be ceil(n_bits / 8) bytes");
+}
+
+/// Little-endian integer of a block's bytes
+fn word(bytes: &[u8]) -> u128 {
+    bytes.iter().rev().fold(0, |acc, &b| acc << 8 | b as u128)
+}
+
+fn put_word(mut value: u128, bytes: &mut [u8]) {
+    for b in bytes {
+        *b = value as u8;
+        value >>= 8;
+    }
+}
+
+impl BlockCode for RsCodec {
+    fn name(&self) -> String {
+        format!("rs({},{})", self.n(), self.k())
+    }
+
+    fn n_bits(&self) -> usize {
+        8 * self.n()
+    }
+
+    fn k_bits(&self) -> usize {
+        8 * self.k()
+    }
+
+    fn encode_block(&self, data: &[u8], out: &mut [u8]) {
+        check_lengths(self, data, out);
+        self.encode_padded_into(data, out);
+    }
+
+    fn decode_block(&self, received: &mut [u8]) -> DecodeOutcome {
+        match self.decode(received) {
+            Err(_) => DecodeOutcome::Detected,
+            Ok((_, positions)) if positions.is_empty() => DecodeOutcome::Clean,
+            Ok((message, positions)) => {
+                let fixed = self.encode(&message);
+                let bits = positions.iter().map(|&p| (received[p] ^ fixed[p]).count_ones() as usize).sum();
+                received.copy_from_slice(&fixed);
+                DecodeOutcome::Corrected { symbols: positions.len(), bits }
+            }
+        }
+    }
+
+    fn extract_data(&self, block: &[u8], data: &mut [u8]) {
+        match self.symbol_order() {
+            SymbolOrder::HighestFirst => data.copy_from_slice(&block[..self.k()]),
+            SymbolOrder::LowestFirst => data.iter_mut().zip(block.iter().rev()).for_each(|(d, &b)| *d = b),
+        }
+    }
+}
+
+impl BlockCode for Bch {
+    fn name(&self) -> String {
+        format!("bch({},{},t={})", self.n(), self.k(), self.t())
+    }
+
+    fn n_bits(&self) -> usize {
+        self.n()
+    }
+
+    fn k_bits(&self) -> usize {
+        self.k()
+    }
+
+    fn encode_block(&self, data: &[u8], out: &mut [u8]) {
+        check_lengths(self, data, out);
+        let message: Vec<u8> = (0..self.k()).map(|i| data[i / 8] >> (i % 8) & 1).collect();
+        out.fill(0);
+        for (i, bit) in self.encode(&message).into_iter().enumerate() {
+            out[i / 8] |= bit << (i % 8);
+        }
+    }
+
+    fn decode_block(&self, received: &mut [u8]) -> DecodeOutcome {
+        let bits: Vec<u8> = (0..self.n()).map(|i| received[i / 8] >> (i % 8) & 1).collect();
+        match self.decode(&bits) {
+            Err(_) => DecodeOutcome::Detected,
+            Ok((_, positions)) if positions.is_empty() => DecodeOutcome::Clean,
+            Ok((_, positions)) => {
+                for &p in &positions {
+                    received[p / 8] ^= 1 << (p % 8);
+                }
+                DecodeOutcome::Corrected { symbols: positions.len(), bits: positions.len() }
+            }
+        }
+    }
+}
+
+/// (72,64) SEC-DED: data bytes are the u64 little-endian, the check byte follows
+impl BlockCode for HMatrix {
+    fn name(&self) -> String {
+        "secded(72,64)".into()
+    }
+
+    fn n_bits(&self) -> usize {
+        72
+    }
+
+    fn k_bits(&self) -> usize {
+        64
+    }
+
+    fn encode_block(&self, data: &[u8], out: &mut [u8]) {
+        check_lengths(self, data, out);
+        out[..8].copy_from_slice(data);
+        out[8] = self.check_bits(word(data) as u64);
+    }
+
+    fn decode_block(&self, received: &mut [u8]) -> DecodeOutcome {
+        match self.decode(word(&received[..8]) as u64, received[8]) {
+            SecdedResult::NoError => DecodeOutcome::Clean,
+            SecdedResult::CorrectedBit(pos) => {
+                received[pos as usize / 8] ^= 1 << (pos % 8);
+                DecodeOutcome::Corrected { symbols: 1, bits: 1 }
+            }
+            SecdedResult::DetectedDouble | SecdedResult::DetectedUncorrectable => DecodeOutcome::Detected,
+        }
+    }
+}
+
+/// DDR5 on-die (136,128) SEC: data bytes are the u128 little-endian, the check byte follows
+/// The device passes an uncorrectable syndrome through unflagged; here it is reported as
+/// Detected, which is what a harness comparing codes wants to count.
+impl BlockCode for OdeccMatrix {
+    fn name(&self) -> String {
+        "odecc(136,128)".into()
+    }
+
+    fn n_bits(&self) -> usize {
+        136
+    }
+
+    fn k_bits(&self) -> usize {
+        128
+    }
+
+    fn encode_block(&self, data: &[u8], out: &mut [u8]) {
+        check_lengths(self, data, out);
+        out[..16].copy_from_slice(data);
+        out[16] = self.check_bits(word(data));
+    }
+
+    fn decode_block(&self, received: &mut [u8]) -> DecodeOutcome {
+        match self.decode(word(&received[..16]), received[16]) {
+            OdeccResult::NoError => DecodeOutcome::Clean,
+            OdeccResult::CorrectedBit(pos) => {
+                received[pos as usize / 8] ^= 1 << (pos % 8);
+                DecodeOutcome::Corrected { symbols: 1, bits: 1 }
+            }
+            OdeccResult::Uncorrectable => DecodeOutcome::Detected,
+        }
+    }
+}
+
+/// Extended (24,12) Golay code (golay::encode / golay::decode) as a BlockCode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Golay24;
+
+/// Perfect (23,12) Golay code (golay::encode23 / golay::decode23) as a BlockCode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Golay23;
+
+fn golay_decode(received: &mut [u8], decode: fn(u32) -> golay::GolayResult) -> DecodeOutcome {
+    match decode(word(received) as u32) {
+        golay::GolayResult::NoError => DecodeOutcome::Clean,
+        golay::GolayResult::Corrected(mask) => {
+            put_word(word(received) ^ mask as u128, received);
+            let bits = mask.count_ones() as usize;
+            DecodeOutcome::Corrected { symbols: bits, bits }
+        }
+        golay::GolayResult::Detected => DecodeOutcome::Detected,
+    }
+}
+
+impl BlockCode for Golay24 {
+    fn name(&self) -> String {
+        "golay(24,12)".into()
+    }
+
+    fn n_bits(&self) -> usize {
+        24
+    }
+
+    fn k_bits(&self) -> usize {
+        12
+    }
+
+    fn encode_block(&self, data: &[u8], out: &mut [u8]) {
+        check_lengths(self, data, out);
+        put_word(golay::encode(word(data) as u16) as u128, out);
+    }
+
+    fn decode_block(&self, received: &mut [u8]) -> DecodeOutcome {
+        golay_decode(received, golay::decode)
+    }
+}
+
+impl BlockCode for Golay23 {
+    fn name(&self) -> String {
+        "golay(23,12)".into()
+    }
+
+    fn n_bits(&self) -> usize {
+        23
+    }
+
+    fn k_bits(&self) -> usize {
+        12
+    }
+
+    fn encode_block(&self, data: &[u8], out: &mut [u8]) {
+        check_lengths(self, data, out);
+        put_word(golay::encode23(word(data) as u16) as u128, out);
+    }
+
+    fn decode_block(&self, received: &mut [u8]) -> DecodeOutcome {
+        golay_decode(received, golay::decode23)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::{simulate_code, ChannelModel, Rng};
+
+    fn codes() -> Vec<Box<dyn BlockCode>> {
+        vec![
+            Box::new(RsCodec::new(4, 20).unwrap()),
+            Box::new(RsCodec::new(4, 20).unwrap().with_symbol_order(SymbolOrder::LowestFirst)),
+            Box::new(Bch::new(8, 255, 2).unwrap()),
+            Box::new(HMatrix::hsiao()),
+            Box::new(OdeccMatrix::hamming()),
+            Box::new(Golay24),
+            Box::new(Golay23),
+        ]
+    }
+
+    #[test]
+    fn test_every_code_corrects_one_bit() {
+        let mut rng = Rng::new(407);
+        for code in codes() {
+            let (n, k) = (code.n_bits(), code.k_bits());
+            for _ in 0..20 {
+                let mut data: Vec<u8> = (0..k.div_ceil(8)).map(|_| rng.next_u64() as u8).collect();
+                code.extract_data(&data.clone(), &mut data); // clear the unused top bits
+                let mut block = vec![0u8; n.div_ceil(8)];
+                code.encode_block(&data, &mut block);
+                if !n.is_multiple_of(8) {
+                    assert_eq!(block[n / 8] >> (n % 8), 0, "{}: padding bits zero", code.name());
+                }
+                let sent = block.clone();
+                assert_eq!(code.decode_block(&mut block), DecodeOutcome::Clean, "{}", code.name());
+                let bit = rng.below(n);
+                block[bit / 8] ^= 1 << (bit % 8);
+                assert_eq!(code.decode_block(&mut block), DecodeOutcome::Corrected { symbols: 1, bits: 1 }, "{} bit {}", code.name(), bit);
+                assert_eq!(block, sent);
+                let mut out = vec![0u8; data.len()];
+                code.extract_data(&block, &mut out);
+                assert_eq!(out, data, "{}", code.name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_harness_compares_families() {
+        // Two random bit flips per block: SEC-DED and the on-die code cannot correct them,
+        // Golay (t = 3), BCH (t = 2) and RS (t = 2 symbols) always do
+        let stats: Vec<_> = codes().iter().map(|code| (code.name(), simulate_code(code.as_ref(), &ChannelModel::BitFlips(2), 400, 7).stats)).collect();
+        for (name, s) in &stats {
+            assert_eq!((s.trials, s.clean + s.corrected + s.detected + s.miscorrected), (400, 400), "{}", name);
+            match name.as_str() {
+                "secded(72,64)" => assert_eq!(s.detected, 400, "{:?}", s),
+                "odecc(136,128)" => assert_eq!(s.corrected, 0, "{:?}", s),
+                _ => assert_eq!(s.corrected, 400, "{}: {:?}", name, s),
+            }
+        }
+        // Four flips: the extended Golay code detects what it cannot correct, the perfect one
+        // never detects anything and miscorrects instead
+        let four = |code: &dyn BlockCode| simulate_code(code, &ChannelModel::BitFlips(4), 300, 8).stats;
+        let (g24, g23) = (four(&Golay24), four(&Golay23));
+        assert_eq!((g24.detected, g24.miscorrected), (300, 0));
+        assert_eq!((g23.detected, g23.miscorrected), (0, 300));
+    }
+}
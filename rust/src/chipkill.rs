@@ -0,0 +1,199 @@
+// Chipkill-correct memory model for x4 DRAM
+// 36 x4 devices per rank (32 data + 4 check); over a burst of 4 beats each device supplies
+// 16 bits, which is exactly one GF(2^16) symbol of an RS(36,32) code. A whole-device failure
+// is therefore a single symbol error. The code runs single-symbol-correct / double-symbol-detect:
+// nsym = 4 could correct two symbols, but limiting correction to one keeps every double fault
+// at distance >= 3 from any other codeword, so it is always detected rather than miscorrected.
+
+use crate::rs_wide::WideRs;
+
+pub const DEVICES: usize = 36;
+pub const DATA_DEVICES: usize = 32;
+pub const CHECK_DEVICES: usize = DEVICES - DATA_DEVICES;
+
+/// Which code symbol each device stores
+/// Devices 0..32 hold the 32 data symbols in the identity map; any permutation can be modeled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceMap {
+    symbol_of_device: [usize; DEVICES],
+}
+
+impl DeviceMap {
+    /// Device i holds code symbol i
+    pub fn identity() -> Self {
+        let mut symbol_of_device = [0usize; DEVICES];
+        for (i, s) in symbol_of_device.iter_mut().enumerate() {
+            *s = i;
+        }
+        Self { symbol_of_device }
+    }
+
+    /// Custom placement; must be a permutation of 0..36
+    pub fn new(symbol_of_device: [usize; DEVICES]) -> Result<Self, &'static str> {
+        let mut seen = [false; DEVICES];
+        for &s in &symbol_of_device {
+            if s >= DEVICES || seen[s] {
+                return Err("device map must be a permutation of the 36 code symbols");
+            }
+            seen[s] = true;
+        }
+        Ok(Self { symbol_of_device })
+    }
+
+    pub fn symbol_of(&self, device: usize) -> usize {
+        self.symbol_of_device[device]
+    }
+
+    pub fn device_of(&self, symbol: usize) -> usize {
+        self.symbol_of_device.iter().position(|&s| s == symbol).expect("permutation")
+    }
+}
+
+impl Default for DeviceMap {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// Access classification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeatStatus {
+    Clean,
+    /// Correctable error confined to one device
+    Ce { device: usize },
+    /// Detected uncorrectable error
+    Due,
+}
+
+/// Result of reading one burst
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeatDecode {
+    pub status: BeatStatus,
+    /// Data symbols, None on DUE
+    pub data: Option<[u16; DATA_DEVICES]>,
+}
+
+/// RS(36,32) over GF(2^16), single-symbol-correct / double-symbol-detect
+pub struct Chipkill {
+    rs: WideRs,
+    map: DeviceMap,
+}
+
+impl Chipkill {
+    pub fn new(map: DeviceMap) -> Self {
+        let rs = WideRs::new(16, DEVICES, CHECK_DEVICES).expect("RS(36,32) over GF(2^16)");
+        Self { rs, map }
+    }
+
+    pub fn map(&self) -> &DeviceMap {
+        &self.map
+    }
+
+    /// Encode 32 data symbols into the 36 device words of one burst (indexed by device)
+    pub fn encode_beat(&self, data: &[u16; DATA_DEVICES]) -> [u16; DEVICES] {
+        let codeword = self.rs.encode(data);
+        let mut beat = [0u16; DEVICES];
+        for (device, word) in beat.iter_mut().enumerate() {
+            *word = codeword[self.map.symbol_of(device)];
+        }
+        beat
+    }
+
+    /// Decode one burst (indexed by device)
+    pub fn decode_beat(&self, beat: &[u16; DEVICES]) -> BeatDecode {
+        let mut codeword = [0u16; DEVICES];
+        for (device, &word) in beat.iter().enumerate() {
+            codeword[self.map.symbol_of(device)] = word;
+        }
+        match self.rs.decode_limited(&codeword, 1) {
+            Ok((message, positions)) => {
+                let mut data = [0u16; DATA_DEVICES];
+                data.copy_from_slice(&message);
+                let status = match positions.first() {
+                    None => BeatStatus::Clean,
+                    Some(&symbol) => BeatStatus::Ce { device: self.map.device_of(symbol) },
+                };
+                BeatDecode { status, data: Some(data) }
+            }
+            Err(_) => BeatDecode { status: BeatStatus::Due, data: None },
+        }
+    }
+}
+
+impl Default for Chipkill {
+    fn default() -> Self {
+        Self::new(DeviceMap::identity())
+    }
+}
+
+/// Fault injection: XOR a nonzero pattern into everything device `device` returns
+/// Because each device holds one symbol this is exactly one symbol error.
+pub fn kill_device(beat: &mut [u16; DEVICES], device: usize, pattern: u16) {
+    beat[device] ^= pattern;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_data(seed: u16) -> [u16; DATA_DEVICES] {
+        let mut data = [0u16; DATA_DEVICES];
+        for (i, d) in data.iter_mut().enumerate() {
+            *d = (i as u16).wrapping_mul(0x9e37).wrapping_add(seed);
+        }
+        data
+    }
+
+    #[test]
+    fn test_clean_read() {
+        let ck = Chipkill::default();
+        let data = sample_data(1);
+        let result = ck.decode_beat(&ck.encode_beat(&data));
+        assert_eq!(result, BeatDecode { status: BeatStatus::Clean, data: Some(data) });
+    }
+
+    #[test]
+    fn test_kill_each_device_is_corrected() {
+        let mut rev = [0usize; DEVICES];
+        for (i, s) in rev.iter_mut().enumerate() {
+            *s = DEVICES - 1 - i;
+        }
+        for ck in [Chipkill::default(), Chipkill::new(DeviceMap::new(rev).unwrap())] {
+            let data = sample_data(7);
+            let beat = ck.encode_beat(&data);
+            for device in 0..DEVICES {
+                for pattern in [0x0001u16, 0xffff, 0x8421] {
+                    let mut bad = beat;
+                    kill_device(&mut bad, device, pattern);
+                    let result = ck.decode_beat(&bad);
+                    assert_eq!(result.status, BeatStatus::Ce { device });
+                    assert_eq!(result.data, Some(data));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_kill_two_devices_is_detected() {
+        let ck = Chipkill::default();
+        let data = sample_data(3);
+        let beat = ck.encode_beat(&data);
+        for a in 0..DEVICES {
+            for b in (a + 1)..DEVICES {
+                let mut bad = beat;
+                kill_device(&mut bad, a, 0x1234 ^ (a as u16));
+                kill_device(&mut bad, b, 0xfedc ^ (b as u16));
+                let result = ck.decode_beat(&bad);
+                assert_eq!(result.status, BeatStatus::Due, "devices {} {}", a, b);
+                assert_eq!(result.data, None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_device_map_validation() {
+        let mut bad = [0usize; DEVICES];
+        bad[1] = 0;
+        assert!(DeviceMap::new(bad).is_err());
+    }
+}
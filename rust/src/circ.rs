@@ -0,0 +1,271 @@
+// Cross-interleaved Reed-Solomon coding (CIRC), the CD/DAT error-correction stack
+// Every 24 data bytes are a frame. C2, RS(28, 24), encodes the frame; the cross interleaver
+// delays symbol i of each C2 word by 4i frames (D = 4, delays 0..=108); C1, RS(32, 28), encodes
+// the 28 symbols leaving the delay lines into a 32-byte channel frame. Both are shortened
+// GF(256) codes with roots alpha^0..alpha^3 and [data | parity] order.
+//
+// Decoding reverses it. C1 corrects single errors only, keeping its miscorrection rate low, and
+// otherwise flags all 28 of its symbols as erasures. The deinterleaver (delays 108 - 4i) spreads
+// those flags so that each C2 word sees at most one symbol from any 4 consecutive channel
+// frames: a burst of up to 16 frames (~500 bytes) costs every C2 word at most 4 erasures, which
+// RS(28, 24) fills in. When C2 fails too, its data symbols are passed through as received and
+// the ones needing concealment (interpolation, in a player) are reported: the C1-flagged ones,
+// or all 24 if C2 found errors C1 did not flag.
+//
+// Simplified against the Red Book: the 2-frame even/odd scramble before C2, the 1-frame delay
+// after C1, parity inversion and C2's parity in the middle of its word are left out. None of
+// them change what a burst costs C2.
+
+use crate::rs::{DecodeOptions, RsCodec};
+
+/// Data bytes per frame, C2's k
+pub const FRAME_DATA: usize = 24;
+/// C2 word length, C1's k
+pub const C2_LEN: usize = 28;
+/// Channel bytes per frame, C1's n
+pub const FRAME_LEN: usize = 32;
+/// Cross-interleave delay unit in frames: symbol i of a C2 word is delayed by DELAY_UNIT * i
+pub const DELAY_UNIT: usize = 4;
+/// Frames the delay lines hold back, and so the frames encode_frames appends to flush them
+pub const MAX_DELAY: usize = DELAY_UNIT * (C2_LEN - 1);
+
+/// A bank of 28 delay lines: symbol i of each frame comes out delays[i] frames later
+/// The lines start full of T::default().
+#[derive(Debug, Clone)]
+pub struct DelayLines<T> {
+    lines: Vec<Vec<T>>,
+    /// Slot of the current frame in each ring
+    at: usize,
+}
+
+impl<T: Copy + Default> DelayLines<T> {
+    pub fn new(delays: [usize; C2_LEN]) -> Self {
+        Self { lines: delays.iter().map(|&d| vec![T::default(); d]).collect(), at: 0 }
+    }
+
+    /// The cross interleaver: symbol i delayed by 4i frames
+    pub fn interleaver() -> Self {
+        Self::new(core::array::from_fn(|i| DELAY_UNIT * i))
+    }
+
+    /// The deinterleaver: symbol i delayed by 108 - 4i frames, so every symbol lines up again
+    pub fn deinterleaver() -> Self {
+        Self::new(core::array::from_fn(|i| MAX_DELAY - DELAY_UNIT * i))
+    }
+
+    /// Push one frame in, get the one leaving the lines
+    pub fn push(&mut self, frame: [T; C2_LEN]) -> [T; C2_LEN] {
+        let at = self.at;
+        self.at += 1;
+        core::array::from_fn(|i| match self.lines[i].len() {
+            0 => frame[i],
+            d => core::mem::replace(&mut self.lines[i][at % d], frame[i]),
+        })
+    }
+}
+
+/// Counts over one decode_frames call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CircStats {
+    pub c1_words: u64,
+    /// Single errors C1 fixed
+    pub c1_corrected: u64,
+    /// C1 words flagged as erasures
+    pub c1_flagged: u64,
+    pub c2_words: u64,
+    /// Symbols C2 fixed, erasures included
+    pub c2_corrected: u64,
+    /// C2 words passed through uncorrected
+    pub c2_failed: u64,
+}
+
+/// What decode_frames recovered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircDecoded {
+    pub data: Vec<u8>,
+    /// Offsets into data of bytes that could not be corrected and need concealing, ascending
+    pub conceal: Vec<usize>,
+    pub stats: CircStats,
+}
+
+/// C2 (28, 24) and C1 (32, 28)
+#[derive(Debug, Clone)]
+pub struct Circ {
+    c1: RsCodec,
+    c2: RsCodec,
+}
+
+impl Default for Circ {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Circ {
+    pub fn new() -> Self {
+        let c1 = RsCodec::new(FRAME_LEN - C2_LEN, FRAME_LEN).expect("RS(32, 28) is a valid code");
+        let c1 = c1.with_decode_options(DecodeOptions { max_correct: Some(1), ..DecodeOptions::default() }).expect("1 is within t = 2");
+        Self { c1, c2: RsCodec::new(C2_LEN - FRAME_DATA, C2_LEN).expect("RS(28, 24) is a valid code") }
+    }
+
+    pub fn c1(&self) -> &RsCodec {
+        &self.c1
+    }
+
+    pub fn c2(&self) -> &RsCodec {
+        &self.c2
+    }
+
+    /// Channel frames for data, MAX_DELAY flush frames included; panics unless data is a whole
+    /// number of 24-byte frames
+    pub fn encode_frames(&self, data: &[u8]) -> Vec<u8> {
+        assert!(data.len().is_multiple_of(FRAME_DATA), "CIRC data must be whole {}-byte frames", FRAME_DATA);
+        let frames = data.len() / FRAME_DATA + MAX_DELAY;
+        let mut out = Vec::with_capacity(frames * FRAME_LEN);
+        let mut interleaver = DelayLines::interleaver();
+        let mut word = [0u8; C2_LEN];
+        for frame in 0..frames {
+            // The all-zero C2 word flushes the lines
+            match data.get(frame * FRAME_DATA..(frame + 1) * FRAME_DATA) {
+                Some(chunk) => word.copy_from_slice(&self.c2.encode(chunk)),
+                None => word = [0; C2_LEN],
+            }
+            out.extend(self.c1.encode(&interleaver.push(word)));
+        }
+        out
+    }
+
+    /// Decode channel frames from encode_frames, propagating C1's erasure flags to C2
+    /// Panics unless channel is a whole number of 32-byte frames, at least MAX_DELAY of them.
+    pub fn decode_frames(&self, channel: &[u8]) -> CircDecoded {
+        assert!(channel.len().is_multiple_of(FRAME_LEN) && channel.len() / FRAME_LEN >= MAX_DELAY, "CIRC channel must be whole {}-byte frames, at least {}", FRAME_LEN, MAX_DELAY);
+        let mut stats = CircStats::default();
+        let mut data = Vec::with_capacity((channel.len() / FRAME_LEN - MAX_DELAY) * FRAME_DATA);
+        let mut conceal = vec![];
+        let mut deinterleaver = DelayLines::<(u8, bool)>::deinterleaver();
+        for (frame, received) in channel.chunks_exact(FRAME_LEN).enumerate() {
+            stats.c1_words += 1;
+            let c1_out: [(u8, bool); C2_LEN] = match self.c1.decode(received) {
+                Ok((message, positions)) => {
+                    stats.c1_corrected += positions.len() as u64;
+                    core::array::from_fn(|i| (message[i], false))
+                }
+                Err(_) => {
+                    stats.c1_flagged += 1;
+                    core::array::from_fn(|i| (received[i], true))
+                }
+            };
+            let symbols = deinterleaver.push(c1_out);
+            // What leaves the lines before they fill is from before the stream
+            if frame < MAX_DELAY {
+                continue;
+            }
+            stats.c2_words += 1;
+            let word: Vec<u8> = symbols.iter().map(|&(s, _)| s).collect();
+            let erasures: Vec<usize> = (0..C2_LEN).filter(|&i| symbols[i].1).collect();
+            let decoded = if erasures.len() <= self.c2.nsym() { self.c2.decode_errata(&word, &erasures).ok() } else { None };
+            let offset = data.len();
+            match decoded {
+                Some((message, positions)) => {
+                    stats.c2_corrected += positions.len() as u64;
+                    data.extend(message);
+                }
+                None => {
+                    stats.c2_failed += 1;
+                    data.extend_from_slice(&word[..FRAME_DATA]);
+                    let flagged: Vec<usize> = erasures.into_iter().filter(|&i| i < FRAME_DATA).collect();
+                    if flagged.is_empty() {
+                        conceal.extend(offset..offset + FRAME_DATA);
+                    } else {
+                        conceal.extend(flagged.into_iter().map(|i| offset + i));
+                    }
+                }
+            }
+        }
+        CircDecoded { data, conceal, stats }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    fn data(frames: usize) -> Vec<u8> {
+        let mut rng = Rng::new(414);
+        (0..frames * FRAME_DATA).map(|_| rng.next_u64() as u8).collect()
+    }
+
+    #[test]
+    fn test_delay_lines() {
+        // Interleaver then deinterleaver delays every symbol by MAX_DELAY frames
+        let mut interleaver = DelayLines::<u16>::interleaver();
+        let mut deinterleaver = DelayLines::<u16>::deinterleaver();
+        for frame in 0..300u16 {
+            let spread = interleaver.push(core::array::from_fn(|i| frame * 100 + i as u16 + 1));
+            if frame >= 12 {
+                // Symbol i left the interleaver 4i frames late
+                assert!((0..4).all(|i| spread[i] == (frame - 4 * i as u16) * 100 + i as u16 + 1));
+            }
+            let out = deinterleaver.push(spread);
+            if frame as usize >= MAX_DELAY {
+                let sent = frame - MAX_DELAY as u16;
+                assert_eq!(out, core::array::from_fn(|i| sent * 100 + i as u16 + 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_clean_and_scattered_errors() {
+        let circ = Circ::new();
+        let data = data(50);
+        let mut channel = circ.encode_frames(&data);
+        assert_eq!(channel.len(), (50 + MAX_DELAY) * FRAME_LEN);
+        let clean = circ.decode_frames(&channel);
+        assert_eq!((&clean.data, clean.conceal.len(), clean.stats.c1_flagged), (&data, 0, 0));
+        // One error per C1 word in some frames, two (flagged, then filled in by C2) in others
+        for frame in (0..channel.len() / FRAME_LEN).step_by(7) {
+            channel[frame * FRAME_LEN + frame % FRAME_LEN] ^= 0x21;
+            if frame.is_multiple_of(2) {
+                channel[frame * FRAME_LEN + 31] ^= 0x42;
+            }
+        }
+        let decoded = circ.decode_frames(&channel);
+        assert_eq!(decoded.data, data);
+        assert!(decoded.conceal.is_empty());
+        assert!(decoded.stats.c1_corrected > 0 && decoded.stats.c1_flagged > 0 && decoded.stats.c2_corrected > 0);
+    }
+
+    #[test]
+    fn test_long_burst_corrected() {
+        // 480 contiguous bytes: 15 C1 words flagged, at most 4 erasures per C2 word
+        let circ = Circ::new();
+        let data = data(200);
+        let mut channel = circ.encode_frames(&data);
+        let start = 120 * FRAME_LEN + 5;
+        channel[start..start + 480].iter_mut().for_each(|b| *b ^= 0xff);
+        let decoded = circ.decode_frames(&channel);
+        assert_eq!(decoded.stats.c1_flagged, 16);
+        assert_eq!(decoded.data, data);
+        assert!(decoded.conceal.is_empty());
+        assert_eq!(decoded.stats.c2_failed, 0);
+    }
+
+    #[test]
+    fn test_longer_burst_flagged() {
+        // 40 frames is 10 erasures per C2 word, past its 4: the damaged bytes come out as
+        // received, and every one of them is flagged for concealment
+        let circ = Circ::new();
+        let data = data(200);
+        let mut channel = circ.encode_frames(&data);
+        let start = 150 * FRAME_LEN;
+        channel[start..start + 40 * FRAME_LEN].iter_mut().for_each(|b| *b ^= 0x5a);
+        let decoded = circ.decode_frames(&channel);
+        assert!(decoded.stats.c2_failed > 0);
+        let wrong: Vec<usize> = (0..data.len()).filter(|&i| decoded.data[i] != data[i]).collect();
+        assert!(!wrong.is_empty());
+        assert!(decoded.conceal.windows(2).all(|w| w[0] < w[1]));
+        assert!(wrong.iter().all(|i| decoded.conceal.binary_search(i).is_ok()));
+    }
+}
@@ -0,0 +1,260 @@
+// Concatenated coding: RS outer code, byte interleaver, rate-1/2 K=7 convolutional inner code
+// The deep-space stack. Transmit RS-encodes `depth` blocks, interleaves them and convolutionally
+// encodes the result; receive runs Viterbi, deinterleaves and RS-decodes. Viterbi's residual
+// errors come in short bursts, and the interleaver spreads each burst over the RS codewords.
+// Every stage's output is kept so the simulator can measure bit and word error rates between them.
+
+use std::ops::Add;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::conv;
+use crate::rs::interleave::Interleaver;
+use crate::rs::sim::{inject, trial_rng, ChannelModel};
+use crate::rs::{RsCodec, RsError};
+
+/// Outer RS code and interleaving depth of the chain
+#[derive(Debug, Clone)]
+pub struct Concatenated {
+    codec: RsCodec,
+    interleaver: Interleaver,
+}
+
+/// What transmit produced for one frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Interleaved RS codewords: the convolutional encoder's input, which Viterbi should return
+    pub outer: Vec<u8>,
+    /// Convolutionally coded bits, packed MSB first: what goes over the channel
+    pub channel: Vec<u8>,
+}
+
+/// What receive recovered from one frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    /// Viterbi output, to compare against Frame::outer
+    pub outer: Vec<u8>,
+    /// The frame's message; a block the RS decoder rejected contributes its uncorrected bytes
+    pub message: Vec<u8>,
+    /// Per RS codeword: corrected positions, or why it failed
+    pub blocks: Vec<Result<Vec<usize>, RsError>>,
+}
+
+impl Decoded {
+    pub fn is_ok(&self) -> bool {
+        self.blocks.iter().all(Result::is_ok)
+    }
+}
+
+impl Concatenated {
+    /// depth RS(nsize, nsize - nsym) codewords per frame; panics if depth is 0
+    pub fn new(nsym: usize, nsize: usize, depth: usize) -> Result<Self, RsError> {
+        Ok(Self { codec: RsCodec::new(nsym, nsize)?, interleaver: Interleaver::new(depth) })
+    }
+
+    pub fn codec(&self) -> &RsCodec {
+        &self.codec
+    }
+
+    pub fn depth(&self) -> usize {
+        self.interleaver.depth()
+    }
+
+    /// Message bytes per frame
+    pub fn message_len(&self) -> usize {
+        self.depth() * self.codec.k()
+    }
+
+    /// Channel bytes per frame
+    pub fn channel_len(&self) -> usize {
+        conv::encoded_len(self.depth() * self.codec.n())
+    }
+
+    /// Encode one frame; panics unless message is message_len bytes
+    pub fn transmit(&self, message: &[u8]) -> Frame {
+        assert_eq!(message.len(), self.message_len(), "frame message must be {} bytes", self.message_len());
+        let codewords: Vec<Vec<u8>> = message.chunks_exact(self.codec.k()).map(|m| self.codec.encode(m)).collect();
+        let refs: Vec<&[u8]> = codewords.iter().map(|c| c.as_slice()).collect();
+        let outer = self.interleaver.interleave(&refs);
+        let channel = conv::encode(&outer);
+        Frame { outer, channel }
+    }
+
+    /// Decode one frame of hard channel bits; panics if received is shorter than channel_len
+    pub fn receive(&self, received: &[u8]) -> Decoded {
+        let outer = conv::decode(received, self.depth() * self.codec.n());
+        let mut message = Vec::with_capacity(self.message_len());
+        let mut blocks = Vec::with_capacity(self.depth());
+        for codeword in self.interleaver.deinterleave(&outer) {
+            match self.codec.decode(&codeword) {
+                Ok((decoded, positions)) => {
+                    message.extend(decoded);
+                    blocks.push(Ok(positions));
+                }
+                Err(e) => {
+                    message.extend_from_slice(&codeword[..self.codec.k()]);
+                    blocks.push(Err(e));
+                }
+            }
+        }
+        Decoded { outer, message, blocks }
+    }
+}
+
+/// Bit and word error counts at each stage of the chain
+/// channel_*: the raw channel, as Viterbi sees it; inner_*: Viterbi output, as the RS decoder
+/// sees it; words: RS codewords; message_*: what is finally delivered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct ConcatStats {
+    pub frames: u64,
+    pub channel_bits: u64,
+    pub channel_bit_errors: u64,
+    pub inner_bits: u64,
+    pub inner_bit_errors: u64,
+    pub words: u64,
+    /// RS codewords rejected by the decoder or delivered with the wrong message
+    pub word_errors: u64,
+    pub message_bits: u64,
+    pub message_bit_errors: u64,
+}
+
+fn bit_errors(a: &[u8], b: &[u8]) -> u64 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones() as u64).sum()
+}
+
+fn ratio(errors: u64, total: u64) -> f64 {
+    errors as f64 / total.max(1) as f64
+}
+
+impl ConcatStats {
+    /// Count one frame: the message sent, its Frame, the channel bits received and their decode
+    pub fn record(&mut self, message: &[u8], sent: &Frame, received: &[u8], decoded: &Decoded) {
+        self.frames += 1;
+        self.channel_bits += 8 * sent.channel.len() as u64;
+        self.channel_bit_errors += bit_errors(&sent.channel, received);
+        self.inner_bits += 8 * sent.outer.len() as u64;
+        self.inner_bit_errors += bit_errors(&sent.outer, &decoded.outer);
+        let k = message.len() / decoded.blocks.len();
+        for (block, (sent, got)) in decoded.blocks.iter().zip(message.chunks(k).zip(decoded.message.chunks(k))) {
+            self.words += 1;
+            self.word_errors += (block.is_err() || sent != got) as u64;
+        }
+        self.message_bits += 8 * message.len() as u64;
+        self.message_bit_errors += bit_errors(message, &decoded.message);
+    }
+
+    /// Bit error rate of the channel
+    pub fn channel_ber(&self) -> f64 {
+        ratio(self.channel_bit_errors, self.channel_bits)
+    }
+
+    /// Bit error rate after Viterbi
+    pub fn inner_ber(&self) -> f64 {
+        ratio(self.inner_bit_errors, self.inner_bits)
+    }
+
+    /// Delivered bit error rate
+    pub fn ber(&self) -> f64 {
+        ratio(self.message_bit_errors, self.message_bits)
+    }
+
+    /// RS word error rate
+    pub fn wer(&self) -> f64 {
+        ratio(self.word_errors, self.words)
+    }
+}
+
+impl Add for ConcatStats {
+    type Output = ConcatStats;
+
+    fn add(self, other: ConcatStats) -> ConcatStats {
+        ConcatStats {
+            frames: self.frames + other.frames,
+            channel_bits: self.channel_bits + other.channel_bits,
+            channel_bit_errors: self.channel_bit_errors + other.channel_bit_errors,
+            inner_bits: self.inner_bits + other.inner_bits,
+            inner_bit_errors: self.inner_bit_errors + other.inner_bit_errors,
+            words: self.words + other.words,
+            word_errors: self.word_errors + other.word_errors,
+            message_bits: self.message_bits + other.message_bits,
+            message_bit_errors: self.message_bit_errors + other.message_bit_errors,
+        }
+    }
+}
+
+/// Monte Carlo over the whole chain: each frame is a random message, transmitted, corrupted by
+/// `channel` on the coded bits, received and counted. Per-frame RNG streams derive from seed
+/// as in sim::simulate, so results do not depend on thread scheduling.
+pub fn simulate(scheme: &Concatenated, channel: &ChannelModel, frames: u64, seed: u64) -> ConcatStats {
+    let run_frame = |frame: u64| -> ConcatStats {
+        let mut rng = trial_rng(seed, frame);
+        let message: Vec<u8> = (0..scheme.message_len()).map(|_| rng.next_u64() as u8).collect();
+        let sent = scheme.transmit(&message);
+        let mut received = sent.channel.clone();
+        inject(channel, &mut rng, &mut received);
+        let mut stats = ConcatStats::default();
+        stats.record(&message, &sent, &received, &scheme.receive(&received));
+        stats
+    };
+
+    #[cfg(feature = "parallel")]
+    let stats = (0..frames).into_par_iter().map(run_frame).reduce(ConcatStats::default, Add::add);
+    #[cfg(not(feature = "parallel"))]
+    let stats = (0..frames).map(run_frame).fold(ConcatStats::default(), Add::add);
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::{simulate as rs_simulate, SimParams};
+
+    #[test]
+    fn test_round_trip() {
+        let scheme = Concatenated::new(32, 255, 4).unwrap();
+        assert_eq!((scheme.message_len(), scheme.channel_len()), (4 * 223, 2 * 4 * 255 + 2));
+        let message: Vec<u8> = (0..scheme.message_len()).map(|i| (i * 7) as u8).collect();
+        let sent = scheme.transmit(&message);
+        let decoded = scheme.receive(&sent.channel);
+        assert!(decoded.is_ok());
+        assert_eq!((&decoded.message, &decoded.outer), (&message, &sent.outer));
+        let mut stats = ConcatStats::default();
+        stats.record(&message, &sent, &sent.channel, &decoded);
+        assert_eq!((stats.words, stats.word_errors, stats.message_bit_errors), (4, 0, 0));
+    }
+
+    #[test]
+    fn test_beats_rs_alone() {
+        // At a 4.5% raw bit error rate RS(255,223) alone sees about 79 byte errors a word, far past
+        // t = 16, and Viterbi alone leaves a bit error rate around 1e-3; the RS decoder only has to
+        // clean up those short bursts, spread over the words by the interleaver
+        let p = 0.045;
+        let alone = rs_simulate(&SimParams { n: 255, nsym: 32, channel: ChannelModel::BitErrorRate(p), trials: 200, seed: 391 }).stats;
+        assert!(alone.block_failure_rate() > 0.99, "{:?}", alone);
+
+        let scheme = Concatenated::new(32, 255, 4).unwrap();
+        let stats = simulate(&scheme, &ChannelModel::BitErrorRate(p), 8, 391);
+        assert!((0.04..0.05).contains(&stats.channel_ber()), "{:?}", stats);
+        assert!(stats.inner_bit_errors > 0, "Viterbi alone leaves errors: {:?}", stats);
+        assert_eq!((stats.words, stats.word_errors, stats.message_bit_errors), (32, 0, 0));
+        assert_eq!(simulate(&scheme, &ChannelModel::BitErrorRate(p), 8, 391), stats);
+    }
+
+    #[test]
+    fn test_interleaver_spreads_viterbi_bursts() {
+        // A 300-bit channel burst overwhelms Viterbi locally; depth 8 keeps each word within t
+        let scheme = Concatenated::new(32, 255, 8).unwrap();
+        let message: Vec<u8> = (0..scheme.message_len()).map(|i| (i * 31 + 5) as u8).collect();
+        let sent = scheme.transmit(&message);
+        let mut received = sent.channel.clone();
+        for b in &mut received[1000..1038] {
+            *b ^= 0x5a;
+        }
+        let decoded = scheme.receive(&received);
+        assert_ne!(decoded.outer, sent.outer);
+        assert!(decoded.is_ok(), "{:?}", decoded.blocks);
+        assert_eq!(decoded.message, message);
+    }
+}
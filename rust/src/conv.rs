@@ -0,0 +1,158 @@
+// Rate-1/2 K=7 convolutional code (generators 171, 133 octal) with Viterbi decoding
+// The inner code of the classic deep-space concatenated stack. The octal generators are read
+// with the current input bit as their leading coefficient; internally the shift register keeps
+// the newest bit in bit 0, so the taps are the generators bit-reversed (0o117, 0o155).
+// Each data byte enters MSB first and every input bit emits its G1 then G2 output bit. Encoding
+// is terminated with K-1 zero bits, so the decoder traces back from state 0. Coded bits are
+// packed MSB first, the last byte zero-padded.
+
+/// Constraint length
+pub const K: usize = 7;
+/// Generator polynomials in the usual octal notation
+pub const GENERATORS: [u8; 2] = [0o171, 0o133];
+
+const STATES: usize = 1 << (K - 1);
+const TAPS: [u8; 2] = [reverse7(GENERATORS[0]), reverse7(GENERATORS[1])];
+
+const fn reverse7(g: u8) -> u8 {
+    g.reverse_bits() >> 1
+}
+
+/// Output bit pair for a 7-bit register (newest input in bit 0), G1 in bit 1 and G2 in bit 0
+const fn outputs(reg: u8) -> u8 {
+    ((reg & TAPS[0]).count_ones() as u8 & 1) << 1 | ((reg & TAPS[1]).count_ones() as u8 & 1)
+}
+
+/// Coded bits for data_len bytes: two per data bit plus two per tail bit
+pub fn encoded_bits(data_len: usize) -> usize {
+    2 * (8 * data_len + K - 1)
+}
+
+/// Bytes encode produces for data_len bytes
+pub fn encoded_len(data_len: usize) -> usize {
+    encoded_bits(data_len).div_ceil(8)
+}
+
+/// Convolutionally encode data, terminating the trellis in state 0
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; encoded_len(data.len())];
+    let mut reg = 0u8;
+    let bits = data.iter().flat_map(|&byte| (0..8).rev().map(move |i| byte >> i & 1)).chain([0; K - 1]);
+    for (i, bit) in bits.enumerate() {
+        reg = (reg << 1 | bit) & 0x7f;
+        let pair = outputs(reg);
+        out[2 * i / 8] |= pair << (6 - 2 * i % 8);
+    }
+    out
+}
+
+/// Hard-decision Viterbi decode of data_len bytes; panics if received is shorter than encoded_len
+pub fn decode(received: &[u8], data_len: usize) -> Vec<u8> {
+    let soft: Vec<u8> = (0..encoded_bits(data_len)).map(|i| if received[i / 8] >> (7 - i % 8) & 1 == 1 { 255 } else { 0 }).collect();
+    decode_soft(&soft, data_len)
+}
+
+/// Soft-decision Viterbi decode of data_len bytes
+/// soft holds one value per coded bit: 0 is a confident 0, 255 a confident 1 and 128 no
+/// information (an erasure). Panics if it is shorter than encoded_bits(data_len).
+pub fn decode_soft(soft: &[u8], data_len: usize) -> Vec<u8> {
+    let steps = 8 * data_len + K - 1;
+    assert!(soft.len() >= 2 * steps, "need {} coded bits, got {}", 2 * steps, soft.len());
+    // Branch cost of each output pair: distance of the two soft values from the expected bits
+    let cost = |step: usize, pair: u8| -> u32 {
+        let (a, b) = (soft[2 * step] as u32, soft[2 * step + 1] as u32);
+        (if pair & 2 != 0 { 255 - a } else { a }) + (if pair & 1 != 0 { 255 - b } else { b })
+    };
+    let mut metric = [u32::MAX; STATES];
+    metric[0] = 0;
+    // decisions[t] bit s: which predecessor state s came from at step t
+    let mut decisions = vec![0u64; steps];
+    for (step, decision) in decisions.iter_mut().enumerate() {
+        let mut next = [u32::MAX; STATES];
+        for (state, slot) in next.iter_mut().enumerate() {
+            // Registers reaching this state are x << 6 | state, from predecessor state >> 1 | x << 5
+            for x in 0..2 {
+                let prev = state >> 1 | x << 5;
+                if metric[prev] == u32::MAX {
+                    continue;
+                }
+                let candidate = metric[prev] + cost(step, outputs((x << 6 | state) as u8));
+                if candidate < *slot {
+                    *slot = candidate;
+                    *decision = *decision & !(1 << state) | (x as u64) << state;
+                }
+            }
+        }
+        metric = next;
+    }
+    let mut out = vec![0u8; data_len];
+    let mut state = 0usize;
+    for step in (0..steps).rev() {
+        if step < 8 * data_len {
+            out[step / 8] |= ((state & 1) as u8) << (7 - step % 8);
+        }
+        state = state >> 1 | ((decisions[step] >> state & 1) as usize) << 5;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    fn flip(coded: &mut [u8], bit: usize) {
+        coded[bit / 8] ^= 0x80 >> (bit % 8);
+    }
+
+    #[test]
+    fn test_impulse_response_is_the_generators() {
+        // A single 1 followed by zeros reads the generators out, G1 and G2 interleaved
+        let coded = encode(&[0x80]);
+        assert_eq!(coded.len(), encoded_len(1));
+        let pairs: Vec<u8> = (0..K).map(|i| coded[2 * i / 8] >> (6 - 2 * i % 8) & 3).collect();
+        let g1: Vec<u8> = (0..K).rev().map(|i| GENERATORS[0] >> i & 1).collect();
+        let g2: Vec<u8> = (0..K).rev().map(|i| GENERATORS[1] >> i & 1).collect();
+        assert_eq!(pairs, g1.iter().zip(&g2).map(|(a, b)| a << 1 | b).collect::<Vec<_>>());
+        // and nothing after them: the weight is all in the generators (free distance 10)
+        assert_eq!(coded.iter().map(|b| b.count_ones()).sum::<u32>(), 10);
+    }
+
+    #[test]
+    fn test_round_trip_and_four_bit_errors() {
+        // Free distance 10: any 4 bit errors anywhere are within the ML decoder's reach
+        let mut rng = Rng::new(391);
+        for len in [0, 1, 2, 17, 100] {
+            let data: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+            let coded = encode(&data);
+            assert_eq!(decode(&coded, len), data);
+            for _ in 0..50 {
+                let mut received = coded.clone();
+                for bit in rng.distinct(4, encoded_bits(len)) {
+                    flip(&mut received, bit);
+                }
+                assert_eq!(decode(&received, len), data, "len {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_soft_decisions() {
+        let mut rng = Rng::new(0x50f7);
+        let data: Vec<u8> = (0..64).map(|_| rng.next_u64() as u8).collect();
+        let coded = encode(&data);
+        let strong: Vec<u8> = (0..encoded_bits(64)).map(|i| if coded[i / 8] >> (7 - i % 8) & 1 == 1 { 255 } else { 0 }).collect();
+        // A quarter of the bits erased
+        let mut soft = strong.clone();
+        for i in rng.distinct(strong.len() / 4, strong.len()) {
+            soft[i] = 128;
+        }
+        assert_eq!(decode_soft(&soft, 64), data);
+        // Dense wrong bits held with little confidence lose to the confident right ones
+        let mut soft = strong.clone();
+        for i in rng.distinct(strong.len() / 10, strong.len()) {
+            soft[i] = if strong[i] == 255 { 100 } else { 155 };
+        }
+        assert_eq!(decode_soft(&soft, 64), data);
+    }
+}
@@ -0,0 +1,132 @@
+// CRC checksums used as an inner integrity check under the RS code.
+// CRC-32 is the IEEE 802.3 polynomial (reflected, 0xEDB88320), CRC-16 is CCITT-FALSE (0x1021, init 0xFFFF).
+// Checksums are appended big-endian so the frame reads [message | crc].
+
+const CRC32_POLY: u32 = 0xEDB8_8320;
+const CRC16_POLY: u16 = 0x1021;
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0usize;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            c = if c & 1 != 0 { (c >> 1) ^ CRC32_POLY } else { c >> 1 };
+            bit += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+}
+
+const fn crc16_table() -> [u16; 256] {
+    let mut table = [0u16; 256];
+    let mut i = 0usize;
+    while i < 256 {
+        let mut c = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            c = if c & 0x8000 != 0 { (c << 1) ^ CRC16_POLY } else { c << 1 };
+            bit += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+}
+
+// Global static tables (computed at compile time)
+static CRC32_TABLE: [u32; 256] = crc32_table();
+static CRC16_TABLE: [u16; 256] = crc16_table();
+
+/// CRC-32 (IEEE) of data
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut c = 0xFFFF_FFFFu32;
+    for &byte in data {
+        c = CRC32_TABLE[((c ^ byte as u32) & 0xFF) as usize] ^ (c >> 8);
+    }
+    c ^ 0xFFFF_FFFF
+}
+
+/// CRC-16/CCITT-FALSE of data
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut c = 0xFFFFu16;
+    for &byte in data {
+        c = CRC16_TABLE[(((c >> 8) as u8) ^ byte) as usize] ^ (c << 8);
+    }
+    c
+}
+
+/// Inner checksum appended to the message before RS encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Crc {
+    Crc16,
+    #[default]
+    Crc32,
+}
+
+impl Crc {
+    /// Checksum size in bytes (how much k shrinks by)
+    pub fn size(self) -> usize {
+        match self {
+            Crc::Crc16 => 2,
+            Crc::Crc32 => 4,
+        }
+    }
+
+    /// Checksum of data as big-endian bytes, `size()` long
+    #[cfg(feature = "std")]
+    pub fn checksum(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Crc::Crc16 => crc16(data).to_be_bytes().to_vec(),
+            Crc::Crc32 => crc32(data).to_be_bytes().to_vec(),
+        }
+    }
+
+    /// Append checksum: frame = [data | crc]
+    #[cfg(feature = "std")]
+    pub fn append(self, data: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(data.len() + self.size());
+        frame.extend_from_slice(data);
+        frame.extend_from_slice(&self.checksum(data));
+        frame
+    }
+
+    /// Check a [data | crc] frame, returning the data part if the checksum matches
+    pub fn verify(self, frame: &[u8]) -> Option<&[u8]> {
+        if frame.len() < self.size() {
+            return None;
+        }
+        let (data, crc) = frame.split_at(frame.len() - self.size());
+        let matches = match self {
+            Crc::Crc16 => crc16(data).to_be_bytes() == crc,
+            Crc::Crc32 => crc32(data).to_be_bytes() == crc,
+        };
+        matches.then_some(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_values() {
+        // Standard check values for the ASCII string "123456789"
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn test_append_verify() {
+        for crc in [Crc::Crc16, Crc::Crc32] {
+            let mut frame = crc.append(b"Hello");
+            assert_eq!(frame.len(), 5 + crc.size());
+            assert_eq!(crc.verify(&frame), Some(&b"Hello"[..]));
+            frame[1] ^= 0x01;
+            assert_eq!(crc.verify(&frame), None);
+        }
+    }
+}
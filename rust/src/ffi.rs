@@ -0,0 +1,230 @@
+// C API (`capi` feature): RS encode/decode over caller buffers, declared in include/ecc_rs.h
+// build.rs regenerates the header from this file with cbindgen. Every entry point returns an
+// EccRsStatus and never unwinds into C: panics are caught at the boundary and reported as
+// ECC_RS_STATUS_PANIC. Nothing here allocates per call; codes use the cached generators and
+// the stack-buffer decoder of rs::in_place. The static library for C/C++ linking is built with
+// `cargo rustc --release --lib --no-default-features --features capi --crate-type staticlib`.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use crate::rs::{cached_generator, decode_in_place, encode_into, validate_params, RsError};
+
+/// Result of every ecc_rs_* call; the values are stable across releases
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EccRsStatus {
+    Ok = 0,
+    /// (nsym, nsize) is not a GF(256) RS code: 1 <= nsym < nsize <= 255 is required
+    InvalidParameters = -1,
+    /// A required pointer was NULL
+    NullPointer = -2,
+    /// The message is not nsize - nsym bytes or the codeword is not nsize bytes
+    LengthMismatch = -3,
+    /// An output buffer's capacity is too small for the result
+    BufferTooSmall = -4,
+    /// More errors than the code can correct; the outputs are not written
+    Uncorrectable = -5,
+    /// A bug inside the library; the message says where
+    Panic = -6,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+struct Failure(EccRsStatus, String);
+
+impl From<RsError> for Failure {
+    fn from(e: RsError) -> Self {
+        let status = match e {
+            RsError::InvalidParameters(_) | RsError::InvalidErasure => EccRsStatus::InvalidParameters,
+            RsError::CodewordTooShort => EccRsStatus::LengthMismatch,
+            _ => EccRsStatus::Uncorrectable,
+        };
+        Failure(status, e.to_string())
+    }
+}
+
+/// Run `body` at the C boundary: record the error message for ecc_rs_last_error_message and
+/// turn a panic into ECC_RS_STATUS_PANIC
+fn boundary(body: impl FnOnce() -> Result<(), Failure>) -> i32 {
+    let Failure(status, message) = match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => Failure(EccRsStatus::Ok, String::new()),
+        Ok(Err(failure)) => failure,
+        Err(payload) => {
+            let detail = payload.downcast_ref::<&str>().copied().or(payload.downcast_ref::<String>().map(String::as_str));
+            Failure(EccRsStatus::Panic, format!("panic: {}", detail.unwrap_or("unknown payload")))
+        }
+    };
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status as i32
+}
+
+/// A slice from a C pointer and length; NULL is only accepted for a zero length
+///
+/// # Safety
+/// A non-NULL `data` must be valid for `len` elements for the returned lifetime.
+unsafe fn slice<'a, T>(data: *const T, len: usize, name: &str) -> Result<&'a [T], Failure> {
+    if data.is_null() {
+        return if len == 0 { Ok(&[]) } else { Err(Failure(EccRsStatus::NullPointer, format!("{} is NULL", name))) };
+    }
+    Ok(std::slice::from_raw_parts(data, len))
+}
+
+/// slice for output buffers
+///
+/// # Safety
+/// As for slice, and nothing else may access the buffer during the call.
+unsafe fn slice_mut<'a, T>(data: *mut T, len: usize, name: &str) -> Result<&'a mut [T], Failure> {
+    if data.is_null() {
+        return if len == 0 { Ok(&mut []) } else { Err(Failure(EccRsStatus::NullPointer, format!("{} is NULL", name))) };
+    }
+    Ok(std::slice::from_raw_parts_mut(data, len))
+}
+
+fn check_params(nsym: usize, nsize: usize) -> Result<usize, Failure> {
+    validate_params(nsym, nsize)?;
+    Ok(nsize - nsym)
+}
+
+/// Encode a k = nsize - nsym byte message into the nsize-byte codeword [message | parity]
+/// Writes nsize bytes to out, whose capacity out_cap must be at least nsize.
+///
+/// # Safety
+/// msg must point to msg_len readable bytes and out to out_cap writable bytes that do not
+/// overlap msg.
+#[no_mangle]
+pub unsafe extern "C" fn ecc_rs_encode(nsym: usize, nsize: usize, msg: *const u8, msg_len: usize, out: *mut u8, out_cap: usize) -> i32 {
+    boundary(|| {
+        let k = check_params(nsym, nsize)?;
+        let message = slice(msg, msg_len, "msg")?;
+        let out = slice_mut(out, out_cap, "out")?;
+        if message.len() != k {
+            return Err(Failure(EccRsStatus::LengthMismatch, format!("msg_len {} does not match k={} for (nsize={}, nsym={})", msg_len, k, nsize, nsym)));
+        }
+        if out.len() < nsize {
+            return Err(Failure(EccRsStatus::BufferTooSmall, format!("out_cap {} is below nsize={}", out_cap, nsize)));
+        }
+        encode_into(message, nsym, cached_generator(nsym), &mut out[..nsize])?;
+        Ok(())
+    })
+}
+
+/// Decode an nsize-byte codeword, correcting up to nsym / 2 symbol errors
+/// On ECC_RS_STATUS_OK the k-byte message is written to msg_out (capacity msg_cap >= k), the
+/// corrected codeword positions, ascending, to positions_out, and their count to *num_positions.
+/// positions_cap of nsym / 2 always suffices; a smaller one fails with
+/// ECC_RS_STATUS_BUFFER_TOO_SMALL only when more positions than that were corrected. On any
+/// failure no output is written.
+///
+/// # Safety
+/// codeword must point to codeword_len readable bytes, msg_out to msg_cap writable bytes,
+/// positions_out to positions_cap writable entries and num_positions to one, none overlapping.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ecc_rs_decode(
+    nsym: usize,
+    nsize: usize,
+    codeword: *const u8,
+    codeword_len: usize,
+    msg_out: *mut u8,
+    msg_cap: usize,
+    positions_out: *mut usize,
+    positions_cap: usize,
+    num_positions: *mut usize,
+) -> i32 {
+    boundary(|| {
+        let k = check_params(nsym, nsize)?;
+        let received = slice(codeword, codeword_len, "codeword")?;
+        let msg_out = slice_mut(msg_out, msg_cap, "msg_out")?;
+        let positions_out = slice_mut(positions_out, positions_cap, "positions_out")?;
+        let num_positions = slice_mut(num_positions, 1, "num_positions")?;
+        if received.len() != nsize {
+            return Err(Failure(EccRsStatus::LengthMismatch, format!("codeword_len {} does not match nsize={}", codeword_len, nsize)));
+        }
+        if msg_out.len() < k {
+            return Err(Failure(EccRsStatus::BufferTooSmall, format!("msg_cap {} is below k={}", msg_cap, k)));
+        }
+        let mut buffer = [0u8; 255];
+        let corrected = &mut buffer[..nsize];
+        corrected.copy_from_slice(received);
+        let mut positions = [0usize; 127];
+        let count = decode_in_place(corrected, nsym, &mut positions)?;
+        if count > positions_out.len() {
+            return Err(Failure(EccRsStatus::BufferTooSmall, format!("{} positions corrected but positions_cap is {}", count, positions_cap)));
+        }
+        msg_out[..k].copy_from_slice(&corrected[..k]);
+        positions_out[..count].copy_from_slice(&positions[..count]);
+        num_positions[0] = count;
+        Ok(())
+    })
+}
+
+/// NUL-terminated description of the last failed ecc_rs_* call on this thread, or "" if it
+/// succeeded; owned by the library and valid until the next ecc_rs_* call on this thread
+#[no_mangle]
+pub extern "C" fn ecc_rs_last_error_message() -> *const c_char {
+    LAST_ERROR.try_with(|last| last.borrow().as_ptr()).unwrap_or(ptr::null())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use std::ffi::CStr;
+
+    fn last_error() -> String {
+        // SAFETY: the pointer is valid until the next ecc_rs_* call on this thread
+        unsafe { CStr::from_ptr(ecc_rs_last_error_message()) }.to_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn test_round_trip_and_statuses() {
+        let (nsym, nsize) = (16, 100);
+        let mut rng = Rng::new(384);
+        let message: Vec<u8> = (0..nsize - nsym).map(|_| rng.next_u64() as u8).collect();
+        let mut codeword = [0u8; 128];
+        unsafe {
+            assert_eq!(ecc_rs_encode(nsym, nsize, message.as_ptr(), message.len(), codeword.as_mut_ptr(), codeword.len()), 0);
+            assert_eq!(last_error(), "");
+            let mut received = codeword[..nsize].to_vec();
+            let errors = rng.distinct(nsym / 2, nsize);
+            for &pos in &errors {
+                received[pos] ^= rng.nonzero_byte();
+            }
+            let (mut decoded, mut positions, mut count) = (vec![0u8; nsize], [0usize; 8], 0usize);
+            let decode = |received: &[u8], decoded: &mut [u8], positions: &mut [usize], count: &mut usize| {
+                ecc_rs_decode(nsym, nsize, received.as_ptr(), received.len(), decoded.as_mut_ptr(), decoded.len(), positions.as_mut_ptr(), positions.len(), count)
+            };
+            assert_eq!(decode(&received, &mut decoded, &mut positions, &mut count), 0);
+            let mut expected = errors.clone();
+            expected.sort_unstable();
+            assert_eq!((&decoded[..nsize - nsym], &positions[..count]), (&message[..], &expected[..]));
+
+            assert_eq!(decode(&received, &mut decoded, &mut positions[..7], &mut count), EccRsStatus::BufferTooSmall as i32);
+            let mut garbled = codeword[..nsize].to_vec();
+            for pos in 0..=nsym / 2 {
+                garbled[pos * 3] ^= 0x5a;
+            }
+            assert_eq!(decode(&garbled, &mut decoded, &mut positions, &mut count), EccRsStatus::Uncorrectable as i32);
+            assert!(!last_error().is_empty());
+            assert_eq!(decode(&received[..50], &mut decoded, &mut positions, &mut count), EccRsStatus::LengthMismatch as i32);
+            assert_eq!(last_error(), "codeword_len 50 does not match nsize=100");
+            assert_eq!(ecc_rs_decode(nsym, nsize, ptr::null(), nsize, decoded.as_mut_ptr(), nsize, positions.as_mut_ptr(), 8, &mut count), EccRsStatus::NullPointer as i32);
+            assert_eq!(last_error(), "codeword is NULL");
+            assert_eq!(ecc_rs_encode(0, nsize, message.as_ptr(), message.len(), codeword.as_mut_ptr(), 128), EccRsStatus::InvalidParameters as i32);
+            assert_eq!(last_error(), "invalid RS parameters: nsym must be at least 1");
+            assert_eq!(ecc_rs_encode(nsym, nsize, message.as_ptr(), message.len(), codeword.as_mut_ptr(), 99), EccRsStatus::BufferTooSmall as i32);
+        }
+    }
+
+    #[test]
+    fn test_panic_is_caught() {
+        assert_eq!(boundary(|| panic!("boom")), EccRsStatus::Panic as i32);
+        assert_eq!(last_error(), "panic: boom");
+    }
+}
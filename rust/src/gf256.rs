@@ -1,24 +1,41 @@
 // GF(2^8) arithmetic with primitive polynomial x^8 + x^4 + x^3 + x^2 + 1 (0x11d)
 // This is the standard polynomial used by most RS implementations including QR codes.
 
-const PRIM_POLY: u16 = 0x11d;
+use crate::gf2m::Field;
+use crate::opcount;
+
+#[cfg(feature = "std")]
+pub mod matrix;
+#[cfg(feature = "std")]
+pub mod simd;
+
+pub const PRIM_POLY: u16 = 0x11d;
+
+/// exp table length: doubled so mul can index log a + log b directly (512 + 256 = 768 bytes of
+/// tables), or with `small-tables` one entry per exponent plus alpha^255 (256 + 256 = 512 bytes)
+#[cfg(not(feature = "small-tables"))]
+pub const EXP_LEN: usize = 512;
+#[cfg(feature = "small-tables")]
+pub const EXP_LEN: usize = 256;
 
 /// Precomputed tables for GF(256) arithmetic
+/// Everything else the codec may build is optional: per-nsym generators and split tables in rs
+/// (the latter not with `small-tables`), and, only with `std`, the SIMD kernels' statics in
+/// simd.rs (NIBBLE_TABLES 8 KB, GFNI_MATRICES 2 KB, POWERS 63.75 KB).
 pub struct Gf256Tables {
-    pub exp: [u8; 512],  // exp[i] = alpha^i, doubled for convenience
+    pub exp: [u8; EXP_LEN],  // exp[i] = alpha^i, doubled for convenience unless small-tables
     pub log: [u8; 256],  // log[x] = i where alpha^i = x (log[0] undefined)
 }
 
 impl Gf256Tables {
     pub const fn new() -> Self {
-        let mut exp = [0u8; 512];
+        let mut exp = [0u8; EXP_LEN];
         let mut log = [0u8; 256];
 
         let mut x: u16 = 1;
         let mut i = 0usize;
         while i < 255 {
             exp[i] = x as u8;
-            exp[i + 255] = x as u8; // duplicate for mod-free lookup
             log[x as usize] = i as u8;
             x <<= 1;
             if x & 0x100 != 0 {
@@ -28,7 +45,12 @@ impl Gf256Tables {
         }
         // exp[255] = 1 (alpha^255 = 1), log[1] already set
         exp[255] = 1;
-        exp[510] = 1;
+        // The doubled table repeats the cycle from 255 on for mod-free lookup
+        let mut i = 256;
+        while i < EXP_LEN {
+            exp[i] = exp[i - 255];
+            i += 1;
+        }
         // log[0] is undefined but set to 0 to avoid issues
         log[0] = 0;
 
@@ -40,7 +62,10 @@ impl Gf256Tables {
         if a == 0 || b == 0 {
             0
         } else {
-            self.exp[(self.log[a as usize] as usize) + (self.log[b as usize] as usize)]
+            let sum = (self.log[a as usize] as usize) + (self.log[b as usize] as usize);
+            #[cfg(feature = "small-tables")]
+            let sum = if sum >= 255 { sum - 255 } else { sum };
+            self.exp[sum]
         }
     }
 
@@ -73,26 +98,84 @@ impl Gf256Tables {
     }
 }
 
+impl Default for Gf256Tables {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Field for Gf256Tables {
+    type Elem = u8;
+
+    fn one(&self) -> u8 {
+        1
+    }
+
+    fn mul(&self, a: u8, b: u8) -> u8 {
+        opcount::mul(1);
+        Gf256Tables::mul(self, a, b)
+    }
+
+    fn inv(&self, a: u8) -> u8 {
+        opcount::inv(1);
+        Gf256Tables::inv(self, a)
+    }
+}
+
 // Global static tables (computed at compile time)
 pub static GF: Gf256Tables = Gf256Tables::new();
 
+/// SIMD multiply kernels compiled into this build, in order of preference
+#[cfg(target_arch = "x86_64")]
+pub const SIMD_KERNELS: &[&str] = &["gfni-avx512", "gfni", "avx2", "ssse3"];
+#[cfg(all(target_arch = "aarch64", feature = "neon"))]
+pub const SIMD_KERNELS: &[&str] = &["neon"];
+#[cfg(not(any(target_arch = "x86_64", all(target_arch = "aarch64", feature = "neon"))))]
+pub const SIMD_KERNELS: &[&str] = &[];
+
+/// Kernel picked for bulk multiplication on this machine
+#[cfg(feature = "std")]
+pub fn active_kernel() -> &'static str {
+    simd::active().name()
+}
+
 #[inline]
 pub fn gf_mul(a: u8, b: u8) -> u8 {
+    opcount::mul(1);
     GF.mul(a, b)
 }
 
+/// Counted by opcount as an inverse and a multiply, which is how hardware divides
 #[inline]
 pub fn gf_div(a: u8, b: u8) -> u8 {
+    opcount::inv(1);
+    opcount::mul(1);
     GF.div(a, b)
 }
 
 #[inline]
 pub fn gf_inv(a: u8) -> u8 {
+    opcount::inv(1);
     GF.inv(a)
 }
 
+/// gf_mul in constant time, for secret operands (sss): shift-and-add over all 8 bits with masks
+/// in place of branches, and no table lookups whose cache footprint depends on a or b
+#[inline]
+pub fn gf_mul_ct(mut a: u8, mut b: u8) -> u8 {
+    opcount::mul(1);
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        a = (a << 1) ^ ((PRIM_POLY & 0xff) as u8 & 0u8.wrapping_sub(a >> 7));
+        b >>= 1;
+    }
+    product
+}
+
 /// Polynomial multiplication in GF(256)[x]
 /// Result degree = deg(p) + deg(q)
+#[cfg(feature = "std")]
 pub fn poly_mul(p: &[u8], q: &[u8]) -> Vec<u8> {
     if p.is_empty() || q.is_empty() {
         return vec![];
@@ -103,13 +186,77 @@ pub fn poly_mul(p: &[u8], q: &[u8]) -> Vec<u8> {
             result[i + j] ^= gf_mul(pi, qj);
         }
     }
+    opcount::add(p.len() * q.len());
     result
 }
 
+/// a^n for any integer n (a^0 = 1, including 0^0); panics on 0 to a negative power
+pub fn gf_pow(a: u8, n: i64) -> u8 {
+    if a == 0 {
+        assert!(n >= 0, "zero to a negative power in GF(256)");
+        return if n == 0 { 1 } else { 0 };
+    }
+    let e = (GF.log[a as usize] as i64 * n.rem_euclid(255)).rem_euclid(255);
+    GF.exp[e as usize]
+}
+
+/// Evaluate at x by Horner's rule, poly[0] = constant term
+pub fn poly_eval(poly: &[u8], x: u8) -> u8 {
+    opcount::add(poly.len());
+    poly.iter().rev().fold(0u8, |acc, &c| gf_mul(acc, x) ^ c)
+}
+
+/// (quotient, remainder) of dividend / divisor, both with poly[0] = constant term
+/// Leading zero coefficients of the divisor are ignored; the remainder has deg(divisor) entries.
+/// Panics if the divisor is zero.
+#[cfg(feature = "std")]
+pub fn poly_div(dividend: &[u8], divisor: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let deg = divisor.iter().rposition(|&c| c != 0).expect("division by the zero polynomial");
+    let lead_inv = gf_inv(divisor[deg]);
+    let mut rem = dividend.to_vec();
+    if rem.len() <= deg {
+        rem.resize(deg, 0);
+        return (vec![], rem);
+    }
+    let mut quotient = vec![0u8; rem.len() - deg];
+    for i in (0..quotient.len()).rev() {
+        let coef = gf_mul(rem[i + deg], lead_inv);
+        quotient[i] = coef;
+        if coef != 0 {
+            for (j, &d) in divisor[..=deg].iter().enumerate() {
+                rem[i + j] ^= gf_mul(d, coef);
+            }
+            opcount::add(deg + 1);
+        }
+    }
+    rem.truncate(deg);
+    (quotient, rem)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_mul_matches_shift_and_add() {
+        // Independent of exp/log, so this also covers the small-tables reduction
+        for a in 0u8..=255 {
+            for b in 0u8..=255 {
+                let (mut x, mut y, mut product) = (a, b, 0u8);
+                while y != 0 {
+                    if y & 1 != 0 {
+                        product ^= x;
+                    }
+                    x = (x << 1) ^ if x & 0x80 != 0 { (PRIM_POLY & 0xff) as u8 } else { 0 };
+                    y >>= 1;
+                }
+                assert_eq!(gf_mul(a, b), product, "a={} b={}", a, b);
+                assert_eq!(gf_mul_ct(a, b), product, "a={} b={}", a, b);
+            }
+        }
+        assert_eq!(std::mem::size_of::<Gf256Tables>(), EXP_LEN + 256);
+    }
+
     #[test]
     fn test_gf_mul_identity() {
         for a in 0u8..=255 {
@@ -137,5 +284,29 @@ mod tests {
             }
         }
     }
-}
 
+    #[test]
+    fn test_gf_pow() {
+        assert_eq!(gf_pow(2, 8), 0x1d);
+        assert_eq!(gf_pow(2, 255), 1);
+        assert_eq!(gf_pow(0x53, -1), gf_inv(0x53));
+        assert_eq!((gf_pow(0, 0), gf_pow(0, 5)), (1, 0));
+        for a in 1u8..=255 {
+            assert_eq!(gf_pow(a, 3), gf_mul(a, gf_mul(a, a)));
+        }
+    }
+
+    #[test]
+    fn test_poly_div() {
+        let divisor = [3, 0, 7, 1];
+        let quotient = [9, 200, 1];
+        let remainder = [5, 6, 0];
+        let mut dividend = poly_mul(&quotient, &divisor);
+        for (d, r) in dividend.iter_mut().zip(remainder) {
+            *d ^= r;
+        }
+        assert_eq!(poly_div(&dividend, &divisor), (quotient.to_vec(), remainder.to_vec()));
+        assert_eq!(poly_div(&[4, 5], &[1, 1, 1, 0]), (vec![], vec![4, 5]));
+        assert_eq!(poly_eval(&dividend, 0x41), gf_mul(poly_eval(&quotient, 0x41), poly_eval(&divisor, 0x41)) ^ poly_eval(&remainder, 0x41));
+    }
+}
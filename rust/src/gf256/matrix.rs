@@ -0,0 +1,122 @@
+// Dense linear algebra over GF(256): row-major byte matrices, Gauss-Jordan elimination
+// Subtraction is XOR, so eliminating a row is one multiply-accumulate per entry. Sizes here are
+// at most a codeword (255), so the cubic elimination is fine for decoding-by-algebra and shard
+// reconstruction, where each matrix is inverted or solved once.
+
+use super::{gf_inv, gf_mul};
+
+/// solve() found no solution: `rank` is the rank of the coefficient matrix, lower than that of
+/// the system with the right-hand side appended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inconsistent {
+    pub rank: usize,
+}
+
+/// One solution x of A x = b, A row-major with b.len() rows and `cols` columns
+/// Overdetermined systems are fine when consistent; when A is rank-deficient the free unknowns
+/// are set to zero. O(rows * cols * min(rows, cols)).
+pub fn solve(mut a: Vec<u8>, cols: usize, mut b: Vec<u8>) -> Result<Vec<u8>, Inconsistent> {
+    let rows = b.len();
+    assert_eq!(a.len(), rows * cols, "matrix is not rows x cols");
+    let mut pivots = Vec::with_capacity(cols.min(rows));
+    for col in 0..cols {
+        let rank = pivots.len();
+        let Some(pivot) = (rank..rows).find(|&r| a[r * cols + col] != 0) else {
+            continue;
+        };
+        for c in col..cols {
+            a.swap(rank * cols + c, pivot * cols + c);
+        }
+        b.swap(rank, pivot);
+        let scale = gf_inv(a[rank * cols + col]);
+        for c in col..cols {
+            a[rank * cols + c] = gf_mul(a[rank * cols + c], scale);
+        }
+        b[rank] = gf_mul(b[rank], scale);
+        for r in (0..rows).filter(|&r| r != rank) {
+            let factor = a[r * cols + col];
+            if factor != 0 {
+                for c in col..cols {
+                    a[r * cols + c] ^= gf_mul(factor, a[rank * cols + c]);
+                }
+                b[r] ^= gf_mul(factor, b[rank]);
+            }
+        }
+        pivots.push(col);
+    }
+    let rank = pivots.len();
+    if b[rank..].iter().any(|&v| v != 0) {
+        return Err(Inconsistent { rank });
+    }
+    let mut x = vec![0u8; cols];
+    for (row, &col) in pivots.iter().enumerate() {
+        x[col] = b[row];
+    }
+    Ok(x)
+}
+
+/// Gauss-Jordan inverse of a row-major k x k matrix; None if singular
+pub fn invert(mut a: Vec<u8>, k: usize) -> Option<Vec<u8>> {
+    let mut inv = vec![0u8; k * k];
+    for i in 0..k {
+        inv[i * k + i] = 1;
+    }
+    for col in 0..k {
+        let pivot = (col..k).find(|&r| a[r * k + col] != 0)?;
+        for c in 0..k {
+            a.swap(col * k + c, pivot * k + c);
+            inv.swap(col * k + c, pivot * k + c);
+        }
+        let scale = gf_inv(a[col * k + col]);
+        for c in 0..k {
+            a[col * k + c] = gf_mul(a[col * k + c], scale);
+            inv[col * k + c] = gf_mul(inv[col * k + c], scale);
+        }
+        for r in (0..k).filter(|&r| r != col) {
+            let factor = a[r * k + col];
+            if factor != 0 {
+                for c in 0..k {
+                    a[r * k + c] ^= gf_mul(factor, a[col * k + c]);
+                    inv[r * k + c] ^= gf_mul(factor, inv[col * k + c]);
+                }
+            }
+        }
+    }
+    Some(inv)
+}
+
+/// A x for row-major A with x.len() columns
+pub fn mul_vec(a: &[u8], x: &[u8]) -> Vec<u8> {
+    a.chunks(x.len()).map(|row| row.iter().zip(x).fold(0u8, |acc, (&a, &x)| acc ^ gf_mul(a, x))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gf256::gf_pow;
+
+    #[test]
+    fn test_solve_square_matches_inverse() {
+        // Vandermonde on distinct points is invertible
+        let k = 6;
+        let a: Vec<u8> = (0..k).flat_map(|r| (0..k).map(move |c| gf_pow(r as u8 + 2, c as i64))).collect();
+        let x: Vec<u8> = (1..=k as u8).collect();
+        let b = mul_vec(&a, &x);
+        assert_eq!(solve(a.clone(), k, b.clone()), Ok(x.clone()));
+        assert_eq!(mul_vec(&invert(a, k).unwrap(), &b), x);
+    }
+
+    #[test]
+    fn test_solve_rank_deficient() {
+        // Row 2 = row 0 + row 1; consistent with any b2 = b0 + b1, the free unknown set to zero
+        let a = vec![1, 2, 3, 0, 1, 5, 1, 3, 6];
+        let x = solve(a.clone(), 3, vec![7, 4, 3]).unwrap();
+        assert_eq!((mul_vec(&a, &x), x[2]), (vec![7, 4, 3], 0));
+        assert_eq!(solve(a.clone(), 3, vec![7, 4, 2]), Err(Inconsistent { rank: 2 }));
+        assert_eq!(invert(a, 3), None);
+        // Overdetermined: four equations in two unknowns
+        let a = vec![1, 0, 0, 1, 1, 1, 2, 3];
+        assert_eq!(solve(a.clone(), 2, mul_vec(&a, &[9, 17])), Ok(vec![9, 17]));
+        assert_eq!(solve(a, 2, vec![9, 17, 0, 0]), Err(Inconsistent { rank: 2 }));
+    }
+}
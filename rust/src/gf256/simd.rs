@@ -0,0 +1,428 @@
+// Vectorized GF(256) kernels for encode, slice multiply and syndromes, picked at runtime
+// Multiplying a vector by one coefficient c is two 16-entry table lookups, one per nibble:
+// c*x = c*(x & 0x0f) ^ c*(x & 0xf0). PSHUFB (x86) and TBL (aarch64) do 16 or 32 such lookups at
+// once, so each message byte's parity update, parity ^= feedback * taps, costs a few instructions
+// per vector instead of nsym log/exp lookups. GFNI does the same multiply in one GF2P8AFFINEQB:
+// x -> c*x is GF(2)-linear, so it is an 8x8 bit matrix whatever the field polynomial (the
+// built-in GF2P8MULB is fixed to 0x11b). The scalar paths stay the oracle every kernel matches.
+// ECC_MODEL_KERNEL=<name> forces a supported kernel, for benchmarking.
+
+use std::sync::OnceLock;
+
+use super::GF;
+use crate::opcount::{self, Stage};
+
+/// Zero-padded copy of up to 255 taps or parity bytes
+#[cfg(any(target_arch = "x86_64", all(target_arch = "aarch64", feature = "neon")))]
+type Padded = [u8; 256];
+
+#[cfg(any(target_arch = "x86_64", all(target_arch = "aarch64", feature = "neon")))]
+fn padded(bytes: &[u8]) -> Padded {
+    let mut out = [0u8; 256];
+    out[..bytes.len()].copy_from_slice(bytes);
+    out
+}
+
+/// Instantiate `$kernel::<V>` for the number of vectors the taps or syndromes need
+#[allow(unused_macros)]
+macro_rules! dispatch {
+    ($kernel:ident, $vectors:expr, $args:tt; $($v:literal)*) => {
+        match $vectors {
+            $($v => $kernel::<$v> $args,)*
+            _ => unreachable!("at most 256 lanes"),
+        }
+    };
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86;
+
+#[cfg(all(target_arch = "aarch64", feature = "neon"))]
+mod neon;
+
+/// Low and high nibble product tables for every coefficient c:
+/// NIBBLE_TABLES[c][0][x] = c*x, NIBBLE_TABLES[c][1][x] = c*(x << 4)
+pub static NIBBLE_TABLES: [[[u8; 16]; 2]; 256] = nibble_tables();
+
+const fn nibble_tables() -> [[[u8; 16]; 2]; 256] {
+    let mut tables = [[[0u8; 16]; 2]; 256];
+    let mut c = 0;
+    while c < 256 {
+        let mut x = 0;
+        while x < 16 {
+            tables[c][0][x] = const_mul(c as u8, x as u8);
+            tables[c][1][x] = const_mul(c as u8, (x << 4) as u8);
+            x += 1;
+        }
+        c += 1;
+    }
+    tables
+}
+
+/// GF2P8AFFINEQB matrices for x -> c*x: byte 7 - i of GFNI_MATRICES[c] selects the input bits
+/// whose images under the multiply have bit i set
+pub static GFNI_MATRICES: [u64; 256] = gfni_matrices();
+
+const fn gfni_matrices() -> [u64; 256] {
+    let mut matrices = [0u64; 256];
+    let mut c = 0;
+    while c < 256 {
+        let mut i = 0;
+        while i < 8 {
+            let mut row = 0u64;
+            let mut k = 0;
+            while k < 8 {
+                if (const_mul(c as u8, 1 << k) >> i) & 1 != 0 {
+                    row |= 1 << k;
+                }
+                k += 1;
+            }
+            matrices[c] |= row << (8 * (7 - i));
+            i += 1;
+        }
+        c += 1;
+    }
+    matrices
+}
+
+/// POWERS[p][j] = alpha^(j*p): row p is what a codeword byte at power p contributes to each
+/// syndrome, per unit of its value; rows are 256 wide so every vector load stays in bounds
+pub static POWERS: [[u8; 256]; 255] = powers();
+
+const fn powers() -> [[u8; 256]; 255] {
+    let exp = super::Gf256Tables::new().exp;
+    let mut powers = [[0u8; 256]; 255];
+    let mut p = 0;
+    while p < 255 {
+        let mut j = 0;
+        while j < 256 {
+            powers[p][j] = exp[(j * p) % 255];
+            j += 1;
+        }
+        p += 1;
+    }
+    powers
+}
+
+/// Shift-and-add product, usable in const context
+const fn const_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= (super::PRIM_POLY & 0xff) as u8;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Implementation of the bulk kernels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    Scalar,
+    Ssse3,
+    Avx2,
+    /// GFNI on 128-bit vectors, for parts without AVX-512
+    Gfni,
+    /// GFNI on 512-bit vectors with AVX-512F/BW
+    GfniAvx512,
+    Neon,
+}
+
+impl Kernel {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kernel::Scalar => "scalar",
+            Kernel::Ssse3 => "ssse3",
+            Kernel::Avx2 => "avx2",
+            Kernel::Gfni => "gfni",
+            Kernel::GfniAvx512 => "gfni-avx512",
+            Kernel::Neon => "neon",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Kernel> {
+        [Kernel::Scalar, Kernel::Ssse3, Kernel::Avx2, Kernel::Gfni, Kernel::GfniAvx512, Kernel::Neon]
+            .into_iter()
+            .find(|k| k.name() == name)
+    }
+
+    /// Whether this kernel is compiled in and the CPU supports it
+    pub fn supported(self) -> bool {
+        match self {
+            Kernel::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Ssse3 => is_x86_feature_detected!("ssse3"),
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Gfni => is_x86_feature_detected!("gfni") && is_x86_feature_detected!("ssse3"),
+            #[cfg(target_arch = "x86_64")]
+            Kernel::GfniAvx512 => {
+                is_x86_feature_detected!("gfni") && is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512bw")
+            }
+            #[cfg(all(target_arch = "aarch64", feature = "neon"))]
+            Kernel::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+}
+
+/// Vector kernels compiled into this build, in order of preference
+/// NEON needs the `neon` feature until its kernels have been built and tested on aarch64 hardware.
+#[cfg(target_arch = "x86_64")]
+pub const COMPILED: &[Kernel] = &[Kernel::GfniAvx512, Kernel::Gfni, Kernel::Avx2, Kernel::Ssse3];
+#[cfg(all(target_arch = "aarch64", feature = "neon"))]
+pub const COMPILED: &[Kernel] = &[Kernel::Neon];
+#[cfg(not(any(target_arch = "x86_64", all(target_arch = "aarch64", feature = "neon"))))]
+pub const COMPILED: &[Kernel] = &[];
+
+/// Best kernel for this CPU, detected once
+/// ECC_MODEL_KERNEL set to a kernel name overrides the choice if this CPU supports that kernel;
+/// any other value is ignored.
+pub fn active() -> Kernel {
+    static ACTIVE: OnceLock<Kernel> = OnceLock::new();
+    *ACTIVE.get_or_init(|| {
+        let forced = std::env::var("ECC_MODEL_KERNEL").ok().and_then(|name| Kernel::from_name(&name));
+        forced
+            .filter(|k| k.supported())
+            .or_else(|| COMPILED.iter().copied().find(|k| k.supported()))
+            .unwrap_or(Kernel::Scalar)
+    })
+}
+
+/// parity = message(x) * x^nsym mod g(x), where taps[j] = g[nsym - 1 - j] (g monic, low order first)
+/// and nsym = taps.len() = parity.len()
+pub fn remainder(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    remainder_with(active(), message, taps, parity)
+}
+
+/// remainder on a given kernel; panics if the CPU does not support it
+pub fn remainder_with(kernel: Kernel, message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    assert_eq!(taps.len(), parity.len(), "one parity byte per tap");
+    assert!(taps.len() <= 255, "at most 255 taps over GF(256)");
+    assert!(kernel.supported(), "{} kernel is not available on this CPU", kernel.name());
+    if taps.is_empty() {
+        return;
+    }
+    let _ops = opcount::stage(Stage::Encode);
+    opcount::mul(message.len() * taps.len());
+    opcount::add(message.len() * taps.len());
+    match kernel {
+        // SAFETY: the CPU supports the kernel, checked above
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Ssse3 => unsafe { x86::remainder_ssse3(message, taps, parity) },
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Avx2 => unsafe { x86::remainder_avx2(message, taps, parity) },
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Gfni => unsafe { x86::remainder_gfni(message, taps, parity) },
+        #[cfg(target_arch = "x86_64")]
+        Kernel::GfniAvx512 => unsafe { x86::remainder_gfni_avx512(message, taps, parity) },
+        #[cfg(all(target_arch = "aarch64", feature = "neon"))]
+        Kernel::Neon => unsafe { neon::remainder_neon(message, taps, parity) },
+        _ => remainder_scalar(message, taps, parity),
+    }
+}
+
+/// Byte-at-a-time LFSR with the same contract as the vector kernels
+/// Not counted by opcount: remainder_with charges every kernel alike.
+pub fn remainder_scalar(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    parity.fill(0);
+    for &m in message {
+        let fb = m ^ parity[0];
+        parity.copy_within(1.., 0);
+        *parity.last_mut().unwrap() = 0;
+        for (r, &t) in parity.iter_mut().zip(taps) {
+            *r ^= GF.mul(t, fb);
+        }
+    }
+}
+
+/// dst[i] ^= c * src[i] over equal-length slices
+pub fn mul_add_slice(dst: &mut [u8], src: &[u8], c: u8) {
+    mul_add_slice_with(active(), dst, src, c)
+}
+
+/// mul_add_slice on a given kernel; panics if the CPU does not support it
+pub fn mul_add_slice_with(kernel: Kernel, dst: &mut [u8], src: &[u8], c: u8) {
+    assert_eq!(dst.len(), src.len(), "slices differ in length");
+    assert!(kernel.supported(), "{} kernel is not available on this CPU", kernel.name());
+    match (c, kernel) {
+        (0, _) => {}
+        (1, _) => dst.iter_mut().zip(src).for_each(|(d, &s)| *d ^= s),
+        // SAFETY: the CPU supports the kernel, checked above
+        #[cfg(target_arch = "x86_64")]
+        (_, Kernel::Ssse3) => unsafe { x86::mul_add_slice_ssse3(dst, src, c) },
+        #[cfg(target_arch = "x86_64")]
+        (_, Kernel::Avx2) => unsafe { x86::mul_add_slice_avx2(dst, src, c) },
+        #[cfg(target_arch = "x86_64")]
+        (_, Kernel::Gfni) => unsafe { x86::mul_add_slice_gfni(dst, src, c) },
+        #[cfg(target_arch = "x86_64")]
+        (_, Kernel::GfniAvx512) => unsafe { x86::mul_add_slice_gfni_avx512(dst, src, c) },
+        #[cfg(all(target_arch = "aarch64", feature = "neon"))]
+        (_, Kernel::Neon) => unsafe { neon::mul_add_slice_neon(dst, src, c) },
+        _ => mul_add_slice_scalar(dst, src, c),
+    }
+}
+
+/// Per-byte nibble lookups; also finishes the vector kernels' tails
+pub fn mul_add_slice_scalar(dst: &mut [u8], src: &[u8], c: u8) {
+    let [lo, hi] = &NIBBLE_TABLES[c as usize];
+    for (d, &s) in dst.iter_mut().zip(src) {
+        *d ^= lo[(s & 0x0f) as usize] ^ hi[(s >> 4) as usize];
+    }
+}
+
+/// out[j] = codeword(alpha^j) for j < out.len(), codeword[0] the highest-order coefficient
+/// Each kernel accumulates byte * POWERS[power] across the codeword, all syndromes at once.
+pub fn syndromes(codeword: &[u8], out: &mut [u8]) {
+    syndromes_with(active(), codeword, out)
+}
+
+/// syndromes on a given kernel; panics if the CPU does not support it
+pub fn syndromes_with(kernel: Kernel, codeword: &[u8], out: &mut [u8]) {
+    assert!(kernel.supported(), "{} kernel is not available on this CPU", kernel.name());
+    opcount::mul(codeword.len() * out.len());
+    opcount::add(codeword.len() * out.len());
+    if out.is_empty() || out.len() > 256 {
+        return syndromes_scalar(codeword, out);
+    }
+    match kernel {
+        // SAFETY: the CPU supports the kernel, checked above, and out fits a POWERS row
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Ssse3 => unsafe { x86::syndromes_ssse3(codeword, out) },
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Avx2 => unsafe { x86::syndromes_avx2(codeword, out) },
+        #[cfg(target_arch = "x86_64")]
+        Kernel::Gfni => unsafe { x86::syndromes_gfni(codeword, out) },
+        #[cfg(target_arch = "x86_64")]
+        Kernel::GfniAvx512 => unsafe { x86::syndromes_gfni_avx512(codeword, out) },
+        #[cfg(all(target_arch = "aarch64", feature = "neon"))]
+        Kernel::Neon => unsafe { neon::syndromes_neon(codeword, out) },
+        _ => syndromes_scalar(codeword, out),
+    }
+}
+
+/// Direct power sum per syndrome, the reference for the vector kernels
+/// Not counted by opcount, which leaves it to syndromes_with, so debug checks are free.
+pub fn syndromes_scalar(codeword: &[u8], out: &mut [u8]) {
+    let n = codeword.len();
+    for (j, syn) in out.iter_mut().enumerate() {
+        let mut s = 0u8;
+        // r(x) = sum_{i=0}^{n-1} r_i * x^i where r_i = codeword[n-1-i]
+        // r(alpha^j) = sum_{i=0}^{n-1} codeword[n-1-i] * alpha^(j*i)
+        for (idx, &byte) in codeword.iter().enumerate() {
+            let power = n - 1 - idx;
+            let alpha_power = GF.exp[(j * power) % 255];
+            s ^= GF.mul(byte, alpha_power);
+        }
+        *syn = s;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gf256::gf_mul;
+    use crate::rs::sim::Rng;
+
+    #[test]
+    fn test_nibble_tables() {
+        for c in 0..=255u8 {
+            for x in 0..=255u8 {
+                let [lo, hi] = NIBBLE_TABLES[c as usize];
+                assert_eq!(lo[(x & 0x0f) as usize] ^ hi[(x >> 4) as usize], gf_mul(c, x));
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernels_match_scalar() {
+        let mut rng = Rng::new(364);
+        let kernels: Vec<Kernel> = COMPILED.iter().copied().filter(|k| k.supported()).collect();
+        for _ in 0..300 {
+            // Messages and tap counts on both sides of one vector
+            let limit = if rng.below(2) == 0 { 20 } else { 254 };
+            let k = rng.below(limit);
+            let nsym = 1 + rng.below(255 - k);
+            let message: Vec<u8> = (0..k).map(|_| rng.next_u64() as u8).collect();
+            let taps: Vec<u8> = (0..nsym).map(|_| rng.next_u64() as u8).collect();
+            let mut expected = vec![0u8; nsym];
+            remainder_scalar(&message, &taps, &mut expected);
+            for &kernel in &kernels {
+                let mut got = vec![0xa5u8; nsym];
+                remainder_with(kernel, &message, &taps, &mut got);
+                assert_eq!(got, expected, "{} k={} nsym={}", kernel.name(), k, nsym);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mul_add_slice_matches_scalar() {
+        let mut rng = Rng::new(365);
+        let kernels: Vec<Kernel> = COMPILED.iter().copied().filter(|k| k.supported()).collect();
+        for len in [0, 1, 15, 16, 17, 31, 32, 33, 100, 1000] {
+            let src: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+            let dst: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+            for c in [0, 1, 2, rng.next_u64() as u8, 255] {
+                let expected: Vec<u8> = dst.iter().zip(&src).map(|(&d, &s)| d ^ gf_mul(c, s)).collect();
+                let mut got = dst.clone();
+                mul_add_slice_scalar(&mut got, &src, c);
+                assert_eq!(got, expected);
+                for &kernel in &kernels {
+                    let mut got = dst.clone();
+                    mul_add_slice_with(kernel, &mut got, &src, c);
+                    assert_eq!(got, expected, "{} len={} c={}", kernel.name(), len, c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_gfni_multiply_exhaustive() {
+        // Every (c, x) pair through each GFNI kernel's core multiply
+        let src: Vec<u8> = (0..=255).collect();
+        for kernel in [Kernel::Gfni, Kernel::GfniAvx512].into_iter().filter(|k| k.supported()) {
+            for c in 0..=255u8 {
+                let mut product = vec![0u8; 256];
+                mul_add_slice_with(kernel, &mut product, &src, c);
+                for x in 0..=255u8 {
+                    assert_eq!(product[x as usize], gf_mul(c, x), "{} c={} x={}", kernel.name(), c, x);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_syndromes_match_scalar() {
+        let mut rng = Rng::new(366);
+        let kernels: Vec<Kernel> = COMPILED.iter().copied().filter(|k| k.supported()).collect();
+        for _ in 0..400 {
+            let nsym = 2 + rng.below(63);
+            let n = nsym + 1 + rng.below(255 - nsym);
+            let codeword: Vec<u8> = (0..n).map(|_| if rng.below(4) == 0 { 0 } else { rng.next_u64() as u8 }).collect();
+            let mut expected = vec![0u8; nsym];
+            syndromes_scalar(&codeword, &mut expected);
+            for &kernel in &kernels {
+                let mut got = vec![0xa5u8; nsym];
+                syndromes_with(kernel, &codeword, &mut got);
+                assert_eq!(got, expected, "{} n={} nsym={}", kernel.name(), n, nsym);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernel_names() {
+        for &kernel in COMPILED {
+            assert_eq!(Kernel::from_name(kernel.name()), Some(kernel));
+        }
+        assert_eq!(Kernel::from_name("scalar"), Some(Kernel::Scalar));
+        assert_eq!(Kernel::from_name("sse9"), None);
+    }
+}
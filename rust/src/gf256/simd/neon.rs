@@ -0,0 +1,97 @@
+// aarch64 NEON kernels: the x86 encode, slice multiply and syndrome algorithms with TBL
+// (vqtbl1q_u8) as the nibble lookup
+// Every 16-byte vector register holds a whole table, so one TBL per nibble is a full lookup; the
+// parity register shifts down a byte with EXT, the same way PALIGNR does it on x86.
+// Compiled only with the `neon` feature: none of these kernels, syndromes_neon included, has yet
+// been built for aarch64 or run against the scalar oracle there (simd.rs tests, e.g. under qemu).
+
+use std::arch::aarch64::*;
+
+use super::{mul_add_slice_scalar, padded, Padded, NIBBLE_TABLES, POWERS};
+
+/// # Safety
+/// The CPU must support NEON; taps and parity have the same length, at most 255
+#[target_feature(enable = "neon")]
+pub unsafe fn remainder_neon(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    dispatch!(remainder_neon_v, taps.len().div_ceil(16), (message, taps, parity); 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16)
+}
+
+#[target_feature(enable = "neon")]
+unsafe fn remainder_neon_v<const V: usize>(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    let mask = vdupq_n_u8(0x0f);
+    let taps = padded(taps);
+    let mut lo = [vdupq_n_u8(0); V];
+    let mut hi = [vdupq_n_u8(0); V];
+    for v in 0..V {
+        let t = vld1q_u8(taps.as_ptr().add(16 * v));
+        lo[v] = vandq_u8(t, mask);
+        hi[v] = vshrq_n_u8(t, 4);
+    }
+    let mut r = [vdupq_n_u8(0); V];
+    for &m in message {
+        let fb = m ^ vgetq_lane_u8(r[0], 0);
+        let [tlo, thi] = &NIBBLE_TABLES[fb as usize];
+        let tlo = vld1q_u8(tlo.as_ptr());
+        let thi = vld1q_u8(thi.as_ptr());
+        for v in 0..V {
+            let next = if v + 1 < V { r[v + 1] } else { vdupq_n_u8(0) };
+            let product = veorq_u8(vqtbl1q_u8(tlo, lo[v]), vqtbl1q_u8(thi, hi[v]));
+            r[v] = veorq_u8(vextq_u8(r[v], next, 1), product);
+        }
+    }
+    let mut out: Padded = [0; 256];
+    for (v, &rv) in r.iter().enumerate() {
+        vst1q_u8(out.as_mut_ptr().add(16 * v), rv);
+    }
+    parity.copy_from_slice(&out[..parity.len()]);
+}
+
+/// # Safety
+/// The CPU must support NEON; dst and src have the same length
+#[target_feature(enable = "neon")]
+pub unsafe fn mul_add_slice_neon(dst: &mut [u8], src: &[u8], c: u8) {
+    let mask = vdupq_n_u8(0x0f);
+    let [tlo, thi] = &NIBBLE_TABLES[c as usize];
+    let tlo = vld1q_u8(tlo.as_ptr());
+    let thi = vld1q_u8(thi.as_ptr());
+    let body = dst.len() / 16 * 16;
+    for i in (0..body).step_by(16) {
+        let s = vld1q_u8(src.as_ptr().add(i));
+        let d = dst.as_mut_ptr().add(i);
+        let product = veorq_u8(vqtbl1q_u8(tlo, vandq_u8(s, mask)), vqtbl1q_u8(thi, vshrq_n_u8(s, 4)));
+        vst1q_u8(d, veorq_u8(vld1q_u8(d), product));
+    }
+    mul_add_slice_scalar(&mut dst[body..], &src[body..], c);
+}
+
+/// # Safety
+/// The CPU must support NEON; out holds 1 to 256 syndromes
+#[target_feature(enable = "neon")]
+pub unsafe fn syndromes_neon(codeword: &[u8], out: &mut [u8]) {
+    dispatch!(syndromes_neon_v, out.len().div_ceil(16), (codeword, out); 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16)
+}
+
+#[target_feature(enable = "neon")]
+unsafe fn syndromes_neon_v<const V: usize>(codeword: &[u8], out: &mut [u8]) {
+    let n = codeword.len();
+    let mask = vdupq_n_u8(0x0f);
+    let mut acc = [vdupq_n_u8(0); V];
+    for (i, &byte) in codeword.iter().enumerate() {
+        if byte == 0 {
+            continue;
+        }
+        let [tlo, thi] = &NIBBLE_TABLES[byte as usize];
+        let tlo = vld1q_u8(tlo.as_ptr());
+        let thi = vld1q_u8(thi.as_ptr());
+        let row = POWERS[(n - 1 - i) % 255].as_ptr();
+        for (v, a) in acc.iter_mut().enumerate() {
+            let p = vld1q_u8(row.add(16 * v));
+            *a = veorq_u8(*a, veorq_u8(vqtbl1q_u8(tlo, vandq_u8(p, mask)), vqtbl1q_u8(thi, vshrq_n_u8(p, 4))));
+        }
+    }
+    let mut syndromes: Padded = [0; 256];
+    for (v, &a) in acc.iter().enumerate() {
+        vst1q_u8(syndromes.as_mut_ptr().add(16 * v), a);
+    }
+    out.copy_from_slice(&syndromes[..out.len()]);
+}
@@ -0,0 +1,354 @@
+// SSSE3, AVX2 and GFNI kernels: the LFSR form of systematic encoding held in registers, slice
+// multiply, and syndromes
+// The parity register is nsym bytes split over V vectors. Per message byte, the feedback
+// fb = m ^ r[0] selects two 16-byte nibble product tables, PSHUFB turns the pre-split taps into
+// fb * taps, and the register shifts down one byte (PALIGNR) as the product is XORed in. Keeping
+// r in registers avoids reloading a window that the previous byte's store only half covered.
+
+use std::arch::x86_64::*;
+
+use super::{mul_add_slice_scalar, padded, Padded, GFNI_MATRICES, NIBBLE_TABLES, POWERS};
+
+/// # Safety
+/// The CPU must support SSSE3; taps and parity have the same length, at most 255
+#[target_feature(enable = "ssse3")]
+pub unsafe fn remainder_ssse3(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    dispatch!(remainder_ssse3_v, taps.len().div_ceil(16), (message, taps, parity); 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16)
+}
+
+#[target_feature(enable = "ssse3")]
+unsafe fn remainder_ssse3_v<const V: usize>(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    let mask = _mm_set1_epi8(0x0f);
+    let taps = padded(taps);
+    let mut lo = [_mm_setzero_si128(); V];
+    let mut hi = [_mm_setzero_si128(); V];
+    for v in 0..V {
+        let t = _mm_loadu_si128(taps.as_ptr().add(16 * v) as *const __m128i);
+        lo[v] = _mm_and_si128(t, mask);
+        hi[v] = _mm_and_si128(_mm_srli_epi64(t, 4), mask);
+    }
+    let mut r = [_mm_setzero_si128(); V];
+    for &m in message {
+        let fb = m ^ _mm_cvtsi128_si32(r[0]) as u8;
+        let [tlo, thi] = &NIBBLE_TABLES[fb as usize];
+        let tlo = _mm_loadu_si128(tlo.as_ptr() as *const __m128i);
+        let thi = _mm_loadu_si128(thi.as_ptr() as *const __m128i);
+        for v in 0..V {
+            let next = if v + 1 < V { r[v + 1] } else { _mm_setzero_si128() };
+            let product = _mm_xor_si128(_mm_shuffle_epi8(tlo, lo[v]), _mm_shuffle_epi8(thi, hi[v]));
+            r[v] = _mm_xor_si128(_mm_alignr_epi8(next, r[v], 1), product);
+        }
+    }
+    let mut out: Padded = [0; 256];
+    for (v, &rv) in r.iter().enumerate() {
+        _mm_storeu_si128(out.as_mut_ptr().add(16 * v) as *mut __m128i, rv);
+    }
+    parity.copy_from_slice(&out[..parity.len()]);
+}
+
+/// # Safety
+/// The CPU must support AVX2; taps and parity have the same length, at most 255
+#[target_feature(enable = "avx2")]
+pub unsafe fn remainder_avx2(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    dispatch!(remainder_avx2_v, taps.len().div_ceil(32), (message, taps, parity); 1 2 3 4 5 6 7 8)
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn remainder_avx2_v<const V: usize>(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    let mask = _mm256_set1_epi8(0x0f);
+    let taps = padded(taps);
+    let mut lo = [_mm256_setzero_si256(); V];
+    let mut hi = [_mm256_setzero_si256(); V];
+    for v in 0..V {
+        let t = _mm256_loadu_si256(taps.as_ptr().add(32 * v) as *const __m256i);
+        lo[v] = _mm256_and_si256(t, mask);
+        hi[v] = _mm256_and_si256(_mm256_srli_epi64(t, 4), mask);
+    }
+    let mut r = [_mm256_setzero_si256(); V];
+    for &m in message {
+        let fb = m ^ _mm_cvtsi128_si32(_mm256_castsi256_si128(r[0])) as u8;
+        // VPSHUFB looks up within each 128-bit lane, so both lanes get the same table
+        let [tlo, thi] = &NIBBLE_TABLES[fb as usize];
+        let tlo = _mm256_broadcastsi128_si256(_mm_loadu_si128(tlo.as_ptr() as *const __m128i));
+        let thi = _mm256_broadcastsi128_si256(_mm_loadu_si128(thi.as_ptr() as *const __m128i));
+        for v in 0..V {
+            let next = if v + 1 < V { r[v + 1] } else { _mm256_setzero_si256() };
+            // VPALIGNR is also per lane: pair each lane with the one above it first
+            let above = _mm256_permute2x128_si256(r[v], next, 0x21);
+            let product = _mm256_xor_si256(_mm256_shuffle_epi8(tlo, lo[v]), _mm256_shuffle_epi8(thi, hi[v]));
+            r[v] = _mm256_xor_si256(_mm256_alignr_epi8(above, r[v], 1), product);
+        }
+    }
+    let mut out: Padded = [0; 256];
+    for (v, &rv) in r.iter().enumerate() {
+        _mm256_storeu_si256(out.as_mut_ptr().add(32 * v) as *mut __m256i, rv);
+    }
+    parity.copy_from_slice(&out[..parity.len()]);
+}
+
+/// # Safety
+/// The CPU must support SSSE3; dst and src have the same length
+#[target_feature(enable = "ssse3")]
+pub unsafe fn mul_add_slice_ssse3(dst: &mut [u8], src: &[u8], c: u8) {
+    let mask = _mm_set1_epi8(0x0f);
+    let [tlo, thi] = &NIBBLE_TABLES[c as usize];
+    let tlo = _mm_loadu_si128(tlo.as_ptr() as *const __m128i);
+    let thi = _mm_loadu_si128(thi.as_ptr() as *const __m128i);
+    let body = dst.len() / 16 * 16;
+    for i in (0..body).step_by(16) {
+        let s = _mm_loadu_si128(src.as_ptr().add(i) as *const __m128i);
+        let d = dst.as_mut_ptr().add(i) as *mut __m128i;
+        let product = _mm_xor_si128(
+            _mm_shuffle_epi8(tlo, _mm_and_si128(s, mask)),
+            _mm_shuffle_epi8(thi, _mm_and_si128(_mm_srli_epi64(s, 4), mask)),
+        );
+        _mm_storeu_si128(d, _mm_xor_si128(_mm_loadu_si128(d), product));
+    }
+    mul_add_slice_scalar(&mut dst[body..], &src[body..], c);
+}
+
+/// # Safety
+/// The CPU must support AVX2; dst and src have the same length
+#[target_feature(enable = "avx2")]
+pub unsafe fn mul_add_slice_avx2(dst: &mut [u8], src: &[u8], c: u8) {
+    let mask = _mm256_set1_epi8(0x0f);
+    let [tlo, thi] = &NIBBLE_TABLES[c as usize];
+    let tlo = _mm256_broadcastsi128_si256(_mm_loadu_si128(tlo.as_ptr() as *const __m128i));
+    let thi = _mm256_broadcastsi128_si256(_mm_loadu_si128(thi.as_ptr() as *const __m128i));
+    let body = dst.len() / 32 * 32;
+    for i in (0..body).step_by(32) {
+        let s = _mm256_loadu_si256(src.as_ptr().add(i) as *const __m256i);
+        let d = dst.as_mut_ptr().add(i) as *mut __m256i;
+        let product = _mm256_xor_si256(
+            _mm256_shuffle_epi8(tlo, _mm256_and_si256(s, mask)),
+            _mm256_shuffle_epi8(thi, _mm256_and_si256(_mm256_srli_epi64(s, 4), mask)),
+        );
+        _mm256_storeu_si256(d, _mm256_xor_si256(_mm256_loadu_si256(d), product));
+    }
+    mul_add_slice_scalar(&mut dst[body..], &src[body..], c);
+}
+
+/// # Safety
+/// The CPU must support SSSE3; out holds 1 to 256 syndromes
+#[target_feature(enable = "ssse3")]
+pub unsafe fn syndromes_ssse3(codeword: &[u8], out: &mut [u8]) {
+    dispatch!(syndromes_ssse3_v, out.len().div_ceil(16), (codeword, out); 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16)
+}
+
+#[target_feature(enable = "ssse3")]
+unsafe fn syndromes_ssse3_v<const V: usize>(codeword: &[u8], out: &mut [u8]) {
+    let n = codeword.len();
+    let mask = _mm_set1_epi8(0x0f);
+    let mut acc = [_mm_setzero_si128(); V];
+    for (i, &byte) in codeword.iter().enumerate() {
+        if byte == 0 {
+            continue;
+        }
+        let [tlo, thi] = &NIBBLE_TABLES[byte as usize];
+        let tlo = _mm_loadu_si128(tlo.as_ptr() as *const __m128i);
+        let thi = _mm_loadu_si128(thi.as_ptr() as *const __m128i);
+        let row = POWERS[(n - 1 - i) % 255].as_ptr();
+        for (v, a) in acc.iter_mut().enumerate() {
+            let p = _mm_loadu_si128(row.add(16 * v) as *const __m128i);
+            let product = _mm_xor_si128(
+                _mm_shuffle_epi8(tlo, _mm_and_si128(p, mask)),
+                _mm_shuffle_epi8(thi, _mm_and_si128(_mm_srli_epi64(p, 4), mask)),
+            );
+            *a = _mm_xor_si128(*a, product);
+        }
+    }
+    let mut syndromes: Padded = [0; 256];
+    for (v, &a) in acc.iter().enumerate() {
+        _mm_storeu_si128(syndromes.as_mut_ptr().add(16 * v) as *mut __m128i, a);
+    }
+    out.copy_from_slice(&syndromes[..out.len()]);
+}
+
+/// # Safety
+/// The CPU must support AVX2; out holds 1 to 256 syndromes
+#[target_feature(enable = "avx2")]
+pub unsafe fn syndromes_avx2(codeword: &[u8], out: &mut [u8]) {
+    dispatch!(syndromes_avx2_v, out.len().div_ceil(32), (codeword, out); 1 2 3 4 5 6 7 8)
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn syndromes_avx2_v<const V: usize>(codeword: &[u8], out: &mut [u8]) {
+    let n = codeword.len();
+    let mask = _mm256_set1_epi8(0x0f);
+    let mut acc = [_mm256_setzero_si256(); V];
+    for (i, &byte) in codeword.iter().enumerate() {
+        if byte == 0 {
+            continue;
+        }
+        let [tlo, thi] = &NIBBLE_TABLES[byte as usize];
+        let tlo = _mm256_broadcastsi128_si256(_mm_loadu_si128(tlo.as_ptr() as *const __m128i));
+        let thi = _mm256_broadcastsi128_si256(_mm_loadu_si128(thi.as_ptr() as *const __m128i));
+        let row = POWERS[(n - 1 - i) % 255].as_ptr();
+        for (v, a) in acc.iter_mut().enumerate() {
+            let p = _mm256_loadu_si256(row.add(32 * v) as *const __m256i);
+            let product = _mm256_xor_si256(
+                _mm256_shuffle_epi8(tlo, _mm256_and_si256(p, mask)),
+                _mm256_shuffle_epi8(thi, _mm256_and_si256(_mm256_srli_epi64(p, 4), mask)),
+            );
+            *a = _mm256_xor_si256(*a, product);
+        }
+    }
+    let mut syndromes: Padded = [0; 256];
+    for (v, &a) in acc.iter().enumerate() {
+        _mm256_storeu_si256(syndromes.as_mut_ptr().add(32 * v) as *mut __m256i, a);
+    }
+    out.copy_from_slice(&syndromes[..out.len()]);
+}
+
+/// x * c for every byte of x, through the GF2P8AFFINEQB matrix of c
+#[inline]
+#[target_feature(enable = "gfni")]
+unsafe fn gfni_mul(x: __m128i, c: u8) -> __m128i {
+    _mm_gf2p8affine_epi64_epi8(x, _mm_set1_epi64x(GFNI_MATRICES[c as usize] as i64), 0)
+}
+
+#[inline]
+#[target_feature(enable = "gfni,avx512f,avx512bw")]
+unsafe fn gfni_mul_512(x: __m512i, c: u8) -> __m512i {
+    _mm512_gf2p8affine_epi64_epi8(x, _mm512_set1_epi64(GFNI_MATRICES[c as usize] as i64), 0)
+}
+
+/// # Safety
+/// The CPU must support GFNI and SSSE3; taps and parity have the same length, at most 255
+#[target_feature(enable = "gfni,ssse3")]
+pub unsafe fn remainder_gfni(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    dispatch!(remainder_gfni_v, taps.len().div_ceil(16), (message, taps, parity); 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16)
+}
+
+#[target_feature(enable = "gfni,ssse3")]
+unsafe fn remainder_gfni_v<const V: usize>(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    let taps = padded(taps);
+    let mut t = [_mm_setzero_si128(); V];
+    for (v, tv) in t.iter_mut().enumerate() {
+        *tv = _mm_loadu_si128(taps.as_ptr().add(16 * v) as *const __m128i);
+    }
+    let mut r = [_mm_setzero_si128(); V];
+    for &m in message {
+        let fb = m ^ _mm_cvtsi128_si32(r[0]) as u8;
+        for v in 0..V {
+            let next = if v + 1 < V { r[v + 1] } else { _mm_setzero_si128() };
+            r[v] = _mm_xor_si128(_mm_alignr_epi8(next, r[v], 1), gfni_mul(t[v], fb));
+        }
+    }
+    let mut out: Padded = [0; 256];
+    for (v, &rv) in r.iter().enumerate() {
+        _mm_storeu_si128(out.as_mut_ptr().add(16 * v) as *mut __m128i, rv);
+    }
+    parity.copy_from_slice(&out[..parity.len()]);
+}
+
+/// # Safety
+/// The CPU must support GFNI, AVX-512F and AVX-512BW; taps and parity have the same length, at most 255
+#[target_feature(enable = "gfni,avx512f,avx512bw")]
+pub unsafe fn remainder_gfni_avx512(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    dispatch!(remainder_gfni_avx512_v, taps.len().div_ceil(64), (message, taps, parity); 1 2 3 4)
+}
+
+#[target_feature(enable = "gfni,avx512f,avx512bw")]
+unsafe fn remainder_gfni_avx512_v<const V: usize>(message: &[u8], taps: &[u8], parity: &mut [u8]) {
+    let taps = padded(taps);
+    let mut t = [_mm512_setzero_si512(); V];
+    for (v, tv) in t.iter_mut().enumerate() {
+        *tv = _mm512_loadu_si512(taps.as_ptr().add(64 * v) as *const _);
+    }
+    let mut r = [_mm512_setzero_si512(); V];
+    for &m in message {
+        let fb = m ^ _mm_cvtsi128_si32(_mm512_castsi512_si128(r[0])) as u8;
+        for v in 0..V {
+            let next = if v + 1 < V { r[v + 1] } else { _mm512_setzero_si512() };
+            // VPALIGNR works per 128-bit lane: first line each lane up with the one above it
+            let above = _mm512_alignr_epi64(next, r[v], 2);
+            r[v] = _mm512_xor_si512(_mm512_alignr_epi8(above, r[v], 1), gfni_mul_512(t[v], fb));
+        }
+    }
+    let mut out: Padded = [0; 256];
+    for (v, &rv) in r.iter().enumerate() {
+        _mm512_storeu_si512(out.as_mut_ptr().add(64 * v) as *mut _, rv);
+    }
+    parity.copy_from_slice(&out[..parity.len()]);
+}
+
+/// # Safety
+/// The CPU must support GFNI; dst and src have the same length
+#[target_feature(enable = "gfni,sse2")]
+pub unsafe fn mul_add_slice_gfni(dst: &mut [u8], src: &[u8], c: u8) {
+    let body = dst.len() / 16 * 16;
+    for i in (0..body).step_by(16) {
+        let s = _mm_loadu_si128(src.as_ptr().add(i) as *const __m128i);
+        let d = dst.as_mut_ptr().add(i) as *mut __m128i;
+        _mm_storeu_si128(d, _mm_xor_si128(_mm_loadu_si128(d), gfni_mul(s, c)));
+    }
+    mul_add_slice_scalar(&mut dst[body..], &src[body..], c);
+}
+
+/// # Safety
+/// The CPU must support GFNI, AVX-512F and AVX-512BW; dst and src have the same length
+#[target_feature(enable = "gfni,avx512f,avx512bw")]
+pub unsafe fn mul_add_slice_gfni_avx512(dst: &mut [u8], src: &[u8], c: u8) {
+    let body = dst.len() / 64 * 64;
+    for i in (0..body).step_by(64) {
+        let s = _mm512_loadu_si512(src.as_ptr().add(i) as *const _);
+        let d = dst.as_mut_ptr().add(i);
+        _mm512_storeu_si512(d as *mut _, _mm512_xor_si512(_mm512_loadu_si512(d as *const _), gfni_mul_512(s, c)));
+    }
+    mul_add_slice_scalar(&mut dst[body..], &src[body..], c);
+}
+
+/// # Safety
+/// The CPU must support GFNI; out holds 1 to 256 syndromes
+#[target_feature(enable = "gfni,sse2")]
+pub unsafe fn syndromes_gfni(codeword: &[u8], out: &mut [u8]) {
+    dispatch!(syndromes_gfni_v, out.len().div_ceil(16), (codeword, out); 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16)
+}
+
+#[target_feature(enable = "gfni,sse2")]
+unsafe fn syndromes_gfni_v<const V: usize>(codeword: &[u8], out: &mut [u8]) {
+    let n = codeword.len();
+    let mut acc = [_mm_setzero_si128(); V];
+    for (i, &byte) in codeword.iter().enumerate() {
+        if byte == 0 {
+            continue;
+        }
+        let row = POWERS[(n - 1 - i) % 255].as_ptr();
+        for (v, a) in acc.iter_mut().enumerate() {
+            *a = _mm_xor_si128(*a, gfni_mul(_mm_loadu_si128(row.add(16 * v) as *const __m128i), byte));
+        }
+    }
+    let mut syndromes: Padded = [0; 256];
+    for (v, &a) in acc.iter().enumerate() {
+        _mm_storeu_si128(syndromes.as_mut_ptr().add(16 * v) as *mut __m128i, a);
+    }
+    out.copy_from_slice(&syndromes[..out.len()]);
+}
+
+/// # Safety
+/// The CPU must support GFNI, AVX-512F and AVX-512BW; out holds 1 to 256 syndromes
+#[target_feature(enable = "gfni,avx512f,avx512bw")]
+pub unsafe fn syndromes_gfni_avx512(codeword: &[u8], out: &mut [u8]) {
+    dispatch!(syndromes_gfni_avx512_v, out.len().div_ceil(64), (codeword, out); 1 2 3 4)
+}
+
+#[target_feature(enable = "gfni,avx512f,avx512bw")]
+unsafe fn syndromes_gfni_avx512_v<const V: usize>(codeword: &[u8], out: &mut [u8]) {
+    let n = codeword.len();
+    let mut acc = [_mm512_setzero_si512(); V];
+    for (i, &byte) in codeword.iter().enumerate() {
+        if byte == 0 {
+            continue;
+        }
+        let row = POWERS[(n - 1 - i) % 255].as_ptr();
+        for (v, a) in acc.iter_mut().enumerate() {
+            *a = _mm512_xor_si512(*a, gfni_mul_512(_mm512_loadu_si512(row.add(64 * v) as *const _), byte));
+        }
+    }
+    let mut syndromes: Padded = [0; 256];
+    for (v, &a) in acc.iter().enumerate() {
+        _mm512_storeu_si512(syndromes.as_mut_ptr().add(64 * v) as *mut _, a);
+    }
+    out.copy_from_slice(&syndromes[..out.len()]);
+}
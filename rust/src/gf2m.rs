@@ -0,0 +1,180 @@
+// GF(2^m) arithmetic for 2 <= m <= 16, elements stored as u16
+// Tables are built at runtime since their size depends on m (GF(256) keeps its own compile-time tables).
+
+use core::ops::{BitXor, BitXorAssign};
+
+/// Default primitive polynomials indexed by m (bit i = coefficient of x^i)
+#[cfg(feature = "std")]
+const DEFAULT_PRIM_POLY: [u32; 17] = [
+    0, 0, 0x7, 0xB, 0x13, 0x25, 0x43, 0x89, 0x11D, 0x211, 0x409, 0x805, 0x1053, 0x201B, 0x4443, 0x8003,
+    0x1100B,
+];
+
+/// Minimal field interface shared by the GF(2^8) and GF(2^m) implementations,
+/// so decoder stages like Berlekamp-Massey can run over either
+pub trait Field {
+    type Elem: Copy + Eq + Default + BitXor<Output = Self::Elem> + BitXorAssign + 'static;
+
+    fn one(&self) -> Self::Elem;
+    fn mul(&self, a: Self::Elem, b: Self::Elem) -> Self::Elem;
+    fn inv(&self, a: Self::Elem) -> Self::Elem;
+}
+
+/// Precomputed tables for GF(2^m)
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gf2m {
+    m: u32,
+    prim_poly: u32,
+    exp: Vec<u16>, // exp[i] = alpha^i, doubled for mod-free lookup
+    log: Vec<u16>, // log[x] = i where alpha^i = x (log[0] undefined)
+}
+
+#[cfg(feature = "std")]
+impl Gf2m {
+    /// Build tables for GF(2^m) modulo prim_poly; None if m is out of range
+    /// or the polynomial is not primitive (x does not generate the whole group)
+    pub fn new(m: u32, prim_poly: u32) -> Option<Self> {
+        if !(2..=16).contains(&m) || prim_poly >> m != 1 {
+            return None;
+        }
+        let order = (1usize << m) - 1;
+        let mut exp = vec![0u16; 2 * order];
+        let mut log = vec![0u16; order + 1];
+
+        let mut x: u32 = 1;
+        for i in 0..order {
+            if i > 0 && x == 1 {
+                return None; // cycle shorter than 2^m - 1
+            }
+            exp[i] = x as u16;
+            exp[i + order] = x as u16;
+            log[x as usize] = i as u16;
+            x <<= 1;
+            if x >> m != 0 {
+                x ^= prim_poly;
+            }
+        }
+        if x != 1 {
+            return None;
+        }
+        Some(Self { m, prim_poly, exp, log })
+    }
+
+    /// GF(2^m) with the standard primitive polynomial for m
+    pub fn with_default_poly(m: u32) -> Option<Self> {
+        DEFAULT_PRIM_POLY.get(m as usize).and_then(|&p| Self::new(m, p))
+    }
+
+    pub fn m(&self) -> u32 {
+        self.m
+    }
+
+    pub fn prim_poly(&self) -> u32 {
+        self.prim_poly
+    }
+
+    /// Multiplicative group order, 2^m - 1
+    pub fn order(&self) -> usize {
+        (1usize << self.m) - 1
+    }
+
+    /// alpha^i for any i
+    #[inline]
+    pub fn exp(&self, i: usize) -> u16 {
+        self.exp[i % self.order()]
+    }
+
+    /// Discrete log of a nonzero element
+    #[inline]
+    pub fn log(&self, a: u16) -> usize {
+        if a == 0 {
+            panic!("log of zero in GF(2^{})", self.m);
+        }
+        self.log[a as usize] as usize
+    }
+
+    #[inline]
+    pub fn mul(&self, a: u16, b: u16) -> u16 {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+        }
+    }
+
+    #[inline]
+    pub fn div(&self, a: u16, b: u16) -> u16 {
+        if b == 0 {
+            panic!("division by zero in GF(2^{})", self.m);
+        }
+        if a == 0 {
+            0
+        } else {
+            let order = self.order();
+            self.exp[self.log[a as usize] as usize + order - self.log[b as usize] as usize]
+        }
+    }
+
+    #[inline]
+    pub fn inv(&self, a: u16) -> u16 {
+        self.div(1, a)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Field for Gf2m {
+    type Elem = u16;
+
+    fn one(&self) -> u16 {
+        1
+    }
+
+    fn mul(&self, a: u16, b: u16) -> u16 {
+        crate::opcount::mul(1);
+        Gf2m::mul(self, a, b)
+    }
+
+    fn inv(&self, a: u16) -> u16 {
+        crate::opcount::inv(1);
+        Gf2m::inv(self, a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_polys_are_primitive() {
+        for m in 2..=16 {
+            assert!(Gf2m::with_default_poly(m).is_some(), "m={}", m);
+        }
+    }
+
+    #[test]
+    fn test_rejects_non_primitive() {
+        // x^4 + x^3 + x^2 + x + 1 is irreducible but has order 5
+        assert!(Gf2m::new(4, 0x1F).is_none());
+        assert!(Gf2m::new(17, 0x20009).is_none());
+    }
+
+    #[test]
+    fn test_inverse_and_division() {
+        let gf = Gf2m::with_default_poly(10).unwrap();
+        for a in 1..=gf.order() as u16 {
+            assert_eq!(gf.mul(a, gf.inv(a)), 1, "a={}", a);
+            assert_eq!(gf.div(gf.mul(a, 0x155), 0x155), a);
+        }
+    }
+
+    #[test]
+    fn test_gf256_agrees_with_static_tables() {
+        let gf = Gf2m::new(8, 0x11D).unwrap();
+        for a in 0u16..256 {
+            for b in 0u16..256 {
+                assert_eq!(gf.mul(a, b), crate::gf256::gf_mul(a as u8, b as u8) as u16);
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+// Binary Golay codes: extended (24,12,8) and the perfect (23,12,7)
+// Systematic layout: bits 0..=11 of a codeword are the data bits, bits 12..=23 the parity
+// data * B, with B the symmetric 12x12 matrix of Lin & Costello (B * B = I). Decoding is the
+// arithmetic algorithm on the syndrome, so no table. The (23,12) code is the extended one
+// punctured at bit 23.
+
+const B: [u16; 12] = rows([
+    "110111000101",
+    "101110001011",
+    "011100010111",
+    "111000101101",
+    "110001011011",
+    "100010110111",
+    "000101101111",
+    "001011011101",
+    "010110111001",
+    "101101110001",
+    "011011100011",
+    "111111111110",
+]);
+
+/// Row strings to masks: character j of row i is bit j of B[i]
+const fn rows(text: [&str; 12]) -> [u16; 12] {
+    let mut out = [0u16; 12];
+    let mut i = 0;
+    while i < 12 {
+        let row = text[i].as_bytes();
+        let mut j = 0;
+        while j < 12 {
+            if row[j] == b'1' {
+                out[i] |= 1 << j;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    out
+}
+
+const DATA_MASK: u32 = 0xFFF;
+const MASK23: u32 = 0x7F_FFFF;
+
+/// Outcome of a Golay decode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GolayResult {
+    NoError,
+    /// 1..=3 bit errors corrected; the mask of the flipped codeword bits
+    Corrected(u32),
+    /// A nonzero syndrome more than 3 bits from every codeword: every 4-bit error, and many heavier
+    /// ones (the rest alias onto a codeword or a correctable pattern)
+    Detected,
+}
+
+impl GolayResult {
+    /// Data bits after applying the correction, None if the error was only detected
+    pub fn corrected_data(self, codeword: u32) -> Option<u16> {
+        match self {
+            GolayResult::NoError => Some((codeword & DATA_MASK) as u16),
+            GolayResult::Corrected(mask) => Some(((codeword ^ mask) & DATA_MASK) as u16),
+            GolayResult::Detected => None,
+        }
+    }
+}
+
+/// v * B over GF(2)
+fn times_b(v: u16) -> u16 {
+    let mut out = 0;
+    let mut bits = v;
+    while bits != 0 {
+        out ^= B[bits.trailing_zeros() as usize];
+        bits &= bits - 1;
+    }
+    out
+}
+
+/// Extended Golay codeword of the low 12 bits of data; panics on higher bits set
+pub fn encode(data: u16) -> u32 {
+    assert!(data < 1 << 12, "Golay data is 12 bits");
+    data as u32 | (times_b(data) as u32) << 12
+}
+
+/// Decode a 24-bit extended Golay word, correcting up to 3 bit errors and detecting 4
+pub fn decode(received: u32) -> GolayResult {
+    assert!(received < 1 << 24, "extended Golay codewords are 24 bits");
+    let (data, parity) = ((received & DATA_MASK) as u16, (received >> 12) as u16);
+    let syndrome = times_b(data) ^ parity;
+    if syndrome == 0 {
+        return GolayResult::NoError;
+    }
+    let error = |data_err: u16, parity_err: u16| GolayResult::Corrected(data_err as u32 | (parity_err as u32) << 12);
+    // Errors only in the parity half, or one data bit plus at most two parity bits
+    if syndrome.count_ones() <= 3 {
+        return error(0, syndrome);
+    }
+    if let Some(i) = (0..12).find(|&i| (syndrome ^ B[i]).count_ones() <= 2) {
+        return error(1 << i, syndrome ^ B[i]);
+    }
+    // The same with the halves swapped: syndrome * B is the data half's error when B * B = I
+    let swapped = times_b(syndrome);
+    if swapped.count_ones() <= 3 {
+        return error(swapped, 0);
+    }
+    if let Some(i) = (0..12).find(|&i| (swapped ^ B[i]).count_ones() <= 2) {
+        return error(swapped ^ B[i], 1 << i);
+    }
+    GolayResult::Detected
+}
+
+/// Perfect (23,12) Golay codeword: the extended codeword without bit 23
+pub fn encode23(data: u16) -> u32 {
+    encode(data) & MASK23
+}
+
+/// Decode a 23-bit Golay word; the code is perfect, so every word is within 3 bits of exactly
+/// one codeword and the result is never Detected
+pub fn decode23(received: u32) -> GolayResult {
+    assert!(received < 1 << 23, "Golay (23,12) codewords are 23 bits");
+    // One guess of the punctured bit adds no error, so it decodes; the other adds one, so it
+    // either decodes to the same codeword or is detected
+    let result = match decode(received) {
+        GolayResult::Detected => decode(received | 1 << 23),
+        result => result,
+    };
+    match result {
+        GolayResult::Corrected(mask) if mask & MASK23 == 0 => GolayResult::NoError,
+        GolayResult::Corrected(mask) => GolayResult::Corrected(mask & MASK23),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    /// Every mask of weight 1..=max over n bits
+    fn patterns(n: u32, max: u32) -> Vec<u32> {
+        (1u32..1 << n).filter(|m| m.count_ones() <= max).collect()
+    }
+
+    #[test]
+    fn test_code_structure() {
+        for (i, row) in B.iter().enumerate() {
+            assert_eq!(times_b(*row), 1 << i, "B * B = I");
+            assert!((0..12).all(|j| (row >> j & 1) == (B[j] >> i & 1)), "B is symmetric");
+        }
+        // Weight enumerator of the extended Golay code: 1 + 759 x^8 + 2576 x^12 + 759 x^16 + x^24
+        let mut weights = [0usize; 25];
+        for data in 0..1 << 12 {
+            weights[encode(data).count_ones() as usize] += 1;
+        }
+        let mut expected = [0usize; 25];
+        (expected[0], expected[8], expected[12], expected[16], expected[24]) = (1, 759, 2576, 759, 1);
+        assert_eq!(weights, expected);
+    }
+
+    #[test]
+    fn test_corrects_every_pattern_up_to_three_bits() {
+        let errors = patterns(24, 3);
+        assert_eq!(errors.len(), 24 + 276 + 2024);
+        let mut rng = Rng::new(390);
+        for _ in 0..40 {
+            let data = rng.below(1 << 12) as u16;
+            let codeword = encode(data);
+            assert_eq!(decode(codeword), GolayResult::NoError);
+            for &e in &errors {
+                let result = decode(codeword ^ e);
+                assert_eq!(result, GolayResult::Corrected(e), "error {:06x}", e);
+                assert_eq!(result.corrected_data(codeword ^ e), Some(data));
+            }
+        }
+    }
+
+    #[test]
+    fn test_detects_every_four_bit_error() {
+        let mut rng = Rng::new(0x601a);
+        for _ in 0..8 {
+            let codeword = encode(rng.below(1 << 12) as u16);
+            for e in (1u32..1 << 24).filter(|m| m.count_ones() == 4) {
+                assert_eq!(decode(codeword ^ e), GolayResult::Detected, "error {:06x}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_perfect_23_12() {
+        let errors = patterns(23, 3);
+        let mut rng = Rng::new(23);
+        for _ in 0..40 {
+            let data = rng.below(1 << 12) as u16;
+            let codeword = encode23(data);
+            assert_eq!(decode23(codeword), GolayResult::NoError);
+            for &e in &errors {
+                let result = decode23(codeword ^ e);
+                assert_eq!(result, GolayResult::Corrected(e), "error {:06x}", e);
+                assert_eq!(result.corrected_data(codeword ^ e), Some(data));
+            }
+        }
+        // Perfect: any word at all lands within 3 bits of a codeword
+        for _ in 0..20_000 {
+            let word = rng.below(1 << 23) as u32;
+            match decode23(word) {
+                GolayResult::NoError => assert_eq!(encode23((word & DATA_MASK) as u16), word),
+                GolayResult::Corrected(mask) => {
+                    assert!(mask.count_ones() <= 3);
+                    assert_eq!(encode23(((word ^ mask) & DATA_MASK) as u16), word ^ mask);
+                }
+                GolayResult::Detected => panic!("{:06x} not decoded", word),
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+// Symbol-to-device bit mapping for the memory ECC model
+// A rank stores each line as beats x devices x device-width bits, but which RS codeword, symbol
+// and symbol bit each of those bits lands in is up to the memory controller, and it decides what
+// a device failure looks like to the code: one symbol per codeword, or several. A SymbolMap is
+// that assignment; memory::Scheme::Mapped stores a line through one, so Fault::Device and the
+// other geometric faults are translated through it before the RS decoder sees them.
+// Stored layout: codeword c's symbol s is byte c * n + s, its bit b is bit b of that byte.
+
+/// Bits per RS symbol
+pub const SYMBOL_BITS: usize = 8;
+
+/// Where one stored bit lands in the RS codewords of a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub codeword: usize,
+    pub symbol: usize,
+    pub bit: usize,
+}
+
+/// Assignment of every (device, beat, bit) coordinate to a distinct (codeword, symbol, bit)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolMap {
+    name: &'static str,
+    devices: usize,
+    beats: usize,
+    width: usize,
+    n: usize,
+    /// Indexed by (device * beats + beat) * width + bit
+    table: Vec<Location>,
+}
+
+impl SymbolMap {
+    /// One device = one symbol: device d's bits, beat-major, fill symbol d of successive codewords
+    /// A device failure is one symbol error per codeword. n is the device count; each device
+    /// must supply whole symbols (beats * width a multiple of 8).
+    pub fn device_per_symbol(devices: usize, beats: usize, width: usize) -> Result<Self, &'static str> {
+        Self::build("device_per_symbol", devices, beats, width, devices, |device, j| Location {
+            codeword: j / SYMBOL_BITS,
+            symbol: device,
+            bit: j % SYMBOL_BITS,
+        })
+    }
+
+    /// Devices paired up, each device's bits alternating between the pair's two symbols
+    /// Symbol 2p + r of a codeword holds the bits of parity r from devices 2p (symbol bits 0..4)
+    /// and 2p + 1 (bits 4..8), so a device failure is two symbol errors per codeword. Needs an
+    /// even device count and whole symbols per device, as device_per_symbol.
+    pub fn bit_interleaved(devices: usize, beats: usize, width: usize) -> Result<Self, &'static str> {
+        if !devices.is_multiple_of(2) {
+            return Err("bit-interleaved mapping pairs devices, so needs an even count");
+        }
+        Self::build("bit_interleaved", devices, beats, width, devices, |device, j| Location {
+            codeword: j / SYMBOL_BITS,
+            symbol: (device & !1) + j % 2,
+            bit: (device % 2) * 4 + j % SYMBOL_BITS / 2,
+        })
+    }
+
+    /// User-supplied map of n-symbol codewords; table[(device * beats + beat) * width + bit] is
+    /// where that bit lands. Must hit every bit of a whole number of codewords exactly once.
+    pub fn from_table(devices: usize, beats: usize, width: usize, n: usize, table: Vec<Location>) -> Result<Self, &'static str> {
+        let bits = devices * beats * width;
+        if bits == 0 || n == 0 {
+            return Err("symbol map needs devices, beats, width and n all nonzero");
+        }
+        if table.len() != bits {
+            return Err("symbol map table needs one entry per device bit");
+        }
+        if !bits.is_multiple_of(n * SYMBOL_BITS) {
+            return Err("device bits must fill a whole number of codewords");
+        }
+        let codewords = bits / (n * SYMBOL_BITS);
+        let mut seen = vec![false; bits];
+        for loc in &table {
+            if loc.codeword >= codewords || loc.symbol >= n || loc.bit >= SYMBOL_BITS {
+                return Err("symbol map location out of range");
+            }
+            let index = (loc.codeword * n + loc.symbol) * SYMBOL_BITS + loc.bit;
+            if seen[index] {
+                return Err("symbol map sends two device bits to the same codeword bit");
+            }
+            seen[index] = true;
+        }
+        Ok(Self { name: "table", devices, beats, width, n, table })
+    }
+
+    /// Table from `place(device, j)`, j = beat * width + bit indexing the device's own bits
+    fn build(name: &'static str, devices: usize, beats: usize, width: usize, n: usize, place: impl Fn(usize, usize) -> Location) -> Result<Self, &'static str> {
+        if !(beats * width).is_multiple_of(SYMBOL_BITS) {
+            return Err("each device must supply whole 8-bit symbols (beats * width a multiple of 8)");
+        }
+        let table = (0..devices).flat_map(|device| (0..beats * width).map(move |j| (device, j))).map(|(device, j)| place(device, j)).collect();
+        Ok(Self { name, ..Self::from_table(devices, beats, width, n, table)? })
+    }
+
+    /// "device_per_symbol", "bit_interleaved" or "table"
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn devices(&self) -> usize {
+        self.devices
+    }
+
+    pub fn beats(&self) -> usize {
+        self.beats
+    }
+
+    /// Bits per device per beat
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Symbols per codeword
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Codewords per line
+    pub fn codewords(&self) -> usize {
+        self.table.len() / (self.n * SYMBOL_BITS)
+    }
+
+    /// Where bit `bit` of `device` in `beat` lands; panics out of range
+    pub fn locate(&self, device: usize, beat: usize, bit: usize) -> Location {
+        assert!(device < self.devices && beat < self.beats && bit < self.width, "device coordinate out of range");
+        self.table[(device * self.beats + beat) * self.width + bit]
+    }
+
+    /// (codeword, symbol) pairs a failure of `device` can touch, sorted
+    pub fn device_symbols(&self, device: usize) -> Vec<(usize, usize)> {
+        let mut symbols: Vec<(usize, usize)> = (0..self.beats)
+            .flat_map(|beat| (0..self.width).map(move |bit| (beat, bit)))
+            .map(|(beat, bit)| self.locate(device, beat, bit))
+            .map(|loc| (loc.codeword, loc.symbol))
+            .collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{Access, Fault, MemoryModel, Scheme, LINE_BYTES};
+    use crate::rs::sim::Rng;
+    use crate::rs::RsCodec;
+
+    // 18 x4 devices over 8 beats: 576 bits, four RS(18,16) codewords of one line
+    const DEVICES: usize = 18;
+    const BEATS: usize = 8;
+    const WIDTH: usize = 4;
+
+    fn model(map: SymbolMap) -> MemoryModel {
+        MemoryModel::new(Scheme::mapped(RsCodec::new(2, 18).unwrap(), map).unwrap(), 397)
+    }
+
+    #[test]
+    fn test_builtin_maps() {
+        let plain = SymbolMap::device_per_symbol(DEVICES, BEATS, WIDTH).unwrap();
+        assert_eq!((plain.n(), plain.codewords()), (18, 4));
+        assert_eq!(plain.locate(5, 3, 2), Location { codeword: 1, symbol: 5, bit: 6 });
+        assert_eq!(plain.device_symbols(5), vec![(0, 5), (1, 5), (2, 5), (3, 5)]);
+
+        let paired = SymbolMap::bit_interleaved(DEVICES, BEATS, WIDTH).unwrap();
+        assert_eq!(paired.locate(5, 3, 2), Location { codeword: 1, symbol: 4, bit: 7 });
+        assert_eq!(paired.device_symbols(5).len(), 8, "two symbols in each of four codewords");
+
+        assert!(SymbolMap::bit_interleaved(17, BEATS, WIDTH).is_err());
+        assert!(SymbolMap::device_per_symbol(DEVICES, 3, WIDTH).is_err());
+        let mut table = plain.table.clone();
+        table[1] = table[0];
+        assert!(SymbolMap::from_table(DEVICES, BEATS, WIDTH, 18, table).is_err());
+        assert!(SymbolMap::from_table(DEVICES, BEATS, WIDTH, 17, plain.table.clone()).is_err());
+    }
+
+    #[test]
+    fn test_same_device_failure_under_two_maps() {
+        // A dead x4 device is one symbol error per RS(18,16) codeword when it owns its symbols,
+        // two when its bits are interleaved with a neighbour's: past t = 1 in every codeword
+        let mut rng = Rng::new(397);
+        let mut line = [0u8; LINE_BYTES];
+        line.iter_mut().for_each(|b| *b = rng.next_u64() as u8);
+        for device in [0, 7, 17] {
+            for pattern in [u32::MAX as u64, 0x9c3a_5e61] {
+                let fault = Fault::Device { device, pattern };
+                let mut good = model(SymbolMap::device_per_symbol(DEVICES, BEATS, WIDTH).unwrap());
+                good.write(0, &line);
+                good.inject(0, fault).unwrap();
+                assert_eq!(good.read(0), (line, Access::Ce), "device {} pattern {:x}", device, pattern);
+
+                let mut bad = model(SymbolMap::bit_interleaved(DEVICES, BEATS, WIDTH).unwrap());
+                bad.write(0, &line);
+                bad.inject(0, fault).unwrap();
+                let access = bad.read(0).1;
+                assert!(matches!(access, Access::Due | Access::Sdc), "device {} pattern {:x}: {:?}", device, pattern, access);
+            }
+        }
+    }
+
+    #[test]
+    fn test_user_table() {
+        // Rotate each codeword's symbols by its index: still one symbol per device per codeword
+        let table = (0..DEVICES)
+            .flat_map(|device| (0..BEATS * WIDTH).map(move |j| (device, j)))
+            .map(|(device, j)| Location { codeword: j / 8, symbol: (device + j / 8) % DEVICES, bit: j % 8 })
+            .collect();
+        let map = SymbolMap::from_table(DEVICES, BEATS, WIDTH, 18, table).unwrap();
+        assert_eq!((map.name(), map.device_symbols(17)), ("table", vec![(0, 17), (1, 0), (2, 1), (3, 2)]));
+        let mut memory = model(map);
+        assert_eq!(memory.scheme().name(), "rs(18,16)/table");
+        let counters = memory.sweep(2000, &crate::memory::FaultMix { device: 1.0, ..Default::default() }, 1).unwrap();
+        assert_eq!((counters.ce, counters.due, counters.sdc), (2000, 0, 0));
+    }
+}
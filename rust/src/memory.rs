@@ -0,0 +1,653 @@
+// Cache-line memory model: 64-byte lines under SEC-DED, chipkill or RS protection
+// Each stored line is a grid of beats x bus lanes, so faults are placed geometrically: a single
+// bit, a whole device (its lanes in every beat) or a row burst (a run of lanes in one beat).
+// Reads are classified against the data last written, which the decoder itself cannot see:
+// clean, CE (corrected), DUE (detected uncorrectable) or SDC (silent data corruption).
+
+use std::collections::HashMap;
+use std::ops::Add;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::chipkill::{BeatStatus, Chipkill, DATA_DEVICES, DEVICES};
+use crate::mapping::SymbolMap;
+use crate::rs::sim::{trial_rng, Rng};
+use crate::rs::RsCodec;
+use crate::secded::{HMatrix, SecdedResult};
+
+pub const LINE_BYTES: usize = 64;
+
+pub type Line = [u8; LINE_BYTES];
+
+/// Protection scheme for one cache line
+/// Secded72: 8 beats of a 72-bit bus, nine x8 devices (device 8 holds the check byte).
+/// ChipkillX4: 4 beats of a 144-bit bus, 36 x4 devices, one RS(36,32) GF(2^16) codeword.
+/// Rs: 64 / k beats of an n-byte bus, one RS(n, k) codeword per beat, device i holds symbol i.
+/// Mapped: 64 / k RS(n, k) codewords laid over the devices by a SymbolMap.
+pub enum Scheme {
+    Secded72(HMatrix),
+    ChipkillX4(Chipkill),
+    Rs(RsCodec),
+    Mapped { codec: RsCodec, map: SymbolMap },
+}
+
+impl Scheme {
+    /// "secded72", "chipkill_x4", or "rs(n,k)" with k dividing 64
+    pub fn parse(spec: &str) -> Result<Self, &'static str> {
+        let spec = spec.trim().to_ascii_lowercase();
+        match spec.as_str() {
+            "secded72" => return Ok(Scheme::Secded72(HMatrix::hsiao())),
+            "chipkill_x4" => return Ok(Scheme::ChipkillX4(Chipkill::default())),
+            _ => {}
+        }
+        let args = spec
+            .strip_prefix("rs(")
+            .and_then(|s| s.strip_suffix(')'))
+            .ok_or("scheme must be \"secded72\", \"chipkill_x4\" or \"rs(n,k)\"")?;
+        let (n, k) = args.split_once(',').ok_or("RS scheme must be written rs(n,k)")?;
+        let n: usize = n.trim().parse().map_err(|_| "RS scheme n must be an integer")?;
+        let k: usize = k.trim().parse().map_err(|_| "RS scheme k must be an integer")?;
+        if k == 0 || k > LINE_BYTES || !LINE_BYTES.is_multiple_of(k) {
+            return Err("RS scheme k must divide the 64-byte line");
+        }
+        if n <= k {
+            return Err("RS scheme needs n > k");
+        }
+        RsCodec::new(n - k, n).map(Scheme::Rs).map_err(|_| "RS scheme needs n <= 255")
+    }
+
+    /// RS codewords stored through `map`; the map's codewords must hold exactly one line
+    pub fn mapped(codec: RsCodec, map: SymbolMap) -> Result<Self, &'static str> {
+        if map.n() != codec.n() {
+            return Err("symbol map codewords must be the codec's n symbols");
+        }
+        if map.codewords() * codec.k() != LINE_BYTES {
+            return Err("symbol map codewords must carry exactly one 64-byte line");
+        }
+        Ok(Scheme::Mapped { codec, map })
+    }
+
+    /// Beats per line
+    pub fn beats(&self) -> usize {
+        match self {
+            Scheme::Secded72(_) => 8,
+            Scheme::ChipkillX4(_) => 4,
+            Scheme::Rs(codec) => LINE_BYTES / codec.k(),
+            Scheme::Mapped { map, .. } => map.beats(),
+        }
+    }
+
+    /// Lanes per device per beat
+    pub fn device_width(&self) -> usize {
+        match self {
+            Scheme::ChipkillX4(_) => 4,
+            Scheme::Secded72(_) | Scheme::Rs(_) => 8,
+            Scheme::Mapped { map, .. } => map.width(),
+        }
+    }
+
+    pub fn devices(&self) -> usize {
+        match self {
+            Scheme::Secded72(_) => 9,
+            Scheme::ChipkillX4(_) => DEVICES,
+            Scheme::Rs(codec) => codec.n(),
+            Scheme::Mapped { map, .. } => map.devices(),
+        }
+    }
+
+    /// Bus width in lanes
+    pub fn bus_bits(&self) -> usize {
+        self.devices() * self.device_width()
+    }
+
+    /// Stored bits per line, beats() * bus_bits()
+    pub fn line_bits(&self) -> usize {
+        self.beats() * self.bus_bits()
+    }
+
+    /// (byte, bit) of the stored line holding `lane` of `beat`
+    fn locate(&self, beat: usize, lane: usize) -> (usize, usize) {
+        match self {
+            Scheme::Secded72(_) => (9 * beat + lane / 8, lane % 8),
+            // Device d's 16-bit symbol holds its 4 lanes of beat b at bits 4b..4b+4
+            Scheme::ChipkillX4(_) => {
+                let bit = 4 * beat + lane % 4;
+                (2 * (lane / 4) + bit / 8, bit % 8)
+            }
+            Scheme::Rs(codec) => (beat * codec.n() + lane / 8, lane % 8),
+            Scheme::Mapped { codec, map } => {
+                let loc = map.locate(lane / map.width(), beat, lane % map.width());
+                (loc.codeword * codec.n() + loc.symbol, loc.bit)
+            }
+        }
+    }
+
+    fn flip(&self, stored: &mut [u8], beat: usize, lane: usize) {
+        let (byte, bit) = self.locate(beat, lane);
+        stored[byte] ^= 1 << bit;
+    }
+
+    pub fn encode(&self, line: &Line) -> Vec<u8> {
+        match self {
+            Scheme::Secded72(h) => line
+                .chunks_exact(8)
+                .flat_map(|word| {
+                    let check = h.check_bits(u64::from_le_bytes(word.try_into().unwrap()));
+                    word.iter().copied().chain([check])
+                })
+                .collect(),
+            Scheme::ChipkillX4(chipkill) => {
+                let mut data = [0u16; DATA_DEVICES];
+                for (symbol, pair) in data.iter_mut().zip(line.chunks_exact(2)) {
+                    *symbol = u16::from_le_bytes([pair[0], pair[1]]);
+                }
+                chipkill.encode_beat(&data).iter().flat_map(|w| w.to_le_bytes()).collect()
+            }
+            Scheme::Rs(codec) | Scheme::Mapped { codec, .. } => line.chunks_exact(codec.k()).flat_map(|chunk| codec.encode(chunk)).collect(),
+        }
+    }
+
+    /// Decoded data and decoder verdict; DUE words come back uncorrected
+    pub fn decode(&self, stored: &[u8]) -> (Line, Verdict) {
+        let mut line = [0u8; LINE_BYTES];
+        let mut verdict = Verdict::Clean;
+        match self {
+            Scheme::Secded72(h) => {
+                for (out, word) in line.chunks_exact_mut(8).zip(stored.chunks_exact(9)) {
+                    let data = u64::from_le_bytes(word[..8].try_into().unwrap());
+                    let result = h.decode(data, word[8]);
+                    verdict = verdict.max(match result {
+                        SecdedResult::NoError => Verdict::Clean,
+                        SecdedResult::CorrectedBit(_) => Verdict::Corrected,
+                        SecdedResult::DetectedDouble | SecdedResult::DetectedUncorrectable => Verdict::Detected,
+                    });
+                    out.copy_from_slice(&result.corrected_data(data).unwrap_or(data).to_le_bytes());
+                }
+            }
+            Scheme::ChipkillX4(chipkill) => {
+                let mut beat = [0u16; DEVICES];
+                for (word, pair) in beat.iter_mut().zip(stored.chunks_exact(2)) {
+                    *word = u16::from_le_bytes([pair[0], pair[1]]);
+                }
+                let decoded = chipkill.decode_beat(&beat);
+                verdict = match decoded.status {
+                    BeatStatus::Clean => Verdict::Clean,
+                    BeatStatus::Ce { .. } => Verdict::Corrected,
+                    BeatStatus::Due => Verdict::Detected,
+                };
+                // Data symbols sit at code positions 0..32 (identity map), i.e. devices 0..32
+                let data = decoded.data.unwrap_or_else(|| beat[..DATA_DEVICES].try_into().unwrap());
+                for (pair, symbol) in line.chunks_exact_mut(2).zip(data) {
+                    pair.copy_from_slice(&symbol.to_le_bytes());
+                }
+            }
+            Scheme::Rs(codec) | Scheme::Mapped { codec, .. } => {
+                let k = codec.k();
+                for (out, codeword) in line.chunks_exact_mut(k).zip(stored.chunks_exact(codec.n())) {
+                    match codec.decode(codeword) {
+                        Ok((message, positions)) => {
+                            out.copy_from_slice(&message);
+                            if !positions.is_empty() {
+                                verdict = verdict.max(Verdict::Corrected);
+                            }
+                        }
+                        Err(_) => {
+                            out.copy_from_slice(&codeword[..k]);
+                            verdict = Verdict::Detected;
+                        }
+                    }
+                }
+            }
+        }
+        (line, verdict)
+    }
+
+    /// Classify a read of `stored` against the data that was written
+    pub fn classify(&self, stored: &[u8], written: &Line) -> (Line, Access) {
+        let (line, verdict) = self.decode(stored);
+        let access = match verdict {
+            Verdict::Detected => Access::Due,
+            _ if line != *written => Access::Sdc,
+            Verdict::Corrected => Access::Ce,
+            Verdict::Clean => Access::Clean,
+        };
+        (line, access)
+    }
+
+    pub(crate) fn apply(&self, stored: &mut [u8], fault: &Fault) -> Result<(), FaultError> {
+        match *fault {
+            Fault::Bit(bit) => {
+                if bit >= self.line_bits() {
+                    return Err(FaultError::BitOutOfRange { bit, line_bits: self.line_bits() });
+                }
+                self.flip(stored, bit / self.bus_bits(), bit % self.bus_bits());
+            }
+            Fault::Device { device, pattern } => {
+                if device >= self.devices() {
+                    return Err(FaultError::DeviceOutOfRange { device, devices: self.devices() });
+                }
+                let width = self.device_width();
+                if self.beats() * width < 64 && pattern >> (self.beats() * width) != 0 {
+                    return Err(FaultError::PatternTooWide { bits: self.beats() * width });
+                }
+                for j in (0..self.beats() * width).filter(|&j| pattern >> j & 1 == 1) {
+                    self.flip(stored, j / width, device * width + j % width);
+                }
+            }
+            Fault::RowBurst { beat, start, len } => {
+                if beat >= self.beats() {
+                    return Err(FaultError::BeatOutOfRange { beat, beats: self.beats() });
+                }
+                if start + len > self.bus_bits() {
+                    return Err(FaultError::BurstOutOfRange { start, len, bus_bits: self.bus_bits() });
+                }
+                for lane in start..start + len {
+                    self.flip(stored, beat, lane);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A random fault of `kind`; device patterns are uniform and nonzero
+    fn random_fault(&self, kind: FaultKind, rng: &mut Rng) -> Vec<Fault> {
+        match kind {
+            FaultKind::Bit => vec![Fault::Bit(rng.below(self.line_bits()))],
+            FaultKind::DoubleBit => rng.distinct(2, self.line_bits()).into_iter().map(Fault::Bit).collect(),
+            FaultKind::Device => {
+                let bits = self.beats() * self.device_width();
+                let mask = if bits >= 64 { u64::MAX } else { (1u64 << bits) - 1 };
+                let pattern = loop {
+                    let p = rng.next_u64() & mask;
+                    if p != 0 {
+                        break p;
+                    }
+                };
+                vec![Fault::Device { device: rng.below(self.devices()), pattern }]
+            }
+            FaultKind::Row => vec![Fault::RowBurst { beat: rng.below(self.beats()), start: 0, len: self.bus_bits() }],
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Scheme::Secded72(_) => "secded72".into(),
+            Scheme::ChipkillX4(_) => "chipkill_x4".into(),
+            Scheme::Rs(codec) => format!("rs({},{})", codec.n(), codec.k()),
+            Scheme::Mapped { codec, map } => format!("rs({},{})/{}", codec.n(), codec.k(), map.name()),
+        }
+    }
+}
+
+/// What the decoder reports, before comparing with the written data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verdict {
+    Clean,
+    Corrected,
+    Detected,
+}
+
+/// Per-access classification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Clean,
+    /// Corrected back to the written data
+    Ce,
+    /// Detected uncorrectable
+    Due,
+    /// Decoder returned wrong data without flagging it
+    Sdc,
+}
+
+impl Access {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Access::Clean => "clean",
+            Access::Ce => "ce",
+            Access::Due => "due",
+            Access::Sdc => "sdc",
+        }
+    }
+}
+
+/// One fault placed in a stored line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Flip stored bit beat * bus_bits + lane
+    Bit(usize),
+    /// XOR `pattern` into the device's bits; bit j is lane j % width of beat j / width
+    Device { device: usize, pattern: u64 },
+    /// Flip lanes start..start + len of one beat
+    RowBurst { beat: usize, start: usize, len: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultError {
+    BitOutOfRange { bit: usize, line_bits: usize },
+    DeviceOutOfRange { device: usize, devices: usize },
+    PatternTooWide { bits: usize },
+    BeatOutOfRange { beat: usize, beats: usize },
+    BurstOutOfRange { start: usize, len: usize, bus_bits: usize },
+}
+
+impl std::fmt::Display for FaultError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaultError::BitOutOfRange { bit, line_bits } => write!(f, "bit {} out of range for {} stored bits", bit, line_bits),
+            FaultError::DeviceOutOfRange { device, devices } => write!(f, "device {} out of range for {} devices", device, devices),
+            FaultError::PatternTooWide { bits } => write!(f, "device pattern must fit in {} bits", bits),
+            FaultError::BeatOutOfRange { beat, beats } => write!(f, "beat {} out of range for {} beats", beat, beats),
+            FaultError::BurstOutOfRange { start, len, bus_bits } => {
+                write!(f, "burst of {} lanes from lane {} overruns the {}-lane bus", len, start, bus_bits)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FaultError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultKind {
+    Bit,
+    DoubleBit,
+    Device,
+    Row,
+}
+
+/// Per-access fault probabilities for sweep(); the remainder is fault-free
+/// double_bit flips two distinct random bits, device XORs a random nonzero pattern into one
+/// device and row flips every lane of one random beat.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FaultMix {
+    pub bit: f64,
+    pub double_bit: f64,
+    pub device: f64,
+    pub row: f64,
+}
+
+impl FaultMix {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let probs = [self.bit, self.double_bit, self.device, self.row];
+        if probs.iter().any(|p| !(0.0..=1.0).contains(p)) {
+            return Err("fault probabilities must be in 0..=1");
+        }
+        if probs.iter().sum::<f64>() > 1.0 + 1e-12 {
+            return Err("fault probabilities must sum to at most 1");
+        }
+        Ok(())
+    }
+
+    fn draw(&self, rng: &mut Rng) -> Option<FaultKind> {
+        let mut u = rng.next_f64();
+        for (p, kind) in [
+            (self.bit, FaultKind::Bit),
+            (self.double_bit, FaultKind::DoubleBit),
+            (self.device, FaultKind::Device),
+            (self.row, FaultKind::Row),
+        ] {
+            if u < p {
+                return Some(kind);
+            }
+            u -= p;
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryCounters {
+    pub writes: u64,
+    pub reads: u64,
+    pub faults: u64,
+    pub clean: u64,
+    pub ce: u64,
+    pub due: u64,
+    pub sdc: u64,
+}
+
+impl MemoryCounters {
+    fn record(&mut self, access: Access) {
+        self.reads += 1;
+        match access {
+            Access::Clean => self.clean += 1,
+            Access::Ce => self.ce += 1,
+            Access::Due => self.due += 1,
+            Access::Sdc => self.sdc += 1,
+        }
+    }
+}
+
+impl Add for MemoryCounters {
+    type Output = MemoryCounters;
+
+    fn add(self, other: MemoryCounters) -> MemoryCounters {
+        MemoryCounters {
+            writes: self.writes + other.writes,
+            reads: self.reads + other.reads,
+            faults: self.faults + other.faults,
+            clean: self.clean + other.clean,
+            ce: self.ce + other.ce,
+            due: self.due + other.due,
+            sdc: self.sdc + other.sdc,
+        }
+    }
+}
+
+struct StoredLine {
+    written: Line,
+    stored: Vec<u8>,
+}
+
+/// Sparse byte-addressed memory of protected 64-byte lines
+/// Addresses name any byte of a line; lines never written read back as zeros.
+pub struct MemoryModel {
+    scheme: Scheme,
+    lines: HashMap<u64, StoredLine>,
+    counters: MemoryCounters,
+    rng: Rng,
+}
+
+impl MemoryModel {
+    /// `seed` drives the random device patterns of inject(); sweeps take their own seed
+    pub fn new(scheme: Scheme, seed: u64) -> Self {
+        Self { scheme, lines: HashMap::new(), counters: MemoryCounters::default(), rng: Rng::new(seed) }
+    }
+
+    pub fn scheme(&self) -> &Scheme {
+        &self.scheme
+    }
+
+    pub fn counters(&self) -> MemoryCounters {
+        self.counters
+    }
+
+    pub fn reset_counters(&mut self) {
+        self.counters = MemoryCounters::default();
+    }
+
+    fn line_mut(&mut self, address: u64) -> &mut StoredLine {
+        let scheme = &self.scheme;
+        self.lines.entry(address / LINE_BYTES as u64).or_insert_with(|| {
+            let written = [0u8; LINE_BYTES];
+            StoredLine { stored: scheme.encode(&written), written }
+        })
+    }
+
+    pub fn write(&mut self, address: u64, data: &Line) {
+        let stored = self.scheme.encode(data);
+        self.lines.insert(address / LINE_BYTES as u64, StoredLine { written: *data, stored });
+        self.counters.writes += 1;
+    }
+
+    /// Decoded line and its classification; the stored bits are left as they are
+    pub fn read(&mut self, address: u64) -> (Line, Access) {
+        let (line, access) = match self.lines.get(&(address / LINE_BYTES as u64)) {
+            Some(line) => self.scheme.classify(&line.stored, &line.written),
+            None => ([0u8; LINE_BYTES], Access::Clean),
+        };
+        self.counters.record(access);
+        (line, access)
+    }
+
+    pub fn inject(&mut self, address: u64, fault: Fault) -> Result<(), FaultError> {
+        // Validate against a scratch line first so a bad fault leaves memory untouched
+        let mut scratch = vec![0u8; self.scheme.encode(&[0; LINE_BYTES]).len()];
+        self.scheme.apply(&mut scratch, &fault)?;
+        let line = self.line_mut(address);
+        for (s, x) in line.stored.iter_mut().zip(scratch) {
+            *s ^= x;
+        }
+        self.counters.faults += 1;
+        Ok(())
+    }
+
+    /// Whole-device fault with a random nonzero pattern
+    pub fn inject_random_device(&mut self, address: u64, device: usize) -> Result<(), FaultError> {
+        let bits = self.scheme.beats() * self.scheme.device_width();
+        let pattern = loop {
+            let p = if bits >= 64 { self.rng.next_u64() } else { self.rng.next_u64() & ((1u64 << bits) - 1) };
+            if p != 0 {
+                break p;
+            }
+        };
+        self.inject(address, Fault::Device { device, pattern })
+    }
+
+    /// `accesses` independent write / maybe-fault / read cycles on a scratch line
+    /// Access i draws its data and fault from trial_rng(seed, i), so the counters depend only on
+    /// the seed, not on thread scheduling. A fault-free access is counted clean without decoding,
+    /// since every scheme reads a valid codeword back unchanged. Adds to and returns the counters.
+    pub fn sweep(&mut self, accesses: u64, mix: &FaultMix, seed: u64) -> Result<MemoryCounters, &'static str> {
+        mix.validate()?;
+        let scheme = &self.scheme;
+        let run_access = |i: u64| -> MemoryCounters {
+            let mut rng = trial_rng(seed, i);
+            let mut counters = MemoryCounters { writes: 1, ..MemoryCounters::default() };
+            let Some(kind) = mix.draw(&mut rng) else {
+                counters.record(Access::Clean);
+                return counters;
+            };
+            let mut written = [0u8; LINE_BYTES];
+            for chunk in written.chunks_exact_mut(8) {
+                chunk.copy_from_slice(&rng.next_u64().to_le_bytes());
+            }
+            let mut stored = scheme.encode(&written);
+            for fault in scheme.random_fault(kind, &mut rng) {
+                scheme.apply(&mut stored, &fault).expect("random faults are in range");
+            }
+            counters.faults = 1;
+            counters.record(scheme.classify(&stored, &written).1);
+            counters
+        };
+
+        #[cfg(feature = "parallel")]
+        let swept = (0..accesses).into_par_iter().map(run_access).reduce(MemoryCounters::default, Add::add);
+        #[cfg(not(feature = "parallel"))]
+        let swept = (0..accesses).map(run_access).fold(MemoryCounters::default(), Add::add);
+        self.counters = self.counters + swept;
+        Ok(swept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_line(seed: u64) -> Line {
+        let mut rng = Rng::new(seed);
+        let mut line = [0u8; LINE_BYTES];
+        line.iter_mut().for_each(|b| *b = rng.next_u64() as u8);
+        line
+    }
+
+    #[test]
+    fn test_geometry_and_round_trip() {
+        for (spec, beats, bus_bits, devices) in
+            [("secded72", 8, 72, 9), ("chipkill_x4", 4, 144, 36), ("rs(34,32)", 2, 272, 34), ("RS(72, 64)", 1, 576, 72)]
+        {
+            let scheme = Scheme::parse(spec).unwrap();
+            assert_eq!((scheme.beats(), scheme.bus_bits(), scheme.devices()), (beats, bus_bits, devices), "{}", spec);
+            let line = sample_line(1);
+            let stored = scheme.encode(&line);
+            assert_eq!(stored.len() * 8, scheme.line_bits());
+            assert_eq!(scheme.classify(&stored, &line), (line, Access::Clean));
+            // Every stored bit is reachable exactly once through (beat, lane)
+            let mut seen = vec![false; scheme.line_bits()];
+            for beat in 0..beats {
+                for lane in 0..bus_bits {
+                    let (byte, bit) = scheme.locate(beat, lane);
+                    assert!(!seen[8 * byte + bit]);
+                    seen[8 * byte + bit] = true;
+                }
+            }
+        }
+        for bad in ["secded64", "rs(34,30)", "rs(32,32)", "rs(300,64)", "rs(34;32)"] {
+            assert!(Scheme::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_fault_classes() {
+        let line = sample_line(2);
+        let mut secded = MemoryModel::new(Scheme::parse("secded72").unwrap(), 1);
+        secded.write(0x1000, &line);
+        secded.inject(0x1000, Fault::Bit(75)).unwrap();
+        assert_eq!(secded.read(0x1010), (line, Access::Ce));
+        secded.inject(0x1000, Fault::Bit(76)).unwrap();
+        assert_eq!(secded.read(0x1000).1, Access::Due);
+        assert_eq!(secded.read(0x2000), ([0u8; LINE_BYTES], Access::Clean));
+
+        let mut chipkill = MemoryModel::new(Scheme::parse("chipkill_x4").unwrap(), 1);
+        chipkill.write(0, &line);
+        chipkill.inject_random_device(0, 17).unwrap();
+        assert_eq!(chipkill.read(0), (line, Access::Ce));
+        chipkill.inject_random_device(0, 3).unwrap();
+        assert_eq!(chipkill.read(0).1, Access::Due);
+        assert_eq!(
+            chipkill.counters(),
+            MemoryCounters { writes: 1, reads: 2, faults: 2, ce: 1, due: 1, ..MemoryCounters::default() }
+        );
+
+        let mut rs = MemoryModel::new(Scheme::parse("rs(36,32)").unwrap(), 1);
+        rs.write(64, &line);
+        rs.inject(64, Fault::RowBurst { beat: 1, start: 8, len: 16 }).unwrap();
+        assert_eq!(rs.read(64), (line, Access::Ce));
+        assert_eq!(
+            rs.inject(64, Fault::Device { device: 2, pattern: 1 << 16 }),
+            Err(FaultError::PatternTooWide { bits: 16 })
+        );
+        assert!(rs.inject(64, Fault::RowBurst { beat: 0, start: 280, len: 9 }).is_err());
+        assert_eq!(rs.counters().faults, 1);
+    }
+
+    #[test]
+    fn test_sweep_deterministic() {
+        let mix = FaultMix { bit: 0.05, double_bit: 0.02, device: 0.02, row: 0.01 };
+        let run = || MemoryModel::new(Scheme::parse("secded72").unwrap(), 0).sweep(20_000, &mix, 42).unwrap();
+        let a = run();
+        assert_eq!(a, run());
+        assert_eq!(a.reads, 20_000);
+        assert_eq!(a.clean + a.ce + a.due + a.sdc, a.reads);
+        // Single-bit faults are always corrected and double-bit faults always detected
+        assert!(a.ce > 0 && a.due > 0);
+
+        let chipkill = MemoryModel::new(Scheme::parse("chipkill_x4").unwrap(), 0)
+            .sweep(5_000, &FaultMix { device: 0.5, ..FaultMix::default() }, 7)
+            .unwrap();
+        assert_eq!((chipkill.ce, chipkill.due, chipkill.sdc), (chipkill.faults, 0, 0));
+        assert!(FaultMix { bit: 0.6, row: 0.6, ..FaultMix::default() }.validate().is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_sweep_independent_of_thread_count() {
+        let mix = FaultMix { bit: 0.1, double_bit: 0.1, device: 0.1, row: 0.1 };
+        let run = |threads| {
+            rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap().install(|| {
+                MemoryModel::new(Scheme::parse("rs(36,32)").unwrap(), 0).sweep(3000, &mix, 5).unwrap()
+            })
+        };
+        assert_eq!(run(1), run(4));
+    }
+}
@@ -0,0 +1,218 @@
+// DDR5 on-die ECC: a (136,128) single-error-correcting Hamming code inside each DRAM device
+// Every 128 data bits the device stores carry 8 check bits the controller never sees. On a read
+// the device corrects what looks like a single-bit error and passes anything else through
+// unflagged, so a double-bit error either survives as-is or, when its syndrome matches a third
+// column, gains a miscorrected bit. Which happens is decided by the H-matrix, which vendors do
+// not publish; the default is a plain Hamming layout and from_columns takes any other.
+// Bit positions: 0..=127 are data bits (bit i of the u128), 128..=135 are check bits 0..=7.
+// system composes this with the controller-side codes of the memory model.
+
+#[cfg(feature = "sim")]
+pub mod system;
+
+pub const DATA_BITS: usize = 128;
+pub const WORD_BITS: usize = 136;
+
+const NO_COLUMN: u8 = 0xFF;
+
+/// Outcome of an on-die decode
+/// As with SEC-DED, the decoder cannot see errors that alias onto a codeword or a single-bit
+/// syndrome; only a comparison with the written data can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OdeccResult {
+    NoError,
+    /// Single-bit error at the given position (0..=135), corrected
+    CorrectedBit(u8),
+    /// Syndrome matching no column: the word is passed through uncorrected
+    Uncorrectable,
+}
+
+impl OdeccResult {
+    /// Data the device returns: corrected if it found a column, else as stored
+    pub fn corrected_data(self, data: u128) -> u128 {
+        match self {
+            OdeccResult::CorrectedBit(pos) if (pos as usize) < DATA_BITS => data ^ (1u128 << pos),
+            _ => data,
+        }
+    }
+}
+
+/// Parity-check matrix as 8-bit columns, one per data bit (check-bit columns are the unit vectors)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OdeccMatrix {
+    data_cols: [u8; DATA_BITS],
+    syndrome_to_pos: [u8; 256],
+}
+
+impl OdeccMatrix {
+    /// Plain Hamming layout: the 128 smallest non-unit columns in increasing order
+    pub const fn hamming() -> Self {
+        let mut data_cols = [0u8; DATA_BITS];
+        let mut count = 0usize;
+        let mut col = 1u32;
+        while count < DATA_BITS {
+            if (col as u8).count_ones() > 1 {
+                data_cols[count] = col as u8;
+                count += 1;
+            }
+            col += 1;
+        }
+        Self { data_cols, syndrome_to_pos: build_syndrome_table(&data_cols) }
+    }
+
+    /// Custom layout; columns must be nonzero, distinct, and distinct from the check-bit unit columns
+    pub fn from_columns(data_cols: [u8; DATA_BITS]) -> Result<Self, &'static str> {
+        let mut seen = [false; 256];
+        for bit in 0..8 {
+            seen[1usize << bit] = true;
+        }
+        for &col in &data_cols {
+            if col == 0 {
+                return Err("H-matrix column must be nonzero");
+            }
+            if seen[col as usize] {
+                return Err("H-matrix columns must be distinct (including the check-bit unit columns)");
+            }
+            seen[col as usize] = true;
+        }
+        Ok(Self { data_cols, syndrome_to_pos: build_syndrome_table(&data_cols) })
+    }
+
+    /// Column for data bit i
+    pub fn data_column(&self, i: usize) -> u8 {
+        self.data_cols[i]
+    }
+
+    /// Column for any position 0..=135
+    pub fn column(&self, pos: usize) -> u8 {
+        if pos < DATA_BITS {
+            self.data_cols[pos]
+        } else {
+            1 << (pos - DATA_BITS)
+        }
+    }
+
+    /// Check bits for a data word
+    pub fn check_bits(&self, data: u128) -> u8 {
+        let mut check = 0u8;
+        let mut bits = data;
+        while bits != 0 {
+            check ^= self.data_cols[bits.trailing_zeros() as usize];
+            bits &= bits - 1;
+        }
+        check
+    }
+
+    /// Classify a received (data, check) pair
+    pub fn decode(&self, data: u128, check: u8) -> OdeccResult {
+        let syndrome = check ^ self.check_bits(data);
+        if syndrome == 0 {
+            return OdeccResult::NoError;
+        }
+        match self.syndrome_to_pos[syndrome as usize] {
+            NO_COLUMN => OdeccResult::Uncorrectable,
+            pos => OdeccResult::CorrectedBit(pos),
+        }
+    }
+}
+
+impl Default for OdeccMatrix {
+    fn default() -> Self {
+        Self::hamming()
+    }
+}
+
+const fn build_syndrome_table(data_cols: &[u8; DATA_BITS]) -> [u8; 256] {
+    let mut table = [NO_COLUMN; 256];
+    let mut i = 0usize;
+    while i < DATA_BITS {
+        table[data_cols[i] as usize] = i as u8;
+        i += 1;
+    }
+    let mut bit = 0usize;
+    while bit < 8 {
+        table[1usize << bit] = (DATA_BITS + bit) as u8;
+        bit += 1;
+    }
+    table
+}
+
+/// Flip bit `pos` (0..=135) of a (data, check) pair
+pub fn flip_bit(data: u128, check: u8, pos: usize) -> (u128, u8) {
+    if pos < DATA_BITS {
+        (data ^ (1u128 << pos), check)
+    } else {
+        (data, check ^ (1u8 << (pos - DATA_BITS)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    #[test]
+    fn test_corrects_every_single_bit_error() {
+        let h = OdeccMatrix::hamming();
+        assert!(OdeccMatrix::from_columns(h.data_cols).is_ok());
+        let mut rng = Rng::new(398);
+        for _ in 0..500 {
+            let data = (rng.next_u64() as u128) << 64 | rng.next_u64() as u128;
+            let check = h.check_bits(data);
+            assert_eq!(h.decode(data, check), OdeccResult::NoError);
+            for pos in 0..WORD_BITS {
+                let (d, c) = flip_bit(data, check, pos);
+                let result = h.decode(d, c);
+                assert_eq!(result, OdeccResult::CorrectedBit(pos as u8));
+                assert_eq!(result.corrected_data(d), data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_double_errors_depend_on_the_matrix() {
+        // Without a ninth check bit there is no DED: a pair either aliases to a third column
+        // (miscorrection) or hits an unused syndrome (passed through)
+        let h = OdeccMatrix::hamming();
+        let (mut miscorrected, mut uncorrectable) = (0, 0);
+        for a in 0..WORD_BITS {
+            for b in a + 1..WORD_BITS {
+                let (d, c) = flip_bit(0, h.check_bits(0), a);
+                let (d, c) = flip_bit(d, c, b);
+                match h.decode(d, c) {
+                    OdeccResult::CorrectedBit(p) => {
+                        assert_eq!(h.column(p as usize), h.column(a) ^ h.column(b));
+                        miscorrected += 1;
+                    }
+                    OdeccResult::Uncorrectable => uncorrectable += 1,
+                    OdeccResult::NoError => panic!("columns {} and {} are equal", a, b),
+                }
+            }
+        }
+        assert_eq!(miscorrected + uncorrectable, 136 * 135 / 2);
+        assert!(miscorrected > 0 && uncorrectable > 0, "{} {}", miscorrected, uncorrectable);
+
+        // Columns 0x07 and 0x19 sum to 0x1e: a data column of the Hamming layout, so that pair
+        // miscorrects there. An odd-weight-first layout (all 120 odd non-unit columns, then the 8
+        // smallest even ones) does not use 0x1e, and passes the same pair through.
+        let mut odd_first: Vec<u8> = (0..=255u8).filter(|&c| c.count_ones() % 2 == 1 && c.count_ones() > 1).collect();
+        odd_first.extend((0..=255u8).filter(|&c| c.count_ones() % 2 == 0 && c != 0).take(DATA_BITS - odd_first.len()));
+        let odd_first = OdeccMatrix::from_columns(odd_first.try_into().unwrap()).unwrap();
+        for (matrix, expected) in [(&h, OdeccResult::CorrectedBit(h.data_cols.iter().position(|&c| c == 0x1e).unwrap() as u8)), (&odd_first, OdeccResult::Uncorrectable)] {
+            let at = |col: u8| matrix.data_cols.iter().position(|&c| c == col).unwrap();
+            let error = 1u128 << at(0x07) | 1u128 << at(0x19);
+            assert_eq!(matrix.decode(error, matrix.check_bits(0)), expected);
+        }
+    }
+
+    #[test]
+    fn test_custom_matrix_validation() {
+        let mut cols = OdeccMatrix::hamming().data_cols;
+        cols[5] = cols[6];
+        assert!(OdeccMatrix::from_columns(cols).is_err());
+        cols[5] = 0x80;
+        assert!(OdeccMatrix::from_columns(cols).is_err());
+        cols[5] = 0;
+        assert!(OdeccMatrix::from_columns(cols).is_err());
+    }
+}
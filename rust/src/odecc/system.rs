@@ -0,0 +1,290 @@
+// On-die ECC under the controller-side codes of the memory model
+// Each device of a memory::Scheme keeps the bits it contributes to a line in the low data bits of
+// one on-die word (bit j = lane j % width of beat j / width, as Fault::Device patterns); the rest
+// of that word holds other lines, which the controller never reads here but whose faults still
+// steer the on-die correction. Cell faults are flips in those 136-bit words. A read runs each
+// faulty word through the on-die decoder, then hands the part of the residual error that lies in
+// this line to the controller. Both codes are linear, so the outcomes depend only on the error
+// patterns, not on the data stored.
+
+use std::ops::Add;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::memory::{Access, Fault, FaultError, Line, MemoryCounters, Scheme, LINE_BYTES};
+use crate::odecc::{flip_bit, OdeccMatrix, OdeccResult, WORD_BITS};
+use crate::rs::sim::trial_rng;
+
+/// A flipped cell: bit `cell` (0..=135, check bits from 128) of `device`'s on-die word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellFault {
+    pub device: usize,
+    pub cell: usize,
+}
+
+/// Outcome of one read through both levels
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Read {
+    /// Each faulty on-die word's device and its outcome against the written data: Ce when the
+    /// device fixed it, Due when it passed the error through, Sdc when it aliased or miscorrected
+    pub die: Vec<(usize, Access)>,
+    pub line: Line,
+    /// The controller's classification of the whole line
+    pub access: Access,
+}
+
+/// Counts of faulty on-die words by outcome
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DieCounters {
+    pub words: u64,
+    pub ce: u64,
+    pub due: u64,
+    pub sdc: u64,
+}
+
+impl Add for DieCounters {
+    type Output = DieCounters;
+
+    fn add(self, other: DieCounters) -> DieCounters {
+        DieCounters { words: self.words + other.words, ce: self.ce + other.ce, due: self.due + other.due, sdc: self.sdc + other.sdc }
+    }
+}
+
+/// Paired results of a sweep: the same faults read with on-die ECC and without it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OnDieStats {
+    pub die: DieCounters,
+    /// Controller outcomes behind on-die ECC
+    pub system: MemoryCounters,
+    /// Controller outcomes with the faults hitting it directly (the RS-only model)
+    pub controller_only: MemoryCounters,
+}
+
+impl Add for OnDieStats {
+    type Output = OnDieStats;
+
+    fn add(self, other: OnDieStats) -> OnDieStats {
+        OnDieStats { die: self.die + other.die, system: self.system + other.system, controller_only: self.controller_only + other.controller_only }
+    }
+}
+
+/// A memory scheme whose devices apply on-die ECC
+pub struct OnDie {
+    scheme: Scheme,
+    matrix: OdeccMatrix,
+}
+
+impl OnDie {
+    /// Each device's share of a line must fit a Fault::Device pattern (64 bits)
+    pub fn new(scheme: Scheme, matrix: OdeccMatrix) -> Result<Self, &'static str> {
+        if scheme.beats() * scheme.device_width() > 64 {
+            return Err("on-die model needs at most 64 bits per device per line");
+        }
+        Ok(Self { scheme, matrix })
+    }
+
+    pub fn scheme(&self) -> &Scheme {
+        &self.scheme
+    }
+
+    pub fn matrix(&self) -> &OdeccMatrix {
+        &self.matrix
+    }
+
+    /// Cells of each device's on-die word, devices() * 136 in all
+    pub fn cells(&self) -> usize {
+        self.scheme.devices() * WORD_BITS
+    }
+
+    /// Per-device error patterns (data, check) of the faulty on-die words, in device order
+    fn words(&self, faults: &[CellFault]) -> Result<Vec<(usize, u128, u8)>, FaultError> {
+        let mut words: Vec<(usize, u128, u8)> = Vec::new();
+        for fault in faults {
+            if fault.device >= self.scheme.devices() {
+                return Err(FaultError::DeviceOutOfRange { device: fault.device, devices: self.scheme.devices() });
+            }
+            if fault.cell >= WORD_BITS {
+                return Err(FaultError::BitOutOfRange { bit: fault.cell, line_bits: WORD_BITS });
+            }
+            let word = match words.iter().position(|w| w.0 == fault.device) {
+                Some(i) => &mut words[i],
+                None => {
+                    words.push((fault.device, 0, 0));
+                    words.last_mut().unwrap()
+                }
+            };
+            (word.1, word.2) = flip_bit(word.1, word.2, fault.cell);
+        }
+        words.sort_unstable_by_key(|w| w.0);
+        Ok(words)
+    }
+
+    /// The line's bits of a data error pattern, as a Fault::Device pattern
+    fn visible(&self, device: usize, error: u128) -> Fault {
+        let bits = self.scheme.beats() * self.scheme.device_width();
+        let mask = if bits >= 64 { u64::MAX } else { (1u64 << bits) - 1 };
+        Fault::Device { device, pattern: error as u64 & mask }
+    }
+
+    fn stored_with(&self, written: &Line, errors: impl Iterator<Item = Fault>) -> Vec<u8> {
+        let mut stored = self.scheme.encode(written);
+        for fault in errors {
+            self.scheme.apply(&mut stored, &fault).expect("device patterns are in range");
+        }
+        stored
+    }
+
+    /// Read `written` back with `faults` in the devices' on-die words
+    pub fn read(&self, written: &Line, faults: &[CellFault]) -> Result<Read, FaultError> {
+        let mut die = Vec::new();
+        let mut residuals = Vec::new();
+        for (device, data, check) in self.words(faults)? {
+            let result = self.matrix.decode(data, check);
+            let residual = match result {
+                OdeccResult::CorrectedBit(pos) => flip_bit(data, check, pos as usize),
+                _ => (data, check),
+            };
+            let access = match result {
+                OdeccResult::Uncorrectable => Access::Due,
+                _ if residual != (0, 0) => Access::Sdc,
+                OdeccResult::CorrectedBit(_) => Access::Ce,
+                OdeccResult::NoError => Access::Clean,
+            };
+            die.push((device, access));
+            residuals.push(self.visible(device, residual.0));
+        }
+        let (line, access) = self.scheme.classify(&self.stored_with(written, residuals.into_iter()), written);
+        Ok(Read { die, line, access })
+    }
+
+    /// The controller's classification with no on-die ECC: the faults' line bits hit it directly
+    pub fn read_controller_only(&self, written: &Line, faults: &[CellFault]) -> Result<Access, FaultError> {
+        let words = self.words(faults)?;
+        let errors = words.into_iter().map(|(device, data, _)| self.visible(device, data));
+        Ok(self.scheme.classify(&self.stored_with(written, errors), written).1)
+    }
+
+    /// `accesses` reads of random lines, each with `cells` distinct random cell faults spread
+    /// over all devices' on-die words, classified with and without on-die ECC. Access i draws
+    /// from trial_rng(seed, i), so results do not depend on thread scheduling.
+    pub fn sweep(&self, accesses: u64, cells: usize, seed: u64) -> OnDieStats {
+        let run_access = |i: u64| -> OnDieStats {
+            let mut rng = trial_rng(seed, i);
+            let mut written = [0u8; LINE_BYTES];
+            for chunk in written.chunks_exact_mut(8) {
+                chunk.copy_from_slice(&rng.next_u64().to_le_bytes());
+            }
+            let faults: Vec<CellFault> = rng.distinct(cells, self.cells()).into_iter().map(|c| CellFault { device: c / WORD_BITS, cell: c % WORD_BITS }).collect();
+            let read = self.read(&written, &faults).expect("random cells are in range");
+            let mut stats = OnDieStats::default();
+            for (_, access) in &read.die {
+                stats.die.words += 1;
+                match access {
+                    Access::Ce => stats.die.ce += 1,
+                    Access::Due => stats.die.due += 1,
+                    Access::Sdc => stats.die.sdc += 1,
+                    Access::Clean => {}
+                }
+            }
+            stats.system = counters(read.access, cells);
+            stats.controller_only = counters(self.read_controller_only(&written, &faults).expect("random cells are in range"), cells);
+            stats
+        };
+
+        #[cfg(feature = "parallel")]
+        let stats = (0..accesses).into_par_iter().map(run_access).reduce(OnDieStats::default, Add::add);
+        #[cfg(not(feature = "parallel"))]
+        let stats = (0..accesses).map(run_access).fold(OnDieStats::default(), Add::add);
+        stats
+    }
+}
+
+/// One read's MemoryCounters
+fn counters(access: Access, cells: usize) -> MemoryCounters {
+    let mut counters = MemoryCounters { writes: 1, reads: 1, faults: cells as u64, ..MemoryCounters::default() };
+    match access {
+        Access::Clean => counters.clean = 1,
+        Access::Ce => counters.ce = 1,
+        Access::Due => counters.due = 1,
+        Access::Sdc => counters.sdc = 1,
+    }
+    counters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::odecc::DATA_BITS;
+    use crate::rs::sim::Rng;
+
+    fn sample_line(seed: u64) -> Line {
+        let mut rng = Rng::new(seed);
+        let mut line = [0u8; LINE_BYTES];
+        line.iter_mut().for_each(|b| *b = rng.next_u64() as u8);
+        line
+    }
+
+    fn on_die(spec: &str) -> OnDie {
+        OnDie::new(Scheme::parse(spec).unwrap(), OdeccMatrix::hamming()).unwrap()
+    }
+
+    #[test]
+    fn test_single_cell_fault_is_masked() {
+        let memory = on_die("rs(18,16)");
+        let line = sample_line(1);
+        // Cell 5 is in this line's bits; cell 130 is an on-die check bit
+        for cell in [5, 130] {
+            let fault = [CellFault { device: 3, cell }];
+            assert_eq!(memory.read(&line, &fault).unwrap(), Read { die: vec![(3, Access::Ce)], line, access: Access::Clean });
+        }
+        assert_eq!(memory.read_controller_only(&line, &[CellFault { device: 3, cell: 5 }]), Ok(Access::Ce));
+        assert_eq!(memory.read_controller_only(&line, &[CellFault { device: 3, cell: 130 }]), Ok(Access::Clean));
+        assert!(memory.read(&line, &[CellFault { device: 18, cell: 0 }]).is_err());
+        assert!(memory.read(&line, &[CellFault { device: 0, cell: 136 }]).is_err());
+    }
+
+    #[test]
+    fn test_two_cell_fault_in_one_word() {
+        let memory = on_die("rs(18,16)");
+        let h = memory.matrix();
+        let line = sample_line(2);
+        // Data bits 0 and 1 (columns 0x03, 0x05) alias onto data bit 2 (0x06): the device turns a
+        // double error into a triple one, all within the line's 32 bits of that device
+        assert_eq!(h.column(0) ^ h.column(1), h.column(2));
+        let read = memory.read(&line, &[CellFault { device: 7, cell: 0 }, CellFault { device: 7, cell: 1 }]).unwrap();
+        assert_eq!(read.die, vec![(7, Access::Sdc)]);
+        // One device is one symbol per RS(18,16) codeword, so the controller still corrects it
+        assert_eq!((read.line, read.access), (line, Access::Ce));
+
+        // Bits 0 and 127 sum to a syndrome the Hamming layout leaves unused: passed through
+        let unused = h.column(0) ^ h.column(127);
+        assert!((0..WORD_BITS).all(|p| h.column(p) != unused));
+        let read = memory.read(&line, &[CellFault { device: 7, cell: 0 }, CellFault { device: 7, cell: 127 }]).unwrap();
+        assert_eq!((read.die, read.access), (vec![(7, Access::Due)], Access::Ce));
+
+        // The miscorrected bit can land in this line even when both faults lie outside it
+        let (a, b) = (32..DATA_BITS)
+            .flat_map(|a| (a + 1..DATA_BITS).map(move |b| (a, b)))
+            .find(|&(a, b)| (0..32).any(|p| h.column(p) == h.column(a) ^ h.column(b)))
+            .unwrap();
+        let faults = [CellFault { device: 2, cell: a }, CellFault { device: 2, cell: b }];
+        assert_eq!(memory.read(&line, &faults).unwrap().access, Access::Ce);
+        assert_eq!(memory.read_controller_only(&line, &faults), Ok(Access::Clean));
+    }
+
+    #[test]
+    fn test_on_die_changes_system_rates() {
+        // Two random cell faults per access, across 18 devices' on-die words: on-die ECC absorbs
+        // the usual case of faults in different devices, which alone is often a CE (one fault in
+        // the line) or a DUE (two symbols of one RS(18,16) codeword)
+        let memory = on_die("rs(18,16)");
+        let stats = memory.sweep(20_000, 2, 398);
+        assert_eq!(stats, memory.sweep(20_000, 2, 398));
+        assert_eq!((stats.system.reads, stats.controller_only.reads), (20_000, 20_000));
+        assert!(stats.die.words > 20_000 && stats.die.sdc > 0 && stats.die.due > 0, "{:?}", stats.die);
+        assert!(stats.system.ce * 5 < stats.controller_only.ce, "{:?}", stats);
+        assert!(stats.system.due < stats.controller_only.due, "{:?}", stats);
+        assert_eq!(stats.system.due + stats.system.sdc, 0, "one faulty device is one symbol per codeword: {:?}", stats);
+    }
+}
@@ -0,0 +1,284 @@
+// Field-operation counts for hardware cost estimates (`opcount` feature)
+// Counted: multiplications (gf_mul, any Field's mul, and a GF(256) division as one inverse and
+// one multiply), inversions, and additions (XORs of field elements) in RS encoding and the
+// decode stages. The SIMD and split-table kernels charge the multiplies and adds of the scalar
+// algorithm they replace, k * nsym for a remainder and n * nsym for syndromes, so the totals do
+// not depend on the kernel; the Chien search's log-domain step, sigma_j X^-j to
+// sigma_j X^-(j+1), counts as the constant multiply it is in hardware. Additions are counted in
+// rs and gf256 only, not in the other codes, and precomputation (generator polynomials, the
+// split tables) is not counted at all. Operations go to the stage entered last on this
+// thread: syndromes, bm, chien, forney and verify are entered with their trace spans, encode by
+// the encoders, and anything else (erasure locators, applying corrections, decode_to's in-place
+// path, custom codes' stages) is `other`.
+// Counters are per thread and read with measure(). Without the feature the hooks are empty
+// inline functions and the stage guard a zero-sized value, so nothing is left after inlining.
+
+#[cfg(feature = "opcount")]
+pub use imp::*;
+
+/// Where counted operations are charged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    Encode,
+    Syndromes,
+    Bm,
+    Chien,
+    Forney,
+    Verify,
+    Other,
+}
+
+#[cfg(not(feature = "opcount"))]
+#[inline(always)]
+pub(crate) fn mul(_count: usize) {}
+
+#[cfg(not(feature = "opcount"))]
+#[inline(always)]
+pub(crate) fn inv(_count: usize) {}
+
+#[cfg(not(feature = "opcount"))]
+#[inline(always)]
+pub(crate) fn add(_count: usize) {}
+
+/// The guard stage() gives without the feature
+#[cfg(all(feature = "std", not(feature = "opcount")))]
+pub(crate) struct NoStage;
+
+#[cfg(all(feature = "std", not(feature = "opcount")))]
+#[inline(always)]
+pub(crate) fn stage(_stage: Stage) -> NoStage {
+    NoStage
+}
+
+#[cfg(feature = "opcount")]
+mod imp {
+    use super::Stage;
+    use std::cell::{Cell, RefCell};
+    use std::ops::{Add, AddAssign};
+
+    /// Field operations of one kind of work
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Ops {
+        pub mul: u64,
+        pub inv: u64,
+        pub add: u64,
+    }
+
+    impl Ops {
+        pub const ZERO: Ops = Ops { mul: 0, inv: 0, add: 0 };
+    }
+
+    impl Add for Ops {
+        type Output = Ops;
+
+        fn add(self, other: Ops) -> Ops {
+            Ops { mul: self.mul + other.mul, inv: self.inv + other.inv, add: self.add + other.add }
+        }
+    }
+
+    impl AddAssign for Ops {
+        fn add_assign(&mut self, other: Ops) {
+            *self = *self + other;
+        }
+    }
+
+    /// Ops per stage, as measure() reports them
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct StageOps {
+        pub encode: Ops,
+        pub syndromes: Ops,
+        pub bm: Ops,
+        pub chien: Ops,
+        pub forney: Ops,
+        pub verify: Ops,
+        pub other: Ops,
+    }
+
+    impl StageOps {
+        pub const ZERO: StageOps = StageOps { encode: Ops::ZERO, syndromes: Ops::ZERO, bm: Ops::ZERO, chien: Ops::ZERO, forney: Ops::ZERO, verify: Ops::ZERO, other: Ops::ZERO };
+
+        pub fn stage(&self, stage: Stage) -> Ops {
+            match stage {
+                Stage::Encode => self.encode,
+                Stage::Syndromes => self.syndromes,
+                Stage::Bm => self.bm,
+                Stage::Chien => self.chien,
+                Stage::Forney => self.forney,
+                Stage::Verify => self.verify,
+                Stage::Other => self.other,
+            }
+        }
+
+        fn stage_mut(&mut self, stage: Stage) -> &mut Ops {
+            match stage {
+                Stage::Encode => &mut self.encode,
+                Stage::Syndromes => &mut self.syndromes,
+                Stage::Bm => &mut self.bm,
+                Stage::Chien => &mut self.chien,
+                Stage::Forney => &mut self.forney,
+                Stage::Verify => &mut self.verify,
+                Stage::Other => &mut self.other,
+            }
+        }
+
+        pub fn total(&self) -> Ops {
+            self.encode + self.syndromes + self.bm + self.chien + self.forney + self.verify + self.other
+        }
+    }
+
+    impl AddAssign for StageOps {
+        fn add_assign(&mut self, other: StageOps) {
+            for stage in [Stage::Encode, Stage::Syndromes, Stage::Bm, Stage::Chien, Stage::Forney, Stage::Verify, Stage::Other] {
+                *self.stage_mut(stage) += other.stage(stage);
+            }
+        }
+    }
+
+    thread_local! {
+        static COUNTS: RefCell<StageOps> = const { RefCell::new(StageOps::ZERO) };
+        static CURRENT: Cell<Stage> = const { Cell::new(Stage::Other) };
+    }
+
+    fn charge(f: impl FnOnce(&mut Ops)) {
+        COUNTS.with(|counts| f(counts.borrow_mut().stage_mut(CURRENT.get())));
+    }
+
+    #[inline]
+    pub(crate) fn mul(count: usize) {
+        charge(|ops| ops.mul += count as u64);
+    }
+
+    #[inline]
+    pub(crate) fn inv(count: usize) {
+        charge(|ops| ops.inv += count as u64);
+    }
+
+    #[inline]
+    pub(crate) fn add(count: usize) {
+        charge(|ops| ops.add += count as u64);
+    }
+
+    /// Restores the previous stage when dropped
+    pub(crate) struct Entered(Stage);
+
+    impl Drop for Entered {
+        fn drop(&mut self) {
+            CURRENT.set(self.0);
+        }
+    }
+
+    /// Charge this thread's operations to `stage` until the guard drops
+    pub(crate) fn stage(stage: Stage) -> Entered {
+        Entered(CURRENT.replace(stage))
+    }
+
+    /// Run f and return what it did on this thread, per stage
+    /// Nested calls work: the outer one sees the inner one's operations too.
+    pub fn measure<R>(f: impl FnOnce() -> R) -> (R, StageOps) {
+        let outer = COUNTS.with(|counts| counts.replace(StageOps::ZERO));
+        let result = f();
+        let ops = COUNTS.with(|counts| {
+            let mut counts = counts.borrow_mut();
+            let ops = *counts;
+            *counts = outer;
+            counts.add_assign(ops);
+            ops
+        });
+        (result, ops)
+    }
+}
+
+#[cfg(all(test, feature = "opcount"))]
+mod tests {
+    use super::*;
+    use crate::gf256::simd::{self, Kernel};
+    use crate::rs::decoder::RsDecoder;
+    use crate::rs::sim::Rng;
+    #[cfg(not(feature = "small-tables"))]
+    use crate::rs::split::cached_split_tables;
+    use crate::rs::{cached_generator, calc_syndromes, decode, encode, encode_scalar, encode_with, BmVariant};
+
+    fn message(k: usize, seed: u64) -> Vec<u8> {
+        let mut rng = Rng::new(seed);
+        (0..k).map(|_| rng.nonzero_byte()).collect()
+    }
+
+    #[test]
+    fn test_encode_counts() {
+        // The naive division multiplies every nonzero message byte into all nsym taps; the
+        // kernels are charged the same whatever the message
+        for (k, nsym) in [(1, 2), (11, 4), (223, 32)] {
+            let generator = cached_generator(nsym);
+            let message = message(k, 416);
+            let (_, ops) = measure(|| encode_scalar(&message, nsym, generator));
+            let expected = Ops { mul: (k * nsym) as u64, inv: 0, add: (k * nsym) as u64 };
+            assert_eq!(ops, StageOps { encode: expected, ..StageOps::ZERO }, "k={} nsym={}", k, nsym);
+            let zeros = vec![0u8; k];
+            for m in [&message, &zeros] {
+                #[cfg(not(feature = "small-tables"))]
+                assert_eq!(measure(|| cached_split_tables(nsym).encode(m)).1.encode, expected);
+                if simd::active() != Kernel::Scalar {
+                    assert_eq!(measure(|| encode_with(simd::active(), m, nsym, generator)).1.encode, expected);
+                }
+            }
+            // Skipping zero coefficients is the naive path's only data dependence
+            assert_eq!(measure(|| encode_scalar(&zeros, nsym, generator)).1, StageOps::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_decode_stage_counts() {
+        let (n, nsym) = (40, 8);
+        let codeword = encode(&message(n - nsym, 1), nsym, cached_generator(nsym));
+        // Clean: only the n * nsym syndrome work
+        let (result, ops) = measure(|| decode(&codeword, nsym));
+        assert!(result.is_ok());
+        let syndromes = Ops { mul: (n * nsym) as u64, inv: 0, add: (n * nsym) as u64 };
+        assert_eq!(ops, StageOps { syndromes, ..StageOps::ZERO });
+        assert_eq!(measure(|| calc_syndromes(&codeword, nsym)).1.total(), syndromes);
+
+        // Three errors: BM does the inverses, Forney one division per error, and the residual
+        // syndrome update t * nsym multiplies
+        let mut received = codeword.clone();
+        for (pos, e) in [(2, 0x11), (17, 0x80), (39, 0x05)] {
+            received[pos] ^= e;
+        }
+        let (result, ops) = measure(|| decode(&received, nsym));
+        assert_eq!(result.unwrap().1, vec![2, 17, 39]);
+        assert_eq!(ops.syndromes, syndromes);
+        assert!(ops.bm.inv > 0 && ops.bm.mul > 0);
+        assert!(ops.chien.mul > 0 && ops.chien.inv == 0);
+        assert_eq!(ops.forney.inv, 3);
+        assert_eq!(ops.verify, Ops { mul: 3 * nsym as u64, inv: 0, add: 3 * nsym as u64 });
+        assert_eq!(ops.other, Ops { mul: 0, inv: 0, add: 3 });
+
+        // Nested measurements add up
+        let ((_, inner), outer) = measure(|| measure(|| decode(&received, nsym)));
+        assert_eq!((inner, outer), (ops, ops));
+    }
+
+    #[test]
+    fn test_bm_variants_differ() {
+        // The inversionless recurrence trades BM's inverses for multiplies; the locators differ
+        // by a scale factor, which leaves the Chien and Forney work unchanged
+        let (n, nsym) = (60, 16);
+        let mut rng = Rng::new(7);
+        for errors in 1..=nsym / 2 {
+            let mut received = encode(&message(n - nsym, errors as u64), nsym, cached_generator(nsym));
+            for pos in rng.distinct(errors, n) {
+                received[pos] ^= rng.nonzero_byte();
+            }
+            let mut standard = RsDecoder::new(nsym, n);
+            let mut inversionless = RsDecoder::new(nsym, n).with_bm(BmVariant::Inversionless);
+            let (a, a_ops) = measure(|| standard.decode_with(&received).map(|(m, p)| (m.to_vec(), p.to_vec())));
+            let (b, b_ops) = measure(|| inversionless.decode_with(&received).map(|(m, p)| (m.to_vec(), p.to_vec())));
+            assert_eq!(a, b);
+            assert!(a_ops.bm.inv > 0, "errors={}", errors);
+            assert_eq!(b_ops.bm.inv, 0);
+            assert!(b_ops.bm.mul > a_ops.bm.mul, "errors={}: {:?} {:?}", errors, a_ops.bm, b_ops.bm);
+            assert_eq!((a_ops.syndromes, a_ops.chien, a_ops.forney, a_ops.verify), (b_ops.syndromes, b_ops.chien, b_ops.forney, b_ops.verify));
+        }
+    }
+}
@@ -0,0 +1,296 @@
+// Two-dimensional RS product code with iterative row/column decoding
+// Data is a k_c x k_r grid, row-major. Each data row becomes an n_r-byte row codeword, then each
+// of the n_r columns (row parity included) becomes an n_c-byte column codeword, giving an
+// n_c x n_r matrix. The corner, where the column parity rows cross the row parity columns, is
+// therefore the column code's parity of the row parity. Both codes are linear, so this is also
+// the row code's parity of the column parity: every row and every column of the matrix is a
+// codeword, and decoding treats the corner as ordinary row and column symbols.
+//
+// Decoding alternates a pass over the rows with a pass over the columns, each fixing what its
+// code can and leaving failed lines alone, until every row and column checks clean, a full
+// iteration changes nothing, or the iteration cap is reached. A row with more than t_r errors
+// is fixed by the columns through it, and vice versa, so patterns far beyond either code alone
+// decode; what defeats it is an error rectangle more than t_c rows by t_r columns.
+
+use std::fmt;
+
+use crate::rs::{RsCodec, RsError};
+
+/// Iterations decode runs before giving up
+pub const DEFAULT_MAX_ITERATIONS: usize = 8;
+
+/// Row code across, column code down; see the module comment for the layout
+#[derive(Debug, Clone)]
+pub struct ProductCode {
+    row: RsCodec,
+    column: RsCodec,
+    max_iterations: usize,
+}
+
+/// What one row pass and the column pass after it did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Iteration {
+    /// Symbols corrected by the row pass
+    pub row_corrections: usize,
+    /// Rows the row decoder rejected
+    pub failed_rows: usize,
+    pub column_corrections: usize,
+    pub failed_columns: usize,
+}
+
+/// How decode stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProductStatus {
+    /// Every row and column is a codeword
+    Converged,
+    /// An iteration corrected nothing while lines still failed
+    Stalled,
+    /// The cap was reached with lines still failing
+    IterationLimit,
+}
+
+/// Per-iteration counts and the final status
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProductReport {
+    pub iterations: Vec<Iteration>,
+    pub status: ProductStatus,
+}
+
+impl ProductReport {
+    pub fn corrected(&self) -> usize {
+        self.iterations.iter().map(|i| i.row_corrections + i.column_corrections).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProductError {
+    /// The matrix is not n_c rows of n_r bytes; `row` is the first bad row, if the count is right
+    Shape { rows: usize, expected_rows: usize, row: Option<usize>, expected_cols: usize },
+    /// Decoding did not converge; the report says how far it got
+    Uncorrectable(ProductReport),
+}
+
+impl fmt::Display for ProductError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProductError::Shape { rows, expected_rows, row: None, .. } => write!(f, "matrix has {} rows, expected {}", rows, expected_rows),
+            ProductError::Shape { row: Some(row), expected_cols, .. } => write!(f, "matrix row {} is not {} bytes", row, expected_cols),
+            ProductError::Uncorrectable(report) => write!(f, "product decode {:?} after {} iterations", report.status, report.iterations.len()),
+        }
+    }
+}
+
+impl std::error::Error for ProductError {}
+
+/// Fix one line in place with `codec`: Ok(corrections) or the decoder's error
+fn correct_line(codec: &RsCodec, line: &mut [u8]) -> Result<usize, RsError> {
+    let fixes = codec.corrections(line)?;
+    for &(pos, byte) in &fixes {
+        line[pos] = byte;
+    }
+    Ok(fixes.len())
+}
+
+impl ProductCode {
+    /// row_codec protects each row (n_r bytes), col_codec each column (n_c bytes)
+    pub fn new(row_codec: RsCodec, col_codec: RsCodec) -> Self {
+        Self { row: row_codec, column: col_codec, max_iterations: DEFAULT_MAX_ITERATIONS }
+    }
+
+    /// Cap on row + column iterations; at least one is always run
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations.max(1);
+        self
+    }
+
+    pub fn row_codec(&self) -> &RsCodec {
+        &self.row
+    }
+
+    pub fn col_codec(&self) -> &RsCodec {
+        &self.column
+    }
+
+    /// Data bytes per matrix: k_c * k_r
+    pub fn data_len(&self) -> usize {
+        self.column.k() * self.row.k()
+    }
+
+    /// (rows, columns) of the encoded matrix: (n_c, n_r)
+    pub fn shape(&self) -> (usize, usize) {
+        (self.column.n(), self.row.n())
+    }
+
+    /// Encode a k_c x k_r row-major grid; panics unless data is data_len bytes
+    pub fn encode(&self, data: &[u8]) -> Vec<Vec<u8>> {
+        assert_eq!(data.len(), self.data_len(), "product data must be {} bytes", self.data_len());
+        let mut matrix: Vec<Vec<u8>> = data.chunks_exact(self.row.k()).map(|row| self.row.encode(row)).collect();
+        matrix.resize(self.column.n(), vec![0; self.row.n()]);
+        let mut column = vec![0u8; self.column.k()];
+        for j in 0..self.row.n() {
+            for (c, row) in column.iter_mut().zip(&matrix) {
+                *c = row[j];
+            }
+            let parity = &self.column.encode(&column)[self.column.k()..];
+            for (row, &b) in matrix[self.column.k()..].iter_mut().zip(parity) {
+                row[j] = b;
+            }
+        }
+        matrix
+    }
+
+    /// Decode iteratively; on convergence the k_c x k_r data, row-major, and the report
+    pub fn decode(&self, matrix: &[Vec<u8>]) -> Result<(Vec<u8>, ProductReport), ProductError> {
+        let (n_c, n_r) = self.shape();
+        if matrix.len() != n_c {
+            return Err(ProductError::Shape { rows: matrix.len(), expected_rows: n_c, row: None, expected_cols: n_r });
+        }
+        if let Some(row) = matrix.iter().position(|r| r.len() != n_r) {
+            return Err(ProductError::Shape { rows: n_c, expected_rows: n_c, row: Some(row), expected_cols: n_r });
+        }
+        let mut matrix = matrix.to_vec();
+        let mut iterations = vec![];
+        let mut column = vec![0u8; n_c];
+        let status = loop {
+            let mut pass = Iteration::default();
+            for row in matrix.iter_mut() {
+                match correct_line(&self.row, row) {
+                    Ok(fixed) => pass.row_corrections += fixed,
+                    Err(_) => pass.failed_rows += 1,
+                }
+            }
+            for j in 0..n_r {
+                for (c, row) in column.iter_mut().zip(&matrix) {
+                    *c = row[j];
+                }
+                match correct_line(&self.column, &mut column) {
+                    Ok(0) => {}
+                    Ok(fixed) => {
+                        pass.column_corrections += fixed;
+                        for (row, &c) in matrix.iter_mut().zip(&column) {
+                            row[j] = c;
+                        }
+                    }
+                    Err(_) => pass.failed_columns += 1,
+                }
+            }
+            iterations.push(pass);
+            // Columns that all decode may still leave a row the column pass broke or never saw
+            // fixed, so clean means every row checks too
+            if pass.failed_columns == 0 && matrix.iter().all(|row| self.row.check(row)) {
+                break ProductStatus::Converged;
+            }
+            if pass.row_corrections + pass.column_corrections == 0 {
+                break ProductStatus::Stalled;
+            }
+            if iterations.len() == self.max_iterations {
+                break ProductStatus::IterationLimit;
+            }
+        };
+        let report = ProductReport { iterations, status };
+        if status != ProductStatus::Converged {
+            return Err(ProductError::Uncorrectable(report));
+        }
+        let data = matrix[..self.column.k()].iter().flat_map(|row| &row[..self.row.k()]).copied().collect();
+        Ok((data, report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    /// RS(12, 8) rows and RS(10, 6) columns: t = 2 each way, 6 x 8 data
+    fn code() -> ProductCode {
+        ProductCode::new(RsCodec::new(4, 12).unwrap(), RsCodec::new(4, 10).unwrap())
+    }
+
+    fn data(rng: &mut Rng) -> Vec<u8> {
+        (0..48).map(|_| rng.next_u64() as u8).collect()
+    }
+
+    #[test]
+    fn test_every_line_is_a_codeword() {
+        let code = code();
+        let data = data(&mut Rng::new(413));
+        let matrix = code.encode(&data);
+        assert_eq!((matrix.len(), matrix[0].len()), code.shape());
+        assert!(matrix.iter().all(|row| code.row_codec().check(row)));
+        for j in 0..12 {
+            let column: Vec<u8> = matrix.iter().map(|row| row[j]).collect();
+            assert!(code.col_codec().check(&column), "column {}", j);
+        }
+        // The corner is the same whichever code is applied last
+        let transposed = ProductCode::new(code.col_codec().clone(), code.row_codec().clone());
+        let by_columns: Vec<u8> = (0..8).flat_map(|j| (0..6).map(|i| data[i * 8 + j]).collect::<Vec<_>>()).collect();
+        let other = transposed.encode(&by_columns);
+        assert!((0..10).all(|i| (0..12).all(|j| matrix[i][j] == other[j][i])));
+        assert_eq!(code.decode(&matrix), Ok((data, ProductReport { iterations: vec![Iteration::default()], status: ProductStatus::Converged })));
+    }
+
+    #[test]
+    fn test_column_pass_rescues_overloaded_row() {
+        // Five errors in row 1, past the row code's t = 2: the row pass gives up on it, each
+        // column then sees one error and fixes it
+        let code = code();
+        let data = data(&mut Rng::new(4131));
+        let mut matrix = code.encode(&data);
+        for j in [0, 3, 5, 9, 11] {
+            matrix[1][j] ^= 0x40 + j as u8;
+        }
+        assert!(code.row_codec().decode(&matrix[1]).is_err());
+        let (decoded, report) = code.decode(&matrix).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(report.status, ProductStatus::Converged);
+        assert_eq!(report.iterations, [Iteration { row_corrections: 0, failed_rows: 1, column_corrections: 5, failed_columns: 0 }]);
+    }
+
+    #[test]
+    fn test_converges_over_iterations() {
+        // Rows 0-2 have three errors each and column 0 three, all past t = 2. The first column
+        // pass clears each row's other two errors, leaving the second row pass one per row
+        let code = code();
+        let data = data(&mut Rng::new(4132));
+        let mut matrix = code.encode(&data);
+        for (i, j) in [(0, 0), (0, 3), (0, 4), (1, 0), (1, 5), (1, 6), (2, 0), (2, 8), (2, 9)] {
+            matrix[i][j] ^= 0x5a;
+        }
+        let (decoded, report) = code.decode(&matrix).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(report.status, ProductStatus::Converged);
+        assert_eq!(
+            report.iterations,
+            [
+                Iteration { row_corrections: 0, failed_rows: 3, column_corrections: 6, failed_columns: 1 },
+                Iteration { row_corrections: 3, failed_rows: 0, column_corrections: 0, failed_columns: 0 },
+            ]
+        );
+        assert_eq!(report.corrected(), 9);
+        assert!(matches!(code.clone().with_max_iterations(1).decode(&matrix), Err(ProductError::Uncorrectable(ProductReport { status: ProductStatus::IterationLimit, .. }))));
+    }
+
+    #[test]
+    fn test_error_rectangle_stalls() {
+        // 3 x 3 errors beat both codes: nothing is corrected and decode stops after one iteration
+        let code = code();
+        let mut matrix = code.encode(&data(&mut Rng::new(4133)));
+        for i in [1, 4, 6] {
+            for j in [0, 5, 8] {
+                matrix[i][j] ^= 0x11;
+            }
+        }
+        let Err(ProductError::Uncorrectable(report)) = code.decode(&matrix) else { panic!() };
+        assert_eq!(report.status, ProductStatus::Stalled);
+        assert_eq!(report.iterations, [Iteration { row_corrections: 0, failed_rows: 3, column_corrections: 0, failed_columns: 3 }]);
+        let capped = code.clone().with_max_iterations(0);
+        assert!(matches!(capped.decode(&matrix), Err(ProductError::Uncorrectable(ProductReport { status: ProductStatus::Stalled, .. }))));
+        assert!(matches!(code.decode(&matrix[1..]), Err(ProductError::Shape { rows: 9, row: None, .. })));
+        let mut ragged = matrix.clone();
+        ragged[3].pop();
+        assert!(matches!(code.decode(&ragged), Err(ProductError::Shape { row: Some(3), .. })));
+    }
+}
@@ -0,0 +1,1368 @@
+// PyO3 bindings: the ecc_model._rs extension module
+// The module declares gil_used = false, so free-threaded CPython (3.13t) imports it without
+// re-enabling the GIL. Frozen classes (RSCodec, ShardCodec, Interleaver, ...) are immutable apart
+// from atomic counters and may be shared by any number of threads; StreamEncoder, StreamDecoder
+// and MemoryModel hold mutable state, and a second thread using one concurrently gets a
+// RuntimeError ("Already borrowed") instead of a race. Process-wide state is limited to the
+// const-built GF/CRC tables and the exception classes, created once behind a OnceLock.
+
+use std::borrow::Cow;
+
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyInt, PyIterator, PyString, PyTuple};
+
+#[cfg(feature = "numpy")]
+mod array;
+mod blocks;
+mod detached;
+mod errors;
+mod file;
+mod gf;
+mod interleave;
+#[cfg(feature = "sim")]
+mod memory;
+mod plan;
+mod pool;
+#[cfg(feature = "numpy")]
+mod raid;
+mod shard;
+#[cfg(feature = "sim")]
+mod sim;
+mod stages;
+mod stats;
+mod stream;
+#[cfg(feature = "numpy")]
+mod wide;
+
+use errors::{codec_decode_error, decode_error, length_error, parameter_error};
+
+use crate::crc::Crc;
+use crate::gf256::{active_kernel, PRIM_POLY, SIMD_KERNELS, GF};
+use crate::rs::{build_generator, calc_syndromes, error_locations, BitErrors, BlockResult, DecodeOptions, decode_batch_with as rs_decode_batch_with, decode_crc as rs_decode_crc, encode_batch_with as rs_encode_batch_with, encode_crc as rs_encode_crc, pad_truncated, check_pinned, validate_params, CodeOptions, PositionSet, RsCodec, RsError};
+
+/// Any contiguous uint8 buffer-protocol object (bytes, bytearray, memoryview, numpy), borrowed without copying,
+/// or any other iterable of ints 0..=255 (list, tuple, range, generator), copied into a Vec
+pub struct BytesInput {
+    source: Source,
+}
+
+enum Source {
+    /// An exact bytes object, which no Python code can change
+    Bytes(PyBuffer<u8>),
+    /// Any other exporter (bytearray, memoryview, numpy), read-only views included: their memory
+    /// can still be written through the object they view
+    Buffer(PyBuffer<u8>),
+    Owned(Vec<u8>),
+}
+
+impl<'py> FromPyObject<'py> for BytesInput {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        // SAFETY: PyObject_CheckBuffer only inspects the type's buffer slots
+        if unsafe { pyo3::ffi::PyObject_CheckBuffer(ob.as_ptr()) } != 0 {
+            let buffer = extract_buffer(ob)?;
+            let source = if ob.is_exact_instance_of::<PyBytes>() { Source::Bytes(buffer) } else { Source::Buffer(buffer) };
+            return Ok(Self { source });
+        }
+        let not_bytes = || PyTypeError::new_err(format!("expected a bytes-like object of uint8 or an iterable of ints, got {}", type_name(ob)));
+        if ob.is_instance_of::<PyString>() {
+            return Err(not_bytes());
+        }
+        let items = ob.try_iter().map_err(|_| not_bytes())?;
+        let mut data = Vec::with_capacity(ob.len().unwrap_or(0));
+        for (i, item) in items.enumerate() {
+            let item = item?;
+            if !item.is_instance_of::<PyInt>() {
+                return Err(parameter_error(format!("element {} must be an int in 0..=255, got {}", i, type_name(&item))));
+            }
+            let byte = item.extract::<u8>().map_err(|_| parameter_error(format!("element {} = {} is out of range 0..=255", i, item)))?;
+            data.push(byte);
+        }
+        Ok(Self { source: Source::Owned(data) })
+    }
+}
+
+fn extract_buffer(ob: &Bound<'_, PyAny>) -> PyResult<PyBuffer<u8>> {
+    let buffer = PyBuffer::<u8>::get(ob).map_err(|_| {
+        PyTypeError::new_err(format!("expected a bytes-like object of uint8, got {}", type_name(ob)))
+    })?;
+    if !buffer.is_c_contiguous() {
+        return Err(PyTypeError::new_err(format!("{} buffer must be contiguous", type_name(ob))));
+    }
+    Ok(buffer)
+}
+
+fn type_name(ob: &Bound<'_, PyAny>) -> String {
+    ob.get_type().name().map(|n| n.to_string()).unwrap_or_else(|_| "object".into())
+}
+
+impl BytesInput {
+    pub fn len(&self) -> usize {
+        match &self.source {
+            Source::Bytes(buffer) | Source::Buffer(buffer) => buffer.len_bytes(),
+            Source::Owned(data) => data.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        match &self.source {
+            Source::Bytes(_) | Source::Buffer(_) if self.is_empty() => &[],
+            // SAFETY: the buffer is C-contiguous u8 (checked on extraction), and the exporter keeps
+            // the memory alive until the Py_buffer is released when `buffer` drops
+            Source::Bytes(buffer) | Source::Buffer(buffer) => unsafe { std::slice::from_raw_parts(buffer.buf_ptr() as *const u8, self.len()) },
+            Source::Owned(data) => data,
+        }
+    }
+
+    /// Whether no Python code can change the bytes while the GIL is released: only an exact
+    /// bytes object or our own copy. A read-only memoryview or numpy view does not qualify.
+    fn immutable(&self) -> bool {
+        matches!(self.source, Source::Bytes(_) | Source::Owned(_))
+    }
+
+    /// The bytes, borrowed if immutable and otherwise copied while the GIL is held
+    fn snapshot(&self) -> Cow<'_, [u8]> {
+        match self.immutable() {
+            true => Cow::Borrowed(self.as_slice()),
+            false => Cow::Owned(self.as_slice().to_vec()),
+        }
+    }
+
+    /// Run `f` on the bytes with the GIL released. Other threads can run meanwhile, so a buffer
+    /// they could write (bytearray, numpy, or a read-only view of one) is copied first and `f`
+    /// sees the copy; an exact bytes object is borrowed.
+    /// Free-threaded builds have no GIL to hold during the copy: as with hashlib or zlib there,
+    /// the caller must not resize or write a buffer while another thread is encoding or decoding it.
+    fn with_slice<R: Send>(&self, py: Python<'_>, f: impl FnOnce(&[u8]) -> R + Send) -> R {
+        let data = self.snapshot();
+        py.allow_threads(|| f(&data))
+    }
+}
+
+/// Run `f` on several inputs with the GIL released, each borrowed or copied as in with_slice
+fn with_slices<R: Send>(py: Python<'_>, inputs: &[BytesInput], f: impl FnOnce(&[&[u8]]) -> R + Send) -> R {
+    let data: Vec<Cow<'_, [u8]>> = inputs.iter().map(BytesInput::snapshot).collect();
+    let slices: Vec<&[u8]> = data.iter().map(|d| &d[..]).collect();
+    py.allow_threads(|| f(&slices))
+}
+
+/// Writable contiguous uint8 buffer (bytearray, writable memoryview, numpy) filled in place
+/// Other Python threads may read and write the buffer while a call is using it. Its memory is
+/// only touched with the GIL held: work done without the GIL runs on a copy taken first, and
+/// corrections go back as XOR deltas (xor_at), so a concurrent write elsewhere in the buffer
+/// survives, and one to a corrected byte keeps its own change with the correction on top.
+/// Free-threaded builds have no GIL to serialize the copy and the writes against such a write,
+/// so there the caller must not touch the buffer during the call.
+pub struct BytesOutput {
+    buffer: PyBuffer<u8>,
+}
+
+impl<'py> FromPyObject<'py> for BytesOutput {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let buffer = extract_buffer(ob)?;
+        if buffer.readonly() {
+            return Err(PyTypeError::new_err(format!("{} buffer is read-only", type_name(ob))));
+        }
+        Ok(Self { buffer })
+    }
+}
+
+impl BytesOutput {
+    pub fn len(&self) -> usize {
+        self.buffer.len_bytes()
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        if self.len() == 0 {
+            return &[];
+        }
+        // SAFETY: C-contiguous u8 buffer (checked on extraction) kept alive by `buffer`
+        unsafe { std::slice::from_raw_parts(self.buffer.buf_ptr() as *const u8, self.len()) }
+    }
+
+    /// Copy `data` into the buffer at `offset`; the caller checks the bounds. The GIL must be
+    /// held (it is, via `py`) so no Python code can resize or read the buffer meanwhile.
+    fn write_at(&mut self, _py: Python<'_>, offset: usize, data: &[u8]) {
+        assert!(offset + data.len() <= self.len());
+        // SAFETY: writable C-contiguous u8 buffer (checked on extraction) with room for data;
+        // callers pass an owned Vec, so `data` cannot alias the destination
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), (self.buffer.buf_ptr() as *mut u8).add(offset), data.len()) }
+    }
+
+    /// XOR `delta` into the byte at `offset`, with the GIL held as for write_at
+    fn xor_at(&mut self, py: Python<'_>, offset: usize, delta: u8) {
+        let byte = self.as_slice()[offset] ^ delta;
+        self.write_at(py, offset, &[byte]);
+    }
+}
+
+/// Address of the memory a BytesInput borrows (test hook for the zero-copy guarantee)
+#[pyfunction]
+fn _buffer_address(data: BytesInput) -> usize {
+    data.as_slice().as_ptr() as usize
+}
+
+/// Iterable of ints -> byte positions; range and uniqueness are checked by the decoder,
+/// but negative values are rejected here since they cannot be positions at all
+fn extract_positions(ob: &Bound<'_, PyAny>, n: usize) -> PyResult<Vec<usize>> {
+    let mut positions = Vec::new();
+    for item in ob.try_iter()? {
+        let pos: i64 = item?.extract()?;
+        if pos < 0 {
+            return Err(parameter_error(format!("erasure position {} out of range for n={}", pos, n)));
+        }
+        positions.push(pos as usize);
+    }
+    Ok(positions)
+}
+
+/// pinned= positions (known-good bytes), each below n
+fn extract_pinned(ob: Option<&Bound<'_, PyAny>>, n: usize) -> PyResult<PositionSet> {
+    let Some(ob) = ob else { return Ok(PositionSet::EMPTY) };
+    let mut positions = Vec::new();
+    for item in ob.try_iter()? {
+        let pos: i64 = item?.extract()?;
+        if pos < 0 || pos as usize >= n {
+            return Err(parameter_error(format!("pinned position {} out of range for n={}", pos, n)));
+        }
+        positions.push(pos as usize);
+    }
+    PositionSet::new(&positions).map_err(|e| parameter_error(e.to_string()))
+}
+
+/// Invalid (nsym, nsize) surfaces as ParameterError (a ValueError), naming the violated constraint
+fn param_error(e: RsError, nsym: usize, nsize: usize) -> PyErr {
+    parameter_error(format!("{} (nsym={}, nsize={})", e, nsym, nsize))
+}
+
+/// (message, corrected positions) from a successful decode
+type Decoded = (Vec<u8>, Vec<usize>);
+
+/// Failed-decode retries RSCodec.decode makes with erasure hints from on_failure
+const MAX_HINT_RETRIES: usize = 3;
+
+fn check_params(nsym: usize, nsize: usize) -> PyResult<()> {
+    validate_params(nsym, nsize).map_err(|e| param_error(e, nsym, nsize))
+}
+
+/// Detailed outcome of RSCodec.decode_detailed
+/// `codeword` is the corrected codeword, or None when the input was already valid.
+/// Iterating yields (message, positions), so it unpacks like the tuple API.
+#[pyclass(name = "DecodeResult", module = "ecc_model._rs", frozen, eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PyDecodeResult {
+    message: Vec<u8>,
+    #[pyo3(get)]
+    positions: Vec<usize>,
+    magnitudes: Vec<u8>,
+    codeword: Option<Vec<u8>>,
+    /// The erase_pos hints, sorted
+    erasures: Vec<usize>,
+    /// Codeword length and parity count, to locate positions
+    n: usize,
+    nsym: usize,
+    /// decode_detailed's msg_len, for padded codewords
+    msg_len: Option<usize>,
+}
+
+#[pymethods]
+impl PyDecodeResult {
+    #[getter]
+    fn message<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.message)
+    }
+
+    /// Error value XORed into each corrected position, aligned with `positions`
+    #[getter]
+    fn magnitudes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.magnitudes)
+    }
+
+    #[getter]
+    fn corrected(&self) -> bool {
+        !self.positions.is_empty()
+    }
+
+    #[getter]
+    fn num_corrected(&self) -> usize {
+        self.positions.len()
+    }
+
+    #[getter]
+    fn codeword<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyBytes>> {
+        self.codeword.as_ref().map(|c| PyBytes::new(py, c))
+    }
+
+    /// Corrected positions that were passed in erase_pos
+    #[getter]
+    fn erasure_positions(&self) -> Vec<usize> {
+        self.positions.iter().copied().filter(|p| self.erasures.binary_search(p).is_ok()).collect()
+    }
+
+    /// Corrected positions the decoder found on its own; with erasure_positions, this
+    /// partitions `positions`
+    #[getter]
+    fn error_positions(&self) -> Vec<usize> {
+        self.positions.iter().copied().filter(|p| self.erasures.binary_search(p).is_err()).collect()
+    }
+
+    /// Hinted positions whose byte turned out to be correct
+    #[getter]
+    fn unused_erasures(&self) -> Vec<usize> {
+        self.erasures.iter().copied().filter(|p| !self.positions.contains(p)).collect()
+    }
+
+    /// (position, bit) pairs flipped at error_positions, bit 0 = LSB; erasure_positions were
+    /// replaced outright, so they list no bits
+    #[getter]
+    fn bit_errors(&self) -> Vec<(usize, u8)> {
+        self.bit_report().bits
+    }
+
+    /// error_positions with exactly one bit flipped
+    #[getter]
+    fn single_bit_errors(&self) -> usize {
+        self.bit_report().single_bit
+    }
+
+    /// error_positions with two or more bits flipped
+    #[getter]
+    fn multi_bit_errors(&self) -> usize {
+        self.bit_report().multi_bit
+    }
+
+    /// (position, is_parity, message_index) for each of `positions`; message_index is None for
+    /// parity, and with msg_len for the zero padding after the message
+    #[getter]
+    fn locations(&self) -> Vec<(usize, bool, Option<usize>)> {
+        error_locations(&self.positions, self.n, self.nsym, self.msg_len).into_iter().map(|l| (l.position, l.is_parity, l.message_index)).collect()
+    }
+
+    /// Corrected positions in the parity symbols
+    #[getter]
+    fn parity_positions(&self) -> Vec<usize> {
+        self.positions.iter().copied().filter(|&p| p >= self.n - self.nsym).collect()
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        (self.message(py), self.positions.clone()).into_pyobject(py)?.try_iter()
+    }
+
+    fn __repr__(&self) -> String {
+        let codeword = match &self.codeword {
+            Some(c) => format!("<{} bytes>", c.len()),
+            None => "None".into(),
+        };
+        let hints = if self.erasures.is_empty() {
+            String::new()
+        } else {
+            format!(
+                ", erasure_positions={:?}, error_positions={:?}, unused_erasures={:?}",
+                self.erasure_positions(), self.error_positions(), self.unused_erasures()
+            )
+        };
+        format!(
+            "DecodeResult(num_corrected={}, positions={:?}, magnitudes={:?}, message=<{} bytes>, codeword={}{})",
+            self.positions.len(), self.positions, self.magnitudes, self.message.len(), codeword, hints
+        )
+    }
+}
+
+impl PyDecodeResult {
+    fn bit_report(&self) -> BitErrors {
+        BitErrors::new(&self.positions, &self.magnitudes, &self.erasures)
+    }
+}
+
+/// RSCodec(nsym, nsize, fcr, generator, prim, max_correct), as passed back through __reduce__
+type CtorArgs = (usize, usize, usize, u8, u32, Option<usize>);
+
+/// Reed-Solomon codec over GF(256) with cached parameters and generator polynomial
+/// max_correct caps the errors any decode may correct below nsym // 2. A block with more than
+/// t errors can land within t of a different codeword and be miscorrected; with the cap at m,
+/// patterns of up to nsym - m errors are always detected instead, and blocks the cap refuses
+/// raise UncorrectableError (reason "... more than max_correct=m allows"). Erasures are not
+/// counted, and max_correct=nsym // 2 decodes exactly as no cap does.
+#[pyclass(name = "RSCodec", module = "ecc_model._rs", frozen)]
+pub struct PyRsCodec {
+    codec: RsCodec,
+    stats: stats::CodecStats,
+}
+
+impl PyRsCodec {
+    /// Default-parameter codec, as used by the module-level functions
+    fn standard(nsym: usize, nsize: usize) -> PyResult<Self> {
+        let codec = RsCodec::new(nsym, nsize).map_err(|e| param_error(e, nsym, nsize))?;
+        Ok(Self { codec, stats: Default::default() })
+    }
+
+    fn with_max_correct(self, max_correct: Option<usize>) -> PyResult<Self> {
+        let Self { codec, stats } = self;
+        let (nsym, nsize) = (codec.nsym(), codec.n());
+        let codec = codec.with_decode_options(DecodeOptions { max_correct, ..DecodeOptions::default() }).map_err(|e| {
+            parameter_error(format!("{} (max_correct={}, nsym={}, nsize={})", e, max_correct.unwrap_or_default(), nsym, nsize))
+        })?;
+        Ok(Self { codec, stats })
+    }
+
+    fn check_codeword_len(&self, len: usize) -> PyResult<()> {
+        if len != self.codec.n() {
+            return Err(length_error(format!(
+                "codeword length {} does not match expected n={}",
+                len, self.codec.n()
+            )));
+        }
+        Ok(())
+    }
+
+    /// msg_len as a message length in 0..=k
+    fn check_msg_len(&self, msg_len: Option<i64>) -> PyResult<Option<usize>> {
+        match msg_len {
+            Some(m) if m < 0 || m as usize > self.codec.k() => Err(parameter_error(format!("msg_len={} must be in 0..={} (k)", m, self.codec.k()))),
+            m => Ok(m.map(|m| m as usize)),
+        }
+    }
+
+    /// One decoder run over the borrowed codeword, GIL released when the buffer allows
+    /// A correction at a `pinned` position turns the result into PinnedPositionConflict.
+    fn decode_with(&self, py: Python<'_>, codeword: &BytesInput, erasures: Option<&[usize]>, msg_len: Option<usize>, pinned: &PositionSet) -> BlockResult {
+        let result = codeword.with_slice(py, |cw| match (erasures, msg_len) {
+            (_, Some(msg_len)) => self.codec.decode_padded(cw, erasures.unwrap_or_default(), msg_len),
+            (None, None) => self.codec.decode(cw),
+            (Some(erasures), None) => self.codec.decode_errata(cw, erasures),
+        })?;
+        check_pinned(&result.1, pinned)?;
+        Ok(result)
+    }
+
+    /// decode(on_failure=...) retries: ask the callback for erasure candidates after each failure
+    /// The GIL is only held for the callback itself. Returns the erasures and result of the first
+    /// successful retry, or None (with a note added to `original`) once the hints run out.
+    #[allow(clippy::too_many_arguments)]
+    fn retry_with_hints(
+        &self,
+        py: Python<'_>,
+        codeword: &BytesInput,
+        erasures: Option<&[usize]>,
+        msg_len: Option<usize>,
+        pinned: &PositionSet,
+        on_failure: &Bound<'_, PyAny>,
+        original: &PyErr,
+    ) -> PyResult<Option<(Vec<usize>, Decoded)>> {
+        let received = PyBytes::new(py, codeword.as_slice());
+        let mut error = original.clone_ref(py);
+        let mut tried = vec![];
+        for _ in 0..MAX_HINT_RETRIES {
+            let hints = on_failure.call1((&received, error.value(py)))?;
+            if hints.is_none() {
+                break;
+            }
+            let mut hinted = erasures.unwrap_or_default().to_vec();
+            for p in extract_positions(&hints, self.codec.n())? {
+                if p >= self.codec.n() {
+                    return Err(parameter_error(format!("on_failure hint {} out of range for n={}", p, self.codec.n())));
+                }
+                if !hinted.contains(&p) {
+                    hinted.push(p);
+                }
+            }
+            match self.decode_with(py, codeword, Some(&hinted), msg_len, pinned) {
+                Ok(result) => return Ok(Some((hinted, result))),
+                Err(e) => error = codec_decode_error(py, e, &self.codec, codeword.as_slice(), ""),
+            }
+            tried.push(hinted);
+        }
+        let note = match tried.len() {
+            0 => "on_failure offered no erasure hints".to_string(),
+            n => format!("on_failure erasure hints tried {} time(s), all failed: {:?}", n, tried),
+        };
+        original.value(py).call_method1("add_note", (note,))?;
+        Ok(None)
+    }
+}
+
+#[pymethods]
+impl PyRsCodec {
+    /// fcr, generator (the primitive element whose powers are the roots) and prim (the field
+    /// polynomial) follow reedsolo's naming; the defaults are the standard code
+    #[new]
+    #[pyo3(signature = (nsym, nsize, fcr=0, generator=2, prim=0x11d, max_correct=None))]
+    fn new(nsym: usize, nsize: usize, fcr: usize, generator: u32, prim: u32, max_correct: Option<usize>) -> PyResult<Self> {
+        let generator = u8::try_from(generator)
+            .map_err(|_| parameter_error(format!("generator {} is not an element of GF(256)", generator)))?;
+        let options = CodeOptions { fcr, generator, prim };
+        let codec = RsCodec::with_options(nsym, nsize, options).map_err(|e| match e {
+            RsError::InvalidParameters(_) if options != CodeOptions::default() => parameter_error(format!(
+                "{} (nsym={}, nsize={}, fcr={}, generator={}, prim={:#x})",
+                e, nsym, nsize, fcr, generator, prim
+            )),
+            e => param_error(e, nsym, nsize),
+        })?;
+        Self { codec, stats: Default::default() }.with_max_correct(max_correct)
+    }
+
+    #[getter]
+    fn fcr(&self) -> usize {
+        self.codec.options().fcr
+    }
+
+    /// The generator element passed to the constructor (not the polynomial, see `generator`)
+    #[getter]
+    fn generator_element(&self) -> u8 {
+        self.codec.options().generator
+    }
+
+    #[getter]
+    fn prim(&self) -> u32 {
+        self.codec.options().prim
+    }
+
+    /// Correction cap below nsym // 2, or None for the full capability
+    #[getter]
+    fn max_correct(&self) -> Option<usize> {
+        self.codec.decode_options().max_correct
+    }
+
+    #[getter]
+    fn nsym(&self) -> usize {
+        self.codec.nsym()
+    }
+
+    #[getter]
+    fn n(&self) -> usize {
+        self.codec.n()
+    }
+
+    #[getter]
+    fn k(&self) -> usize {
+        self.codec.k()
+    }
+
+    #[getter]
+    fn max_errors(&self) -> usize {
+        self.codec.max_errors()
+    }
+
+    /// Erasures correctable when no errors are present (one per parity symbol)
+    #[getter]
+    fn max_erasures(&self) -> usize {
+        self.codec.nsym()
+    }
+
+    /// Generator polynomial coefficients, index 0 = constant term, index nsym = leading 1
+    #[getter]
+    fn generator<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.codec.generator())
+    }
+
+    /// Encode k message bytes into an n-byte codeword
+    /// pad=True accepts shorter messages and zero-fills them to k; decode them with msg_len.
+    #[pyo3(signature = (data, pad=false))]
+    fn encode<'py>(&self, py: Python<'py>, data: BytesInput, pad: bool) -> PyResult<Bound<'py, PyBytes>> {
+        let k = self.codec.k();
+        if data.len() > k || (data.len() < k && !pad) {
+            let hint = if data.len() < k { "; pass pad=True to zero-fill short messages" } else { "" };
+            return Err(length_error(format!(
+                "message length {} does not match expected k={} for (n={}, nsym={}){}",
+                data.len(), k, self.codec.n(), self.codec.nsym(), hint
+            )));
+        }
+        // Written straight into the result bytes; the closure cannot fail, so no half-built object escapes
+        let codeword = PyBytes::new_with(py, self.codec.n(), |codeword| {
+            data.with_slice(py, |message| self.codec.encode_padded_into(message, codeword));
+            Ok(())
+        })?;
+        self.stats.record_encode(1);
+        Ok(codeword)
+    }
+
+    /// Decode an n-byte codeword, returning (message, corrected positions)
+    /// With `erase_pos` (known-bad byte positions) up to nsym erasures, or e errors plus h erasures
+    /// with 2e + h <= nsym, are corrected and the result is (message, positions, erased): `erased`
+    /// is the subset of corrected positions that were flagged as erasures.
+    /// With `msg_len` (for pad=True codewords) only the first msg_len message bytes are returned,
+    /// and the zero padding after them is restored without using correction capacity.
+    /// With `on_failure`, a failed decode calls `on_failure(codeword, error)`, which returns
+    /// candidate erasure positions (or None to give up); each candidate list is added to erase_pos
+    /// and decoded again, up to MAX_HINT_RETRIES times. If every retry fails the original
+    /// exception is raised, with a note recording the hints that were tried.
+    /// With `allow_truncated=True` a codeword cut short by up to nsym bytes is accepted: the
+    /// missing tail is zero-filled and erased (and listed in `erased` when erase_pos is given).
+    /// More than nsym missing raises UncorrectableError with detected_errors the missing count.
+    /// With `pinned` (byte positions known to be correct, e.g. a header checked separately) a
+    /// decode that would change any of them raises PinnedPositionConflict, whose `positions`
+    /// lists them: errors located there mean the decoder has landed on the wrong codeword.
+    #[pyo3(signature = (codeword, erase_pos=None, msg_len=None, on_failure=None, allow_truncated=false, pinned=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn decode<'py>(
+        &self,
+        py: Python<'py>,
+        codeword: BytesInput,
+        erase_pos: Option<&Bound<'py, PyAny>>,
+        msg_len: Option<i64>,
+        on_failure: Option<&Bound<'py, PyAny>>,
+        allow_truncated: bool,
+        pinned: Option<&Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyTuple>> {
+        let pinned = extract_pinned(pinned, self.codec.n())?;
+        let truncated = allow_truncated && codeword.len() < self.codec.n();
+        if !truncated {
+            self.check_codeword_len(codeword.len())?;
+        }
+        let mut erasures = match erase_pos {
+            None => None,
+            Some(ob) => Some(extract_positions(ob, self.codec.n())?),
+        };
+        let report_erased = erasures.is_some();
+        let codeword = if truncated {
+            let (padded, filled) = pad_truncated(codeword.as_slice(), self.codec.nsym(), self.codec.n())
+                .map_err(|e| codec_decode_error(py, e, &self.codec, codeword.as_slice(), ""))?;
+            let mut all = erasures.unwrap_or_default();
+            all.extend(filled.into_iter().filter(|p| !all.contains(p)).collect::<Vec<_>>());
+            erasures = Some(all);
+            BytesInput { source: Source::Owned(padded) }
+        } else {
+            codeword
+        };
+        let msg_len = self.check_msg_len(msg_len)?;
+        let mut result = if erasures.is_none() && msg_len.is_none() && pinned.is_empty() {
+            // The common case decodes straight into the result bytes; if it fails they are
+            // dropped here, never having been handed to Python
+            let mut outcome = Err(RsError::TooManyErrors);
+            let message = PyBytes::new_with(py, codeword.len() - self.codec.nsym(), |message| {
+                outcome = codeword.with_slice(py, |cw| self.codec.decode_to(cw, message));
+                Ok(())
+            })?;
+            match outcome {
+                Ok(positions) => {
+                    self.stats.record_success(positions.len());
+                    return (message, positions).into_pyobject(py);
+                }
+                Err(e) => Err(e),
+            }
+        } else {
+            self.decode_with(py, &codeword, erasures.as_deref(), msg_len, &pinned)
+        };
+        if let (Err(e), Some(on_failure)) = (&result, on_failure) {
+            let original = codec_decode_error(py, *e, &self.codec, codeword.as_slice(), "");
+            match self.retry_with_hints(py, &codeword, erasures.as_deref(), msg_len, &pinned, on_failure, &original)? {
+                Some((hinted, retried)) => {
+                    erasures = erasures.map(|_| hinted);
+                    result = Ok(retried);
+                }
+                None => {
+                    self.stats.record_decode(&result);
+                    return Err(original);
+                }
+            }
+        }
+        self.stats.record_decode(&result);
+        let (decoded, positions) =
+            result.map_err(|e| codec_decode_error(py, e, &self.codec, codeword.as_slice(), ""))?;
+        let message = PyBytes::new(py, &decoded);
+        match erasures.filter(|_| report_erased) {
+            None => (message, positions).into_pyobject(py),
+            Some(erasures) => {
+                let erased: Vec<usize> = positions.iter().copied().filter(|p| erasures.contains(p)).collect();
+                (message, positions, erased).into_pyobject(py)
+            }
+        }
+    }
+
+    /// Decode returning a DecodeResult with magnitudes and the corrected codeword
+    /// With `erase_pos` the result also splits the corrections into erasure_positions (hinted)
+    /// and error_positions (found by the decoder), and lists unused_erasures.
+    /// With `msg_len` (for pad=True codewords) the message is its first msg_len bytes, and
+    /// `locations` reports corrections in the zero padding after it as message_index None.
+    #[pyo3(signature = (codeword, erase_pos=None, msg_len=None))]
+    fn decode_detailed(&self, py: Python<'_>, codeword: BytesInput, erase_pos: Option<&Bound<'_, PyAny>>, msg_len: Option<i64>) -> PyResult<PyDecodeResult> {
+        self.check_codeword_len(codeword.len())?;
+        let mut erasures = match erase_pos {
+            None => vec![],
+            Some(ob) => extract_positions(ob, self.codec.n())?,
+        };
+        let msg_len = self.check_msg_len(msg_len)?;
+        let result = codeword.with_slice(py, |cw| {
+            let result = match msg_len {
+                Some(msg_len) => self.codec.decode_padded(cw, &erasures, msg_len),
+                None => self.codec.decode_errata(cw, &erasures),
+            };
+            self.stats.record_decode(&result);
+            result.map(|(message, positions)| {
+                // Systematic code: re-encoding the message gives the corrected codeword
+                let fixed = (!positions.is_empty()).then(|| self.codec.encode_padded(&message));
+                let magnitudes = match &fixed {
+                    Some(fixed) => positions.iter().map(|&p| cw[p] ^ fixed[p]).collect(),
+                    None => vec![],
+                };
+                (message, positions, magnitudes, fixed)
+            })
+        });
+        let (message, positions, magnitudes, codeword) =
+            result.map_err(|e| codec_decode_error(py, e, &self.codec, codeword.as_slice(), ""))?;
+        erasures.sort_unstable();
+        Ok(PyDecodeResult { message, positions, magnitudes, codeword, erasures, n: self.codec.n(), nsym: self.codec.nsym(), msg_len })
+    }
+
+    /// True if the codeword is valid (all syndromes zero)
+    fn check(&self, py: Python<'_>, codeword: BytesInput) -> PyResult<bool> {
+        self.check_codeword_len(codeword.len())?;
+        Ok(codeword.with_slice(py, |cw| self.codec.check(cw)))
+    }
+
+    /// The nsym syndrome bytes S_j = r(alpha^j), j = 0..nsym-1; all zero for a valid codeword
+    fn syndromes<'py>(&self, py: Python<'py>, codeword: BytesInput) -> PyResult<Bound<'py, PyBytes>> {
+        self.check_codeword_len(codeword.len())?;
+        let syndromes = codeword.with_slice(py, |cw| self.codec.syndromes(cw));
+        Ok(PyBytes::new(py, &syndromes))
+    }
+
+    /// Number of nonzero syndromes
+    fn syndrome_weight(&self, py: Python<'_>, codeword: BytesInput) -> PyResult<usize> {
+        Ok(self.syndromes(py, codeword)?.as_bytes().iter().filter(|&&s| s != 0).count())
+    }
+
+    /// Scan concatenated codewords and return the indices of blocks with nonzero syndromes
+    /// block_len may be any length in nsym+1..=255 (shortened blocks); the buffer must hold a
+    /// whole number of blocks. The GIL is released for read-only buffers.
+    fn check_many(&self, py: Python<'_>, buffer: BytesInput, block_len: usize) -> PyResult<Vec<usize>> {
+        if block_len <= self.codec.nsym() || block_len > 255 {
+            return Err(parameter_error(format!(
+                "block_len {} must be in nsym+1..=255 for nsym={}",
+                block_len, self.codec.nsym()
+            )));
+        }
+        if !buffer.len().is_multiple_of(block_len) {
+            return Err(length_error(format!(
+                "buffer length {} is not a multiple of block_len={} ({} trailing bytes)",
+                buffer.len(), block_len, buffer.len() % block_len
+            )));
+        }
+        Ok(buffer.with_slice(py, |data| self.codec.check_many(data, block_len)))
+    }
+
+    /// Encode data of any length as consecutive codewords, k message bytes each
+    /// The last chunk may be short and becomes a shortened codeword (len + nsym bytes), as in
+    /// reedsolo's RSCodec.encode.
+    fn encode_chunked<'py>(&self, py: Python<'py>, data: BytesInput) -> Bound<'py, PyBytes> {
+        let out = data.with_slice(py, |d| self.codec.encode_chunked(d));
+        self.stats.record_encode(data.len().div_ceil(self.codec.k()));
+        PyBytes::new(py, &out)
+    }
+
+    /// Decode an encode_chunked stream into (message, corrected stream, errata positions)
+    /// `erase_pos` and the returned positions are offsets into the whole stream.
+    #[pyo3(signature = (data, erase_pos=None))]
+    fn decode_chunked<'py>(
+        &self,
+        py: Python<'py>,
+        data: BytesInput,
+        erase_pos: Option<&Bound<'py, PyAny>>,
+    ) -> PyResult<(Bound<'py, PyBytes>, Bound<'py, PyBytes>, Vec<usize>)> {
+        let erasures = match erase_pos {
+            None => vec![],
+            Some(ob) => extract_positions(ob, data.len())?,
+        };
+        let n = self.codec.n();
+        let out = data.with_slice(py, |d| self.codec.decode_chunked_observed(d, &erasures, |result| self.stats.record_decode(result))).map_err(|(i, e)| {
+            let chunk = data.as_slice().chunks(n).nth(i).unwrap_or_default();
+            codec_decode_error(py, e, &self.codec, chunk, &format!("chunk {}: ", i))
+        })?;
+        Ok((PyBytes::new(py, &out.message), PyBytes::new(py, &out.codeword), out.errata))
+    }
+
+    /// Per-chunk validity of an encode_chunked stream
+    fn check_chunked(&self, py: Python<'_>, data: BytesInput) -> Vec<bool> {
+        data.with_slice(py, |d| self.codec.check_chunked(d))
+    }
+
+    /// Counters since construction or the last reset_stats(), for single-block encode/decode,
+    /// decode_detailed and every chunk of encode_chunked/decode_chunked: blocks_encoded,
+    /// blocks_decoded (failures included), blocks_corrected, symbols_corrected and decode_failures
+    fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.stats.to_dict(py)
+    }
+
+    fn reset_stats(&self) {
+        self.stats.reset();
+    }
+
+    /// Pickle as the constructor arguments; the generator is rebuilt on load (counters start at zero)
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, CtorArgs)> {
+        let codec = &slf.get().codec;
+        let options = codec.options();
+        let max_correct = codec.decode_options().max_correct;
+        Ok((slf.get_type().into_any(), (codec.nsym(), codec.n(), options.fcr, options.generator, options.prim, max_correct)))
+    }
+
+    /// A new codec with the same parameters and its own counters, starting at zero as after
+    /// unpickling, so resetting a copy's stats leaves the original's alone
+    fn __copy__(&self) -> Self {
+        Self { codec: self.codec.clone(), stats: Default::default() }
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.__copy__()
+    }
+
+    fn __repr__(&self) -> String {
+        let options = self.codec.options();
+        let max_correct = match self.codec.decode_options().max_correct {
+            Some(m) => format!(", max_correct={}", m),
+            None => String::new(),
+        };
+        if options == CodeOptions::default() {
+            return format!("RSCodec(nsym={}, nsize={}{})", self.codec.nsym(), self.codec.n(), max_correct);
+        }
+        format!(
+            "RSCodec(nsym={}, nsize={}, fcr={}, generator={}, prim={:#x}{})",
+            self.codec.nsym(), self.codec.n(), options.fcr, options.generator, options.prim, max_correct
+        )
+    }
+}
+
+/// Arguments of the module-level encode/decode after resolving the two calling conventions:
+/// the legacy positional `(nsym, nsize, data[, erase_pos])`, recognised by an int first argument,
+/// and data-first `(data, nsym=..., nsize=None)`. A slot filled both ways is a TypeError.
+struct CallArgs<'py> {
+    data: Bound<'py, PyAny>,
+    nsym: usize,
+    nsize: Option<usize>,
+    erase_pos: Option<Bound<'py, PyAny>>,
+}
+
+fn resolve_call_args<'py>(
+    func: &str,
+    data_name: &str,
+    args: &Bound<'py, PyTuple>,
+    keywords: [Option<Bound<'py, PyAny>>; 4],
+) -> PyResult<CallArgs<'py>> {
+    // Slots in keyword order: data, nsym, nsize, erase_pos
+    let legacy = args.len() > 0 && args.get_item(0)?.is_instance_of::<pyo3::types::PyInt>();
+    let order: &[usize] = if legacy { &[1, 2, 0, 3] } else { &[0, 1, 2] };
+    let names = [data_name, "nsym", "nsize", "erase_pos"];
+    if args.len() > order.len() {
+        return Err(PyTypeError::new_err(format!(
+            "{}() takes at most {} positional arguments ({} given)",
+            func, order.len(), args.len()
+        )));
+    }
+    let mut slots = keywords;
+    for (arg, &slot) in args.iter().zip(order) {
+        if slots[slot].is_some() {
+            return Err(PyTypeError::new_err(format!("{}() got multiple values for argument '{}'", func, names[slot])));
+        }
+        slots[slot] = Some(arg);
+    }
+    let [data, nsym, nsize, erase_pos] = slots;
+    let missing = |name: &str| PyTypeError::new_err(format!("{}() missing required argument '{}'", func, name));
+    Ok(CallArgs {
+        data: data.ok_or_else(|| missing(data_name))?,
+        nsym: nsym.ok_or_else(|| missing("nsym"))?.extract()?,
+        nsize: nsize.map(|n| n.extract()).transpose()?,
+        erase_pos: erase_pos.filter(|e| !e.is_none()),
+    })
+}
+
+/// Encode a message; nsize defaults to len(message) + nsym
+/// Accepts `encode(message, nsym=..., nsize=None)` and the legacy `encode(nsym, nsize, message)`.
+/// pad=True zero-fills a message shorter than k = nsize - nsym; decode it with msg_len.
+#[pyfunction]
+#[pyo3(signature = (*args, nsym=None, nsize=None, message=None, pad=false))]
+fn encode<'py>(
+    py: Python<'py>,
+    args: &Bound<'py, PyTuple>,
+    nsym: Option<Bound<'py, PyAny>>,
+    nsize: Option<Bound<'py, PyAny>>,
+    message: Option<Bound<'py, PyAny>>,
+    pad: bool,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let call = resolve_call_args("encode", "message", args, [message, nsym, nsize, None])?;
+    let message: BytesInput = call.data.extract()?;
+    let nsize = match call.nsize {
+        Some(nsize) => nsize,
+        None => {
+            let inferred = message.len() + call.nsym;
+            validate_params(call.nsym, inferred).map_err(|e| {
+                parameter_error(format!(
+                    "{} (nsym={}, nsize={} inferred from message length {} + nsym)",
+                    e, call.nsym, inferred, message.len()
+                ))
+            })?;
+            inferred
+        }
+    };
+    PyRsCodec::standard(call.nsym, nsize)?.encode(py, message, pad)
+}
+
+/// Decode a codeword; n is taken from len(codeword), and nsize, if given, must agree
+/// Accepts `decode(codeword, nsym=..., erase_pos=None)` and the legacy
+/// `decode(nsym, nsize, codeword, erase_pos=None)`. msg_len trims the message of a pad=True
+/// codeword and on_failure supplies erasure hints after a failed decode, as in RSCodec.decode;
+/// max_correct caps the corrections as RSCodec(max_correct=...) does. allow_truncated accepts a
+/// codeword shorter than nsize (which must then be given), and pinned rejects corrections at
+/// known-good positions, both as in RSCodec.decode.
+#[pyfunction]
+#[pyo3(signature = (*args, nsym=None, nsize=None, codeword=None, erase_pos=None, msg_len=None, on_failure=None, max_correct=None, allow_truncated=false, pinned=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode<'py>(
+    py: Python<'py>,
+    args: &Bound<'py, PyTuple>,
+    nsym: Option<Bound<'py, PyAny>>,
+    nsize: Option<Bound<'py, PyAny>>,
+    codeword: Option<Bound<'py, PyAny>>,
+    erase_pos: Option<Bound<'py, PyAny>>,
+    msg_len: Option<i64>,
+    on_failure: Option<Bound<'py, PyAny>>,
+    max_correct: Option<usize>,
+    allow_truncated: bool,
+    pinned: Option<Bound<'py, PyAny>>,
+) -> PyResult<Bound<'py, PyTuple>> {
+    let call = resolve_call_args("decode", "codeword", args, [codeword, nsym, nsize, erase_pos])?;
+    let codeword: BytesInput = call.data.extract()?;
+    let mut n = codeword.len();
+    match call.nsize {
+        Some(nsize) => {
+            check_params(call.nsym, nsize)?;
+            if allow_truncated && n < nsize {
+                // RSCodec.decode checks how much is missing
+                n = nsize;
+            } else if nsize != n {
+                return Err(length_error(format!(
+                    "codeword length {} does not match expected n={} (nsize={} given, n={} inferred from the codeword)",
+                    n, nsize, nsize, n
+                )));
+            }
+        }
+        None => validate_params(call.nsym, n).map_err(|e| {
+            parameter_error(format!("{} (nsym={}, nsize={} inferred from codeword length)", e, call.nsym, n))
+        })?,
+    }
+    let codec = PyRsCodec::standard(call.nsym, n)?.with_max_correct(max_correct)?;
+    codec.decode(py, codeword, call.erase_pos.as_ref(), msg_len, on_failure.as_ref(), allow_truncated, pinned.as_ref())
+}
+
+/// Encode `message` straight into `out[offset:offset + n]`, n = len(message) + nsym
+/// The message may overlap the output region (e.g. a frame encoded in place): it is fully
+/// read before anything is written. Returns n.
+#[pyfunction]
+#[pyo3(signature = (nsym, message, out, offset=0))]
+fn encode_into(py: Python<'_>, nsym: usize, message: BytesInput, mut out: BytesOutput, offset: usize) -> PyResult<usize> {
+    let n = message.len() + nsym;
+    check_params(nsym, n)?;
+    if offset > out.len() || out.len() - offset < n {
+        return Err(length_error(format!(
+            "output buffer of {} bytes has no room for n={} bytes at offset {}",
+            out.len(), n, offset
+        )));
+    }
+    let codec = RsCodec::new(nsym, n).expect("parameters validated");
+    // The codeword is built in its own Vec, so the read of `message` is over before `out` is touched
+    let codeword = codec.encode(message.as_slice());
+    out.write_at(py, offset, &codeword);
+    Ok(n)
+}
+
+/// Correct a writable buffer in place and return the corrected positions
+/// The buffer is one codeword, or with `block_len` a run of block_len-byte codewords whose
+/// positions come back as offsets into the buffer. Decoding runs without the GIL on a copy and
+/// writes nothing until every block has decoded, so on UncorrectableError the buffer is
+/// untouched; corrections are then XORed in, leaving other bytes as Python last wrote them.
+#[pyfunction]
+#[pyo3(signature = (nsym, buf, block_len=None))]
+fn decode_inplace(py: Python<'_>, nsym: usize, mut buf: BytesOutput, block_len: Option<usize>) -> PyResult<Vec<usize>> {
+    let n = match block_len {
+        Some(block_len) => {
+            check_params(nsym, block_len)?;
+            if !buf.len().is_multiple_of(block_len) {
+                return Err(length_error(format!(
+                    "buffer length {} is not a multiple of block_len={} ({} trailing bytes)",
+                    buf.len(), block_len, buf.len() % block_len
+                )));
+            }
+            block_len
+        }
+        None => {
+            check_nsym(nsym)?;
+            if buf.len() <= nsym || buf.len() > 255 {
+                return Err(length_error(format!(
+                    "codeword length {} must be in nsym+1..=255 for nsym={}; pass block_len for a run of codewords",
+                    buf.len(), nsym
+                )));
+            }
+            buf.len()
+        }
+    };
+    let codec = RsCodec::new(nsym, n).expect("parameters validated");
+    let snapshot = buf.as_slice().to_vec();
+    let fixes = py.allow_threads(|| codec.corrections_many(&snapshot)).map_err(|(i, e)| {
+        let context = if block_len.is_some() { format!("codeword {}: ", i) } else { String::new() };
+        decode_error(py, e, &snapshot[i * n..(i + 1) * n], nsym, &context)
+    })?;
+    for &(p, byte) in &fixes {
+        buf.xor_at(py, p, byte ^ snapshot[p]);
+    }
+    Ok(fixes.into_iter().map(|(p, _)| p).collect())
+}
+
+/// Codeword length must leave at least one data byte and fit GF(256), like decode
+fn check_syndrome_input(nsym: usize, codeword: &BytesInput) -> PyResult<()> {
+    if nsym == 0 {
+        return Err(parameter_error("nsym must be at least 1".into()));
+    }
+    if codeword.len() <= nsym || codeword.len() > 255 {
+        return Err(length_error(format!(
+            "codeword length {} must be in nsym+1..=255 for nsym={}",
+            codeword.len(), nsym
+        )));
+    }
+    Ok(())
+}
+
+/// Syndrome bytes S_j = r(alpha^j), j = 0..nsym-1 (same convention as the Rust calc_syndromes)
+#[pyfunction]
+fn syndromes<'py>(py: Python<'py>, nsym: usize, codeword: BytesInput) -> PyResult<Bound<'py, PyBytes>> {
+    check_syndrome_input(nsym, &codeword)?;
+    let syndromes = codeword.with_slice(py, |cw| calc_syndromes(cw, nsym));
+    Ok(PyBytes::new(py, &syndromes))
+}
+
+/// Number of nonzero syndromes, without running the decoder
+#[pyfunction]
+fn syndrome_weight(py: Python<'_>, nsym: usize, codeword: BytesInput) -> PyResult<usize> {
+    Ok(syndromes(py, nsym, codeword)?.as_bytes().iter().filter(|&&s| s != 0).count())
+}
+
+fn check_nsym(nsym: usize) -> PyResult<()> {
+    if nsym == 0 || nsym > 254 {
+        return Err(parameter_error(format!("nsym must be in 1..=254, got {}", nsym)));
+    }
+    Ok(())
+}
+
+/// Generator polynomial g(x) = prod_{i<nsym} (x - alpha^i) as nsym+1 coefficient bytes
+/// Index 0 is the constant term and index nsym the leading coefficient (always 1); field is
+/// GF(256) with primitive polynomial 0x11d, first consecutive root alpha^0.
+#[pyfunction]
+fn generator_poly(py: Python<'_>, nsym: usize) -> PyResult<Bound<'_, PyBytes>> {
+    check_nsym(nsym)?;
+    Ok(PyBytes::new(py, &build_generator(nsym)))
+}
+
+/// Roots of the generator polynomial, alpha^0 .. alpha^(nsym-1), as field elements
+#[pyfunction]
+fn generator_roots(nsym: usize) -> PyResult<Vec<u32>> {
+    check_nsym(nsym)?;
+    // u32 so Python sees list[int] rather than bytes
+    Ok(GF.exp[..nsym].iter().map(|&r| r as u32).collect())
+}
+
+/// Encode many messages in one call; lengths are checked up front and errors name the index
+/// parallel=True releases the GIL and fans the blocks out over the rayon pool
+#[pyfunction]
+#[pyo3(signature = (nsym, nsize, messages, parallel=false))]
+fn encode_batch<'py>(
+    py: Python<'py>,
+    nsym: usize,
+    nsize: usize,
+    messages: Vec<BytesInput>,
+    parallel: bool,
+) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+    check_batch_messages(nsym, nsize, &messages)?;
+    let codewords = with_slices(py, &messages, |slices| rs_encode_batch_with(slices, nsym, nsize, parallel));
+    Ok(codewords.iter().map(|c| PyBytes::new(py, c)).collect())
+}
+
+/// Parameters and every message length, before any block is encoded
+fn check_batch_messages(nsym: usize, nsize: usize, messages: &[BytesInput]) -> PyResult<()> {
+    check_params(nsym, nsize)?;
+    let k = nsize - nsym;
+    if let Some((i, m)) = messages.iter().enumerate().find(|(_, m)| m.len() != k) {
+        return Err(length_error(format!(
+            "message {}: length {} does not match expected k={} for (n={}, nsym={})",
+            i, m.len(), k, nsize, nsym
+        )));
+    }
+    Ok(())
+}
+
+/// Parameters and every codeword length, before any block is decoded
+fn check_batch_codewords(nsym: usize, nsize: usize, codewords: &[BytesInput]) -> PyResult<()> {
+    check_params(nsym, nsize)?;
+    if let Some((i, c)) = codewords.iter().enumerate().find(|(_, c)| c.len() != nsize) {
+        return Err(length_error(format!(
+            "codeword {}: length {} does not match expected n={}",
+            i, c.len(), nsize
+        )));
+    }
+    Ok(())
+}
+
+/// errors="raise" -> false, errors="report" -> true
+fn report_errors(errors: &str) -> PyResult<bool> {
+    match errors {
+        "raise" => Ok(false),
+        "report" => Ok(true),
+        _ => Err(parameter_error(format!("errors must be \"raise\" or \"report\", got {:?}", errors))),
+    }
+}
+
+/// Decode many codewords in one call; parallel=True fans the blocks out over the rayon pool
+/// Every block is decoded even when some fail. With errors="raise" the first failure is raised,
+/// carrying `results` (one (message, positions) per block, None where decoding failed) and
+/// `errors` ({index: exception}) so no other block's result is lost. errors="report" never
+/// raises for undecodable data: each failed block's entry is a BlockError instead. Lengths and
+/// parameters are checked, and raise, before any block is decoded in either mode.
+#[pyfunction]
+#[pyo3(signature = (nsym, nsize, codewords, parallel=false, errors="raise"))]
+fn decode_batch<'py>(
+    py: Python<'py>,
+    nsym: usize,
+    nsize: usize,
+    codewords: Vec<BytesInput>,
+    parallel: bool,
+    errors: &str,
+) -> PyResult<Vec<Bound<'py, PyAny>>> {
+    let report = report_errors(errors)?;
+    check_batch_codewords(nsym, nsize, &codewords)?;
+    let results = with_slices(py, &codewords, |slices| rs_decode_batch_with(slices, nsym, parallel));
+    let slices: Vec<&[u8]> = codewords.iter().map(|c| c.as_slice()).collect();
+    batch_decode_output(py, &slices, results, nsym, report)
+}
+
+/// decode_batch's return value (or raised failure) for the per-block results
+fn batch_decode_output<'py>(
+    py: Python<'py>,
+    codewords: &[&[u8]],
+    results: Vec<BlockResult>,
+    nsym: usize,
+    report: bool,
+) -> PyResult<Vec<Bound<'py, PyAny>>> {
+    collect_batch_output(py, results, report, |message| PyBytes::new(py, &message).into_any(), |i, e| {
+        decode_error(py, e, codewords[i], nsym, &format!("codeword {}: ", i))
+    })
+}
+
+/// batch_decode_output for any symbol type: `message` converts a decoded message to Python and
+/// `error` maps block i's failure to its exception
+fn collect_batch_output<'py, M>(
+    py: Python<'py>,
+    results: Vec<Result<(M, Vec<usize>), RsError>>,
+    report: bool,
+    message: impl Fn(M) -> Bound<'py, PyAny>,
+    error: impl Fn(usize, RsError) -> PyErr,
+) -> PyResult<Vec<Bound<'py, PyAny>>> {
+    let mut decoded = Vec::with_capacity(results.len());
+    let mut failures = vec![];
+    for (i, result) in results.into_iter().enumerate() {
+        match result {
+            Ok((decoded_message, positions)) => decoded.push(Some((message(decoded_message), positions).into_pyobject(py)?.into_any())),
+            Err(e) => {
+                let err = error(i, e);
+                if report {
+                    decoded.push(Some(Bound::new(py, errors::PyBlockError::new(py, i, err))?.into_any()));
+                } else {
+                    failures.push((i, err));
+                    decoded.push(None);
+                }
+            }
+        }
+    }
+    let Some((_, first)) = failures.first() else {
+        return Ok(decoded.into_iter().flatten().collect());
+    };
+    let exc = first.value(py).clone();
+    let errors = PyDict::new(py);
+    for (i, err) in &failures {
+        errors.set_item(i, err.value(py))?;
+    }
+    exc.setattr("results", decoded)?;
+    exc.setattr("errors", errors)?;
+    Err(PyErr::from_value(exc.into_any()))
+}
+
+fn crc_from_bits(crc_bits: u32) -> PyResult<Crc> {
+    match crc_bits {
+        16 => Ok(Crc::Crc16),
+        32 => Ok(Crc::Crc32),
+        _ => Err(parameter_error(format!("crc_bits must be 16 or 32, got {}", crc_bits))),
+    }
+}
+
+/// Message bytes left in an (nsize, nsym) codeword once the CRC is in it; a ParameterError when
+/// nsym parity bytes and the CRC do not fit
+fn crc_message_len(nsym: usize, nsize: usize, crc: Crc) -> PyResult<usize> {
+    check_params(nsym, nsize)?;
+    nsize.checked_sub(nsym + crc.size()).ok_or_else(|| {
+        parameter_error(format!("nsize={} has no room for nsym={} and a {}-byte CRC (nsize >= {} needed)", nsize, nsym, crc.size(), nsym + crc.size()))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (nsym, nsize, message, crc_bits=32))]
+fn encode_crc<'py>(py: Python<'py>, nsym: usize, nsize: usize, message: BytesInput, crc_bits: u32) -> PyResult<Bound<'py, PyBytes>> {
+    let crc = crc_from_bits(crc_bits)?;
+    let k = crc_message_len(nsym, nsize, crc)?;
+    if message.len() != k {
+        return Err(length_error(format!(
+            "message length {} does not match expected k={} for (n={}, nsym={}, crc={} bytes)",
+            message.len(), k, nsize, nsym, crc.size()
+        )));
+    }
+
+    let codeword = message.with_slice(py, |m| rs_encode_crc(m, nsym, crc));
+    Ok(PyBytes::new(py, &codeword))
+}
+
+#[pyfunction]
+#[pyo3(signature = (nsym, nsize, codeword, crc_bits=32))]
+fn decode_crc<'py>(py: Python<'py>, nsym: usize, nsize: usize, codeword: BytesInput, crc_bits: u32) -> PyResult<(Bound<'py, PyBytes>, Vec<usize>)> {
+    let crc = crc_from_bits(crc_bits)?;
+    crc_message_len(nsym, nsize, crc)?;
+    if codeword.len() != nsize {
+        return Err(length_error(format!(
+            "codeword length {} does not match expected n={}",
+            codeword.len(), nsize
+        )));
+    }
+
+    match codeword.with_slice(py, |cw| rs_decode_crc(cw, nsym, crc)) {
+        Ok((decoded, positions)) => Ok((PyBytes::new(py, &decoded), positions)),
+        Err(e) => Err(decode_error(py, e, codeword.as_slice(), nsym, "")),
+    }
+}
+
+/// Reproducible corruption for tests: a `burst_len` run plus `n_errors` scattered errors, all
+/// at distinct positions, and `n_erasures` flagged positions. Returns (corrupted, positions)
+/// or, with erasures, (corrupted, positions, erased) like decode with erase_pos. `positions`
+/// lists every byte that differs; `erased` is the flagged subset, ready to pass as erase_pos.
+/// The same seed gives the same output on every platform; seed=None draws one from the clock.
+#[cfg(feature = "sim")]
+#[pyfunction]
+#[pyo3(signature = (codeword, n_errors=0, n_erasures=0, burst_len=0, seed=None))]
+fn corrupt<'py>(
+    py: Python<'py>,
+    codeword: BytesInput,
+    n_errors: usize,
+    n_erasures: usize,
+    burst_len: usize,
+    seed: Option<u64>,
+) -> PyResult<Bound<'py, PyTuple>> {
+    use crate::rs::sim::{corrupt, Rng};
+
+    let total = n_errors + n_erasures + burst_len;
+    if total > codeword.len() {
+        return Err(parameter_error(format!(
+            "cannot corrupt {} positions (n_errors={}, n_erasures={}, burst_len={}) of a {}-byte codeword",
+            total, n_errors, n_erasures, burst_len, codeword.len()
+        )));
+    }
+    let seed = seed.unwrap_or_else(|| {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+        now.as_nanos() as u64
+    });
+    let mut corrupted = codeword.as_slice().to_vec();
+    let record = corrupt(&mut corrupted, n_errors, n_erasures, burst_len, &mut Rng::new(seed));
+    let mut positions: Vec<usize> = record.positions().into_iter().chain(record.erasures.iter().copied()).collect();
+    positions.sort_unstable();
+    let corrupted = PyBytes::new(py, &corrupted);
+    if n_erasures == 0 {
+        (corrupted, positions).into_pyobject(py)
+    } else {
+        (corrupted, positions, record.erasures).into_pyobject(py)
+    }
+}
+
+/// Build information: crate version, field, enabled features and the active GF kernel
+#[pyfunction]
+fn info(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    let features: Vec<&str> = [
+        ("sim", cfg!(feature = "sim")),
+        ("parallel", cfg!(feature = "parallel")),
+        ("numpy", cfg!(feature = "numpy")),
+    ]
+    .into_iter()
+    .filter_map(|(name, on)| on.then_some(name))
+    .collect();
+    let dict = PyDict::new(py);
+    dict.set_item("version", env!("CARGO_PKG_VERSION"))?;
+    dict.set_item("field", "GF(2^8)")?;
+    dict.set_item("field_polynomial", PRIM_POLY)?;
+    dict.set_item("simd_kernels", SIMD_KERNELS.to_vec())?;
+    dict.set_item("active_kernel", active_kernel())?;
+    dict.set_item("features", features)?;
+    Ok(dict)
+}
+
+#[pymodule(gil_used = false)]
+fn _rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    errors::register(m)?;
+    gf::register(m)?;
+    #[cfg(feature = "numpy")]
+    raid::register(m)?;
+    m.add_class::<PyRsCodec>()?;
+    m.add_class::<PyDecodeResult>()?;
+    m.add_class::<errors::PyBlockError>()?;
+    m.add_class::<blocks::PyBlockStatus>()?;
+    m.add_class::<detached::PyRepairInfo>()?;
+    m.add_class::<file::PyRepairReport>()?;
+    m.add_class::<interleave::PyInterleaver>()?;
+    #[cfg(feature = "sim")]
+    m.add_class::<memory::PyMemoryModel>()?;
+    m.add_class::<plan::PyPlanResult>()?;
+    m.add_class::<pool::PyPool>()?;
+    m.add_class::<pool::PyJob>()?;
+    m.add_class::<shard::PyShardCodec>()?;
+    m.add_class::<stream::PyStreamEncoder>()?;
+    m.add_class::<stream::PyStreamDecoder>()?;
+    #[cfg(feature = "numpy")]
+    {
+        m.add_class::<wide::PyRsCodec16>()?;
+        m.add_class::<wide::PyDecodeResult16>()?;
+    }
+    m.add("MAX_HINT_RETRIES", MAX_HINT_RETRIES)?;
+    m.add_function(wrap_pyfunction!(info, m)?)?;
+    m.add_function(wrap_pyfunction!(encode, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_into, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_inplace, m)?)?;
+    m.add_function(wrap_pyfunction!(detached::repair_detached, m)?)?;
+    m.add_function(wrap_pyfunction!(decode, m)?)?;
+    m.add_function(wrap_pyfunction!(generator_poly, m)?)?;
+    m.add_function(wrap_pyfunction!(generator_roots, m)?)?;
+    m.add_function(wrap_pyfunction!(syndromes, m)?)?;
+    m.add_function(wrap_pyfunction!(syndrome_weight, m)?)?;
+    m.add_function(wrap_pyfunction!(stages::berlekamp_massey, m)?)?;
+    m.add_function(wrap_pyfunction!(stages::chien_search, m)?)?;
+    m.add_function(wrap_pyfunction!(stages::forney, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(blocks::encode_blocks, m)?)?;
+    m.add_function(wrap_pyfunction!(blocks::decode_blocks, m)?)?;
+    #[cfg(feature = "sim")]
+    m.add_function(wrap_pyfunction!(corrupt, m)?)?;
+    #[cfg(feature = "sim")]
+    m.add_function(wrap_pyfunction!(sim::simulate, m)?)?;
+    #[cfg(feature = "numpy")]
+    {
+        m.add_function(wrap_pyfunction!(array::encode_array, m)?)?;
+        m.add_function(wrap_pyfunction!(array::decode_array, m)?)?;
+    }
+    m.add_function(wrap_pyfunction!(interleave::interleave, m)?)?;
+    m.add_function(wrap_pyfunction!(interleave::deinterleave, m)?)?;
+    m.add_function(wrap_pyfunction!(interleave::encode_interleaved, m)?)?;
+    m.add_function(wrap_pyfunction!(interleave::decode_interleaved, m)?)?;
+    m.add_function(wrap_pyfunction!(file::encode_file, m)?)?;
+    m.add_function(wrap_pyfunction!(file::decode_file, m)?)?;
+    m.add_function(wrap_pyfunction!(file::repair_file, m)?)?;
+    m.add_function(wrap_pyfunction!(plan::plan, m)?)?;
+    m.add_function(wrap_pyfunction!(plan::block_failure_rate, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_crc, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_crc, m)?)?;
+    m.add_function(wrap_pyfunction!(_buffer_address, m)?)?;
+    Ok(())
+}
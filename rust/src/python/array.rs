@@ -0,0 +1,67 @@
+// NumPy 2D block API: one row per block, dtype uint8, C-contiguous
+
+use numpy::ndarray::{Array1, Array2};
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyArrayMethods, PyUntypedArrayMethods};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+
+use super::check_params;
+use crate::rs::{decode_batch, encode_batch};
+
+/// (decoded messages, per-block status)
+type DecodedArrays<'py> = (Bound<'py, PyArray2<u8>>, Bound<'py, PyArray1<i32>>);
+
+/// Borrow a 2D C-contiguous uint8 array as (rows, cols, flat data)
+fn with_rows<R>(arr: &Bound<'_, PyAny>, f: impl FnOnce(usize, usize, &[u8]) -> PyResult<R>) -> PyResult<R> {
+    let arr = arr.downcast::<PyArray2<u8>>().map_err(|_| {
+        PyTypeError::new_err("expected a 2D numpy array of dtype uint8 with shape (num_blocks, block_len)")
+    })?;
+    if !arr.is_c_contiguous() {
+        return Err(PyTypeError::new_err("array must be C-contiguous; pass numpy.ascontiguousarray(arr)"));
+    }
+    let readonly = arr.readonly();
+    let (rows, cols) = (arr.shape()[0], arr.shape()[1]);
+    let data = readonly.as_slice().map_err(|e| PyTypeError::new_err(e.to_string()))?;
+    f(rows, cols, data)
+}
+
+/// Encode a (num_blocks, k) array into (num_blocks, n) with n = k + nsym
+#[pyfunction]
+pub fn encode_array<'py>(py: Python<'py>, nsym: usize, arr: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyArray2<u8>>> {
+    with_rows(arr, |rows, k, data| {
+        let n = k + nsym;
+        check_params(nsym, n)?;
+        let messages: Vec<&[u8]> = data.chunks(k).collect();
+        let flat: Vec<u8> = encode_batch(&messages, nsym, n).concat();
+        let out = Array2::from_shape_vec((rows, n), flat).expect("rows * n codeword bytes");
+        Ok(out.into_pyarray(py))
+    })
+}
+
+/// Decode a (num_blocks, n) array
+/// Returns (decoded (num_blocks, k), status (num_blocks,) int32) where status is the number of
+/// corrected symbols, or -1 if the block was uncorrectable; failed rows carry the received data bytes.
+#[pyfunction]
+pub fn decode_array<'py>(py: Python<'py>, nsym: usize, arr: &Bound<'py, PyAny>) -> PyResult<DecodedArrays<'py>> {
+    with_rows(arr, |rows, n, data| {
+        check_params(nsym, n)?;
+        let k = n - nsym;
+        let codewords: Vec<&[u8]> = data.chunks(n).collect();
+        let mut decoded = Vec::with_capacity(rows * k);
+        let mut status = Vec::with_capacity(rows);
+        for (codeword, result) in codewords.iter().zip(decode_batch(&codewords, nsym)) {
+            match result {
+                Ok((message, positions)) => {
+                    decoded.extend_from_slice(&message);
+                    status.push(positions.len() as i32);
+                }
+                Err(_) => {
+                    decoded.extend_from_slice(&codeword[..k]);
+                    status.push(-1);
+                }
+            }
+        }
+        let decoded = Array2::from_shape_vec((rows, k), decoded).expect("rows * k message bytes");
+        Ok((decoded.into_pyarray(py), Array1::from(status).into_pyarray(py)))
+    })
+}
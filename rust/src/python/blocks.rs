@@ -0,0 +1,99 @@
+// Contiguous-buffer batch API: many blocks back to back in one buffer, one bytes object out
+// Codewords are written straight into the result bytes, so the only per-block Python objects
+// are decode_blocks' BlockStatus entries.
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use super::errors::length_error;
+use super::{check_params, BytesInput};
+use crate::rs::{decode_blocks_into, encode_blocks_into};
+
+/// Outcome of one block of decode_blocks
+#[pyclass(name = "BlockStatus", module = "ecc_model._rs", frozen, eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PyBlockStatus {
+    #[pyo3(get)]
+    index: usize,
+    /// Corrected positions within the block
+    #[pyo3(get)]
+    positions: Vec<usize>,
+    /// Why the block could not be decoded, None if it was
+    #[pyo3(get)]
+    error: Option<String>,
+}
+
+#[pymethods]
+impl PyBlockStatus {
+    #[getter]
+    fn ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    #[getter]
+    fn num_corrected(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.error {
+            None => format!("BlockStatus(index={}, positions={:?})", self.index, self.positions),
+            Some(error) => format!("BlockStatus(index={}, error={:?})", self.index, error),
+        }
+    }
+}
+
+fn check_multiple(what: &str, len: usize, unit: &str, block: usize) -> PyResult<()> {
+    if !len.is_multiple_of(block) {
+        return Err(length_error(format!(
+            "{} length {} is not a multiple of {}={} ({} trailing bytes)",
+            what, len, unit, block, len % block
+        )));
+    }
+    Ok(())
+}
+
+/// Encode back-to-back k-byte messages into back-to-back codewords
+/// parallel=True fans the blocks out over the rayon pool.
+#[pyfunction]
+#[pyo3(signature = (nsym, nsize, data, parallel=false))]
+pub fn encode_blocks<'py>(py: Python<'py>, nsym: usize, nsize: usize, data: BytesInput, parallel: bool) -> PyResult<Bound<'py, PyBytes>> {
+    check_params(nsym, nsize)?;
+    let k = nsize - nsym;
+    check_multiple("data", data.len(), "k", k)?;
+    PyBytes::new_with(py, data.len() / k * nsize, |out| {
+        data.with_slice(py, |d| encode_blocks_into(d, out, nsym, nsize, parallel));
+        Ok(())
+    })
+}
+
+/// Decode back-to-back codewords into (back-to-back messages, [BlockStatus per block])
+/// Failed blocks do not raise: their status carries the error and their message slot holds the
+/// received data bytes.
+#[pyfunction]
+#[pyo3(signature = (nsym, nsize, data, parallel=false))]
+pub fn decode_blocks<'py>(
+    py: Python<'py>,
+    nsym: usize,
+    nsize: usize,
+    data: BytesInput,
+    parallel: bool,
+) -> PyResult<(Bound<'py, PyBytes>, Vec<PyBlockStatus>)> {
+    check_params(nsym, nsize)?;
+    check_multiple("data", data.len(), "n", nsize)?;
+    let k = nsize - nsym;
+    let mut results = vec![];
+    let messages = PyBytes::new_with(py, data.len() / nsize * k, |out| {
+        results = data.with_slice(py, |d| decode_blocks_into(d, out, nsym, nsize, parallel));
+        Ok(())
+    })?;
+    let status = results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| match result {
+            Ok(positions) => PyBlockStatus { index, positions, error: None },
+            Err(e) => PyBlockStatus { index, positions: vec![], error: Some(e.to_string()) },
+        })
+        .collect();
+    Ok((messages, status))
+}
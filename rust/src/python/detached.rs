@@ -0,0 +1,94 @@
+// repair_detached(): correct a data buffer against parity kept in a separate buffer
+// The logical codeword is data || parity; rs::detached decodes it across the two slices, so
+// callers with page and parity files never build the n-byte concatenation.
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use super::errors::{length_error, syndrome_decode_error};
+use super::{check_nsym, BytesInput, BytesOutput};
+use crate::rs::{corrections_split, syndromes_split};
+
+/// Outcome of repair_detached
+/// Positions are offsets into their own buffer. `parity` holds the corrected parity bytes when
+/// return_parity=True was passed, else None.
+#[pyclass(name = "RepairInfo", module = "ecc_model._rs", frozen, eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PyRepairInfo {
+    #[pyo3(get)]
+    data_positions: Vec<usize>,
+    #[pyo3(get)]
+    parity_positions: Vec<usize>,
+    parity: Option<Vec<u8>>,
+}
+
+#[pymethods]
+impl PyRepairInfo {
+    #[getter]
+    fn parity<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyBytes>> {
+        self.parity.as_ref().map(|p| PyBytes::new(py, p))
+    }
+
+    /// True when the parity buffer was damaged and should be rewritten
+    #[getter]
+    fn parity_corrected(&self) -> bool {
+        !self.parity_positions.is_empty()
+    }
+
+    #[getter]
+    fn num_corrected(&self) -> usize {
+        self.data_positions.len() + self.parity_positions.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RepairInfo(data_positions={:?}, parity_positions={:?})",
+            self.data_positions, self.parity_positions
+        )
+    }
+}
+
+/// Correct `data` in place using its nsym parity bytes stored separately in `parity`
+/// The codeword is data || parity (len(data) + nsym <= 255). Corrections that fall in the
+/// parity are reported, and with return_parity=True the corrected parity comes back in
+/// RepairInfo.parity; the parity buffer itself is never written. On UncorrectableError
+/// neither buffer is modified.
+#[pyfunction]
+#[pyo3(signature = (nsym, data, parity, return_parity=false))]
+pub fn repair_detached(
+    py: Python<'_>,
+    nsym: usize,
+    mut data: BytesOutput,
+    parity: BytesInput,
+    return_parity: bool,
+) -> PyResult<PyRepairInfo> {
+    check_nsym(nsym)?;
+    if parity.len() != nsym {
+        return Err(length_error(format!("parity length {} does not match nsym={}", parity.len(), nsym)));
+    }
+    let k = data.len();
+    if k == 0 || k + nsym > 255 {
+        return Err(length_error(format!("data length {} must be in 1..={} for nsym={}", k, 255 - nsym, nsym)));
+    }
+    // Both buffers are copied (parity unless exact bytes) before the GIL is released, and the
+    // corrections go back into `data` as XOR deltas with it held again (see BytesOutput)
+    let (data_bytes, parity_bytes) = (data.as_slice().to_vec(), parity.snapshot());
+    let fixes = py
+        .allow_threads(|| corrections_split(&data_bytes, &parity_bytes))
+        .map_err(|e| syndrome_decode_error(py, e, &syndromes_split(&data_bytes, &parity_bytes, nsym), ""))?;
+
+    let mut corrected_parity = return_parity.then(|| parity_bytes.to_vec());
+    let (mut data_positions, mut parity_positions) = (vec![], vec![]);
+    for (pos, mag) in fixes {
+        if pos < k {
+            data.xor_at(py, pos, mag);
+            data_positions.push(pos);
+        } else {
+            if let Some(p) = corrected_parity.as_mut() {
+                p[pos - k] ^= mag;
+            }
+            parity_positions.push(pos - k);
+        }
+    }
+    Ok(PyRepairInfo { data_positions, parity_positions, parity: corrected_parity })
+}
@@ -0,0 +1,221 @@
+// Python exception hierarchy
+//   ECCError(RuntimeError)                  base for everything the codec raises
+//   ├── LengthError(ECCError, ValueError)   wrong message/codeword length
+//   ├── ParameterError(ECCError, ValueError) invalid (nsym, nsize), crc_bits, erasures
+//   ├── UncorrectableError(ECCError)        decoder gave up; carries diagnostics
+//   └── MiscorrectionError(ECCError)        RS decode succeeded but the CRC check failed
+//       └── PinnedPositionConflict          ... or would change bytes pinned as correct
+// ECCError derives RuntimeError and the argument errors also derive ValueError, so code written
+// against the earlier RuntimeError/ValueError behavior keeps working.
+
+use std::sync::OnceLock;
+
+use pyo3::prelude::*;
+use pyo3::sync::OnceLockExt;
+use pyo3::types::{PyDict, PyString, PyTuple, PyType};
+
+#[cfg(feature = "numpy")]
+use crate::rs::berlekamp_massey_in;
+use crate::rs::{berlekamp_massey, calc_syndromes, RsCodec, RsError};
+#[cfg(feature = "numpy")]
+use crate::rs_wide::WideRs;
+
+struct Classes {
+    ecc: Py<PyType>,
+    length: Py<PyType>,
+    parameter: Py<PyType>,
+    uncorrectable: Py<PyType>,
+    miscorrection: Py<PyType>,
+    pinned: Py<PyType>,
+}
+
+/// Created once per process; a OnceLock so free-threaded builds cannot race two sets into use
+static CLASSES: OnceLock<Classes> = OnceLock::new();
+
+fn new_class(py: Python<'_>, name: &str, doc: &str, bases: Vec<Bound<'_, PyAny>>) -> PyResult<Py<PyType>> {
+    let namespace = PyDict::new(py);
+    namespace.set_item("__module__", "ecc_model._rs")?;
+    namespace.set_item("__doc__", doc)?;
+    let class = py.get_type::<PyType>().call1((name, PyTuple::new(py, bases)?, namespace))?;
+    Ok(class.downcast_into::<PyType>()?.unbind())
+}
+
+fn classes(py: Python<'_>) -> &'static Classes {
+    CLASSES.get_or_init_py_attached(py, || {
+        let build = || -> PyResult<Classes> {
+            let runtime = py.get_type::<pyo3::exceptions::PyRuntimeError>().into_any();
+            let value = py.get_type::<pyo3::exceptions::PyValueError>().into_any();
+            let ecc = new_class(py, "ECCError", "Base class for ecc_model codec errors", vec![runtime])?;
+            let base = || ecc.bind(py).clone().into_any();
+            let miscorrection = new_class(py, "MiscorrectionError", "Decoder landed on the wrong codeword", vec![base()])?;
+            let pinned = new_class(
+                py,
+                "PinnedPositionConflict",
+                "Decoder located errors at positions pinned as correct; see positions",
+                vec![miscorrection.bind(py).clone().into_any()],
+            )?;
+            Ok(Classes {
+                length: new_class(py, "LengthError", "Message or codeword has the wrong length", vec![base(), value.clone()])?,
+                parameter: new_class(py, "ParameterError", "Invalid code parameters", vec![base(), value])?,
+                uncorrectable: new_class(
+                    py,
+                    "UncorrectableError",
+                    "Too many errors to correct; see detected_errors, capacity, syndrome_weight",
+                    vec![base()],
+                )?,
+                miscorrection,
+                pinned,
+                ecc,
+            })
+        };
+        build().expect("creating exception classes")
+    })
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let c = classes(m.py());
+    m.add("ECCError", c.ecc.clone_ref(m.py()))?;
+    m.add("LengthError", c.length.clone_ref(m.py()))?;
+    m.add("ParameterError", c.parameter.clone_ref(m.py()))?;
+    m.add("UncorrectableError", c.uncorrectable.clone_ref(m.py()))?;
+    m.add("MiscorrectionError", c.miscorrection.clone_ref(m.py()))?;
+    m.add("PinnedPositionConflict", c.pinned.clone_ref(m.py()))?;
+    Ok(())
+}
+
+fn raise(class: impl FnOnce(&Classes) -> &Py<PyType>, msg: String) -> PyErr {
+    Python::with_gil(|py| PyErr::from_type(class(classes(py)).bind(py).clone(), msg))
+}
+
+pub fn ecc_error(msg: String) -> PyErr {
+    raise(|c| &c.ecc, msg)
+}
+
+pub fn length_error(msg: String) -> PyErr {
+    raise(|c| &c.length, msg)
+}
+
+pub fn parameter_error(msg: String) -> PyErr {
+    raise(|c| &c.parameter, msg)
+}
+
+/// Map a decode failure onto the hierarchy; `context` prefixes the message (e.g. "codeword 3: ")
+/// For uncorrectable blocks the syndromes are recomputed to attach diagnostics.
+pub fn decode_error(py: Python<'_>, e: RsError, codeword: &[u8], nsym: usize, context: &str) -> PyErr {
+    map_decode_error(py, e, nsym, context, || {
+        let syndromes = calc_syndromes(codeword, nsym);
+        (syndromes.iter().filter(|&&s| s != 0).count(), berlekamp_massey(&syndromes).len() - 1)
+    })
+}
+
+/// decode_error from precomputed syndromes, for codewords that are never assembled in one buffer
+pub fn syndrome_decode_error(py: Python<'_>, e: RsError, syndromes: &[u8], context: &str) -> PyErr {
+    map_decode_error(py, e, syndromes.len(), context, || {
+        (syndromes.iter().filter(|&&s| s != 0).count(), berlekamp_massey(syndromes).len() - 1)
+    })
+}
+
+/// decode_error for a codec that may use non-default CodeOptions (its own roots and field)
+pub fn codec_decode_error(py: Python<'_>, e: RsError, codec: &RsCodec, codeword: &[u8], context: &str) -> PyErr {
+    map_decode_error(py, e, codec.nsym(), context, || {
+        let weight = codec.syndromes(codeword).iter().filter(|&&s| s != 0).count();
+        (weight, codec.locator_degree(codeword))
+    })
+}
+
+/// decode_error for RSCodec16's GF(2^16) codewords
+#[cfg(feature = "numpy")]
+pub fn wide_decode_error(py: Python<'_>, e: RsError, rs: &WideRs, codeword: &[u16], context: &str) -> PyErr {
+    map_decode_error(py, e, rs.nsym(), context, || {
+        let syndromes = rs.syndromes(codeword);
+        (syndromes.iter().filter(|&&s| s != 0).count(), berlekamp_massey_in(rs.field(), &syndromes).len() - 1)
+    })
+}
+
+/// `diagnose` returns (syndrome_weight, detected_errors) and only runs for uncorrectable blocks
+fn map_decode_error(py: Python<'_>, e: RsError, nsym: usize, context: &str, diagnose: impl FnOnce() -> (usize, usize)) -> PyErr {
+    let msg = format!("{}{}", context, e);
+    match e {
+        RsError::CodewordTooShort => length_error(msg),
+        RsError::InvalidParameters(_) | RsError::InvalidErasure => parameter_error(msg),
+        RsError::MiscorrectionDetected => raise(|c| &c.miscorrection, msg),
+        RsError::TrivialLocator | RsError::TooManyErrors | RsError::ChienSearchFailed | RsError::VerificationFailed | RsError::FaultModelViolation { .. } | RsError::SingularSystem { .. } => {
+            let (syndrome_weight, detected_errors) = diagnose();
+            uncorrectable_error(py, msg, detected_errors, nsym / 2, Some(syndrome_weight), e.to_string())
+        }
+        // Refused rather than failed: capacity is the configured limit, not nsym / 2
+        RsError::CorrectionLimit { found, limit } => {
+            let (syndrome_weight, _) = diagnose();
+            uncorrectable_error(py, msg, found, limit, Some(syndrome_weight), e.to_string())
+        }
+        // Refused before decoding: the missing symbols against the erasure budget
+        RsError::Truncated { missing, nsym } => uncorrectable_error(py, msg, missing, nsym, None, e.to_string()),
+        RsError::PinnedPositionConflict { positions } => pinned_error(py, msg, positions.iter().collect()),
+    }
+}
+
+/// PinnedPositionConflict carrying the offending positions
+fn pinned_error(py: Python<'_>, msg: String, positions: Vec<usize>) -> PyErr {
+    let build = || -> PyResult<PyErr> {
+        let err = classes(py).pinned.bind(py).call1((msg,))?;
+        err.setattr("positions", positions)?;
+        Ok(PyErr::from_value(err))
+    };
+    build().unwrap_or_else(|err| err)
+}
+
+/// UncorrectableError with its diagnostic attributes; syndrome_weight is None when the failure
+/// was not a syndrome decode (e.g. too few shards to reconstruct)
+pub fn uncorrectable_error(
+    py: Python<'_>,
+    msg: String,
+    detected_errors: usize,
+    capacity: usize,
+    syndrome_weight: Option<usize>,
+    reason: String,
+) -> PyErr {
+    let build = || -> PyResult<PyErr> {
+        let err = classes(py).uncorrectable.bind(py).call1((msg.clone(),))?;
+        err.setattr("detected_errors", detected_errors)?;
+        err.setattr("capacity", capacity)?;
+        err.setattr("syndrome_weight", syndrome_weight)?;
+        err.setattr("reason", reason)?;
+        Ok(PyErr::from_value(err))
+    };
+    build().unwrap_or_else(|err| err)
+}
+
+/// One failed block of a batch decoded with errors="report", in place of its result
+/// Carries the exception that errors="raise" would have raised for the block.
+#[pyclass(name = "BlockError", module = "ecc_model._rs", frozen)]
+pub struct PyBlockError {
+    #[pyo3(get)]
+    index: usize,
+    #[pyo3(get)]
+    exception: Py<PyAny>,
+}
+
+impl PyBlockError {
+    pub fn new(py: Python<'_>, index: usize, err: PyErr) -> Self {
+        Self { index, exception: err.into_value(py).into_any() }
+    }
+}
+
+#[pymethods]
+impl PyBlockError {
+    /// The exception class, e.g. UncorrectableError
+    #[getter(r#type)]
+    fn exc_type<'py>(&self, py: Python<'py>) -> Bound<'py, PyType> {
+        self.exception.bind(py).get_type()
+    }
+
+    #[getter]
+    fn message<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyString>> {
+        self.exception.bind(py).str()
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let exception = self.exception.bind(py);
+        Ok(format!("BlockError(index={}, type={}, message={})", self.index, exception.get_type().name()?, self.message(py)?.repr()?))
+    }
+}
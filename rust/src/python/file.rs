@@ -0,0 +1,148 @@
+// File-level encode/decode: the whole loop runs without the GIL
+// The progress callable is invoked every `progress_every` blocks (and at the end); the GIL is
+// re-acquired only for that call. An exception raised by the callable stops the job and is
+// re-raised once the loop has unwound.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use super::errors::{ecc_error, parameter_error};
+use crate::rs::{decode_file as rs_decode_file, encode_file_interleaved as rs_encode_file, repair_file as rs_repair_file, FileError, RepairReport};
+
+/// Run `job` without the GIL, handing it a progress hook that calls `progress` under the GIL
+fn run_with_progress<R: Send>(
+    py: Python<'_>,
+    progress: Option<PyObject>,
+    job: impl FnOnce(Option<&mut dyn FnMut(u64, u64) -> bool>) -> Result<R, FileError> + Send,
+) -> PyResult<R> {
+    let mut raised: Option<PyErr> = None;
+    let result = py.allow_threads(|| match &progress {
+        Some(callable) => {
+            let mut hook = |done: u64, total: u64| {
+                Python::with_gil(|py| match callable.call1(py, (done, total)) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        raised = Some(e);
+                        false
+                    }
+                })
+            };
+            job(Some(&mut hook))
+        }
+        None => job(None),
+    });
+    result.map_err(|e| match e {
+        FileError::Cancelled => raised.take().unwrap_or_else(|| file_error(e)),
+        e => file_error(e),
+    })
+}
+
+fn file_error(e: FileError) -> PyErr {
+    match e {
+        FileError::Io(io) => io.into(),
+        FileError::Params(_) | FileError::ParamMismatch { .. } => parameter_error(e.to_string()),
+        FileError::BadHeader(_) | FileError::Truncated | FileError::Cancelled | FileError::LengthMismatch { .. } | FileError::LengthChanged { .. } => ecc_error(e.to_string()),
+    }
+}
+
+/// Protect src_path into dst_path; returns the number of codewords written
+/// interleave > 1 stripes that many codewords together against bursts; decode_file and
+/// repair_file read the depth from the header.
+#[pyfunction]
+#[pyo3(signature = (src_path, dst_path, nsym, nsize, progress=None, progress_every=1024, interleave=1))]
+#[allow(clippy::too_many_arguments)]
+pub fn encode_file(
+    py: Python<'_>,
+    src_path: PathBuf,
+    dst_path: PathBuf,
+    nsym: usize,
+    nsize: usize,
+    progress: Option<PyObject>,
+    progress_every: u64,
+    interleave: usize,
+) -> PyResult<u64> {
+    let header = run_with_progress(py, progress, |hook| {
+        rs_encode_file(&src_path, &dst_path, nsym, nsize, interleave, hook, progress_every)
+    })?;
+    Ok(header.blocks())
+}
+
+/// Recover src_path into dst_path; parameters come from the file header
+/// Returns a dict: blocks, corrected_blocks, corrected_symbols, failed_offsets (offsets into the original data of
+/// blocks written through uncorrected), length.
+#[pyfunction]
+#[pyo3(signature = (src_path, dst_path, progress=None, progress_every=1024))]
+pub fn decode_file<'py>(
+    py: Python<'py>,
+    src_path: PathBuf,
+    dst_path: PathBuf,
+    progress: Option<PyObject>,
+    progress_every: u64,
+) -> PyResult<Bound<'py, PyDict>> {
+    let report = run_with_progress(py, progress, |hook| rs_decode_file(&src_path, &dst_path, hook, progress_every))?;
+    let dict = PyDict::new(py);
+    dict.set_item("blocks", report.blocks)?;
+    dict.set_item("corrected_blocks", report.corrected_blocks)?;
+    dict.set_item("corrected_symbols", report.corrected_symbols)?;
+    dict.set_item("failed_offsets", report.failed_offsets)?;
+    dict.set_item("length", report.original_len)?;
+    Ok(dict)
+}
+
+/// Result of repair_file
+#[pyclass(name = "RepairReport", module = "ecc_model._rs", frozen)]
+pub struct PyRepairReport {
+    report: RepairReport,
+}
+
+#[pymethods]
+impl PyRepairReport {
+    /// Total number of codewords scanned
+    #[getter]
+    fn blocks(&self) -> u64 {
+        self.report.blocks
+    }
+
+    /// {block index: corrected symbols} for every block rewritten on disk
+    #[getter]
+    fn corrected(&self) -> BTreeMap<u64, usize> {
+        self.report.repaired.iter().copied().collect()
+    }
+
+    #[getter]
+    fn total_corrected(&self) -> usize {
+        self.report.repaired.iter().map(|&(_, count)| count).sum()
+    }
+
+    /// Indices of blocks that could not be corrected (left as they were)
+    #[getter]
+    fn unrecoverable(&self) -> Vec<u64> {
+        self.report.unrecoverable.clone()
+    }
+
+    /// Container header copies rewritten (0 to 2)
+    #[getter]
+    fn headers_rewritten(&self) -> usize {
+        self.report.headers_rewritten
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RepairReport(blocks={}, repaired={}, total_corrected={}, unrecoverable={:?}, headers_rewritten={})",
+            self.report.blocks, self.report.repaired.len(), self.total_corrected(), self.report.unrecoverable, self.report.headers_rewritten
+        )
+    }
+}
+
+/// Correct an encode_file output in place; only blocks that need correction are rewritten
+/// A damaged or missing copy of the header is rewritten too. nsym/nsize, when given, must match
+/// the file header.
+#[pyfunction]
+#[pyo3(signature = (path, nsym=None, nsize=None))]
+pub fn repair_file(py: Python<'_>, path: PathBuf, nsym: Option<usize>, nsize: Option<usize>) -> PyResult<PyRepairReport> {
+    let report = py.allow_threads(|| rs_repair_file(&path, nsym, nsize));
+    Ok(PyRepairReport { report: report.map_err(file_error)? })
+}
@@ -0,0 +1,102 @@
+// ecc_model._rs.gf: the GF(256) arithmetic behind the codec (primitive polynomial 0x11d)
+// Polynomials are bytes-like or int sequences with poly[0] the constant term, the order used
+// by RSCodec.generator; results come back as bytes.
+
+use pyo3::exceptions::PyZeroDivisionError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use super::errors::parameter_error;
+use super::BytesInput;
+use crate::gf256::{gf_div, gf_inv, gf_mul, gf_pow, poly_div as gf_poly_div, poly_eval as gf_poly_eval, poly_mul as gf_poly_mul, GF};
+
+fn element(name: &str, value: i64) -> PyResult<u8> {
+    u8::try_from(value).map_err(|_| parameter_error(format!("{}={} is not an element of GF(256) (0..=255)", name, value)))
+}
+
+#[pyfunction]
+fn mul(a: i64, b: i64) -> PyResult<u8> {
+    Ok(gf_mul(element("a", a)?, element("b", b)?))
+}
+
+#[pyfunction]
+fn div(a: i64, b: i64) -> PyResult<u8> {
+    let (a, b) = (element("a", a)?, element("b", b)?);
+    if b == 0 {
+        return Err(PyZeroDivisionError::new_err("division by zero in GF(256)"));
+    }
+    Ok(gf_div(a, b))
+}
+
+#[pyfunction]
+fn inv(a: i64) -> PyResult<u8> {
+    match element("a", a)? {
+        0 => Err(PyZeroDivisionError::new_err("0 has no inverse in GF(256)")),
+        a => Ok(gf_inv(a)),
+    }
+}
+
+/// a**n; n may be negative (a**-1 == inv(a)), and pow(0, 0) == 1
+#[pyfunction]
+fn pow(a: i64, n: i64) -> PyResult<u8> {
+    let a = element("a", a)?;
+    if a == 0 && n < 0 {
+        return Err(PyZeroDivisionError::new_err("0 cannot be raised to a negative power in GF(256)"));
+    }
+    Ok(gf_pow(a, n))
+}
+
+/// alpha**i for the primitive element alpha = 2; i is taken mod 255
+#[pyfunction]
+fn exp(i: i64) -> u8 {
+    GF.exp[i.rem_euclid(255) as usize]
+}
+
+/// Discrete log base alpha = 2, in 0..255
+#[pyfunction]
+fn log(a: i64) -> PyResult<u8> {
+    match element("a", a)? {
+        0 => Err(parameter_error("log(0) is undefined in GF(256)".into())),
+        a => Ok(GF.log[a as usize]),
+    }
+}
+
+#[pyfunction]
+fn poly_mul<'py>(py: Python<'py>, p: BytesInput, q: BytesInput) -> Bound<'py, PyBytes> {
+    PyBytes::new(py, &gf_poly_mul(p.as_slice(), q.as_slice()))
+}
+
+#[pyfunction]
+fn poly_eval(poly: BytesInput, x: i64) -> PyResult<u8> {
+    Ok(gf_poly_eval(poly.as_slice(), element("x", x)?))
+}
+
+/// (quotient, remainder); the remainder always has deg(divisor) coefficients
+#[pyfunction]
+fn poly_div<'py>(py: Python<'py>, dividend: BytesInput, divisor: BytesInput) -> PyResult<(Bound<'py, PyBytes>, Bound<'py, PyBytes>)> {
+    if divisor.as_slice().iter().all(|&c| c == 0) {
+        return Err(PyZeroDivisionError::new_err("division by the zero polynomial"));
+    }
+    let (quotient, remainder) = gf_poly_div(dividend.as_slice(), divisor.as_slice());
+    Ok((PyBytes::new(py, &quotient), PyBytes::new(py, &remainder)))
+}
+
+/// Build the submodule and make `import ecc_model._rs.gf` work as well as attribute access
+pub fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = parent.py();
+    let m = PyModule::new(py, "gf")?;
+    m.gil_used(false)?;
+    m.add("PRIM_POLY", crate::gf256::PRIM_POLY)?;
+    m.add_function(wrap_pyfunction!(mul, &m)?)?;
+    m.add_function(wrap_pyfunction!(div, &m)?)?;
+    m.add_function(wrap_pyfunction!(inv, &m)?)?;
+    m.add_function(wrap_pyfunction!(pow, &m)?)?;
+    m.add_function(wrap_pyfunction!(exp, &m)?)?;
+    m.add_function(wrap_pyfunction!(log, &m)?)?;
+    m.add_function(wrap_pyfunction!(poly_mul, &m)?)?;
+    m.add_function(wrap_pyfunction!(poly_eval, &m)?)?;
+    m.add_function(wrap_pyfunction!(poly_div, &m)?)?;
+    parent.add_submodule(&m)?;
+    py.import("sys")?.getattr("modules")?.set_item("ecc_model._rs.gf", &m)?;
+    Ok(())
+}
@@ -0,0 +1,122 @@
+// Interleaver bindings: byte striping and interleaved encode/decode
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use super::errors::{decode_error, length_error, parameter_error};
+use super::{check_params, with_slices, BytesInput};
+use crate::rs::{decode_interleaved as rs_decode_interleaved, encode_interleaved as rs_encode_interleaved, Interleaver};
+
+fn interleaver(depth: usize) -> PyResult<Interleaver> {
+    if depth == 0 {
+        return Err(parameter_error("interleaver depth must be at least 1".into()));
+    }
+    Ok(Interleaver::new(depth))
+}
+
+/// Byte interleaver: stream[p * depth + i] is byte p of block i
+#[pyclass(name = "Interleaver", module = "ecc_model._rs", frozen)]
+pub struct PyInterleaver {
+    inner: Interleaver,
+}
+
+#[pymethods]
+impl PyInterleaver {
+    #[new]
+    fn new(depth: usize) -> PyResult<Self> {
+        Ok(Self { inner: interleaver(depth)? })
+    }
+
+    #[getter]
+    fn depth(&self) -> usize {
+        self.inner.depth()
+    }
+
+    /// Stripe exactly `depth` equal-length blocks into one buffer
+    fn interleave<'py>(&self, py: Python<'py>, blocks: Vec<BytesInput>) -> PyResult<Bound<'py, PyBytes>> {
+        if blocks.len() != self.inner.depth() {
+            return Err(length_error(format!("expected {} blocks, got {}", self.inner.depth(), blocks.len())));
+        }
+        if let Some((i, b)) = blocks.iter().enumerate().find(|(_, b)| b.len() != blocks[0].len()) {
+            return Err(length_error(format!(
+                "block {} has length {}, expected {} like block 0",
+                i, b.len(), blocks[0].len()
+            )));
+        }
+        let out = with_slices(py, &blocks, |slices| self.inner.interleave(slices));
+        Ok(PyBytes::new(py, &out))
+    }
+
+    /// Split a striped buffer back into `depth` blocks
+    fn deinterleave<'py>(&self, py: Python<'py>, data: BytesInput) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+        if !data.len().is_multiple_of(self.inner.depth()) {
+            return Err(length_error(format!(
+                "data length {} is not a multiple of depth={}",
+                data.len(), self.inner.depth()
+            )));
+        }
+        let blocks = data.with_slice(py, |d| self.inner.deinterleave(d));
+        Ok(blocks.iter().map(|b| PyBytes::new(py, b)).collect())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Interleaver(depth={})", self.inner.depth())
+    }
+}
+
+/// Module-level deinterleave for a stream of `depth` blocks of `block_len` bytes
+#[pyfunction]
+pub fn deinterleave<'py>(py: Python<'py>, data: BytesInput, block_len: usize, depth: usize) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+    if data.len() != block_len * depth {
+        return Err(length_error(format!(
+            "data length {} does not match block_len={} * depth={}",
+            data.len(), block_len, depth
+        )));
+    }
+    PyInterleaver::new(depth)?.deinterleave(py, data)
+}
+
+/// Module-level interleave; depth is the number of blocks
+#[pyfunction]
+pub fn interleave<'py>(py: Python<'py>, blocks: Vec<BytesInput>) -> PyResult<Bound<'py, PyBytes>> {
+    PyInterleaver::new(blocks.len())?.interleave(py, blocks)
+}
+
+/// Encode data as groups of `depth` interleaved codewords, zero-padding the last group
+#[pyfunction]
+pub fn encode_interleaved<'py>(py: Python<'py>, data: BytesInput, nsym: usize, nsize: usize, depth: usize) -> PyResult<Bound<'py, PyBytes>> {
+    check_params(nsym, nsize)?;
+    interleaver(depth)?;
+    let out = data.with_slice(py, |d| rs_encode_interleaved(d, nsym, nsize, depth)).expect("parameters validated");
+    Ok(PyBytes::new(py, &out))
+}
+
+/// Decode an encode_interleaved stream into (data, positions, parity_positions)
+/// `positions` are the corrected data bytes as offsets into the decoded data (before `length`
+/// trims the zero padding added by encode_interleaved), `parity_positions` the corrected parity
+/// bytes as offsets into `stream`.
+#[pyfunction]
+#[pyo3(signature = (stream, nsym, nsize, depth, length=None))]
+pub fn decode_interleaved<'py>(
+    py: Python<'py>,
+    stream: BytesInput,
+    nsym: usize,
+    nsize: usize,
+    depth: usize,
+    length: Option<usize>,
+) -> PyResult<(Bound<'py, PyBytes>, Vec<usize>, Vec<usize>)> {
+    check_params(nsym, nsize)?;
+    interleaver(depth)?;
+    if !stream.len().is_multiple_of(depth * nsize) {
+        return Err(length_error(format!(
+            "stream length {} is not a multiple of depth * nsize = {}",
+            stream.len(), depth * nsize
+        )));
+    }
+    let out = stream.with_slice(py, |s| rs_decode_interleaved(s, nsym, nsize, depth)).map_err(|(index, e)| {
+        let codeword = Interleaver::new(depth).deinterleave(&stream.as_slice()[index / depth * depth * nsize..][..depth * nsize]);
+        decode_error(py, e, &codeword[index % depth], nsym, &format!("codeword {}: ", index))
+    })?;
+    let keep = length.map_or(out.data.len(), |l| l.min(out.data.len()));
+    Ok((PyBytes::new(py, &out.data[..keep]), out.positions, out.parity_positions))
+}
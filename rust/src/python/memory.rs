@@ -0,0 +1,178 @@
+// MemoryModel bindings over crate::memory (`sim` feature)
+// Single accesses go through write/read/inject_*; sweep() runs whole Monte Carlo loops in Rust
+// without the GIL.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use super::errors::{length_error, parameter_error};
+use super::BytesInput;
+use crate::memory::{Fault, FaultMix, Line, MemoryCounters, MemoryModel, Scheme, LINE_BYTES};
+
+fn counters_dict<'py>(py: Python<'py>, counters: &MemoryCounters) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for (key, value) in [
+        ("writes", counters.writes),
+        ("reads", counters.reads),
+        ("faults", counters.faults),
+        ("clean", counters.clean),
+        ("ce", counters.ce),
+        ("due", counters.due),
+        ("sdc", counters.sdc),
+    ] {
+        dict.set_item(key, value)?;
+    }
+    Ok(dict)
+}
+
+fn fault_mix(faults: &Bound<'_, PyDict>) -> PyResult<FaultMix> {
+    let mut mix = FaultMix::default();
+    for (key, value) in faults.iter() {
+        let key: String = key.extract()?;
+        let slot = match key.as_str() {
+            "bit" => &mut mix.bit,
+            "double_bit" => &mut mix.double_bit,
+            "device" => &mut mix.device,
+            "row" => &mut mix.row,
+            _ => {
+                return Err(parameter_error(format!(
+                    "unknown fault kind {:?}: expected \"bit\", \"double_bit\", \"device\" or \"row\"",
+                    key
+                )))
+            }
+        };
+        *slot = value.extract()?;
+    }
+    mix.validate().map_err(|e| parameter_error(format!("{} (got {:?})", e, mix)))?;
+    Ok(mix)
+}
+
+/// MemoryModel(scheme, seed=0): byte-addressed memory of ECC-protected 64-byte cache lines
+/// scheme is "secded72", "chipkill_x4" or "rs(n,k)" with k dividing 64. Each line is stored as
+/// `beats` beats of a `bus_bits`-wide bus split over `devices` devices of `device_width` lanes.
+/// read() classifies every access as "clean", "ce", "due" or "sdc" against the data last
+/// written, and all accesses accumulate into counters().
+#[pyclass(name = "MemoryModel", module = "ecc_model._rs")]
+pub struct PyMemoryModel {
+    inner: MemoryModel,
+}
+
+impl PyMemoryModel {
+    fn inject(&mut self, address: u64, fault: Fault) -> PyResult<()> {
+        self.inner.inject(address, fault).map_err(|e| parameter_error(e.to_string()))
+    }
+}
+
+#[pymethods]
+impl PyMemoryModel {
+    #[new]
+    #[pyo3(signature = (scheme, seed=0))]
+    fn new(scheme: &str, seed: u64) -> PyResult<Self> {
+        let scheme = Scheme::parse(scheme).map_err(|e| parameter_error(format!("{} (got {:?})", e, scheme)))?;
+        Ok(Self { inner: MemoryModel::new(scheme, seed) })
+    }
+
+    /// Canonical scheme name
+    #[getter]
+    fn scheme(&self) -> String {
+        self.inner.scheme().name()
+    }
+
+    #[getter]
+    fn beats(&self) -> usize {
+        self.inner.scheme().beats()
+    }
+
+    #[getter]
+    fn bus_bits(&self) -> usize {
+        self.inner.scheme().bus_bits()
+    }
+
+    #[getter]
+    fn devices(&self) -> usize {
+        self.inner.scheme().devices()
+    }
+
+    /// Lanes per device in each beat
+    #[getter]
+    fn device_width(&self) -> usize {
+        self.inner.scheme().device_width()
+    }
+
+    /// Stored bits per line, beats * bus_bits
+    #[getter]
+    fn line_bits(&self) -> usize {
+        self.inner.scheme().line_bits()
+    }
+
+    /// Store a 64-byte line at the line containing `address`
+    fn write(&mut self, address: u64, line: BytesInput) -> PyResult<()> {
+        let line: Line = line.as_slice().try_into().map_err(|_| {
+            length_error(format!("a cache line is {} bytes, got {}", LINE_BYTES, line.len()))
+        })?;
+        self.inner.write(address, &line);
+        Ok(())
+    }
+
+    /// (data, outcome) with outcome one of "clean", "ce", "due", "sdc"; DUE data is uncorrected
+    fn read<'py>(&mut self, py: Python<'py>, address: u64) -> (Bound<'py, PyBytes>, &'static str) {
+        let (line, access) = self.inner.read(address);
+        (PyBytes::new(py, &line), access.as_str())
+    }
+
+    /// Flip stored bit `bit` = beat * bus_bits + lane
+    fn inject_bit(&mut self, address: u64, bit: usize) -> PyResult<()> {
+        self.inject(address, Fault::Bit(bit))
+    }
+
+    /// Corrupt every bit `device` holds in the line: bit j of `pattern` is lane
+    /// j % device_width of beat j // device_width; pattern=None draws a random nonzero one
+    #[pyo3(signature = (address, device, pattern=None))]
+    fn inject_device(&mut self, address: u64, device: usize, pattern: Option<u64>) -> PyResult<()> {
+        match pattern {
+            Some(pattern) => self.inject(address, Fault::Device { device, pattern }),
+            None => self.inner.inject_random_device(address, device).map_err(|e| parameter_error(e.to_string())),
+        }
+    }
+
+    /// Flip `length` consecutive lanes of one beat starting at lane `start` (default: the whole beat)
+    #[pyo3(signature = (address, beat, start=0, length=None))]
+    fn inject_row_burst(&mut self, address: u64, beat: usize, start: usize, length: Option<usize>) -> PyResult<()> {
+        let len = length.unwrap_or_else(|| self.inner.scheme().bus_bits().saturating_sub(start));
+        self.inject(address, Fault::RowBurst { beat, start, len })
+    }
+
+    /// Run `accesses` write / fault / read cycles natively and return their counters
+    /// `faults` maps "bit", "double_bit", "device" and "row" to per-access probabilities
+    /// (summing to at most 1). The result depends only on `seed`; it is also added to counters().
+    #[pyo3(signature = (accesses, faults, seed=0))]
+    fn sweep<'py>(
+        &mut self,
+        py: Python<'py>,
+        accesses: u64,
+        faults: &Bound<'py, PyDict>,
+        seed: u64,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let mix = fault_mix(faults)?;
+        let inner = &mut self.inner;
+        let swept = py.allow_threads(|| inner.sweep(accesses, &mix, seed)).map_err(|e| parameter_error(e.into()))?;
+        counters_dict(py, &swept)
+    }
+
+    /// Running totals: writes, reads, faults, clean, ce, due, sdc
+    fn counters<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        counters_dict(py, &self.inner.counters())
+    }
+
+    fn reset_counters(&mut self) {
+        self.inner.reset_counters();
+    }
+
+    fn __repr__(&self) -> String {
+        let c = self.inner.counters();
+        format!(
+            "MemoryModel(scheme={:?}, reads={}, ce={}, due={}, sdc={})",
+            self.inner.scheme().name(), c.reads, c.ce, c.due, c.sdc
+        )
+    }
+}
@@ -0,0 +1,114 @@
+// Parity planning bindings: plan() and block_failure_rate() over rs::plan
+
+use pyo3::prelude::*;
+
+use super::errors::parameter_error;
+use super::param_error;
+use crate::rs::{block_failure_rate as rs_block_failure_rate, plan as rs_plan, validate_params, Burst, Plan};
+
+fn burst(burst_len: Option<usize>, depth: usize) -> PyResult<Burst> {
+    let burst = Burst { len: burst_len.unwrap_or(1), depth };
+    if burst.len == 0 || burst.depth == 0 {
+        return Err(parameter_error(format!(
+            "burst_len and depth must be at least 1 (burst_len={}, depth={})",
+            burst.len, burst.depth
+        )));
+    }
+    Ok(burst)
+}
+
+/// Result of plan(): the smallest nsym meeting the target, plus every candidate tried
+#[pyclass(name = "PlanResult", module = "ecc_model._rs", frozen)]
+pub struct PyPlanResult {
+    inner: Plan,
+}
+
+#[pymethods]
+impl PyPlanResult {
+    #[getter]
+    fn nsize(&self) -> usize {
+        self.inner.nsize
+    }
+
+    #[getter]
+    fn nsym(&self) -> usize {
+        self.inner.nsym
+    }
+
+    /// Correctable symbol errors per codeword, nsym // 2
+    #[getter]
+    fn t(&self) -> usize {
+        self.inner.t()
+    }
+
+    #[getter]
+    fn block_failure_rate(&self) -> f64 {
+        self.inner.block_failure_rate
+    }
+
+    /// k / n
+    #[getter]
+    fn code_rate(&self) -> f64 {
+        self.inner.code_rate()
+    }
+
+    /// Parity bytes per message byte, nsym / k
+    #[getter]
+    fn overhead(&self) -> f64 {
+        self.inner.overhead()
+    }
+
+    /// [(nsym, block failure rate)] for nsym = 1 up to the chosen value
+    #[getter]
+    fn candidates(&self) -> Vec<(usize, f64)> {
+        self.inner.candidates.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PlanResult(nsize={}, nsym={}, block_failure_rate={:e}, code_rate={:.4})",
+            self.inner.nsize, self.inner.nsym, self.inner.block_failure_rate, self.inner.code_rate()
+        )
+    }
+}
+
+/// Smallest nsym for which an (nsize, nsym) codeword fails with probability at most
+/// `target_block_failure_rate` when each symbol is corrupted independently with probability
+/// `symbol_error_rate`
+/// With burst_len, errors arrive as bursts of that many symbols, spread over `depth`
+/// interleaved codewords; each burst then costs ceil(burst_len / depth) symbols per codeword.
+#[pyfunction]
+#[pyo3(signature = (nsize, symbol_error_rate, target_block_failure_rate, burst_len=None, depth=1))]
+pub fn plan(
+    nsize: usize,
+    symbol_error_rate: f64,
+    target_block_failure_rate: f64,
+    burst_len: Option<usize>,
+    depth: usize,
+) -> PyResult<PyPlanResult> {
+    let burst = burst(burst_len, depth)?;
+    let inner = rs_plan(nsize, symbol_error_rate, target_block_failure_rate, burst).map_err(|e| {
+        parameter_error(format!(
+            "{} (nsize={}, symbol_error_rate={}, target_block_failure_rate={})",
+            e, nsize, symbol_error_rate, target_block_failure_rate
+        ))
+    })?;
+    Ok(PyPlanResult { inner })
+}
+
+/// Block failure probability of one (nsize, nsym) codeword under the plan() error model
+#[pyfunction]
+#[pyo3(signature = (nsize, nsym, symbol_error_rate, burst_len=None, depth=1))]
+pub fn block_failure_rate(
+    nsize: usize,
+    nsym: usize,
+    symbol_error_rate: f64,
+    burst_len: Option<usize>,
+    depth: usize,
+) -> PyResult<f64> {
+    validate_params(nsym, nsize).map_err(|e| param_error(e, nsym, nsize))?;
+    if !(0.0..=1.0).contains(&symbol_error_rate) {
+        return Err(parameter_error(format!("symbol_error_rate must be in 0..=1, got {}", symbol_error_rate)));
+    }
+    Ok(rs_block_failure_rate(nsize, nsym, symbol_error_rate, burst(burst_len, depth)?))
+}
@@ -0,0 +1,324 @@
+// Pool: batch encode/decode on Rust-owned worker threads, with concurrent.futures-style Jobs
+// Inputs are copied when a job is submitted, so workers never touch Python objects and run
+// without the GIL; an asyncio loop can poll Job.done() or wait on Job.result() from an executor
+// thread while the work proceeds. A job's state sits behind its own mutex and condvar, and the
+// output is only turned into Python objects by whichever thread calls result().
+
+use std::collections::VecDeque;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use pyo3::exceptions::PyTimeoutError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyList, PyTuple};
+
+use super::errors::{ecc_error, parameter_error};
+use super::{batch_decode_output, check_batch_codewords, check_batch_messages, report_errors, BytesInput};
+use crate::rs::{decode_batch_with, encode_batch_with, BlockResult};
+
+enum Work {
+    Encode { nsym: usize, nsize: usize, messages: Vec<Vec<u8>> },
+    Decode { nsym: usize, codewords: Vec<Vec<u8>>, report: bool },
+}
+
+enum Output {
+    Encoded(Vec<Vec<u8>>),
+    /// The codewords stay with the results so failures can carry syndromes, as decode_batch's do
+    Decoded { nsym: usize, codewords: Vec<Vec<u8>>, results: Vec<BlockResult>, report: bool },
+}
+
+impl Work {
+    fn run(self) -> Output {
+        match self {
+            Work::Encode { nsym, nsize, messages } => {
+                let slices: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+                Output::Encoded(encode_batch_with(&slices, nsym, nsize, false))
+            }
+            Work::Decode { nsym, codewords, report } => {
+                let slices: Vec<&[u8]> = codewords.iter().map(|c| c.as_slice()).collect();
+                let results = decode_batch_with(&slices, nsym, false);
+                Output::Decoded { nsym, codewords, results, report }
+            }
+        }
+    }
+}
+
+enum State {
+    Pending(Work),
+    Running,
+    /// Err holds a worker panic message
+    Done(Arc<Result<Output, String>>),
+    Cancelled,
+}
+
+struct JobInner {
+    state: Mutex<State>,
+    finished: Condvar,
+}
+
+impl JobInner {
+    fn finish(&self, state: State) {
+        *self.state.lock().unwrap() = state;
+        self.finished.notify_all();
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: VecDeque<Arc<JobInner>>,
+    closed: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+    ready: Condvar,
+}
+
+impl Shared {
+    fn close(&self) {
+        self.queue.lock().unwrap().closed = true;
+        self.ready.notify_all();
+    }
+
+    /// Next job to run, or None once the pool is closed and drained
+    fn next(&self) -> Option<Arc<JobInner>> {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            if let Some(job) = queue.jobs.pop_front() {
+                return Some(job);
+            }
+            if queue.closed {
+                return None;
+            }
+            queue = self.ready.wait(queue).unwrap();
+        }
+    }
+}
+
+fn worker(shared: Arc<Shared>) {
+    while let Some(job) = shared.next() {
+        let work = {
+            let mut state = job.state.lock().unwrap();
+            match std::mem::replace(&mut *state, State::Running) {
+                State::Pending(work) => work,
+                // Cancelled while queued
+                other => {
+                    *state = other;
+                    continue;
+                }
+            }
+        };
+        let output = catch_unwind(AssertUnwindSafe(|| work.run())).map_err(|panic| {
+            panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "worker panicked".into())
+        });
+        job.finish(State::Done(Arc::new(output)));
+    }
+}
+
+/// Fixed set of worker threads running submitted encode/decode batches
+/// Each job is one batch, run serially on one worker; overlapping jobs run side by side.
+/// shutdown() (or leaving a `with` block) finishes the queued jobs before the threads exit.
+#[pyclass(name = "Pool", module = "ecc_model._rs", frozen)]
+pub struct PyPool {
+    shared: Arc<Shared>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+    threads: usize,
+}
+
+impl PyPool {
+    fn submit(&self, work: Work) -> PyResult<PyJob> {
+        let job = Arc::new(JobInner { state: Mutex::new(State::Pending(work)), finished: Condvar::new() });
+        let mut queue = self.shared.queue.lock().unwrap();
+        if queue.closed {
+            return Err(ecc_error("cannot submit to a pool after shutdown".into()));
+        }
+        queue.jobs.push_back(job.clone());
+        drop(queue);
+        self.shared.ready.notify_one();
+        Ok(PyJob { inner: job })
+    }
+}
+
+#[pymethods]
+impl PyPool {
+    /// threads defaults to the number of CPUs
+    #[new]
+    #[pyo3(signature = (threads=None))]
+    fn new(threads: Option<usize>) -> PyResult<Self> {
+        let threads = match threads {
+            Some(0) => return Err(parameter_error("threads must be at least 1".into())),
+            Some(n) => n,
+            None => thread::available_parallelism().map_or(1, |n| n.get()),
+        };
+        let shared = Arc::new(Shared::default());
+        let workers = (0..threads)
+            .map(|i| {
+                let shared = shared.clone();
+                thread::Builder::new().name(format!("ecc-pool-{}", i)).spawn(move || worker(shared))
+            })
+            .collect::<std::io::Result<Vec<_>>>()
+            .map_err(|e| ecc_error(format!("could not start pool threads: {}", e)))?;
+        Ok(Self { shared, workers: Mutex::new(workers), threads })
+    }
+
+    #[getter]
+    fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Queue encode_batch(nsym, nsize, messages); lengths are checked, and raise, here
+    fn submit_encode(&self, nsym: usize, nsize: usize, messages: Vec<BytesInput>) -> PyResult<PyJob> {
+        check_batch_messages(nsym, nsize, &messages)?;
+        let messages = messages.iter().map(|m| m.as_slice().to_vec()).collect();
+        self.submit(Work::Encode { nsym, nsize, messages })
+    }
+
+    /// Queue decode_batch(nsym, nsize, codewords, errors=errors); result() then returns or raises
+    /// exactly what decode_batch would. Lengths and parameters are checked, and raise, here.
+    #[pyo3(signature = (nsym, nsize, codewords, errors="raise"))]
+    fn submit_decode(&self, nsym: usize, nsize: usize, codewords: Vec<BytesInput>, errors: &str) -> PyResult<PyJob> {
+        let report = report_errors(errors)?;
+        check_batch_codewords(nsym, nsize, &codewords)?;
+        let codewords = codewords.iter().map(|c| c.as_slice().to_vec()).collect();
+        self.submit(Work::Decode { nsym, codewords, report })
+    }
+
+    /// Stop accepting jobs; queued jobs still run. wait=True blocks until the threads exit.
+    #[pyo3(signature = (wait=true))]
+    fn shutdown(&self, py: Python<'_>, wait: bool) {
+        self.shared.close();
+        if wait {
+            let workers = std::mem::take(&mut *self.workers.lock().unwrap());
+            py.allow_threads(|| {
+                for handle in workers {
+                    // A worker cannot panic: job panics are caught and stored in the job
+                    let _ = handle.join();
+                }
+            });
+        }
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&self, py: Python<'_>, _args: &Bound<'_, PyTuple>) {
+        self.shutdown(py, true);
+    }
+
+    fn __repr__(&self) -> String {
+        let queue = self.shared.queue.lock().unwrap();
+        let state = if queue.closed { ", shutdown" } else { "" };
+        format!("Pool(threads={}, queued={}{})", self.threads, queue.jobs.len(), state)
+    }
+}
+
+impl Drop for PyPool {
+    /// Dropping without shutdown() lets the workers drain the queue and exit in the background
+    fn drop(&mut self) {
+        self.shared.close();
+    }
+}
+
+/// Handle to one submitted batch, usable from any thread
+#[pyclass(name = "Job", module = "ecc_model._rs", frozen)]
+pub struct PyJob {
+    inner: Arc<JobInner>,
+}
+
+#[pymethods]
+impl PyJob {
+    fn done(&self) -> bool {
+        matches!(*self.inner.state.lock().unwrap(), State::Done(_) | State::Cancelled)
+    }
+
+    fn running(&self) -> bool {
+        matches!(*self.inner.state.lock().unwrap(), State::Running)
+    }
+
+    fn cancelled(&self) -> bool {
+        matches!(*self.inner.state.lock().unwrap(), State::Cancelled)
+    }
+
+    /// Cancel a job that has not started; False once it is running or done
+    fn cancel(&self) -> bool {
+        let mut state = self.inner.state.lock().unwrap();
+        match *state {
+            State::Pending(_) => *state = State::Cancelled,
+            State::Cancelled => {}
+            State::Running | State::Done(_) => return false,
+        }
+        drop(state);
+        self.inner.finished.notify_all();
+        true
+    }
+
+    /// The batch's result, waiting up to `timeout` seconds (None waits forever) with the GIL released
+    /// Raises TimeoutError if the job is still unfinished, concurrent.futures.CancelledError if it
+    /// was cancelled, and whatever the batch function would raise if decoding failed.
+    #[pyo3(signature = (timeout=None))]
+    fn result<'py>(&self, py: Python<'py>, timeout: Option<f64>) -> PyResult<Bound<'py, PyAny>> {
+        let timeout = match timeout {
+            Some(t) if !(t >= 0.0 && t.is_finite()) => {
+                return Err(parameter_error(format!("timeout must be a non-negative number of seconds, got {}", t)))
+            }
+            t => t.map(Duration::from_secs_f64),
+        };
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let output = py.allow_threads(|| {
+            let mut state = self.inner.state.lock().unwrap();
+            loop {
+                match &*state {
+                    State::Done(output) => return Ok(output.clone()),
+                    State::Cancelled => return Err(true),
+                    State::Pending(_) | State::Running => {}
+                }
+                state = match deadline {
+                    None => self.inner.finished.wait(state).unwrap(),
+                    Some(deadline) => {
+                        let left = deadline.saturating_duration_since(Instant::now());
+                        if left.is_zero() {
+                            return Err(false);
+                        }
+                        self.inner.finished.wait_timeout(state, left).unwrap().0
+                    }
+                };
+            }
+        });
+        // Converted with the job unlocked: allocating Python objects may run other threads
+        match output {
+            Err(true) => {
+                let cancelled = py.import("concurrent.futures")?.getattr("CancelledError")?;
+                Err(PyErr::from_value(cancelled.call0()?))
+            }
+            Err(false) => Err(PyTimeoutError::new_err(format!("job not finished after {:?}", timeout.unwrap_or_default()))),
+            Ok(output) => match &*output {
+                Err(panic) => Err(ecc_error(format!("pool job failed: {}", panic))),
+                Ok(Output::Encoded(codewords)) => Ok(PyList::new(py, codewords.iter().map(|c| PyBytes::new(py, c)))?.into_any()),
+                Ok(Output::Decoded { nsym, codewords, results, report }) => {
+                    let slices: Vec<&[u8]> = codewords.iter().map(|c| c.as_slice()).collect();
+                    let decoded = batch_decode_output(py, &slices, results.clone(), *nsym, *report)?;
+                    Ok(PyList::new(py, decoded)?.into_any())
+                }
+            },
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        let state = match *self.inner.state.lock().unwrap() {
+            State::Pending(_) => "pending",
+            State::Running => "running",
+            State::Done(_) => "done",
+            State::Cancelled => "cancelled",
+        };
+        format!("Job(state={})", state)
+    }
+}
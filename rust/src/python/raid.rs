@@ -0,0 +1,149 @@
+// ecc_model._rs.raid: RAID-6 P/Q parity over numpy uint8 arrays (`numpy` feature)
+// The GF work runs with the GIL released on copies of the arrays taken while it was held:
+// rust-numpy's borrow checker only guards Rust code, and Python can write an array at any time.
+// update_pq XORs its change into P and Q with the GIL held again, so concurrent writes to other
+// bytes survive. Shape and dtype problems raise ValueError subclasses naming the array at fault.
+
+use numpy::{dtype, IntoPyArray, PyArray1, PyArrayDescrMethods, PyArrayDyn, PyArrayMethods, PyUntypedArray, PyUntypedArrayMethods};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+
+use super::errors::{length_error, parameter_error, uncorrectable_error};
+use super::type_name;
+use crate::raid::{compute_pq as raid_compute_pq, reconstruct as raid_reconstruct, update_pq as raid_update_pq, RaidError};
+
+fn raid_error(py: Python<'_>, e: RaidError) -> PyErr {
+    match e {
+        RaidError::BlockCount(_) | RaidError::IndexOutOfRange(_) => parameter_error(e.to_string()),
+        RaidError::LengthMismatch { .. } => length_error(e.to_string()),
+        RaidError::TooManyMissing(missing) => uncorrectable_error(py, e.to_string(), missing, 2, None, e.to_string()),
+    }
+}
+
+/// `ob` as a C-contiguous uint8 array with `ndim` dimensions
+fn u8_array<'py>(ob: &Bound<'py, PyAny>, name: &str, ndim: usize) -> PyResult<Bound<'py, PyArrayDyn<u8>>> {
+    let arr = ob
+        .downcast::<PyUntypedArray>()
+        .map_err(|_| PyTypeError::new_err(format!("{} must be a numpy array of dtype uint8, got {}", name, type_name(ob))))?;
+    if !arr.dtype().is_equiv_to(&dtype::<u8>(ob.py())) {
+        return Err(parameter_error(format!("{} has dtype {}, expected uint8", name, arr.dtype())));
+    }
+    if arr.ndim() != ndim {
+        return Err(parameter_error(format!("{} has {} dimensions {:?}, expected {}", name, arr.ndim(), arr.shape(), ndim)));
+    }
+    if !arr.is_c_contiguous() {
+        return Err(parameter_error(format!("{} must be C-contiguous; pass numpy.ascontiguousarray({})", name, name)));
+    }
+    Ok(arr.downcast::<PyArrayDyn<u8>>()?.clone())
+}
+
+/// The array's bytes, copied with the GIL held
+fn copied(arr: &Bound<'_, PyArrayDyn<u8>>, name: &str) -> PyResult<Vec<u8>> {
+    let guard = arr.try_readonly().map_err(|e| parameter_error(format!("{}: {}", name, e)))?;
+    Ok(guard.as_slice().expect("checked C-contiguous").to_vec())
+}
+
+/// (P, Q)
+type PqArrays<'py> = (Bound<'py, PyArray1<u8>>, Bound<'py, PyArray1<u8>>);
+
+/// (P, Q) as 1D arrays of blocklen for a (k, blocklen) array of data blocks
+#[pyfunction]
+fn compute_pq<'py>(py: Python<'py>, blocks: &Bound<'py, PyAny>) -> PyResult<PqArrays<'py>> {
+    let arr = u8_array(blocks, "blocks", 2)?;
+    let (k, len) = (arr.shape()[0], arr.shape()[1]);
+    let data = copied(&arr, "blocks")?;
+    let (p, q) = py
+        .allow_threads(|| {
+            let rows: Vec<&[u8]> = (0..k).map(|i| &data[i * len..(i + 1) * len]).collect();
+            raid_compute_pq(&rows)
+        })
+        .map_err(|e| raid_error(py, e))?;
+    Ok((p.into_pyarray(py), q.into_pyarray(py)))
+}
+
+/// The k data blocks with the missing ones rebuilt
+/// `blocks` is a sequence of 1D uint8 arrays with None for lost blocks; P or Q may also be None
+/// as long as at most two members are missing. Present blocks are returned as the same objects.
+#[pyfunction]
+#[pyo3(signature = (blocks, P, Q))]
+#[allow(non_snake_case)]
+fn reconstruct<'py>(
+    py: Python<'py>,
+    blocks: Vec<Option<Bound<'py, PyAny>>>,
+    P: Option<Bound<'py, PyAny>>,
+    Q: Option<Bound<'py, PyAny>>,
+) -> PyResult<Bound<'py, PyList>> {
+    let arrays: Vec<Option<Bound<'py, PyArrayDyn<u8>>>> = blocks
+        .iter()
+        .enumerate()
+        .map(|(i, b)| b.as_ref().map(|b| u8_array(b, &format!("blocks[{}]", i), 1)).transpose())
+        .collect::<PyResult<_>>()?;
+    let p = P.as_ref().map(|p| u8_array(p, "P", 1)).transpose()?;
+    let q = Q.as_ref().map(|q| u8_array(q, "Q", 1)).transpose()?;
+
+    let copies: Vec<Option<Vec<u8>>> = arrays
+        .iter()
+        .enumerate()
+        .map(|(i, a)| a.as_ref().map(|a| copied(a, &format!("blocks[{}]", i))).transpose())
+        .collect::<PyResult<_>>()?;
+    let p_bytes = p.as_ref().map(|p| copied(p, "P")).transpose()?;
+    let q_bytes = q.as_ref().map(|q| copied(q, "Q")).transpose()?;
+    let slices: Vec<Option<&[u8]>> = copies.iter().map(|c| c.as_deref()).collect();
+
+    let rebuilt = py
+        .allow_threads(|| raid_reconstruct(&slices, p_bytes.as_deref(), q_bytes.as_deref()))
+        .map_err(|e| raid_error(py, e))?;
+    let mut rebuilt = rebuilt.into_iter();
+    let out = PyList::empty(py);
+    for (block, array) in blocks.iter().zip(&arrays) {
+        match (block, array) {
+            (Some(block), Some(_)) => out.append(block)?,
+            _ => out.append(rebuilt.next().expect("one rebuilt block per None").into_pyarray(py))?,
+        }
+    }
+    Ok(out)
+}
+
+/// Small-write path: update P and Q in place for data block `index` changing from old_block to new_block
+#[pyfunction]
+#[pyo3(signature = (P, Q, index, old_block, new_block))]
+#[allow(non_snake_case)]
+fn update_pq(
+    py: Python<'_>,
+    P: &Bound<'_, PyAny>,
+    Q: &Bound<'_, PyAny>,
+    index: usize,
+    old_block: &Bound<'_, PyAny>,
+    new_block: &Bound<'_, PyAny>,
+) -> PyResult<()> {
+    let (p, q) = (u8_array(P, "P", 1)?, u8_array(Q, "Q", 1)?);
+    let (old, new) = (u8_array(old_block, "old_block", 1)?, u8_array(new_block, "new_block", 1)?);
+    let (old_bytes, new_bytes) = (copied(&old, "old_block")?, copied(&new, "new_block")?);
+    // The update is linear: work out what it adds to zeroed P and Q, then XOR that in
+    let (mut p_delta, mut q_delta) = (vec![0u8; p.len()], vec![0u8; q.len()]);
+    py.allow_threads(|| raid_update_pq(&mut p_delta, &mut q_delta, index, &old_bytes, &new_bytes)).map_err(|e| raid_error(py, e))?;
+    // Both borrowed before either is written, so a refused borrow leaves P and Q as they were
+    let mut p_guard = p.try_readwrite().map_err(|e| parameter_error(format!("P: {}", e)))?;
+    let mut q_guard = q.try_readwrite().map_err(|e| parameter_error(format!("Q: {}", e)))?;
+    for (guard, delta) in [(&mut p_guard, p_delta), (&mut q_guard, q_delta)] {
+        for (byte, d) in guard.as_slice_mut().expect("checked C-contiguous").iter_mut().zip(delta) {
+            *byte ^= d;
+        }
+    }
+    Ok(())
+}
+
+/// Build the submodule and make `import ecc_model._rs.raid` work as well as attribute access
+pub fn register(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = parent.py();
+    let m = PyModule::new(py, "raid")?;
+    m.gil_used(false)?;
+    m.add("MAX_DATA_BLOCKS", crate::raid::MAX_DATA_BLOCKS)?;
+    m.add_function(wrap_pyfunction!(compute_pq, &m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruct, &m)?)?;
+    m.add_function(wrap_pyfunction!(update_pq, &m)?)?;
+    parent.add_submodule(&m)?;
+    py.import("sys")?.getattr("modules")?.set_item("ecc_model._rs.raid", &m)?;
+    Ok(())
+}
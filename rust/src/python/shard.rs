@@ -0,0 +1,141 @@
+// ShardCodec bindings: split bytes into k data + m parity shards, rejoin from any k
+// Shards are passed as {index: bytes} or as a list with None for the missing ones. The matrix
+// work runs without the GIL when every shard buffer is read-only.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use super::errors::{ecc_error, length_error, parameter_error, uncorrectable_error};
+use super::{param_error, with_slices, BytesInput};
+use crate::rs::{RsError, ShardCodec, ShardError};
+
+/// ShardCodec(k, m): any k of the k + m shards recover the data
+/// split() prefixes the data with its length (u64 little-endian) and zero-pads it to k equal
+/// shards, so join() returns exactly the original bytes.
+#[pyclass(name = "ShardCodec", module = "ecc_model._rs", frozen)]
+pub struct PyShardCodec {
+    inner: ShardCodec,
+}
+
+/// {index: shard} or [shard | None, ...] -> one slot per shard
+fn extract_shards(ob: &Bound<'_, PyAny>, n: usize) -> PyResult<Vec<Option<BytesInput>>> {
+    let mut slots: Vec<Option<BytesInput>> = (0..n).map(|_| None).collect();
+    if let Ok(dict) = ob.downcast::<PyDict>() {
+        for (key, value) in dict.iter() {
+            let index: i64 = key.extract()?;
+            if index < 0 || index as usize >= n {
+                return Err(parameter_error(format!("shard index {} out of range for {} shards", index, n)));
+            }
+            slots[index as usize] = Some(value.extract()?);
+        }
+        return Ok(slots);
+    }
+    let items: Vec<Option<BytesInput>> = ob.extract()?;
+    if items.len() != n {
+        return Err(length_error(format!("expected {} shard slots, got {}", n, items.len())));
+    }
+    Ok(items)
+}
+
+impl PyShardCodec {
+    /// Run `f` on the shard slots, without the GIL if every present buffer is read-only
+    fn with_shards<R: Send>(
+        &self,
+        py: Python<'_>,
+        shards: &Bound<'_, PyAny>,
+        f: impl FnOnce(&[Option<&[u8]>]) -> Result<R, ShardError> + Send,
+    ) -> PyResult<R> {
+        let slots = extract_shards(shards, self.inner.total_shards())?;
+        let present: Vec<usize> = (0..slots.len()).filter(|&i| slots[i].is_some()).collect();
+        let inputs: Vec<BytesInput> = slots.into_iter().flatten().collect();
+        let n = self.inner.total_shards();
+        let result = with_slices(py, &inputs, |slices| {
+            let mut views: Vec<Option<&[u8]>> = vec![None; n];
+            for (&i, &slice) in present.iter().zip(slices) {
+                views[i] = Some(slice);
+            }
+            f(&views)
+        });
+        result.map_err(|e| self.shard_error(py, e))
+    }
+
+    fn shard_error(&self, py: Python<'_>, e: ShardError) -> PyErr {
+        match e {
+            ShardError::TooFewShards { present, .. } => uncorrectable_error(
+                py,
+                e.to_string(),
+                self.inner.total_shards() - present,
+                self.inner.parity_shards(),
+                None,
+                e.to_string(),
+            ),
+            ShardError::WrongCount { .. } | ShardError::LengthMismatch { .. } | ShardError::WindowOutOfRange { .. } => length_error(e.to_string()),
+            ShardError::BadLengthPrefix => ecc_error(e.to_string()),
+        }
+    }
+}
+
+#[pymethods]
+impl PyShardCodec {
+    #[new]
+    fn new(k: usize, m: usize) -> PyResult<Self> {
+        let inner = ShardCodec::new(k, m).map_err(|e| match e {
+            RsError::InvalidParameters(_) => parameter_error(format!("{} (k={}, m={})", e, k, m)),
+            e => param_error(e, m, k + m),
+        })?;
+        Ok(Self { inner })
+    }
+
+    #[getter]
+    fn k(&self) -> usize {
+        self.inner.data_shards()
+    }
+
+    #[getter]
+    fn m(&self) -> usize {
+        self.inner.parity_shards()
+    }
+
+    /// Total shards, k + m
+    #[getter]
+    fn n(&self) -> usize {
+        self.inner.total_shards()
+    }
+
+    /// Shard length split() produces for `length` bytes of data
+    fn shard_len(&self, length: usize) -> usize {
+        self.inner.shard_len(length)
+    }
+
+    /// k data shards followed by m parity shards, all shard_len(len(data)) bytes
+    fn split<'py>(&self, py: Python<'py>, data: BytesInput) -> Vec<Bound<'py, PyBytes>> {
+        let shards = data.with_slice(py, |d| self.inner.split(d));
+        shards.iter().map(|s| PyBytes::new(py, s)).collect()
+    }
+
+    /// The original data from any k shards of a split()
+    fn join<'py>(&self, py: Python<'py>, shards: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyBytes>> {
+        let data = self.with_shards(py, shards, |views| self.inner.join(views))?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// All k + m shards in index order, rebuilding the missing ones
+    fn reconstruct<'py>(&self, py: Python<'py>, shards: &Bound<'py, PyAny>) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+        let all = self.with_shards(py, shards, |views| self.inner.reconstruct(views))?;
+        Ok(all.iter().map(|s| PyBytes::new(py, s)).collect())
+    }
+
+    /// Bytes offset..offset + length of shard `index`, rebuilt from that window of k others
+    fn reconstruct_range<'py>(&self, py: Python<'py>, shards: &Bound<'py, PyAny>, index: usize, offset: usize, length: usize) -> PyResult<Bound<'py, PyBytes>> {
+        let n = self.inner.total_shards();
+        if index >= n {
+            return Err(parameter_error(format!("shard index {} out of range for {} shards", index, n)));
+        }
+        let window = self.with_shards(py, shards, |views| self.inner.reconstruct_range(views, index, offset, length))?;
+        Ok(PyBytes::new(py, &window))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ShardCodec(k={}, m={})", self.inner.data_shards(), self.inner.parity_shards())
+    }
+}
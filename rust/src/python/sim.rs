@@ -0,0 +1,115 @@
+// simulate(): Monte Carlo decoder statistics over a native or Python channel (`sim` feature)
+// Native channels run in parallel without the GIL. A Python channel is called once per trial on
+// the calling thread; only the corruption happens in Python, encode/decode/classify stay native.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyByteArray, PyDict};
+
+use super::errors::{length_error, parameter_error};
+use super::{check_params, type_name};
+use crate::rs::sim::{simulate as rs_simulate, simulate_with, ChannelModel, SimParams, SimReport};
+
+const CHANNELS: &str = "\"symbol_errors\", \"symbol_error_rate\", \"burst\", \"erasures\", \"bit_flips\" or \"bit_error_rate\"";
+
+/// ChannelModel for a native channel name and its `param`
+fn native_channel(name: &str, param: Option<&Bound<'_, PyAny>>, n: usize) -> PyResult<ChannelModel> {
+    let Some(param) = param else {
+        return Err(parameter_error(format!("channel {:?} needs param", name)));
+    };
+    let count = |limit: usize| -> PyResult<usize> {
+        let count: usize = param
+            .extract()
+            .map_err(|_| parameter_error(format!("channel {:?} needs an int param, got {}", name, param)))?;
+        if count > limit {
+            return Err(parameter_error(format!("channel {:?}: param={} exceeds {} for n={}", name, count, limit, n)));
+        }
+        Ok(count)
+    };
+    Ok(match name {
+        "symbol_errors" => ChannelModel::SymbolErrors(count(n)?),
+        "burst" => ChannelModel::Burst(count(n)?),
+        "erasures" => ChannelModel::Erasures(count(n)?),
+        "bit_flips" => ChannelModel::BitFlips(count(8 * n)?),
+        "symbol_error_rate" | "bit_error_rate" => {
+            let p: f64 = param.extract()?;
+            if !(0.0..=1.0).contains(&p) {
+                return Err(parameter_error(format!("channel {:?}: param={} must be in 0..=1", name, p)));
+            }
+            if name == "bit_error_rate" {
+                ChannelModel::BitErrorRate(p)
+            } else {
+                ChannelModel::SymbolErrorRate(p)
+            }
+        }
+        _ => return Err(parameter_error(format!("unknown channel {:?}: expected a callable or {}", name, CHANNELS))),
+    })
+}
+
+fn report_dict<'py>(py: Python<'py>, report: &SimReport, nsym: usize) -> PyResult<Bound<'py, PyDict>> {
+    let stats = &report.stats;
+    let dict = PyDict::new(py);
+    for (key, value) in [
+        ("trials", stats.trials),
+        ("clean", stats.clean),
+        ("corrected", stats.corrected),
+        ("detected", stats.detected),
+        ("miscorrected", stats.miscorrected),
+    ] {
+        dict.set_item(key, value)?;
+    }
+    dict.set_item("block_failure_rate", stats.block_failure_rate())?;
+    // Fixed length so runs with different outcomes line up index for index
+    let mut histogram = report.histogram.clone();
+    histogram.resize(histogram.len().max(nsym + 1), 0);
+    dict.set_item("histogram", histogram)?;
+    Ok(dict)
+}
+
+/// Encode `trials` random messages, pass each codeword through `channel`, decode, and count
+/// outcomes against the sent message: clean, corrected, detected, miscorrected, plus
+/// block_failure_rate and `histogram`, where histogram[i] counts trials the decoder accepted
+/// after correcting i symbols (length at least nsym + 1).
+/// `channel` is a native model name ("symbol_errors", "burst", "erasures", "bit_flips" with an
+/// int `param`, or "symbol_error_rate" / "bit_error_rate" with a probability) or a callable
+/// `channel(codeword: bytearray, rng_seed: int) -> None` that corrupts the codeword in place.
+/// Callables run serially on this thread; each rng_seed is derived from `seed` and the trial
+/// index, so a channel that only uses rng_seed for randomness gives reproducible results.
+#[pyfunction]
+#[pyo3(signature = (nsym, nsize, trials, channel, seed=0, param=None))]
+pub fn simulate<'py>(
+    py: Python<'py>,
+    nsym: usize,
+    nsize: usize,
+    trials: u64,
+    channel: &Bound<'py, PyAny>,
+    seed: u64,
+    param: Option<&Bound<'py, PyAny>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    check_params(nsym, nsize)?;
+    if let Ok(name) = channel.extract::<&str>() {
+        let params = SimParams { n: nsize, nsym, channel: native_channel(name, param, nsize)?, trials, seed };
+        let report = py.allow_threads(|| rs_simulate(&params));
+        return report_dict(py, &report, nsym);
+    }
+    if !channel.is_callable() {
+        return Err(parameter_error(format!("channel must be a callable or one of {}, got {}", CHANNELS, type_name(channel))));
+    }
+    if param.is_some() {
+        return Err(parameter_error("param applies only to native channels".into()));
+    }
+    let report = simulate_with(nsize, nsym, trials, seed, |codeword, rng_seed| -> PyResult<()> {
+        let block = PyByteArray::new(py, codeword);
+        channel.call1((&block, rng_seed))?;
+        if block.len() != codeword.len() {
+            return Err(length_error(format!(
+                "channel resized the codeword from {} to {} bytes; it must modify it in place",
+                codeword.len(), block.len()
+            )));
+        }
+        // to_vec copies out safely: the channel may have kept a reference to the bytearray and,
+        // without a GIL, be writing it from another thread
+        codeword.copy_from_slice(&block.to_vec());
+        Ok(())
+    })?;
+    report_dict(py, &report, nsym)
+}
@@ -0,0 +1,61 @@
+// The individual decoder stages, for stepping through a decode by hand
+// Conventions match rs.rs: syndromes are S_j = r(alpha^j); sigma has sigma[0] = 1 as the
+// constant term; position p of an n-byte codeword has locator X = alpha^(n-1-p).
+// Inputs are validated here so no combination of arguments can panic in the Rust code.
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use super::errors::{length_error, parameter_error};
+use super::BytesInput;
+use crate::rs::{berlekamp_massey as rs_berlekamp_massey, chien_search as rs_chien_search, forney as rs_forney};
+
+fn check_n(n: usize) -> PyResult<()> {
+    if n == 0 || n > 255 {
+        return Err(parameter_error(format!("n must be in 1..=255, got {}", n)));
+    }
+    Ok(())
+}
+
+fn check_sigma(sigma: &BytesInput) -> PyResult<()> {
+    match sigma.as_slice().first() {
+        None => Err(length_error("sigma must have at least the constant term".into())),
+        Some(&c) if c != 1 => Err(parameter_error(format!("sigma[0] must be 1 (got {})", c))),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Error locator sigma(x) from the syndromes, constant term first; its degree is the number
+/// of errors the decoder assumes (b"\x01" for all-zero syndromes)
+#[pyfunction]
+pub fn berlekamp_massey<'py>(py: Python<'py>, syndromes: BytesInput) -> Bound<'py, PyBytes> {
+    let sigma = syndromes.with_slice(py, rs_berlekamp_massey);
+    PyBytes::new(py, &sigma)
+}
+
+/// Positions p in 0..n with sigma(X_p^-1) == 0, ascending
+/// Fewer roots than deg(sigma) means the codeword is uncorrectable.
+#[pyfunction]
+pub fn chien_search(py: Python<'_>, sigma: BytesInput, n: usize) -> PyResult<Vec<usize>> {
+    check_n(n)?;
+    check_sigma(&sigma)?;
+    Ok(sigma.with_slice(py, |s| rs_chien_search(s, n)))
+}
+
+/// Error magnitudes at `positions`, one byte each: XOR them into the received word to correct it
+#[pyfunction]
+pub fn forney<'py>(
+    py: Python<'py>,
+    syndromes: BytesInput,
+    sigma: BytesInput,
+    positions: Vec<usize>,
+    n: usize,
+) -> PyResult<Bound<'py, PyBytes>> {
+    check_n(n)?;
+    check_sigma(&sigma)?;
+    if let Some(&pos) = positions.iter().find(|&&pos| pos >= n) {
+        return Err(parameter_error(format!("position {} out of range for n={}", pos, n)));
+    }
+    let magnitudes = rs_forney(syndromes.as_slice(), sigma.as_slice(), &positions, n);
+    Ok(PyBytes::new(py, &magnitudes))
+}
@@ -0,0 +1,62 @@
+// Per-RSCodec usage counters
+// Relaxed atomics: a codec is shared across threads with the GIL released, and the counters are
+// independent tallies that order no other memory, so each update is one fetch_add.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::rs::RsError;
+
+#[derive(Default)]
+pub struct CodecStats {
+    encoded: AtomicU64,
+    decoded: AtomicU64,
+    corrected: AtomicU64,
+    symbols: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl CodecStats {
+    /// `blocks` codewords encoded
+    pub fn record_encode(&self, blocks: usize) {
+        self.encoded.fetch_add(blocks as u64, Ordering::Relaxed);
+    }
+
+    /// One decoder run; `Ok` carries the corrected positions
+    pub fn record_decode<T>(&self, result: &Result<(T, Vec<usize>), RsError>) {
+        match result {
+            Ok((_, positions)) => self.record_success(positions.len()),
+            Err(_) => {
+                self.decoded.fetch_add(1, Ordering::Relaxed);
+                self.failures.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// One successful decoder run that corrected `symbols` symbols
+    pub fn record_success(&self, symbols: usize) {
+        self.decoded.fetch_add(1, Ordering::Relaxed);
+        if symbols > 0 {
+            self.corrected.fetch_add(1, Ordering::Relaxed);
+            self.symbols.fetch_add(symbols as u64, Ordering::Relaxed);
+        }
+    }
+
+    pub fn reset(&self) {
+        for counter in [&self.encoded, &self.decoded, &self.corrected, &self.symbols, &self.failures] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("blocks_encoded", self.encoded.load(Ordering::Relaxed))?;
+        dict.set_item("blocks_decoded", self.decoded.load(Ordering::Relaxed))?;
+        dict.set_item("blocks_corrected", self.corrected.load(Ordering::Relaxed))?;
+        dict.set_item("symbols_corrected", self.symbols.load(Ordering::Relaxed))?;
+        dict.set_item("decode_failures", self.failures.load(Ordering::Relaxed))?;
+        Ok(dict)
+    }
+}
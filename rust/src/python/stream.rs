@@ -0,0 +1,113 @@
+// Streaming bindings: feed chunks of any size, get back whole codewords / payload
+// The encoder and decoder keep less than one block buffered. Read-only chunks are processed
+// without the GIL; the object itself stays borrowed mutably for the call.
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use super::errors::length_error;
+use super::{param_error, BytesInput};
+use crate::rs::{StreamDecoder, StreamEncoder};
+
+/// StreamEncoder(nsym, nsize): feed(chunk) returns the codewords completed so far, flush()
+/// encodes the remainder as a final shortened codeword (len + nsym bytes)
+#[pyclass(name = "StreamEncoder", module = "ecc_model._rs")]
+pub struct PyStreamEncoder {
+    inner: StreamEncoder,
+}
+
+#[pymethods]
+impl PyStreamEncoder {
+    #[new]
+    fn new(nsym: usize, nsize: usize) -> PyResult<Self> {
+        Ok(Self { inner: StreamEncoder::new(nsym, nsize).map_err(|e| param_error(e, nsym, nsize))? })
+    }
+
+    /// Message bytes buffered until the next block fills
+    #[getter]
+    fn pending(&self) -> usize {
+        self.inner.pending()
+    }
+
+    fn feed<'py>(&mut self, py: Python<'py>, chunk: BytesInput) -> Bound<'py, PyBytes> {
+        let inner = &mut self.inner;
+        let out = chunk.with_slice(py, |c| inner.feed(c));
+        PyBytes::new(py, &out)
+    }
+
+    /// Emit the buffered bytes (b"" if none); the encoder can keep going afterwards
+    fn flush<'py>(&mut self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.inner.flush())
+    }
+
+    fn __repr__(&self) -> String {
+        let codec = self.inner.codec();
+        format!("StreamEncoder(nsym={}, nsize={}, pending={})", codec.nsym(), codec.n(), self.inner.pending())
+    }
+}
+
+/// StreamDecoder(nsym, nsize): feed(chunk) returns the corrected payload of every codeword
+/// completed so far, flush() decodes the final shortened codeword
+/// Uncorrectable codewords are passed through uncorrected and listed in failed_offsets.
+#[pyclass(name = "StreamDecoder", module = "ecc_model._rs")]
+pub struct PyStreamDecoder {
+    inner: StreamDecoder,
+}
+
+#[pymethods]
+impl PyStreamDecoder {
+    #[new]
+    fn new(nsym: usize, nsize: usize) -> PyResult<Self> {
+        Ok(Self { inner: StreamDecoder::new(nsym, nsize).map_err(|e| param_error(e, nsym, nsize))? })
+    }
+
+    /// Codeword bytes buffered until the next codeword fills
+    #[getter]
+    fn pending(&self) -> usize {
+        self.inner.pending()
+    }
+
+    /// Codewords decoded so far
+    #[getter]
+    fn blocks(&self) -> u64 {
+        self.inner.stats().blocks
+    }
+
+    #[getter]
+    fn corrected_symbols(&self) -> u64 {
+        self.inner.stats().corrected_symbols
+    }
+
+    /// Payload offsets of the codewords that could not be corrected
+    #[getter]
+    fn failed_offsets(&self) -> Vec<u64> {
+        self.inner.stats().failed_offsets.clone()
+    }
+
+    fn feed<'py>(&mut self, py: Python<'py>, chunk: BytesInput) -> Bound<'py, PyBytes> {
+        let inner = &mut self.inner;
+        let out = chunk.with_slice(py, |c| inner.feed(c));
+        PyBytes::new(py, &out)
+    }
+
+    /// Decode the buffered tail; LengthError (a truncated stream) if nsym bytes or fewer remain
+    fn flush<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let pending = self.inner.pending();
+        let out = self.inner.flush().map_err(|_| {
+            length_error(format!(
+                "stream ends with {} bytes, too short for a codeword with nsym={}",
+                pending, self.inner.codec().nsym()
+            ))
+        })?;
+        Ok(PyBytes::new(py, &out))
+    }
+
+    fn __repr__(&self) -> String {
+        let codec = self.inner.codec();
+        let stats = self.inner.stats();
+        format!(
+            "StreamDecoder(nsym={}, nsize={}, blocks={}, corrected_symbols={}, failed={})",
+            codec.nsym(), codec.n(), stats.blocks, stats.corrected_symbols, stats.failed_offsets.len()
+        )
+    }
+}
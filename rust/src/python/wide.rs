@@ -0,0 +1,359 @@
+// ecc_model._rs.RSCodec16: RS over GF(2^16) on numpy uint16 arrays (`numpy` feature)
+// Symbols come in as 1D C-contiguous uint16 arrays (2D, one row per block, for the batch
+// methods) and go back out the same way. As a convenience bytes, bytearray and memoryview are
+// accepted too, holding the symbols little-endian: symbol i is byte 2i (low) and byte 2i + 1
+// (high); results for them come back as bytes in that packing. The GF work runs with the GIL
+// released, on symbols copied out while it was held: numpy's borrow flags only guard Rust code,
+// and Python can write an array, or a view of it, at any time. A dtype, dimension or contiguity
+// mismatch is a TypeError.
+
+use numpy::ndarray::Array2;
+use numpy::{dtype, IntoPyArray, PyArray1, PyArray2, PyArrayDescrMethods, PyArrayMethods, PyReadonlyArray2, PyUntypedArray, PyUntypedArrayMethods};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyByteArray, PyBytes, PyIterator, PyMemoryView, PyTuple};
+
+use super::errors::{length_error, parameter_error, wide_decode_error};
+use super::{collect_batch_output, extract_positions, report_errors, type_name, BytesInput};
+use crate::rs_wide::{WideBlockResult, WideRs};
+
+/// The GF(2^16) field of every RSCodec16
+const M: u32 = 16;
+
+/// `ob` as a C-contiguous uint16 numpy array with `ndim` dimensions; `expected` names the
+/// accepted types for the error when it is not a numpy array at all
+fn u16_array<'py>(ob: &Bound<'py, PyAny>, name: &str, ndim: usize, expected: &str) -> PyResult<Bound<'py, PyUntypedArray>> {
+    // rust-numpy panics if numpy cannot be imported; without it nothing is an array anyway
+    let arr = match ob.py().import("numpy") {
+        Ok(_) => ob.downcast::<PyUntypedArray>().ok(),
+        Err(_) => None,
+    };
+    let arr = arr.ok_or_else(|| PyTypeError::new_err(format!("{} must be {}, got {}", name, expected, type_name(ob))))?;
+    if !arr.dtype().is_equiv_to(&dtype::<u16>(ob.py())) {
+        return Err(PyTypeError::new_err(format!("{} has dtype {}, expected uint16", name, arr.dtype())));
+    }
+    if arr.ndim() != ndim {
+        return Err(PyTypeError::new_err(format!("{} has {} dimensions {:?}, expected {}", name, arr.ndim(), arr.shape(), ndim)));
+    }
+    if !arr.is_c_contiguous() {
+        return Err(PyTypeError::new_err(format!("{} must be C-contiguous; pass numpy.ascontiguousarray({})", name, name)));
+    }
+    Ok(arr.clone())
+}
+
+/// Symbols as passed in, copied: from a uint16 array, or unpacked from little-endian bytes
+enum Symbols {
+    Array(Vec<u16>),
+    Packed(Vec<u16>),
+}
+
+impl Symbols {
+    fn extract(ob: &Bound<'_, PyAny>, name: &str) -> PyResult<Self> {
+        if !(ob.is_instance_of::<PyBytes>() || ob.is_instance_of::<PyByteArray>() || ob.is_instance_of::<PyMemoryView>()) {
+            let arr = u16_array(ob, name, 1, "a numpy array of dtype uint16 or a bytes-like object")?;
+            let arr = arr.into_any().downcast_into::<PyArray1<u16>>()?;
+            let arr = arr.try_readonly().map_err(|e| parameter_error(format!("{}: {}", name, e)))?;
+            return Ok(Symbols::Array(arr.as_slice().expect("checked C-contiguous").to_vec()));
+        }
+        let bytes: BytesInput = ob.extract()?;
+        let bytes = bytes.as_slice();
+        if !bytes.len().is_multiple_of(2) {
+            return Err(length_error(format!("{} has {} bytes, not a whole number of 2-byte symbols", name, bytes.len())));
+        }
+        Ok(Symbols::Packed(bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect()))
+    }
+
+    fn as_slice(&self) -> &[u16] {
+        match self {
+            Symbols::Array(symbols) | Symbols::Packed(symbols) => symbols,
+        }
+    }
+
+    fn packed(&self) -> bool {
+        matches!(self, Symbols::Packed(_))
+    }
+}
+
+/// Symbols in the form they came in: a uint16 array, or little-endian bytes
+fn symbols_out<'py>(py: Python<'py>, symbols: &[u16], packed: bool) -> Bound<'py, PyAny> {
+    if packed {
+        let bytes: Vec<u8> = symbols.iter().flat_map(|s| s.to_le_bytes()).collect();
+        PyBytes::new(py, &bytes).into_any()
+    } else {
+        symbols.to_vec().into_pyarray(py).into_any()
+    }
+}
+
+/// 2D (num_blocks, len) uint16 array, borrowed; copy it before releasing the GIL
+fn u16_rows<'py>(ob: &Bound<'py, PyAny>, name: &str) -> PyResult<PyReadonlyArray2<'py, u16>> {
+    let arr = u16_array(ob, name, 2, "a 2D numpy array of dtype uint16 with shape (num_blocks, block_len)")?;
+    arr.into_any().downcast_into::<PyArray2<u16>>()?.try_readonly().map_err(|e| parameter_error(format!("{}: {}", name, e)))
+}
+
+/// Detailed outcome of RSCodec16.decode_detailed, as DecodeResult is for RSCodec
+/// message, magnitudes and codeword come back in the form the codeword was passed in.
+#[pyclass(name = "DecodeResult16", module = "ecc_model._rs", frozen)]
+pub struct PyDecodeResult16 {
+    message: Vec<u16>,
+    #[pyo3(get)]
+    positions: Vec<usize>,
+    magnitudes: Vec<u16>,
+    codeword: Option<Vec<u16>>,
+    /// The erase_pos hints, sorted
+    erasures: Vec<usize>,
+    n: usize,
+    nsym: usize,
+    packed: bool,
+}
+
+#[pymethods]
+impl PyDecodeResult16 {
+    #[getter]
+    fn message<'py>(&self, py: Python<'py>) -> Bound<'py, PyAny> {
+        symbols_out(py, &self.message, self.packed)
+    }
+
+    /// Error value XORed into each corrected position, aligned with `positions`
+    #[getter]
+    fn magnitudes<'py>(&self, py: Python<'py>) -> Bound<'py, PyAny> {
+        symbols_out(py, &self.magnitudes, self.packed)
+    }
+
+    #[getter]
+    fn corrected(&self) -> bool {
+        !self.positions.is_empty()
+    }
+
+    #[getter]
+    fn num_corrected(&self) -> usize {
+        self.positions.len()
+    }
+
+    #[getter]
+    fn codeword<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyAny>> {
+        self.codeword.as_ref().map(|c| symbols_out(py, c, self.packed))
+    }
+
+    /// Corrected positions that were passed in erase_pos
+    #[getter]
+    fn erasure_positions(&self) -> Vec<usize> {
+        self.positions.iter().copied().filter(|p| self.erasures.binary_search(p).is_ok()).collect()
+    }
+
+    /// Corrected positions the decoder found on its own
+    #[getter]
+    fn error_positions(&self) -> Vec<usize> {
+        self.positions.iter().copied().filter(|p| self.erasures.binary_search(p).is_err()).collect()
+    }
+
+    /// Hinted positions whose symbol turned out to be correct
+    #[getter]
+    fn unused_erasures(&self) -> Vec<usize> {
+        self.erasures.iter().copied().filter(|p| !self.positions.contains(p)).collect()
+    }
+
+    /// (position, bit) pairs flipped at error_positions, bit 0 = LSB, bits 0..16
+    #[getter]
+    fn bit_errors(&self) -> Vec<(usize, u8)> {
+        let mut bits: Vec<(usize, u8)> = self
+            .positions
+            .iter()
+            .zip(&self.magnitudes)
+            .filter(|(p, _)| self.erasures.binary_search(p).is_err())
+            .flat_map(|(&pos, &mag)| (0..16u8).filter(move |b| mag >> b & 1 != 0).map(move |b| (pos, b)))
+            .collect();
+        bits.sort_unstable();
+        bits
+    }
+
+    /// (position, is_parity, message_index) for each of `positions`; message_index is None for parity
+    #[getter]
+    fn locations(&self) -> Vec<(usize, bool, Option<usize>)> {
+        let k = self.n - self.nsym;
+        self.positions.iter().map(|&p| (p, p >= k, (p < k).then_some(p))).collect()
+    }
+
+    /// Corrected positions in the parity symbols
+    #[getter]
+    fn parity_positions(&self) -> Vec<usize> {
+        self.positions.iter().copied().filter(|&p| p >= self.n - self.nsym).collect()
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        (self.message(py), self.positions.clone()).into_pyobject(py)?.try_iter()
+    }
+
+    fn __repr__(&self) -> String {
+        let codeword = match &self.codeword {
+            Some(c) => format!("<{} symbols>", c.len()),
+            None => "None".into(),
+        };
+        format!(
+            "DecodeResult16(num_corrected={}, positions={:?}, magnitudes={:?}, message=<{} symbols>, codeword={})",
+            self.positions.len(), self.positions, self.magnitudes, self.message.len(), codeword
+        )
+    }
+}
+
+/// Reed-Solomon codec over GF(2^16) (x^16 + x^12 + x^3 + x + 1), nsize up to 65535 symbols
+/// Same conventions as RSCodec with 16-bit symbols: systematic, position 0 first, generator
+/// roots alpha^0 .. alpha^(nsym-1). Positions and lengths count symbols, not bytes.
+#[pyclass(name = "RSCodec16", module = "ecc_model._rs", frozen)]
+pub struct PyRsCodec16 {
+    rs: WideRs,
+}
+
+impl PyRsCodec16 {
+    fn check_len(&self, what: &str, len: usize, expected: usize, name: &str) -> PyResult<()> {
+        if len != expected {
+            return Err(length_error(format!("{} length {} does not match expected {}={}", what, len, name, expected)));
+        }
+        Ok(())
+    }
+
+    fn erasures(&self, erase_pos: Option<&Bound<'_, PyAny>>) -> PyResult<Option<Vec<usize>>> {
+        erase_pos.map(|ob| extract_positions(ob, self.rs.n())).transpose()
+    }
+}
+
+#[pymethods]
+impl PyRsCodec16 {
+    #[new]
+    fn new(nsym: usize, nsize: usize) -> PyResult<Self> {
+        let rs = WideRs::new(M, nsize, nsym)
+            .ok_or_else(|| parameter_error(format!("RSCodec16 needs 1 <= nsym < nsize <= 65535 (nsym={}, nsize={})", nsym, nsize)))?;
+        Ok(Self { rs })
+    }
+
+    #[getter]
+    fn nsym(&self) -> usize {
+        self.rs.nsym()
+    }
+
+    #[getter]
+    fn n(&self) -> usize {
+        self.rs.n()
+    }
+
+    #[getter]
+    fn k(&self) -> usize {
+        self.rs.k()
+    }
+
+    /// Encode k symbols into an n-symbol codeword
+    fn encode<'py>(&self, py: Python<'py>, data: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let message = Symbols::extract(data, "data")?;
+        let symbols = message.as_slice();
+        self.check_len("message", symbols.len(), self.rs.k(), "k")?;
+        let codeword = py.allow_threads(|| self.rs.encode(symbols));
+        Ok(symbols_out(py, &codeword, message.packed()))
+    }
+
+    /// Decode an n-symbol codeword, returning (message, corrected positions)
+    /// With `erase_pos` (known-bad symbol positions) e errors plus h erasures with 2e + h <= nsym
+    /// are corrected and the result is (message, positions, erased), as for RSCodec.decode.
+    #[pyo3(signature = (codeword, erase_pos=None))]
+    fn decode<'py>(&self, py: Python<'py>, codeword: &Bound<'py, PyAny>, erase_pos: Option<&Bound<'py, PyAny>>) -> PyResult<Bound<'py, PyTuple>> {
+        let received = Symbols::extract(codeword, "codeword")?;
+        let cw = received.as_slice();
+        self.check_len("codeword", cw.len(), self.rs.n(), "n")?;
+        let erasures = self.erasures(erase_pos)?;
+        let hints = erasures.as_deref().unwrap_or_default();
+        let (message, positions) = py
+            .allow_threads(|| self.rs.decode_errata(cw, hints))
+            .map_err(|e| wide_decode_error(py, e, &self.rs, cw, ""))?;
+        let message = symbols_out(py, &message, received.packed());
+        match erasures {
+            None => (message, positions).into_pyobject(py),
+            Some(erasures) => {
+                let erased: Vec<usize> = positions.iter().copied().filter(|p| erasures.contains(p)).collect();
+                (message, positions, erased).into_pyobject(py)
+            }
+        }
+    }
+
+    /// Decode and return a DecodeResult16 (magnitudes, corrected codeword, erasure breakdown)
+    #[pyo3(signature = (codeword, erase_pos=None))]
+    fn decode_detailed(&self, py: Python<'_>, codeword: &Bound<'_, PyAny>, erase_pos: Option<&Bound<'_, PyAny>>) -> PyResult<PyDecodeResult16> {
+        let received = Symbols::extract(codeword, "codeword")?;
+        let cw = received.as_slice();
+        self.check_len("codeword", cw.len(), self.rs.n(), "n")?;
+        let mut erasures = self.erasures(erase_pos)?.unwrap_or_default();
+        let fixes = py
+            .allow_threads(|| self.rs.corrections(cw, &erasures))
+            .map_err(|e| wide_decode_error(py, e, &self.rs, cw, ""))?;
+        let fixed = (!fixes.is_empty()).then(|| {
+            let mut fixed = cw.to_vec();
+            for &(pos, magnitude) in &fixes {
+                fixed[pos] ^= magnitude;
+            }
+            fixed
+        });
+        let message = fixed.as_deref().unwrap_or(cw)[..self.rs.k()].to_vec();
+        let (positions, magnitudes) = fixes.into_iter().unzip();
+        erasures.sort_unstable();
+        Ok(PyDecodeResult16 { message, positions, magnitudes, codeword: fixed, erasures, n: self.rs.n(), nsym: self.rs.nsym(), packed: received.packed() })
+    }
+
+    /// True if the codeword is valid (all syndromes zero)
+    fn check(&self, py: Python<'_>, codeword: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let received = Symbols::extract(codeword, "codeword")?;
+        let cw = received.as_slice();
+        self.check_len("codeword", cw.len(), self.rs.n(), "n")?;
+        Ok(py.allow_threads(|| self.rs.syndromes(cw).iter().all(|&s| s == 0)))
+    }
+
+    /// The nsym syndromes S_j = r(alpha^j), in the form the codeword was passed in
+    fn syndromes<'py>(&self, py: Python<'py>, codeword: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let received = Symbols::extract(codeword, "codeword")?;
+        let cw = received.as_slice();
+        self.check_len("codeword", cw.len(), self.rs.n(), "n")?;
+        let syndromes = py.allow_threads(|| self.rs.syndromes(cw));
+        Ok(symbols_out(py, &syndromes, received.packed()))
+    }
+
+    /// Encode a (num_blocks, k) uint16 array into (num_blocks, n)
+    /// parallel=True fans the blocks out over the rayon pool.
+    #[pyo3(signature = (messages, parallel=false))]
+    fn encode_batch<'py>(&self, py: Python<'py>, messages: &Bound<'py, PyAny>, parallel: bool) -> PyResult<Bound<'py, PyArray2<u16>>> {
+        let arr = u16_rows(messages, "messages")?;
+        let (rows, k) = (arr.shape()[0], arr.shape()[1]);
+        self.check_len("message", k, self.rs.k(), "k")?;
+        let data = arr.as_slice().expect("checked C-contiguous").to_vec();
+        let flat: Vec<u16> = py.allow_threads(|| {
+            let blocks: Vec<&[u16]> = data.chunks(k.max(1)).collect();
+            self.rs.encode_batch(&blocks, parallel).concat()
+        });
+        Ok(Array2::from_shape_vec((rows, self.rs.n()), flat).expect("rows * n codeword symbols").into_pyarray(py))
+    }
+
+    /// Decode each row of a (num_blocks, n) uint16 array with the same erase_pos, returning one
+    /// (message, positions) per block; failures raise or are reported as in decode_batch
+    #[pyo3(signature = (codewords, erase_pos=None, parallel=false, errors="raise"))]
+    fn decode_batch<'py>(
+        &self,
+        py: Python<'py>,
+        codewords: &Bound<'py, PyAny>,
+        erase_pos: Option<&Bound<'py, PyAny>>,
+        parallel: bool,
+        errors: &str,
+    ) -> PyResult<Vec<Bound<'py, PyAny>>> {
+        let report = report_errors(errors)?;
+        let arr = u16_rows(codewords, "codewords")?;
+        let n = self.rs.n();
+        if arr.shape()[1] != n {
+            return Err(length_error(format!("codewords have length {}, expected n={}", arr.shape()[1], n)));
+        }
+        let erasures = self.erasures(erase_pos)?.unwrap_or_default();
+        let data = arr.as_slice().expect("checked C-contiguous").to_vec();
+        let blocks: Vec<&[u16]> = data.chunks(n).collect();
+        let results: Vec<WideBlockResult> = py.allow_threads(|| self.rs.decode_batch(&blocks, &erasures, parallel));
+        collect_batch_output(py, results, report, |message| message.into_pyarray(py).into_any(), |i, e| {
+            wide_decode_error(py, e, &self.rs, blocks[i], &format!("codeword {}: ", i))
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RSCodec16(nsym={}, nsize={})", self.rs.nsym(), self.rs.n())
+    }
+}
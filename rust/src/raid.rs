@@ -0,0 +1,255 @@
+// RAID-6 P/Q parity over GF(256) with primitive polynomial 0x11d, as in the Linux md driver
+// P is the XOR of the k data blocks and Q = sum g^i * D_i with g = 2, so any two of the k + 2
+// blocks can be lost. Byte j of every block is an independent stripe; k is at most 255 because
+// g has order 255.
+
+use std::fmt;
+
+use crate::gf256::{gf_div, gf_mul, simd, GF};
+
+/// Largest number of data blocks Q can tell apart
+pub const MAX_DATA_BLOCKS: usize = 255;
+
+/// A member of the stripe set, for error messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Block {
+    Data(usize),
+    P,
+    Q,
+}
+
+impl fmt::Display for Block {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Block::Data(i) => write!(f, "blocks[{}]", i),
+            Block::P => f.write_str("P"),
+            Block::Q => f.write_str("Q"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaidError {
+    /// k must be in 1..=MAX_DATA_BLOCKS
+    BlockCount(usize),
+    /// update_pq index past the last data block Q can address
+    IndexOutOfRange(usize),
+    /// Every present block, P and Q must have one length
+    LengthMismatch { block: Block, len: usize, expected: usize },
+    /// More than two of the k data blocks, P and Q are missing
+    TooManyMissing(usize),
+}
+
+impl fmt::Display for RaidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaidError::BlockCount(k) => write!(f, "need 1..={} data blocks, got {}", MAX_DATA_BLOCKS, k),
+            RaidError::IndexOutOfRange(i) => write!(f, "block index {} out of range 0..{}", i, MAX_DATA_BLOCKS),
+            RaidError::LengthMismatch { block, len, expected } => {
+                write!(f, "{} has length {}, expected blocklen={}", block, len, expected)
+            }
+            RaidError::TooManyMissing(missing) => {
+                write!(f, "{} blocks missing (counting P and Q), at most 2 can be rebuilt", missing)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RaidError {}
+
+/// x * g for every byte, g = 2
+#[inline]
+fn mul2(x: u8) -> u8 {
+    (x << 1) ^ if x & 0x80 != 0 { 0x1d } else { 0 }
+}
+
+/// dst[i] ^= c * src[i]
+fn mul_add(dst: &mut [u8], src: &[u8], c: u8) {
+    simd::mul_add_slice(dst, src, c)
+}
+
+/// P and Q over the data blocks, missing ones counted as zero (Horner's rule for Q)
+fn partial_pq(blocks: &[Option<&[u8]>], len: usize) -> (Vec<u8>, Vec<u8>) {
+    let mut p = vec![0u8; len];
+    let mut q = vec![0u8; len];
+    for block in blocks.iter().rev() {
+        match block {
+            Some(data) => {
+                for ((p, q), &d) in p.iter_mut().zip(q.iter_mut()).zip(*data) {
+                    *p ^= d;
+                    *q = mul2(*q) ^ d;
+                }
+            }
+            None => q.iter_mut().for_each(|q| *q = mul2(*q)),
+        }
+    }
+    (p, q)
+}
+
+fn check_len(block: Block, data: &[u8], expected: usize) -> Result<(), RaidError> {
+    if data.len() != expected {
+        return Err(RaidError::LengthMismatch { block, len: data.len(), expected });
+    }
+    Ok(())
+}
+
+/// (P, Q) for k equal-length data blocks
+pub fn compute_pq(blocks: &[&[u8]]) -> Result<(Vec<u8>, Vec<u8>), RaidError> {
+    if blocks.is_empty() || blocks.len() > MAX_DATA_BLOCKS {
+        return Err(RaidError::BlockCount(blocks.len()));
+    }
+    let len = blocks[0].len();
+    for (i, block) in blocks.iter().enumerate() {
+        check_len(Block::Data(i), block, len)?;
+    }
+    let present: Vec<Option<&[u8]>> = blocks.iter().map(|&b| Some(b)).collect();
+    Ok(partial_pq(&present, len))
+}
+
+/// The missing data blocks, one per None in index order, from the rest of the stripe set
+/// Any two of the data blocks, P and Q may be missing; lost parity is not rebuilt here.
+pub fn reconstruct(blocks: &[Option<&[u8]>], p: Option<&[u8]>, q: Option<&[u8]>) -> Result<Vec<Vec<u8>>, RaidError> {
+    if blocks.is_empty() || blocks.len() > MAX_DATA_BLOCKS {
+        return Err(RaidError::BlockCount(blocks.len()));
+    }
+    let missing: Vec<usize> = (0..blocks.len()).filter(|&i| blocks[i].is_none()).collect();
+    let lost = missing.len() + p.is_none() as usize + q.is_none() as usize;
+    if lost > 2 {
+        return Err(RaidError::TooManyMissing(lost));
+    }
+    let members = blocks.iter().enumerate().filter_map(|(i, b)| b.map(|b| (Block::Data(i), b)));
+    let mut members = members.chain(p.map(|p| (Block::P, p))).chain(q.map(|q| (Block::Q, q))).peekable();
+    let len = members.peek().map_or(0, |(_, b)| b.len());
+    for (block, data) in members {
+        check_len(block, data, len)?;
+    }
+    if missing.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Syndromes: what the missing blocks contribute to P and Q
+    let (mut pxy, mut qxy) = partial_pq(blocks, len);
+    if let Some(p) = p {
+        mul_add(&mut pxy, p, 1);
+    }
+    if let Some(q) = q {
+        mul_add(&mut qxy, q, 1);
+    }
+    let g = |i: usize| GF.exp[i];
+    match (missing.as_slice(), p) {
+        (&[_], Some(_)) => Ok(vec![pxy]),
+        (&[x], None) => {
+            // Q syndrome = g^x * D_x
+            let mut dx = vec![0u8; len];
+            mul_add(&mut dx, &qxy, gf_div(1, g(x)));
+            Ok(vec![dx])
+        }
+        (&[x, y], _) => {
+            // Pxy = Dx + Dy, Qxy = g^x Dx + g^y Dy, so Dx = A Pxy + B Qxy with
+            // A = g^(y-x) / (g^(y-x) + 1) and B = g^-x / (g^(y-x) + 1)
+            let denom = g(y - x) ^ 1;
+            let mut dx = vec![0u8; len];
+            mul_add(&mut dx, &pxy, gf_div(g(y - x), denom));
+            mul_add(&mut dx, &qxy, gf_div(gf_div(1, g(x)), denom));
+            mul_add(&mut pxy, &dx, 1);
+            Ok(vec![dx, pxy])
+        }
+        _ => unreachable!("at most two data blocks are missing"),
+    }
+}
+
+/// Small-write path: fold the change of data block `index` from `old` to `new` into P and Q
+pub fn update_pq(p: &mut [u8], q: &mut [u8], index: usize, old: &[u8], new: &[u8]) -> Result<(), RaidError> {
+    if index >= MAX_DATA_BLOCKS {
+        return Err(RaidError::IndexOutOfRange(index));
+    }
+    let len = p.len();
+    check_len(Block::Q, q, len)?;
+    check_len(Block::Data(index), old, len)?;
+    check_len(Block::Data(index), new, len)?;
+    let coef = GF.exp[index];
+    let row: Vec<u8> = (0..=255u8).map(|x| gf_mul(coef, x)).collect();
+    for (((p, q), &o), &n) in p.iter_mut().zip(q.iter_mut()).zip(old).zip(new) {
+        let delta = o ^ n;
+        *p ^= delta;
+        *q ^= row[delta as usize];
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    fn random_blocks(k: usize, len: usize, seed: u64) -> Vec<Vec<u8>> {
+        let mut rng = Rng::new(seed);
+        (0..k).map(|_| (0..len).map(|_| rng.next_u64() as u8).collect()).collect()
+    }
+
+    #[test]
+    fn test_pq_matches_definition() {
+        let blocks = random_blocks(6, 64, 351);
+        let refs: Vec<&[u8]> = blocks.iter().map(|b| b.as_slice()).collect();
+        let (p, q) = compute_pq(&refs).unwrap();
+        for j in 0..64 {
+            let column: Vec<u8> = blocks.iter().map(|b| b[j]).collect();
+            assert_eq!(p[j], column.iter().fold(0, |acc, &d| acc ^ d));
+            assert_eq!(q[j], column.iter().enumerate().fold(0, |acc, (i, &d)| acc ^ gf_mul(GF.exp[i], d)));
+        }
+    }
+
+    #[test]
+    fn test_any_two_lost_are_rebuilt() {
+        let k = 7;
+        let blocks = random_blocks(k, 100, 6);
+        let refs: Vec<&[u8]> = blocks.iter().map(|b| b.as_slice()).collect();
+        let (p, q) = compute_pq(&refs).unwrap();
+        // Members 0..k are data, k is P and k + 1 is Q
+        for a in 0..k + 2 {
+            for b in a..k + 2 {
+                let lost = |i: usize| i == a || i == b;
+                let present: Vec<Option<&[u8]>> = (0..k).map(|i| (!lost(i)).then_some(refs[i])).collect();
+                let p = (!lost(k)).then_some(p.as_slice());
+                let q = (!lost(k + 1)).then_some(q.as_slice());
+                let expected: Vec<Vec<u8>> = (0..k).filter(|&i| lost(i)).map(|i| blocks[i].clone()).collect();
+                assert_eq!(reconstruct(&present, p, q).unwrap(), expected, "lost {} and {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_pq_matches_recompute() {
+        let mut blocks = random_blocks(5, 32, 12);
+        let refs: Vec<&[u8]> = blocks.iter().map(|b| b.as_slice()).collect();
+        let (mut p, mut q) = compute_pq(&refs).unwrap();
+        let new = random_blocks(1, 32, 13).remove(0);
+        update_pq(&mut p, &mut q, 3, &blocks[3], &new).unwrap();
+        blocks[3] = new;
+        let refs: Vec<&[u8]> = blocks.iter().map(|b| b.as_slice()).collect();
+        assert_eq!(compute_pq(&refs).unwrap(), (p, q));
+    }
+
+    #[test]
+    fn test_raid_errors() {
+        let block = [0u8; 4];
+        assert_eq!(compute_pq(&[]), Err(RaidError::BlockCount(0)));
+        assert_eq!(
+            compute_pq(&[&block, &block[..3]]),
+            Err(RaidError::LengthMismatch { block: Block::Data(1), len: 3, expected: 4 })
+        );
+        assert_eq!(reconstruct(&[None, None, Some(&block)], Some(&block), None), Err(RaidError::TooManyMissing(3)));
+        assert_eq!(
+            reconstruct(&[None, Some(&block)], Some(&block[..2]), None),
+            Err(RaidError::LengthMismatch { block: Block::P, len: 2, expected: 4 })
+        );
+        assert_eq!(reconstruct(&[Some(&block)], Some(&block), Some(&block)), Ok(vec![]));
+        let (mut p, mut q) = ([0u8; 4], [0u8; 4]);
+        assert_eq!(update_pq(&mut p, &mut q, 255, &block, &block), Err(RaidError::IndexOutOfRange(255)));
+        assert_eq!(
+            update_pq(&mut p, &mut q[..3], 0, &block, &block),
+            Err(RaidError::LengthMismatch { block: Block::Q, len: 3, expected: 4 })
+        );
+        assert_eq!(RaidError::TooManyMissing(3).to_string(), "3 blocks missing (counting P and Q), at most 2 can be rebuilt");
+    }
+}
@@ -3,7 +3,322 @@
 // Polynomial convention: coeff[0] is constant term (x^0), coeff[i] is x^i coefficient
 // Codeword position mapping: position 0 = highest power of x (first byte = x^(n-1) coefficient)
 
-use crate::gf256::{gf_mul, gf_div, gf_inv, poly_mul, GF};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "std")]
+use crate::crc::Crc;
+#[cfg(feature = "std")]
+use crate::gf256::simd::{self, Kernel};
+#[cfg(feature = "std")]
+use crate::gf256::{gf_div, poly_mul};
+use crate::gf256::{gf_mul, GF};
+#[cfg(feature = "std")]
+use crate::gf2m::Field;
+use crate::opcount;
+#[cfg(feature = "std")]
+use crate::opcount::Stage;
+#[cfg(feature = "std")]
+use crate::trace::{event, span};
+
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod chunked;
+#[cfg(feature = "std")]
+pub mod codec;
+#[cfg(feature = "std")]
+pub mod codeword;
+#[cfg(feature = "std")]
+pub mod container;
+#[cfg(feature = "std")]
+pub mod custom;
+#[cfg(feature = "std")]
+pub mod decoder;
+#[cfg(feature = "std")]
+pub mod detached;
+#[cfg(feature = "std")]
+pub mod file;
+pub mod fixed;
+pub mod in_place;
+#[cfg(feature = "std")]
+pub mod interleave;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "std")]
+pub mod list;
+#[cfg(feature = "std")]
+pub mod meta;
+#[cfg(feature = "std")]
+pub mod plan;
+#[cfg(feature = "std")]
+pub mod recovery;
+#[cfg(feature = "std")]
+pub mod shard;
+#[cfg(feature = "std")]
+pub mod split;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(feature = "std")]
+pub mod verify;
+#[cfg(feature = "std")]
+pub mod welch;
+
+#[cfg(feature = "std")]
+pub use analysis::{min_distance_exhaustive, verify_min_distance, weight_enumerator, DistanceReport};
+#[cfg(feature = "std")]
+pub use batch::{
+    decode_batch, decode_batch_screen, decode_batch_screen_with, decode_batch_with, decode_blocks_into, encode_batch, encode_batch_soa,
+    encode_batch_with, encode_blocks_into, BatchOutcome, BlockResult,
+};
+#[cfg(feature = "std")]
+pub use chunked::ChunkedDecode;
+#[cfg(feature = "std")]
+pub use codec::{CodecParams, DecodeOptions, DecodeOutcome, RsCodec, SymbolOrder};
+#[cfg(feature = "std")]
+pub use codeword::{Codeword, CorrectionSummary};
+#[cfg(feature = "std")]
+pub use custom::{CodeOptions, CustomRs};
+#[cfg(feature = "std")]
+pub use decoder::RsDecoder;
+#[cfg(feature = "std")]
+pub use detached::{corrections_split, syndromes_split};
+#[cfg(feature = "std")]
+pub use file::{decode_file, encode_file, encode_file_interleaved, repair_file, verify_file, DecodeFileReport, FileError, FileHeader, RepairReport, VerifyFileReport};
+pub use fixed::{ErrorPositions, RsCode};
+pub use in_place::{decode_in_place, encode_into, generator_into, parity_into};
+#[cfg(feature = "std")]
+pub use interleave::{decode_interleaved, encode_interleaved, InterleavedDecode, Interleaver};
+#[cfg(feature = "std")]
+pub use io::{RsReader, RsWriter};
+#[cfg(feature = "std")]
+pub use list::{decode_list, Candidate, MAX_LIST_HYPOTHESES};
+#[cfg(feature = "std")]
+pub use meta::{MetaCodec, META_SYMBOL};
+#[cfg(feature = "std")]
+pub use plan::{binomial_tail, block_failure_rate, plan, Burst, Plan};
+#[cfg(feature = "std")]
+pub use shard::{ShardCodec, ShardError};
+#[cfg(feature = "std")]
+pub use split::{cached_split_tables, SplitTables};
+#[cfg(feature = "std")]
+pub use stream::{Pipeline, StreamDecoder, StreamEncoder, StreamStats};
+#[cfg(feature = "std")]
+pub use verify::{verify_exhaustive, Counterexample, VerifyReport};
+#[cfg(feature = "std")]
+pub use welch::decode_bw;
+
+/// Decode failure modes
+/// With `serde` this is {"kind": variant} plus "detail" for the variants that carry data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "kind", content = "detail"))]
+pub enum RsError {
+    CodewordTooShort,
+    TrivialLocator,
+    TooManyErrors,
+    ChienSearchFailed,
+    VerificationFailed,
+    /// RS decode succeeded but a check beyond the code failed (the inner CRC, reserved metadata
+    /// bits): the decoder landed on the wrong codeword
+    MiscorrectionDetected,
+    /// Erasure position out of range or listed twice
+    InvalidErasure,
+    /// (nsym, nsize) outside 1 <= nsym < nsize <= 255; names the violated constraint
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "intern_constraint"))]
+    InvalidParameters(&'static str),
+    /// The decoder located `found` errors, within nsym / 2 but over DecodeOptions::max_correct
+    CorrectionLimit { found: usize, limit: usize },
+    /// decode_bounded: the corrections touched `groups` device groups where the fault model allows one
+    FaultModelViolation { groups: usize },
+    /// decode_bw: the Berlekamp-Welch system has no solution; `rank` is its coefficient rank
+    SingularSystem { rank: usize, unknowns: usize },
+    /// decode_truncated: `missing` trailing symbols were lost, more than the nsym erasures fill
+    Truncated { missing: usize, nsym: usize },
+    /// The decoder located errors at positions DecodeOptions::pinned asserts are correct, so the
+    /// decode is a miscorrection; `positions` are the offending ones
+    PinnedPositionConflict { positions: PositionSet },
+}
+
+impl fmt::Display for RsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            RsError::CodewordTooShort => "codeword too short",
+            RsError::TrivialLocator => "nonzero syndrome but trivial locator",
+            RsError::TooManyErrors => "too many errors",
+            RsError::ChienSearchFailed => "Chien search failed",
+            RsError::VerificationFailed => "verification failed",
+            RsError::MiscorrectionDetected => "miscorrection detected: check failed after RS correction",
+            RsError::InvalidErasure => "erasure position out of range or repeated",
+            RsError::InvalidParameters(constraint) => return write!(f, "invalid RS parameters: {}", constraint),
+            RsError::CorrectionLimit { found, limit } => {
+                return write!(f, "{} errors located, more than max_correct={} allows", found, limit)
+            }
+            RsError::FaultModelViolation { groups } => {
+                return write!(f, "corrections span {} device groups, the fault model allows one", groups)
+            }
+            RsError::SingularSystem { rank, unknowns } => {
+                return write!(f, "Berlekamp-Welch system has no solution (rank {} in {} unknowns)", rank, unknowns)
+            }
+            RsError::Truncated { missing, nsym } => {
+                return write!(f, "{} trailing symbols missing, more than nsym={} erasures can fill", missing, nsym)
+            }
+            RsError::PinnedPositionConflict { positions } => return write!(f, "errors located at pinned positions {:?}", positions),
+        };
+        f.write_str(msg)
+    }
+}
+
+impl core::error::Error for RsError {}
+
+/// A set of codeword positions 0..=255 held as a bitmap, so DecodeOptions and RsError stay Copy
+/// and allocation-free; iterates in ascending order. With `serde` it is a list of positions.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(into = "Vec<usize>", try_from = "Vec<usize>"))]
+pub struct PositionSet([u64; 4]);
+
+impl PositionSet {
+    pub const EMPTY: PositionSet = PositionSet([0; 4]);
+
+    /// The set of `positions`; InvalidParameters if one is 256 or more (outside any GF(256)
+    /// codeword)
+    pub fn new(positions: &[usize]) -> Result<Self, RsError> {
+        let mut set = Self::EMPTY;
+        for &pos in positions {
+            if pos > 255 {
+                return Err(RsError::InvalidParameters("positions must be below 256"));
+            }
+            set.0[pos / 64] |= 1 << (pos % 64);
+        }
+        Ok(set)
+    }
+
+    pub fn contains(&self, pos: usize) -> bool {
+        pos < 256 && self.0[pos / 64] >> (pos % 64) & 1 != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// Largest position in the set
+    pub fn max(&self) -> Option<usize> {
+        self.iter().last()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..256).filter(|&pos| self.contains(pos))
+    }
+}
+
+impl fmt::Debug for PositionSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<PositionSet> for Vec<usize> {
+    fn from(set: PositionSet) -> Vec<usize> {
+        set.iter().collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Vec<usize>> for PositionSet {
+    type Error = RsError;
+
+    fn try_from(positions: Vec<usize>) -> Result<Self, RsError> {
+        PositionSet::new(&positions)
+    }
+}
+
+/// PinnedPositionConflict if any of the corrected `positions` is pinned
+pub fn check_pinned(positions: &[usize], pinned: &PositionSet) -> Result<(), RsError> {
+    let mut conflicts = PositionSet::EMPTY;
+    for &pos in positions.iter().filter(|&&pos| pinned.contains(pos)) {
+        conflicts.0[pos / 64] |= 1 << (pos % 64);
+    }
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    Err(RsError::PinnedPositionConflict { positions: conflicts })
+}
+
+/// Every InvalidParameters constraint the crate returns, so a deserialized one maps back to the
+/// &'static str it was serialized from
+#[cfg(feature = "serde")]
+static CONSTRAINTS: &[&str] = &[
+    "RsWriter/RsReader need k >= 2: one data byte of each block is its payload length",
+    "a Codeword is always HighestFirst",
+    "at least one data shard is required",
+    "at least one parity shard is required",
+    "burst length and interleaving depth must be at least 1",
+    "codeword buffer must be message.len() + nsym bytes",
+    "data + parity shards must be at most 255",
+    "depth must be at least 1",
+    "fcr must be less than 255",
+    "fixed_work needs the default CodeOptions",
+    "generator must be a primitive element of the field",
+    "group_size must be at least 1",
+    "interleave depth must be in 1..=255",
+    "max_correct must be at most nsym / 2",
+    "metadata bits must be 1..=8",
+    "metadata symbol needs k >= 2",
+    "msg_len must be at most k",
+    "no nsym < nsize reaches the target block failure rate",
+    "nsize must be at most 255 over GF(256)",
+    "nsym must be at least 1",
+    "nsym must be less than nsize (k >= 1)",
+    "pinned positions must be below nsize",
+    "pinned positions must be inside the codeword",
+    "positions buffer must hold nsym / 2 entries",
+    "positions must be below 256",
+    "prim must be a primitive polynomial of degree 8",
+    "received codeword longer than n",
+    "recovery block size must be in 1..=u32::MAX",
+    "redundancy_percent leaves no room for data in a 255-block stripe",
+    "redundancy_percent must be at least 1",
+    "sigma degree must be at most nsym / 2",
+    "sigma[0] must be nonzero",
+    "symbol error rate must be in 0..=1",
+    "target block failure rate must be in (0, 1]",
+    "threads must be at least 1",
+];
+
+/// What a deserialized constraint this version does not know (say from a newer one) becomes
+#[cfg(feature = "serde")]
+pub const UNKNOWN_CONSTRAINT: &str = "unknown constraint (serialized by another version)";
+
+/// A deserialized InvalidParameters constraint as the matching CONSTRAINTS entry, or
+/// UNKNOWN_CONSTRAINT; nothing is allocated that outlives the call
+#[cfg(feature = "serde")]
+fn intern_constraint<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<&'static str, D::Error> {
+    let constraint = <String as serde::Deserialize>::deserialize(deserializer)?;
+    Ok(CONSTRAINTS.iter().copied().find(|&known| known == constraint).unwrap_or(UNKNOWN_CONSTRAINT))
+}
+
+/// Check 1 <= nsym < nsize <= 255 (so k = nsize - nsym >= 1)
+pub fn validate_params(nsym: usize, nsize: usize) -> Result<(), RsError> {
+    if nsym == 0 {
+        return Err(RsError::InvalidParameters("nsym must be at least 1"));
+    }
+    if nsize > 255 {
+        return Err(RsError::InvalidParameters("nsize must be at most 255 over GF(256)"));
+    }
+    if nsym >= nsize {
+        return Err(RsError::InvalidParameters("nsym must be less than nsize (k >= 1)"));
+    }
+    Ok(())
+}
 
 /// Evaluate polynomial at x in GF(256)
 /// poly[0] is the x^0 coefficient, poly[i] is x^i coefficient
@@ -12,6 +327,7 @@ fn poly_eval_at(poly: &[u8], x: u8) -> u8 {
         return 0;
     }
     // Horner's method: start from highest degree
+    opcount::add(poly.len());
     let mut result = 0u8;
     for &coef in poly.iter().rev() {
         result = (gf_mul(result, x)) ^ coef;
@@ -21,19 +337,58 @@ fn poly_eval_at(poly: &[u8], x: u8) -> u8 {
 
 /// Build generator polynomial for nsym parity symbols
 /// g(x) = (x - alpha^0)(x - alpha^1)...(x - alpha^(nsym-1))
+#[cfg(feature = "std")]
 pub fn build_generator(nsym: usize) -> Vec<u8> {
-    let mut g = vec![1u8];
+    let mut g = Vec::with_capacity(nsym + 1);
+    g.push(1u8);
     for i in 0..nsym {
         let root = GF.exp[i]; // alpha^i
-        // Multiply by (x + alpha^i): in GF(2), subtraction = addition
-        g = poly_mul(&g, &[root, 1]);
+        // Multiply by (x + alpha^i) in place: in GF(2), subtraction = addition
+        g.push(0);
+        for j in (0..g.len()).rev() {
+            let shifted = if j > 0 { g[j - 1] } else { 0 };
+            g[j] = shifted ^ GF.mul(g[j], root);
+        }
     }
     g
 }
 
+/// build_generator(nsym), built once per nsym and shared for the life of the process
+/// Lock-free after first use, so concurrent encoders with the GIL released never contend; one
+/// slot per possible nsym bounds the cache at 255 polynomials of at most 256 bytes.
+#[cfg(feature = "std")]
+pub fn cached_generator(nsym: usize) -> &'static [u8] {
+    static CACHE: [OnceLock<Vec<u8>>; 255] = [const { OnceLock::new() }; 255];
+    CACHE[nsym].get_or_init(|| build_generator(nsym))
+}
+
 /// Systematic RS encode: given k-byte message, produce n-byte codeword
 /// codeword = [message | parity]
+#[cfg(feature = "std")]
 pub fn encode(message: &[u8], nsym: usize, generator: &[u8]) -> Vec<u8> {
+    encode_with(simd::active(), message, nsym, generator)
+}
+
+/// encode on a given kernel; Kernel::Scalar runs the original synthetic division
+#[cfg(feature = "std")]
+pub fn encode_with(kernel: Kernel, message: &[u8], nsym: usize, generator: &[u8]) -> Vec<u8> {
+    if kernel == Kernel::Scalar {
+        return encode_scalar(message, nsym, generator);
+    }
+    let k = message.len();
+    let mut codeword = vec![0u8; k + nsym];
+    codeword[..k].copy_from_slice(message);
+    let mut taps = [0u8; 255];
+    for (t, &g) in taps.iter_mut().zip(generator[..nsym].iter().rev()) {
+        *t = g;
+    }
+    simd::remainder_with(kernel, message, &taps[..nsym], &mut codeword[k..]);
+    codeword
+}
+
+/// Byte-at-a-time encode, the oracle for the SIMD kernels
+#[cfg(feature = "std")]
+pub fn encode_scalar(message: &[u8], nsym: usize, generator: &[u8]) -> Vec<u8> {
     let k = message.len();
     let n = k + nsym;
     
@@ -43,12 +398,14 @@ pub fn encode(message: &[u8], nsym: usize, generator: &[u8]) -> Vec<u8> {
     codeword[..k].copy_from_slice(message);
     
     // Synthetic division
+    let _ops = opcount::stage(Stage::Encode);
     for i in 0..k {
         let coef = codeword[i];
         if coef != 0 {
             for j in 1..=nsym {
                 codeword[i + j] ^= gf_mul(generator[nsym - j], coef);
             }
+            opcount::add(nsym);
         }
     }
     
@@ -60,22 +417,38 @@ pub fn encode(message: &[u8], nsym: usize, generator: &[u8]) -> Vec<u8> {
 /// Compute syndromes S_j = r(alpha^j) for j = 0..nsym-1
 /// where r(x) is received codeword as polynomial
 /// Codeword bytes map to polynomial: codeword[i] is coefficient of x^(n-1-i)
+#[cfg(feature = "std")]
 pub fn calc_syndromes(codeword: &[u8], nsym: usize) -> Vec<u8> {
-    let n = codeword.len();
     let mut syndromes = vec![0u8; nsym];
-    
-    for j in 0..nsym {
-        let mut s = 0u8;
-        // r(x) = sum_{i=0}^{n-1} r_i * x^i where r_i = codeword[n-1-i]
-        // r(alpha^j) = sum_{i=0}^{n-1} codeword[n-1-i] * alpha^(j*i)
-        for (idx, &byte) in codeword.iter().enumerate() {
-            let power = (n - 1 - idx) as usize;
-            let alpha_power = GF.exp[(j * power) % 255];
-            s ^= gf_mul(byte, alpha_power);
+    simd::syndromes(codeword, &mut syndromes);
+    syndromes
+}
+
+/// Syndromes of the word with `magnitudes` XORed in at `positions`, updated in place from those
+/// of the original: S_j ^= e * X^j with X = alpha^(n-1-pos), O(t * nsym) instead of O(n * nsym)
+pub fn update_syndromes(syndromes: &mut [u8], n: usize, positions: &[usize], magnitudes: &[u8]) {
+    for (&pos, &mag) in positions.iter().zip(magnitudes) {
+        let x = GF.exp[(n - 1 - pos) % 255];
+        let mut term = mag;
+        for s in syndromes.iter_mut() {
+            *s ^= term;
+            term = gf_mul(term, x);
         }
-        syndromes[j] = s;
+        opcount::add(syndromes.len());
+    }
+}
+
+/// Syndromes of `corrected`, given those of the received word and the corrections that made it,
+/// computed the cheaper way: update_syndromes costs t * nsym scalar multiplies (about 4 ns each),
+/// a SIMD syndrome pass roughly 0.5-2.5 us at any size. Past t * nsym = 512, which only
+/// high-redundancy codes with many errors reach, the pass wins; with no vector kernel it never does.
+#[cfg(feature = "std")]
+pub(crate) fn residual_syndromes(syndromes: &mut [u8], corrected: &[u8], positions: &[usize], magnitudes: &[u8]) {
+    if positions.len() * syndromes.len() > 512 && simd::active() != Kernel::Scalar {
+        simd::syndromes(corrected, syndromes);
+    } else {
+        update_syndromes(syndromes, corrected.len(), positions, magnitudes);
     }
-    syndromes
 }
 
 /// Check if all syndromes are zero (no errors)
@@ -85,110 +458,388 @@ pub fn syndromes_zero(syndromes: &[u8]) -> bool {
 
 /// Berlekamp-Massey algorithm to find error locator polynomial sigma(x)
 /// sigma(x) = prod_{j} (1 - X_j * x) where X_j = alpha^(position_j)
+#[cfg(feature = "std")]
 pub fn berlekamp_massey(syndromes: &[u8]) -> Vec<u8> {
+    berlekamp_massey_in(&GF, syndromes)
+}
+
+/// Berlekamp-Massey over any field; the binary BCH decoder uses this with GF(2^m)
+#[cfg(feature = "std")]
+pub fn berlekamp_massey_in<F: Field>(field: &F, syndromes: &[F::Elem]) -> Vec<F::Elem> {
+    let (mut sigma, mut prev) = (Vec::new(), Vec::new());
+    berlekamp_massey_into(field, syndromes, &mut sigma, &mut prev);
+    sigma
+}
+
+/// berlekamp_massey_in into caller-owned buffers: sigma receives the locator, prev is scratch
+/// Both are sized to syndromes.len() + 1 once, the most the locators can reach, so a buffer that
+/// already has that capacity is never reallocated. Entries past a locator's logical length are
+/// kept zero, which lets a length change swap the two buffers instead of copying one.
+#[cfg(feature = "std")]
+pub fn berlekamp_massey_into<F: Field>(field: &F, syndromes: &[F::Elem], sigma: &mut Vec<F::Elem>, prev: &mut Vec<F::Elem>) {
+    let zero = F::Elem::default();
     let n = syndromes.len();
-    let mut c = vec![1u8]; // Current error locator
-    let mut b = vec![1u8]; // Previous error locator
+    for buf in [&mut *sigma, &mut *prev] {
+        buf.clear();
+        buf.resize(n + 1, zero);
+        buf[0] = field.one();
+    }
+    let (c, b) = (sigma, prev); // Current and previous error locator
+    let (mut c_len, mut b_len) = (1usize, 1usize);
     let mut l = 0usize;    // Number of errors
     let mut m = 1usize;    // Shift counter
-    let mut delta_prev = 1u8;
-    
+    let mut delta_prev = field.one();
+
     for r in 0..n {
         // Compute discrepancy
         let mut delta = syndromes[r];
-        for i in 1..=l.min(c.len() - 1) {
-            delta ^= gf_mul(c[i], syndromes[r - i]);
+        for i in 1..=l.min(c_len - 1) {
+            delta ^= field.mul(c[i], syndromes[r - i]);
         }
-        
-        if delta == 0 {
+        opcount::add(l.min(c_len - 1));
+        event!(Trace, "bm.iteration", r = r, discrepancy = crate::trace::elem(delta), l = l, c_len = c_len, b_len = b_len);
+        if delta == zero {
             m += 1;
-        } else if 2 * l <= r {
-            // Length change
-            let t = c.clone();
-            let scale = gf_mul(delta, gf_inv(delta_prev));
-            
-            // c(x) = c(x) - delta/delta_prev * x^m * b(x)
-            while c.len() < b.len() + m {
-                c.push(0);
-            }
-            for (i, &bi) in b.iter().enumerate() {
-                c[i + m] ^= gf_mul(scale, bi);
+            continue;
+        }
+
+        // c(x) = c(x) - delta/delta_prev * x^m * b(x)
+        let scale = field.mul(delta, field.inv(delta_prev));
+        let len = c_len.max(b_len + m);
+        if 2 * l <= r {
+            // Length change: b takes the old c; going downwards reads each c[i - m] before it is replaced
+            std::mem::swap(c, b);
+            for i in (0..len).rev() {
+                let shifted = if i >= m { field.mul(scale, c[i - m]) } else { zero };
+                c[i] = b[i] ^ shifted;
             }
-            
+            opcount::add(len - m);
+            b_len = c_len;
             l = r + 1 - l;
-            b = t;
             delta_prev = delta;
             m = 1;
         } else {
-            // No length change
-            let scale = gf_mul(delta, gf_inv(delta_prev));
-            while c.len() < b.len() + m {
-                c.push(0);
-            }
-            for (i, &bi) in b.iter().enumerate() {
-                c[i + m] ^= gf_mul(scale, bi);
+            for (i, &bi) in b[..b_len].iter().enumerate() {
+                c[i + m] ^= field.mul(scale, bi);
             }
+            opcount::add(b_len);
             m += 1;
         }
+        c_len = len;
     }
-    
+
     // Trim trailing zeros
-    while c.len() > 1 && c.last() == Some(&0) {
-        c.pop();
+    while c_len > 1 && c[c_len - 1] == zero {
+        c_len -= 1;
     }
-    
-    c
+    c.truncate(c_len);
+}
+
+/// Which Berlekamp-Massey formulation finds the error locator (DecodeOptions::bm)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BmVariant {
+    /// berlekamp_massey: sigma normalized to sigma_0 = 1, one field inverse per length change
+    #[default]
+    Standard,
+    /// berlekamp_massey_inversionless: no inverses, sigma up to a nonzero scale factor
+    Inversionless,
+}
+
+/// Inversionless Berlekamp-Massey (the iBM of Sarwate and Shanbhag, the recurrence hardware
+/// RiBM datapaths implement): a nonzero scalar multiple of berlekamp_massey's sigma, found
+/// without a field inverse. With delta the discrepancy, each step is
+///   lambda <- gamma * lambda - delta * x * b,
+///   then if delta != 0 and k >= 0: b <- old lambda, gamma <- delta, k <- -k - 1
+///   else: b <- x * b, k <- k + 1,
+/// starting from lambda = b = 1, gamma = 1, k = 0; k tracks r - 2L, so the length changes are
+/// BM's. The scale is left in, as the hardware leaves it: roots are unchanged, and Forney's
+/// X Omega(X^-1) / sigma'(X^-1) is a ratio in which it cancels (Omega = S sigma mod x^nsym scales
+/// with sigma), so Chien and Forney give the same errors either way.
+#[cfg(feature = "std")]
+pub fn berlekamp_massey_inversionless(syndromes: &[u8]) -> Vec<u8> {
+    berlekamp_massey_inversionless_in(&GF, syndromes)
+}
+
+/// berlekamp_massey_inversionless over any field
+#[cfg(feature = "std")]
+pub fn berlekamp_massey_inversionless_in<F: Field>(field: &F, syndromes: &[F::Elem]) -> Vec<F::Elem> {
+    let (mut lambda, mut b) = (Vec::new(), Vec::new());
+    berlekamp_massey_inversionless_into(field, syndromes, &mut lambda, &mut b);
+    lambda
+}
+
+/// berlekamp_massey_inversionless into caller-owned buffers, as berlekamp_massey_into: lambda
+/// receives the locator, b is scratch. With the `trace` feature every iteration emits an
+/// "ibm.iteration" event (Trace level) with the state it started from: r, discrepancy, gamma,
+/// k, lambda and b.
+#[cfg(feature = "std")]
+pub fn berlekamp_massey_inversionless_into<F: Field>(field: &F, syndromes: &[F::Elem], lambda: &mut Vec<F::Elem>, b: &mut Vec<F::Elem>) {
+    let zero = F::Elem::default();
+    let n = syndromes.len();
+    for buf in [&mut *lambda, &mut *b] {
+        buf.clear();
+        buf.resize(n + 1, zero);
+        buf[0] = field.one();
+    }
+    // Coefficients past these lengths are zero
+    let (mut lambda_len, mut b_len) = (1usize, 1usize);
+    let mut gamma = field.one();
+    let mut k = 0isize;
+
+    for r in 0..n {
+        let delta = (0..lambda_len.min(r + 1)).fold(zero, |acc, i| acc ^ field.mul(lambda[i], syndromes[r - i]));
+        opcount::add(lambda_len.min(r + 1));
+        event!(
+            Trace,
+            "ibm.iteration",
+            r = r,
+            discrepancy = crate::trace::elem(delta),
+            gamma = crate::trace::elem(gamma),
+            k = k,
+            lambda = lambda[..lambda_len].iter().map(|&c| crate::trace::elem(c)).collect::<Vec<u32>>(),
+            b = b[..b_len].iter().map(|&c| crate::trace::elem(c)).collect::<Vec<u32>>(),
+        );
+        let len = lambda_len.max(b_len + 1).min(n + 1);
+        opcount::add(len - 1);
+        if delta != zero && k >= 0 {
+            // b takes the old lambda; going downwards reads each lambda[i - 1] (the old b) before
+            // it is replaced
+            std::mem::swap(lambda, b);
+            for i in (0..len).rev() {
+                let shifted = if i > 0 { field.mul(delta, lambda[i - 1]) } else { zero };
+                lambda[i] = field.mul(gamma, b[i]) ^ shifted;
+            }
+            b_len = lambda_len;
+            gamma = delta;
+            k = -k - 1;
+        } else {
+            for i in (0..len).rev() {
+                let shifted = if i > 0 { field.mul(delta, b[i - 1]) } else { zero };
+                lambda[i] = field.mul(gamma, lambda[i]) ^ shifted;
+            }
+            // b <- x b
+            b.copy_within(..b_len.min(n), 1);
+            b[0] = zero;
+            b_len = (b_len + 1).min(n + 1);
+            k += 1;
+        }
+        lambda_len = len;
+    }
+
+    while lambda_len > 1 && lambda[lambda_len - 1] == zero {
+        lambda_len -= 1;
+    }
+    lambda.truncate(lambda_len);
+}
+
+/// Berlekamp-Massey with data-independent work, for DecodeOptions::fixed_work: all nsym
+/// iterations run over the full nsym + 1 coefficients, with no skip on a zero discrepancy and
+/// both branches of the length change computed. Written as Massey's shifted-b form,
+///   T = g lambda - delta x b;  b <- (change ? s lambda : x b);  lambda <- T,
+/// with change = delta != 0 and 2L <= r. Standard takes g = 1, s = 1 / delta (the normalized
+/// locator berlekamp_massey_into gives); Inversionless g = the last nonzero change's delta,
+/// s = 1 (berlekamp_massey_inversionless_into's). sigma is trimmed of trailing zeros at the end.
+#[cfg(feature = "std")]
+pub(crate) fn berlekamp_massey_fixed_into(syndromes: &[u8], bm: BmVariant, sigma: &mut Vec<u8>, b: &mut Vec<u8>) {
+    let n = syndromes.len();
+    for buf in [&mut *sigma, &mut *b] {
+        buf.clear();
+        buf.resize(n + 1, 0);
+        buf[0] = 1;
+    }
+    let mut l = 0usize;
+    let mut gamma = 1u8;
+    for r in 0..n {
+        let delta = (0..=r).fold(0u8, |acc, i| acc ^ gf_mul(sigma[i], syndromes[r - i]));
+        opcount::add(r + 1 + n + 1);
+        event!(Trace, "bm.iteration", r = r, discrepancy = delta as usize, l = l);
+        let change = delta != 0 && 2 * l <= r;
+        let (g, s) = match bm {
+            BmVariant::Standard => (1, if delta != 0 { gf_div(1, delta) } else { 0 }),
+            BmVariant::Inversionless => (gamma, 1),
+        };
+        // Downwards, so b[i - 1] is read before it is replaced
+        for i in (0..=n).rev() {
+            let shifted = if i > 0 { b[i - 1] } else { 0 };
+            let old = sigma[i];
+            sigma[i] = gf_mul(g, old) ^ gf_mul(delta, shifted);
+            b[i] = if change { gf_mul(s, old) } else { shifted };
+        }
+        if change {
+            l = r + 1 - l;
+            gamma = delta;
+        }
+    }
+    let len = sigma.iter().rposition(|&c| c != 0).map_or(1, |d| d + 1);
+    sigma.truncate(len);
 }
 
 /// Chien search: find roots of error locator polynomial
 /// sigma(X_j^-1) = 0 means error at position where X_j = alpha^(n-1-pos)
+#[cfg(feature = "std")]
 pub fn chien_search(sigma: &[u8], n: usize) -> Vec<usize> {
     let mut positions = Vec::new();
-    
-    // For each possible position, check if it's an error location
+    chien_search_into(sigma, n, &mut positions);
+    positions
+}
+
+/// chien_search into a caller-owned buffer, which is cleared first
+#[cfg(feature = "std")]
+pub fn chien_search_into(sigma: &[u8], n: usize, positions: &mut Vec<usize>) {
+    positions.clear();
+    positions.extend(chien_roots(sigma, n).map(|root| root.pos));
+}
+
+/// An error location found by the Chien search, with the locator values Forney needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChienRoot {
+    pub pos: usize,
+    /// X_j = alpha^(n-1-pos)
+    pub x: u8,
+    /// X_j^-1, the root of sigma
+    pub x_inv: u8,
+}
+
+/// Roots of sigma among the n positions, ascending by position
+/// The classic incremental search: term j is kept as log(sigma_j X^-j), which steps by j from one
+/// position to the next, so each evaluation is table lookups and XORs with no chain of multiplies.
+/// A degree-d sigma has at most d roots, so the scan stops at the d-th. Nothing is allocated.
+fn chien_roots(sigma: &[u8], n: usize) -> impl Iterator<Item = ChienRoot> + '_ {
+    // X^-255 = 1 at every position, so a term of degree 255 or more folds onto degree j mod 255:
+    // the same roots from at most 255 coefficients, whatever length sigma comes in at
+    let mut coefficients = [0u8; 255];
+    for (j, &c) in sigma.iter().enumerate() {
+        coefficients[j % 255] ^= c;
+    }
+    let degree = coefficients.iter().rposition(|&c| c != 0).unwrap_or(0);
+    // log X^-1 at position 0, where X = alpha^(n-1); it grows by one per position
+    let first = (255 - (n - 1) % 255) % 255;
+    let mut logs = [0u8; 255];
+    for (j, (log, &c)) in logs.iter_mut().zip(&coefficients).enumerate().take(degree + 1) {
+        if c != 0 {
+            *log = ((GF.log[c as usize] as usize + j * first) % 255) as u8;
+        }
+    }
+    // Each nonzero term is a constant multiply (the log step) and an add per position
+    let terms = coefficients[..=degree].iter().filter(|&&c| c != 0).count();
+    (0..n)
+        .filter_map(move |pos| {
+            opcount::mul(terms);
+            opcount::add(terms);
+            let mut value = 0u8;
+            for (j, log) in logs[..=degree].iter_mut().enumerate() {
+                if coefficients[j] != 0 {
+                    value ^= GF.exp[*log as usize];
+                    let next = *log as usize + j % 255;
+                    *log = if next >= 255 { next - 255 } else { next } as u8;
+                }
+            }
+            let x_exp = (n - 1 - pos) % 255;
+            (value == 0).then(|| ChienRoot { pos, x: GF.exp[x_exp], x_inv: GF.exp[(255 - x_exp) % 255] })
+        })
+        .take(degree)
+}
+
+/// chien_search keeping X_j and X_j^-1 for forney_roots_into; roots is cleared first
+#[cfg(feature = "std")]
+pub fn chien_roots_into(sigma: &[u8], n: usize, roots: &mut Vec<ChienRoot>) {
+    roots.clear();
+    roots.extend(chien_roots(sigma, n));
+}
+
+/// Chien and Forney with data-independent work, for DecodeOptions::fixed_work: as hardware
+/// does, every one of the n positions gets sigma, Omega and sigma' evaluated over their full
+/// nsym + 1, nsym and (nsym + 1) / 2 coefficient widths, and the magnitude computed; roots and
+/// magnitudes keep the positions where sigma vanishes, ascending. Omega is the whole
+/// S sigma mod x^nsym, which forney_roots_into truncates below deg sigma: the magnitudes agree
+/// whenever the roots are the true error locations, and verification fails either way when not.
+#[cfg(feature = "std")]
+pub(crate) fn chien_forney_fixed(syndromes: &[u8], sigma: &[u8], n: usize, roots: &mut Vec<ChienRoot>, magnitudes: &mut Vec<u8>) {
+    let nsym = syndromes.len();
+    let mut padded = [0u8; 256];
+    padded[..sigma.len()].copy_from_slice(sigma);
+    let sigma = &padded[..=nsym];
+    let mut omega = [0u8; 255];
+    {
+        let _ops = opcount::stage(Stage::Forney);
+        for (i, o) in omega[..nsym].iter_mut().enumerate() {
+            *o = (0..=i).fold(0u8, |acc, j| acc ^ gf_mul(syndromes[i - j], sigma[j]));
+            opcount::add(i + 1);
+        }
+    }
+    let mut sigma_prime = [0u8; 128];
+    for (d, &c) in sigma_prime.iter_mut().zip(sigma.iter().skip(1).step_by(2)) {
+        *d = c;
+    }
+    roots.clear();
+    magnitudes.clear();
     for pos in 0..n {
-        // X_j = alpha^(n-1-pos), so X_j^-1 = alpha^(pos-n+1) = alpha^(pos+256-n) mod 255
-        let exp = ((pos as i32) - (n as i32) + 1 + 510) as usize % 255;
-        let x_inv = if exp == 0 { 1u8 } else { GF.exp[exp] };
-        
-        if poly_eval_at(sigma, x_inv) == 0 {
-            positions.push(pos);
+        let x_exp = (n - 1 - pos) % 255;
+        let (x, x_inv) = (GF.exp[x_exp], GF.exp[(255 - x_exp) % 255]);
+        let value = poly_eval_at(sigma, x_inv);
+        let _ops = opcount::stage(Stage::Forney);
+        let denominator = poly_eval_at(&sigma_prime[..nsym.div_ceil(2)], gf_mul(x_inv, x_inv));
+        let numerator = gf_mul(x, poly_eval_at(&omega[..nsym], x_inv));
+        let magnitude = if denominator != 0 { gf_div(numerator, denominator) } else { 0 };
+        if value == 0 {
+            roots.push(ChienRoot { pos, x, x_inv });
+            magnitudes.push(magnitude);
         }
     }
-    positions
 }
 
 /// Forney algorithm: compute error magnitudes
+#[cfg(feature = "std")]
 pub fn forney(syndromes: &[u8], sigma: &[u8], positions: &[usize], n: usize) -> Vec<u8> {
+    let mut buffers = ForneyBuffers::default();
+    forney_into(syndromes, sigma, positions, n, &mut buffers);
+    buffers.magnitudes
+}
+
+/// Scratch and output of forney_into; magnitudes holds the result
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct ForneyBuffers {
+    pub omega: Vec<u8>,
+    pub sigma_prime: Vec<u8>,
+    pub magnitudes: Vec<u8>,
+}
+
+/// forney into caller-owned buffers, reused without reallocating once they have grown
+#[cfg(feature = "std")]
+pub fn forney_into(syndromes: &[u8], sigma: &[u8], positions: &[usize], n: usize, buffers: &mut ForneyBuffers) {
     let nsym = syndromes.len();
+    let ForneyBuffers { omega, sigma_prime, magnitudes } = buffers;
     
     // Omega(x) = S(x) * sigma(x) mod x^nsym
     // S(x) = S_0 + S_1*x + ...
-    let mut omega = vec![0u8; nsym];
-    for i in 0..nsym {
-        for (j, &sj) in sigma.iter().enumerate() {
-            if i >= j {
-                omega[i] ^= gf_mul(syndromes[i - j], sj);
-            }
+    omega.clear();
+    omega.resize(nsym, 0);
+    for (i, o) in omega.iter_mut().enumerate() {
+        for (j, &sj) in sigma.iter().enumerate().take(i + 1) {
+            *o ^= gf_mul(syndromes[i - j], sj);
         }
+        opcount::add(sigma.len().min(i + 1));
     }
     
     // Formal derivative: sigma'(x) = sum of odd-indexed terms
     // d/dx (c_i * x^i) = i * c_i * x^(i-1), and in char 2, i is 0 if even
-    let mut sigma_prime = vec![0u8; sigma.len()];
+    sigma_prime.clear();
+    sigma_prime.resize(sigma.len(), 0);
     for i in (1..sigma.len()).step_by(2) {
         sigma_prime[i - 1] = sigma[i];
     }
     
-    let mut magnitudes = Vec::with_capacity(positions.len());
+    magnitudes.clear();
     for &pos in positions {
         // X_j = alpha^(n-1-pos)
-        let x_exp = ((n - 1 - pos) % 255) as usize;
+        let x_exp = (n - 1 - pos) % 255;
         let x_j = GF.exp[x_exp];
         let x_j_inv = GF.exp[(255 - x_exp) % 255];
         
-        let omega_val = poly_eval_at(&omega, x_j_inv);
-        let sigma_prime_val = poly_eval_at(&sigma_prime, x_j_inv);
+        let omega_val = poly_eval_at(omega, x_j_inv);
+        let sigma_prime_val = poly_eval_at(sigma_prime, x_j_inv);
         
         if sigma_prime_val == 0 {
             // This shouldn't happen for valid error patterns
@@ -198,58 +849,376 @@ pub fn forney(syndromes: &[u8], sigma: &[u8], positions: &[usize], n: usize) ->
             magnitudes.push(gf_mul(x_j, gf_div(omega_val, sigma_prime_val)));
         }
     }
-    magnitudes
+}
+
+/// Forney for roots from chien_roots_into, whose count must equal deg sigma
+/// When the roots are the true error locations, Omega = S * sigma mod x^nsym has degree below
+/// deg sigma, so only those coefficients are built; with wrong roots the magnitudes may differ
+/// from forney's, but no set of magnitudes at wrong roots passes syndrome verification anyway.
+/// sigma'(x) only has even powers, so it is evaluated from the odd coefficients of sigma at x^2.
+#[cfg(feature = "std")]
+pub fn forney_roots_into(syndromes: &[u8], sigma: &[u8], roots: &[ChienRoot], buffers: &mut ForneyBuffers) {
+    let degree = (sigma.len() - 1).min(syndromes.len());
+    let ForneyBuffers { omega, sigma_prime, magnitudes } = buffers;
+    omega.clear();
+    omega.extend((0..degree).map(|i| sigma[..=i].iter().enumerate().fold(0u8, |acc, (j, &sj)| acc ^ gf_mul(syndromes[i - j], sj))));
+    opcount::add(degree * (degree + 1) / 2);
+    sigma_prime.clear();
+    sigma_prime.extend(sigma.iter().skip(1).step_by(2));
+
+    magnitudes.clear();
+    magnitudes.extend(roots.iter().map(|root| {
+        let sigma_prime_val = poly_eval_at(sigma_prime, gf_mul(root.x_inv, root.x_inv));
+        if sigma_prime_val == 0 {
+            0
+        } else {
+            gf_mul(root.x, gf_div(poly_eval_at(omega, root.x_inv), sigma_prime_val))
+        }
+    }));
 }
 
 /// Decode RS codeword
-pub fn decode(codeword: &[u8], nsym: usize) -> Result<(Vec<u8>, Vec<usize>), &'static str> {
+/// Allocates a fresh RsDecoder per call; keep one around to decode many blocks without allocating.
+#[cfg(feature = "std")]
+pub fn decode(codeword: &[u8], nsym: usize) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+    let mut decoder = RsDecoder::new(nsym, codeword.len());
+    decoder.decode_with(codeword).map(|(message, positions)| (message.to_vec(), positions.to_vec()))
+}
+
+/// decode with an externally computed error locator (a hardware BM, say) in place of BM's
+/// sigma must have sigma[0] != 0 and degree at most nsym / 2, else InvalidParameters; Chien,
+/// Forney, correction and verification then run as in decode. The flag is set when sigma is
+/// inconsistent with the syndromes, failing the key equation sum_i sigma_i S_(r-i) = 0 for
+/// degree <= r < nsym; the result is then always an error. A consistent sigma can still fail,
+/// e.g. with a root where nothing is wrong, but is never a miscorrection: verification passes
+/// only for the unique codeword within nsym / 2. Feeding berlekamp_massey's sigma reproduces decode.
+#[cfg(feature = "std")]
+pub fn decode_with_sigma(codeword: &[u8], nsym: usize, sigma: &[u8]) -> (BlockResult, bool) {
+    let mut decoder = RsDecoder::new(nsym, codeword.len());
+    let (result, inconsistent) = decoder.decode_with_sigma(codeword, sigma);
+    let k = codeword.len().saturating_sub(nsym);
+    (result.map(|(corrected, positions)| (corrected[..k].to_vec(), positions.to_vec())), inconsistent)
+}
+
+/// Errors-and-erasures decode: corrects e errors plus the h flagged erasures when 2e + h <= nsym
+/// Returns the message and the positions actually changed, ascending; an erasure whose byte
+/// turned out to be correct is not reported.
+#[cfg(feature = "std")]
+pub fn decode_errata(codeword: &[u8], nsym: usize, erasures: &[usize]) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+    decode_errata_with(codeword, nsym, erasures, DecodeOptions::default())
+}
+
+/// decode for a codeword whose tail was cut off in transit: the n - received.len() missing
+/// trailing symbols are zero-filled and declared erasures, and the rest of the nsym budget goes
+/// on errors (2e + missing <= nsym). Returns what decode_errata does; a reconstructed tail
+/// position is reported when its symbol was nonzero. With exactly nsym missing nothing is left
+/// to detect errors with, so any received prefix decodes. Truncated when more than nsym symbols
+/// are missing, InvalidParameters when received is longer than n.
+#[cfg(feature = "std")]
+pub fn decode_truncated(received: &[u8], nsym: usize, n: usize) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+    validate_params(nsym, n)?;
+    let (codeword, erasures) = pad_truncated(received, nsym, n)?;
+    decode_errata(&codeword, nsym, &erasures)
+}
+
+/// received zero-filled to n symbols, and the filled positions
+#[cfg(feature = "std")]
+pub(crate) fn pad_truncated(received: &[u8], nsym: usize, n: usize) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+    if received.len() > n {
+        return Err(RsError::InvalidParameters("received codeword longer than n"));
+    }
+    let missing = n - received.len();
+    if missing > nsym {
+        return Err(RsError::Truncated { missing, nsym });
+    }
+    let mut codeword = received.to_vec();
+    codeword.resize(n, 0);
+    Ok((codeword, (received.len()..n).collect()))
+}
+
+/// decode_errata with DecodeOptions' Berlekamp-Massey variant and, for errors-only decodes,
+/// fixed_work; max_correct is left to the caller
+#[cfg(feature = "std")]
+pub(crate) fn decode_errata_with(codeword: &[u8], nsym: usize, erasures: &[usize], options: DecodeOptions) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+    let bm = options.bm;
     let n = codeword.len();
     if n < nsym {
-        return Err("codeword too short");
+        return Err(RsError::CodewordTooShort);
+    }
+    validate_params(nsym, n)?;
+    if erasures.is_empty() {
+        let mut decoder = RsDecoder::new(nsym, n).with_bm(bm).with_fixed_work(options.fixed_work);
+        return decoder.decode_with(codeword).map(|(message, positions)| (message.to_vec(), positions.to_vec()));
+    }
+    let _span = span!(Debug, "decode", n = n, nsym = nsym, erasures = erasures);
+    decoder::traced(errata_stages(codeword, nsym, erasures, bm))
+}
+
+#[cfg(feature = "std")]
+fn errata_stages(codeword: &[u8], nsym: usize, erasures: &[usize], bm: BmVariant) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+    let n = codeword.len();
+    let mut seen = vec![false; n];
+    for &pos in erasures {
+        if pos >= n || seen[pos] {
+            return Err(RsError::InvalidErasure);
+        }
+        seen[pos] = true;
+    }
+    let h = erasures.len();
+    if h > nsym {
+        return Err(RsError::TooManyErrors);
     }
     let k = n - nsym;
-    
-    let syndromes = calc_syndromes(codeword, nsym);
-    
+
+    let syndromes = {
+        let _span = span!(Debug, "syndromes");
+        let _ops = opcount::stage(Stage::Syndromes);
+        let syndromes = calc_syndromes(codeword, nsym);
+        event!(Trace, "syndromes", syndromes = &syndromes[..]);
+        syndromes
+    };
     if syndromes_zero(&syndromes) {
         return Ok((codeword[..k].to_vec(), vec![]));
     }
-    
-    let sigma = berlekamp_massey(&syndromes);
-    let num_errors = sigma.len() - 1;
-    
-    if num_errors == 0 {
-        return Err("nonzero syndrome but trivial locator");
+
+    // Erasure locator Gamma(x) = prod (1 + X_i x), multiplied out in place
+    let mut gamma = Vec::with_capacity(h + 1);
+    gamma.push(1u8);
+    for &pos in erasures {
+        let x = GF.exp[(n - 1 - pos) % 255];
+        gamma.push(0);
+        for j in (1..gamma.len()).rev() {
+            gamma[j] ^= gf_mul(gamma[j - 1], x);
+        }
+        opcount::add(gamma.len() - 1);
     }
-    if num_errors > nsym / 2 {
-        return Err("too many errors");
+
+    // Forney syndromes: coefficients h..nsym-1 of S(x) * Gamma(x) no longer see the erasures;
+    // the first h are never used, so they are not computed
+    let modified: Vec<u8> = (h..nsym).map(|i| gamma.iter().enumerate().fold(0u8, |acc, (j, &gj)| acc ^ gf_mul(syndromes[i - j], gj))).collect();
+    opcount::add(modified.len() * gamma.len());
+    let lambda = {
+        let _span = span!(Debug, "bm");
+        let _ops = opcount::stage(Stage::Bm);
+        let lambda = match bm {
+            BmVariant::Standard => berlekamp_massey(&modified),
+            BmVariant::Inversionless => berlekamp_massey_inversionless(&modified),
+        };
+        event!(Debug, "bm.locator", errors = lambda.len() - 1, locator = &lambda[..]);
+        lambda
+    };
+    let num_errors = lambda.len() - 1;
+    if 2 * num_errors + h > nsym {
+        return Err(RsError::TooManyErrors);
     }
-    
-    let positions = chien_search(&sigma, n);
-    
-    if positions.len() != num_errors {
-        return Err("Chien search failed");
+
+    // Errata locator = error locator * erasure locator
+    let psi = poly_mul(&lambda, &gamma);
+    let mut roots = vec![];
+    let positions: Vec<usize> = {
+        let _span = span!(Debug, "chien");
+        let _ops = opcount::stage(Stage::Chien);
+        chien_roots_into(&psi, n, &mut roots);
+        let positions: Vec<usize> = roots.iter().map(|root| root.pos).collect();
+        event!(Debug, "chien.roots", found = roots.len(), positions = &positions[..]);
+        positions
+    };
+    if roots.len() != psi.len() - 1 {
+        return Err(RsError::ChienSearchFailed);
     }
-    
-    let magnitudes = forney(&syndromes, &sigma, &positions, n);
-    
+    let magnitudes = {
+        let _span = span!(Debug, "forney");
+        let _ops = opcount::stage(Stage::Forney);
+        let mut forney_buffers = ForneyBuffers::default();
+        forney_roots_into(&syndromes, &psi, &roots, &mut forney_buffers);
+        event!(Trace, "forney.magnitudes", magnitudes = &forney_buffers.magnitudes[..]);
+        forney_buffers.magnitudes
+    };
+
     let mut corrected = codeword.to_vec();
     for (&pos, &mag) in positions.iter().zip(magnitudes.iter()) {
         corrected[pos] ^= mag;
     }
-    
-    // Verify
-    let check = calc_syndromes(&corrected, nsym);
-    if !syndromes_zero(&check) {
-        return Err("verification failed");
+    opcount::add(positions.len());
+    let _span = span!(Debug, "verify");
+    let _ops = opcount::stage(Stage::Verify);
+    let mut residual = syndromes;
+    residual_syndromes(&mut residual, &corrected, &positions, &magnitudes);
+    #[cfg(debug_assertions)]
+    {
+        let mut full = vec![0u8; nsym];
+        simd::syndromes_scalar(&corrected, &mut full);
+        debug_assert_eq!(residual, full);
     }
-    
-    Ok((corrected[..k].to_vec(), positions))
+    let clean = syndromes_zero(&residual);
+    event!(Debug, "verify", clean = clean as usize);
+    if !clean {
+        return Err(RsError::VerificationFailed);
+    }
+    let changed = positions.into_iter().zip(magnitudes).filter(|&(_, mag)| mag != 0).map(|(pos, _)| pos).collect();
+    Ok((corrected[..k].to_vec(), changed))
+}
+
+/// CRC-framed encode: codeword = [message | crc | parity]
+/// The CRC is covered by the RS parity, so k = n - nsym - crc.size()
+#[cfg(feature = "std")]
+pub fn encode_crc(message: &[u8], nsym: usize, crc: Crc) -> Vec<u8> {
+    encode(&crc.append(message), nsym, cached_generator(nsym))
+}
+
+/// Decode a CRC-framed codeword and validate the CRC after RS correction
+/// A CRC mismatch on a "successful" decode is reported as MiscorrectionDetected
+#[cfg(feature = "std")]
+pub fn decode_crc(codeword: &[u8], nsym: usize, crc: Crc) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+    if codeword.len() < nsym + crc.size() {
+        return Err(RsError::CodewordTooShort);
+    }
+    let (frame, positions) = decode(codeword, nsym)?;
+    match crc.verify(&frame) {
+        Some(message) => Ok((message.to_vec(), positions)),
+        None => Err(RsError::MiscorrectionDetected),
+    }
+}
+
+/// Decode under a single-device fault model: all corrections must fall in one aligned group of
+/// group_size symbols, the groups starting at position alignment (mod group_size)
+/// When the plain decode fails, or corrects across groups, each group is retried as erasures
+/// and a decode that only changes symbols of the erased group is kept if it is the only one.
+/// That also recovers a whole-device fault wider than nsym / 2 when group_size <= nsym.
+/// Fails with FaultModelViolation if the plain decode spanned groups and no retry fits, otherwise
+/// with the plain decode's error.
+#[cfg(feature = "std")]
+pub fn decode_bounded(codeword: &[u8], nsym: usize, group_size: usize, alignment: usize) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+    if group_size == 0 {
+        return Err(RsError::InvalidParameters("group_size must be at least 1"));
+    }
+    let n = codeword.len();
+    let shift = group_size - alignment % group_size;
+    let group = |pos: usize| (pos + shift) / group_size;
+    let groups_touched = |positions: &[usize]| {
+        let mut groups: Vec<usize> = positions.iter().map(|&p| group(p)).collect();
+        groups.dedup();
+        groups.len()
+    };
+    let error = match decode(codeword, nsym) {
+        Ok((message, positions)) if groups_touched(&positions) <= 1 => return Ok((message, positions)),
+        Ok((_, positions)) => RsError::FaultModelViolation { groups: groups_touched(&positions) },
+        Err(e) => e,
+    };
+    let mut found: Option<(Vec<u8>, Vec<usize>)> = None;
+    let last = if n == 0 { 0 } else { group(n - 1) };
+    for g in group(0)..=last {
+        let erasures: Vec<usize> = (0..n).filter(|&p| group(p) == g).collect();
+        if erasures.len() > nsym {
+            continue;
+        }
+        let Ok((message, positions)) = decode_errata(codeword, nsym, &erasures) else { continue };
+        if positions.iter().any(|&p| group(p) != g) {
+            continue;
+        }
+        match &found {
+            Some((other, _)) if *other != message => return Err(error),
+            _ => found = Some((message, positions)),
+        }
+    }
+    found.ok_or(error)
+}
+
+/// (position, bit) pairs set in each correction magnitude, bit 0 = LSB, ascending
+/// Panics unless positions and magnitudes have equal length.
+#[cfg(feature = "std")]
+pub fn bit_errors(positions: &[usize], magnitudes: &[u8]) -> Vec<(usize, u8)> {
+    assert_eq!(positions.len(), magnitudes.len(), "one magnitude per position");
+    let mut bits: Vec<(usize, u8)> =
+        positions.iter().zip(magnitudes).flat_map(|(&pos, &mag)| (0..8u8).filter(move |b| mag >> b & 1 != 0).map(move |b| (pos, b))).collect();
+    bits.sort_unstable();
+    bits
+}
+
+/// Bit-level view of a correction for fault attribution
+/// An erasure's byte was flagged unreadable, so its magnitude replaces the byte rather than
+/// flipping known bits: erasure positions are listed in `replaced` and add no bits or counts.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitErrors {
+    /// Flipped (position, bit) pairs at the error positions, as bit_errors
+    pub bits: Vec<(usize, u8)>,
+    /// Error positions with exactly one bit flipped
+    pub single_bit: usize,
+    /// Error positions with two or more bits flipped
+    pub multi_bit: usize,
+    /// Corrected erasure positions, ascending
+    pub replaced: Vec<usize>,
+}
+
+#[cfg(feature = "std")]
+impl BitErrors {
+    /// From decode's positions, their magnitudes (received ^ corrected) and the erasures passed in
+    pub fn new(positions: &[usize], magnitudes: &[u8], erasures: &[usize]) -> Self {
+        assert_eq!(positions.len(), magnitudes.len(), "one magnitude per position");
+        let mut report = BitErrors::default();
+        let (mut errors, mut masks) = (vec![], vec![]);
+        for (&pos, &mag) in positions.iter().zip(magnitudes) {
+            if erasures.contains(&pos) {
+                report.replaced.push(pos);
+                continue;
+            }
+            match mag.count_ones() {
+                0 => continue,
+                1 => report.single_bit += 1,
+                _ => report.multi_bit += 1,
+            }
+            errors.push(pos);
+            masks.push(mag);
+        }
+        report.bits = bit_errors(&errors, &masks);
+        report.replaced.sort_unstable();
+        report
+    }
+}
+
+/// Where a corrected position falls: data or parity, and which message byte
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorLocation {
+    /// Codeword index, as in decode's positions
+    pub position: usize,
+    /// One of the nsym parity symbols at the end of the codeword
+    pub is_parity: bool,
+    /// Offset into the message; None for parity, and for the zero padding after a padded message
+    pub message_index: Option<usize>,
+}
+
+#[cfg(feature = "std")]
+impl ErrorLocation {
+    /// In the data symbols but past msg_len: padding that encode_padded added
+    pub fn is_padding(&self) -> bool {
+        !self.is_parity && self.message_index.is_none()
+    }
+}
+
+/// Locate corrected positions of a codeword_len-byte codeword with nsym parity symbols
+/// Codewords are [message | parity], so data positions are message offsets. With msg_len (a
+/// decode_padded codeword) the offsets are into the msg_len-byte message, and data positions
+/// from msg_len on are padding. Panics if a position or msg_len is past the codeword's bounds.
+#[cfg(feature = "std")]
+pub fn error_locations(positions: &[usize], codeword_len: usize, nsym: usize, msg_len: Option<usize>) -> Vec<ErrorLocation> {
+    let k = codeword_len - nsym;
+    let msg_len = msg_len.unwrap_or(k);
+    assert!(msg_len <= k, "msg_len must be at most k={}", k);
+    positions
+        .iter()
+        .map(|&position| {
+            assert!(position < codeword_len, "position {} outside the {}-byte codeword", position, codeword_len);
+            ErrorLocation { position, is_parity: position >= k, message_index: (position < msg_len).then_some(position) }
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::sim::{Channel, Rng};
 
     #[test]
     fn test_syndrome_zero_for_valid_codeword() {
@@ -278,13 +1247,15 @@ mod tests {
         let nsym = 4;
         let gen = build_generator(nsym);
         let message = b"Hello";
-        let mut codeword = encode(message, nsym, &gen);
-        
-        codeword[2] ^= 0x55;
-        
-        let (decoded, positions) = decode(&codeword, nsym).unwrap();
-        assert_eq!(decoded, message);
-        assert_eq!(positions, vec![2]);
+        let channel = Channel::symbol_errors(1, 0x5eed);
+        for _ in 0..20 {
+            let mut codeword = encode(message, nsym, &gen);
+            let record = channel.apply(&mut codeword);
+
+            let (decoded, positions) = decode(&codeword, nsym).unwrap();
+            assert_eq!(decoded, message);
+            assert_eq!(positions, record.positions());
+        }
     }
 
     #[test]
@@ -292,15 +1263,15 @@ mod tests {
         let nsym = 4;
         let gen = build_generator(nsym);
         let message = b"Hello";
-        let mut codeword = encode(message, nsym, &gen);
-        
-        codeword[1] ^= 0x12;
-        codeword[4] ^= 0x34;
-        
-        let (decoded, positions) = decode(&codeword, nsym).unwrap();
-        assert_eq!(decoded, message);
-        assert!(positions.contains(&1));
-        assert!(positions.contains(&4));
+        let channel = Channel::symbol_errors(2, 0x5eed);
+        for _ in 0..20 {
+            let mut codeword = encode(message, nsym, &gen);
+            let record = channel.apply(&mut codeword);
+
+            let (decoded, positions) = decode(&codeword, nsym).unwrap();
+            assert_eq!(decoded, message);
+            assert_eq!(positions, record.positions());
+        }
     }
 
     #[test]
@@ -308,13 +1279,618 @@ mod tests {
         let nsym = 4;
         let gen = build_generator(nsym);
         let message = b"Hello";
-        let mut codeword = encode(message, nsym, &gen);
-        
-        codeword[0] ^= 0x11;
-        codeword[2] ^= 0x22;
-        codeword[4] ^= 0x33;
-        
-        let result = decode(&codeword, nsym);
-        assert!(result.is_err());
+        let channel = Channel::symbol_errors(3, 0x5eed);
+        for _ in 0..20 {
+            let mut codeword = encode(message, nsym, &gen);
+            channel.apply(&mut codeword);
+
+            let result = decode(&codeword, nsym);
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_decode_with_sigma() {
+        let mut rng = Rng::new(402);
+        let nsym = 10;
+        for trial in 0..600 {
+            let message: Vec<u8> = (0..40).map(|_| rng.next_u64() as u8).collect();
+            let codeword = encode(&message, nsym, cached_generator(nsym));
+            let mut received = codeword.clone();
+            // Up to t + 2 errors, so failing decodes are compared too
+            let errors = rng.below(nsym / 2 + 3);
+            for p in rng.distinct(errors, codeword.len()) {
+                received[p] ^= rng.nonzero_byte();
+            }
+            let sigma = berlekamp_massey(&calc_syndromes(&received, nsym));
+            if sigma.len() - 1 > nsym / 2 {
+                assert!(matches!(decode_with_sigma(&received, nsym, &sigma), (Err(RsError::InvalidParameters(_)), false)));
+                continue;
+            }
+            let expected = decode(&received, nsym);
+            let (result, inconsistent) = decode_with_sigma(&received, nsym, &sigma);
+            assert_eq!(result, expected, "trial {}", trial);
+            // Past t no locator of degree <= t generates the syndromes, so BM's may be flagged too
+            assert!(!inconsistent || expected.is_err(), "trial {}", trial);
+            // A nonzero multiple of BM's sigma has the same roots
+            let scaled: Vec<u8> = sigma.iter().map(|&c| gf_mul(c, 0x53)).collect();
+            assert_eq!(decode_with_sigma(&received, nsym, &scaled).0, expected);
+
+            // Corrupt one coefficient: never a successful decode, and always flagged
+            let mut corrupt = sigma.clone();
+            corrupt.resize(sigma.len().max(2), 0);
+            let i = 1 + rng.below(corrupt.len() - 1);
+            corrupt[i] ^= rng.nonzero_byte();
+            let (result, inconsistent) = decode_with_sigma(&received, nsym, &corrupt);
+            assert!(result.is_err(), "trial {}: {:?}", trial, result);
+            // Except a clean word's [1] grown to [1, x], which all-zero syndromes cannot contradict
+            assert!(inconsistent || expected.is_err() || sigma.len() == 1, "trial {}", trial);
+        }
+        let codeword = encode(&[1, 2, 3], 4, cached_generator(4));
+        assert_eq!(decode_with_sigma(&codeword, 4, &[1]), (Ok((vec![1, 2, 3], vec![])), false));
+        // A locator for a clean codeword is consistent with its zero syndromes but still refused
+        assert!(matches!(decode_with_sigma(&codeword, 4, &[1, 7]), (Err(RsError::ChienSearchFailed | RsError::VerificationFailed), false)));
+        assert!(matches!(decode_with_sigma(&codeword, 4, &[0, 1]), (Err(RsError::InvalidParameters(_)), false)));
+        let mut received = codeword.clone();
+        received[1] ^= 0x40;
+        assert!(matches!(decode_with_sigma(&received, 4, &[1, 2, 3, 0, 0]), (Err(RsError::ChienSearchFailed | RsError::VerificationFailed), true)));
+        assert!(matches!(decode_with_sigma(&codeword, 4, &[1, 2, 3, 4]), (Err(RsError::InvalidParameters(_)), false)));
+    }
+
+    #[test]
+    fn test_errata_decode() {
+        let nsym = 6;
+        let gen = build_generator(nsym);
+        let message = b"errata";
+        let mut rng = sim::Rng::new(31);
+        // Every split with 2e + h = nsym
+        for (errors, erasures) in [(0usize, 6usize), (1, 4), (2, 2), (3, 0)] {
+            for _ in 0..20 {
+                let mut codeword = encode(message, nsym, &gen);
+                let hit = rng.distinct(errors + erasures, codeword.len());
+                for &pos in &hit {
+                    codeword[pos] ^= rng.nonzero_byte();
+                }
+                let (decoded, positions) = decode_errata(&codeword, nsym, &hit[..erasures]).unwrap();
+                assert_eq!(decoded, message);
+                let mut expected = hit.clone();
+                expected.sort_unstable();
+                assert_eq!(positions, expected);
+            }
+        }
+        let codeword = encode(message, nsym, &gen);
+        assert_eq!(decode_errata(&codeword, nsym, &[1, 1]), Err(RsError::InvalidErasure));
+        assert_eq!(decode_errata(&codeword, nsym, &[12]), Err(RsError::InvalidErasure));
+    }
+
+    #[test]
+    fn test_decode_truncated() {
+        let (n, nsym) = (40, 8);
+        let mut rng = sim::Rng::new(418);
+        let message: Vec<u8> = (0..n - nsym).map(|_| rng.nonzero_byte()).collect();
+        let codeword = encode(&message, nsym, cached_generator(nsym));
+        // One lost symbol leaves room for 3 errors, nsym lost none
+        for (cut, errors) in [(1, 3), (nsym, 0)] {
+            let mut received = codeword[..n - cut].to_vec();
+            for pos in rng.distinct(errors, n - cut) {
+                received[pos] ^= rng.nonzero_byte();
+            }
+            let (decoded, positions) = decode_truncated(&received, nsym, n).unwrap();
+            assert_eq!(decoded, message);
+            assert!(positions.iter().filter(|&&p| p >= n - cut).count() <= cut);
+            assert_eq!(positions.iter().filter(|&&p| p < n - cut).count(), errors);
+        }
+        assert_eq!(decode_truncated(&codeword, nsym, n).unwrap(), (message.clone(), vec![]));
+        assert_eq!(decode_truncated(&codeword[..n - nsym - 1], nsym, n), Err(RsError::Truncated { missing: nsym + 1, nsym }));
+        assert!(matches!(decode_truncated(&[0u8; 41], nsym, n), Err(RsError::InvalidParameters(_))));
+        // nsym losses use the whole budget: an error on top is filled in, not detected
+        let mut received = codeword[..n - nsym].to_vec();
+        received[3] ^= 1;
+        assert_eq!(decode_truncated(&received, nsym, n).unwrap().0, received);
+    }
+
+    #[test]
+    fn test_inversionless_bm_is_scaled_standard() {
+        let mut rng = sim::Rng::new(410);
+        let gf1024 = crate::gf2m::Gf2m::new(10, 0x409).unwrap();
+        for trial in 0..2000 {
+            // Arbitrary syndromes, mostly not from any correctable pattern, so locators past t and
+            // with trailing zeros turn up too
+            let len = if trial % 100 == 0 { 254 } else { 1 + rng.below(40) };
+            let syndromes: Vec<u8> = (0..len).map(|_| if rng.below(4) == 0 { 0 } else { rng.next_u64() as u8 }).collect();
+            let (standard, inversionless) = (berlekamp_massey(&syndromes), berlekamp_massey_inversionless(&syndromes));
+            let scale = inversionless[0];
+            assert_ne!(scale, 0);
+            assert_eq!(inversionless, standard.iter().map(|&c| gf_mul(c, scale)).collect::<Vec<_>>(), "{:?}", syndromes);
+
+            let wide: Vec<u16> = (0..len).map(|_| rng.below(1024) as u16).collect();
+            let (standard, inversionless) = (berlekamp_massey_in(&gf1024, &wide), berlekamp_massey_inversionless_in(&gf1024, &wide));
+            assert_eq!(inversionless, standard.iter().map(|&c| gf1024.mul(c, inversionless[0])).collect::<Vec<_>>(), "{:?}", wide);
+        }
+    }
+
+    #[test]
+    fn test_chien_forney_are_scale_invariant() {
+        let mut rng = sim::Rng::new(4100);
+        let (nsym, n) = (16, 100);
+        let gen = cached_generator(nsym);
+        for _ in 0..300 {
+            let message: Vec<u8> = (0..n - nsym).map(|_| rng.next_u64() as u8).collect();
+            let mut received = encode(&message, nsym, gen);
+            let errors = 1 + rng.below(nsym / 2);
+            for pos in rng.distinct(errors, n) {
+                received[pos] ^= rng.nonzero_byte();
+            }
+            let syndromes = calc_syndromes(&received, nsym);
+            let sigma = berlekamp_massey(&syndromes);
+            let factor = rng.nonzero_byte();
+            let scaled: Vec<u8> = sigma.iter().map(|&c| gf_mul(c, factor)).collect();
+            let (mut roots, mut scaled_roots) = (vec![], vec![]);
+            chien_roots_into(&sigma, n, &mut roots);
+            chien_roots_into(&scaled, n, &mut scaled_roots);
+            assert_eq!(roots, scaled_roots);
+            let (mut plain, mut from_scaled) = (ForneyBuffers::default(), ForneyBuffers::default());
+            forney_roots_into(&syndromes, &sigma, &roots, &mut plain);
+            forney_roots_into(&syndromes, &scaled, &roots, &mut from_scaled);
+            assert_eq!(plain.magnitudes, from_scaled.magnitudes);
+        }
+    }
+
+    #[test]
+    fn test_chien_matches_direct_evaluation() {
+        let mut rng = sim::Rng::new(4090);
+        for _ in 0..300 {
+            let n = 2 + rng.below(254);
+            // Locators of real error sets, and arbitrary polynomials with fewer roots than degree
+            let sigma: Vec<u8> = if rng.below(2) == 0 {
+                let count = 1 + rng.below(n.min(130));
+                let positions = rng.distinct(count, n);
+                positions.iter().fold(vec![1u8], |s, &p| poly_mul(&s, &[1, GF.exp[(n - 1 - p) % 255]]))
+            } else {
+                (0..2 + rng.below(200)).map(|_| rng.next_u64() as u8).collect()
+            };
+            let direct: Vec<usize> = (0..n).filter(|&p| poly_eval_at(&sigma, GF.exp[(255 - (n - 1 - p) % 255) % 255]) == 0).collect();
+            assert_eq!(chien_search(&sigma, n), direct, "n={} sigma={:?}", n, sigma);
+        }
+        // Longer than the field has exponents: the terms fold modulo 255 rather than overrun
+        for sigma in [[&[1u8][..], &[0; 298], &[5]].concat(), (0..600).map(|_| rng.next_u64() as u8).collect()] {
+            let direct: Vec<usize> = (0..255).filter(|&p| poly_eval_at(&sigma, GF.exp[(255 - (254 - p) % 255) % 255]) == 0).collect();
+            assert_eq!(chien_search(&sigma, 255), direct);
+        }
+    }
+
+    #[test]
+    fn test_high_redundancy_codes() {
+        let mut rng = sim::Rng::new(409);
+        // (nsym, errors, erasures): RS(255,1) up to its full t = 127 and all 254 erasures,
+        // RS(255,128) with 60+ errors, RS(255,253) with its single correctable error
+        for (nsym, errors, erasures) in [(254, 127, 0), (254, 100, 54), (254, 0, 254), (254, 1, 0), (127, 60, 0), (127, 63, 0), (127, 40, 47), (2, 1, 0)] {
+            let codec = RsCodec::new(nsym, 255).unwrap();
+            let custom = RsCodec::with_options(nsym, 255, CodeOptions { fcr: 1, generator: 2, prim: 0x187 }).unwrap();
+            let mut decoder = RsDecoder::new(nsym, 255);
+            for _ in 0..5 {
+                let message: Vec<u8> = (0..255 - nsym).map(|_| rng.next_u64() as u8).collect();
+                let codeword = codec.encode(&message);
+                assert!(syndromes_zero(&calc_syndromes(&codeword, nsym)));
+                let mut received = codeword.clone();
+                let hit = rng.distinct(errors + erasures, 255);
+                for &pos in &hit {
+                    received[pos] ^= rng.nonzero_byte();
+                }
+                let flagged = rng.distinct(erasures, hit.len()).into_iter().map(|i| hit[i]).collect::<Vec<_>>();
+                assert_eq!(codec.decode_errata(&received, &flagged).unwrap(), (message.clone(), hit.clone()), "nsym={} errors={} erasures={}", nsym, errors, erasures);
+                // The same error pattern through the general-field path
+                let mut other = custom.encode(&message);
+                for &pos in &hit {
+                    other[pos] ^= received[pos] ^ codeword[pos];
+                }
+                assert_eq!(custom.decode_errata(&other, &flagged).unwrap(), (message.clone(), hit.clone()));
+                if erasures == 0 {
+                    assert_eq!(decoder.decode_with(&received).unwrap(), (&message[..], &hit[..]));
+                }
+            }
+        }
+        // One past t cannot decode back to the sent byte: minimum distance is 255
+        let codec = RsCodec::new(254, 255).unwrap();
+        let mut received = codec.encode(&[0x5a]);
+        for pos in rng.distinct(128, 255) {
+            received[pos] ^= rng.nonzero_byte();
+        }
+        assert!(!matches!(codec.decode(&received), Ok((message, _)) if message == [0x5a]));
+    }
+
+    #[test]
+    fn test_simd_encode_matches_scalar() {
+        let mut rng = sim::Rng::new(364);
+        let kernels: Vec<Kernel> = simd::COMPILED.iter().copied().filter(|k| k.supported()).collect();
+        for _ in 0..200 {
+            // Messages shorter than one vector as well as full-length blocks
+            let limit = if rng.below(2) == 0 { 31 } else { 254 };
+            let k = 1 + rng.below(limit);
+            let nsym = 1 + rng.below(255 - k);
+            let message: Vec<u8> = (0..k).map(|_| rng.next_u64() as u8).collect();
+            let gen = cached_generator(nsym);
+            let expected = encode_scalar(&message, nsym, gen);
+            assert!(syndromes_zero(&calc_syndromes(&expected, nsym)));
+            assert_eq!(encode(&message, nsym, gen), expected, "k={} nsym={}", k, nsym);
+            for &kernel in &kernels {
+                assert_eq!(encode_with(kernel, &message, nsym, gen), expected, "{} k={} nsym={}", kernel.name(), k, nsym);
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_syndromes_matches_recomputation() {
+        let mut rng = sim::Rng::new(372);
+        for _ in 0..300 {
+            let n = 2 + rng.below(254);
+            let nsym = 1 + rng.below(n - 1);
+            let mut word: Vec<u8> = (0..n).map(|_| rng.next_u64() as u8).collect();
+            let mut syndromes = calc_syndromes(&word, nsym);
+            let count = rng.below(n.min(20) + 1);
+            let positions = rng.distinct(count, n);
+            let magnitudes: Vec<u8> = positions.iter().map(|_| rng.next_u64() as u8).collect();
+            for (&pos, &mag) in positions.iter().zip(&magnitudes) {
+                word[pos] ^= mag;
+            }
+            update_syndromes(&mut syndromes, n, &positions, &magnitudes);
+            assert_eq!(syndromes, calc_syndromes(&word, nsym), "n={} nsym={} positions={:?}", n, nsym, positions);
+        }
+    }
+
+    // The clone-per-length-change implementation berlekamp_massey_into replaced, kept as its oracle
+    fn berlekamp_massey_reference<F: Field>(field: &F, syndromes: &[F::Elem]) -> Vec<F::Elem> {
+        let zero = F::Elem::default();
+        let n = syndromes.len();
+        let mut c = vec![field.one()]; // Current error locator
+        let mut b = vec![field.one()]; // Previous error locator
+        let mut l = 0usize;    // Number of errors
+        let mut m = 1usize;    // Shift counter
+        let mut delta_prev = field.one();
+
+        for r in 0..n {
+            // Compute discrepancy
+            let mut delta = syndromes[r];
+            for i in 1..=l.min(c.len() - 1) {
+                delta ^= field.mul(c[i], syndromes[r - i]);
+            }
+
+            if delta == zero {
+                m += 1;
+            } else if 2 * l <= r {
+                // Length change
+                let t = c.clone();
+                let scale = field.mul(delta, field.inv(delta_prev));
+
+                // c(x) = c(x) - delta/delta_prev * x^m * b(x)
+                while c.len() < b.len() + m {
+                    c.push(zero);
+                }
+                for (i, &bi) in b.iter().enumerate() {
+                    c[i + m] ^= field.mul(scale, bi);
+                }
+
+                l = r + 1 - l;
+                b = t;
+                delta_prev = delta;
+                m = 1;
+            } else {
+                // No length change
+                let scale = field.mul(delta, field.inv(delta_prev));
+                while c.len() < b.len() + m {
+                    c.push(zero);
+                }
+                for (i, &bi) in b.iter().enumerate() {
+                    c[i + m] ^= field.mul(scale, bi);
+                }
+                m += 1;
+            }
+        }
+
+        // Trim trailing zeros
+        while c.len() > 1 && c.last() == Some(&zero) {
+            c.pop();
+        }
+
+        c
+    }
+
+    #[test]
+    fn test_berlekamp_massey_matches_reference() {
+        let mut rng = sim::Rng::new(373);
+        let gf4096 = crate::gf2m::Gf2m::new(12, 0x1053).unwrap();
+        let (mut sigma, mut prev) = (Vec::new(), Vec::new());
+        for trial in 0..40_000 {
+            let nsym = rng.below(65);
+            // Sparse vectors exercise the zero-discrepancy steps and the trailing-zero trim
+            let density = 1 + rng.below(4);
+            let syndromes: Vec<u8> = (0..nsym).map(|_| if rng.below(4) < density { rng.next_u64() as u8 } else { 0 }).collect();
+            let expected = berlekamp_massey_reference(&GF, &syndromes);
+            assert_eq!(berlekamp_massey(&syndromes), expected, "{:?}", syndromes);
+            berlekamp_massey_into(&GF, &syndromes, &mut sigma, &mut prev);
+            assert_eq!(sigma, expected);
+            if trial % 4 == 0 {
+                let wide: Vec<u16> = (0..nsym).map(|_| (rng.next_u64() % 4096) as u16).collect();
+                assert_eq!(berlekamp_massey_in(&gf4096, &wide), berlekamp_massey_reference(&gf4096, &wide), "{:?}", wide);
+            }
+        }
+    }
+
+    // Chien search then the full-Omega forney on positions, checked by recomputing syndromes:
+    // the stages forney_roots_into replaced, kept as its oracle
+    fn decode_reference(codeword: &[u8], nsym: usize, erasures: &[usize]) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+        let (n, h) = (codeword.len(), erasures.len());
+        let syndromes = calc_syndromes(codeword, nsym);
+        if syndromes_zero(&syndromes) {
+            return Ok((codeword[..n - nsym].to_vec(), vec![]));
+        }
+        let gamma = erasures.iter().fold(vec![1u8], |gamma, &pos| poly_mul(&gamma, &[1, GF.exp[(n - 1 - pos) % 255]]));
+        let modified: Vec<u8> = (0..nsym).map(|i| gamma.iter().enumerate().take(i + 1).fold(0, |acc, (j, &gj)| acc ^ gf_mul(syndromes[i - j], gj))).collect();
+        let lambda = berlekamp_massey(&modified[h..]);
+        if h == 0 && lambda.len() == 1 {
+            return Err(RsError::TrivialLocator);
+        }
+        if 2 * (lambda.len() - 1) + h > nsym {
+            return Err(RsError::TooManyErrors);
+        }
+        let psi = poly_mul(&lambda, &gamma);
+        let positions = chien_search(&psi, n);
+        if positions.len() != psi.len() - 1 {
+            return Err(RsError::ChienSearchFailed);
+        }
+        let mut corrected = codeword.to_vec();
+        let magnitudes = forney(&syndromes, &psi, &positions, n);
+        for (&pos, &mag) in positions.iter().zip(&magnitudes) {
+            corrected[pos] ^= mag;
+        }
+        if !syndromes_zero(&calc_syndromes(&corrected, nsym)) {
+            return Err(RsError::VerificationFailed);
+        }
+        let changed = positions.into_iter().zip(magnitudes).filter(|&(_, mag)| mag != 0).map(|(pos, _)| pos).collect();
+        Ok((corrected[..n - nsym].to_vec(), changed))
+    }
+
+    #[test]
+    fn test_forney_roots_matches_reference() {
+        let mut rng = sim::Rng::new(378);
+        for trial in 0..6_000 {
+            let nsym = [2, 7, 16, 32, 64][trial % 5];
+            let n = nsym + 1 + rng.below(255 - nsym);
+            let message: Vec<u8> = (0..n - nsym).map(|_| rng.next_u64() as u8).collect();
+            let mut received = encode(&message, nsym, cached_generator(nsym));
+            // Up to nsym symbols hit, so beyond-capacity patterns reach Chien and Forney too
+            let count = rng.below(nsym.min(n) + 1);
+            let hit = rng.distinct(count, n);
+            for &pos in &hit {
+                received[pos] ^= rng.nonzero_byte();
+            }
+            let expected = decode_reference(&received, nsym, &[]);
+            assert_eq!(decode(&received, nsym), expected, "n={} nsym={} hit={:?}", n, nsym, hit);
+            let erasures = &hit[..rng.below(hit.len() + 1)];
+            assert_eq!(decode_errata(&received, nsym, erasures), decode_reference(&received, nsym, erasures), "n={} nsym={} hit={:?} erasures={}", n, nsym, hit, erasures.len());
+        }
+    }
+
+    #[test]
+    fn test_cached_generator_matches_build() {
+        // Every slot filled from many threads at once must match a fresh build and be shared
+        std::thread::scope(|scope| {
+            for t in 0..8 {
+                scope.spawn(move || {
+                    for nsym in (0..255).map(|i| (i * 7 + t * 31) % 255) {
+                        assert_eq!(cached_generator(nsym), build_generator(nsym).as_slice(), "nsym={}", nsym);
+                    }
+                });
+            }
+        });
+        assert!(std::ptr::eq(cached_generator(32), cached_generator(32)));
+    }
+
+    #[test]
+    fn test_parameter_validation() {
+        for (nsym, nsize) in [(1, 2), (2, 255), (254, 255), (32, 255)] {
+            assert_eq!(validate_params(nsym, nsize), Ok(()), "({}, {})", nsym, nsize);
+        }
+        let cases = [
+            (0, 10, "nsym must be at least 1"),
+            (0, 256, "nsym must be at least 1"),
+            (4, 256, "nsize must be at most 255 over GF(256)"),
+            (4, 300, "nsize must be at most 255 over GF(256)"),
+            (255, 255, "nsym must be less than nsize (k >= 1)"),
+            (10, 10, "nsym must be less than nsize (k >= 1)"),
+            (11, 10, "nsym must be less than nsize (k >= 1)"),
+            (1, 1, "nsym must be less than nsize (k >= 1)"),
+        ];
+        for (nsym, nsize, constraint) in cases {
+            assert_eq!(validate_params(nsym, nsize), Err(RsError::InvalidParameters(constraint)), "({}, {})", nsym, nsize);
+        }
+        assert_eq!(RsError::InvalidParameters("nsym must be at least 1").to_string(), "invalid RS parameters: nsym must be at least 1");
+
+        assert_eq!(decode(&[0u8; 256], 4), Err(RsError::InvalidParameters("nsize must be at most 255 over GF(256)")));
+        assert_eq!(decode(&[0u8; 4], 4), Err(RsError::InvalidParameters("nsym must be less than nsize (k >= 1)")));
+        assert_eq!(decode(&[0u8; 3], 4), Err(RsError::CodewordTooShort));
+        assert!(decode(&[0u8; 255], 4).is_ok());
+    }
+
+    #[test]
+    fn test_crc_round_trip() {
+        let nsym = 4;
+        for crc in [Crc::Crc16, Crc::Crc32] {
+            let message = b"Hello, world";
+            let mut codeword = encode_crc(message, nsym, crc);
+            assert_eq!(codeword.len(), message.len() + crc.size() + nsym);
+            assert_eq!(decode_crc(&codeword, nsym, crc).unwrap(), (message.to_vec(), vec![]));
+
+            codeword[3] ^= 0x42;
+            codeword[message.len() + 1] ^= 0x07; // inside the CRC bytes
+            let (decoded, positions) = decode_crc(&codeword, nsym, crc).unwrap();
+            assert_eq!(decoded, message);
+            assert_eq!(positions, vec![3, message.len() + 1]);
+        }
+    }
+
+    #[test]
+    fn test_crc_catches_miscorrection() {
+        let nsym = 4;
+        let crc = Crc::Crc32;
+        let gen = build_generator(nsym);
+        let message = b"Hello, world";
+        let sent = encode_crc(message, nsym, crc);
+
+        // A different valid RS codeword whose frame has a stale CRC. A received word
+        // within t of it decodes "successfully" to the wrong frame
+        let mut wrong_frame = crc.append(message);
+        wrong_frame[0] ^= 0x01;
+        let mut received = encode(&wrong_frame, nsym, &gen);
+        assert_ne!(received, sent);
+        received[6] ^= 0x5a;
+
+        let (frame, _) = decode(&received, nsym).unwrap();
+        assert_eq!(frame, wrong_frame);
+        assert_eq!(decode_crc(&received, nsym, crc), Err(RsError::MiscorrectionDetected));
+    }
+
+    #[test]
+    fn test_crc_length_validation() {
+        assert_eq!(decode_crc(&[0u8; 7], 4, Crc::Crc32), Err(RsError::CodewordTooShort));
+        assert_eq!(decode_crc(&[0u8; 8], 4, Crc::Crc32).unwrap(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_decode_bounded() {
+        let (nsym, gen) = (16, build_generator(16));
+        let mut rng = Rng::new(392);
+        let message: Vec<u8> = (0..48).map(|_| rng.next_u64() as u8).collect();
+        let codeword = encode(&message, nsym, &gen);
+        let hit = |positions: &[usize], rng: &mut Rng| {
+            let mut received = codeword.clone();
+            for &p in positions {
+                received[p] ^= rng.nonzero_byte();
+            }
+            received
+        };
+        // Four symbols of one x4 device, then the same count over two devices
+        let received = hit(&[8, 9, 10, 11], &mut rng);
+        assert_eq!(decode_bounded(&received, nsym, 4, 0).unwrap(), (message.clone(), vec![8, 9, 10, 11]));
+        let received = hit(&[6, 7, 8, 9], &mut rng);
+        assert_eq!(decode(&received, nsym).unwrap().0, message);
+        assert_eq!(decode_bounded(&received, nsym, 4, 0), Err(RsError::FaultModelViolation { groups: 2 }));
+        // Shifting the group boundaries by alignment puts them in one device
+        assert_eq!(decode_bounded(&received, nsym, 4, 2).unwrap(), (message.clone(), vec![6, 7, 8, 9]));
+        // A whole 12-symbol device is past t = 8, but erasing the right group finds it
+        let device: Vec<usize> = (36..48).collect();
+        let received = hit(&device, &mut rng);
+        assert!(decode(&received, nsym).is_err());
+        assert_eq!(decode_bounded(&received, nsym, 12, 0).unwrap(), (message.clone(), device));
+        assert_eq!(decode_bounded(&codeword, nsym, 0, 0), Err(RsError::InvalidParameters("group_size must be at least 1")));
+    }
+
+    #[test]
+    fn test_decode_bounded_rejects_miscorrections() {
+        // Beyond t, a miscorrection rarely lands all its changes in one device
+        let (nsym, gen) = (4, build_generator(4));
+        let mut rng = Rng::new(0xb0);
+        let (mut plain, mut bounded) = (0, 0);
+        for _ in 0..2000 {
+            let message: Vec<u8> = (0..60).map(|_| rng.next_u64() as u8).collect();
+            let mut received = encode(&message, nsym, &gen);
+            for p in rng.distinct(4, received.len()) {
+                received[p] ^= rng.nonzero_byte();
+            }
+            plain += decode(&received, nsym).is_ok() as usize;
+            bounded += decode_bounded(&received, nsym, 2, 0).is_ok() as usize;
+        }
+        assert!(plain > 40 && bounded * 5 < plain, "plain {} bounded {}", plain, bounded);
+    }
+
+    #[test]
+    fn test_error_locations() {
+        // RS(40,32) carrying a 20-byte padded message: data 0..20, padding 20..32, parity 32..40
+        let codec = codec::RsCodec::new(8, 40).unwrap();
+        let message: Vec<u8> = (1..=20).collect();
+        let mut received = codec.encode_padded(&message);
+        for p in [3, 25, 36] {
+            received[p] ^= 0x11;
+        }
+        let (decoded, positions) = codec.decode_padded(&received, &[], 20).unwrap();
+        assert_eq!((decoded, &positions), (message, &vec![3, 25, 36]));
+        let located = error_locations(&positions, 40, 8, Some(20));
+        let at = |position, is_parity, message_index| ErrorLocation { position, is_parity, message_index };
+        assert_eq!(located, vec![at(3, false, Some(3)), at(25, false, None), at(36, true, None)]);
+        assert_eq!(located.iter().map(ErrorLocation::is_padding).collect::<Vec<_>>(), vec![false, true, false]);
+        // Without msg_len every data position is a message byte
+        assert_eq!(error_locations(&positions, 40, 8, None)[1], at(25, false, Some(25)));
+        assert_eq!(error_locations(&[31, 32], 40, 8, None), vec![at(31, false, Some(31)), at(32, true, None)]);
+    }
+
+    #[test]
+    fn test_bit_errors() {
+        let (nsym, gen) = (8, build_generator(8));
+        let message: Vec<u8> = (0..32u8).map(|i| i.wrapping_mul(37)).collect();
+        let codeword = encode(&message, nsym, &gen);
+        let mut received = codeword.clone();
+        received[3] ^= 0x10; // one bit
+        received[20] ^= 0x81; // bits 0 and 7
+        received[35] ^= 0x06; // parity symbol, bits 1 and 2
+        received[9] = 0; // erased: whatever was read there is replaced
+        let (decoded, positions) = decode_errata(&received, nsym, &[9]).unwrap();
+        assert_eq!((decoded, positions.clone()), (message, vec![3, 9, 20, 35]));
+        let magnitudes: Vec<u8> = positions.iter().map(|&p| received[p] ^ codeword[p]).collect();
+        let report = BitErrors::new(&positions, &magnitudes, &[9]);
+        assert_eq!(report.bits, vec![(3, 4), (20, 0), (20, 7), (35, 1), (35, 2)]);
+        assert_eq!((report.single_bit, report.multi_bit, report.replaced), (1, 2, vec![9]));
+        // Without the erasure flag the same magnitude reads as flips
+        assert_eq!(bit_errors(&[9], &[codeword[9]]).len(), codeword[9].count_ones() as usize);
+        assert_eq!(BitErrors::new(&[], &[], &[]), BitErrors::default());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_error_json_tagged() {
+        let cases = [
+            (RsError::TooManyErrors, r#"{"kind":"TooManyErrors"}"#),
+            (RsError::InvalidParameters("nsym must be at least 1"), r#"{"kind":"InvalidParameters","detail":"nsym must be at least 1"}"#),
+            (RsError::CorrectionLimit { found: 5, limit: 3 }, r#"{"kind":"CorrectionLimit","detail":{"found":5,"limit":3}}"#),
+            (RsError::FaultModelViolation { groups: 2 }, r#"{"kind":"FaultModelViolation","detail":{"groups":2}}"#),
+            (RsError::SingularSystem { rank: 5, unknowns: 6 }, r#"{"kind":"SingularSystem","detail":{"rank":5,"unknowns":6}}"#),
+            (RsError::Truncated { missing: 9, nsym: 8 }, r#"{"kind":"Truncated","detail":{"missing":9,"nsym":8}}"#),
+            (RsError::PinnedPositionConflict { positions: PositionSet::new(&[3, 200]).unwrap() }, r#"{"kind":"PinnedPositionConflict","detail":{"positions":[3,200]}}"#),
+        ];
+        for (error, json) in cases {
+            assert_eq!(serde_json::to_string(&error).unwrap(), json);
+            assert_eq!(serde_json::from_str::<RsError>(json).unwrap(), error);
+        }
+        // Known constraints come back as the crate's own strings, anything else as the fallback
+        let known = serde_json::from_str::<RsError>(r#"{"kind":"InvalidParameters","detail":"fcr must be less than 255"}"#).unwrap();
+        assert_eq!(known, RsError::InvalidParameters("fcr must be less than 255"));
+        let unknown = serde_json::from_str::<RsError>(r#"{"kind":"InvalidParameters","detail":"from a newer version"}"#).unwrap();
+        assert_eq!(unknown, RsError::InvalidParameters(UNKNOWN_CONSTRAINT));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_constraint_table_is_complete() {
+        // Every InvalidParameters literal in the sources must round-trip through serde
+        fn literals(dir: &std::path::Path, out: &mut Vec<String>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    literals(&path, out);
+                } else if path.extension().is_some_and(|e| e == "rs") {
+                    let text = std::fs::read_to_string(&path).unwrap();
+                    let needle = "InvalidParameters(\"";
+                    out.extend(text.match_indices(needle).map(|(i, _)| {
+                        let rest = &text[i + needle.len()..];
+                        rest[..rest.find('"').unwrap()].to_string()
+                    }));
+                }
+            }
+        }
+        let mut found = vec![];
+        literals(&std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut found);
+        assert!(found.len() > 30);
+        for constraint in found {
+            assert!(CONSTRAINTS.contains(&constraint.as_str()), "{:?} is missing from CONSTRAINTS", constraint);
+        }
     }
 }
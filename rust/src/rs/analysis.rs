@@ -0,0 +1,226 @@
+// Weight distribution and minimum distance of RS codes, computed from the encoder itself
+// Enumerating messages is out of reach even for tiny GF(256) codes (RS(15,11) has 256^11), so the
+// exact functions enumerate position sets instead. With G the k x n generator matrix (row i the
+// codeword of the unit message e_i), the codewords vanishing on a position set S form a space of
+// dimension k - rank(G_S). That gives the minimum distance, d = n - max{|S| : rank(G_S) < k}, and
+// the weight enumerator by binomial inversion. 2^n rank computations, so keep n to about 20.
+// For full-size codes verify_min_distance checks the low-weight messages instead.
+
+use crate::gf256::{gf_inv, gf_mul};
+use crate::rs::verify::Combinations;
+use crate::rs::{build_generator, encode};
+
+/// Largest n the position-set enumeration accepts
+pub const MAX_EXHAUSTIVE_N: usize = 20;
+
+/// Rows of the systematic generator matrix of RS(n, k): row i encodes the unit message e_i
+pub fn generator_matrix(n: usize, k: usize) -> Vec<Vec<u8>> {
+    assert!(0 < k && k < n && n <= 255, "invalid RS parameters");
+    let generator = build_generator(n - k);
+    (0..k)
+        .map(|i| {
+            let mut message = vec![0u8; k];
+            message[i] = 1;
+            encode(&message, n - k, &generator)
+        })
+        .collect()
+}
+
+/// Columns of generator_matrix(n, k), the k-vectors a position set selects
+fn columns(n: usize, k: usize) -> Vec<Vec<u8>> {
+    assert!(n <= MAX_EXHAUSTIVE_N, "position-set enumeration is for n <= {}", MAX_EXHAUSTIVE_N);
+    let rows = generator_matrix(n, k);
+    (0..n).map(|j| rows.iter().map(|row| row[j]).collect()).collect()
+}
+
+/// Rank over GF(256) of the given vectors (all the same length)
+fn rank(mut vectors: Vec<Vec<u8>>) -> usize {
+    let mut rank = 0;
+    let width = vectors.first().map_or(0, Vec::len);
+    for col in 0..width {
+        let Some(pivot) = (rank..vectors.len()).find(|&r| vectors[r][col] != 0) else { continue };
+        vectors.swap(rank, pivot);
+        let inv = gf_inv(vectors[rank][col]);
+        let pivot_row: Vec<u8> = vectors[rank].iter().map(|&v| gf_mul(v, inv)).collect();
+        for row in vectors.iter_mut().skip(rank + 1) {
+            let factor = row[col];
+            if factor != 0 {
+                for (x, &p) in row.iter_mut().zip(&pivot_row) {
+                    *x ^= gf_mul(factor, p);
+                }
+            }
+        }
+        rank += 1;
+    }
+    rank
+}
+
+/// ranks[s][r]: how many s-subsets S of the positions have rank(G_S) = r
+fn rank_profile(n: usize, k: usize) -> Vec<Vec<u64>> {
+    let columns = columns(n, k);
+    let mut ranks = vec![vec![0u64; k + 1]; n + 1];
+    for (s, counts) in ranks.iter_mut().enumerate() {
+        for set in Combinations::new(n, s) {
+            counts[rank(set.iter().map(|&j| columns[j].clone()).collect())] += 1;
+        }
+    }
+    ranks
+}
+
+/// Minimum Hamming distance of RS(n, k) by enumerating position sets; panics past MAX_EXHAUSTIVE_N
+/// A rank-deficient set's subsets are rank-deficient too, so the search stops at the first size
+/// where every set has full rank.
+pub fn min_distance_exhaustive(n: usize, k: usize) -> usize {
+    let columns = columns(n, k);
+    // Every set smaller than k is deficient, which is the Singleton bound d <= n - k + 1
+    let mut largest_deficient = k - 1;
+    for s in k..n {
+        if !Combinations::new(n, s).any(|set| rank(set.iter().map(|&j| columns[j].clone()).collect()) < k) {
+            break;
+        }
+        largest_deficient = s;
+    }
+    n - largest_deficient
+}
+
+/// Number of codewords of each weight 0..=n of RS(n, k), by enumerating position sets
+/// With q = 256, N_s = sum over s-sets S of q^(k - rank(G_S)) counts each codeword of weight w
+/// C(n - w, s) times; solving that triangular system from w = 0 up gives A_w. Panics past
+/// MAX_EXHAUSTIVE_N, or if 8k + n reaches 128 and the sums could overflow u128.
+pub fn weight_enumerator(n: usize, k: usize) -> Vec<u128> {
+    assert!(8 * k + n < 128, "weight enumerator of RS({}, {}) overflows u128", n, k);
+    let ranks = rank_profile(n, k);
+    let sets: Vec<u128> = ranks.iter().map(|counts| counts.iter().enumerate().map(|(r, &c)| c as u128 * (1u128 << (8 * (k - r)))).sum()).collect();
+    let mut weights = vec![0u128; n + 1];
+    for w in 0..=n {
+        let s = n - w;
+        let lighter: u128 = (0..w).map(|v| weights[v] * binomial(n - v, s)).sum();
+        weights[w] = sets[s] - lighter;
+    }
+    weights
+}
+
+fn binomial(n: usize, r: usize) -> u128 {
+    if r > n {
+        return 0;
+    }
+    (0..r).fold(1u128, |acc, i| acc * (n - i) as u128 / (i + 1) as u128)
+}
+
+/// Result of `verify_min_distance`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceReport {
+    pub n: usize,
+    pub nsym: usize,
+    /// Number of nonzero codewords whose weight was checked
+    pub codewords_checked: u64,
+    /// Lightest weight seen
+    pub min_weight: usize,
+    /// A message encoding to a codeword of that weight
+    pub witness: Vec<u8>,
+}
+
+impl DistanceReport {
+    /// No sampled codeword beat the Singleton bound's d = nsym + 1, and one met it
+    pub fn is_mds(&self) -> bool {
+        self.min_weight == self.nsym + 1
+    }
+}
+
+/// Sample the low-weight codewords of RS(n, n - nsym) for one lighter than nsym + 1
+/// Checks every single-monomial message e_i (its scalar multiples have the same weight) and every
+/// e_i + m e_j for m in `magnitudes`; zero magnitudes are ignored. The unit message e_{k-1}
+/// encodes to the generator polynomial, weight nsym + 1, so a clean report finds exactly that.
+pub fn verify_min_distance(n: usize, nsym: usize, magnitudes: &[u8]) -> DistanceReport {
+    assert!(nsym < n && n <= 255, "invalid RS parameters");
+    let k = n - nsym;
+    let rows = generator_matrix(n, k);
+    let weight = |codeword: &[u8]| codeword.iter().filter(|&&b| b != 0).count();
+    let unit = |i: usize| (0..k).map(|j| (i == j) as u8).collect::<Vec<u8>>();
+
+    let mut report = DistanceReport { n, nsym, codewords_checked: 0, min_weight: n + 1, witness: Vec::new() };
+    let mut consider = |w: usize, message: &dyn Fn() -> Vec<u8>| {
+        report.codewords_checked += 1;
+        if w < report.min_weight {
+            report.min_weight = w;
+            report.witness = message();
+        }
+    };
+    for (i, row) in rows.iter().enumerate() {
+        consider(weight(row), &|| unit(i));
+    }
+    let mut magnitudes: Vec<u8> = magnitudes.iter().copied().filter(|&m| m != 0).collect();
+    magnitudes.sort_unstable();
+    magnitudes.dedup();
+    for pair in Combinations::new(k, 2) {
+        let (i, j) = (pair[0], pair[1]);
+        for &m in &magnitudes {
+            let w = rows[i].iter().zip(&rows[j]).filter(|&(&a, &b)| a != gf_mul(m, b)).count();
+            consider(w, &|| {
+                let mut message = unit(i);
+                message[j] = m;
+                message
+            });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gf256::gf_pow;
+
+    #[test]
+    fn test_rank() {
+        assert_eq!(rank(vec![vec![1, 2, 3], vec![1, 2, 4], vec![0, 0, 7]]), 2);
+        assert_eq!(rank(vec![vec![1, 2, 3], vec![gf_mul(5, 1), gf_mul(5, 2), gf_mul(5, 3)]]), 1);
+        assert_eq!(rank(vec![vec![0, 0], vec![0, 0]]), 0);
+    }
+
+    #[test]
+    fn test_15_11_is_mds() {
+        assert_eq!(min_distance_exhaustive(15, 11), 5);
+        for (n, k) in [(6, 2), (10, 7), (12, 1)] {
+            assert_eq!(min_distance_exhaustive(n, k), n - k + 1, "RS({}, {})", n, k);
+        }
+    }
+
+    #[test]
+    fn test_weight_enumerator_matches_mds_formula() {
+        // An MDS code's weight distribution depends only on n, k and q:
+        // A_w = C(n, w) * sum_{j=0}^{w-d} (-1)^j C(w, j) (q^(w-d+1-j) - 1)
+        let mds = |n: usize, k: usize, w: usize| -> u128 {
+            let d = n - k + 1;
+            if w == 0 {
+                return 1;
+            }
+            if w < d {
+                return 0;
+            }
+            let terms = (0..=w - d).map(|j| (j, binomial(w, j) as i128 * ((1i128 << (8 * (w - d + 1 - j))) - 1)));
+            let sum: i128 = terms.map(|(j, t)| if j % 2 == 0 { t } else { -t }).sum();
+            binomial(n, w) * sum as u128
+        };
+        for (n, k) in [(15, 11), (8, 3)] {
+            let weights = weight_enumerator(n, k);
+            assert_eq!(weights.iter().sum::<u128>(), 1u128 << (8 * k), "RS({}, {}) has q^k codewords", n, k);
+            assert_eq!(weights, (0..=n).map(|w| mds(n, k, w)).collect::<Vec<_>>(), "RS({}, {})", n, k);
+        }
+        // Minimum-weight codewords of RS(15,11): C(15, 5) supports, each with q - 1 multiples
+        assert_eq!(weight_enumerator(15, 11)[5], 3003 * 255);
+    }
+
+    #[test]
+    fn test_verify_min_distance() {
+        let report = verify_min_distance(255, 32, &[]);
+        assert_eq!((report.codewords_checked, report.min_weight), (223, 33));
+        assert!(report.is_mds());
+        assert_eq!(encode(&report.witness, 32, &build_generator(32)).iter().filter(|&&b| b != 0).count(), 33);
+
+        let magnitudes: Vec<u8> = (0..8).map(|i| gf_pow(2, i * 31)).chain([0]).collect();
+        let report = verify_min_distance(40, 8, &magnitudes);
+        assert_eq!(report.codewords_checked, 32 + 496 * 8);
+        assert!(report.is_mds(), "{:?}", report);
+    }
+}
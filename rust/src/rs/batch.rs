@@ -0,0 +1,374 @@
+// Batch encode/decode of independent blocks
+// With the `parallel` feature blocks are spread across the rayon pool; output order always
+// matches input order and one generator is shared by every worker. encode_batch_soa instead
+// vectorizes across blocks: byte i of every message sits in one row, so each step of the parity
+// recurrence is a handful of slice multiply-adds over all lanes at once. decode_batch_screen
+// is the scrubbing path: a syndrome pass over every block, then full decodes for the dirty few.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::gf256::simd;
+use crate::rs::{build_generator, cached_generator, decode, encode, syndromes_zero, RsDecoder, RsError};
+
+/// Per-block decode outcome: (message, corrected positions) or the failure for that block
+pub type BlockResult = Result<(Vec<u8>, Vec<usize>), RsError>;
+
+/// Map `f` over the items, on the rayon pool when `parallel` is set and the feature is enabled
+pub(crate) fn map_blocks<T: Sync, R: Send>(items: &[T], parallel: bool, f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    #[cfg(feature = "parallel")]
+    if parallel {
+        return items.par_iter().map(f).collect();
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = parallel;
+    items.iter().map(f).collect()
+}
+
+/// Encode every message into an nsize-byte codeword, in parallel when the feature is enabled
+/// Panics if any message is not exactly k = nsize - nsym bytes.
+pub fn encode_batch(messages: &[&[u8]], nsym: usize, nsize: usize) -> Vec<Vec<u8>> {
+    encode_batch_with(messages, nsym, nsize, true)
+}
+
+/// encode_batch with an explicit choice between the rayon pool and the calling thread
+pub fn encode_batch_with(messages: &[&[u8]], nsym: usize, nsize: usize, parallel: bool) -> Vec<Vec<u8>> {
+    assert!(nsym < nsize && nsize <= 255, "invalid RS parameters");
+    let k = nsize - nsym;
+    if let Some((i, m)) = messages.iter().enumerate().find(|(_, m)| m.len() != k) {
+        panic!("message {} has length {}, expected k={}", i, m.len(), k);
+    }
+    let generator = build_generator(nsym);
+    map_blocks(messages, parallel, |message| encode(message, nsym, &generator))
+}
+
+/// Decode every codeword; a failing block does not affect the others
+pub fn decode_batch(codewords: &[&[u8]], nsym: usize) -> Vec<BlockResult> {
+    decode_batch_with(codewords, nsym, true)
+}
+
+/// decode_batch with an explicit choice between the rayon pool and the calling thread
+pub fn decode_batch_with(codewords: &[&[u8]], nsym: usize, parallel: bool) -> Vec<BlockResult> {
+    map_blocks(codewords, parallel, |codeword| decode(codeword, nsym))
+}
+
+/// Pair up `in_len`-byte input chunks with `out_len`-byte output chunks and map `f` over them
+fn map_chunks<R: Send>(
+    data: &[u8],
+    in_len: usize,
+    out: &mut [u8],
+    out_len: usize,
+    parallel: bool,
+    f: impl Fn(&[u8], &mut [u8]) -> R + Sync + Send,
+) -> Vec<R> {
+    #[cfg(feature = "parallel")]
+    if parallel {
+        return data.par_chunks(in_len).zip(out.par_chunks_mut(out_len)).map(|(i, o)| f(i, o)).collect();
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = parallel;
+    data.chunks(in_len).zip(out.chunks_mut(out_len)).map(|(i, o)| f(i, o)).collect()
+}
+
+/// Encode back-to-back k-byte messages into back-to-back nsize-byte codewords in `out`
+/// Panics unless data.len() is a multiple of k = nsize - nsym and `out` holds one codeword per message.
+pub fn encode_blocks_into(data: &[u8], out: &mut [u8], nsym: usize, nsize: usize, parallel: bool) {
+    assert!(nsym < nsize && nsize <= 255, "invalid RS parameters");
+    let k = nsize - nsym;
+    assert!(data.len().is_multiple_of(k), "data length {} is not a multiple of k={}", data.len(), k);
+    assert_eq!(out.len(), data.len() / k * nsize, "output must hold one codeword per message");
+    let generator = build_generator(nsym);
+    map_chunks(data, k, out, nsize, parallel, |message, codeword| {
+        codeword.copy_from_slice(&encode(message, nsym, &generator));
+    });
+}
+
+/// Decode back-to-back nsize-byte codewords into back-to-back k-byte messages in `out`
+/// Returns each block's corrected positions or failure; a failed block's slot in `out` holds
+/// its received data bytes. Panics unless the lengths line up as for encode_blocks_into.
+pub fn decode_blocks_into(data: &[u8], out: &mut [u8], nsym: usize, nsize: usize, parallel: bool) -> Vec<Result<Vec<usize>, RsError>> {
+    assert!(nsym < nsize && nsize <= 255, "invalid RS parameters");
+    let k = nsize - nsym;
+    assert!(data.len().is_multiple_of(nsize), "data length {} is not a multiple of n={}", data.len(), nsize);
+    assert_eq!(out.len(), data.len() / nsize * k, "output must hold one message per codeword");
+    map_chunks(data, nsize, out, k, parallel, |codeword, message| match decode(codeword, nsym) {
+        Ok((decoded, positions)) => {
+            message.copy_from_slice(&decoded);
+            Ok(positions)
+        }
+        Err(e) => {
+            message.copy_from_slice(&codeword[..k]);
+            Err(e)
+        }
+    })
+}
+
+/// Result of decode_batch_screen over a flat buffer of n-byte blocks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchOutcome {
+    n: usize,
+    /// Per block: corrected positions (empty for a clean block) or why it could not be decoded
+    pub status: Vec<Result<Vec<usize>, RsError>>,
+    /// (block index, corrected codeword) for every block that needed and got a correction, ascending
+    pub corrected: Vec<(usize, Vec<u8>)>,
+}
+
+impl BatchOutcome {
+    /// Blocks whose syndromes were all zero
+    pub fn clean(&self) -> usize {
+        self.status.len() - self.corrected.len() - self.failed()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.status.iter().filter(|s| s.is_err()).count()
+    }
+
+    /// Write the corrected codewords over their blocks in the buffer that was screened
+    pub fn apply(&self, blocks: &mut [u8]) {
+        assert_eq!(blocks.len(), self.status.len() * self.n, "buffer must hold the screened blocks");
+        for (i, codeword) in &self.corrected {
+            blocks[i * self.n..(i + 1) * self.n].copy_from_slice(codeword);
+        }
+    }
+}
+
+/// Two-pass decode of back-to-back n-byte codewords, in parallel when the feature is enabled
+/// Pass one computes every block's syndromes with the SIMD kernel; pass two runs the full
+/// decoder, starting from those syndromes, on the dirty blocks only. Panics unless
+/// 1 <= nsym < n <= 255 and blocks.len() is a multiple of n.
+pub fn decode_batch_screen(blocks: &[u8], n: usize, nsym: usize) -> BatchOutcome {
+    decode_batch_screen_with(blocks, n, nsym, true)
+}
+
+/// decode_batch_screen with an explicit choice between the rayon pool and the calling thread
+pub fn decode_batch_screen_with(blocks: &[u8], n: usize, nsym: usize, parallel: bool) -> BatchOutcome {
+    assert!(nsym >= 1 && nsym < n && n <= 255, "invalid RS parameters");
+    assert!(blocks.len().is_multiple_of(n), "blocks length {} is not a multiple of n={}", blocks.len(), n);
+    let count = blocks.len() / n;
+    let mut syndromes = vec![0u8; count * nsym];
+    let screen = |(block, out): (&[u8], &mut [u8])| simd::syndromes(block, out);
+    #[cfg(feature = "parallel")]
+    if parallel {
+        blocks.par_chunks(n).zip(syndromes.par_chunks_mut(nsym)).for_each(screen);
+    } else {
+        blocks.chunks(n).zip(syndromes.chunks_mut(nsym)).for_each(screen);
+    }
+    #[cfg(not(feature = "parallel"))]
+    blocks.chunks(n).zip(syndromes.chunks_mut(nsym)).for_each(screen);
+
+    let dirty: Vec<usize> = (0..count).filter(|&i| !syndromes_zero(&syndromes[i * nsym..(i + 1) * nsym])).collect();
+    let repair = |decoder: &mut RsDecoder, &i: &usize| {
+        let result = decoder.decode_dirty(&blocks[i * n..(i + 1) * n], &syndromes[i * nsym..(i + 1) * nsym]);
+        result.map(|(codeword, positions)| (codeword.to_vec(), positions.to_vec()))
+    };
+    #[cfg(feature = "parallel")]
+    let repaired: Vec<_> = if parallel {
+        dirty.par_iter().map_init(|| RsDecoder::new(nsym, n), repair).collect()
+    } else {
+        let mut decoder = RsDecoder::new(nsym, n);
+        dirty.iter().map(|i| repair(&mut decoder, i)).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let repaired: Vec<_> = {
+        let _ = parallel;
+        let mut decoder = RsDecoder::new(nsym, n);
+        dirty.iter().map(|i| repair(&mut decoder, i)).collect()
+    };
+
+    let mut status = vec![Ok(vec![]); count];
+    let mut corrected = Vec::with_capacity(dirty.len());
+    for (i, result) in dirty.into_iter().zip(repaired) {
+        status[i] = result.map(|(codeword, positions)| {
+            corrected.push((i, codeword));
+            positions
+        });
+    }
+    BatchOutcome { n, status, corrected }
+}
+
+/// Fewer blocks than this and encode_batch_soa encodes block by block: the transposes cost more
+/// than lane-parallel recurrence saves
+pub const SOA_MIN_BLOCKS: usize = 16;
+
+/// Lanes per structure-of-arrays pass; the nsym register rows then stay within L1 for nsym <= 64
+const SOA_LANES: usize = 256;
+
+/// Encode N = messages.len() / k back-to-back k-byte messages into N back-to-back codewords
+/// Messages are transposed SOA_LANES at a time so byte i of every block is contiguous, the
+/// parity recurrence runs once over all lanes, and the parity rows are transposed back into
+/// `out`. Below SOA_MIN_BLOCKS blocks it uses the per-block encoder. Panics unless
+/// 1 <= nsym, k + nsym <= 255, messages.len() is a multiple of k and out holds N * (k + nsym) bytes.
+pub fn encode_batch_soa(messages: &[u8], k: usize, nsym: usize, out: &mut [u8]) {
+    assert!(k >= 1 && nsym >= 1 && k + nsym <= 255, "invalid RS parameters");
+    let n = k + nsym;
+    assert!(messages.len().is_multiple_of(k), "messages length {} is not a multiple of k={}", messages.len(), k);
+    assert_eq!(out.len(), messages.len() / k * n, "output must hold one codeword per message");
+    let mut taps = [0u8; 255];
+    for (t, &g) in taps.iter_mut().zip(cached_generator(nsym)[..nsym].iter().rev()) {
+        *t = g;
+    }
+    let taps = &taps[..nsym];
+    let kernel = simd::active();
+    if messages.len() / k < SOA_MIN_BLOCKS {
+        for (message, codeword) in messages.chunks(k).zip(out.chunks_mut(n)) {
+            codeword[..k].copy_from_slice(message);
+            simd::remainder_with(kernel, message, taps, &mut codeword[k..]);
+        }
+        return;
+    }
+
+    let mut columns = vec![0u8; k * SOA_LANES];
+    let mut registers = vec![0u8; nsym * SOA_LANES];
+    let mut feedback = vec![0u8; SOA_LANES];
+    for (group, codewords) in messages.chunks(k * SOA_LANES).zip(out.chunks_mut(n * SOA_LANES)) {
+        let lanes = group.len() / k;
+        let columns = &mut columns[..k * lanes];
+        let registers = &mut registers[..nsym * lanes];
+        let feedback = &mut feedback[..lanes];
+        for (lane, (message, codeword)) in group.chunks(k).zip(codewords.chunks_mut(n)).enumerate() {
+            codeword[..k].copy_from_slice(message);
+            for (i, &byte) in message.iter().enumerate() {
+                columns[i * lanes + lane] = byte;
+            }
+        }
+        registers.fill(0);
+
+        // Register rows form a ring: after step i, parity byte j of every lane is row (i + 1 + j) % nsym
+        for (i, column) in columns.chunks(lanes).enumerate() {
+            let head = &mut registers[i % nsym * lanes..][..lanes];
+            for ((fb, &byte), r) in feedback.iter_mut().zip(column).zip(head.iter_mut()) {
+                *fb = byte ^ *r;
+                *r = 0;
+            }
+            for (j, &tap) in taps.iter().enumerate() {
+                let row = (i + 1 + j) % nsym;
+                simd::mul_add_slice_with(kernel, &mut registers[row * lanes..][..lanes], feedback, tap);
+            }
+        }
+
+        for (j, row) in (0..nsym).map(|j| (j, (k + j) % nsym)) {
+            for (lane, &byte) in registers[row * lanes..][..lanes].iter().enumerate() {
+                codewords[lane * n + k + j] = byte;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    #[test]
+    fn test_mixed_batch_preserves_order() {
+        let (nsym, nsize) = (8, 64);
+        let mut rng = Rng::new(18);
+        let messages: Vec<Vec<u8>> =
+            (0..300).map(|_| (0..nsize - nsym).map(|_| rng.next_u64() as u8).collect()).collect();
+        let refs: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+        let mut codewords = encode_batch(&refs, nsym, nsize);
+        let generator = build_generator(nsym);
+        for (m, c) in messages.iter().zip(&codewords) {
+            assert_eq!(c, &encode(m, nsym, &generator));
+        }
+
+        // Block i gets i % 3 flavours: clean, 4 errors (correctable), 5 errors (uncorrectable)
+        for (i, c) in codewords.iter_mut().enumerate() {
+            let errors = [0, 4, 5][i % 3];
+            for pos in rng.distinct(errors, nsize) {
+                c[pos] ^= rng.nonzero_byte();
+            }
+        }
+        let refs: Vec<&[u8]> = codewords.iter().map(|c| c.as_slice()).collect();
+        let results = decode_batch(&refs, nsym);
+        assert_eq!(results, decode_batch_with(&refs, nsym, false));
+        assert_eq!(results.len(), messages.len());
+        for (i, (result, message)) in results.iter().zip(&messages).enumerate() {
+            match i % 3 {
+                0 => assert_eq!(result, &Ok((message.clone(), vec![]))),
+                1 => assert_eq!(result.as_ref().map(|(m, p)| (m, p.len())), Ok((message, 4))),
+                _ => assert!(result.as_ref().map_or(true, |(m, _)| m != message)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_contiguous_blocks_match_batch() {
+        let (nsym, nsize) = (4, 20);
+        let data: Vec<u8> = (0..16 * 50).map(|i| (i * 7) as u8).collect();
+        let mut codewords = vec![0u8; 20 * 50];
+        encode_blocks_into(&data, &mut codewords, nsym, nsize, true);
+        let messages: Vec<&[u8]> = data.chunks(16).collect();
+        assert_eq!(codewords, encode_batch(&messages, nsym, nsize).concat());
+
+        codewords[3 * 20 + 5] ^= 0x40;
+        codewords[7 * 20..7 * 20 + 3].iter_mut().for_each(|b| *b ^= 0xff);
+        for parallel in [false, true] {
+            let mut decoded = vec![0u8; data.len()];
+            let status = decode_blocks_into(&codewords, &mut decoded, nsym, nsize, parallel);
+            assert_eq!(status[3], Ok(vec![5]));
+            assert!(status[7].is_err());
+            assert_eq!(status.iter().filter(|s| s.is_err()).count(), 1);
+            assert_eq!(&decoded[7 * 16..8 * 16], &codewords[7 * 20..7 * 20 + 16]);
+            decoded[7 * 16..8 * 16].copy_from_slice(&data[7 * 16..8 * 16]);
+            assert_eq!(decoded, data);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "message 1 has length 3")]
+    fn test_encode_batch_rejects_wrong_length() {
+        encode_batch(&[&[0u8; 4], &[0u8; 3]], 4, 8);
+    }
+
+    #[test]
+    fn test_soa_matches_per_block() {
+        let mut rng = Rng::new(371);
+        let mut cases = vec![(1, 223, 32), (SOA_MIN_BLOCKS, 1, 1), (SOA_LANES + 1, 10, 6), (2 * SOA_LANES + 33, 50, 16)];
+        for _ in 0..40 {
+            let nsym = 1 + rng.below(64);
+            let k = 1 + rng.below(255 - nsym);
+            let blocks = 1 + rng.below(600);
+            cases.push((blocks, k, nsym));
+        }
+        for (blocks, k, nsym) in cases {
+            let messages: Vec<u8> = (0..blocks * k).map(|_| rng.next_u64() as u8).collect();
+            let mut out = vec![0u8; blocks * (k + nsym)];
+            encode_batch_soa(&messages, k, nsym, &mut out);
+            let generator = build_generator(nsym);
+            let expected: Vec<u8> = messages.chunks(k).flat_map(|m| encode(m, nsym, &generator)).collect();
+            assert_eq!(out, expected, "blocks={} k={} nsym={}", blocks, k, nsym);
+        }
+    }
+
+    #[test]
+    fn test_screen_matches_per_block_decode() {
+        let (nsym, n) = (16, 120);
+        let mut rng = Rng::new(374);
+        let generator = build_generator(nsym);
+        // All clean, all dirty (correctable or not), and a mix of both
+        for errors_of in [|_: usize| 0, |i: usize| 1 + i % 9, |i: usize| [0, 0, 0, 3, 8, 9][i % 6]] {
+            let messages: Vec<u8> = (0..70 * (n - nsym)).map(|_| rng.next_u64() as u8).collect();
+            let mut blocks: Vec<u8> = messages.chunks(n - nsym).flat_map(|m| encode(m, nsym, &generator)).collect();
+            for (i, block) in blocks.chunks_mut(n).enumerate() {
+                for pos in rng.distinct(errors_of(i), n) {
+                    block[pos] ^= rng.nonzero_byte();
+                }
+            }
+            let outcome = decode_batch_screen(&blocks, n, nsym);
+            assert_eq!(outcome, decode_batch_screen_with(&blocks, n, nsym, false));
+            let mut repaired = blocks.clone();
+            outcome.apply(&mut repaired);
+            for (i, (block, status)) in blocks.chunks(n).zip(&outcome.status).enumerate() {
+                let expected = decode(block, nsym);
+                assert_eq!(status, &expected.clone().map(|(_, positions)| positions), "block {}", i);
+                if let Ok((message, _)) = expected {
+                    assert_eq!(&repaired[i * n..i * n + n - nsym], message.as_slice());
+                }
+            }
+            let clean = blocks.chunks(n).filter(|b| decode(b, nsym) == Ok((b[..n - nsym].to_vec(), vec![]))).count();
+            assert_eq!(outcome.clean(), clean);
+            assert_eq!(outcome.clean() + outcome.corrected.len() + outcome.failed(), 70);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// Messages of any length as a run of codewords, the way reedsolo's RSCodec chunks them
+// The data is split into k-byte chunks, each encoded to an n-byte codeword; the last chunk may
+// be shorter and is encoded as a shortened codeword of len + nsym bytes. Decoding splits the
+// stream into n-byte chunks the same way.
+
+use crate::rs::{RsCodec, RsError};
+
+/// Output of RsCodec::decode_chunked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkedDecode {
+    /// Concatenated corrected messages
+    pub message: Vec<u8>,
+    /// Concatenated corrected codewords (message and parity)
+    pub codeword: Vec<u8>,
+    /// Corrected positions as offsets into the encoded stream, ascending
+    pub errata: Vec<usize>,
+}
+
+impl RsCodec {
+    /// Length of the encode_chunked output for a `len`-byte message
+    pub fn chunked_len(&self, len: usize) -> usize {
+        len + len.div_ceil(self.k()) * self.nsym()
+    }
+
+    /// Encode any-length data as consecutive codewords (the last one shortened)
+    pub fn encode_chunked(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.chunked_len(data.len()));
+        for chunk in data.chunks(self.k()) {
+            out.extend(self.encode_shortened(chunk));
+        }
+        out
+    }
+
+    /// Encode 1..=k bytes; a short message is zero-extended in front and the zeros dropped
+    fn encode_shortened(&self, chunk: &[u8]) -> Vec<u8> {
+        let pad = self.k() - chunk.len();
+        if pad == 0 {
+            return self.encode(chunk);
+        }
+        let mut message = vec![0u8; pad];
+        message.extend_from_slice(chunk);
+        self.encode(&message).split_off(pad)
+    }
+
+    /// Decode an encode_chunked stream
+    /// `erasures` are offsets into the stream. A failure reports the index of the first chunk
+    /// that could not be decoded; a trailing chunk of nsym bytes or fewer is CodewordTooShort.
+    pub fn decode_chunked(&self, stream: &[u8], erasures: &[usize]) -> Result<ChunkedDecode, (usize, RsError)> {
+        self.decode_chunked_observed(stream, erasures, |_| {})
+    }
+
+    /// decode_chunked, handing `observe` each chunk's decode result as it comes (for the Python
+    /// codec's counters); chunks rejected before decoding are not observed
+    pub(crate) fn decode_chunked_observed(
+        &self,
+        stream: &[u8],
+        erasures: &[usize],
+        mut observe: impl FnMut(&Result<(Vec<u8>, Vec<usize>), RsError>),
+    ) -> Result<ChunkedDecode, (usize, RsError)> {
+        let n = self.n();
+        if let Some(&pos) = erasures.iter().find(|&&pos| pos >= stream.len()) {
+            return Err((pos / n, RsError::InvalidErasure));
+        }
+        let mut out = ChunkedDecode { message: vec![], codeword: Vec::with_capacity(stream.len()), errata: vec![] };
+        for (i, chunk) in stream.chunks(n).enumerate() {
+            let start = i * n;
+            if chunk.len() <= self.nsym() {
+                return Err((i, RsError::CodewordTooShort));
+            }
+            let local: Vec<usize> = erasures.iter().filter(|&&p| p / n == i).map(|&p| p - start).collect();
+            let result = self.decode_errata(chunk, &local);
+            observe(&result);
+            let (message, fixed) = result.map_err(|e| (i, e))?;
+            // Systematic code: re-encoding the message gives the corrected codeword
+            if fixed.is_empty() {
+                out.codeword.extend_from_slice(chunk);
+            } else {
+                out.codeword.extend(self.encode_shortened(&message));
+            }
+            out.message.extend(message);
+            out.errata.extend(fixed.iter().map(|&pos| start + pos));
+        }
+        Ok(out)
+    }
+
+    /// Per-chunk validity of an encode_chunked stream
+    pub fn check_chunked(&self, stream: &[u8]) -> Vec<bool> {
+        stream.chunks(self.n()).map(|chunk| chunk.len() > self.nsym() && self.check(chunk)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunked_round_trip() {
+        let codec = RsCodec::new(10, 40).unwrap();
+        let data: Vec<u8> = (0..100u8).collect();
+        let mut stream = codec.encode_chunked(&data);
+        // 100 bytes -> chunks of 30, 30, 30, 10
+        assert_eq!(stream.len(), codec.chunked_len(100));
+        assert_eq!(stream.len(), 3 * 40 + 20);
+        assert_eq!(codec.check_chunked(&stream), vec![true; 4]);
+        assert!(codec.encode_chunked(&[]).is_empty());
+
+        for pos in [3, 45, 79, 121, 139] {
+            stream[pos] ^= 0x5a;
+        }
+        assert_eq!(codec.check_chunked(&stream), vec![false, false, true, false]);
+        let clean = codec.encode_chunked(&data);
+        let out = codec.decode_chunked(&stream, &[]).unwrap();
+        assert_eq!(out.message, data);
+        assert_eq!(out.codeword, clean);
+        assert_eq!(out.errata, vec![3, 45, 79, 121, 139]);
+
+        // Ten erasures in the shortened tail chunk
+        let erased: Vec<usize> = (120..130).collect();
+        let mut damaged = clean.clone();
+        for &pos in &erased {
+            damaged[pos] = 0;
+        }
+        assert_eq!(codec.decode_chunked(&damaged, &erased).unwrap().message, data);
+    }
+
+    #[test]
+    fn test_chunked_failures() {
+        let codec = RsCodec::new(4, 20).unwrap();
+        let mut stream = codec.encode_chunked(&[7u8; 40]);
+        assert_eq!(codec.decode_chunked(&stream[..44], &[]), Err((2, RsError::CodewordTooShort)));
+        for b in &mut stream[20..23] {
+            *b ^= 1;
+        }
+        assert_eq!(codec.decode_chunked(&stream, &[]).unwrap_err().0, 1);
+        assert_eq!(codec.decode_chunked(&stream, &[stream.len()]).unwrap_err().1, RsError::InvalidErasure);
+    }
+}
@@ -0,0 +1,825 @@
+// Validated RS parameters with the generator polynomial built once
+
+use std::borrow::Cow;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::gf256::simd;
+#[cfg(not(feature = "small-tables"))]
+use crate::gf256::simd::Kernel;
+use crate::rs::{
+    berlekamp_massey_in, cached_generator, calc_syndromes, check_pinned, decode_errata_with, encode, pad_truncated, parity_into, syndromes_zero, validate_params, BmVariant, CodeOptions,
+    Codeword, CustomRs, PositionSet, RsError,
+};
+use crate::rs::in_place::correct_in_place;
+#[cfg(not(feature = "small-tables"))]
+use crate::rs::{cached_split_tables, SplitTables};
+
+/// Decoder policy, independent of the code itself (see CodeOptions for that)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct DecodeOptions {
+    /// Most unflagged errors a decode may correct; None allows the full nsym / 2
+    /// A received word more than t symbols from the sent codeword can still lie within t of
+    /// another codeword, and bounded-distance decoding then "corrects" it to the wrong message.
+    /// Capping corrections at m turns those into detected failures: an error pattern of weight w
+    /// can only miscorrect if w + m > nsym, so up to nsym - m errors are always detected, at the
+    /// cost of rejecting blocks with between m + 1 and t real errors. Erasures do not count.
+    pub max_correct: Option<usize>,
+    /// Berlekamp-Massey formulation; both decode every block identically, Inversionless mirrors
+    /// hardware datapaths for verification against them
+    pub bm: BmVariant,
+    /// Do the same work for every block, as a hardware decoder does, for latency modeling: no
+    /// clean-codeword shortcut, BM always nsym iterations at full width, Chien and Forney over
+    /// all n positions (see RsDecoder::with_fixed_work). Results are identical. Errors-only
+    /// decodes of the default code only: erasure decodes take the normal path, and codecs with
+    /// custom CodeOptions reject it.
+    pub fixed_work: bool,
+    /// Positions known to be correct (a header validated out of band, bytes re-read from a
+    /// redundant copy), in the codec's symbol order. A decode that locates an error at one is a
+    /// miscorrection and fails with PinnedPositionConflict instead of applying it; nothing else
+    /// changes, so pinned bytes still count towards the syndromes and the correction capacity.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "PositionSet::is_empty"))]
+    pub pinned: PositionSet,
+}
+
+/// Which end of the codeword polynomial comes first in the bytes
+/// This crate writes the highest-degree coefficient first: byte 0 is x^(n-1), the message leads
+/// and the parity ends the codeword. Some libraries and hardware blocks use the mirror image, byte
+/// 0 as x^0 with the parity at the front. A LowestFirst codec encodes to, decodes from and reports
+/// positions and erasures in that order; messages are in the same order for both, so decoding the
+/// reversed codeword with the opposite order gives the same message and mirrored positions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SymbolOrder {
+    #[default]
+    HighestFirst,
+    LowestFirst,
+}
+
+/// Everything RsCodec::from_params needs to rebuild a codec, for recording which code produced a
+/// result. Codewords are always systematic, [message | parity] in HighestFirst order. With
+/// `serde`, options, decode_options and symbol_order may be absent and then mean the defaults, as
+/// in forms saved before they existed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodecParams {
+    pub nsize: usize,
+    pub nsym: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub options: CodeOptions,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub decode_options: DecodeOptions,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub symbol_order: SymbolOrder,
+}
+
+/// decode_ref's result: a clean block's message is borrowed from the codeword, only a block that
+/// needed corrections (or any block of a LowestFirst codec) is copied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeOutcome<'a> {
+    Clean(&'a [u8]),
+    Corrected { message: Vec<u8>, positions: Vec<usize> },
+}
+
+impl DecodeOutcome<'_> {
+    pub fn message(&self) -> &[u8] {
+        match self {
+            DecodeOutcome::Clean(message) => message,
+            DecodeOutcome::Corrected { message, .. } => message,
+        }
+    }
+
+    /// Corrected positions, ascending; empty when clean
+    pub fn positions(&self) -> &[usize] {
+        match self {
+            DecodeOutcome::Clean(_) => &[],
+            DecodeOutcome::Corrected { positions, .. } => positions,
+        }
+    }
+
+    /// True if nothing was corrected (a LowestFirst codec's clean blocks come back as Corrected
+    /// with no positions, since their message is not a slice of the codeword)
+    pub fn is_clean(&self) -> bool {
+        self.positions().is_empty()
+    }
+
+    /// (message, positions) as decode returns them
+    pub fn into_owned(self) -> (Vec<u8>, Vec<usize>) {
+        match self {
+            DecodeOutcome::Clean(message) => (message.to_vec(), vec![]),
+            DecodeOutcome::Corrected { message, positions } => (message, positions),
+        }
+    }
+}
+
+/// RS(nsize, nsize - nsym) over GF(256)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsCodec {
+    nsym: usize,
+    nsize: usize,
+    generator: Vec<u8>,
+    /// Word-at-a-time encoder for the default code when no SIMD kernel is available; shared per
+    /// nsym, 256 bytes per 8 parity bytes (1 KB for nsym = 32). Not built with `small-tables`.
+    #[cfg(not(feature = "small-tables"))]
+    split: &'static SplitTables,
+    /// Set only for non-default CodeOptions; the default code uses the static-table path
+    custom: Option<Box<CustomRs>>,
+    decode_options: DecodeOptions,
+    order: SymbolOrder,
+}
+
+impl RsCodec {
+    /// Fails with InvalidParameters unless 1 <= nsym < nsize <= 255
+    pub fn new(nsym: usize, nsize: usize) -> Result<Self, RsError> {
+        validate_params(nsym, nsize)?;
+        Ok(Self {
+            nsym,
+            nsize,
+            generator: cached_generator(nsym).to_vec(),
+            #[cfg(not(feature = "small-tables"))]
+            split: cached_split_tables(nsym),
+            custom: None,
+            decode_options: DecodeOptions::default(),
+            order: SymbolOrder::HighestFirst,
+        })
+    }
+
+    /// Codec with a non-default fcr, generator element or field polynomial
+    /// Also fails with InvalidParameters if prim is not primitive or the generator element is not.
+    pub fn with_options(nsym: usize, nsize: usize, options: CodeOptions) -> Result<Self, RsError> {
+        if options == CodeOptions::default() {
+            return Self::new(nsym, nsize);
+        }
+        let custom = CustomRs::new(nsym, nsize, options)?;
+        Ok(Self {
+            nsym,
+            nsize,
+            generator: custom.generator().to_vec(),
+            #[cfg(not(feature = "small-tables"))]
+            split: cached_split_tables(nsym),
+            custom: Some(Box::new(custom)),
+            decode_options: DecodeOptions::default(),
+            order: SymbolOrder::HighestFirst,
+        })
+    }
+
+    pub fn options(&self) -> CodeOptions {
+        self.custom.as_ref().map_or_else(CodeOptions::default, |c| c.options())
+    }
+
+    /// The codec's parameters; from_params(&codec.params()) equals codec
+    pub fn params(&self) -> CodecParams {
+        CodecParams { nsize: self.nsize, nsym: self.nsym, options: self.options(), decode_options: self.decode_options, symbol_order: self.order }
+    }
+
+    /// with_options, with_decode_options and with_symbol_order, with their validation
+    pub fn from_params(params: &CodecParams) -> Result<Self, RsError> {
+        Ok(Self::with_options(params.nsym, params.nsize, params.options)?.with_decode_options(params.decode_options)?.with_symbol_order(params.symbol_order))
+    }
+
+    /// The same code with a decoder policy; fails with InvalidParameters if max_correct > nsym / 2
+    /// or a pinned position is outside the codeword
+    pub fn with_decode_options(mut self, options: DecodeOptions) -> Result<Self, RsError> {
+        if options.max_correct.is_some_and(|m| m > self.max_errors()) {
+            return Err(RsError::InvalidParameters("max_correct must be at most nsym / 2"));
+        }
+        if options.pinned.max().is_some_and(|p| p >= self.nsize) {
+            return Err(RsError::InvalidParameters("pinned positions must be below nsize"));
+        }
+        if options.fixed_work && self.custom.is_some() {
+            return Err(RsError::InvalidParameters("fixed_work needs the default CodeOptions"));
+        }
+        self.decode_options = options;
+        Ok(self)
+    }
+
+    pub fn decode_options(&self) -> DecodeOptions {
+        self.decode_options
+    }
+
+    /// The same code with its bytes in `order`; see SymbolOrder
+    pub fn with_symbol_order(mut self, order: SymbolOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn symbol_order(&self) -> SymbolOrder {
+        self.order
+    }
+
+    /// The codeword in HighestFirst order: borrowed as is, or reversed for LowestFirst
+    fn highest_first<'a>(&self, codeword: &'a [u8]) -> Cow<'a, [u8]> {
+        match self.order {
+            SymbolOrder::HighestFirst => Cow::Borrowed(codeword),
+            SymbolOrder::LowestFirst => Cow::Owned(codeword.iter().rev().copied().collect()),
+        }
+    }
+
+    /// Run a HighestFirst decode on a codeword in this codec's order, mirroring erasures on the
+    /// way in and corrected positions (kept ascending) on the way out. Out-of-range erasures are
+    /// passed through unchanged so they fail as they would have.
+    fn ordered<T>(
+        &self,
+        codeword: &[u8],
+        erasures: &[usize],
+        decode: impl FnOnce(&[u8], &[usize]) -> Result<(T, Vec<usize>), RsError>,
+    ) -> Result<(T, Vec<usize>), RsError> {
+        if self.order == SymbolOrder::HighestFirst {
+            return decode(codeword, erasures);
+        }
+        let n = codeword.len();
+        let mirror = |p: usize| if p < n { n - 1 - p } else { p };
+        let erasures: Vec<usize> = erasures.iter().map(|&p| mirror(p)).collect();
+        let (value, positions) = decode(&self.highest_first(codeword), &erasures)?;
+        Ok((value, positions.into_iter().rev().map(mirror).collect()))
+    }
+
+    pub fn nsym(&self) -> usize {
+        self.nsym
+    }
+
+    pub fn n(&self) -> usize {
+        self.nsize
+    }
+
+    pub fn k(&self) -> usize {
+        self.nsize - self.nsym
+    }
+
+    /// Symbol errors correctable without erasure information
+    pub fn max_errors(&self) -> usize {
+        self.nsym / 2
+    }
+
+    /// Generator polynomial, generator[i] = coefficient of x^i
+    pub fn generator(&self) -> &[u8] {
+        &self.generator
+    }
+
+    /// Systematic encode; panics unless message is exactly k bytes
+    pub fn encode(&self, message: &[u8]) -> Vec<u8> {
+        assert_eq!(message.len(), self.k(), "message must be k={} bytes", self.k());
+        let mut codeword = match &self.custom {
+            Some(custom) => custom.encode(message),
+            #[cfg(not(feature = "small-tables"))]
+            None if simd::active() == Kernel::Scalar => self.split.encode(message),
+            None => encode(message, self.nsym, &self.generator),
+        };
+        if self.order == SymbolOrder::LowestFirst {
+            codeword.reverse();
+        }
+        codeword
+    }
+
+    /// encode as a Codeword, which keeps nsym and the code options with the bytes
+    /// A Codeword is always HighestFirst, so a LowestFirst codec gets InvalidParameters.
+    pub fn encode_cw(&self, message: &[u8]) -> Result<Codeword, RsError> {
+        if self.order != SymbolOrder::HighestFirst {
+            return Err(RsError::InvalidParameters("a Codeword is always HighestFirst"));
+        }
+        Ok(Codeword::encoded(self.encode(message), self.nsym, self.options()))
+    }
+
+    pub fn decode(&self, codeword: &[u8]) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+        self.ordered(codeword, &[], |codeword, _| self.decode_errata_highest_first(codeword, &[]))
+    }
+
+    /// decode without copying clean blocks: all-zero syndromes return the message slice of the
+    /// codeword itself, with no heap allocation for the default code; anything else goes through
+    /// decode (same errors, same max_correct policy). With fixed_work every block goes through
+    /// decode, so a clean block is reported as Corrected with no positions.
+    pub fn decode_ref<'a>(&self, codeword: &'a [u8]) -> Result<DecodeOutcome<'a>, RsError> {
+        let n = codeword.len();
+        // Lengths decode would reject take the slow path so the errors stay identical, a
+        // LowestFirst message is not a slice of the codeword, and skipping the decoder for clean
+        // blocks would make fixed_work's timing depend on the data again
+        if n > self.nsym && n <= 255 && self.order == SymbolOrder::HighestFirst && !self.decode_options.fixed_work {
+            let clean = match &self.custom {
+                Some(custom) => syndromes_zero(&custom.syndromes(codeword)),
+                None => {
+                    let mut syndromes = [0u8; 255];
+                    simd::syndromes(codeword, &mut syndromes[..self.nsym]);
+                    syndromes_zero(&syndromes[..self.nsym])
+                }
+            };
+            if clean {
+                return Ok(DecodeOutcome::Clean(&codeword[..n - self.nsym]));
+            }
+        }
+        let (message, positions) = self.decode(codeword)?;
+        Ok(DecodeOutcome::Corrected { message, positions })
+    }
+
+    /// Errors-and-erasures decode, see `rs::decode_errata`
+    pub fn decode_errata(&self, codeword: &[u8], erasures: &[usize]) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+        self.ordered(codeword, erasures, |codeword, erasures| self.decode_errata_highest_first(codeword, erasures))
+    }
+
+    /// decode_errata for a codeword missing up to nsym symbols at the end of the slice (the
+    /// end of transmission in either symbol order), see `rs::decode_truncated`
+    pub fn decode_truncated(&self, received: &[u8]) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+        let (codeword, erasures) = pad_truncated(received, self.nsym, self.n())?;
+        self.decode_errata(&codeword, &erasures)
+    }
+
+    fn decode_errata_highest_first(&self, codeword: &[u8], erasures: &[usize]) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+        let result = match &self.custom {
+            Some(custom) => custom.decode_errata_bm(codeword, erasures, self.decode_options.bm),
+            None => decode_errata_with(codeword, self.nsym, erasures, self.decode_options),
+        };
+        self.limit(result, erasures, codeword.len())
+    }
+
+    /// Apply DecodeOptions::max_correct and pinned to the decode of an n-symbol codeword:
+    /// corrected positions that were not erasures are errors the decoder located. Positions and
+    /// erasures here are HighestFirst, pinned positions in the codec's order.
+    fn limit<T>(&self, result: Result<(T, Vec<usize>), RsError>, erasures: &[usize], n: usize) -> Result<(T, Vec<usize>), RsError> {
+        let DecodeOptions { max_correct, pinned, .. } = self.decode_options;
+        // A shorter codeword than nsize can still leave pinned positions outside it
+        if pinned.max().is_some_and(|p| p >= n) {
+            return Err(RsError::InvalidParameters("pinned positions must be inside the codeword"));
+        }
+        let Ok((_, positions)) = &result else {
+            return result;
+        };
+        if let Some(limit) = max_correct {
+            let found = positions.iter().filter(|p| !erasures.contains(p)).count();
+            if found > limit {
+                return Err(RsError::CorrectionLimit { found, limit });
+            }
+        }
+        if !pinned.is_empty() {
+            let own_order: Vec<usize> = match self.order {
+                SymbolOrder::HighestFirst => positions.clone(),
+                SymbolOrder::LowestFirst => positions.iter().map(|&p| n - 1 - p).collect(),
+            };
+            check_pinned(&own_order, &pinned)?;
+        }
+        result
+    }
+
+    /// Encode a message of at most k bytes, zero-filling data positions len..k
+    pub fn encode_padded(&self, message: &[u8]) -> Vec<u8> {
+        assert!(message.len() <= self.k(), "message must be at most k={} bytes", self.k());
+        let mut padded = message.to_vec();
+        padded.resize(self.k(), 0);
+        self.encode(&padded)
+    }
+
+    /// encode_padded into an n-byte buffer the caller owns; the default code writes the codeword
+    /// straight into it with no intermediate allocation
+    pub fn encode_padded_into(&self, message: &[u8], codeword: &mut [u8]) {
+        assert!(message.len() <= self.k(), "message must be at most k={} bytes", self.k());
+        assert_eq!(codeword.len(), self.n(), "codeword buffer must be n={} bytes", self.n());
+        if self.custom.is_some() || self.order == SymbolOrder::LowestFirst {
+            codeword.copy_from_slice(&self.encode_padded(message));
+            return;
+        }
+        let (data, parity) = codeword.split_at_mut(self.k());
+        data[..message.len()].copy_from_slice(message);
+        data[message.len()..].fill(0);
+        #[cfg(not(feature = "small-tables"))]
+        if simd::active() == Kernel::Scalar {
+            self.split.remainder(data, parity);
+            return;
+        }
+        parity_into(data, &self.generator, parity);
+    }
+
+    /// decode writing the message into `message` (codeword.len() - nsym bytes) instead of a new
+    /// Vec, and returning the corrected positions. Clean codewords are a syndrome check and a copy;
+    /// otherwise the default code corrects a stack copy of the codeword, so only the positions are
+    /// allocated. On error `message` is left unspecified.
+    pub fn decode_to(&self, codeword: &[u8], message: &mut [u8]) -> Result<Vec<usize>, RsError> {
+        let n = codeword.len();
+        assert_eq!(message.len(), n.saturating_sub(self.nsym), "message buffer must be codeword.len() - nsym bytes");
+        // Lengths decode would reject take its path so the errors stay identical
+        // correct_in_place runs the standard Berlekamp-Massey only, with the usual early exits
+        let options = self.decode_options;
+        if self.custom.is_some() || self.order == SymbolOrder::LowestFirst || options.bm != BmVariant::Standard || options.fixed_work || n <= self.nsym || n > 255 {
+            let (decoded, positions) = self.decode(codeword)?;
+            message.copy_from_slice(&decoded);
+            return Ok(positions);
+        }
+        let mut syndromes = [0u8; 255];
+        let syndromes = &mut syndromes[..self.nsym];
+        simd::syndromes(codeword, syndromes);
+        if syndromes_zero(syndromes) {
+            message.copy_from_slice(&codeword[..n - self.nsym]);
+            return Ok(vec![]);
+        }
+        let mut received = [0u8; 255];
+        let received = &mut received[..n];
+        received.copy_from_slice(codeword);
+        let mut positions = [0usize; 127];
+        let count = correct_in_place(received, syndromes, &mut positions)?;
+        let ((), positions) = self.limit(Ok(((), positions[..count].to_vec())), &[], n)?;
+        message.copy_from_slice(&received[..n - self.nsym]);
+        Ok(positions)
+    }
+
+    /// Decode an encode_padded codeword whose message was `msg_len` bytes
+    /// Data positions msg_len..k are known zeros, as in a shortened code: they are reset before
+    /// decoding, so damage there is repaired (and reported) without spending correction
+    /// capacity, and erasures there are dropped. Returns the msg_len-byte message and every
+    /// corrected position, ascending. (For a LowestFirst codec the padding is the mirrored range.)
+    pub fn decode_padded(&self, codeword: &[u8], erasures: &[usize], msg_len: usize) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+        let k = self.k();
+        if msg_len > k {
+            return Err(RsError::InvalidParameters("msg_len must be at most k"));
+        }
+        if codeword.len() != self.n() {
+            return Err(RsError::CodewordTooShort);
+        }
+        self.ordered(codeword, erasures, |codeword, erasures| {
+            let mut received = codeword.to_vec();
+            let mut positions: Vec<usize> = (msg_len..k).filter(|&p| received[p] != 0).collect();
+            received[msg_len..k].fill(0);
+            let erasures: Vec<usize> = erasures.iter().copied().filter(|p| !(msg_len..k).contains(p)).collect();
+            let (mut message, fixed) = self.decode_errata_highest_first(&received, &erasures)?;
+            positions.extend(fixed);
+            positions.sort_unstable();
+            message.truncate(msg_len);
+            Ok((message, positions))
+        })
+    }
+
+    /// Syndromes at the code's nsym roots
+    pub fn syndromes(&self, codeword: &[u8]) -> Vec<u8> {
+        let codeword = self.highest_first(codeword);
+        match &self.custom {
+            Some(custom) => custom.syndromes(&codeword),
+            None => calc_syndromes(&codeword, self.nsym),
+        }
+    }
+
+    /// Degree of the Berlekamp-Massey locator for the received word (errors the decoder "sees")
+    pub fn locator_degree(&self, codeword: &[u8]) -> usize {
+        let syndromes: Vec<u16> = self.syndromes(codeword).iter().map(|&s| s as u16).collect();
+        let field = crate::gf2m::Gf2m::new(8, self.options().prim).expect("validated on construction");
+        berlekamp_massey_in(&field, &syndromes).len() - 1
+    }
+
+    /// True if the codeword has all-zero syndromes
+    pub fn check(&self, codeword: &[u8]) -> bool {
+        syndromes_zero(&self.syndromes(codeword))
+    }
+
+    /// (position, corrected byte) for every symbol the decoder fixes; the codeword is not modified
+    pub fn corrections(&self, codeword: &[u8]) -> Result<Vec<(usize, u8)>, RsError> {
+        let (message, positions) = self.decode(codeword)?;
+        if positions.is_empty() {
+            return Ok(vec![]);
+        }
+        // Systematic code: re-encoding the message gives the corrected codeword
+        let fixed = self.encode(&message);
+        Ok(positions.into_iter().map(|p| (p, fixed[p])).collect())
+    }
+
+    /// corrections() over a buffer of concatenated n-byte codewords, with positions as offsets
+    /// into the buffer; the error names the first block that failed. A partial tail is ignored.
+    pub fn corrections_many(&self, buffer: &[u8]) -> Result<Vec<(usize, u8)>, (usize, RsError)> {
+        let n = self.n();
+        let block = |(i, block): (usize, &[u8])| {
+            let fixes = self.corrections(block).map_err(|e| (i, e))?;
+            Ok(fixes.into_iter().map(|(p, b)| (i * n + p, b)).collect::<Vec<_>>())
+        };
+
+        #[cfg(feature = "parallel")]
+        let results: Vec<_> = buffer.par_chunks_exact(n).enumerate().map(block).collect();
+        #[cfg(not(feature = "parallel"))]
+        let results: Vec<_> = buffer.chunks_exact(n).enumerate().map(block).collect();
+        results.into_iter().collect::<Result<Vec<_>, _>>().map(|fixes| fixes.concat())
+    }
+
+    /// Indices of the dirty blocks in a buffer of concatenated `block_len`-byte codewords
+    /// Any block length works (shortened codes share the syndromes); a partial tail is ignored.
+    pub fn check_many(&self, buffer: &[u8], block_len: usize) -> Vec<usize> {
+        let dirty = |(i, block): (usize, &[u8])| (!self.check(block)).then_some(i);
+
+        #[cfg(feature = "parallel")]
+        let indices = buffer.par_chunks_exact(block_len).enumerate().filter_map(dirty).collect();
+        #[cfg(not(feature = "parallel"))]
+        let indices = buffer.chunks_exact(block_len).enumerate().filter_map(dirty).collect();
+        indices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_round_trip() {
+        let codec = RsCodec::new(8, 40).unwrap();
+        assert_eq!((codec.n(), codec.k(), codec.nsym(), codec.max_errors()), (40, 32, 8, 4));
+        let message: Vec<u8> = (0..32).collect();
+        let mut codeword = codec.encode(&message);
+        assert!(codec.check(&codeword));
+        codeword[0] ^= 1;
+        assert!(!codec.check(&codeword));
+        assert_eq!(codec.decode(&codeword).unwrap(), (message, vec![0]));
+        assert!(matches!(RsCodec::new(0, 40), Err(RsError::InvalidParameters(_))));
+
+        let mut buffer: Vec<u8> = (0..10).flat_map(|i| codec.encode(&[i as u8; 32])).collect();
+        buffer[3 * 40 + 7] ^= 1;
+        buffer[9 * 40] ^= 1;
+        assert_eq!(codec.check_many(&buffer, 40), vec![3, 9]);
+
+        let corrections = codec.corrections_many(&buffer).unwrap();
+        assert_eq!(corrections, vec![(3 * 40 + 7, buffer[3 * 40 + 7] ^ 1), (9 * 40, buffer[9 * 40] ^ 1)]);
+        buffer[5 * 40..5 * 40 + 5].iter_mut().for_each(|b| *b ^= 0xff);
+        assert_eq!(codec.corrections_many(&buffer), Err((5, RsError::TooManyErrors)));
+    }
+
+    #[test]
+    fn test_padded_region_costs_no_capacity() {
+        let codec = RsCodec::new(8, 64).unwrap();
+        let message = b"0123456789";
+        let mut codeword = codec.encode_padded(message);
+        assert_eq!(&codeword[10..56], &[0u8; 46][..]);
+        // 4 errors (= t) in the real data and parity, plus 6 in the padding
+        for p in [1, 9, 57, 63, 10, 20, 30, 40, 50, 55] {
+            codeword[p] ^= 0x5a;
+        }
+        assert!(codec.decode(&codeword).is_err());
+        let (decoded, positions) = codec.decode_padded(&codeword, &[], 10).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(positions, vec![1, 9, 10, 20, 30, 40, 50, 55, 57, 63]);
+        assert_eq!(codec.decode_padded(&codeword, &[20], 10).unwrap().1.len(), 10);
+        assert_eq!(codec.decode_padded(&codeword, &[], 57), Err(RsError::InvalidParameters("msg_len must be at most k")));
+        assert_eq!(codec.decode_padded(&codec.encode_padded(&[]), &[], 0).unwrap(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_max_correct() {
+        let codec = RsCodec::new(8, 40).unwrap();
+        let capped = codec.clone().with_decode_options(DecodeOptions { max_correct: Some(2), ..DecodeOptions::default() }).unwrap();
+        let full = codec.clone().with_decode_options(DecodeOptions { max_correct: Some(4), ..DecodeOptions::default() }).unwrap();
+        let message: Vec<u8> = (0..32).collect();
+        let mut codeword = codec.encode(&message);
+        for p in [3, 17, 38] {
+            codeword[p] ^= 0x81;
+        }
+        assert_eq!(codec.decode(&codeword).unwrap(), (message.clone(), vec![3, 17, 38]));
+        assert_eq!(capped.decode(&codeword), Err(RsError::CorrectionLimit { found: 3, limit: 2 }));
+        // Erasures are not counted against the cap, and the cap at t changes nothing
+        assert_eq!(capped.decode_errata(&codeword, &[17]).unwrap().1, vec![3, 17, 38]);
+        for errors in 0..=5 {
+            let mut received = codec.encode(&message);
+            received[..errors].iter_mut().for_each(|b| *b ^= 0x33);
+            assert_eq!(full.decode(&received), codec.decode(&received));
+        }
+        assert!(matches!(codec.with_decode_options(DecodeOptions { max_correct: Some(5), ..DecodeOptions::default() }), Err(RsError::InvalidParameters(_))));
+    }
+
+    #[test]
+    fn test_decode_ref_outcomes() {
+        let message: Vec<u8> = (0..32).collect();
+        let custom = RsCodec::with_options(8, 40, CodeOptions { fcr: 1, ..CodeOptions::default() }).unwrap();
+        for codec in [RsCodec::new(8, 40).unwrap(), custom] {
+            let mut codeword = codec.encode(&message);
+            let outcome = codec.decode_ref(&codeword).unwrap();
+            assert!(outcome.is_clean());
+            assert_eq!((outcome.message(), outcome.positions()), (&message[..], &[][..]));
+            // Borrowed, not copied
+            assert!(std::ptr::eq(outcome.message().as_ptr(), codeword.as_ptr()));
+
+            codeword[5] ^= 0x40;
+            codeword[39] ^= 1;
+            let outcome = codec.decode_ref(&codeword).unwrap();
+            assert_eq!(outcome, DecodeOutcome::Corrected { message: message.clone(), positions: vec![5, 39] });
+            assert_eq!(outcome.into_owned(), codec.decode(&codeword).unwrap());
+
+            codeword[..5].iter_mut().for_each(|b| *b ^= 0xff);
+            assert_eq!(codec.decode_ref(&codeword).unwrap_err(), codec.decode(&codeword).unwrap_err());
+            assert_eq!(codec.decode_ref(&codeword[..8]).unwrap_err(), codec.decode(&codeword[..8]).unwrap_err());
+        }
+    }
+
+    #[test]
+    fn test_into_buffers_match_owned() {
+        let mut rng = crate::rs::sim::Rng::new(381);
+        let custom = RsCodec::with_options(8, 40, CodeOptions { fcr: 1, ..CodeOptions::default() }).unwrap();
+        let capped = RsCodec::new(8, 40).unwrap().with_decode_options(DecodeOptions { max_correct: Some(2), ..DecodeOptions::default() }).unwrap();
+        for codec in [RsCodec::new(8, 40).unwrap(), RsCodec::new(32, 255).unwrap(), custom, capped] {
+            let (n, k) = (codec.n(), codec.k());
+            for trial in 0..200 {
+                let message: Vec<u8> = (0..rng.below(k + 1)).map(|_| rng.next_u64() as u8).collect();
+                let mut codeword = vec![0xee; n];
+                codec.encode_padded_into(&message, &mut codeword);
+                assert_eq!(codeword, codec.encode_padded(&message));
+
+                for pos in rng.distinct(trial % (codec.nsym() / 2 + 2), n) {
+                    codeword[pos] ^= rng.nonzero_byte();
+                }
+                let mut decoded = vec![0u8; k];
+                match (codec.decode_to(&codeword, &mut decoded), codec.decode(&codeword)) {
+                    (Ok(positions), Ok(expected)) => assert_eq!((decoded, positions), expected),
+                    (result, expected) => assert_eq!(result.err(), expected.err()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_truncated() {
+        let custom = CodeOptions { fcr: 1, generator: 2, prim: 0x187 };
+        for high in [RsCodec::new(8, 40).unwrap(), RsCodec::with_options(8, 40, custom).unwrap()] {
+            let message: Vec<u8> = (1..=32).collect();
+            let codeword = high.encode(&message);
+            for cut in [1, 8] {
+                assert_eq!(high.decode_truncated(&codeword[..40 - cut]).unwrap().0, message);
+            }
+            assert_eq!(high.decode_truncated(&codeword[..31]), Err(RsError::Truncated { missing: 9, nsym: 8 }));
+            // LowestFirst loses the end of its own slice, the head of the message
+            let low = high.clone().with_symbol_order(SymbolOrder::LowestFirst);
+            let reversed = low.encode(&message);
+            let (decoded, positions) = low.decode_truncated(&reversed[..36]).unwrap();
+            assert_eq!(decoded, message);
+            assert!(positions.iter().all(|&p| p >= 36));
+        }
+    }
+
+    #[test]
+    fn test_lowest_first_mirrors_highest_first() {
+        let mut rng = crate::rs::sim::Rng::new(408);
+        let custom = CodeOptions { fcr: 1, generator: 2, prim: 0x187 };
+        for high in [RsCodec::new(8, 40).unwrap(), RsCodec::with_options(8, 40, custom).unwrap()] {
+            let low = high.clone().with_symbol_order(SymbolOrder::LowestFirst);
+            let mirror = |positions: &[usize]| -> Vec<usize> { positions.iter().rev().map(|&p| 39 - p).collect() };
+            for errors in 0..=4 {
+                let message: Vec<u8> = (0..32).map(|_| rng.next_u64() as u8).collect();
+                let mut codeword = high.encode(&message);
+                let mut reversed: Vec<u8> = codeword.iter().rev().copied().collect();
+                assert_eq!(low.encode(&message), reversed);
+                assert!(low.check(&reversed));
+                for pos in rng.distinct(errors, 40) {
+                    codeword[pos] ^= rng.nonzero_byte();
+                }
+                reversed = codeword.iter().rev().copied().collect();
+                assert_eq!(low.syndromes(&reversed), high.syndromes(&codeword));
+
+                let (decoded, positions) = high.decode(&codeword).unwrap();
+                assert_eq!(decoded, message);
+                assert_eq!(low.decode(&reversed).unwrap(), (message.clone(), mirror(&positions)));
+                let outcome = low.decode_ref(&reversed).unwrap();
+                assert_eq!((outcome.message(), outcome.positions(), outcome.is_clean()), (&message[..], &mirror(&positions)[..], errors == 0));
+                let mut buffer = vec![0u8; 32];
+                assert_eq!(low.decode_to(&reversed, &mut buffer).unwrap(), mirror(&positions));
+                assert_eq!(buffer, message);
+                assert_eq!(low.corrections(&reversed).unwrap().len(), errors);
+
+                // Erasures are given, and reported, in the codec's own order
+                let erased = mirror(&positions[..errors / 2]);
+                let (decoded, got) = low.decode_errata(&reversed, &erased).unwrap();
+                assert_eq!((decoded, got), (message, mirror(&positions)));
+            }
+            // Padding sits at the front of the data, which for LowestFirst is just past the parity
+            let mut reversed = low.encode_padded(b"abc");
+            assert_eq!(&reversed[8..37], &[0u8; 29][..]);
+            reversed[20] ^= 0x11;
+            assert_eq!(low.decode_padded(&reversed, &[], 3).unwrap(), (b"abc".to_vec(), vec![20]));
+            let mut into = vec![0u8; 40];
+            low.encode_padded_into(b"abc", &mut into);
+            assert_eq!(into, low.encode_padded(b"abc"));
+        }
+    }
+
+    #[test]
+    fn test_inversionless_option() {
+        // Errors and erasures through both code paths give what the standard variant gives
+        let mut rng = crate::rs::sim::Rng::new(4101);
+        let inversionless = DecodeOptions { bm: BmVariant::Inversionless, ..DecodeOptions::default() };
+        let custom = CodeOptions { fcr: 1, generator: 2, prim: 0x187 };
+        for standard in [RsCodec::new(10, 60).unwrap(), RsCodec::with_options(10, 60, custom).unwrap()] {
+            let codec = standard.clone().with_decode_options(inversionless).unwrap();
+            for _ in 0..300 {
+                let mut received = standard.encode(&(0..50).map(|_| rng.next_u64() as u8).collect::<Vec<_>>());
+                let hit = rng.below(12);
+                let positions = rng.distinct(hit, 60);
+                for &pos in &positions {
+                    received[pos] ^= rng.nonzero_byte();
+                }
+                let flagged = rng.below(hit + 1);
+                let erasures = &positions[..flagged];
+                assert_eq!(codec.decode_errata(&received, erasures), standard.decode_errata(&received, erasures));
+                assert_eq!(codec.decode(&received), standard.decode(&received));
+                let mut message = vec![0u8; 50];
+                assert_eq!(codec.decode_to(&received, &mut message).ok(), standard.decode(&received).ok().map(|(_, p)| p));
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_work_option() {
+        let mut rng = crate::rs::sim::Rng::new(415);
+        let fixed = DecodeOptions { fixed_work: true, ..DecodeOptions::default() };
+        let standard = RsCodec::new(10, 60).unwrap();
+        for codec in [standard.clone().with_decode_options(fixed).unwrap(), standard.clone().with_symbol_order(SymbolOrder::LowestFirst).with_decode_options(fixed).unwrap()] {
+            let reference = standard.clone().with_symbol_order(codec.symbol_order());
+            for _ in 0..200 {
+                let mut received = codec.encode(&(0..50).map(|_| rng.next_u64() as u8).collect::<Vec<_>>());
+                let errors = rng.below(8);
+                for pos in rng.distinct(errors, 60) {
+                    received[pos] ^= rng.nonzero_byte();
+                }
+                assert_eq!(codec.decode(&received), reference.decode(&received));
+                let mut message = vec![0u8; 50];
+                assert_eq!(codec.decode_to(&received, &mut message).ok(), reference.decode(&received).ok().map(|(_, p)| p));
+                // decode_ref runs the full decoder even on clean blocks
+                let outcome = codec.decode_ref(&received);
+                assert_eq!(outcome.as_ref().map(|o| o.message().to_vec()).ok(), reference.decode(&received).ok().map(|(m, _)| m));
+                assert!(!matches!(outcome, Ok(DecodeOutcome::Clean(_))));
+            }
+        }
+        let clean = standard.encode(&[7; 50]);
+        let fixed_codec = standard.clone().with_decode_options(fixed).unwrap();
+        assert_eq!(fixed_codec.decode_ref(&clean), Ok(DecodeOutcome::Corrected { message: vec![7; 50], positions: vec![] }));
+        assert_eq!(standard.decode_ref(&clean), Ok(DecodeOutcome::Clean(&clean[..50])));
+        let custom = RsCodec::with_options(10, 60, CodeOptions { fcr: 1, ..CodeOptions::default() }).unwrap();
+        assert!(matches!(custom.with_decode_options(fixed), Err(RsError::InvalidParameters(_))));
+    }
+
+    #[test]
+    fn test_pinned_positions() {
+        let plain = RsCodec::new(4, 12).unwrap();
+        let message: Vec<u8> = (1..=8).collect();
+        let mut received = plain.encode(&message);
+        // Three errors against a t = 2 code: the decoder "corrects" 6 and 9 and lands on the wrong codeword
+        for (pos, e) in [(0, 5), (1, 70), (2, 0x55)] {
+            received[pos] ^= e;
+        }
+        let (wrong, positions) = plain.decode(&received).unwrap();
+        assert_ne!(wrong, message);
+        assert_eq!(positions, [6, 9]);
+
+        // Bytes 4..8 checked separately (say a header): the correction at 6 gives the miscorrection away
+        let options = DecodeOptions { pinned: PositionSet::new(&[4, 5, 6, 7]).unwrap(), ..DecodeOptions::default() };
+        let pinned = plain.clone().with_decode_options(options).unwrap();
+        let conflict = Err(RsError::PinnedPositionConflict { positions: PositionSet::new(&[6]).unwrap() });
+        assert_eq!(pinned.decode(&received), conflict);
+        assert_eq!(pinned.decode_to(&received, &mut [0u8; 8]), conflict.map(|(_, p)| p));
+        assert_eq!(pinned.decode_errata(&received, &[0]).map(|(_, p)| p), plain.decode_errata(&received, &[0]).map(|(_, p)| p));
+
+        // Corrections elsewhere, and clean codewords, decode as before
+        let mut fixable = plain.encode(&message);
+        fixable[0] ^= 1;
+        fixable[10] ^= 2;
+        assert_eq!(pinned.decode(&fixable).unwrap(), (message.clone(), vec![0, 10]));
+        assert_eq!(pinned.decode(&plain.encode(&message)).unwrap(), (message.clone(), vec![]));
+
+        // Pinned positions are in the codec's own order
+        let low = plain.clone().with_symbol_order(SymbolOrder::LowestFirst);
+        let reversed: Vec<u8> = received.iter().rev().copied().collect();
+        let mirrored = DecodeOptions { pinned: PositionSet::new(&[4, 5, 6, 7].map(|p| 11 - p)).unwrap(), ..DecodeOptions::default() };
+        assert_eq!(
+            low.with_decode_options(mirrored).unwrap().decode(&reversed),
+            Err(RsError::PinnedPositionConflict { positions: PositionSet::new(&[5]).unwrap() })
+        );
+
+        let outside = DecodeOptions { pinned: PositionSet::new(&[12]).unwrap(), ..DecodeOptions::default() };
+        assert!(matches!(plain.with_decode_options(outside), Err(RsError::InvalidParameters(_))));
+        assert!(matches!(PositionSet::new(&[256]), Err(RsError::InvalidParameters(_))));
+    }
+
+    #[test]
+    fn test_params_round_trip() {
+        let options = CodeOptions { fcr: 1, generator: 2, prim: 0x12d };
+        for codec in [
+            RsCodec::new(32, 255).unwrap(),
+            RsCodec::with_options(10, 60, options).unwrap().with_decode_options(DecodeOptions { max_correct: Some(3), bm: BmVariant::Inversionless, fixed_work: false, ..DecodeOptions::default() }).unwrap(),
+            RsCodec::new(4, 20).unwrap().with_symbol_order(SymbolOrder::LowestFirst),
+            RsCodec::new(16, 200).unwrap().with_decode_options(DecodeOptions { fixed_work: true, ..DecodeOptions::default() }).unwrap(),
+            RsCodec::new(8, 40).unwrap().with_decode_options(DecodeOptions { pinned: PositionSet::new(&[0, 39]).unwrap(), ..DecodeOptions::default() }).unwrap(),
+        ] {
+            assert_eq!(RsCodec::from_params(&codec.params()).unwrap(), codec);
+            #[cfg(feature = "serde")]
+            {
+                let json = serde_json::to_string(&codec.params()).unwrap();
+                let params: CodecParams = serde_json::from_str(&json).unwrap();
+                assert_eq!(RsCodec::from_params(&params).unwrap(), codec, "{}", json);
+            }
+        }
+        let over = CodecParams { decode_options: DecodeOptions { max_correct: Some(5), ..DecodeOptions::default() }, ..RsCodec::new(8, 40).unwrap().params() };
+        assert!(matches!(RsCodec::from_params(&over), Err(RsError::InvalidParameters(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_params_json_defaults() {
+        let codec = RsCodec::new(32, 255).unwrap();
+        assert_eq!(
+            serde_json::to_string(&codec.params()).unwrap(),
+            r#"{"nsize":255,"nsym":32,"options":{"fcr":0,"generator":2,"prim":285},"decode_options":{"max_correct":null,"bm":"Standard","fixed_work":false},"symbol_order":"HighestFirst"}"#
+        );
+        // Saved before options and decode_options existed, or with only some fields set
+        let old: CodecParams = serde_json::from_str(r#"{"nsize":255,"nsym":32}"#).unwrap();
+        assert_eq!(RsCodec::from_params(&old).unwrap(), codec);
+        let partial: CodecParams = serde_json::from_str(r#"{"nsize":64,"nsym":8,"options":{"fcr":1},"decode_options":{}}"#).unwrap();
+        assert_eq!((partial.options, partial.decode_options), (CodeOptions { fcr: 1, ..CodeOptions::default() }, DecodeOptions::default()));
+        assert!(serde_json::from_str::<CodecParams>(r#"{"nsym":8}"#).is_err());
+    }
+}
@@ -0,0 +1,251 @@
+// Codeword: the bytes of one systematic RS codeword together with the code that made them
+// Carrying nsym (and the CodeOptions) with the bytes keeps the data/parity boundary at
+// n - nsym without every caller re-deriving it. The bytes are owned and never copied: data(),
+// parity() and as_bytes() are slices of them, correct() works in place, and into_vec() hands
+// the buffer back. The decoder policy (DecodeOptions::max_correct) is not part of the code and
+// is not carried: correct() always corrects up to nsym / 2.
+
+use crate::gf256::simd;
+use crate::rs::{decode_in_place, syndromes_zero, validate_params, CodeOptions, CustomRs, RsError};
+
+/// Owned [data | parity] bytes of an RS(n, n - nsym) codeword
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "Raw"))]
+pub struct Codeword {
+    bytes: Vec<u8>,
+    nsym: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    options: CodeOptions,
+}
+
+/// What correct() changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorrectionSummary {
+    /// Corrected positions, ascending
+    pub positions: Vec<usize>,
+    /// received ^ corrected at each position
+    pub magnitudes: Vec<u8>,
+    /// How many positions fell in the data and in the parity
+    pub data: usize,
+    pub parity: usize,
+}
+
+impl CorrectionSummary {
+    pub fn is_clean(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+impl Codeword {
+    /// Wrap received bytes of the default code; InvalidParameters unless 1 <= nsym < len <= 255
+    pub fn from_bytes(bytes: Vec<u8>, nsym: usize) -> Result<Self, RsError> {
+        Self::with_options(bytes, nsym, CodeOptions::default())
+    }
+
+    /// from_bytes for a code with non-default CodeOptions, validated as CustomRs::new does
+    pub fn with_options(bytes: Vec<u8>, nsym: usize, options: CodeOptions) -> Result<Self, RsError> {
+        validate_params(nsym, bytes.len())?;
+        if options != CodeOptions::default() {
+            CustomRs::new(nsym, bytes.len(), options)?;
+        }
+        Ok(Self { bytes, nsym, options })
+    }
+
+    /// RsCodec::encode_cw's constructor: bytes are a codeword of a codec already validated
+    pub(crate) fn encoded(bytes: Vec<u8>, nsym: usize, options: CodeOptions) -> Self {
+        Self { bytes, nsym, options }
+    }
+
+    pub fn n(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn nsym(&self) -> usize {
+        self.nsym
+    }
+
+    pub fn k(&self) -> usize {
+        self.bytes.len() - self.nsym
+    }
+
+    pub fn options(&self) -> CodeOptions {
+        self.options
+    }
+
+    /// The k data (message) bytes
+    pub fn data(&self) -> &[u8] {
+        &self.bytes[..self.k()]
+    }
+
+    /// The nsym parity bytes
+    pub fn parity(&self) -> &[u8] {
+        &self.bytes[self.k()..]
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Mutable bytes, for injecting errors; the length cannot change, so nsym stays valid
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    fn custom(&self) -> Option<CustomRs> {
+        (self.options != CodeOptions::default()).then(|| CustomRs::new(self.nsym, self.n(), self.options).expect("validated on construction"))
+    }
+
+    /// True if the syndromes are all zero
+    pub fn verify(&self) -> bool {
+        match self.custom() {
+            Some(custom) => syndromes_zero(&custom.syndromes(&self.bytes)),
+            None => {
+                let mut syndromes = [0u8; 255];
+                simd::syndromes(&self.bytes, &mut syndromes[..self.nsym]);
+                syndromes_zero(&syndromes[..self.nsym])
+            }
+        }
+    }
+
+    /// Correct up to nsym / 2 symbol errors in place; on error the bytes are left as they were
+    pub fn correct(&mut self) -> Result<CorrectionSummary, RsError> {
+        let mut received = [0u8; 255];
+        received[..self.n()].copy_from_slice(&self.bytes);
+        let positions = match self.custom() {
+            Some(custom) => {
+                let (message, positions) = custom.decode_errata(&self.bytes, &[])?;
+                if !positions.is_empty() {
+                    self.bytes = custom.encode(&message);
+                }
+                positions
+            }
+            None => {
+                let mut positions = [0usize; 127];
+                let count = decode_in_place(&mut self.bytes, self.nsym, &mut positions[..self.nsym / 2])?;
+                positions[..count].to_vec()
+            }
+        };
+        let k = self.k();
+        let data = positions.iter().filter(|&&p| p < k).count();
+        let magnitudes = positions.iter().map(|&p| received[p] ^ self.bytes[p]).collect();
+        Ok(CorrectionSummary { parity: positions.len() - data, data, magnitudes, positions })
+    }
+}
+
+impl AsRef<[u8]> for Codeword {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl From<Codeword> for Vec<u8> {
+    fn from(codeword: Codeword) -> Self {
+        codeword.bytes
+    }
+}
+
+/// Unvalidated serde form; deserializing goes through with_options
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct Raw {
+    bytes: Vec<u8>,
+    nsym: usize,
+    #[serde(default)]
+    options: CodeOptions,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Raw> for Codeword {
+    type Error = RsError;
+
+    fn try_from(raw: Raw) -> Result<Self, RsError> {
+        Self::with_options(raw.bytes, raw.nsym, raw.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use crate::rs::{RsCodec, SymbolOrder};
+
+    #[test]
+    fn test_accessors() {
+        let codec = RsCodec::new(6, 20).unwrap();
+        let message: Vec<u8> = (1..=14).collect();
+        let codeword = codec.encode_cw(&message).unwrap();
+        assert_eq!((codeword.n(), codeword.k(), codeword.nsym()), (20, 14, 6));
+        assert_eq!(codeword.data(), &message[..]);
+        assert_eq!(codeword.parity(), &codec.encode(&message)[14..]);
+        assert_eq!(codeword.as_bytes(), &codec.encode(&message)[..]);
+        assert!(codeword.verify());
+        let bytes: Vec<u8> = codeword.clone().into();
+        assert_eq!(Codeword::from_bytes(bytes, 6), Ok(codeword));
+    }
+
+    #[test]
+    fn test_correct_in_place() {
+        let mut rng = Rng::new(403);
+        let custom = CodeOptions { fcr: 1, generator: 2, prim: 0x187 };
+        for codec in [RsCodec::new(8, 40).unwrap(), RsCodec::with_options(8, 40, custom).unwrap()] {
+            for errors in 0..=4 {
+                let message: Vec<u8> = (0..32).map(|_| rng.next_u64() as u8).collect();
+                let sent = codec.encode_cw(&message).unwrap();
+                let mut codeword = sent.clone();
+                let mut positions = rng.distinct(errors, 40);
+                positions.sort_unstable();
+                let magnitudes: Vec<u8> = positions.iter().map(|_| rng.nonzero_byte()).collect();
+                for (&p, &e) in positions.iter().zip(&magnitudes) {
+                    codeword.as_bytes_mut()[p] ^= e;
+                }
+                assert_eq!(codeword.verify(), errors == 0);
+                let summary = codeword.correct().unwrap();
+                assert_eq!((&summary.positions, &summary.magnitudes), (&positions, &magnitudes), "{:?}", codec.options());
+                assert_eq!(summary.data, positions.iter().filter(|&&p| p < 32).count());
+                assert_eq!((summary.data + summary.parity, summary.is_clean()), (errors, errors == 0));
+                assert_eq!(codeword, sent);
+            }
+            // Past t the bytes are left alone
+            let mut codeword = codec.encode_cw(&[7; 32]).unwrap();
+            codeword.as_bytes_mut()[..8].iter_mut().for_each(|b| *b ^= 0xa5);
+            let before = codeword.clone();
+            assert!(codeword.correct().is_err());
+            assert_eq!(codeword, before);
+        }
+    }
+
+    #[test]
+    fn test_misuse() {
+        let codec = RsCodec::new(8, 40).unwrap();
+        let bytes = codec.encode(&[3; 32]);
+        assert!(matches!(Codeword::from_bytes(bytes.clone(), 0), Err(RsError::InvalidParameters(_))));
+        assert!(matches!(Codeword::from_bytes(bytes.clone(), 40), Err(RsError::InvalidParameters(_))));
+        assert!(matches!(Codeword::from_bytes(vec![0; 300], 8), Err(RsError::InvalidParameters(_))));
+        let bad = CodeOptions { prim: 0x100, ..CodeOptions::default() };
+        assert!(matches!(Codeword::with_options(bytes.clone(), 8, bad), Err(RsError::InvalidParameters(_))));
+        // Too many parity symbols claimed: the extra syndromes are not zero, and correcting
+        // refuses rather than rewriting a good codeword
+        let mut wrong = Codeword::from_bytes(bytes.clone(), 10).unwrap();
+        assert!(!wrong.verify());
+        assert!(wrong.correct().is_err());
+        assert_eq!(wrong.as_bytes(), &bytes[..]);
+        // A Codeword is HighestFirst, which a LowestFirst codec's bytes are not
+        let lowest = codec.with_symbol_order(SymbolOrder::LowestFirst);
+        assert!(matches!(lowest.encode_cw(&[3; 32]), Err(RsError::InvalidParameters(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json() {
+        let codeword = RsCodec::new(2, 6).unwrap().encode_cw(&[1, 2, 3, 4]).unwrap();
+        let json = serde_json::to_string(&codeword).unwrap();
+        assert!(json.starts_with(r#"{"bytes":[1,2,3,4,"#), "{}", json);
+        assert_eq!(serde_json::from_str::<Codeword>(&json).unwrap(), codeword);
+        assert!(serde_json::from_str::<Codeword>(r#"{"bytes":[1,2],"nsym":2}"#).is_err());
+        assert_eq!(serde_json::from_str::<Codeword>(r#"{"bytes":[0,0,0],"nsym":2}"#).unwrap().options(), CodeOptions::default());
+    }
+}
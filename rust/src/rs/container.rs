@@ -0,0 +1,600 @@
+// Self-describing container for RS-protected files, ECRS version 3
+// Layout: header block | codeword groups | the same header block again as a trailer.
+// The header block is an RS(32, 20) codeword over a 20-byte message: magic "ECRS", version,
+// nsym, nsize, interleave depth, original length (u64 little-endian) and the CRC-32 of those 16
+// bytes (little-endian). The code is systematic, so an intact file still opens with the readable
+// magic and fields. Up to 6 damaged bytes per copy are corrected, the CRC rejects a
+// miscorrection, and when the leading copy is beyond repair (a lost first sector) the trailer is
+// used. Groups are file.rs's: d codewords per d * k data bytes, striped so byte p of codeword i
+// sits at p * d + i, the last group zero-padded and the padding dropped on read.
+// Reader also opens the bare 16-byte-header files of versions 1 and 2, which have no trailer.
+
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::crc::crc32;
+use crate::rs::file::{self, read_full, stripe, unstripe, DecodeFileReport, FileError, FileHeader, RepairReport, VerifyFileReport, HEADER_LEN, MAGIC};
+use crate::rs::{RsCodec, RsError};
+
+pub const VERSION: u8 = 3;
+/// The 16 bytes of header fields and their CRC-32
+const HEADER_MESSAGE_LEN: usize = 20;
+/// Parity of the header code: up to HEADER_NSYM / 2 damaged bytes per copy are corrected
+pub const HEADER_NSYM: usize = 12;
+/// Length of each header copy, and so the offset of the first group
+pub const HEADER_BLOCK_LEN: usize = HEADER_MESSAGE_LEN + HEADER_NSYM;
+
+fn header_codec() -> RsCodec {
+    RsCodec::new(HEADER_NSYM, HEADER_BLOCK_LEN).expect("RS(32, 20) is a valid code")
+}
+
+/// The codec for (nsym, nsize), after checking the interleave depth too
+pub(crate) fn check_params(nsym: usize, nsize: usize, depth: usize) -> Result<RsCodec, FileError> {
+    let codec = RsCodec::new(nsym, nsize).map_err(FileError::Params)?;
+    if !(1..=255).contains(&depth) {
+        return Err(FileError::Params(RsError::InvalidParameters("interleave depth must be in 1..=255")));
+    }
+    Ok(codec)
+}
+
+/// One RS-protected copy of the header, as written at both ends of a container
+pub fn header_block(header: &FileHeader) -> [u8; HEADER_BLOCK_LEN] {
+    let mut message = [0u8; HEADER_MESSAGE_LEN];
+    message[..4].copy_from_slice(&MAGIC);
+    message[4] = VERSION;
+    message[5] = header.nsym as u8;
+    message[6] = header.nsize as u8;
+    message[7] = header.depth as u8;
+    message[8..16].copy_from_slice(&header.original_len.to_le_bytes());
+    let crc = crc32(&message[..16]);
+    message[16..].copy_from_slice(&crc.to_le_bytes());
+    let mut block = [0u8; HEADER_BLOCK_LEN];
+    block.copy_from_slice(&header_codec().encode(&message));
+    block
+}
+
+/// Decode one header copy; returns the header and the number of bytes corrected in it
+pub fn parse_header_block(block: &[u8; HEADER_BLOCK_LEN]) -> Result<(FileHeader, usize), FileError> {
+    let (message, positions) = header_codec().decode(block).map_err(|_| FileError::BadHeader("container header is beyond repair"))?;
+    if crc32(&message[..16]).to_le_bytes() != message[16..] {
+        return Err(FileError::BadHeader("container header fails its checksum"));
+    }
+    if message[..4] != MAGIC || message[4] != VERSION {
+        return Err(FileError::BadHeader("not an ECRS container header"));
+    }
+    let mut len = [0u8; 8];
+    len.copy_from_slice(&message[8..16]);
+    let header = FileHeader { nsym: message[5] as usize, nsize: message[6] as usize, depth: message[7] as usize, original_len: u64::from_le_bytes(len) };
+    check_params(header.nsym, header.nsize, header.depth)?;
+    Ok((header, positions.len()))
+}
+
+/// Condition of one header copy when the file was opened
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CopyState {
+    #[default]
+    Clean,
+    /// Readable after correcting this many bytes
+    Corrected(usize),
+    /// Beyond repair, failing its checksum, or disagreeing with the leading copy
+    Damaged,
+    /// Cut off: the file ends before the trailer
+    Missing,
+}
+
+impl fmt::Display for CopyState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CopyState::Clean => f.write_str("clean"),
+            CopyState::Corrected(count) => write!(f, "corrected ({} bytes)", count),
+            CopyState::Damaged => f.write_str("damaged"),
+            CopyState::Missing => f.write_str("missing"),
+        }
+    }
+}
+
+/// Condition of both header copies; `trailer` is None for version 1 and 2 files, which have none
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct HeaderState {
+    pub leading: CopyState,
+    pub trailer: Option<CopyState>,
+}
+
+impl HeaderState {
+    /// True when every copy the format has is present and undamaged
+    pub fn is_intact(&self) -> bool {
+        self.leading == CopyState::Clean && self.trailer.is_none_or(|t| t == CopyState::Clean)
+    }
+}
+
+fn copy_state(corrected: usize) -> CopyState {
+    if corrected == 0 {
+        CopyState::Clean
+    } else {
+        CopyState::Corrected(corrected)
+    }
+}
+
+/// Streams data into a container: the leading header on creation, a group whenever d * k bytes
+/// have arrived, and the padded last group and trailer on finish
+pub struct Writer<W: Write> {
+    inner: W,
+    header: FileHeader,
+    codec: RsCodec,
+    /// Data of the group being filled, d * k bytes
+    chunk: Vec<u8>,
+    filled: usize,
+    group: Vec<u8>,
+    written: u64,
+    blocks: u64,
+}
+
+impl<W: Write> Writer<W> {
+    /// Write the leading header for `original_len` bytes of data, all of which must be written
+    /// before finish; depth is 1..=255, 1 for consecutive codewords
+    pub fn new(mut inner: W, nsym: usize, nsize: usize, depth: usize, original_len: u64) -> Result<Self, FileError> {
+        let codec = check_params(nsym, nsize, depth)?;
+        let header = FileHeader { nsym, nsize, depth, original_len };
+        inner.write_all(&header_block(&header))?;
+        let (chunk, group) = (vec![0u8; codec.k() * depth], vec![0u8; codec.n() * depth]);
+        Ok(Self { inner, header, codec, chunk, filled: 0, group, written: 0, blocks: 0 })
+    }
+
+    pub fn header(&self) -> FileHeader {
+        self.header
+    }
+
+    /// Codewords written so far; the last partial group is only written by finish
+    pub fn blocks_written(&self) -> u64 {
+        self.blocks
+    }
+
+    fn write_group(&mut self) -> io::Result<()> {
+        let (k, depth) = (self.codec.k(), self.header.depth);
+        self.chunk[self.filled..].fill(0);
+        for (i, message) in self.chunk.chunks_exact(k).enumerate() {
+            stripe(&self.codec.encode(message), &mut self.group, i, depth);
+        }
+        self.inner.write_all(&self.group)?;
+        self.blocks += depth as u64;
+        self.filled = 0;
+        Ok(())
+    }
+
+    /// Write the padded last group and the trailer, then flush; returns the inner writer
+    pub fn finish(mut self) -> Result<W, FileError> {
+        if self.written != self.header.original_len {
+            return Err(FileError::LengthMismatch { declared: self.header.original_len, written: self.written });
+        }
+        if self.filled > 0 {
+            self.write_group()?;
+        }
+        self.inner.write_all(&header_block(&self.header))?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for Writer<W> {
+    /// Fails with InvalidInput once the declared original length is reached
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let room = self.header.original_len - self.written;
+        if room == 0 && !buf.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "data beyond the container's declared length"));
+        }
+        let take = buf.len().min(self.chunk.len() - self.filled).min(usize::try_from(room).unwrap_or(usize::MAX));
+        self.chunk[self.filled..self.filled + take].copy_from_slice(&buf[..take]);
+        self.filled += take;
+        self.written += take as u64;
+        if self.filled == self.chunk.len() {
+            self.write_group()?;
+        }
+        Ok(take)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Opens a container, or a version 1 or 2 file, and decodes it a group at a time
+/// Read yields the original data with every correctable block corrected; blocks that are not are
+/// passed through as stored and listed in report(). A file cut short fails the read that reaches
+/// the cut with UnexpectedEof, or next_group with FileError::Truncated.
+pub struct Reader<R> {
+    inner: R,
+    header: FileHeader,
+    state: HeaderState,
+    codec: RsCodec,
+    data_start: u64,
+    group: Vec<u8>,
+    block: Vec<u8>,
+    /// Original bytes of the current group, and how many Read has returned
+    data: Vec<u8>,
+    consumed: usize,
+    next: u64,
+    report: DecodeFileReport,
+}
+
+impl<R: Read + Seek> Reader<R> {
+    /// Read the header from the leading copy, else from the trailer
+    /// Fails with BadHeader when neither copy is readable, including a file cut short whose
+    /// leading copy is damaged.
+    pub fn new(mut inner: R) -> Result<Self, FileError> {
+        let file_len = inner.seek(SeekFrom::End(0))?;
+        inner.seek(SeekFrom::Start(0))?;
+        let mut raw = [0u8; HEADER_BLOCK_LEN];
+        let got = read_full(&mut inner, &mut raw)?;
+        let leading = if got == HEADER_BLOCK_LEN { parse_header_block(&raw).ok() } else { None };
+        let magic = got >= MAGIC.len() && raw[..4] == MAGIC;
+
+        let (header, state, data_start) = if leading.is_none() && got >= HEADER_LEN && magic && matches!(raw[4], file::VERSION | file::VERSION_INTERLEAVED) {
+            let mut bare = [0u8; HEADER_LEN];
+            bare.copy_from_slice(&raw[..HEADER_LEN]);
+            let header = FileHeader::parse(&bare)?;
+            check_params(header.nsym, header.nsize, header.depth)?;
+            (header, HeaderState { leading: CopyState::Clean, trailer: None }, HEADER_LEN as u64)
+        } else {
+            let data_start = HEADER_BLOCK_LEN as u64;
+            // Where the trailer belongs by the leading copy; without one, the last 32 bytes
+            let trailer_at = match &leading {
+                Some((header, _)) => Some(data_start + header.blocks() * header.nsize as u64).filter(|&at| at + HEADER_BLOCK_LEN as u64 <= file_len),
+                None => file_len.checked_sub(HEADER_BLOCK_LEN as u64).filter(|&at| at >= data_start),
+            };
+            let trailer = match trailer_at {
+                Some(at) => {
+                    inner.seek(SeekFrom::Start(at))?;
+                    read_full(&mut inner, &mut raw)?;
+                    Some(parse_header_block(&raw).ok())
+                }
+                None => None,
+            };
+            let (header, leading_state, trailer_state) = match (leading, trailer) {
+                (Some((header, fixed)), None) => (header, copy_state(fixed), CopyState::Missing),
+                (Some((header, fixed)), Some(Some((copy, copy_fixed)))) if copy == header => (header, copy_state(fixed), copy_state(copy_fixed)),
+                (Some((header, fixed)), Some(_)) => (header, copy_state(fixed), CopyState::Damaged),
+                (None, Some(Some((copy, copy_fixed)))) => (copy, CopyState::Damaged, copy_state(copy_fixed)),
+                (None, _) if got < HEADER_LEN => return Err(FileError::BadHeader("file too short for an ECRS header")),
+                (None, _) if !magic => return Err(FileError::BadHeader("not an ECRS file (bad magic)")),
+                (None, _) => return Err(FileError::BadHeader("neither copy of the container header is readable")),
+            };
+            (header, HeaderState { leading: leading_state, trailer: Some(trailer_state) }, data_start)
+        };
+
+        let codec = check_params(header.nsym, header.nsize, header.depth)?;
+        inner.seek(SeekFrom::Start(data_start))?;
+        let (group, block) = (vec![0u8; codec.n() * header.depth], vec![0u8; codec.n()]);
+        let report = DecodeFileReport { blocks: header.blocks(), original_len: header.original_len, ..Default::default() };
+        Ok(Self { inner, header, state, codec, data_start, group, block, data: vec![], consumed: 0, next: 0, report })
+    }
+
+    pub fn header(&self) -> FileHeader {
+        self.header
+    }
+
+    pub fn header_state(&self) -> HeaderState {
+        self.state
+    }
+
+    /// Corrections and failures of the groups decoded so far
+    pub fn report(&self) -> &DecodeFileReport {
+        &self.report
+    }
+
+    /// Codewords decoded so far
+    pub fn blocks_read(&self) -> u64 {
+        self.next
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Decode the next group; returns its original bytes, padding dropped, or None after the last
+    pub fn next_group(&mut self) -> Result<Option<&[u8]>, FileError> {
+        let (k, depth) = (self.codec.k(), self.header.depth);
+        if self.next >= self.header.blocks() {
+            return Ok(None);
+        }
+        if read_full(&mut self.inner, &mut self.group)? != self.group.len() {
+            return Err(FileError::Truncated);
+        }
+        let start = self.next * k as u64;
+        self.data.clear();
+        for i in 0..depth {
+            unstripe(&self.group, i, depth, &mut self.block);
+            match self.codec.decode(&self.block) {
+                Ok((message, positions)) => {
+                    self.report.corrected_blocks += !positions.is_empty() as u64;
+                    self.report.corrected_symbols += positions.len() as u64;
+                    self.data.extend_from_slice(&message);
+                }
+                Err(_) => {
+                    self.report.failed_offsets.push(start + (i * k) as u64);
+                    self.data.extend_from_slice(&self.block[..k]);
+                }
+            }
+        }
+        self.next += depth as u64;
+        let keep = (self.header.original_len - start).min(self.data.len() as u64) as usize;
+        self.data.truncate(keep);
+        Ok(Some(&self.data))
+    }
+
+    /// Back to the first group with a fresh report
+    fn rewind(&mut self) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Start(self.data_start))?;
+        self.next = 0;
+        self.data.clear();
+        self.consumed = 0;
+        self.report = DecodeFileReport { blocks: self.header.blocks(), original_len: self.header.original_len, ..Default::default() };
+        Ok(())
+    }
+
+    /// Syndrome check of every block, without decoding; reading restarts from the first group
+    pub fn verify(&mut self) -> Result<VerifyFileReport, FileError> {
+        self.rewind()?;
+        let (n, depth) = (self.codec.n(), self.header.depth);
+        let mut result = VerifyFileReport { blocks: self.header.blocks(), header: self.state, ..Default::default() };
+        for first in (0..result.blocks).step_by(depth) {
+            if read_full(&mut self.inner, &mut self.group)? != self.group.len() {
+                return Err(FileError::Truncated);
+            }
+            for i in 0..depth {
+                unstripe(&self.group, i, depth, &mut self.block[..n]);
+                if !self.codec.check(&self.block) {
+                    result.dirty.push(first + i as u64);
+                }
+            }
+        }
+        self.rewind()?;
+        Ok(result)
+    }
+}
+
+impl<R: Read + Write + Seek> Reader<R> {
+    /// repair_with flushing after each write
+    pub fn repair(&mut self) -> Result<RepairReport, FileError> {
+        self.repair_with(|inner| inner.flush())
+    }
+
+    /// Correct the file in place: every group holding a correctable dirty block is rewritten
+    /// whole, then any damaged or missing header copy, each write followed by `commit`
+    /// (File::sync_data for durability). A file cut short is repaired up to the cut and then
+    /// fails with Truncated. Reading restarts from the first group afterwards.
+    pub fn repair_with(&mut self, mut commit: impl FnMut(&mut R) -> io::Result<()>) -> Result<RepairReport, FileError> {
+        self.rewind()?;
+        let (n, depth) = (self.codec.n(), self.header.depth);
+        let mut result = RepairReport { blocks: self.header.blocks(), ..Default::default() };
+        for first in (0..result.blocks).step_by(depth) {
+            if read_full(&mut self.inner, &mut self.group)? != self.group.len() {
+                return Err(FileError::Truncated);
+            }
+            let mut changed = false;
+            for i in 0..depth {
+                unstripe(&self.group, i, depth, &mut self.block);
+                if self.codec.check(&self.block) {
+                    continue;
+                }
+                match self.codec.decode(&self.block) {
+                    Ok((message, positions)) => {
+                        stripe(&self.codec.encode(&message), &mut self.group, i, depth);
+                        result.repaired.push((first + i as u64, positions.len()));
+                        changed = true;
+                    }
+                    Err(_) => result.unrecoverable.push(first + i as u64),
+                }
+            }
+            if changed {
+                self.inner.seek(SeekFrom::Start(self.data_start + first * n as u64))?;
+                self.inner.write_all(&self.group)?;
+                commit(&mut self.inner)?;
+            }
+        }
+
+        let block = header_block(&self.header);
+        if self.state.leading != CopyState::Clean {
+            self.inner.seek(SeekFrom::Start(0))?;
+            self.inner.write_all(&block)?;
+            commit(&mut self.inner)?;
+            result.headers_rewritten += 1;
+        }
+        if self.state.trailer.is_some_and(|t| t != CopyState::Clean) {
+            self.inner.seek(SeekFrom::Start(self.data_start + result.blocks * n as u64))?;
+            self.inner.write_all(&block)?;
+            commit(&mut self.inner)?;
+            result.headers_rewritten += 1;
+        }
+        if self.state.trailer.is_some() {
+            self.state = HeaderState { leading: CopyState::Clean, trailer: Some(CopyState::Clean) };
+        }
+        self.rewind()?;
+        Ok(result)
+    }
+}
+
+impl<R: Read + Seek> Read for Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.consumed == self.data.len() {
+            self.consumed = 0;
+            let more = self.next_group().map_err(|e| match e {
+                FileError::Io(e) => e,
+                FileError::Truncated => io::Error::new(io::ErrorKind::UnexpectedEof, e),
+                e => io::Error::new(io::ErrorKind::InvalidData, e),
+            })?;
+            if more.is_none() {
+                self.data.clear();
+                return Ok(0);
+            }
+        }
+        let len = buf.len().min(self.data.len() - self.consumed);
+        buf[..len].copy_from_slice(&self.data[self.consumed..][..len]);
+        self.consumed += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use std::io::Cursor;
+
+    fn container(data: &[u8], nsym: usize, nsize: usize, depth: usize) -> Vec<u8> {
+        let mut writer = Writer::new(vec![], nsym, nsize, depth, data.len() as u64).unwrap();
+        // Uneven writes, so groups fill across calls
+        for piece in data.chunks(97) {
+            writer.write_all(piece).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn read_all(bytes: &[u8]) -> (Vec<u8>, Reader<Cursor<&[u8]>>) {
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        let mut out = vec![];
+        reader.read_to_end(&mut out).unwrap();
+        (out, reader)
+    }
+
+    #[test]
+    fn test_layout_and_round_trip() {
+        let mut rng = Rng::new(386);
+        let data: Vec<u8> = (0..5_000).map(|_| rng.next_u64() as u8).collect();
+        let bytes = container(&data, 8, 64, 3);
+        // ceil(5000 / (3 * 56)) = 30 groups of 3
+        assert_eq!(bytes.len(), 2 * HEADER_BLOCK_LEN + 90 * 64);
+        assert_eq!(bytes[..8], [b'E', b'C', b'R', b'S', VERSION, 8, 64, 3]);
+        assert_eq!(bytes[..HEADER_BLOCK_LEN], bytes[bytes.len() - HEADER_BLOCK_LEN..]);
+        let (out, reader) = read_all(&bytes);
+        assert_eq!(out, data);
+        assert_eq!(reader.header(), FileHeader { nsym: 8, nsize: 64, depth: 3, original_len: 5_000 });
+        assert!(reader.header_state().is_intact());
+        assert_eq!(reader.report().blocks, 90);
+
+        let empty = container(&[], 8, 64, 1);
+        assert_eq!(empty.len(), 2 * HEADER_BLOCK_LEN);
+        assert_eq!(read_all(&empty).0, b"");
+
+        let mut long = Writer::new(vec![], 8, 64, 1, 10).unwrap();
+        assert_eq!(long.write_all(b"123456789012").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        let mut short = Writer::new(vec![], 8, 64, 1, 10).unwrap();
+        short.write_all(b"12345").unwrap();
+        assert!(matches!(short.finish(), Err(FileError::LengthMismatch { declared: 10, written: 5 })));
+        assert!(matches!(Writer::new(vec![], 8, 64, 0, 10), Err(FileError::Params(_))));
+    }
+
+    #[test]
+    fn test_header_damage() {
+        let data: Vec<u8> = (0..3_000u32).map(|i| (i * 31 % 253) as u8).collect();
+        let pristine = container(&data, 16, 128, 2);
+        let end = pristine.len();
+
+        // Six bytes of the leading copy, t = 6: corrected in place
+        let mut damaged = pristine.clone();
+        for i in [0, 4, 9, 15, 20, 31] {
+            damaged[i] ^= 0xa5;
+        }
+        let (out, reader) = read_all(&damaged);
+        assert_eq!(out, data);
+        assert_eq!(reader.header_state(), HeaderState { leading: CopyState::Corrected(6), trailer: Some(CopyState::Clean) });
+
+        // A lost first sector takes the leading copy and the first groups: the trailer describes
+        // the file and the data blocks fail on their own
+        let mut wiped = pristine.clone();
+        wiped[..512].fill(0xff); // all zeros would be a valid codeword, silently decoding to zeros
+        let (out, reader) = read_all(&wiped);
+        assert_eq!(reader.header_state(), HeaderState { leading: CopyState::Damaged, trailer: Some(CopyState::Clean) });
+        assert_eq!(reader.report().failed_offsets, vec![0, 112, 224, 336]);
+        assert_eq!(out[448..], data[448..]);
+
+        // Leading copy gone, data intact: repair rewrites it and the file is as written
+        let mut file = pristine.clone();
+        file[..HEADER_BLOCK_LEN].fill(0xff);
+        let mut reader = Reader::new(Cursor::new(&mut file)).unwrap();
+        let report = reader.repair().unwrap();
+        assert_eq!((report.repaired.len(), report.headers_rewritten), (0, 1));
+        assert_eq!(file, pristine);
+
+        // The trailer disagreeing with a readable leading copy is marked damaged and rewritten
+        let mut file = pristine.clone();
+        let forged = header_block(&FileHeader { nsym: 16, nsize: 128, depth: 2, original_len: 7 });
+        file[end - HEADER_BLOCK_LEN..].copy_from_slice(&forged);
+        let mut reader = Reader::new(Cursor::new(&mut file)).unwrap();
+        assert_eq!(reader.header().original_len, 3_000);
+        assert_eq!(reader.header_state().trailer, Some(CopyState::Damaged));
+        assert_eq!(reader.repair().unwrap().headers_rewritten, 1);
+        assert_eq!(file, pristine);
+
+        // Both copies beyond repair
+        let mut both = pristine.clone();
+        for i in (4..11).chain(end - 7..end) {
+            both[i] ^= 0xff; // seven errors in each copy, t = 6
+        }
+        assert!(matches!(Reader::new(Cursor::new(&both[..])), Err(FileError::BadHeader("neither copy of the container header is readable"))));
+        assert!(matches!(Reader::new(Cursor::new(&both[..10])), Err(FileError::BadHeader("file too short for an ECRS header"))));
+    }
+
+    #[test]
+    fn test_data_damage_and_truncation() {
+        let data: Vec<u8> = (0..4_000u32).map(|i| (i * 17 % 251) as u8).collect();
+        let pristine = container(&data, 8, 64, 1);
+        let block = |b: usize| HEADER_BLOCK_LEN + 64 * b;
+
+        let mut damaged = pristine.clone();
+        damaged[block(3) + 5] ^= 0x11;
+        for i in 0..5 {
+            damaged[block(20) + i * 9] ^= 0x22; // five errors, t = 4
+        }
+        let mut file = damaged.clone();
+        let mut reader = Reader::new(Cursor::new(&mut file)).unwrap();
+        assert_eq!(reader.verify().unwrap().dirty, vec![3, 20]);
+        let mut out = vec![];
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(reader.report().failed_offsets, vec![20 * 56]);
+        assert_eq!((out[..20 * 56].to_vec(), out[21 * 56..].to_vec()), (data[..20 * 56].to_vec(), data[21 * 56..].to_vec()));
+        let report = reader.repair().unwrap();
+        assert_eq!((report.repaired, report.unrecoverable, report.headers_rewritten), (vec![(3, 1)], vec![20], 0));
+        assert_eq!(file[..block(20)], pristine[..block(20)]);
+
+        // Cut inside the data: the groups before the cut still decode, then the read fails
+        let cut = &pristine[..block(30) + 10];
+        let mut reader = Reader::new(Cursor::new(cut)).unwrap();
+        assert_eq!(reader.header_state().trailer, Some(CopyState::Missing));
+        let mut out = vec![];
+        let e = reader.read_to_end(&mut out).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(out, data[..30 * 56]);
+        assert!(matches!(Reader::new(Cursor::new(cut)).unwrap().verify(), Err(FileError::Truncated)));
+
+        // Cut through the trailer only: the data is whole and repair writes the trailer back
+        let mut file = pristine[..pristine.len() - 12].to_vec();
+        let mut reader = Reader::new(Cursor::new(&mut file)).unwrap();
+        assert_eq!(reader.read_to_end(&mut vec![]).unwrap(), data.len());
+        assert_eq!(reader.repair().unwrap().headers_rewritten, 1);
+        assert!(reader.header_state().is_intact());
+        assert_eq!(file, pristine);
+    }
+
+    #[test]
+    fn test_reads_bare_header_files() {
+        let data: Vec<u8> = (0..1_000u32).map(|i| (i * 3 % 256) as u8).collect();
+        let header = FileHeader { nsym: 8, nsize: 64, depth: 1, original_len: data.len() as u64 };
+        let codec = RsCodec::new(8, 64).unwrap();
+        let mut file = header.to_bytes().to_vec();
+        for chunk in data.chunks(56) {
+            let mut message = chunk.to_vec();
+            message.resize(56, 0);
+            file.extend(codec.encode(&message));
+        }
+        file[HEADER_LEN + 64 * 4] ^= 0x40;
+        let (out, reader) = read_all(&file);
+        assert_eq!(out, data);
+        assert_eq!(reader.header_state(), HeaderState { leading: CopyState::Clean, trailer: None });
+        assert_eq!(reader.report().corrected_symbols, 1);
+        let mut reader = Reader::new(Cursor::new(&mut file)).unwrap();
+        assert_eq!(reader.repair().unwrap().repaired, vec![(4, 1)]);
+        assert!(reader.header_state().is_intact());
+    }
+}
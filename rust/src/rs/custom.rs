@@ -0,0 +1,303 @@
+// RS over GF(256) with a non-default parameterization, for interop with other libraries
+// The generator polynomial has roots beta^(fcr + j), j = 0..nsym-1, where beta is the chosen
+// generator element of GF(2^8) modulo `prim`. The defaults (fcr = 0, beta = 2, prim = 0x11d)
+// are the code in rs.rs, which keeps its faster static-table path; RsCodec only routes here
+// for other choices.
+
+use crate::gf2m::Gf2m;
+use crate::rs::{berlekamp_massey_in, berlekamp_massey_inversionless_in, validate_params, BmVariant, RsError};
+
+/// Code construction knobs, named as in reedsolo; fields missing when deserialized take the defaults
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct CodeOptions {
+    /// First consecutive root exponent
+    pub fcr: usize,
+    /// Field element whose powers are the roots (must be primitive)
+    pub generator: u8,
+    /// Primitive polynomial of the field, x^8 bit included
+    pub prim: u32,
+}
+
+impl Default for CodeOptions {
+    fn default() -> Self {
+        Self { fcr: 0, generator: 2, prim: 0x11d }
+    }
+}
+
+/// RS(nsize, nsize - nsym) for arbitrary CodeOptions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomRs {
+    field: Gf2m,
+    nsym: usize,
+    nsize: usize,
+    options: CodeOptions,
+    gen_log: usize, // log_alpha(beta), coprime to 255
+    generator: Vec<u8>,
+}
+
+impl CustomRs {
+    pub fn new(nsym: usize, nsize: usize, options: CodeOptions) -> Result<Self, RsError> {
+        validate_params(nsym, nsize)?;
+        if options.fcr >= 255 {
+            return Err(RsError::InvalidParameters("fcr must be less than 255"));
+        }
+        let field = Gf2m::new(8, options.prim)
+            .ok_or(RsError::InvalidParameters("prim must be a primitive polynomial of degree 8"))?;
+        if options.generator == 0 {
+            return Err(RsError::InvalidParameters("generator must be a primitive element of the field"));
+        }
+        let gen_log = field.log(options.generator as u16);
+        // beta = alpha^gen_log has order 255 / gcd(gen_log, 255)
+        if gcd(gen_log, 255) != 1 {
+            return Err(RsError::InvalidParameters("generator must be a primitive element of the field"));
+        }
+        let mut code = Self { field, nsym, nsize, options, gen_log, generator: vec![1] };
+        for j in 0..nsym {
+            let root = code.beta_pow(options.fcr + j);
+            let mut next = vec![0u8; code.generator.len() + 1];
+            for (d, &coef) in code.generator.iter().enumerate() {
+                next[d] ^= code.mul(coef, root);
+                next[d + 1] ^= coef;
+            }
+            code.generator = next;
+        }
+        Ok(code)
+    }
+
+    pub fn options(&self) -> CodeOptions {
+        self.options
+    }
+
+    /// Generator polynomial, generator[i] = coefficient of x^i
+    pub fn generator(&self) -> &[u8] {
+        &self.generator
+    }
+
+    fn mul(&self, a: u8, b: u8) -> u8 {
+        self.field.mul(a as u16, b as u16) as u8
+    }
+
+    fn div(&self, a: u8, b: u8) -> u8 {
+        self.field.div(a as u16, b as u16) as u8
+    }
+
+    /// beta^e for any e >= 0
+    fn beta_pow(&self, e: usize) -> u8 {
+        self.field.exp(self.gen_log * (e % 255)) as u8
+    }
+
+    fn eval(&self, poly: &[u8], x: u8) -> u8 {
+        poly.iter().rev().fold(0u8, |acc, &c| self.mul(acc, x) ^ c)
+    }
+
+    fn poly_mul(&self, p: &[u8], q: &[u8]) -> Vec<u8> {
+        let mut out = vec![0u8; p.len() + q.len() - 1];
+        for (i, &a) in p.iter().enumerate() {
+            for (j, &b) in q.iter().enumerate() {
+                out[i + j] ^= self.mul(a, b);
+            }
+        }
+        out
+    }
+
+    /// Systematic encode; panics unless message is exactly k bytes
+    pub fn encode(&self, message: &[u8]) -> Vec<u8> {
+        let (k, nsym) = (self.nsize - self.nsym, self.nsym);
+        assert_eq!(message.len(), k, "message must be k={} bytes", k);
+        let mut codeword = vec![0u8; self.nsize];
+        codeword[..k].copy_from_slice(message);
+        for i in 0..k {
+            let coef = codeword[i];
+            if coef != 0 {
+                for j in 1..=nsym {
+                    codeword[i + j] ^= self.mul(self.generator[nsym - j], coef);
+                }
+            }
+        }
+        codeword[..k].copy_from_slice(message);
+        codeword
+    }
+
+    /// Syndromes S_j = r(beta^(fcr + j)), j = 0..nsym-1
+    pub fn syndromes(&self, codeword: &[u8]) -> Vec<u8> {
+        let n = codeword.len();
+        (0..self.nsym)
+            .map(|j| {
+                let root_exp = self.options.fcr + j;
+                codeword
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |s, (idx, &byte)| s ^ self.mul(byte, self.beta_pow(root_exp * (n - 1 - idx) % 255)))
+            })
+            .collect()
+    }
+
+    /// Errors-and-erasures decode with the same contract as rs::decode_errata
+    /// (an empty erasure list is plain error correction)
+    pub fn decode_errata(&self, codeword: &[u8], erasures: &[usize]) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+        self.decode_errata_bm(codeword, erasures, BmVariant::Standard)
+    }
+
+    /// decode_errata finding the locator with the given Berlekamp-Massey variant
+    pub(crate) fn decode_errata_bm(&self, codeword: &[u8], erasures: &[usize], bm: BmVariant) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+        let (n, nsym) = (codeword.len(), self.nsym);
+        if n < nsym {
+            return Err(RsError::CodewordTooShort);
+        }
+        validate_params(nsym, n)?;
+        let mut seen = vec![false; n];
+        for &pos in erasures {
+            if pos >= n || seen[pos] {
+                return Err(RsError::InvalidErasure);
+            }
+            seen[pos] = true;
+        }
+        let h = erasures.len();
+        if h > nsym {
+            return Err(RsError::TooManyErrors);
+        }
+        let k = n - nsym;
+        let syndromes = self.syndromes(codeword);
+        if syndromes.iter().all(|&s| s == 0) {
+            return Ok((codeword[..k].to_vec(), vec![]));
+        }
+
+        // With Y_i = e_i * X_i^fcr the syndromes are S_j = sum Y_i X_i^j, the fcr = 0 form,
+        // so the usual locator machinery applies with X_i = beta^(n-1-pos)
+        let x_of = |pos: usize| self.beta_pow(n - 1 - pos);
+        let mut gamma = Vec::with_capacity(h + 1);
+        gamma.push(1u8);
+        for &pos in erasures {
+            gamma.push(0);
+            for j in (1..gamma.len()).rev() {
+                gamma[j] ^= self.mul(gamma[j - 1], x_of(pos));
+            }
+        }
+        // Only the modified syndromes h..nsym are used, as in rs::decode_errata
+        let wide: Vec<u16> = (h..nsym).map(|i| gamma.iter().enumerate().fold(0u8, |m, (j, &gj)| m ^ self.mul(syndromes[i - j], gj)) as u16).collect();
+        let lambda = match bm {
+            BmVariant::Standard => berlekamp_massey_in(&self.field, &wide),
+            BmVariant::Inversionless => berlekamp_massey_inversionless_in(&self.field, &wide),
+        };
+        let lambda: Vec<u8> = lambda.iter().map(|&c| c as u8).collect();
+        let num_errors = lambda.len() - 1;
+        if num_errors == 0 && h == 0 {
+            return Err(RsError::TrivialLocator);
+        }
+        if 2 * num_errors + h > nsym {
+            return Err(RsError::TooManyErrors);
+        }
+
+        let psi = self.poly_mul(&lambda, &gamma);
+        // psi has at most deg psi roots, so the search can stop at the last one
+        let positions: Vec<usize> = (0..n).filter(|&pos| self.eval(&psi, self.field.inv(x_of(pos) as u16) as u8) == 0).take(psi.len() - 1).collect();
+        if positions.len() != psi.len() - 1 {
+            return Err(RsError::ChienSearchFailed);
+        }
+
+        // Forney for the fcr = 0 form, then undo the X_i^fcr scaling. Omega = S psi mod x^nsym has
+        // degree below deg psi for a true errata locator, and verification catches any other
+        let mut omega = vec![0u8; (psi.len() - 1).min(nsym)];
+        for (i, o) in omega.iter_mut().enumerate() {
+            for (j, &pj) in psi.iter().enumerate().take(i + 1) {
+                *o ^= self.mul(syndromes[i - j], pj);
+            }
+        }
+        let mut psi_prime = vec![0u8; psi.len()];
+        for i in (1..psi.len()).step_by(2) {
+            psi_prime[i - 1] = psi[i];
+        }
+        let mut corrected = codeword.to_vec();
+        let mut changed = vec![];
+        for &pos in &positions {
+            let x = x_of(pos);
+            let x_inv = self.field.inv(x as u16) as u8;
+            let denom = self.eval(&psi_prime, x_inv);
+            if denom == 0 {
+                return Err(RsError::VerificationFailed);
+            }
+            let scaled = self.mul(x, self.div(self.eval(&omega, x_inv), denom));
+            let x_fcr_inv = self.beta_pow((255 - (n - 1 - pos) % 255) * self.options.fcr % 255);
+            let magnitude = self.mul(scaled, x_fcr_inv);
+            if magnitude != 0 {
+                corrected[pos] ^= magnitude;
+                changed.push(pos);
+            }
+        }
+        if self.syndromes(&corrected).iter().any(|&s| s != 0) {
+            return Err(RsError::VerificationFailed);
+        }
+        Ok((corrected[..k].to_vec(), changed))
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use crate::rs::{build_generator, decode, encode};
+
+    #[test]
+    fn test_defaults_match_standard_code() {
+        let code = CustomRs::new(10, 40, CodeOptions::default()).unwrap();
+        assert_eq!(code.generator(), &build_generator(10)[..]);
+        let message: Vec<u8> = (0..30).collect();
+        let mut codeword = code.encode(&message);
+        assert_eq!(codeword, encode(&message, 10, &build_generator(10)));
+        codeword[4] ^= 0x10;
+        codeword[33] ^= 0x99;
+        assert_eq!(code.decode_errata(&codeword, &[]), decode(&codeword, 10));
+    }
+
+    #[test]
+    fn test_custom_options_correct_errata() {
+        let mut rng = Rng::new(341);
+        // alpha^7 and alpha^11 are primitive since 7 and 11 are coprime to 255
+        let element = |prim, e| Gf2m::new(8, prim).unwrap().exp(e) as u8;
+        for options in [
+            CodeOptions { fcr: 1, ..Default::default() },
+            CodeOptions { fcr: 120, generator: element(0x11d, 7), prim: 0x11d },
+            CodeOptions { fcr: 0, generator: 2, prim: 0x12d },
+            CodeOptions { fcr: 7, generator: element(0x187, 11), prim: 0x187 },
+        ] {
+            let code = CustomRs::new(12, 60, options).unwrap();
+            for trial in 0..100 {
+                let message: Vec<u8> = (0..48).map(|_| rng.next_u64() as u8).collect();
+                let clean = code.encode(&message);
+                assert!(code.syndromes(&clean).iter().all(|&s| s == 0), "{:?}", options);
+                let mut received = clean.clone();
+                let (errors, erasures) = [(6, 0), (3, 6), (0, 12), (4, 3)][trial % 4];
+                let positions = rng.distinct(errors + erasures, 60);
+                let erased = &positions[..erasures];
+                for &pos in &positions {
+                    received[pos] ^= rng.nonzero_byte();
+                }
+                let (decoded, changed) = code.decode_errata(&received, erased).unwrap();
+                assert_eq!(decoded, message, "{:?}", options);
+                assert_eq!(changed, positions);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_options() {
+        let bad = |options| CustomRs::new(4, 20, options).unwrap_err();
+        // x^8 + x^4 + x^3 + x + 1 (the AES polynomial) is irreducible but x is not primitive
+        assert!(matches!(bad(CodeOptions { prim: 0x11b, ..Default::default() }), RsError::InvalidParameters(m) if m.contains("prim")));
+        assert!(matches!(bad(CodeOptions { prim: 0x1ff, ..Default::default() }), RsError::InvalidParameters(_)));
+        // alpha^3 has order 85
+        let not_primitive = Gf2m::new(8, 0x11d).unwrap().exp(3) as u8;
+        assert!(matches!(bad(CodeOptions { generator: not_primitive, ..Default::default() }), RsError::InvalidParameters(m) if m.contains("generator")));
+        assert!(matches!(bad(CodeOptions { generator: 0, ..Default::default() }), RsError::InvalidParameters(_)));
+        assert!(matches!(bad(CodeOptions { fcr: 255, ..Default::default() }), RsError::InvalidParameters(_)));
+    }
+}
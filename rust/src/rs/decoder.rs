@@ -0,0 +1,480 @@
+// RsDecoder: errors-only decode into buffers kept across calls
+// The stateless rs::decode builds one per call. Holding on to a decoder makes a clean block cost
+// no heap allocation at all (the message is borrowed from the input) and a dirty one none once
+// the buffers have grown to the code's size, which new() does up front.
+
+use crate::gf256::{gf_mul, simd, GF};
+use crate::rs::{
+    berlekamp_massey_fixed_into, berlekamp_massey_into, berlekamp_massey_inversionless_into, chien_forney_fixed, chien_roots_into, forney_roots_into, residual_syndromes,
+    syndromes_zero, validate_params, BmVariant, ChienRoot, ForneyBuffers, RsError,
+};
+use crate::opcount::{self, Stage};
+use crate::trace::{event, span};
+
+/// Corrected bytes and positions, borrowed from the decoder
+type Borrowed<'a> = Result<(&'a [u8], &'a [usize]), RsError>;
+
+/// Reusable workspace for decoding RS(n, n - nsym) blocks over GF(256)
+#[derive(Debug, Clone)]
+pub struct RsDecoder {
+    nsym: usize,
+    syndromes: Vec<u8>,
+    /// Berlekamp-Massey: current and previous locator (lambda and b for the inversionless variant)
+    sigma: Vec<u8>,
+    prev: Vec<u8>,
+    roots: Vec<ChienRoot>,
+    positions: Vec<usize>,
+    forney: ForneyBuffers,
+    corrected: Vec<u8>,
+    bm: BmVariant,
+    fixed_work: bool,
+}
+
+impl RsDecoder {
+    /// Buffers sized for nsize-byte codewords; decode_with accepts every length rs::decode does
+    pub fn new(nsym: usize, nsize: usize) -> Self {
+        // BM's locators can reach nsym + 1 terms before trailing zeros are trimmed
+        let locator = nsym + 1;
+        Self {
+            nsym,
+            syndromes: Vec::with_capacity(nsym),
+            sigma: Vec::with_capacity(locator),
+            prev: Vec::with_capacity(locator),
+            roots: Vec::with_capacity(nsym / 2 + 1),
+            positions: Vec::with_capacity(nsym / 2 + 1),
+            forney: ForneyBuffers {
+                omega: Vec::with_capacity(nsym / 2),
+                sigma_prime: Vec::with_capacity(nsym / 4 + 1),
+                magnitudes: Vec::with_capacity(nsym / 2 + 1),
+            },
+            corrected: Vec::with_capacity(nsize),
+            bm: BmVariant::Standard,
+            fixed_work: false,
+        }
+    }
+
+    /// The same workspace finding locators with the given Berlekamp-Massey variant
+    pub fn with_bm(mut self, bm: BmVariant) -> Self {
+        self.bm = bm;
+        self
+    }
+
+    /// The same workspace doing data-independent work per block (DecodeOptions::fixed_work): BM
+    /// runs all nsym iterations at full width, Chien and Forney visit all n positions, and a
+    /// clean codeword goes through every stage too. Results are identical either way.
+    pub fn with_fixed_work(mut self, fixed_work: bool) -> Self {
+        self.fixed_work = fixed_work;
+        if fixed_work {
+            // A failing locator can have up to nsym roots
+            self.roots.reserve(self.nsym + 1);
+            self.positions.reserve(self.nsym + 1);
+            self.forney.magnitudes.reserve(self.nsym + 1);
+        }
+        self
+    }
+
+    pub fn nsym(&self) -> usize {
+        self.nsym
+    }
+
+    pub fn bm(&self) -> BmVariant {
+        self.bm
+    }
+
+    pub fn fixed_work(&self) -> bool {
+        self.fixed_work
+    }
+
+    /// rs::decode without the copies: (message, corrected positions) borrowed from the codeword
+    /// when it is clean and from this decoder otherwise, valid until the next call
+    pub fn decode_with<'a>(&'a mut self, codeword: &'a [u8]) -> Result<(&'a [u8], &'a [usize]), RsError> {
+        let _span = span!(Debug, "decode", n = codeword.len(), nsym = self.nsym);
+        traced(self.decode_stages(codeword))
+    }
+
+    fn decode_stages<'a>(&'a mut self, codeword: &'a [u8]) -> Result<(&'a [u8], &'a [usize]), RsError> {
+        let (n, nsym) = (codeword.len(), self.nsym);
+        if n < nsym {
+            return Err(RsError::CodewordTooShort);
+        }
+        validate_params(nsym, n)?;
+        let k = n - nsym;
+
+        {
+            let _span = span!(Debug, "syndromes");
+            let _ops = opcount::stage(Stage::Syndromes);
+            self.syndromes.resize(nsym, 0);
+            simd::syndromes(codeword, &mut self.syndromes);
+            event!(Trace, "syndromes", syndromes = &self.syndromes[..]);
+        }
+        if self.fixed_work {
+            let (corrected, positions) = self.correct_fixed(codeword)?;
+            return Ok((&corrected[..k], positions));
+        }
+        if syndromes_zero(&self.syndromes) {
+            return Ok((&codeword[..k], &[]));
+        }
+        let (corrected, positions) = self.correct(codeword)?;
+        Ok((&corrected[..k], positions))
+    }
+
+    /// Correct a codeword whose syndromes were already computed, by a screening pass say:
+    /// (corrected codeword, corrected positions), held by this decoder until the next call
+    pub fn decode_dirty<'a>(&'a mut self, codeword: &[u8], syndromes: &[u8]) -> Result<(&'a [u8], &'a [usize]), RsError> {
+        let _span = span!(Debug, "decode", n = codeword.len(), nsym = self.nsym);
+        let (n, nsym) = (codeword.len(), self.nsym);
+        if n < nsym {
+            return traced(Err(RsError::CodewordTooShort));
+        }
+        traced(validate_params(nsym, n))?;
+        assert_eq!(syndromes.len(), nsym, "syndromes must be nsym={} bytes", nsym);
+        self.syndromes.clear();
+        self.syndromes.extend_from_slice(syndromes);
+        if self.fixed_work {
+            return traced(self.correct_fixed(codeword));
+        }
+        if syndromes_zero(syndromes) {
+            self.corrected.clear();
+            self.corrected.extend_from_slice(codeword);
+            return Ok((&self.corrected, &[]));
+        }
+        traced(self.correct(codeword))
+    }
+
+    /// BM, Chien, Forney and verification from self.syndromes; n and nsym already validated
+    fn correct(&mut self, codeword: &[u8]) -> Result<(&[u8], &[usize]), RsError> {
+        let nsym = self.nsym;
+        let num_errors = {
+            let _span = span!(Debug, "bm");
+            let _ops = opcount::stage(Stage::Bm);
+            match self.bm {
+                BmVariant::Standard => berlekamp_massey_into(&GF, &self.syndromes, &mut self.sigma, &mut self.prev),
+                BmVariant::Inversionless => berlekamp_massey_inversionless_into(&GF, &self.syndromes, &mut self.sigma, &mut self.prev),
+            }
+            let num_errors = self.sigma.len() - 1;
+            event!(Debug, "bm.locator", errors = num_errors, locator = &self.sigma[..]);
+            num_errors
+        };
+        if num_errors == 0 {
+            return Err(RsError::TrivialLocator);
+        }
+        if num_errors > nsym / 2 {
+            return Err(RsError::TooManyErrors);
+        }
+        self.locate_and_correct(codeword)
+    }
+
+    /// correct for fixed_work: every stage runs whatever the syndromes, and only then are the
+    /// outcomes checked, in correct's order, so the result is the same
+    fn correct_fixed(&mut self, codeword: &[u8]) -> Result<(&[u8], &[usize]), RsError> {
+        let (n, nsym) = (codeword.len(), self.nsym);
+        let clean = syndromes_zero(&self.syndromes);
+        let num_errors = {
+            let _span = span!(Debug, "bm");
+            let _ops = opcount::stage(Stage::Bm);
+            berlekamp_massey_fixed_into(&self.syndromes, self.bm, &mut self.sigma, &mut self.prev);
+            let num_errors = self.sigma.len() - 1;
+            event!(Debug, "bm.locator", errors = num_errors, locator = &self.sigma[..]);
+            num_errors
+        };
+        {
+            let _span = span!(Debug, "chien");
+            let _ops = opcount::stage(Stage::Chien);
+            chien_forney_fixed(&self.syndromes, &self.sigma, n, &mut self.roots, &mut self.forney.magnitudes);
+            self.positions.clear();
+            self.positions.extend(self.roots.iter().map(|root| root.pos));
+            event!(Debug, "chien.roots", found = self.roots.len(), positions = &self.positions[..]);
+            event!(Trace, "forney.magnitudes", magnitudes = &self.forney.magnitudes[..]);
+        }
+        self.corrected.clear();
+        self.corrected.extend_from_slice(codeword);
+        for (&pos, &mag) in self.positions.iter().zip(&self.forney.magnitudes) {
+            self.corrected[pos] ^= mag;
+        }
+        opcount::add(self.positions.len());
+        let verified = {
+            let _span = span!(Debug, "verify");
+            let _ops = opcount::stage(Stage::Verify);
+            simd::syndromes(&self.corrected, &mut self.syndromes);
+            let verified = syndromes_zero(&self.syndromes) && !self.forney.magnitudes.contains(&0);
+            event!(Debug, "verify", clean = verified as usize);
+            verified
+        };
+        if clean {
+            return Ok((&self.corrected, &[]));
+        }
+        if num_errors == 0 {
+            return Err(RsError::TrivialLocator);
+        }
+        if num_errors > nsym / 2 {
+            return Err(RsError::TooManyErrors);
+        }
+        if self.roots.len() != num_errors {
+            return Err(RsError::ChienSearchFailed);
+        }
+        if !verified {
+            return Err(RsError::VerificationFailed);
+        }
+        Ok((&self.corrected, &self.positions))
+    }
+
+    /// rs::decode_with_sigma: sigma in place of BM's locator, at most nsym / 2 errors and
+    /// sigma[0] nonzero (trailing zeros are ignored)
+    /// Returns the decode and whether sigma fails the key equation against the syndromes; such a
+    /// decode is always an error. Buffers are held by this decoder until the next call, as
+    /// decode_dirty.
+    pub fn decode_with_sigma<'a>(&'a mut self, codeword: &[u8], sigma: &[u8]) -> (Borrowed<'a>, bool) {
+        let _span = span!(Debug, "decode", n = codeword.len(), nsym = self.nsym, sigma = sigma);
+        let (n, nsym) = (codeword.len(), self.nsym);
+        if n < nsym {
+            return (traced(Err(RsError::CodewordTooShort)), false);
+        }
+        if let Err(error) = validate_params(nsym, n) {
+            return (traced(Err(error)), false);
+        }
+        let degree = sigma.iter().rposition(|&c| c != 0).unwrap_or(0);
+        if sigma.first().is_none_or(|&c| c == 0) {
+            return (traced(Err(RsError::InvalidParameters("sigma[0] must be nonzero"))), false);
+        }
+        if degree > nsym / 2 {
+            return (traced(Err(RsError::InvalidParameters("sigma degree must be at most nsym / 2"))), false);
+        }
+        {
+            let _span = span!(Debug, "syndromes");
+            let _ops = opcount::stage(Stage::Syndromes);
+            self.syndromes.resize(nsym, 0);
+            simd::syndromes(codeword, &mut self.syndromes);
+            event!(Trace, "syndromes", syndromes = &self.syndromes[..]);
+        }
+        self.sigma.clear();
+        self.sigma.extend_from_slice(&sigma[..=degree]);
+        // Key equation: sigma generates the syndromes, sum_i sigma_i S_(r-i) = 0 for r >= degree
+        let inconsistent = (degree..nsym).any(|r| {
+            opcount::add(degree + 1);
+            self.sigma.iter().enumerate().fold(0, |acc, (i, &c)| acc ^ gf_mul(c, self.syndromes[r - i])) != 0
+        });
+        event!(Debug, "sigma.check", degree = degree, inconsistent = inconsistent as usize);
+        let result = match (syndromes_zero(&self.syndromes), degree) {
+            (true, 0) => {
+                self.corrected.clear();
+                self.corrected.extend_from_slice(codeword);
+                Ok((&self.corrected[..], &[][..]))
+            }
+            (false, 0) => Err(RsError::TrivialLocator),
+            _ => self.locate_and_correct(codeword),
+        };
+        (traced(result), inconsistent)
+    }
+
+    /// Chien, Forney and verification from self.syndromes and self.sigma
+    fn locate_and_correct(&mut self, codeword: &[u8]) -> Result<(&[u8], &[usize]), RsError> {
+        let n = codeword.len();
+        let num_errors = self.sigma.len() - 1;
+        {
+            let _span = span!(Debug, "chien");
+            let _ops = opcount::stage(Stage::Chien);
+            chien_roots_into(&self.sigma, n, &mut self.roots);
+            self.positions.clear();
+            self.positions.extend(self.roots.iter().map(|root| root.pos));
+            event!(Debug, "chien.roots", found = self.roots.len(), positions = &self.positions[..]);
+        }
+        if self.roots.len() != num_errors {
+            return Err(RsError::ChienSearchFailed);
+        }
+        {
+            let _span = span!(Debug, "forney");
+            let _ops = opcount::stage(Stage::Forney);
+            forney_roots_into(&self.syndromes, &self.sigma, &self.roots, &mut self.forney);
+            event!(Trace, "forney.magnitudes", magnitudes = &self.forney.magnitudes[..]);
+        }
+
+        self.corrected.clear();
+        self.corrected.extend_from_slice(codeword);
+        for (&pos, &mag) in self.positions.iter().zip(&self.forney.magnitudes) {
+            self.corrected[pos] ^= mag;
+        }
+        opcount::add(self.positions.len());
+
+        // Verify: the corrected word must have zero syndromes (see residual_syndromes for how)
+        let _span = span!(Debug, "verify");
+        let _ops = opcount::stage(Stage::Verify);
+        residual_syndromes(&mut self.syndromes, &self.corrected, &self.positions, &self.forney.magnitudes);
+        #[cfg(debug_assertions)]
+        {
+            let (mut full, nsym) = ([0u8; 255], self.nsym);
+            simd::syndromes_scalar(&self.corrected, &mut full[..nsym]);
+            debug_assert_eq!(self.syndromes, full[..nsym]);
+        }
+        // A zero magnitude is a root where nothing was wrong: BM's locator never has one, so
+        // only a supplied sigma (decode_with_sigma) can fail here
+        let clean = syndromes_zero(&self.syndromes) && !self.forney.magnitudes.contains(&0);
+        event!(Debug, "verify", clean = clean as usize);
+        if !clean {
+            return Err(RsError::VerificationFailed);
+        }
+        Ok((&self.corrected, &self.positions))
+    }
+}
+
+/// Emit a failed decode as a "decode.failed" event carrying the RsError, inside the decode span
+pub(crate) fn traced<T>(result: Result<T, RsError>) -> Result<T, RsError> {
+    if let Err(error) = &result {
+        event!(Debug, "decode.failed", error = *error);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use crate::rs::{berlekamp_massey, cached_generator, calc_syndromes, chien_search, decode, encode, forney, BmVariant};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    /// Counts this thread's allocations, so concurrently running tests do not interfere
+    struct CountingAlloc;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.with(|a| a.set(a.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static COUNTING: CountingAlloc = CountingAlloc;
+
+    fn allocations_during(f: impl FnOnce()) -> usize {
+        let before = ALLOCATIONS.with(|a| a.get());
+        f();
+        ALLOCATIONS.with(|a| a.get()) - before
+    }
+
+    /// The decoder as it was before the workspace, from the stateless building blocks
+    fn reference_decode(codeword: &[u8], nsym: usize) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+        let n = codeword.len();
+        let syndromes = calc_syndromes(codeword, nsym);
+        if syndromes_zero(&syndromes) {
+            return Ok((codeword[..n - nsym].to_vec(), vec![]));
+        }
+        let sigma = berlekamp_massey(&syndromes);
+        let num_errors = sigma.len() - 1;
+        if num_errors == 0 {
+            return Err(RsError::TrivialLocator);
+        }
+        if num_errors > nsym / 2 {
+            return Err(RsError::TooManyErrors);
+        }
+        let positions = chien_search(&sigma, n);
+        if positions.len() != num_errors {
+            return Err(RsError::ChienSearchFailed);
+        }
+        let mut corrected = codeword.to_vec();
+        for (&pos, &mag) in positions.iter().zip(&forney(&syndromes, &sigma, &positions, n)) {
+            corrected[pos] ^= mag;
+        }
+        if !syndromes_zero(&calc_syndromes(&corrected, nsym)) {
+            return Err(RsError::VerificationFailed);
+        }
+        Ok((corrected[..n - nsym].to_vec(), positions))
+    }
+
+    #[test]
+    fn test_matches_stateless_decode() {
+        let mut rng = Rng::new(368);
+        for (nsym, n) in [(2, 10), (16, 100), (32, 255)] {
+            let mut decoder = RsDecoder::new(nsym, n);
+            for _ in 0..200 {
+                let message: Vec<u8> = (0..n - nsym).map(|_| rng.next_u64() as u8).collect();
+                let mut received = encode(&message, nsym, cached_generator(nsym));
+                // Up to nsym errors, so every failure mode turns up
+                let errors = rng.below(nsym + 1);
+                for pos in rng.distinct(errors, n) {
+                    received[pos] ^= rng.nonzero_byte();
+                }
+                let expected = reference_decode(&received, nsym);
+                assert_eq!(decode(&received, nsym), expected);
+                let got = decoder.decode_with(&received).map(|(m, p)| (m.to_vec(), p.to_vec()));
+                assert_eq!(got, expected);
+            }
+        }
+        assert_eq!(RsDecoder::new(8, 4).decode_with(&[0; 4]), Err(RsError::CodewordTooShort));
+    }
+
+    #[test]
+    fn test_inversionless_decodes_identically() {
+        // Both variants over the same received words, up to nsym errors so every failure mode
+        // turns up: the same corrected codeword, positions or error every time
+        let mut rng = Rng::new(410);
+        for (nsym, n) in [(2, 10), (8, 40), (16, 100), (32, 255), (254, 255)] {
+            let mut standard = RsDecoder::new(nsym, n);
+            let mut inversionless = RsDecoder::new(nsym, n).with_bm(BmVariant::Inversionless);
+            for _ in 0..300 {
+                let message: Vec<u8> = (0..n - nsym).map(|_| rng.next_u64() as u8).collect();
+                let mut received = encode(&message, nsym, cached_generator(nsym));
+                let errors = rng.below(nsym + 1);
+                for pos in rng.distinct(errors, n) {
+                    received[pos] ^= rng.nonzero_byte();
+                }
+                let syndromes = calc_syndromes(&received, nsym);
+                let expected = standard.decode_dirty(&received, &syndromes).map(|(c, p)| (c.to_vec(), p.to_vec()));
+                assert_eq!(inversionless.decode_dirty(&received, &syndromes).map(|(c, p)| (c.to_vec(), p.to_vec())), expected, "nsym={} errors={}", nsym, errors);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_work_decodes_identically() {
+        // Clean words, correctable ones and failures of every kind, for both BM variants, through
+        // decode_with and decode_dirty
+        let mut rng = Rng::new(4150);
+        for (nsym, n) in [(1, 8), (2, 10), (8, 40), (16, 100), (32, 255), (254, 255)] {
+            for bm in [BmVariant::Standard, BmVariant::Inversionless] {
+                let mut normal = RsDecoder::new(nsym, n).with_bm(bm);
+                let mut fixed = RsDecoder::new(nsym, n).with_bm(bm).with_fixed_work(true);
+                for _ in 0..100 {
+                    let message: Vec<u8> = (0..n - nsym).map(|_| rng.next_u64() as u8).collect();
+                    let mut received = encode(&message, nsym, cached_generator(nsym));
+                    let errors = rng.below(nsym + 2).min(n);
+                    for pos in rng.distinct(errors, n) {
+                        received[pos] ^= rng.nonzero_byte();
+                    }
+                    let expected = normal.decode_with(&received).map(|(m, p)| (m.to_vec(), p.to_vec()));
+                    assert_eq!(fixed.decode_with(&received).map(|(m, p)| (m.to_vec(), p.to_vec())), expected, "nsym={} errors={} {:?}", nsym, errors, bm);
+                    let syndromes = calc_syndromes(&received, nsym);
+                    let expected = normal.decode_dirty(&received, &syndromes).map(|(c, p)| (c.to_vec(), p.to_vec()));
+                    assert_eq!(fixed.decode_dirty(&received, &syndromes).map(|(c, p)| (c.to_vec(), p.to_vec())), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_allocations_on_hot_path() {
+        let (nsym, n) = (32, 255);
+        let mut rng = Rng::new(3680);
+        let message: Vec<u8> = (0..n - nsym).map(|_| rng.next_u64() as u8).collect();
+        let clean = encode(&message, nsym, cached_generator(nsym));
+        let mut dirty = clean.clone();
+        for pos in rng.distinct(nsym / 2, n) {
+            dirty[pos] ^= rng.nonzero_byte();
+        }
+        let mut decoder = RsDecoder::new(nsym, n);
+        // Warm up: the kernel is detected once per process
+        decoder.decode_with(&dirty).unwrap();
+
+        assert_eq!(allocations_during(|| assert!(decoder.decode_with(&clean).is_ok())), 0);
+        assert_eq!(allocations_during(|| assert_eq!(decoder.decode_with(&dirty).unwrap().1.len(), nsym / 2)), 0);
+        // The stateless wrapper pays for the workspace on every call
+        assert!(allocations_during(|| drop(decode(&clean, nsym))) > 0);
+    }
+}
@@ -0,0 +1,95 @@
+// Decoding a codeword stored as two buffers, data || parity, without joining them
+// Syndromes are accumulated across both slices by Horner's rule, and the error pattern is
+// checked against those syndromes directly, so the n-byte codeword is never assembled.
+
+use crate::gf256::{gf_mul, GF};
+use crate::rs::{berlekamp_massey, chien_roots_into, forney_roots_into, syndromes_zero, update_syndromes, validate_params, ForneyBuffers, RsError};
+
+/// Syndromes of the logical codeword data || parity, equal to calc_syndromes of the concatenation
+pub fn syndromes_split(data: &[u8], parity: &[u8], nsym: usize) -> Vec<u8> {
+    (0..nsym)
+        .map(|j| {
+            let root = GF.exp[j % 255];
+            data.iter().chain(parity).fold(0u8, |s, &byte| gf_mul(s, root) ^ byte)
+        })
+        .collect()
+}
+
+/// (position, xor magnitude) for every symbol of data || parity the decoder corrects
+/// nsym is parity.len(); positions below data.len() are in the data buffer, the rest in the
+/// parity buffer at position - data.len(). Neither buffer is modified.
+pub fn corrections_split(data: &[u8], parity: &[u8]) -> Result<Vec<(usize, u8)>, RsError> {
+    let (nsym, n) = (parity.len(), data.len() + parity.len());
+    validate_params(nsym, n)?;
+    let syndromes = syndromes_split(data, parity, nsym);
+    if syndromes_zero(&syndromes) {
+        return Ok(vec![]);
+    }
+
+    let sigma = berlekamp_massey(&syndromes);
+    let num_errors = sigma.len() - 1;
+    if num_errors == 0 {
+        return Err(RsError::TrivialLocator);
+    }
+    if num_errors > nsym / 2 {
+        return Err(RsError::TooManyErrors);
+    }
+    let mut roots = vec![];
+    chien_roots_into(&sigma, n, &mut roots);
+    if roots.len() != num_errors {
+        return Err(RsError::ChienSearchFailed);
+    }
+    let positions: Vec<usize> = roots.iter().map(|root| root.pos).collect();
+    let mut forney_buffers = ForneyBuffers::default();
+    forney_roots_into(&syndromes, &sigma, &roots, &mut forney_buffers);
+    let magnitudes = forney_buffers.magnitudes;
+
+    // Syndromes are linear, so the correction is valid iff the error pattern alone reproduces them
+    let mut residual = syndromes;
+    update_syndromes(&mut residual, n, &positions, &magnitudes);
+    if !syndromes_zero(&residual) {
+        return Err(RsError::VerificationFailed);
+    }
+    Ok(positions.into_iter().zip(magnitudes).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use crate::rs::{build_generator, calc_syndromes, decode, encode};
+
+    #[test]
+    fn test_matches_joined_decode() {
+        let nsym = 16;
+        let mut rng = Rng::new(361);
+        for k in [1, 40, 239] {
+            let message: Vec<u8> = (0..k).map(|_| rng.next_u64() as u8).collect();
+            let codeword = encode(&message, nsym, &build_generator(nsym));
+            for errors in 0..=nsym / 2 + 1 {
+                let mut received = codeword.clone();
+                for pos in rng.distinct(errors, codeword.len()) {
+                    received[pos] ^= rng.nonzero_byte();
+                }
+                let (data, parity) = received.split_at(k);
+                assert_eq!(syndromes_split(data, parity, nsym), calc_syndromes(&received, nsym));
+                match (corrections_split(data, parity), decode(&received, nsym)) {
+                    (Ok(fixes), Ok((_, positions))) => {
+                        assert_eq!(fixes.iter().map(|&(p, _)| p).collect::<Vec<_>>(), positions);
+                        for (p, mag) in fixes {
+                            received[p] ^= mag;
+                        }
+                        assert_eq!(received, codeword);
+                    }
+                    (split, joined) => assert_eq!(split.err(), joined.err(), "k={} errors={}", k, errors),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_split() {
+        assert_eq!(corrections_split(&[0; 250], &[0; 6]), Err(RsError::InvalidParameters("nsize must be at most 255 over GF(256)")));
+        assert!(matches!(corrections_split(&[], &[0; 4]), Err(RsError::InvalidParameters(_))));
+    }
+}
@@ -0,0 +1,436 @@
+// Whole-file protection: the data cut into k-byte chunks, each the message of an n-byte codeword
+// The final chunk is zero-padded and the padding is dropped again on decode using the recorded
+// length. With an interleave depth d >= 2 every d * k bytes of data become d codewords striped as
+// rs::Interleaver does, so a burst of b bytes costs each codeword at most ceil(b / d) symbols;
+// the last group is padded to d whole codewords.
+// encode_file writes the version 3 container of rs::container, whose header is RS-protected and
+// repeated at the end of the file. Versions 1 and 2 put a bare 16-byte header first: magic
+// "ECRS", version, nsym, nsize, depth (version 2 only; byte 7 is 0 in version 1), original length
+// (u64 little-endian). decode_file, repair_file and verify_file read all three.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::rs::container::{self, HeaderState, Reader};
+use crate::rs::RsError;
+
+pub const MAGIC: [u8; 4] = *b"ECRS";
+pub const VERSION: u8 = 1;
+/// Version of files written with an interleave depth of 2 or more, which version 1 readers reject
+pub const VERSION_INTERLEAVED: u8 = 2;
+/// Length of the bare header of versions 1 and 2
+pub const HEADER_LEN: usize = 16;
+
+/// Parameters recorded in the header of a protected file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHeader {
+    pub nsym: usize,
+    pub nsize: usize,
+    /// Interleave depth, 1 for plain consecutive codewords
+    pub depth: usize,
+    pub original_len: u64,
+}
+
+impl FileHeader {
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut out = [0u8; HEADER_LEN];
+        out[..4].copy_from_slice(&MAGIC);
+        out[5] = self.nsym as u8;
+        out[6] = self.nsize as u8;
+        if self.depth > 1 {
+            out[4] = VERSION_INTERLEAVED;
+            out[7] = self.depth as u8;
+        } else {
+            out[4] = VERSION;
+        }
+        out[8..].copy_from_slice(&self.original_len.to_le_bytes());
+        out
+    }
+
+    pub fn parse(bytes: &[u8; HEADER_LEN]) -> Result<Self, FileError> {
+        if bytes[..4] != MAGIC {
+            return Err(FileError::BadHeader("not an ECRS file (bad magic)"));
+        }
+        let depth = match bytes[4] {
+            VERSION => 1,
+            VERSION_INTERLEAVED if bytes[7] >= 2 => bytes[7] as usize,
+            VERSION_INTERLEAVED => return Err(FileError::BadHeader("interleaved ECRS header with depth below 2")),
+            _ => return Err(FileError::BadHeader("unsupported ECRS version")),
+        };
+        let mut len = [0u8; 8];
+        len.copy_from_slice(&bytes[8..]);
+        Ok(Self { nsym: bytes[5] as usize, nsize: bytes[6] as usize, depth, original_len: u64::from_le_bytes(len) })
+    }
+
+    /// Number of codewords following the header, a multiple of depth
+    pub fn blocks(&self) -> u64 {
+        let k = (self.nsize - self.nsym) as u64;
+        self.original_len.div_ceil(k * self.depth as u64) * self.depth as u64
+    }
+}
+
+/// Outcome of decode_file; unrecoverable blocks are passed through uncorrected
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct DecodeFileReport {
+    pub blocks: u64,
+    /// Blocks with at least one corrected symbol
+    pub corrected_blocks: u64,
+    pub corrected_symbols: u64,
+    /// Offsets in the original data of blocks that could not be corrected
+    pub failed_offsets: Vec<u64>,
+    pub original_len: u64,
+}
+
+/// Outcome of repair_file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct RepairReport {
+    pub blocks: u64,
+    /// (block index, corrected symbols) for every block that was rewritten
+    pub repaired: Vec<(u64, usize)>,
+    /// Blocks left untouched because they could not be corrected
+    pub unrecoverable: Vec<u64>,
+    /// Container header copies rewritten because they were damaged or missing
+    pub headers_rewritten: usize,
+}
+
+/// Outcome of verify_file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct VerifyFileReport {
+    pub blocks: u64,
+    /// Blocks whose syndromes are nonzero, correctable or not
+    pub dirty: Vec<u64>,
+    pub header: HeaderState,
+}
+
+#[derive(Debug)]
+pub enum FileError {
+    Io(io::Error),
+    BadHeader(&'static str),
+    Params(RsError),
+    /// The encoded file ends before the last block the header promises
+    Truncated,
+    /// The progress callback asked to stop
+    Cancelled,
+    /// repair_file was given parameters that disagree with the header
+    ParamMismatch { header: (usize, usize), given: (usize, usize) },
+    /// A container was finished with a different amount of data than its header declares
+    LengthMismatch { declared: u64, written: u64 },
+    /// The data file a recovery sidecar protects is no longer the length it was made for
+    LengthChanged { expected: u64, actual: u64 },
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::Io(e) => write!(f, "I/O error: {}", e),
+            FileError::BadHeader(msg) => f.write_str(msg),
+            FileError::Params(e) => write!(f, "{}", e),
+            FileError::Truncated => f.write_str("encoded file is truncated"),
+            FileError::Cancelled => f.write_str("cancelled by progress callback"),
+            FileError::ParamMismatch { header, given } => write!(
+                f,
+                "file was encoded with (nsym={}, nsize={}), not (nsym={}, nsize={})",
+                header.0, header.1, given.0, given.1
+            ),
+            FileError::LengthMismatch { declared, written } => write!(f, "container declares {} bytes of data but {} were written", declared, written),
+            FileError::LengthChanged { expected, actual } => write!(f, "data file is {} bytes but the recovery file was made for {}", actual, expected),
+        }
+    }
+}
+
+impl std::error::Error for FileError {}
+
+impl From<io::Error> for FileError {
+    fn from(e: io::Error) -> Self {
+        FileError::Io(e)
+    }
+}
+
+/// Progress hook: called with (blocks_done, blocks_total), return false to stop
+pub type Progress<'a> = &'a mut dyn FnMut(u64, u64) -> bool;
+
+/// Fill `buf` as far as the reader allows; returns the byte count (short only at EOF)
+pub(crate) fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Call the hook when the count passes a multiple of `every` on its way from `before` to `done`
+fn report(progress: &mut Option<Progress<'_>>, every: u64, before: u64, done: u64, total: u64) -> Result<(), FileError> {
+    if let Some(cb) = progress.as_mut() {
+        let every = every.max(1);
+        if (done / every != before / every || done == total) && !cb(done, total) {
+            return Err(FileError::Cancelled);
+        }
+    }
+    Ok(())
+}
+
+/// Protect `src` into `dst`; returns the header written
+pub fn encode_file(
+    src: &Path,
+    dst: &Path,
+    nsym: usize,
+    nsize: usize,
+    progress: Option<Progress<'_>>,
+    progress_every: u64,
+) -> Result<FileHeader, FileError> {
+    encode_file_interleaved(src, dst, nsym, nsize, 1, progress, progress_every)
+}
+
+/// encode_file with codewords striped `depth` at a time (1..=255; 1 is encode_file's layout)
+pub fn encode_file_interleaved(
+    src: &Path,
+    dst: &Path,
+    nsym: usize,
+    nsize: usize,
+    depth: usize,
+    mut progress: Option<Progress<'_>>,
+    progress_every: u64,
+) -> Result<FileHeader, FileError> {
+    let k = container::check_params(nsym, nsize, depth)?.k();
+    let input = File::open(src)?;
+    let original_len = input.metadata()?.len();
+    let mut writer = container::Writer::new(BufWriter::new(File::create(dst)?), nsym, nsize, depth, original_len)?;
+    let header = writer.header();
+    let total = header.blocks();
+
+    let mut reader = BufReader::new(input);
+    let mut chunk = vec![0u8; k * depth];
+    let (mut remaining, mut done) = (original_len, 0);
+    while remaining > 0 {
+        let want = remaining.min(chunk.len() as u64) as usize;
+        let got = read_full(&mut reader, &mut chunk[..want])?;
+        if got == 0 {
+            break; // source shrank while reading; finish reports it
+        }
+        writer.write_all(&chunk[..got])?;
+        remaining -= got as u64;
+        let before = done;
+        done = (original_len - remaining).div_ceil((k * depth) as u64) * depth as u64;
+        report(&mut progress, progress_every, before, done, total)?;
+    }
+    writer.finish()?;
+    Ok(header)
+}
+
+/// Write codeword i of a group into its interleaved positions
+pub(crate) fn stripe(codeword: &[u8], group: &mut [u8], i: usize, depth: usize) {
+    for (p, &byte) in codeword.iter().enumerate() {
+        group[p * depth + i] = byte;
+    }
+}
+
+/// Read codeword i of a group back out of its interleaved positions
+pub(crate) fn unstripe(group: &[u8], i: usize, depth: usize, codeword: &mut [u8]) {
+    for (p, byte) in codeword.iter_mut().enumerate() {
+        *byte = group[p * depth + i];
+    }
+}
+
+/// Recover the original file from `src` into `dst`, correcting every block it can
+pub fn decode_file(
+    src: &Path,
+    dst: &Path,
+    mut progress: Option<Progress<'_>>,
+    progress_every: u64,
+) -> Result<DecodeFileReport, FileError> {
+    let mut reader = Reader::new(BufReader::new(File::open(src)?))?;
+    let total = reader.header().blocks();
+    let mut writer = BufWriter::new(File::create(dst)?);
+    let mut done = 0;
+    while let Some(data) = reader.next_group()? {
+        writer.write_all(data)?;
+        let before = done;
+        done = reader.blocks_read();
+        report(&mut progress, progress_every, before, done, total)?;
+    }
+    writer.flush()?;
+    Ok(reader.report().clone())
+}
+
+/// Correct a protected file in place, rewriting only the groups whose syndromes are nonzero
+/// Each repaired group is written and synced before the scan continues, so an interrupted
+/// repair leaves every block either original or fully corrected. A damaged or missing container
+/// header copy is rewritten last. `nsym`/`nsize`, when given, must match the header.
+pub fn repair_file(path: &Path, nsym: Option<usize>, nsize: Option<usize>) -> Result<RepairReport, FileError> {
+    let mut reader = Reader::new(OpenOptions::new().read(true).write(true).open(path)?)?;
+    let header = reader.header();
+    let recorded = (header.nsym, header.nsize);
+    let given = (nsym.unwrap_or(header.nsym), nsize.unwrap_or(header.nsize));
+    if given != recorded {
+        return Err(FileError::ParamMismatch { header: recorded, given });
+    }
+    reader.repair_with(|file| file.sync_data())
+}
+
+/// Syndrome check of every block of a protected file and the state of its header copies,
+/// without decoding or writing anything
+pub fn verify_file(path: &Path) -> Result<VerifyFileReport, FileError> {
+    Reader::new(BufReader::new(File::open(path)?))?.verify()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::container::{CopyState, HEADER_BLOCK_LEN};
+    use std::fs;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("ecc_model_file_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_round_trip_with_damage() {
+        let (src, enc, dec) = (temp_path("src"), temp_path("enc"), temp_path("dec"));
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7 % 251) as u8).collect();
+        fs::write(&src, &data).unwrap();
+
+        let mut calls = vec![];
+        let mut cb = |done, total| {
+            calls.push((done, total));
+            true
+        };
+        let header = encode_file(&src, &enc, 8, 64, Some(&mut cb), 50).unwrap();
+        assert_eq!(header.blocks(), 179); // ceil(10000 / 56)
+        assert_eq!(calls.last(), Some(&(179, 179)));
+        assert_eq!(calls.len(), 4); // 50, 100, 150, 179
+
+        let mut encoded = fs::read(&enc).unwrap();
+        assert_eq!(encoded.len(), 2 * HEADER_BLOCK_LEN + 179 * 64);
+        encoded[HEADER_BLOCK_LEN + 3] ^= 0xff; // block 0: one error
+        for i in 0..5 {
+            encoded[HEADER_BLOCK_LEN + 64 * 7 + i] ^= 0x55; // block 7: five errors, t = 4
+        }
+        fs::write(&enc, &encoded).unwrap();
+
+        let report = decode_file(&enc, &dec, None, 1).unwrap();
+        assert_eq!(report.corrected_symbols, 1);
+        assert_eq!(report.failed_offsets, vec![7 * 56]);
+        let out = fs::read(&dec).unwrap();
+        assert_eq!(out.len(), data.len());
+        assert_eq!(out[..7 * 56], data[..7 * 56]);
+        assert_eq!(out[8 * 56..], data[8 * 56..]);
+
+        // Losing the trailer costs nothing; losing the end of the last block fails the decode
+        fs::write(&enc, &encoded[..encoded.len() - HEADER_BLOCK_LEN]).unwrap();
+        assert_eq!(decode_file(&enc, &dec, None, 1).unwrap().failed_offsets, vec![7 * 56]);
+        fs::write(&enc, &encoded[..encoded.len() - HEADER_BLOCK_LEN - 1]).unwrap();
+        assert!(matches!(decode_file(&enc, &dec, None, 1), Err(FileError::Truncated)));
+        for p in [src, enc, dec] {
+            fs::remove_file(p).ok();
+        }
+    }
+
+    #[test]
+    fn test_repair_in_place() {
+        let (src, enc) = (temp_path("repair_src"), temp_path("repair_enc"));
+        let data: Vec<u8> = (0..5_000u32).map(|i| (i * 13 % 256) as u8).collect();
+        fs::write(&src, &data).unwrap();
+        encode_file(&src, &enc, 8, 64, None, 1).unwrap();
+        let pristine = fs::read(&enc).unwrap();
+
+        let mut damaged = pristine.clone();
+        damaged[HEADER_BLOCK_LEN + 64 * 2 + 10] ^= 1;
+        damaged[HEADER_BLOCK_LEN + 64 * 2 + 63] ^= 2; // parity byte
+        for i in 0..5 {
+            damaged[HEADER_BLOCK_LEN + 64 * 30 + i] ^= 0x80;
+        }
+        fs::write(&enc, &damaged).unwrap();
+        assert!(matches!(repair_file(&enc, Some(4), None), Err(FileError::ParamMismatch { .. })));
+
+        let report = repair_file(&enc, Some(8), Some(64)).unwrap();
+        assert_eq!(report.repaired, vec![(2, 2)]);
+        assert_eq!(report.unrecoverable, vec![30]);
+        let repaired = fs::read(&enc).unwrap();
+        let block30 = HEADER_BLOCK_LEN + 64 * 30..HEADER_BLOCK_LEN + 64 * 31;
+        assert_eq!(repaired[block30.clone()], damaged[block30.clone()]);
+        assert_eq!(repaired[..block30.start], pristine[..block30.start]);
+        assert_eq!(repaired[block30.end..], pristine[block30.end..]);
+        for p in [src, enc] {
+            fs::remove_file(p).ok();
+        }
+    }
+
+    #[test]
+    fn test_interleaved_burst() {
+        let (src, enc, dec) = (temp_path("il_src"), temp_path("il_enc"), temp_path("il_dec"));
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 11 % 241) as u8).collect();
+        fs::write(&src, &data).unwrap();
+        assert!(matches!(encode_file_interleaved(&src, &enc, 8, 64, 0, None, 1), Err(FileError::Params(_))));
+        let header = encode_file_interleaved(&src, &enc, 8, 64, 4, None, 1).unwrap();
+        assert_eq!(header.blocks(), 180); // ceil(10000 / (4 * 56)) = 45 groups of 4
+        let pristine = fs::read(&enc).unwrap();
+        assert_eq!(pristine.len(), 2 * HEADER_BLOCK_LEN + 180 * 64);
+        assert_eq!(verify_file(&enc).unwrap(), VerifyFileReport { blocks: 180, dirty: vec![], header: HeaderState { leading: CopyState::Clean, trailer: Some(CopyState::Clean) } });
+
+        // 16 bytes inside group 3: four per codeword, t = 4; 17 in group 10 is one too many for block 40
+        let mut damaged = pristine.clone();
+        let (burst, fatal) = (HEADER_BLOCK_LEN + 256 * 3 + 100, HEADER_BLOCK_LEN + 256 * 10 + 8);
+        for b in &mut damaged[burst..burst + 16] {
+            *b ^= 0x3c;
+        }
+        for b in &mut damaged[fatal..fatal + 17] {
+            *b ^= 0xc3;
+        }
+        fs::write(&enc, &damaged).unwrap();
+        assert_eq!(verify_file(&enc).unwrap().dirty, vec![12, 13, 14, 15, 40, 41, 42, 43]);
+
+        let report = decode_file(&enc, &dec, None, 1).unwrap();
+        assert_eq!((report.corrected_blocks, report.corrected_symbols), (7, 28));
+        assert_eq!(report.failed_offsets, vec![40 * 56]);
+        let out = fs::read(&dec).unwrap();
+        assert_eq!(out[..40 * 56], data[..40 * 56]);
+        assert_eq!(out[41 * 56..], data[41 * 56..]);
+
+        let report = repair_file(&enc, None, None).unwrap();
+        assert_eq!(report.repaired, vec![(12, 4), (13, 4), (14, 4), (15, 4), (41, 4), (42, 4), (43, 4)]);
+        assert_eq!(report.unrecoverable, vec![40]);
+        assert_eq!(verify_file(&enc).unwrap().dirty, vec![40]);
+        let repaired = fs::read(&enc).unwrap();
+        assert_eq!(repaired[..fatal], pristine[..fatal]);
+        for p in [src, enc, dec] {
+            fs::remove_file(p).ok();
+        }
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let header = FileHeader { nsym: 32, nsize: 255, depth: 1, original_len: 1 << 40 };
+        assert_eq!(FileHeader::parse(&header.to_bytes()).unwrap(), header);
+        assert_eq!(header.to_bytes()[4..8], [VERSION, 32, 255, 0]);
+        let interleaved = FileHeader { depth: 4, ..header };
+        assert_eq!(FileHeader::parse(&interleaved.to_bytes()).unwrap(), interleaved);
+        assert_eq!(interleaved.to_bytes()[4..8], [VERSION_INTERLEAVED, 32, 255, 4]);
+        let mut bad = header.to_bytes();
+        bad[0] = b'X';
+        assert!(matches!(FileHeader::parse(&bad), Err(FileError::BadHeader(_))));
+        let mut shallow = interleaved.to_bytes();
+        shallow[7] = 1;
+        assert!(matches!(FileHeader::parse(&shallow), Err(FileError::BadHeader(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_report_json_defaults() {
+        let report = DecodeFileReport { blocks: 9, corrected_blocks: 2, corrected_symbols: 5, failed_offsets: vec![448], original_len: 500 };
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(serde_json::from_str::<DecodeFileReport>(&json).unwrap(), report);
+        // Written before corrected_blocks existed
+        let old: DecodeFileReport = serde_json::from_str(r#"{"blocks":9,"corrected_symbols":5,"failed_offsets":[448],"original_len":500}"#).unwrap();
+        assert_eq!(old, DecodeFileReport { corrected_blocks: 0, ..report });
+    }
+}
@@ -0,0 +1,140 @@
+// Fixed-size RS codes with parameters resolved at compile time
+// Codewords, messages and positions are arrays, and the work is done by the stack-only
+// encode_into/decode_in_place, so encode and decode never touch the heap and build under no_std.
+// Output is bit-identical to the slice-based encode/decode in rs.rs.
+
+use crate::gf256::{gf_mul, GF};
+use crate::rs::{decode_in_place, encode_into, generator_into, RsError};
+
+/// Corrected positions, at most NSYM of them, ascending
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorPositions<const NSYM: usize> {
+    positions: [usize; NSYM],
+    len: usize,
+}
+
+impl<const NSYM: usize> ErrorPositions<NSYM> {
+    fn new() -> Self {
+        Self { positions: [0; NSYM], len: 0 }
+    }
+
+    pub fn as_slice(&self) -> &[usize] {
+        &self.positions[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// RS(N, N - NSYM) over GF(256)
+#[derive(Debug, Clone)]
+pub struct RsCode<const N: usize, const NSYM: usize> {
+    generator: [u8; N], // generator[i] = coefficient of x^i, degree NSYM
+}
+
+impl<const N: usize, const NSYM: usize> RsCode<N, NSYM> {
+    const VALID: () = assert!(0 < NSYM && NSYM < N && N <= 255, "RsCode requires 0 < NSYM < N <= 255");
+
+    pub fn new() -> Self {
+        let () = Self::VALID;
+        let mut generator = [0u8; N];
+        generator_into(NSYM, &mut generator);
+        Self { generator }
+    }
+
+    pub const fn n(&self) -> usize {
+        N
+    }
+
+    pub const fn nsym(&self) -> usize {
+        NSYM
+    }
+
+    pub const fn k(&self) -> usize {
+        N - NSYM
+    }
+
+    /// Systematic encode; K must equal N - NSYM (checked at compile time)
+    pub fn encode<const K: usize>(&self, message: &[u8; K]) -> [u8; N] {
+        const { assert!(K + NSYM == N, "message length must be N - NSYM") };
+        let mut codeword = [0u8; N];
+        encode_into(message, NSYM, &self.generator, &mut codeword).expect("N and NSYM are checked at compile time");
+        codeword
+    }
+
+    /// Syndromes S_j = r(alpha^j), j = 0..NSYM-1
+    pub fn syndromes(&self, codeword: &[u8; N]) -> [u8; NSYM] {
+        let mut syndromes = [0u8; NSYM];
+        for (j, syn) in syndromes.iter_mut().enumerate() {
+            for (idx, &byte) in codeword.iter().enumerate() {
+                *syn ^= gf_mul(byte, GF.exp[(j * (N - 1 - idx)) % 255]);
+            }
+        }
+        syndromes
+    }
+
+    /// Decode correcting up to NSYM/2 symbol errors; K must equal N - NSYM
+    pub fn decode<const K: usize>(&self, codeword: &[u8; N]) -> Result<([u8; K], ErrorPositions<NSYM>), RsError> {
+        const { assert!(K + NSYM == N, "message length must be N - NSYM") };
+        let mut corrected = *codeword;
+        let mut positions = ErrorPositions::<NSYM>::new();
+        positions.len = decode_in_place(&mut corrected, NSYM, &mut positions.positions)?;
+        let mut message = [0u8; K];
+        message.copy_from_slice(&corrected[..K]);
+        Ok((message, positions))
+    }
+}
+
+impl<const N: usize, const NSYM: usize> Default for RsCode<N, NSYM> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use crate::rs::{build_generator, decode, encode};
+
+    fn compare_with_dynamic<const N: usize, const NSYM: usize, const K: usize>(seed: u64) {
+        let code = RsCode::<N, NSYM>::new();
+        let generator = build_generator(NSYM);
+        let mut rng = Rng::new(seed);
+        for trial in 0..200 {
+            let mut message = [0u8; K];
+            message.iter_mut().for_each(|b| *b = rng.next_u64() as u8);
+            let mut codeword = code.encode(&message);
+            assert_eq!(codeword.to_vec(), encode(&message, NSYM, &generator));
+
+            // Up to one past capability so failures are compared too
+            for pos in rng.distinct(trial % (NSYM / 2 + 2), N) {
+                codeword[pos] ^= rng.nonzero_byte();
+            }
+            let fixed = code.decode::<K>(&codeword).map(|(m, p)| (m.to_vec(), p.as_slice().to_vec()));
+            assert_eq!(fixed, decode(&codeword, NSYM));
+        }
+    }
+
+    #[test]
+    fn test_matches_dynamic_api() {
+        compare_with_dynamic::<255, 32, 223>(1);
+        compare_with_dynamic::<36, 4, 32>(2);
+        compare_with_dynamic::<15, 6, 9>(3);
+    }
+
+    #[test]
+    fn test_parameters() {
+        let code = RsCode::<255, 32>::default();
+        assert_eq!((code.n(), code.k(), code.nsym()), (255, 223, 32));
+        assert_eq!(code.generator[..=32], build_generator(32)[..]);
+        let (message, positions) = code.decode(&code.encode(&[7u8; 223])).unwrap();
+        assert_eq!(message, [7u8; 223]);
+        assert!(positions.is_empty());
+    }
+}
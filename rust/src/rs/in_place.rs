@@ -0,0 +1,236 @@
+// Encode and decode into caller-owned buffers, the RS API of no_std builds
+// Scratch polynomials live in stack arrays sized for the longest GF(256) code, so nothing here
+// allocates. With `std` the vector kernels compute parity and syndromes; without it the byte loops
+// below do. Output is bit-identical to encode and decode in rs.rs.
+
+#[cfg(feature = "std")]
+use crate::gf256::simd;
+use crate::gf256::{gf_div, gf_mul, GF};
+use crate::rs::{chien_roots, poly_eval_at, syndromes_zero, update_syndromes, validate_params, ChienRoot, RsError};
+
+/// g(x) = (x - alpha^0)...(x - alpha^(nsym-1)) into generator[..=nsym], as build_generator returns it
+/// Panics if generator is shorter than nsym + 1.
+pub fn generator_into(nsym: usize, generator: &mut [u8]) {
+    let generator = &mut generator[..=nsym];
+    generator.fill(0);
+    generator[0] = 1;
+    for i in 0..nsym {
+        let root = GF.exp[i];
+        for d in (1..=i + 1).rev() {
+            generator[d] = generator[d - 1] ^ gf_mul(generator[d], root);
+        }
+        generator[0] = gf_mul(generator[0], root);
+    }
+}
+
+/// Systematic encode into codeword = [message | parity], which must be message.len() + nsym bytes
+/// generator is build_generator(nsym) or what generator_into wrote.
+pub fn encode_into(message: &[u8], nsym: usize, generator: &[u8], codeword: &mut [u8]) -> Result<(), RsError> {
+    validate_params(nsym, codeword.len())?;
+    if codeword.len() != message.len() + nsym {
+        return Err(RsError::InvalidParameters("codeword buffer must be message.len() + nsym bytes"));
+    }
+    let (data, parity) = codeword.split_at_mut(message.len());
+    data.copy_from_slice(message);
+    parity_into(message, generator, parity);
+    Ok(())
+}
+
+/// parity = message(x) * x^nsym mod g(x) with nsym = parity.len(), for callers that lay out the
+/// codeword themselves; generator as for encode_into
+pub fn parity_into(message: &[u8], generator: &[u8], parity: &mut [u8]) {
+    let nsym = parity.len();
+    // LFSR taps, highest-order generator coefficient first
+    let mut taps = [0u8; 255];
+    for (t, &g) in taps.iter_mut().zip(generator[..nsym].iter().rev()) {
+        *t = g;
+    }
+    let taps = &taps[..nsym];
+    #[cfg(feature = "std")]
+    simd::remainder(message, taps, parity);
+    #[cfg(not(feature = "std"))]
+    {
+        parity.fill(0);
+        for &m in message {
+            let fb = m ^ parity[0];
+            parity.copy_within(1.., 0);
+            parity[nsym - 1] = 0;
+            for (r, &t) in parity.iter_mut().zip(taps) {
+                *r ^= gf_mul(t, fb);
+            }
+        }
+    }
+}
+
+/// Correct up to nsym / 2 symbol errors in place; the message is then codeword[..len - nsym]
+/// Writes the corrected positions, ascending, to positions[..count] and returns count, so
+/// positions must hold nsym / 2 entries. On error the codeword is left as it was.
+pub fn decode_in_place(codeword: &mut [u8], nsym: usize, positions: &mut [usize]) -> Result<usize, RsError> {
+    let n = codeword.len();
+    if n < nsym {
+        return Err(RsError::CodewordTooShort);
+    }
+    validate_params(nsym, n)?;
+    if positions.len() < nsym / 2 {
+        return Err(RsError::InvalidParameters("positions buffer must hold nsym / 2 entries"));
+    }
+
+    let mut syndromes = [0u8; 255];
+    let syndromes = &mut syndromes[..nsym];
+    #[cfg(feature = "std")]
+    simd::syndromes(codeword, syndromes);
+    #[cfg(not(feature = "std"))]
+    for (j, s) in syndromes.iter_mut().enumerate() {
+        let root = GF.exp[j];
+        *s = codeword.iter().fold(0u8, |acc, &byte| gf_mul(acc, root) ^ byte);
+    }
+    if syndromes_zero(syndromes) {
+        return Ok(0);
+    }
+    correct_in_place(codeword, syndromes, positions)
+}
+
+/// decode_in_place from the codeword's nonzero syndromes, one per parity byte; lengths already
+/// checked. The syndromes are used as scratch.
+pub(crate) fn correct_in_place(codeword: &mut [u8], syndromes: &mut [u8], positions: &mut [usize]) -> Result<usize, RsError> {
+    let (n, nsym) = (codeword.len(), syndromes.len());
+    let sigma = berlekamp_massey(syndromes);
+    let num_errors = sigma.iter().rposition(|&c| c != 0).unwrap_or(0);
+    if num_errors == 0 {
+        return Err(RsError::TrivialLocator);
+    }
+    if num_errors > nsym / 2 {
+        return Err(RsError::TooManyErrors);
+    }
+    let sigma = &sigma[..=num_errors];
+
+    let mut roots = [ChienRoot { pos: 0, x: 0, x_inv: 0 }; 128];
+    let mut count = 0;
+    for root in chien_roots(sigma, n) {
+        if count == num_errors {
+            return Err(RsError::ChienSearchFailed);
+        }
+        roots[count] = root;
+        count += 1;
+    }
+    if count != num_errors {
+        return Err(RsError::ChienSearchFailed);
+    }
+    let roots = &roots[..count];
+
+    // Forney as forney_roots_into: Omega below deg sigma, sigma' from the odd coefficients at x^2
+    let mut omega = [0u8; 128];
+    for (i, o) in omega[..num_errors].iter_mut().enumerate() {
+        *o = sigma[..=i].iter().enumerate().fold(0u8, |acc, (j, &sj)| acc ^ gf_mul(syndromes[i - j], sj));
+    }
+    let mut sigma_prime = [0u8; 64];
+    for (p, &s) in sigma_prime.iter_mut().zip(sigma.iter().skip(1).step_by(2)) {
+        *p = s;
+    }
+    let mut magnitudes = [0u8; 128];
+    for (mag, root) in magnitudes.iter_mut().zip(roots) {
+        let sigma_prime_val = poly_eval_at(&sigma_prime[..num_errors.div_ceil(2)], gf_mul(root.x_inv, root.x_inv));
+        if sigma_prime_val != 0 {
+            *mag = gf_mul(root.x, gf_div(poly_eval_at(&omega[..num_errors], root.x_inv), sigma_prime_val));
+        }
+    }
+    let positions = &mut positions[..count];
+    for (p, root) in positions.iter_mut().zip(roots) {
+        *p = root.pos;
+    }
+    let magnitudes = &magnitudes[..count];
+
+    update_syndromes(syndromes, n, positions, magnitudes);
+    if !syndromes_zero(syndromes) {
+        return Err(RsError::VerificationFailed);
+    }
+    for (&pos, &mag) in positions.iter().zip(magnitudes) {
+        codeword[pos] ^= mag;
+    }
+    Ok(count)
+}
+
+/// Berlekamp-Massey over stack arrays; the locator degree never exceeds syndromes.len() <= 254
+fn berlekamp_massey(syndromes: &[u8]) -> [u8; 256] {
+    let mut c = [0u8; 256];
+    let mut b = [0u8; 256];
+    c[0] = 1;
+    b[0] = 1;
+    let mut l = 0usize;
+    let mut m = 1usize;
+    let mut delta_prev = 1u8;
+
+    for r in 0..syndromes.len() {
+        let mut delta = syndromes[r];
+        for i in 1..=l {
+            delta ^= gf_mul(c[i], syndromes[r - i]);
+        }
+        if delta == 0 {
+            m += 1;
+            continue;
+        }
+        let scale = gf_div(delta, delta_prev);
+        let previous = c;
+        for i in 0..c.len() - m {
+            c[i + m] ^= gf_mul(scale, b[i]);
+        }
+        if 2 * l <= r {
+            l = r + 1 - l;
+            b = previous;
+            delta_prev = delta;
+            m = 1;
+        } else {
+            m += 1;
+        }
+    }
+    c
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use crate::rs::{build_generator, decode, encode};
+
+    #[test]
+    fn test_matches_heap_api() {
+        let mut rng = Rng::new(379);
+        let mut generator = [0u8; 256];
+        for trial in 0..3_000 {
+            let n = 2 + rng.below(254);
+            let nsym = 1 + rng.below(n - 1);
+            let message: Vec<u8> = (0..n - nsym).map(|_| rng.next_u64() as u8).collect();
+            generator_into(nsym, &mut generator);
+            assert_eq!(generator[..=nsym], build_generator(nsym)[..]);
+            let mut codeword = vec![0u8; n];
+            encode_into(&message, nsym, &generator, &mut codeword).unwrap();
+            assert_eq!(codeword, encode(&message, nsym, &generator));
+
+            // Up to nsym errors, so failures are compared too
+            for pos in rng.distinct(trial % (nsym + 1), n) {
+                codeword[pos] ^= rng.nonzero_byte();
+            }
+            let received = codeword.clone();
+            let mut positions = [0usize; 127];
+            let in_place = decode_in_place(&mut codeword, nsym, &mut positions[..nsym / 2]);
+            match (&in_place, decode(&received, nsym)) {
+                (Ok(count), Ok((message, expected))) => {
+                    assert_eq!(&codeword[..n - nsym], &message[..]);
+                    assert_eq!(positions[..*count], expected[..]);
+                }
+                (_, expected) => {
+                    assert_eq!(in_place.err(), expected.err(), "n={} nsym={}", n, nsym);
+                    assert_eq!(codeword, received);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_buffer_validation() {
+        let generator = build_generator(4);
+        assert!(matches!(encode_into(b"abc", 4, &generator, &mut [0u8; 8]), Err(RsError::InvalidParameters(_))));
+        assert!(matches!(decode_in_place(&mut [0u8; 12], 4, &mut [0usize; 1]), Err(RsError::InvalidParameters(_))));
+        assert_eq!(decode_in_place(&mut [0u8; 3], 4, &mut [0usize; 2]), Err(RsError::CodewordTooShort));
+    }
+}
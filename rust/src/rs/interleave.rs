@@ -0,0 +1,163 @@
+// Byte interleaving for burst protection
+// `depth` codewords of equal length are striped byte by byte: stream[p * depth + i] is byte p
+// of codeword i, so a contiguous burst of b bytes costs each codeword at most ceil(b / depth)
+// symbols.
+
+use crate::rs::{RsCodec, RsError};
+
+/// Stripes groups of `depth` equal-length blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interleaver {
+    depth: usize,
+}
+
+impl Interleaver {
+    /// Panics if depth is 0
+    pub fn new(depth: usize) -> Self {
+        assert!(depth > 0, "interleaver depth must be at least 1");
+        Self { depth }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Interleave exactly `depth` blocks of one common length
+    pub fn interleave(&self, blocks: &[&[u8]]) -> Vec<u8> {
+        assert_eq!(blocks.len(), self.depth, "expected {} blocks", self.depth);
+        let len = blocks[0].len();
+        assert!(blocks.iter().all(|b| b.len() == len), "interleaved blocks must have equal length");
+        let mut out = vec![0u8; len * self.depth];
+        for (i, block) in blocks.iter().enumerate() {
+            for (p, &byte) in block.iter().enumerate() {
+                out[p * self.depth + i] = byte;
+            }
+        }
+        out
+    }
+
+    /// Inverse of interleave; panics unless data.len() is a multiple of depth
+    pub fn deinterleave(&self, data: &[u8]) -> Vec<Vec<u8>> {
+        assert!(data.len().is_multiple_of(self.depth), "data length must be a multiple of depth");
+        (0..self.depth).map(|i| data.iter().skip(i).step_by(self.depth).copied().collect()).collect()
+    }
+
+    /// Position in the interleaved stream of byte `pos` of block `block`
+    pub fn stream_offset(&self, block: usize, pos: usize) -> usize {
+        pos * self.depth + block
+    }
+}
+
+/// Encode `data` as groups of `depth` interleaved codewords
+/// The data is zero-padded to a whole number of groups (depth * k bytes each); every group
+/// becomes depth * nsize bytes of output.
+pub fn encode_interleaved(data: &[u8], nsym: usize, nsize: usize, depth: usize) -> Result<Vec<u8>, RsError> {
+    let codec = RsCodec::new(nsym, nsize)?;
+    let interleaver = Interleaver::new(depth);
+    let k = codec.k();
+    let mut out = Vec::with_capacity(data.len().div_ceil(k * depth) * depth * nsize);
+    for group in data.chunks(k * depth) {
+        let mut padded = group.to_vec();
+        padded.resize(k * depth, 0);
+        let codewords: Vec<Vec<u8>> = padded.chunks_exact(k).map(|m| codec.encode(m)).collect();
+        let refs: Vec<&[u8]> = codewords.iter().map(|c| c.as_slice()).collect();
+        out.extend(interleaver.interleave(&refs));
+    }
+    Ok(out)
+}
+
+/// Output of decode_interleaved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterleavedDecode {
+    /// The (padded) data, in the order encode_interleaved was given it
+    pub data: Vec<u8>,
+    /// Corrected data bytes as offsets into `data`, ascending
+    pub positions: Vec<usize>,
+    /// Corrected parity bytes as offsets into the stream, ascending; they have no place in `data`
+    pub parity_positions: Vec<usize>,
+}
+
+/// Decode an encode_interleaved stream
+/// A failure reports the index of the first uncorrectable codeword, counted in stream order
+/// (group * depth + block).
+pub fn decode_interleaved(stream: &[u8], nsym: usize, nsize: usize, depth: usize) -> Result<InterleavedDecode, (usize, RsError)> {
+    let codec = RsCodec::new(nsym, nsize).map_err(|e| (0, e))?;
+    let interleaver = Interleaver::new(depth);
+    let (group_len, k) = (depth * nsize, codec.k());
+    if !stream.len().is_multiple_of(group_len) {
+        return Err((stream.len() / nsize, RsError::CodewordTooShort));
+    }
+    let mut out = InterleavedDecode { data: Vec::with_capacity(stream.len() / nsize * k), positions: vec![], parity_positions: vec![] };
+    for (g, group) in stream.chunks_exact(group_len).enumerate() {
+        for (i, codeword) in interleaver.deinterleave(group).iter().enumerate() {
+            let (message, fixed) = codec.decode(codeword).map_err(|e| (g * depth + i, e))?;
+            out.data.extend(message);
+            // Codeword g * depth + i holds data bytes (g * depth + i) * k .. + k, then its parity
+            for p in fixed {
+                if p < k {
+                    out.positions.push((g * depth + i) * k + p);
+                } else {
+                    out.parity_positions.push(g * group_len + interleaver.stream_offset(i, p));
+                }
+            }
+        }
+    }
+    out.positions.sort_unstable();
+    out.parity_positions.sort_unstable();
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interleave_round_trip() {
+        let il = Interleaver::new(3);
+        let blocks: [&[u8]; 3] = [b"abcd", b"efgh", b"ijkl"];
+        let striped = il.interleave(&blocks);
+        assert_eq!(striped, b"aeibfjcgkdhl");
+        assert_eq!(il.deinterleave(&striped), blocks.map(|b| b.to_vec()));
+        assert_eq!(striped[il.stream_offset(1, 2)], b'g');
+    }
+
+    #[test]
+    fn test_burst_spreads_across_codewords() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i % 253) as u8).collect();
+        let mut stream = encode_interleaved(&data, 8, 64, 8).unwrap();
+        assert_eq!(stream.len(), 3 * 8 * 64); // 1000 bytes -> 3 groups of 8 * 56
+        let burst = 600..632; // 32 bytes: 4 per codeword, t = 4
+        for b in &mut stream[burst.clone()] {
+            *b ^= 0xa5;
+        }
+        let out = decode_interleaved(&stream, 8, 64, 8).unwrap();
+        assert_eq!(out.data[..data.len()], data[..]);
+        // Group 1 starts at stream offset 512, so the burst is bytes 11..15 of its codewords, which
+        // are codewords 8..16 overall and hold data bytes c * 56 .. c * 56 + 56 (k = 56)
+        assert_eq!(out.positions, (8..16).flat_map(|c| (11..15).map(move |p| c * 56 + p)).collect::<Vec<_>>());
+        assert!(out.parity_positions.is_empty());
+
+        for b in &mut stream[10..50] {
+            *b ^= 0x11; // 40 bytes: 5 per codeword
+        }
+        assert!(matches!(decode_interleaved(&stream, 8, 64, 8), Err((_, e)) if e != RsError::CodewordTooShort));
+    }
+
+    #[test]
+    fn test_positions_in_data_order() {
+        let (nsym, nsize, depth) = (4, 10, 3);
+        let data: Vec<u8> = (1..=36).collect(); // two groups of three 6-byte messages
+        let mut stream = encode_interleaved(&data, nsym, nsize, depth).unwrap();
+        let il = Interleaver::new(depth);
+        // Data byte d sits in codeword d / 6 at position d % 6; parity byte 8 of codeword 4
+        for d in [0, 7, 17, 30] {
+            stream[(d / 18) * 30 + il.stream_offset(d / 6 % 3, d % 6)] ^= 0x40;
+        }
+        let parity = 30 + il.stream_offset(1, 8);
+        stream[parity] ^= 0x40;
+        let out = decode_interleaved(&stream, nsym, nsize, depth).unwrap();
+        assert_eq!(out.data, data);
+        assert_eq!(out.positions, [0, 7, 17, 30]);
+        assert_eq!(out.parity_positions, [parity]);
+    }
+}
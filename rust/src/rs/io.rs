@@ -0,0 +1,401 @@
+// std::io adapters: RsWriter adds FEC to any Write, RsReader strips and corrects it from any Read
+// Every n-byte codeword's message is [len | payload | zero padding]: the first data byte counts
+// the payload bytes it carries, at most k - 1. Full blocks carry k - 1; flush (and drop) pads a
+// partial block out to a whole codeword, so a flushed stream is readable up to that point
+// without waiting for EOF, as a socket needs. Unlike StreamEncoder's shortened final codeword
+// this costs one byte per block, but the reader never needs to know where the stream ends.
+//
+// Both sides keep their place across short reads and writes and across Interrupted or
+// WouldBlock from the inner stream, so retrying after an error loses and duplicates nothing.
+// An uncorrectable block surfaces as io::ErrorKind::InvalidData with the RsError as its source;
+// the block is consumed and the next read carries on with the next one.
+
+use std::io::{self, Read, Write};
+
+use crate::rs::stream::StreamStats;
+use crate::rs::{RsCodec, RsError};
+
+/// Payload bytes per codeword: k less the length byte
+fn capacity(codec: &RsCodec) -> Result<usize, RsError> {
+    match codec.k() {
+        0 | 1 => Err(RsError::InvalidParameters("RsWriter/RsReader need k >= 2: one data byte of each block is its payload length")),
+        k => Ok(k - 1),
+    }
+}
+
+/// Encodes everything written to it onto `W`, one n-byte codeword per k - 1 payload bytes
+/// Dropping the writer flushes it, ignoring errors; call finish to see them.
+#[derive(Debug)]
+pub struct RsWriter<W: Write> {
+    /// None only once finish has taken it
+    inner: Option<W>,
+    codec: RsCodec,
+    capacity: usize,
+    pending: Vec<u8>,
+    /// An encoded codeword and how much of it the inner writer has accepted
+    out: Vec<u8>,
+    written: usize,
+}
+
+impl<W: Write> RsWriter<W> {
+    /// InvalidParameters if the codec's k is below 2
+    pub fn new(inner: W, codec: RsCodec) -> Result<Self, RsError> {
+        let capacity = capacity(&codec)?;
+        Ok(Self { inner: Some(inner), out: Vec::with_capacity(codec.n()), codec, capacity, pending: Vec::with_capacity(capacity), written: 0 })
+    }
+
+    pub fn codec(&self) -> &RsCodec {
+        &self.codec
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("writer in use")
+    }
+
+    /// The inner writer; writing to it directly corrupts the codeword stream
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.as_mut().expect("writer in use")
+    }
+
+    /// Payload bytes held back until a block fills or the writer is flushed (at most k - 1)
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Flush, including a padded final block, and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.inner.take().expect("writer in use"))
+    }
+
+    /// Hand the rest of the current codeword to the inner writer
+    fn drain(&mut self) -> io::Result<()> {
+        let inner = self.inner.as_mut().expect("writer in use");
+        while self.written < self.out.len() {
+            match inner.write(&self.out[self.written..]) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write the whole codeword")),
+                Ok(n) => self.written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.out.clear();
+        self.written = 0;
+        Ok(())
+    }
+
+    /// Encode the pending payload, padded to a whole block, and write it out
+    fn emit(&mut self) -> io::Result<()> {
+        let mut message = vec![0u8; self.codec.k()];
+        message[0] = self.pending.len() as u8;
+        message[1..=self.pending.len()].copy_from_slice(&self.pending);
+        self.out = self.codec.encode(&message);
+        self.pending.clear();
+        self.drain()
+    }
+}
+
+impl<W: Write> Write for RsWriter<W> {
+    /// Takes what fits in the current block; a full block is written out by the next write or
+    /// flush, so an inner error is returned before any of `buf` is taken
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.drain()?;
+        if self.pending.len() == self.capacity {
+            self.emit()?;
+        }
+        let take = (self.capacity - self.pending.len()).min(buf.len());
+        self.pending.extend_from_slice(&buf[..take]);
+        Ok(take)
+    }
+
+    /// Writes out the pending payload as a padded block, then flushes the inner writer
+    fn flush(&mut self) -> io::Result<()> {
+        self.drain()?;
+        if !self.pending.is_empty() {
+            self.emit()?;
+        }
+        self.get_mut().flush()
+    }
+}
+
+impl<W: Write> Drop for RsWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() && !std::thread::panicking() {
+            let _ = self.flush();
+        }
+    }
+}
+
+/// Reads RsWriter's codewords from `R`, corrects them and serves the payload
+#[derive(Debug)]
+pub struct RsReader<R: Read> {
+    inner: R,
+    codec: RsCodec,
+    capacity: usize,
+    /// The codeword being read and how much of it has arrived
+    block: Vec<u8>,
+    filled: usize,
+    /// Decoded payload of the last block and how much of it has been served
+    payload: Vec<u8>,
+    served: usize,
+    stats: StreamStats,
+    offset: u64,
+}
+
+impl<R: Read> RsReader<R> {
+    /// InvalidParameters if the codec's k is below 2
+    pub fn new(inner: R, codec: RsCodec) -> Result<Self, RsError> {
+        let capacity = capacity(&codec)?;
+        Ok(Self { inner, block: vec![0; codec.n()], codec, capacity, filled: 0, payload: Vec::with_capacity(capacity), served: 0, stats: StreamStats::default(), offset: 0 })
+    }
+
+    pub fn codec(&self) -> &RsCodec {
+        &self.codec
+    }
+
+    /// Blocks read, symbols corrected, and the payload offsets at which a block failed
+    pub fn stats(&self) -> &StreamStats {
+        &self.stats
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read and decode the next codeword into payload; Ok(false) at a clean end of stream
+    fn next_block(&mut self) -> io::Result<bool> {
+        while self.filled < self.block.len() {
+            match self.inner.read(&mut self.block[self.filled..]) {
+                Ok(0) if self.filled == 0 => return Ok(false),
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("stream ends {} bytes into a {}-byte codeword", self.filled, self.block.len()))),
+                Ok(n) => self.filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.filled = 0;
+        self.stats.blocks += 1;
+        let decoded = self.codec.decode(&self.block).and_then(|(message, positions)| match message[0] as usize {
+            len if len <= self.capacity => Ok((message, len, positions.len())),
+            // A length no writer produces: the decoder landed on the wrong codeword
+            _ => Err(RsError::MiscorrectionDetected),
+        });
+        match decoded {
+            Ok((message, len, corrected)) => {
+                self.stats.corrected_symbols += corrected as u64;
+                self.payload.clear();
+                self.payload.extend_from_slice(&message[1..=len]);
+                self.served = 0;
+                self.offset += len as u64;
+                Ok(true)
+            }
+            Err(e) => {
+                self.stats.failed_offsets.push(self.offset);
+                Err(io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for RsReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        // Padded blocks can carry no payload at all (a flush with nothing pending never writes
+        // one, but a stream may be cut anywhere), so keep going until there is some
+        while self.served == self.payload.len() {
+            if !self.next_block()? {
+                return Ok(0);
+            }
+        }
+        let n = (self.payload.len() - self.served).min(buf.len());
+        buf[..n].copy_from_slice(&self.payload[self.served..self.served + n]);
+        self.served += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use std::io::Cursor;
+
+    /// Moves at most `step` bytes per call and fails every third call with `kind`
+    struct Choppy<T> {
+        inner: T,
+        step: usize,
+        calls: usize,
+        kind: io::ErrorKind,
+    }
+
+    impl<T> Choppy<T> {
+        fn new(inner: T, step: usize, kind: io::ErrorKind) -> Self {
+            Self { inner, step, calls: 0, kind }
+        }
+
+        fn hiccup(&mut self) -> io::Result<()> {
+            self.calls += 1;
+            match self.calls % 3 {
+                0 => Err(io::Error::new(self.kind, "hiccup")),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl<T: Write> Write for Choppy<T> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.hiccup()?;
+            self.inner.write(&buf[..buf.len().min(self.step)])
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl<T: Read> Read for Choppy<T> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.hiccup()?;
+            let len = buf.len().min(self.step);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    fn codec() -> RsCodec {
+        RsCodec::new(8, 40).unwrap()
+    }
+
+    fn written(payload: &[u8]) -> Vec<u8> {
+        let mut writer = RsWriter::new(Vec::new(), codec()).unwrap();
+        writer.write_all(payload).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn read_all(stream: &[u8]) -> io::Result<Vec<u8>> {
+        let mut out = vec![];
+        RsReader::new(Cursor::new(stream), codec()).unwrap().read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn test_round_trip_with_corruption() {
+        // 31 payload bytes per 40-byte block; lengths around the boundaries, the last block short
+        let mut rng = Rng::new(412);
+        for len in [0usize, 1, 30, 31, 32, 62, 100, 1000] {
+            let payload: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+            let mut stream = written(&payload);
+            assert_eq!(stream.len(), len.div_ceil(31) * 40);
+            for block in stream.chunks_mut(40) {
+                for pos in rng.distinct(4, 40) {
+                    block[pos] ^= rng.nonzero_byte();
+                }
+            }
+            let mut reader = RsReader::new(Cursor::new(&stream), codec()).unwrap();
+            let mut out = vec![];
+            reader.read_to_end(&mut out).unwrap();
+            assert_eq!(out, payload, "len={}", len);
+            assert_eq!(reader.stats().corrected_symbols, 4 * reader.stats().blocks);
+        }
+    }
+
+    #[test]
+    fn test_flush_mid_stream() {
+        // Each flush pads a block, readable before the writer is done
+        let mut writer = RsWriter::new(Vec::new(), codec()).unwrap();
+        writer.write_all(b"hello").unwrap();
+        writer.flush().unwrap();
+        assert_eq!((writer.get_ref().len(), writer.pending()), (40, 0));
+        assert_eq!(read_all(writer.get_ref()).unwrap(), b"hello");
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref().len(), 40);
+        writer.write_all(b", world").unwrap();
+        let stream = writer.finish().unwrap();
+        assert_eq!(read_all(&stream).unwrap(), b"hello, world");
+        // Drop flushes too
+        let mut stream = vec![];
+        RsWriter::new(&mut stream, codec()).unwrap().write_all(b"dropped").unwrap();
+        assert_eq!(read_all(&stream).unwrap(), b"dropped");
+    }
+
+    #[test]
+    fn test_partial_reads_and_writes() {
+        // Short transfers and Interrupted are absorbed; WouldBlock is returned and the call
+        // retried, the way a nonblocking caller would
+        let payload: Vec<u8> = (0..500).map(|i| (i * 7) as u8).collect();
+        let retry = |f: &mut dyn FnMut() -> io::Result<usize>| loop {
+            match f() {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                other => break other.unwrap(),
+            }
+        };
+        for kind in [io::ErrorKind::Interrupted, io::ErrorKind::WouldBlock] {
+            let mut writer = RsWriter::new(Choppy::new(Vec::new(), 7, kind), codec()).unwrap();
+            let mut rest = &payload[..];
+            while !rest.is_empty() {
+                let n = retry(&mut || writer.write(rest));
+                rest = &rest[n..];
+            }
+            retry(&mut || writer.flush().map(|()| 0));
+            let stream = writer.finish().unwrap().inner;
+            assert_eq!(stream, written(&payload), "{:?}", kind);
+
+            let mut reader = RsReader::new(Choppy::new(Cursor::new(&stream), 5, kind), codec()).unwrap();
+            let mut out = vec![];
+            let mut buf = [0u8; 13];
+            loop {
+                match retry(&mut || reader.read(&mut buf)) {
+                    0 => break,
+                    n => out.extend_from_slice(&buf[..n]),
+                }
+            }
+            assert_eq!(out, payload, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn test_uncorrectable_block() {
+        let payload: Vec<u8> = (0..93).collect();
+        let mut stream = written(&payload);
+        stream[40..45].iter_mut().for_each(|b| *b ^= 0x5a);
+        let mut reader = RsReader::new(Cursor::new(&stream), codec()).unwrap();
+        let mut first = [0u8; 31];
+        reader.read_exact(&mut first).unwrap();
+        let err = reader.read(&mut [0u8; 8]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let source = err.get_ref().and_then(|e| e.downcast_ref::<RsError>()).expect("RsError source");
+        assert!(matches!(source, RsError::TooManyErrors | RsError::ChienSearchFailed | RsError::VerificationFailed | RsError::TrivialLocator), "{:?}", source);
+        assert_eq!(reader.stats().failed_offsets, [31]);
+        // The bad block is skipped and reading carries on
+        let mut rest = vec![];
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, &payload[62..]);
+    }
+
+    #[test]
+    fn test_truncated_and_misuse() {
+        let stream = written(&[1; 40]);
+        let err = read_all(&stream[..60]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        // A valid codeword whose length byte says more than a block holds
+        let mut message = vec![0u8; 32];
+        message[0] = 32;
+        let err = read_all(&codec().encode(&message)).unwrap_err();
+        assert_eq!(err.get_ref().and_then(|e| e.downcast_ref::<RsError>()), Some(&RsError::MiscorrectionDetected));
+        let k1 = RsCodec::new(8, 9).unwrap();
+        assert!(matches!(RsWriter::new(Vec::new(), k1.clone()), Err(RsError::InvalidParameters(_))));
+        assert!(matches!(RsReader::new(Cursor::new(vec![]), k1), Err(RsError::InvalidParameters(_))));
+    }
+}
@@ -0,0 +1,147 @@
+// Bounded-radius list decoding past t = nsym/2
+// A codeword at distance e > t from the received word is found by the errors-and-erasures decoder
+// once h = 2e - nsym of its error positions are flagged as erasures (leaving 2(e - h) + h = nsym).
+// So for each e in t+1..=radius we try every h-subset of positions as an erasure hypothesis and
+// keep every distinct codeword that lands within the radius.
+//
+// Complexity: sum over e of C(n, 2e - nsym) errata decodes, each O(n * nsym). Radius t+1 costs
+// C(n, 1) or C(n, 2) decodes; each further step multiplies that by roughly n^2, so the total is
+// capped at MAX_LIST_HYPOTHESES.
+
+use std::collections::BTreeMap;
+
+use crate::rs::verify::Combinations;
+use crate::rs::{build_generator, decode, decode_errata, encode};
+
+/// Upper bound on erasure hypotheses tried by `decode_list`
+pub const MAX_LIST_HYPOTHESES: u64 = 1 << 20;
+
+/// One codeword within the list-decoding radius
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub message: Vec<u8>,
+    /// Positions where the candidate codeword differs from the received word, ascending
+    pub positions: Vec<usize>,
+    /// Hamming distance from the received word (= positions.len())
+    pub distance: usize,
+}
+
+/// All codewords within `radius` symbols of `codeword`, sorted by distance then message
+/// Radius is clamped to nsym, and further reduced to the largest radius whose hypothesis count
+/// fits in MAX_LIST_HYPOTHESES, so the result is complete only up to the radius actually searched.
+pub fn decode_list(codeword: &[u8], nsym: usize, radius: usize) -> Vec<Candidate> {
+    let n = codeword.len();
+    if n <= nsym {
+        return vec![];
+    }
+    let t = nsym / 2;
+    let radius = searchable_radius(n, nsym, radius.min(nsym));
+    let generator = build_generator(nsym);
+
+    // Keyed by message, which identifies the codeword
+    let mut found: BTreeMap<Vec<u8>, Candidate> = BTreeMap::new();
+    let mut consider = |message: Vec<u8>| {
+        if found.contains_key(&message) {
+            return;
+        }
+        let candidate = encode(&message, nsym, &generator);
+        let positions: Vec<usize> = (0..n).filter(|&i| candidate[i] != codeword[i]).collect();
+        if positions.len() <= radius {
+            let distance = positions.len();
+            found.insert(message.clone(), Candidate { message, positions, distance });
+        }
+    };
+
+    if let Ok((message, _)) = decode(codeword, nsym) {
+        consider(message);
+    }
+    for e in t + 1..=radius {
+        for erasures in Combinations::new(n, 2 * e - nsym) {
+            if let Ok((message, _)) = decode_errata(codeword, nsym, &erasures) {
+                consider(message);
+            }
+        }
+    }
+
+    let mut candidates: Vec<Candidate> = found.into_values().collect();
+    candidates.sort_by(|a, b| a.distance.cmp(&b.distance).then_with(|| a.message.cmp(&b.message)));
+    candidates
+}
+
+/// Largest radius <= `radius` whose total hypothesis count stays within the cap
+fn searchable_radius(n: usize, nsym: usize, radius: usize) -> usize {
+    let t = nsym / 2;
+    let mut total = 0u64;
+    for e in t + 1..=radius {
+        total = total.saturating_add(binomial(n, 2 * e - nsym));
+        if total > MAX_LIST_HYPOTHESES {
+            return e - 1;
+        }
+    }
+    radius
+}
+
+fn binomial(n: usize, r: usize) -> u64 {
+    let r = r.min(n - r) as u64;
+    let mut acc = 1u64;
+    for i in 0..r {
+        acc = acc.saturating_mul(n as u64 - i) / (i + 1);
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_within_t_is_unique() {
+        let nsym = 4;
+        let message = b"list decode".to_vec();
+        let mut codeword = encode(&message, nsym, &build_generator(nsym));
+        codeword[2] ^= 0x11;
+        let list = decode_list(&codeword, nsym, 2);
+        assert_eq!(list, vec![Candidate { message, positions: vec![2], distance: 1 }]);
+    }
+
+    #[test]
+    fn test_equidistant_received_word_lists_both() {
+        let nsym = 4;
+        let generator = build_generator(nsym);
+        let c1 = encode(b"equidistant", nsym, &generator);
+        let n = c1.len();
+        // The codeword of message 0...01 is g(x) itself: weight nsym + 1 = 5 in the last 5 symbols
+        let mut unit = vec![0u8; n - nsym];
+        unit[n - nsym - 1] = 1;
+        let w = encode(&unit, nsym, &generator);
+        let c2: Vec<u8> = c1.iter().zip(&w).map(|(a, b)| a ^ b).collect();
+
+        // Agree with c2 on two of w's positions, with c1 on two, and with neither on the fifth
+        let mut received = c1.clone();
+        received[n - 5] ^= w[n - 5];
+        received[n - 4] ^= w[n - 4];
+        received[n - 1] ^= w[n - 1] ^ 0x01;
+        let dist = |c: &[u8]| c.iter().zip(&received).filter(|(a, b)| a != b).count();
+        assert_eq!((dist(&c1), dist(&c2)), (3, 3));
+
+        assert!(decode(&received, nsym).map(|(m, _)| m) != Ok(c1[..n - nsym].to_vec()));
+        assert!(decode_list(&received, nsym, 2).is_empty());
+
+        let list = decode_list(&received, nsym, 3);
+        for c in [&c1, &c2] {
+            let candidate = list.iter().find(|cand| cand.message == c[..n - nsym]).expect("codeword listed");
+            assert_eq!(candidate.distance, 3);
+            assert_eq!(candidate.positions, (0..n).filter(|&i| c[i] != received[i]).collect::<Vec<_>>());
+        }
+        assert!(list.windows(2).all(|w| w[0].distance <= w[1].distance));
+        assert!(list.iter().all(|cand| cand.distance <= 3));
+    }
+
+    #[test]
+    fn test_work_cap() {
+        assert_eq!(searchable_radius(255, 4, 3), 3);
+        // C(255, 4) alone exceeds the cap
+        assert_eq!(searchable_radius(255, 4, 4), 3);
+        assert_eq!(binomial(255, 2), 32385);
+    }
+}
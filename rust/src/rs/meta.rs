@@ -0,0 +1,159 @@
+// Metadata bits (poison, tags) carried in a reserved symbol of each codeword
+// Placement, fixed for compatibility: codeword symbol 0 is the metadata symbol. Metadata bit i is
+// bit i of that byte, for i < bits; the bits above are reserved and always written as zero. The
+// message follows in symbols 1..k and the nsym parity symbols end the codeword, as usual.
+// The metadata symbol is an ordinary RS symbol, so errors in it are corrected or detected like any
+// other and t stays nsym / 2; what the metadata costs is one data byte per codeword. The reserved
+// bits buy some detection back: a decode that leaves one of them set has landed on a codeword
+// that was never written, and is reported as MiscorrectionDetected.
+
+use crate::rs::{RsCodec, RsError};
+
+/// Codeword position of the metadata symbol
+pub const META_SYMBOL: usize = 0;
+
+/// An RsCodec with `bits` metadata bits reserved in symbol 0
+#[derive(Debug, Clone)]
+pub struct MetaCodec {
+    codec: RsCodec,
+    bits: usize,
+}
+
+impl MetaCodec {
+    /// Reserve 1..=8 metadata bits; the codec must leave at least one data byte (k >= 2)
+    pub fn new(codec: RsCodec, bits: usize) -> Result<Self, RsError> {
+        if !(1..=8).contains(&bits) {
+            return Err(RsError::InvalidParameters("metadata bits must be 1..=8"));
+        }
+        if codec.k() < 2 {
+            return Err(RsError::InvalidParameters("metadata symbol needs k >= 2"));
+        }
+        Ok(Self { codec, bits })
+    }
+
+    pub fn codec(&self) -> &RsCodec {
+        &self.codec
+    }
+
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Data bytes per codeword, k - 1
+    pub fn data_len(&self) -> usize {
+        self.codec.k() - 1
+    }
+
+    /// Reserved bits of the metadata symbol, zero in every codeword written
+    pub fn reserved_mask(&self) -> u8 {
+        !((1u16 << self.bits) - 1) as u8
+    }
+
+    /// Encode message with metadata; panics unless message is data_len bytes and meta fits in bits
+    pub fn encode_with_meta(&self, message: &[u8], meta: u8) -> Vec<u8> {
+        assert_eq!(message.len(), self.data_len(), "message must be k - 1 = {} bytes", self.data_len());
+        assert_eq!(meta & self.reserved_mask(), 0, "metadata must fit in {} bits", self.bits);
+        let mut frame = Vec::with_capacity(self.codec.k());
+        frame.push(meta);
+        frame.extend_from_slice(message);
+        self.codec.encode(&frame)
+    }
+
+    /// Data, metadata and corrected positions (position 0 is the metadata symbol)
+    pub fn decode_with_meta(&self, codeword: &[u8]) -> Result<(Vec<u8>, u8, Vec<usize>), RsError> {
+        self.split(self.codec.decode(codeword)?)
+    }
+
+    /// decode_with_meta with known erasures
+    pub fn decode_errata_with_meta(&self, codeword: &[u8], erasures: &[usize]) -> Result<(Vec<u8>, u8, Vec<usize>), RsError> {
+        self.split(self.codec.decode_errata(codeword, erasures)?)
+    }
+
+    fn split(&self, (mut frame, positions): (Vec<u8>, Vec<usize>)) -> Result<(Vec<u8>, u8, Vec<usize>), RsError> {
+        let meta = frame.remove(META_SYMBOL);
+        if meta & self.reserved_mask() != 0 {
+            return Err(RsError::MiscorrectionDetected);
+        }
+        Ok((frame, meta, positions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    #[test]
+    fn test_placement() {
+        let codec = MetaCodec::new(RsCodec::new(4, 12).unwrap(), 2).unwrap();
+        assert_eq!((codec.data_len(), codec.reserved_mask()), (7, 0xfc));
+        let message = [1, 2, 3, 4, 5, 6, 7];
+        let codeword = codec.encode_with_meta(&message, 0b10);
+        assert_eq!(codeword, RsCodec::new(4, 12).unwrap().encode(&[0b10, 1, 2, 3, 4, 5, 6, 7]));
+        assert_eq!(codec.decode_with_meta(&codeword), Ok((message.to_vec(), 0b10, vec![])));
+        assert_eq!(MetaCodec::new(RsCodec::new(8, 255).unwrap(), 8).unwrap().reserved_mask(), 0);
+        assert!(MetaCodec::new(RsCodec::new(4, 12).unwrap(), 0).is_err());
+        assert!(MetaCodec::new(RsCodec::new(4, 12).unwrap(), 9).is_err());
+        assert!(MetaCodec::new(RsCodec::new(4, 5).unwrap(), 1).is_err());
+    }
+
+    #[test]
+    fn test_round_trip_through_errors() {
+        // t errors and nsym - 2e erasures, with the metadata symbol always among the hits
+        let codec = MetaCodec::new(RsCodec::new(8, 40).unwrap(), 3).unwrap();
+        let mut rng = Rng::new(399);
+        for errors in 0..=4 {
+            let erasures = 8 - 2 * errors;
+            for trial in 0..200 {
+                let message: Vec<u8> = (0..codec.data_len()).map(|_| rng.next_u64() as u8).collect();
+                let meta = rng.below(8) as u8;
+                let codeword = codec.encode_with_meta(&message, meta);
+                let mut positions = vec![META_SYMBOL];
+                positions.extend(rng.distinct(errors + erasures - 1, 39).into_iter().map(|p| p + 1));
+                // Alternate whether the metadata symbol is one of the errors or one of the erasures
+                if trial % 2 == 1 {
+                    positions.rotate_left(1);
+                }
+                let mut received = codeword.clone();
+                for &p in &positions {
+                    received[p] ^= rng.nonzero_byte();
+                }
+                let (data, got, mut corrected) = codec.decode_errata_with_meta(&received, &positions[errors..]).unwrap();
+                corrected.sort_unstable();
+                positions.sort_unstable();
+                assert_eq!((data, got, corrected), (message, meta, positions), "{} errors", errors);
+            }
+        }
+        // A lone flipped metadata bit is an ordinary symbol error
+        let message = vec![0x5a; codec.data_len()];
+        let mut received = codec.encode_with_meta(&message, 0b101);
+        received[META_SYMBOL] ^= 0b001;
+        assert_eq!(codec.decode_with_meta(&received), Ok((message, 0b101, vec![META_SYMBOL])));
+    }
+
+    #[test]
+    fn test_reserved_bits_catch_miscorrections() {
+        // Two errors against t = 1 often decode to another codeword; with one metadata bit, those
+        // whose result has a reserved bit set are reported instead of returned
+        let codec = MetaCodec::new(RsCodec::new(2, 18).unwrap(), 1).unwrap();
+        let mut rng = Rng::new(0x3e7a);
+        let (mut caught, mut miscorrected) = (0, 0);
+        for _ in 0..20_000 {
+            let message: Vec<u8> = (0..codec.data_len()).map(|_| rng.next_u64() as u8).collect();
+            let codeword = codec.encode_with_meta(&message, rng.below(2) as u8);
+            let mut received = codeword.clone();
+            for p in rng.distinct(2, 18) {
+                received[p] ^= rng.nonzero_byte();
+            }
+            match (codec.codec().decode(&received), codec.decode_with_meta(&received)) {
+                (Ok((frame, _)), Err(RsError::MiscorrectionDetected)) => {
+                    assert_ne!(frame[META_SYMBOL] & codec.reserved_mask(), 0);
+                    caught += 1;
+                }
+                (Ok((frame, _)), Ok(_)) if frame != codeword[..codec.codec().k()] => miscorrected += 1,
+                (plain, meta) => assert_eq!(plain.is_ok(), meta.is_ok()),
+            }
+        }
+        assert!(caught > 0 && miscorrected > 0, "caught {} missed {}", caught, miscorrected);
+    }
+}
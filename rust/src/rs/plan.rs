@@ -0,0 +1,152 @@
+// Parity planning: the smallest nsym whose block failure probability meets a target
+// Model: each codeword symbol is corrupted independently with probability p, and a codeword
+// fails when more than t = nsym / 2 symbols are hit, so P(fail) is the binomial tail P(X > t).
+// Terms are summed in the log domain so rates down to ~1e-300 keep full relative precision.
+
+use crate::rs::{validate_params, RsError};
+
+/// Error bursts of `len` consecutive channel symbols, striped over `depth` interleaved codewords
+/// Each burst then hits ceil(len / depth) symbols of every codeword it touches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Burst {
+    pub len: usize,
+    pub depth: usize,
+}
+
+impl Burst {
+    /// Independent symbol errors, the default model
+    pub const NONE: Burst = Burst { len: 1, depth: 1 };
+
+    /// Symbols of one codeword corrupted by a single burst
+    pub fn symbols_per_codeword(&self) -> usize {
+        self.len.div_ceil(self.depth)
+    }
+}
+
+/// Outcome of plan(); `candidates` lists (nsym, block failure rate) for every nsym tried
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub nsize: usize,
+    pub nsym: usize,
+    pub block_failure_rate: f64,
+    pub candidates: Vec<(usize, f64)>,
+}
+
+impl Plan {
+    /// Correctable symbol errors per codeword
+    pub fn t(&self) -> usize {
+        self.nsym / 2
+    }
+
+    /// k / n
+    pub fn code_rate(&self) -> f64 {
+        (self.nsize - self.nsym) as f64 / self.nsize as f64
+    }
+
+    /// Parity bytes per message byte, nsym / k
+    pub fn overhead(&self) -> f64 {
+        self.nsym as f64 / (self.nsize - self.nsym) as f64
+    }
+}
+
+/// P(X > t) for X ~ Binomial(n, p)
+pub fn binomial_tail(n: usize, t: usize, p: f64) -> f64 {
+    if t >= n || p <= 0.0 {
+        return 0.0;
+    }
+    if p >= 1.0 {
+        return 1.0;
+    }
+    let (ln_p, ln_q) = (p.ln(), (-p).ln_1p());
+    // ln C(n, i) built up incrementally; terms i = t+1..=n
+    let mut ln_binom = 0.0;
+    let mut terms = Vec::with_capacity(n - t);
+    for i in 1..=n {
+        ln_binom += ((n - i + 1) as f64).ln() - (i as f64).ln();
+        if i > t {
+            terms.push(ln_binom + i as f64 * ln_p + (n - i) as f64 * ln_q);
+        }
+    }
+    let max = terms.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let sum: f64 = terms.iter().map(|&x| (x - max).exp()).sum();
+    (max + sum.ln()).exp().min(1.0)
+}
+
+/// Block failure probability of an (nsize, nsym) codeword at symbol error rate p
+/// With bursts, burst hits per codeword are modelled as Binomial(nsize, p / s), each hit
+/// corrupting s = burst.symbols_per_codeword() symbols, which keeps the mean symbol error rate p.
+pub fn block_failure_rate(nsize: usize, nsym: usize, p: f64, burst: Burst) -> f64 {
+    let s = burst.symbols_per_codeword();
+    binomial_tail(nsize, nsym / 2 / s, p / s as f64)
+}
+
+/// Smallest nsym in 1..nsize with block_failure_rate <= target
+/// Fails with InvalidParameters for p outside 0..=1, target outside (0, 1], a zero burst length
+/// or depth, or when even nsym = nsize - 1 misses the target.
+pub fn plan(nsize: usize, p: f64, target: f64, burst: Burst) -> Result<Plan, RsError> {
+    validate_params(1, nsize)?;
+    if !(0.0..=1.0).contains(&p) {
+        return Err(RsError::InvalidParameters("symbol error rate must be in 0..=1"));
+    }
+    if !(target > 0.0 && target <= 1.0) {
+        return Err(RsError::InvalidParameters("target block failure rate must be in (0, 1]"));
+    }
+    if burst.len == 0 || burst.depth == 0 {
+        return Err(RsError::InvalidParameters("burst length and interleaving depth must be at least 1"));
+    }
+    let mut candidates = Vec::new();
+    for nsym in 1..nsize {
+        let rate = block_failure_rate(nsize, nsym, p, burst);
+        candidates.push((nsym, rate));
+        if rate <= target {
+            return Ok(Plan { nsize, nsym, block_failure_rate: rate, candidates });
+        }
+    }
+    Err(RsError::InvalidParameters("no nsym < nsize reaches the target block failure rate"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-12 * b.abs()
+    }
+
+    #[test]
+    fn test_binomial_tail_known_values() {
+        // Exact values from rational arithmetic
+        assert!(close(binomial_tail(15, 2, 0.1), 0.184061069106391));
+        assert!(close(binomial_tail(255, 0, 1e-3), 0.2251823635029947));
+        assert!(close(binomial_tail(255, 16, 1e-2), 1.400228694454406e-09));
+        assert!(close(binomial_tail(255, 8, 1e-5), 1.0867561732906995e-29));
+        assert_eq!(binomial_tail(10, 10, 0.5), 0.0);
+        assert_eq!(binomial_tail(10, 3, 0.0), 0.0);
+        assert_eq!(binomial_tail(10, 3, 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_plan_smallest_nsym() {
+        let result = plan(255, 1e-2, 1e-9, Burst::NONE).unwrap();
+        assert_eq!(result.nsym, 34);
+        assert!(result.block_failure_rate <= 1e-9);
+        assert!(result.candidates[result.nsym - 2].1 > 1e-9);
+        assert_eq!(result.candidates.len(), result.nsym);
+        assert!(result.candidates.windows(2).all(|w| w[1].1 <= w[0].1));
+        assert!(close(result.code_rate(), 221.0 / 255.0));
+
+        assert_eq!(plan(255, 0.0, 1e-12, Burst::NONE).unwrap().nsym, 1);
+        assert!(plan(255, 0.9, 1e-3, Burst::NONE).is_err());
+        assert!(plan(255, 1e-3, 0.0, Burst::NONE).is_err());
+        assert!(plan(256, 1e-3, 1e-3, Burst::NONE).is_err());
+    }
+
+    #[test]
+    fn test_interleaving_undoes_bursts() {
+        let bursty = plan(255, 1e-3, 1e-12, Burst { len: 8, depth: 1 }).unwrap();
+        let spread = plan(255, 1e-3, 1e-12, Burst { len: 8, depth: 8 }).unwrap();
+        assert_eq!(spread.nsym, plan(255, 1e-3, 1e-12, Burst::NONE).unwrap().nsym);
+        assert!(bursty.nsym > spread.nsym);
+        assert_eq!(bursty.t() % 8, 0);
+    }
+}
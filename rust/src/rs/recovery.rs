@@ -0,0 +1,409 @@
+// PAR-style recovery sidecars: parity for an existing file, kept beside it in an .ecc file
+// The data file is cut into block_size-byte blocks, the last zero-padded for coding. Block i
+// belongs to stripe i % S at position i / S, so a contiguous damaged region is spread over the
+// stripes; each stripe's k data blocks get m parity blocks from rs::ShardCodec, m / k being
+// about the requested redundancy. The sidecar records every data block's offset and SHA-256 and
+// every parity block with its SHA-256. repair hashes the data file, treats each block whose hash
+// mismatches as an erasure and rebuilds it from the intact blocks of its stripe, so a stripe
+// survives any m damaged blocks, parity blocks included. Only rebuilt blocks are written back.
+//
+// Sidecar layout, integers little-endian:
+//   header      magic "ECRV", version, 3 zero bytes, block_size u32, data_len u64, stripes u32,
+//               k u16, m u16, CRC-32 of the preceding 28 bytes
+//   data table  per data block: offset u64, SHA-256
+//   parity      per stripe, per parity block: SHA-256, block_size bytes
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::crc::crc32;
+use crate::rs::file::{read_full, FileError};
+use crate::rs::{RsError, ShardCodec};
+use crate::sha256::sha256;
+
+pub const MAGIC: [u8; 4] = *b"ECRV";
+pub const VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 32;
+/// Block size of create: fine enough that small damage costs little parity, coarse enough that
+/// the 40-byte table entries stay under 1% of the data
+pub const DEFAULT_BLOCK_SIZE: usize = 4096;
+const HASH_LEN: usize = 32;
+const ENTRY_LEN: usize = 8 + HASH_LEN;
+
+/// How a data file is cut into blocks and stripes, as recorded in the sidecar header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Layout {
+    pub block_size: usize,
+    pub data_len: u64,
+    pub stripes: u64,
+    /// Data blocks per stripe; positions past the last block of the file count as zero blocks
+    pub data_blocks: usize,
+    pub parity_blocks: usize,
+}
+
+impl Layout {
+    /// The fewest stripes of at most 255 blocks that give each stripe at least
+    /// `redundancy_percent` parity
+    pub fn new(data_len: u64, block_size: usize, redundancy_percent: u32) -> Result<Self, FileError> {
+        if block_size == 0 || block_size > u32::MAX as usize {
+            return Err(FileError::Params(RsError::InvalidParameters("recovery block size must be in 1..=u32::MAX")));
+        }
+        if redundancy_percent == 0 {
+            return Err(FileError::Params(RsError::InvalidParameters("redundancy_percent must be at least 1")));
+        }
+        let parity_for = |k: usize| (k as u64 * redundancy_percent as u64).div_ceil(100) as usize;
+        let max_k = (1..255).rev().find(|&k| k + parity_for(k) <= 255);
+        let max_k = max_k.ok_or(FileError::Params(RsError::InvalidParameters("redundancy_percent leaves no room for data in a 255-block stripe")))?;
+        let blocks = data_len.div_ceil(block_size as u64);
+        let stripes = blocks.div_ceil(max_k as u64);
+        let data_blocks = if stripes == 0 { 1 } else { blocks.div_ceil(stripes) as usize };
+        Ok(Self { block_size, data_len, stripes, data_blocks, parity_blocks: parity_for(data_blocks) })
+    }
+
+    /// Data blocks in the file
+    pub fn blocks(&self) -> u64 {
+        self.data_len.div_ceil(self.block_size as u64)
+    }
+
+    /// Index of the data block at `position` of `stripe`, None for the zero blocks past the end
+    fn block_at(&self, stripe: u64, position: usize) -> Option<u64> {
+        Some(position as u64 * self.stripes + stripe).filter(|&i| i < self.blocks())
+    }
+
+    /// Bytes of data block `index` in the file; only the last block is short
+    fn block_len(&self, index: u64) -> usize {
+        (self.data_len - index * self.block_size as u64).min(self.block_size as u64) as usize
+    }
+
+    fn parity_offset(&self, stripe: u64, j: usize) -> u64 {
+        let parity_start = HEADER_LEN as u64 + self.blocks() * ENTRY_LEN as u64;
+        parity_start + (stripe * self.parity_blocks as u64 + j as u64) * (HASH_LEN + self.block_size) as u64
+    }
+
+    fn to_bytes(self) -> [u8; HEADER_LEN] {
+        let mut out = [0u8; HEADER_LEN];
+        out[..4].copy_from_slice(&MAGIC);
+        out[4] = VERSION;
+        out[8..12].copy_from_slice(&(self.block_size as u32).to_le_bytes());
+        out[12..20].copy_from_slice(&self.data_len.to_le_bytes());
+        out[20..24].copy_from_slice(&(self.stripes as u32).to_le_bytes());
+        out[24..26].copy_from_slice(&(self.data_blocks as u16).to_le_bytes());
+        out[26..28].copy_from_slice(&(self.parity_blocks as u16).to_le_bytes());
+        let crc = crc32(&out[..28]);
+        out[28..].copy_from_slice(&crc.to_le_bytes());
+        out
+    }
+
+    fn parse(bytes: &[u8; HEADER_LEN]) -> Result<Self, FileError> {
+        if bytes[..4] != MAGIC {
+            return Err(FileError::BadHeader("not a recovery file (bad magic)"));
+        }
+        if crc32(&bytes[..28]).to_le_bytes() != bytes[28..] {
+            return Err(FileError::BadHeader("recovery file header fails its checksum"));
+        }
+        if bytes[4] != VERSION {
+            return Err(FileError::BadHeader("unsupported recovery file version"));
+        }
+        let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize;
+        let u32_at = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+        let mut len = [0u8; 8];
+        len.copy_from_slice(&bytes[12..20]);
+        let layout = Self {
+            block_size: u32_at(8) as usize,
+            data_len: u64::from_le_bytes(len),
+            stripes: u32_at(20) as u64,
+            data_blocks: u16_at(24),
+            parity_blocks: u16_at(26),
+        };
+        let fits = layout.block_size > 0 && layout.data_blocks + layout.parity_blocks <= 255 && layout.stripes * layout.data_blocks as u64 >= layout.blocks();
+        if !fits || ShardCodec::new(layout.data_blocks, layout.parity_blocks).is_err() {
+            return Err(FileError::BadHeader("recovery file header describes an impossible layout"));
+        }
+        Ok(layout)
+    }
+}
+
+/// Outcome of repair
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct RecoveryReport {
+    pub blocks: u64,
+    /// Data blocks whose hash mismatched and that were rebuilt and written back
+    pub repaired: Vec<u64>,
+    /// Damaged data blocks left as they were: their stripe lost more blocks than it has parity
+    pub unrecoverable: Vec<u64>,
+    /// Sidecar parity blocks failing their hash, among the stripes that needed repair
+    pub damaged_parity: u64,
+}
+
+/// Block `index` of the data file into buf, zero-padded past the end of the file
+fn read_block(data: &mut File, layout: &Layout, index: u64, buf: &mut [u8]) -> Result<(), FileError> {
+    let len = layout.block_len(index);
+    data.seek(SeekFrom::Start(index * layout.block_size as u64))?;
+    if read_full(data, &mut buf[..len])? != len {
+        return Err(FileError::LengthChanged { expected: layout.data_len, actual: data.metadata()?.len() });
+    }
+    buf[len..].fill(0);
+    Ok(())
+}
+
+/// create with DEFAULT_BLOCK_SIZE
+pub fn create(data_path: &Path, ecc_path: &Path, redundancy_percent: u32) -> Result<Layout, FileError> {
+    create_with_block_size(data_path, ecc_path, redundancy_percent, DEFAULT_BLOCK_SIZE)
+}
+
+/// Write a sidecar for `data_path` to `ecc_path`, leaving the data file untouched
+/// Every stripe gets at least `redundancy_percent` parity, so repair restores any damage up to
+/// that share of each stripe's blocks; one more block per stripe may be lost if the parity
+/// rounded up. Returns the layout written.
+pub fn create_with_block_size(data_path: &Path, ecc_path: &Path, redundancy_percent: u32, block_size: usize) -> Result<Layout, FileError> {
+    let mut data = File::open(data_path)?;
+    let layout = Layout::new(data.metadata()?.len(), block_size, redundancy_percent)?;
+    let codec = ShardCodec::new(layout.data_blocks, layout.parity_blocks).map_err(FileError::Params)?;
+    let mut out = BufWriter::new(File::create(ecc_path)?);
+    out.write_all(&layout.to_bytes())?;
+
+    let mut block = vec![0u8; block_size];
+    let mut reader = BufReader::new(&data);
+    for index in 0..layout.blocks() {
+        let len = layout.block_len(index);
+        if read_full(&mut reader, &mut block[..len])? != len {
+            return Err(FileError::LengthChanged { expected: layout.data_len, actual: data.metadata()?.len() });
+        }
+        out.write_all(&(index * block_size as u64).to_le_bytes())?;
+        out.write_all(&sha256(&block[..len]))?;
+    }
+
+    let mut stripe = vec![0u8; layout.data_blocks * block_size];
+    for s in 0..layout.stripes {
+        for (position, buf) in stripe.chunks_exact_mut(block_size).enumerate() {
+            match layout.block_at(s, position) {
+                Some(index) => read_block(&mut data, &layout, index, buf)?,
+                None => buf.fill(0),
+            }
+        }
+        let shards: Vec<&[u8]> = stripe.chunks_exact(block_size).collect();
+        for parity in codec.encode(&shards) {
+            out.write_all(&sha256(&parity))?;
+            out.write_all(&parity)?;
+        }
+    }
+    out.flush()?;
+    Ok(layout)
+}
+
+/// Restore every damaged block of `data_path` that `ecc_path` can, writing it back in place
+/// Fails with LengthChanged, touching nothing, when the data file is no longer the length the
+/// sidecar was made for.
+pub fn repair(data_path: &Path, ecc_path: &Path) -> Result<RecoveryReport, FileError> {
+    let mut ecc = BufReader::new(File::open(ecc_path)?);
+    let mut raw = [0u8; HEADER_LEN];
+    if read_full(&mut ecc, &mut raw)? != HEADER_LEN {
+        return Err(FileError::BadHeader("file too short for a recovery header"));
+    }
+    let layout = Layout::parse(&raw)?;
+    let mut data = OpenOptions::new().read(true).write(true).open(data_path)?;
+    let actual = data.metadata()?.len();
+    if actual != layout.data_len {
+        return Err(FileError::LengthChanged { expected: layout.data_len, actual });
+    }
+    let codec = ShardCodec::new(layout.data_blocks, layout.parity_blocks).map_err(FileError::Params)?;
+    let block_size = layout.block_size;
+
+    // Hash pass: the sidecar table and the data file side by side
+    let mut hashes = Vec::with_capacity(layout.blocks() as usize);
+    let mut damaged = vec![];
+    let (mut entry, mut block) = ([0u8; ENTRY_LEN], vec![0u8; block_size]);
+    let mut reader = BufReader::new(&data);
+    for index in 0..layout.blocks() {
+        if read_full(&mut ecc, &mut entry)? != ENTRY_LEN {
+            return Err(FileError::Truncated);
+        }
+        if entry[..8] != (index * block_size as u64).to_le_bytes() {
+            return Err(FileError::BadHeader("recovery table entry has the wrong block offset"));
+        }
+        let len = layout.block_len(index);
+        read_full(&mut reader, &mut block[..len])?;
+        let mut hash = [0u8; HASH_LEN];
+        hash.copy_from_slice(&entry[8..]);
+        if sha256(&block[..len]) != hash {
+            damaged.push(index);
+        }
+        hashes.push(hash);
+    }
+
+    let mut result = RecoveryReport { blocks: layout.blocks(), ..Default::default() };
+    let (k, m) = (layout.data_blocks, layout.parity_blocks);
+    let mut stripes: Vec<u64> = damaged.iter().map(|&i| i % layout.stripes).collect();
+    stripes.sort_unstable();
+    stripes.dedup();
+    let mut shards = vec![0u8; (k + m) * block_size];
+    let mut parity_hash = [0u8; HASH_LEN];
+    for s in stripes {
+        let mut present = vec![true; k + m];
+        let mut lost = vec![];
+        for (position, buf) in shards[..k * block_size].chunks_exact_mut(block_size).enumerate() {
+            match layout.block_at(s, position) {
+                Some(index) if damaged.binary_search(&index).is_ok() => {
+                    present[position] = false;
+                    lost.push((position, index));
+                }
+                Some(index) => read_block(&mut data, &layout, index, buf)?,
+                None => buf.fill(0),
+            }
+        }
+        let ecc = ecc.get_mut();
+        for (j, buf) in shards[k * block_size..].chunks_exact_mut(block_size).enumerate() {
+            ecc.seek(SeekFrom::Start(layout.parity_offset(s, j)))?;
+            if read_full(ecc, &mut parity_hash)? != HASH_LEN || read_full(ecc, buf)? != block_size || sha256(buf) != parity_hash {
+                present[k + j] = false;
+                result.damaged_parity += 1;
+            }
+        }
+        let available: Vec<Option<&[u8]>> = shards.chunks_exact(block_size).zip(&present).map(|(shard, &ok)| ok.then_some(shard)).collect();
+        let Ok(rebuilt) = codec.reconstruct(&available) else {
+            result.unrecoverable.extend(lost.iter().map(|&(_, index)| index));
+            continue;
+        };
+        for (position, index) in lost {
+            let restored = &rebuilt[position][..layout.block_len(index)];
+            // A mismatch here means the table entry, not the block, is what was damaged
+            if sha256(restored) != hashes[index as usize] {
+                result.unrecoverable.push(index);
+                continue;
+            }
+            data.seek(SeekFrom::Start(index * block_size as u64))?;
+            data.write_all(restored)?;
+            result.repaired.push(index);
+        }
+    }
+    data.sync_data()?;
+    result.repaired.sort_unstable();
+    result.unrecoverable.sort_unstable();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use std::fs;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("ecc_model_recovery_{}_{}", std::process::id(), name))
+    }
+
+    /// XOR random bytes into `len` bytes at `offset`, every one of them changed
+    fn damage(bytes: &mut [u8], offset: usize, len: usize, rng: &mut Rng) {
+        for b in &mut bytes[offset..offset + len] {
+            *b ^= rng.nonzero_byte();
+        }
+    }
+
+    #[test]
+    fn test_layout() {
+        // 10%: k + ceil(k / 10) <= 255 allows k = 231; 1025 blocks take 5 stripes of 205 + 21
+        let layout = Layout::new(4 * 1024 * 1024 + 1, 4096, 10).unwrap();
+        assert_eq!((layout.blocks(), layout.stripes, layout.data_blocks, layout.parity_blocks), (1025, 5, 205, 21));
+        assert_eq!(Layout::parse(&layout.to_bytes()).unwrap(), layout);
+        let empty = Layout::new(0, 4096, 10).unwrap();
+        assert_eq!((empty.blocks(), empty.stripes), (0, 0));
+        assert_eq!(Layout::new(10, 4096, 100).unwrap().parity_blocks, 1);
+        assert!(matches!(Layout::new(10, 4096, 0), Err(FileError::Params(_))));
+        assert!(matches!(Layout::new(10, 4096, 25_500), Err(FileError::Params(_))));
+        assert!(matches!(Layout::new(10, 0, 10), Err(FileError::Params(_))));
+        let mut bad = layout.to_bytes();
+        bad[13] ^= 1;
+        assert!(matches!(Layout::parse(&bad), Err(FileError::BadHeader("recovery file header fails its checksum"))));
+    }
+
+    #[test]
+    fn test_repair_random_damage() {
+        let (data_path, ecc_path) = (temp_path("data"), temp_path("ecc"));
+        let mut rng = Rng::new(387);
+        let data: Vec<u8> = (0..3 * 1024 * 1024 + 777).map(|_| rng.next_u64() as u8).collect();
+        fs::write(&data_path, &data).unwrap();
+        let layout = create(&data_path, &ecc_path, 10).unwrap();
+        // 769 blocks in 4 stripes of 193 data + 20 parity
+        assert_eq!((layout.blocks(), layout.stripes, layout.data_blocks, layout.parity_blocks), (769, 4, 193, 20));
+        assert_eq!(fs::metadata(&ecc_path).unwrap().len(), (32 + 769 * 40 + 4 * 20 * (32 + 4096)) as u64);
+        assert_eq!(repair(&data_path, &ecc_path).unwrap(), RecoveryReport { blocks: 769, ..Default::default() });
+
+        // Random regions within budget: a region of up to 6 blocks costs a stripe at most 2, so 8 of
+        // them, the last block and two damaged sidecar parity blocks stay under 20 per stripe
+        for round in 0..3 {
+            let mut damaged = data.clone();
+            for _ in 0..8 {
+                let len = 1 + rng.below(5 * 4096);
+                let offset = rng.below(data.len() - len);
+                damage(&mut damaged, offset, len, &mut rng);
+            }
+            damage(&mut damaged, data.len() - 5, 5, &mut rng); // the short last block
+            fs::write(&data_path, &damaged).unwrap();
+            let mut ecc = fs::read(&ecc_path).unwrap();
+            let pristine_ecc = ecc.clone();
+            for j in [round, 7] {
+                let at = layout.parity_offset(1, j) as usize + 32 + 100;
+                damage(&mut ecc, at, 1, &mut rng);
+            }
+            fs::write(&ecc_path, &ecc).unwrap();
+
+            let report = repair(&data_path, &ecc_path).unwrap();
+            assert!(report.unrecoverable.is_empty(), "{:?}", report);
+            assert!(report.repaired.contains(&768));
+            assert_eq!(fs::read(&data_path).unwrap(), data, "round {}", round);
+            fs::write(&ecc_path, &pristine_ecc).unwrap();
+        }
+
+        // 21 blocks of stripe 1 is one past its parity: those stay damaged, other stripes are restored
+        let mut damaged = data.clone();
+        let lost: Vec<u64> = (0..21).map(|i| 4 * i * 7 + 1).collect();
+        for &index in &lost {
+            damage(&mut damaged, index as usize * 4096 + 300, 3, &mut rng);
+        }
+        damage(&mut damaged, 4096 * 600 + 9, 1, &mut rng);
+        fs::write(&data_path, &damaged).unwrap();
+        let report = repair(&data_path, &ecc_path).unwrap();
+        assert_eq!(report.repaired, vec![600]);
+        assert_eq!(report.unrecoverable, lost);
+        let mut expected = data.clone();
+        for &index in &lost {
+            let block = index as usize * 4096..(index as usize + 1) * 4096;
+            expected[block.clone()].copy_from_slice(&damaged[block]);
+        }
+        assert_eq!(fs::read(&data_path).unwrap(), expected);
+
+        // A file that changed length is refused untouched
+        fs::write(&data_path, &data[..data.len() - 1]).unwrap();
+        let e = repair(&data_path, &ecc_path).unwrap_err();
+        assert!(matches!(e, FileError::LengthChanged { expected: 3146505, actual: 3146504 }), "{:?}", e);
+        assert_eq!(e.to_string(), "data file is 3146504 bytes but the recovery file was made for 3146505");
+        for p in [data_path, ecc_path] {
+            fs::remove_file(p).ok();
+        }
+    }
+
+    #[test]
+    fn test_small_and_empty_files() {
+        let (data_path, ecc_path) = (temp_path("small"), temp_path("small_ecc"));
+        for data in [&b""[..], b"x", b"a short file of a few words"] {
+            fs::write(&data_path, data).unwrap();
+            create_with_block_size(&data_path, &ecc_path, 50, 8).unwrap();
+            let mut damaged = data.to_vec();
+            if let Some(last) = damaged.last_mut() {
+                *last ^= 0x20;
+            }
+            fs::write(&data_path, &damaged).unwrap();
+            let report = repair(&data_path, &ecc_path).unwrap();
+            assert_eq!(report.repaired.len(), usize::from(!data.is_empty()));
+            assert_eq!(fs::read(&data_path).unwrap(), data);
+        }
+        fs::write(&ecc_path, b"not a recovery file, just text").unwrap();
+        assert!(matches!(repair(&data_path, &ecc_path), Err(FileError::BadHeader(_))));
+        for p in [data_path, ecc_path] {
+            fs::remove_file(p).ok();
+        }
+    }
+}
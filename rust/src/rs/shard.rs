@@ -0,0 +1,348 @@
+// Erasure coding across shards (zfec/RAID style): k data shards plus m parity shards, any k of
+// which recover the rest
+// Byte j of shard i is symbol i of codeword j of RS(k + m, k), so data shards are positions
+// 0..k and parity shards positions k..k+m. Being systematic and MDS, the code has a generator
+// matrix G = [I | P] any k columns of which are invertible; reconstruction inverts the columns
+// of the shards at hand once and then runs one multiply-accumulate pass per output shard.
+
+use std::fmt;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::gf256::gf_mul;
+use crate::gf256::matrix::invert;
+use crate::rs::{RsCodec, RsError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShardError {
+    /// The shard slice must have one (possibly missing) entry per shard
+    WrongCount { given: usize, expected: usize },
+    TooFewShards { present: usize, needed: usize },
+    /// Present shards must all have one length
+    LengthMismatch { index: usize, len: usize, expected: usize },
+    /// The joined data's length prefix is inconsistent with the shard size
+    BadLengthPrefix,
+    /// reconstruct_range: a present shard ends before the requested window does
+    WindowOutOfRange { index: usize, len: usize, end: usize },
+}
+
+impl fmt::Display for ShardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShardError::WrongCount { given, expected } => write!(f, "expected {} shard slots, got {}", expected, given),
+            ShardError::TooFewShards { present, needed } => write!(
+                f,
+                "only {} shards present, need {}: {} more required",
+                present, needed, needed - present
+            ),
+            ShardError::LengthMismatch { index, len, expected } => {
+                write!(f, "shard {} has length {}, expected {}", index, len, expected)
+            }
+            ShardError::BadLengthPrefix => f.write_str("length prefix does not fit the shards (corrupt data shards?)"),
+            ShardError::WindowOutOfRange { index, len, end } => {
+                write!(f, "shard {} has length {}, the window ends at {}", index, len, end)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShardError {}
+
+/// Bytes of the u64 little-endian length prefix split() puts in front of the data
+const LEN_PREFIX: usize = 8;
+
+/// dst[i] ^= c * src[i]
+fn mul_add(dst: &mut [u8], src: &[u8], c: u8) {
+    match c {
+        0 => {}
+        1 => dst.iter_mut().zip(src).for_each(|(d, &s)| *d ^= s),
+        _ => {
+            let row: Vec<u8> = (0..=255u8).map(|x| gf_mul(c, x)).collect();
+            dst.iter_mut().zip(src).for_each(|(d, &s)| *d ^= row[s as usize]);
+        }
+    }
+}
+
+/// k data + m parity shards, k + m <= 255
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardCodec {
+    k: usize,
+    m: usize,
+    /// parity[i * m + j]: weight of data shard i in parity shard j
+    parity: Vec<u8>,
+}
+
+impl ShardCodec {
+    pub fn new(data_shards: usize, parity_shards: usize) -> Result<Self, RsError> {
+        let (k, m) = (data_shards, parity_shards);
+        if k == 0 {
+            return Err(RsError::InvalidParameters("at least one data shard is required"));
+        }
+        if m == 0 {
+            return Err(RsError::InvalidParameters("at least one parity shard is required"));
+        }
+        if k + m > 255 {
+            return Err(RsError::InvalidParameters("data + parity shards must be at most 255"));
+        }
+        let codec = RsCodec::new(m, k + m)?;
+        let mut parity = Vec::with_capacity(k * m);
+        for i in 0..k {
+            let mut unit = vec![0u8; k];
+            unit[i] = 1;
+            parity.extend_from_slice(&codec.encode(&unit)[k..]);
+        }
+        Ok(Self { k, m, parity })
+    }
+
+    pub fn data_shards(&self) -> usize {
+        self.k
+    }
+
+    pub fn parity_shards(&self) -> usize {
+        self.m
+    }
+
+    pub fn total_shards(&self) -> usize {
+        self.k + self.m
+    }
+
+    /// Column `shard` of G = [I | P]
+    fn column(&self, shard: usize) -> Vec<u8> {
+        if shard < self.k {
+            (0..self.k).map(|i| (i == shard) as u8).collect()
+        } else {
+            (0..self.k).map(|i| self.parity[i * self.m + shard - self.k]).collect()
+        }
+    }
+
+    /// One output shard per weight vector: sum over i of weights[i] * sources[i]
+    fn combine(&self, sources: &[&[u8]], weights: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        let len = sources.first().map_or(0, |s| s.len());
+        let build = |w: &Vec<u8>| {
+            let mut out = vec![0u8; len];
+            for (src, &c) in sources.iter().zip(w) {
+                mul_add(&mut out, src, c);
+            }
+            out
+        };
+        #[cfg(feature = "parallel")]
+        let shards = weights.par_iter().map(build).collect();
+        #[cfg(not(feature = "parallel"))]
+        let shards = weights.iter().map(build).collect();
+        shards
+    }
+
+    /// The m parity shards for k equal-length data shards; panics on a count or length mismatch
+    pub fn encode(&self, data: &[&[u8]]) -> Vec<Vec<u8>> {
+        assert_eq!(data.len(), self.k, "expected {} data shards", self.k);
+        assert!(data.iter().all(|d| d.len() == data[0].len()), "data shards must have equal length");
+        let weights: Vec<Vec<u8>> = (self.k..self.k + self.m).map(|j| self.column(j)).collect();
+        self.combine(data, &weights)
+    }
+
+    /// Rebuild the shards listed in `wanted` from the ones present in `shards`
+    fn rebuild(&self, shards: &[Option<&[u8]>], wanted: &[usize]) -> Result<Vec<Vec<u8>>, ShardError> {
+        let n = self.total_shards();
+        if shards.len() != n {
+            return Err(ShardError::WrongCount { given: shards.len(), expected: n });
+        }
+        let present: Vec<usize> = (0..n).filter(|&i| shards[i].is_some()).collect();
+        if let Some(&first) = present.first() {
+            let expected = shards[first].unwrap().len();
+            if let Some(&index) = present.iter().find(|&&i| shards[i].unwrap().len() != expected) {
+                return Err(ShardError::LengthMismatch { index, len: shards[index].unwrap().len(), expected });
+            }
+        }
+        if present.len() < self.k {
+            return Err(ShardError::TooFewShards { present: present.len(), needed: self.k });
+        }
+        if wanted.is_empty() {
+            return Ok(vec![]);
+        }
+        let (chosen, weights) = self.plan(&present, wanted);
+        let sources: Vec<&[u8]> = chosen.iter().map(|&i| shards[i].unwrap()).collect();
+        Ok(self.combine(&sources, &weights))
+    }
+
+    /// The k present shards to rebuild from, and for each wanted shard its weights over them
+    fn plan<'p>(&self, present: &'p [usize], wanted: &[usize]) -> (&'p [usize], Vec<Vec<u8>>) {
+        // Prefer data shards: their columns are unit vectors, so the inverse stays sparse
+        let chosen = &present[..self.k];
+        let mut g_s = vec![0u8; self.k * self.k];
+        for (c, &shard) in chosen.iter().enumerate() {
+            for (r, v) in self.column(shard).into_iter().enumerate() {
+                g_s[r * self.k + c] = v;
+            }
+        }
+        let inv = invert(g_s, self.k).expect("any k columns of an MDS generator are independent");
+        // c_j = c_S * (G_S^-1 * G_j)
+        let weights = wanted
+            .iter()
+            .map(|&j| {
+                let col = self.column(j);
+                (0..self.k).map(|r| (0..self.k).fold(0u8, |acc, t| acc ^ gf_mul(inv[r * self.k + t], col[t]))).collect()
+            })
+            .collect();
+        (chosen, weights)
+    }
+
+    /// All k + m shards, copying the present ones and rebuilding the missing
+    pub fn reconstruct(&self, shards: &[Option<&[u8]>]) -> Result<Vec<Vec<u8>>, ShardError> {
+        let missing: Vec<usize> = (0..shards.len()).filter(|&i| shards[i].is_none()).collect();
+        let mut rebuilt = self.rebuild(shards, &missing)?.into_iter();
+        Ok(shards.iter().map(|s| s.map_or_else(|| rebuilt.next().unwrap(), |s| s.to_vec())).collect())
+    }
+
+    /// Bytes offset..offset + len of shard `missing_index`, rebuilt from the same window of k
+    /// present shards without touching the rest of them: the code runs bytewise across shards,
+    /// so serving a small read from a lost shard costs k windows, not k whole shards
+    /// Present shards need not share a length here, but each must cover the window. A present
+    /// missing_index is simply copied. Panics if missing_index is not a shard index.
+    pub fn reconstruct_range(&self, shards: &[Option<&[u8]>], missing_index: usize, offset: usize, len: usize) -> Result<Vec<u8>, ShardError> {
+        let n = self.total_shards();
+        assert!(missing_index < n, "shard index {} out of range for {} shards", missing_index, n);
+        if shards.len() != n {
+            return Err(ShardError::WrongCount { given: shards.len(), expected: n });
+        }
+        let end = offset.saturating_add(len);
+        let present: Vec<usize> = (0..n).filter(|&i| shards[i].is_some()).collect();
+        if let Some(&index) = present.iter().find(|&&i| shards[i].unwrap().len() < end) {
+            return Err(ShardError::WindowOutOfRange { index, len: shards[index].unwrap().len(), end });
+        }
+        if let Some(shard) = shards[missing_index] {
+            return Ok(shard[offset..end].to_vec());
+        }
+        if present.len() < self.k {
+            return Err(ShardError::TooFewShards { present: present.len(), needed: self.k });
+        }
+        let (chosen, weights) = self.plan(&present, &[missing_index]);
+        let sources: Vec<&[u8]> = chosen.iter().map(|&i| &shards[i].unwrap()[offset..end]).collect();
+        Ok(self.combine(&sources, &weights).pop().unwrap())
+    }
+
+    /// Shard length split() uses for `len` bytes of data
+    pub fn shard_len(&self, len: usize) -> usize {
+        (LEN_PREFIX + len).div_ceil(self.k)
+    }
+
+    /// Length-prefix the data, zero-pad it to k equal shards and append the m parity shards
+    pub fn split(&self, data: &[u8]) -> Vec<Vec<u8>> {
+        let shard_len = self.shard_len(data.len());
+        let mut framed = Vec::with_capacity(shard_len * self.k);
+        framed.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        framed.extend_from_slice(data);
+        framed.resize(shard_len * self.k, 0);
+        let mut shards: Vec<Vec<u8>> = framed.chunks_exact(shard_len).map(|c| c.to_vec()).collect();
+        let refs: Vec<&[u8]> = shards.iter().map(|s| s.as_slice()).collect();
+        let parity = self.encode(&refs);
+        shards.extend(parity);
+        shards
+    }
+
+    /// Inverse of split from any k shards
+    pub fn join(&self, shards: &[Option<&[u8]>]) -> Result<Vec<u8>, ShardError> {
+        let missing: Vec<usize> = (0..self.k.min(shards.len())).filter(|&i| shards[i].is_none()).collect();
+        let mut rebuilt = self.rebuild(shards, &missing)?.into_iter();
+        let mut framed = Vec::new();
+        for shard in &shards[..self.k] {
+            match shard {
+                Some(s) => framed.extend_from_slice(s),
+                None => framed.extend(rebuilt.next().unwrap()),
+            }
+        }
+        if framed.len() < LEN_PREFIX {
+            return Err(ShardError::BadLengthPrefix);
+        }
+        let len = u64::from_le_bytes(framed[..LEN_PREFIX].try_into().unwrap());
+        if len > (framed.len() - LEN_PREFIX) as u64 {
+            return Err(ShardError::BadLengthPrefix);
+        }
+        framed.truncate(LEN_PREFIX + len as usize);
+        Ok(framed.split_off(LEN_PREFIX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    #[test]
+    fn test_any_k_shards_recover() {
+        let codec = ShardCodec::new(5, 3).unwrap();
+        let mut rng = Rng::new(348);
+        let data: Vec<u8> = (0..10_000).map(|_| rng.next_u64() as u8).collect();
+        let shards = codec.split(&data);
+        assert_eq!(shards.len(), 8);
+        assert!(shards.iter().all(|s| s.len() == codec.shard_len(data.len())));
+        // Every shard column is an RS codeword
+        let rs = RsCodec::new(3, 8).unwrap();
+        let column: Vec<u8> = shards.iter().map(|s| s[1234]).collect();
+        assert!(rs.check(&column));
+
+        for mask in 0u32..256 {
+            let present: Vec<Option<&[u8]>> = (0..8).map(|i| (mask >> i & 1 == 1).then(|| shards[i].as_slice())).collect();
+            let count = mask.count_ones() as usize;
+            if count < 5 {
+                assert_eq!(codec.join(&present), Err(ShardError::TooFewShards { present: count, needed: 5 }));
+                continue;
+            }
+            assert_eq!(codec.join(&present).unwrap(), data, "mask={:08b}", mask);
+            assert_eq!(codec.reconstruct(&present).unwrap(), shards, "mask={:08b}", mask);
+        }
+    }
+
+    #[test]
+    fn test_shard_errors() {
+        let codec = ShardCodec::new(2, 1).unwrap();
+        assert!(ShardCodec::new(200, 56).is_err());
+        assert!(ShardCodec::new(0, 2).is_err());
+        let shards = codec.split(b"");
+        assert_eq!(codec.join(&[Some(&shards[0]), None, Some(&shards[2])]).unwrap(), b"");
+        assert_eq!(codec.join(&[Some(&shards[0])]), Err(ShardError::WrongCount { given: 1, expected: 3 }));
+        assert_eq!(
+            codec.join(&[Some(&shards[0]), Some(&[1u8][..]), None]),
+            Err(ShardError::LengthMismatch { index: 1, len: 1, expected: 4 })
+        );
+        let bogus = [0xffu8; 4];
+        assert_eq!(codec.join(&[Some(&bogus), Some(&bogus), None]), Err(ShardError::BadLengthPrefix));
+    }
+
+    #[test]
+    fn test_reconstruct_range() {
+        let codec = ShardCodec::new(6, 3).unwrap();
+        let mut rng = Rng::new(405);
+        let data: Vec<u8> = (0..3000).map(|_| rng.next_u64() as u8).collect();
+        let shards = codec.split(&data);
+        let shard_len = shards[0].len();
+        for trial in 0..300 {
+            // Up to m shards lost, the wanted one among them
+            let count = 1 + rng.below(3);
+            let lost = rng.distinct(count, 9);
+            let present: Vec<Option<&[u8]>> = (0..9).map(|i| (!lost.contains(&i)).then(|| shards[i].as_slice())).collect();
+            let full = codec.reconstruct(&present).unwrap();
+            let missing = lost[0];
+            let (offset, len) = match trial % 4 {
+                0 => (rng.below(shard_len + 1), 0),
+                1 => (rng.below(shard_len), 1),
+                2 => (0, shard_len),
+                _ => {
+                    let offset = rng.below(shard_len);
+                    (offset, rng.below(shard_len - offset + 1))
+                }
+            };
+            let window = codec.reconstruct_range(&present, missing, offset, len).unwrap();
+            assert_eq!(window, full[missing][offset..offset + len], "trial {} shard {} {}..+{}", trial, missing, offset, len);
+        }
+        // Windows at the end of the shards, and slices that only reach as far as the window
+        let present: Vec<Option<&[u8]>> = (0..9).map(|i| (i > 2).then(|| &shards[i][..shard_len - 3 + i % 3])).collect();
+        assert_eq!(codec.reconstruct_range(&present, 1, shard_len - 5, 2).unwrap(), shards[1][shard_len - 5..shard_len - 3]);
+        assert_eq!(codec.reconstruct_range(&present, 1, shard_len - 5, 3), Err(ShardError::WindowOutOfRange { index: 3, len: shard_len - 3, end: shard_len - 2 }));
+        assert_eq!(codec.reconstruct_range(&present, 4, 0, 7).unwrap(), shards[4][..7]);
+
+        let two: Vec<Option<&[u8]>> = (0..9).map(|i| (i >= 4).then(|| shards[i].as_slice())).collect();
+        assert_eq!(codec.reconstruct_range(&two, 0, 0, 1), Err(ShardError::TooFewShards { present: 5, needed: 6 }));
+        assert_eq!(codec.reconstruct_range(&two[..8], 0, 0, 1), Err(ShardError::WrongCount { given: 8, expected: 9 }));
+    }
+}
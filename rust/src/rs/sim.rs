@@ -0,0 +1,1019 @@
+// Error-injection channel models with seeded, reproducible randomness
+// The PRNG is SplitMix64: tiny, fast, and bit-identical on every platform for a given seed.
+
+use std::cell::Cell;
+use std::ops::Add;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::block::{BlockCode, DecodeOutcome};
+use crate::rs::interleave::Interleaver;
+use crate::rs::verify::Combinations;
+use crate::rs::{build_generator, decode, encode, RsCodec, RsError};
+
+/// SplitMix64 pseudo-random generator
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform integer in 0..n (n > 0), computed in u64 so results do not depend on usize width
+    pub fn below(&mut self, n: usize) -> usize {
+        (((self.next_u64() as u128) * (n as u128)) >> 64) as usize
+    }
+
+    /// Uniform byte in 1..=255
+    pub fn nonzero_byte(&mut self) -> u8 {
+        1 + self.below(255) as u8
+    }
+
+    /// Uniform float in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// `count` distinct values from 0..n in ascending order (count is clamped to n)
+    pub fn distinct(&mut self, count: usize, n: usize) -> Vec<usize> {
+        let count = count.min(n);
+        // Partial Fisher-Yates over the index range
+        let mut pool: Vec<usize> = (0..n).collect();
+        for i in 0..count {
+            let j = i + self.below(n - i);
+            pool.swap(i, j);
+        }
+        let mut chosen = pool[..count].to_vec();
+        chosen.sort_unstable();
+        chosen
+    }
+}
+
+/// What a channel does to each codeword
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelModel {
+    /// Exactly `count` distinct symbols get a random nonzero error
+    SymbolErrors(usize),
+    /// Each symbol independently gets a random nonzero error with probability p
+    SymbolErrorRate(f64),
+    /// `len` contiguous symbols at a random offset each get a random nonzero error
+    Burst(usize),
+    /// `count` distinct symbols are erased (set to zero) and their positions reported
+    Erasures(usize),
+    /// Exactly `count` distinct bits are flipped
+    BitFlips(usize),
+    /// Each bit is independently flipped with probability p (a binary symmetric channel)
+    BitErrorRate(f64),
+}
+
+/// Ground truth of one injection
+/// `errors` lists unflagged corruption as (position, xor mask) in ascending position order.
+/// `erasures` lists positions that were overwritten with zero; they are not repeated in `errors`
+/// and the byte may be unchanged if it was already zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InjectionRecord {
+    pub errors: Vec<(usize, u8)>,
+    pub erasures: Vec<usize>,
+}
+
+impl InjectionRecord {
+    /// Positions of the unflagged errors
+    pub fn positions(&self) -> Vec<usize> {
+        self.errors.iter().map(|&(pos, _)| pos).collect()
+    }
+
+    /// Every flipped bit as (position, bit index), bit 0 = LSB
+    pub fn flipped_bits(&self) -> Vec<(usize, u8)> {
+        self.errors
+            .iter()
+            .flat_map(|&(pos, mask)| (0..8u8).filter(move |b| mask >> b & 1 != 0).map(move |b| (pos, b)))
+            .collect()
+    }
+}
+
+/// A channel model plus its RNG stream
+/// Successive `apply` calls continue the same stream, so a sequence of injections
+/// is reproducible from the seed alone.
+#[derive(Debug)]
+pub struct Channel {
+    model: ChannelModel,
+    seed: u64,
+    state: Cell<u64>,
+}
+
+impl Channel {
+    pub fn new(model: ChannelModel, seed: u64) -> Self {
+        Self { model, seed, state: Cell::new(seed) }
+    }
+
+    pub fn symbol_errors(count: usize, seed: u64) -> Self {
+        Self::new(ChannelModel::SymbolErrors(count), seed)
+    }
+
+    pub fn symbol_error_rate(p: f64, seed: u64) -> Self {
+        Self::new(ChannelModel::SymbolErrorRate(p), seed)
+    }
+
+    pub fn burst(len: usize, seed: u64) -> Self {
+        Self::new(ChannelModel::Burst(len), seed)
+    }
+
+    pub fn erasures(count: usize, seed: u64) -> Self {
+        Self::new(ChannelModel::Erasures(count), seed)
+    }
+
+    pub fn bit_flips(count: usize, seed: u64) -> Self {
+        Self::new(ChannelModel::BitFlips(count), seed)
+    }
+
+    pub fn bit_error_rate(p: f64, seed: u64) -> Self {
+        Self::new(ChannelModel::BitErrorRate(p), seed)
+    }
+
+    pub fn model(&self) -> &ChannelModel {
+        &self.model
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Corrupt a codeword in place and report exactly what changed
+    pub fn apply(&self, codeword: &mut [u8]) -> InjectionRecord {
+        let mut rng = Rng { state: self.state.get() };
+        let record = inject(&self.model, &mut rng, codeword);
+        self.state.set(rng.state);
+        record
+    }
+}
+
+/// Apply one channel realization using the caller's RNG
+pub fn inject(model: &ChannelModel, rng: &mut Rng, codeword: &mut [u8]) -> InjectionRecord {
+    let n_bits = codeword.len() * 8;
+    inject_bits(model, rng, codeword, n_bits)
+}
+
+/// inject() for a block of n_bits bits in ceil(n_bits / 8) bytes (the BlockCode layout)
+/// Bit channels only touch bits below n_bits; symbol channels treat each byte as a symbol and
+/// drop the part of a mask that lands in the unused top bits, along with any mask left empty.
+/// With n_bits = 8 * len this is inject(), drawing the same random numbers.
+pub fn inject_bits(model: &ChannelModel, rng: &mut Rng, codeword: &mut [u8], n_bits: usize) -> InjectionRecord {
+    let n = codeword.len();
+    assert_eq!(n, n_bits.div_ceil(8), "codeword must be ceil(n_bits / 8) bytes");
+    let mut record = InjectionRecord::default();
+    match *model {
+        ChannelModel::SymbolErrors(count) => {
+            for pos in rng.distinct(count, n) {
+                record.errors.push((pos, rng.nonzero_byte()));
+            }
+        }
+        ChannelModel::SymbolErrorRate(p) => {
+            for pos in 0..n {
+                if rng.next_f64() < p {
+                    record.errors.push((pos, rng.nonzero_byte()));
+                }
+            }
+        }
+        ChannelModel::Burst(len) => {
+            let len = len.min(n);
+            if len > 0 {
+                let start = rng.below(n - len + 1);
+                for pos in start..start + len {
+                    record.errors.push((pos, rng.nonzero_byte()));
+                }
+            }
+        }
+        ChannelModel::Erasures(count) => {
+            record.erasures = rng.distinct(count, n);
+        }
+        ChannelModel::BitFlips(count) => {
+            for bit in rng.distinct(count, n_bits) {
+                let (pos, mask) = (bit / 8, 1u8 << (bit % 8));
+                match record.errors.last_mut() {
+                    Some((last, m)) if *last == pos => *m |= mask,
+                    _ => record.errors.push((pos, mask)),
+                }
+            }
+        }
+        ChannelModel::BitErrorRate(p) => {
+            for pos in 0..n {
+                let mask = (0..8).filter(|&b| pos * 8 + b < n_bits && rng.next_f64() < p).fold(0u8, |m, b| m | 1 << b);
+                if mask != 0 {
+                    record.errors.push((pos, mask));
+                }
+            }
+        }
+    }
+    if !n_bits.is_multiple_of(8) {
+        let last = (1u8 << (n_bits % 8)) - 1;
+        record.errors.retain_mut(|(pos, mask)| {
+            if *pos == n - 1 {
+                *mask &= last;
+            }
+            *mask != 0
+        });
+    }
+    for &(pos, mask) in &record.errors {
+        codeword[pos] ^= mask;
+    }
+    for &pos in &record.erasures {
+        codeword[pos] = 0;
+    }
+    record
+}
+
+/// Mixed corruption for test fixtures: a burst, scattered errors and flagged erasures, all at
+/// distinct positions. The burst goes at a random offset, then errors and erasures are drawn
+/// from the remaining positions. Unlike ChannelModel::Erasures, erased bytes get a nonzero xor
+/// rather than being zeroed, so every reported position really differs from the input.
+/// `record.errors` holds the burst and scattered errors; `record.erasures` the flagged ones.
+/// Panics if burst_len + errors + erasures exceeds the codeword length.
+pub fn corrupt(codeword: &mut [u8], errors: usize, erasures: usize, burst_len: usize, rng: &mut Rng) -> InjectionRecord {
+    let n = codeword.len();
+    assert!(burst_len + errors + erasures <= n, "more corrupted positions than symbols");
+    let start = rng.below(n - burst_len + 1);
+    let free: Vec<usize> = (0..n).filter(|p| !(start..start + burst_len).contains(p)).collect();
+    let picked: Vec<usize> = rng.distinct(errors + erasures, free.len()).into_iter().map(|i| free[i]).collect();
+    // Split the picked positions between errors and erasures at random
+    let flagged = rng.distinct(erasures, picked.len());
+
+    let mut record = InjectionRecord::default();
+    for (i, &pos) in picked.iter().enumerate() {
+        if flagged.binary_search(&i).is_ok() {
+            record.erasures.push(pos);
+        } else {
+            record.errors.push((pos, 0));
+        }
+    }
+    record.errors.extend((start..start + burst_len).map(|pos| (pos, 0)));
+    record.errors.sort_unstable();
+    for (pos, mask) in &mut record.errors {
+        *mask = rng.nonzero_byte();
+        codeword[*pos] ^= *mask;
+    }
+    for &pos in &record.erasures {
+        codeword[pos] ^= rng.nonzero_byte();
+    }
+    record
+}
+
+/// Monte Carlo run description
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimParams {
+    pub n: usize,
+    pub nsym: usize,
+    pub channel: ChannelModel,
+    pub trials: u64,
+    pub seed: u64,
+}
+
+/// Outcome counts over all trials
+/// clean: decoded with no corrections; corrected: errors repaired back to the sent message;
+/// detected: decoder reported failure; miscorrected: decoder succeeded with the wrong message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct SimStats {
+    pub trials: u64,
+    pub clean: u64,
+    pub corrected: u64,
+    pub detected: u64,
+    pub miscorrected: u64,
+}
+
+impl SimStats {
+    /// Fraction of blocks not delivered correctly (detected + miscorrected)
+    pub fn block_failure_rate(&self) -> f64 {
+        (self.detected + self.miscorrected) as f64 / self.trials.max(1) as f64
+    }
+}
+
+impl Add for SimStats {
+    type Output = SimStats;
+
+    fn add(self, other: SimStats) -> SimStats {
+        SimStats {
+            trials: self.trials + other.trials,
+            clean: self.clean + other.clean,
+            corrected: self.corrected + other.corrected,
+            detected: self.detected + other.detected,
+            miscorrected: self.miscorrected + other.miscorrected,
+        }
+    }
+}
+
+/// Independent RNG stream for one trial, derived from the run seed and trial index
+/// so results do not depend on how trials are scheduled across threads
+pub fn trial_rng(seed: u64, trial: u64) -> Rng {
+    let mut mixer = Rng::new(seed ^ trial.wrapping_mul(0xD1B5_4A32_D192_ED03));
+    Rng::new(mixer.next_u64())
+}
+
+/// SimStats plus the distribution of how many symbols the decoder corrected
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct SimReport {
+    pub stats: SimStats,
+    /// histogram[i]: trials the decoder accepted after correcting i symbols (clean, corrected
+    /// and miscorrected alike); detected failures are not counted, so the sum is trials - detected
+    pub histogram: Vec<u64>,
+}
+
+impl SimReport {
+    /// Classify one decode against the message that was sent
+    pub fn record(&mut self, message: &[u8], result: &Result<(Vec<u8>, Vec<usize>), RsError>) {
+        self.stats.trials += 1;
+        match result {
+            Ok((decoded, _)) if decoded != message => self.stats.miscorrected += 1,
+            Ok((_, positions)) if positions.is_empty() => self.stats.clean += 1,
+            Ok(_) => self.stats.corrected += 1,
+            Err(_) => self.stats.detected += 1,
+        }
+        if let Ok((_, positions)) = result {
+            if self.histogram.len() <= positions.len() {
+                self.histogram.resize(positions.len() + 1, 0);
+            }
+            self.histogram[positions.len()] += 1;
+        }
+    }
+}
+
+impl Add for SimReport {
+    type Output = SimReport;
+
+    fn add(mut self, other: SimReport) -> SimReport {
+        if self.histogram.len() < other.histogram.len() {
+            self.histogram.resize(other.histogram.len(), 0);
+        }
+        for (a, b) in self.histogram.iter_mut().zip(other.histogram) {
+            *a += b;
+        }
+        SimReport { stats: self.stats + other.stats, histogram: self.histogram }
+    }
+}
+
+impl SimReport {
+    /// record() for a BlockCode decode: `data` is what the decoder handed back
+    pub fn record_block(&mut self, message: &[u8], data: &[u8], outcome: DecodeOutcome) {
+        let corrected = match outcome {
+            DecodeOutcome::Detected => {
+                self.stats.trials += 1;
+                self.stats.detected += 1;
+                return;
+            }
+            DecodeOutcome::Clean => 0,
+            DecodeOutcome::Corrected { symbols, .. } => symbols,
+        };
+        self.stats.trials += 1;
+        if data != message {
+            self.stats.miscorrected += 1;
+        } else if corrected == 0 {
+            self.stats.clean += 1;
+        } else {
+            self.stats.corrected += 1;
+        }
+        if self.histogram.len() <= corrected {
+            self.histogram.resize(corrected + 1, 0);
+        }
+        self.histogram[corrected] += 1;
+    }
+}
+
+/// Trial's RNG stream and its random k-byte message, drawn first from that stream
+fn trial_message(seed: u64, trial: u64, k: usize) -> (Rng, Vec<u8>) {
+    let mut rng = trial_rng(seed, trial);
+    let message = (0..k).map(|_| rng.next_u64() as u8).collect();
+    (rng, message)
+}
+
+/// Estimate decoder performance for (n, nsym) over a channel
+/// Each trial encodes a random message, corrupts it, decodes, and compares against ground truth.
+pub fn monte_carlo(params: &SimParams) -> SimStats {
+    simulate(params).stats
+}
+
+/// monte_carlo plus the corrected-symbol histogram
+pub fn simulate(params: &SimParams) -> SimReport {
+    assert!(params.nsym < params.n && params.n <= 255, "invalid RS parameters");
+    let codec = RsCodec::new(params.nsym, params.n).expect("validated above");
+    simulate_code(&codec, &params.channel, params.trials, params.seed)
+}
+
+/// simulate() for any BlockCode, so codes of different families can be swept over one channel
+/// Messages are ceil(k_bits / 8) random bytes with the unused top bits cleared, and the channel
+/// is applied through inject_bits. The histogram counts corrected symbols, which for a binary
+/// code are bits.
+pub fn simulate_code(code: &dyn BlockCode, channel: &ChannelModel, trials: u64, seed: u64) -> SimReport {
+    let (k_bits, n_bits) = (code.k_bits(), code.n_bits());
+    let run_trial = |trial: u64| -> SimReport {
+        let (mut rng, mut message) = trial_message(seed, trial, k_bits.div_ceil(8));
+        if !k_bits.is_multiple_of(8) {
+            message[k_bits / 8] &= (1 << (k_bits % 8)) - 1;
+        }
+        let mut block = vec![0u8; n_bits.div_ceil(8)];
+        code.encode_block(&message, &mut block);
+        inject_bits(channel, &mut rng, &mut block, n_bits);
+        let outcome = code.decode_block(&mut block);
+        let mut data = vec![0u8; message.len()];
+        code.extract_data(&block, &mut data);
+        let mut report = SimReport::default();
+        report.record_block(&message, &data, outcome);
+        report
+    };
+
+    #[cfg(feature = "parallel")]
+    let report = (0..trials).into_par_iter().map(run_trial).reduce(SimReport::default, Add::add);
+    #[cfg(not(feature = "parallel"))]
+    let report = (0..trials).map(run_trial).fold(SimReport::default(), Add::add);
+    report
+}
+
+/// simulate() over groups of `depth` codewords byte-interleaved as rs::interleave does, the
+/// channel applied once to each group's depth * n byte stream
+/// The trial's messages are drawn one after another from its stream, then the channel, so depth 1
+/// is exactly simulate(). Stats count codewords (trials * depth of them), not groups. As in
+/// simulate_code, erasure flags are not passed to the decoder.
+pub fn simulate_interleaved(codec: &RsCodec, depth: usize, channel: &ChannelModel, trials: u64, seed: u64) -> SimReport {
+    let interleaver = Interleaver::new(depth);
+    let k = codec.k();
+    let run_trial = |trial: u64| -> SimReport {
+        let (mut rng, first) = trial_message(seed, trial, k);
+        let mut messages = vec![first];
+        messages.extend((1..depth).map(|_| (0..k).map(|_| rng.next_u64() as u8).collect::<Vec<u8>>()));
+        let codewords: Vec<Vec<u8>> = messages.iter().map(|m| codec.encode(m)).collect();
+        let refs: Vec<&[u8]> = codewords.iter().map(|c| c.as_slice()).collect();
+        let mut stream = interleaver.interleave(&refs);
+        inject(channel, &mut rng, &mut stream);
+        let mut report = SimReport::default();
+        for (message, received) in messages.iter().zip(interleaver.deinterleave(&stream)) {
+            report.record(message, &codec.decode(&received));
+        }
+        report
+    };
+
+    #[cfg(feature = "parallel")]
+    let report = (0..trials).into_par_iter().map(run_trial).reduce(SimReport::default, Add::add);
+    #[cfg(not(feature = "parallel"))]
+    let report = (0..trials).map(run_trial).fold(SimReport::default(), Add::add);
+    report
+}
+
+/// simulate() over a caller-supplied channel, run serially in trial order
+/// `channel(codeword, seed)` corrupts the codeword in place; the seed is drawn from the trial's
+/// stream after the message, so a deterministic channel gives results reproducible from `seed`.
+/// The first channel error stops the run and is returned.
+pub fn simulate_with<E>(
+    n: usize,
+    nsym: usize,
+    trials: u64,
+    seed: u64,
+    mut channel: impl FnMut(&mut [u8], u64) -> Result<(), E>,
+) -> Result<SimReport, E> {
+    assert!(nsym < n && n <= 255, "invalid RS parameters");
+    let generator = build_generator(nsym);
+    let mut report = SimReport::default();
+    for trial in 0..trials {
+        let (mut rng, message) = trial_message(seed, trial, n - nsym);
+        let mut codeword = encode(&message, nsym, &generator);
+        channel(&mut codeword, rng.next_u64())?;
+        report.record(&message, &decode(&codeword, nsym));
+    }
+    Ok(report)
+}
+
+/// Decode outcomes for error patterns of one weight
+/// corrected: decoded back to the original (only possible within t); detected: decode error;
+/// miscorrected: decode succeeded with a different message, i.e. silent data corruption
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct MiscorrectionStats {
+    pub error_weight: usize,
+    pub trials: u64,
+    pub corrected: u64,
+    pub detected: u64,
+    pub miscorrected: u64,
+}
+
+impl MiscorrectionStats {
+    /// Fraction of patterns silently miscorrected
+    pub fn miscorrection_rate(&self) -> f64 {
+        self.miscorrected as f64 / self.trials.max(1) as f64
+    }
+
+    /// Fraction of uncorrected patterns (detected or miscorrected) that were miscorrected: the
+    /// quantity the 1/t! rule of thumb approximates
+    pub fn miscorrected_given_uncorrected(&self) -> f64 {
+        self.miscorrected as f64 / (self.detected + self.miscorrected).max(1) as f64
+    }
+}
+
+/// Monte Carlo estimate: each trial hits exactly error_weight distinct symbols of a random
+/// codeword with random nonzero errors and classifies the decode, as simulate() does
+pub fn miscorrection_rate(n: usize, nsym: usize, error_weight: usize, trials: u64, seed: u64) -> MiscorrectionStats {
+    assert!(error_weight <= n, "error weight exceeds the codeword length");
+    let stats = monte_carlo(&SimParams { n, nsym, channel: ChannelModel::SymbolErrors(error_weight), trials, seed });
+    MiscorrectionStats {
+        error_weight,
+        trials: stats.trials,
+        corrected: stats.clean + stats.corrected,
+        detected: stats.detected,
+        miscorrected: stats.miscorrected,
+    }
+}
+
+/// Exact counts over every pattern of error_weight errors: all C(n, w) position sets with all
+/// 255^w nonzero magnitudes
+/// The code is linear, so the outcome depends only on the error pattern, and scaling a pattern by
+/// a nonzero constant scales the decoder's answer with it: only patterns whose first magnitude is
+/// 1 are decoded, each standing for 255. That is C(n, w) * 255^(w - 1) decodes, so keep n and w
+/// small (n = 15 with w = 3 is about 30 million).
+pub fn miscorrection_exhaustive(n: usize, nsym: usize, error_weight: usize) -> MiscorrectionStats {
+    assert!(nsym < n && n <= 255, "invalid RS parameters");
+    assert!((1..=n).contains(&error_weight), "error weight must be in 1..=n");
+    let codeword = vec![0u8; n];
+    let check_set = |positions: Vec<usize>| -> MiscorrectionStats {
+        let mut stats = MiscorrectionStats { error_weight, ..MiscorrectionStats::default() };
+        let mut tail = vec![1u8; error_weight - 1];
+        loop {
+            let mut received = codeword.clone();
+            received[positions[0]] = 1;
+            for (&pos, &mag) in positions[1..].iter().zip(&tail) {
+                received[pos] = mag;
+            }
+            stats.trials += 255;
+            match decode(&received, nsym) {
+                Ok((message, _)) if message.iter().all(|&b| b == 0) => stats.corrected += 255,
+                Ok(_) => stats.miscorrected += 255,
+                Err(_) => stats.detected += 255,
+            }
+            // Next nonzero magnitude assignment, as a base-255 counter over 1..=255
+            let Some(i) = tail.iter().rposition(|&m| m != 255) else { return stats };
+            tail[i] += 1;
+            tail[i + 1..].fill(1);
+        }
+    };
+    let merge = |a: MiscorrectionStats, b: MiscorrectionStats| MiscorrectionStats {
+        error_weight,
+        trials: a.trials + b.trials,
+        corrected: a.corrected + b.corrected,
+        detected: a.detected + b.detected,
+        miscorrected: a.miscorrected + b.miscorrected,
+    };
+    let empty = MiscorrectionStats { error_weight, ..MiscorrectionStats::default() };
+
+    #[cfg(feature = "parallel")]
+    let stats = Combinations::new(n, error_weight).par_bridge().map(check_set).reduce(|| empty, merge);
+    #[cfg(not(feature = "parallel"))]
+    let stats = Combinations::new(n, error_weight).map(check_set).fold(empty, merge);
+    stats
+}
+
+/// Outcomes of the scrub at one point in time, summed over codewords
+/// ce: corrected back to the written data; due: decoder failure; sdc: accepted with wrong data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct ScrubPoint {
+    pub time: u64,
+    pub ce: u64,
+    pub due: u64,
+    pub sdc: u64,
+}
+
+/// Scrubber::run output
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct ScrubReport {
+    /// One point per scrub, in time order
+    pub series: Vec<ScrubPoint>,
+    /// errors_at_scrub[i]: codeword scrubs that found i symbols differing from the written data
+    pub errors_at_scrub: Vec<u64>,
+}
+
+impl ScrubReport {
+    pub fn ce(&self) -> u64 {
+        self.series.iter().map(|p| p.ce).sum()
+    }
+
+    pub fn due(&self) -> u64 {
+        self.series.iter().map(|p| p.due).sum()
+    }
+
+    pub fn sdc(&self) -> u64 {
+        self.series.iter().map(|p| p.sdc).sum()
+    }
+}
+
+impl Add for ScrubReport {
+    type Output = ScrubReport;
+
+    fn add(mut self, other: ScrubReport) -> ScrubReport {
+        if self.series.is_empty() {
+            return other;
+        }
+        for (a, b) in self.series.iter_mut().zip(other.series) {
+            (a.ce, a.due, a.sdc) = (a.ce + b.ce, a.due + b.due, a.sdc + b.sdc);
+        }
+        if self.errors_at_scrub.len() < other.errors_at_scrub.len() {
+            self.errors_at_scrub.resize(other.errors_at_scrub.len(), 0);
+        }
+        for (a, b) in self.errors_at_scrub.iter_mut().zip(other.errors_at_scrub) {
+            *a += b;
+        }
+        self
+    }
+}
+
+/// Scrub-interval study: symbol errors accrue in stored codewords over time, and a periodic
+/// scrub decodes and rewrites each one
+/// Every time step, each symbol independently takes a random nonzero error with probability
+/// error_rate, on top of any it already holds. Scrubs run at every multiple of interval and at the
+/// horizon, so every interval covers the same exposure. A DUE or SDC is counted and the word is
+/// restored from elsewhere, so each event counts once. Codewords are independent, each on its
+/// own trial_rng(seed, index) stream, and run in parallel.
+#[derive(Debug, Clone)]
+pub struct Scrubber {
+    codec: RsCodec,
+    codewords: usize,
+    error_rate: f64,
+    interval: u64,
+    horizon: u64,
+    seed: u64,
+}
+
+impl Scrubber {
+    /// Panics if interval is 0 or error_rate is outside 0..=1
+    pub fn new(codec: RsCodec, codewords: usize, error_rate: f64, interval: u64, horizon: u64, seed: u64) -> Self {
+        assert!(interval > 0, "scrub interval must be at least 1");
+        assert!((0.0..=1.0).contains(&error_rate), "error rate must be in 0..=1");
+        Self { codec, codewords, error_rate, interval, horizon, seed }
+    }
+
+    /// Scrub times: the multiples of interval up to the horizon, then the horizon itself
+    pub fn scrub_times(&self) -> Vec<u64> {
+        let mut times: Vec<u64> = (1..=self.horizon / self.interval).map(|i| i * self.interval).collect();
+        if !self.horizon.is_multiple_of(self.interval) {
+            times.push(self.horizon);
+        }
+        times
+    }
+
+    pub fn run(&self) -> ScrubReport {
+        let times = self.scrub_times();
+        let run_codeword = |index: u64| -> ScrubReport {
+            let mut rng = trial_rng(self.seed, index);
+            let message: Vec<u8> = (0..self.codec.k()).map(|_| rng.next_u64() as u8).collect();
+            let mut written = self.codec.encode(&message);
+            let mut stored = written.clone();
+            let n = stored.len() as u64;
+            let mut report = ScrubReport::default();
+            let mut previous = 0;
+            for &time in &times {
+                // Hits over the (step, symbol) slots since the last scrub, by geometric gaps
+                let slots = (time - previous) * n;
+                let mut slot = self.next_hit(&mut rng, 0);
+                while slot < slots {
+                    stored[(slot % n) as usize] ^= rng.nonzero_byte();
+                    slot = self.next_hit(&mut rng, slot + 1);
+                }
+                previous = time;
+
+                let errors = stored.iter().zip(&written).filter(|(a, b)| a != b).count();
+                if report.errors_at_scrub.len() <= errors {
+                    report.errors_at_scrub.resize(errors + 1, 0);
+                }
+                report.errors_at_scrub[errors] += 1;
+                let mut point = ScrubPoint { time, ..ScrubPoint::default() };
+                match self.codec.decode(&stored) {
+                    Ok((decoded, _)) if decoded[..] != written[..decoded.len()] => {
+                        point.sdc = 1;
+                        written = self.codec.encode(&decoded);
+                    }
+                    Ok((_, positions)) if positions.is_empty() => {}
+                    Ok(_) => point.ce = 1,
+                    Err(_) => point.due = 1,
+                }
+                stored.clone_from(&written);
+                report.series.push(point);
+            }
+            report
+        };
+
+        let empty = ScrubReport { series: times.iter().map(|&time| ScrubPoint { time, ..ScrubPoint::default() }).collect(), errors_at_scrub: vec![] };
+        #[cfg(feature = "parallel")]
+        let report = (0..self.codewords as u64).into_par_iter().map(run_codeword).reduce(|| empty.clone(), Add::add);
+        #[cfg(not(feature = "parallel"))]
+        let report = (0..self.codewords as u64).map(run_codeword).fold(empty, Add::add);
+        report
+    }
+
+    /// First hit slot at or after `from`: geometric gaps with success probability error_rate
+    fn next_hit(&self, rng: &mut Rng, from: u64) -> u64 {
+        if self.error_rate <= 0.0 {
+            return u64::MAX;
+        }
+        if self.error_rate >= 1.0 {
+            return from;
+        }
+        let gap = ((1.0 - rng.next_f64()).ln() / (1.0 - self.error_rate).ln()).floor();
+        from.saturating_add(if gap >= u64::MAX as f64 { u64::MAX } else { gap as u64 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_for_seed() {
+        let models = [
+            ChannelModel::SymbolErrors(3),
+            ChannelModel::SymbolErrorRate(0.1),
+            ChannelModel::Burst(5),
+            ChannelModel::Erasures(4),
+            ChannelModel::BitFlips(6),
+            ChannelModel::BitErrorRate(0.02),
+        ];
+        for model in models {
+            let a = Channel::new(model.clone(), 42);
+            let b = Channel::new(model.clone(), 42);
+            for _ in 0..10 {
+                let mut x = vec![0xA5u8; 40];
+                let mut y = vec![0xA5u8; 40];
+                assert_eq!(a.apply(&mut x), b.apply(&mut y));
+                assert_eq!(x, y);
+            }
+        }
+        // Known first value pins the generator across platforms and refactors
+        assert_eq!(Rng::new(0).next_u64(), 0xE220_A839_7B1D_CDAF);
+    }
+
+    #[test]
+    fn test_corrupt_mixed() {
+        for seed in 0..50 {
+            let original = vec![0u8; 60];
+            let mut cw = original.clone();
+            let record = corrupt(&mut cw, 4, 3, 6, &mut Rng::new(seed));
+            assert_eq!((record.errors.len(), record.erasures.len()), (10, 3));
+            let mut all: Vec<usize> = record.positions().into_iter().chain(record.erasures.iter().copied()).collect();
+            all.sort_unstable();
+            let changed: Vec<usize> = (0..60).filter(|&p| cw[p] != original[p]).collect();
+            assert_eq!(all, changed);
+            let burst = record.positions();
+            assert!(burst.windows(6).any(|w| w[5] - w[0] == 5));
+        }
+    }
+
+    #[test]
+    fn test_record_matches_changes() {
+        for (i, channel) in [
+            Channel::symbol_errors(4, 1),
+            Channel::symbol_error_rate(0.2, 2),
+            Channel::burst(7, 3),
+            Channel::bit_flips(9, 4),
+            Channel::bit_error_rate(0.05, 5),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let original: Vec<u8> = (0..50u8).collect();
+            let mut cw = original.clone();
+            let record = channel.apply(&mut cw);
+            let changed: Vec<usize> = (0..cw.len()).filter(|&p| cw[p] != original[p]).collect();
+            assert_eq!(record.positions(), changed, "channel {}", i);
+            for &(pos, mask) in &record.errors {
+                assert_eq!(cw[pos] ^ original[pos], mask);
+            }
+        }
+    }
+
+    #[test]
+    fn test_inject_bits_stays_in_block() {
+        let models = [ChannelModel::SymbolErrors(3), ChannelModel::Burst(3), ChannelModel::BitFlips(20), ChannelModel::BitErrorRate(0.3)];
+        for model in models {
+            // Whole bytes: the same draws and the same record as inject
+            let (mut x, mut y) = (vec![0u8; 9], vec![0u8; 9]);
+            assert_eq!(inject(&model, &mut Rng::new(5), &mut x), inject_bits(&model, &mut Rng::new(5), &mut y, 72));
+            assert_eq!(x, y);
+            // A 23-bit block never has its 24th bit touched
+            let mut rng = Rng::new(6);
+            for _ in 0..200 {
+                let mut block = vec![0u8; 3];
+                let record = inject_bits(&model, &mut rng, &mut block, 23);
+                assert_eq!(block[2] & 0x80, 0, "{:?}", model);
+                assert!(record.errors.iter().all(|&(pos, mask)| mask != 0 && block[pos] == mask));
+            }
+        }
+    }
+
+    #[test]
+    fn test_miscorrection_rate() {
+        // Full length RS(255,249), t = 3, hit with 8 errors. The fraction of uncorrectable patterns
+        // that decode is about V(255, 3) / 256^6 = 0.161, close to the McEliece-Swanson rule of
+        // thumb Q = 1/t! = 0.167 for full-length codes
+        let stats = miscorrection_rate(255, 6, 8, 3000, 395);
+        assert_eq!(stats.trials, 3000);
+        assert_eq!(stats.corrected, 0);
+        assert!((stats.miscorrected_given_uncorrected() - 0.161).abs() < 0.02, "{:?}", stats);
+        assert_eq!(miscorrection_rate(255, 6, 8, 3000, 395), stats);
+        // Within t everything comes back
+        assert_eq!(miscorrection_rate(40, 8, 4, 500, 1).corrected, 500);
+        // Shortening makes miscorrection far rarer: (1 + 40 * 255) / 65536 = 0.156 for t = 1
+        let short = miscorrection_rate(40, 2, 3, 4000, 2);
+        assert!((short.miscorrection_rate() - 0.156).abs() < 0.02, "{:?}", short);
+    }
+
+    #[test]
+    fn test_miscorrection_exhaustive() {
+        // RS(15,13), t = 1: every double error either decodes to one of the 1 + 15 * 255 words
+        // within distance 1 of some codeword, or is detected
+        let exact = miscorrection_exhaustive(15, 2, 2);
+        assert_eq!(exact.trials, 105 * 255 * 255);
+        assert_eq!((exact.corrected, exact.detected + exact.miscorrected), (0, exact.trials));
+        // Exactly 13/255 of them, a little under the (1 + 15 * 255) / 256^2 = 0.058 estimate
+        assert_eq!(exact.miscorrected, 105 * 255 * 13);
+        let sampled = miscorrection_rate(15, 2, 2, 20_000, 3);
+        assert!((sampled.miscorrection_rate() - exact.miscorrection_rate()).abs() < 0.01, "{:?} {:?}", sampled, exact);
+        // Within t, exhaustive means every single error is corrected
+        let single = miscorrection_exhaustive(15, 2, 1);
+        assert_eq!((single.trials, single.corrected), (15 * 255, 15 * 255));
+    }
+
+    #[test]
+    fn test_scrubber() {
+        let codec = RsCodec::new(8, 40).unwrap();
+        let scrubber = Scrubber::new(codec.clone(), 50, 1e-3, 30, 100, 9);
+        assert_eq!(scrubber.scrub_times(), vec![30, 60, 90, 100]);
+        let report = scrubber.run();
+        assert_eq!(report, scrubber.run());
+        assert_eq!(report.series.iter().map(|p| p.time).collect::<Vec<_>>(), vec![30, 60, 90, 100]);
+        assert_eq!(report.errors_at_scrub.iter().sum::<u64>(), 50 * 4);
+        // About 1.2 errors per word per full interval: mostly corrections, rarely more than t = 4
+        assert!(report.ce() > 50, "{:?}", report);
+        assert_eq!(report.ce() + report.due() + report.sdc() + report.errors_at_scrub[0], 200);
+        assert_eq!(Scrubber::new(codec, 10, 0.0, 5, 50, 1).run().errors_at_scrub, vec![100]);
+    }
+
+    #[test]
+    fn test_scrub_interval_vs_due() {
+        // Same exposure (horizon) per run; longer intervals let more errors pile up per word
+        let codec = RsCodec::new(8, 40).unwrap();
+        let due: Vec<u64> = [5, 20, 80, 320]
+            .iter()
+            .map(|&interval| Scrubber::new(codec.clone(), 400, 5e-4, interval, 1280, 393).run().due())
+            .collect();
+        assert!(due.windows(2).all(|w| w[0] <= w[1]), "{:?}", due);
+        assert!(due[0] == 0 && due[3] > due[2] && due[2] > 0, "{:?}", due);
+    }
+
+    #[test]
+    fn test_monte_carlo_smoke() {
+        let params = SimParams { n: 34, nsym: 2, channel: ChannelModel::SymbolErrors(1), trials: 500, seed: 7 };
+        let stats = monte_carlo(&params);
+        // Every single-symbol error is within capability
+        assert_eq!(stats, SimStats { trials: 500, corrected: 500, ..SimStats::default() });
+
+        let params = SimParams { channel: ChannelModel::SymbolErrors(0), ..params };
+        assert_eq!(monte_carlo(&params).clean, 500);
+    }
+
+    #[test]
+    fn test_monte_carlo_matches_binomial_tail() {
+        // Failure happens exactly when more than t symbols are hit, so the failure rate
+        // estimates P(X > t) for X ~ Binomial(n, p)
+        let (n, nsym, p) = (15usize, 4usize, 0.1f64);
+        let params = SimParams { n, nsym, channel: ChannelModel::SymbolErrorRate(p), trials: 20_000, seed: 11 };
+        let stats = monte_carlo(&params);
+        let t = nsym / 2;
+        let mut within = 0.0;
+        let mut binom = 1.0; // C(n, i)
+        for i in 0..=t {
+            if i > 0 {
+                binom = binom * (n - i + 1) as f64 / i as f64;
+            }
+            within += binom * p.powi(i as i32) * (1.0 - p).powi((n - i) as i32);
+        }
+        let analytic = 1.0 - within;
+        assert!((stats.block_failure_rate() - analytic).abs() < 0.015, "{} vs {}", stats.block_failure_rate(), analytic);
+        assert_eq!(stats.trials, 20_000);
+        assert_eq!(stats.clean + stats.corrected + stats.detected + stats.miscorrected, stats.trials);
+    }
+
+    #[test]
+    fn test_simulate_with_custom_channel() {
+        let flip_first = |cw: &mut [u8], _seed: u64| -> Result<(), ()> {
+            cw[0] ^= 1;
+            Ok(())
+        };
+        let report = simulate_with(40, 8, 200, 5, flip_first).unwrap();
+        assert_eq!(report.stats, SimStats { trials: 200, corrected: 200, ..SimStats::default() });
+        assert_eq!(report.histogram, vec![0, 200]);
+
+        // The channel seed is a pure function of (seed, trial)
+        let seeds = |seed| {
+            let mut seen = vec![];
+            simulate_with(40, 8, 20, seed, |_: &mut [u8], s| -> Result<(), ()> {
+                seen.push(s);
+                Ok(())
+            })
+            .unwrap();
+            seen
+        };
+        assert_eq!(seeds(3), seeds(3));
+        assert_ne!(seeds(3), seeds(4));
+
+        let mut calls = 0;
+        let stop = simulate_with(40, 8, 100, 0, |_: &mut [u8], _| {
+            calls += 1;
+            match calls {
+                3 => Err("channel failed"),
+                _ => Ok(()),
+            }
+        });
+        assert_eq!((stop, calls), (Err("channel failed"), 3));
+    }
+
+    #[test]
+    fn test_simulate_histogram() {
+        let params = SimParams { n: 40, nsym: 8, channel: ChannelModel::SymbolErrors(3), trials: 300, seed: 2 };
+        let report = simulate(&params);
+        assert_eq!(report.histogram, vec![0, 0, 0, 300]);
+        assert_eq!(report.stats, monte_carlo(&params));
+
+        let params = SimParams { channel: ChannelModel::SymbolErrorRate(0.1), ..params };
+        let report = simulate(&params);
+        assert_eq!(report.histogram.iter().sum::<u64>(), report.stats.trials - report.stats.detected);
+        assert_eq!(report.histogram[0], report.stats.clean);
+    }
+
+    #[test]
+    fn test_simulate_interleaved() {
+        let codec = RsCodec::new(8, 32).unwrap();
+        let params = SimParams { n: 32, nsym: 8, channel: ChannelModel::SymbolErrorRate(0.12), trials: 500, seed: 417 };
+        assert_eq!(simulate_interleaved(&codec, 1, &params.channel, 500, 417), simulate(&params));
+        // A 16-byte burst is 16 errors against t = 4 in one codeword, 4 in each of 4 interleaved
+        let burst = ChannelModel::Burst(16);
+        let alone = simulate_interleaved(&codec, 1, &burst, 200, 1).stats;
+        assert_eq!(alone.trials, 200);
+        assert_eq!(alone.corrected, 0);
+        let spread = simulate_interleaved(&codec, 4, &burst, 200, 1).stats;
+        assert_eq!(spread, SimStats { trials: 800, corrected: 800, ..SimStats::default() });
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_monte_carlo_independent_of_thread_count() {
+        let params = SimParams { n: 20, nsym: 4, channel: ChannelModel::SymbolErrorRate(0.15), trials: 3000, seed: 99 };
+        let run = |threads| {
+            rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap().install(|| monte_carlo(&params))
+        };
+        assert_eq!(run(1), run(4));
+    }
+
+    #[test]
+    fn test_shape_of_each_model() {
+        let mut cw = vec![0x11u8; 30];
+        let burst = Channel::burst(6, 9).apply(&mut cw);
+        let positions = burst.positions();
+        assert_eq!(positions.len(), 6);
+        assert!(positions.windows(2).all(|w| w[1] == w[0] + 1));
+
+        let mut cw = vec![0x11u8; 30];
+        let bits = Channel::bit_flips(12, 9).apply(&mut cw);
+        assert_eq!(bits.flipped_bits().len(), 12);
+
+        let mut cw = vec![0x11u8; 30];
+        let erased = Channel::erasures(5, 9).apply(&mut cw);
+        assert_eq!(erased.erasures.len(), 5);
+        assert!(erased.errors.is_empty());
+        assert!(erased.erasures.iter().all(|&p| cw[p] == 0));
+        assert_eq!(cw.iter().filter(|&&b| b == 0).count(), 5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_report_json_round_trip() {
+        let params = SimParams { n: 40, nsym: 8, channel: ChannelModel::SymbolErrorRate(0.05), trials: 500, seed: 385 };
+        let report = simulate(&params);
+        let json = serde_json::to_string(&(&params, &report)).unwrap();
+        assert_eq!(serde_json::from_str::<(SimParams, SimReport)>(&json).unwrap(), (params, report));
+        assert_eq!(serde_json::to_string(&ChannelModel::Burst(5)).unwrap(), r#"{"Burst":5}"#);
+
+        // A report saved without the histogram
+        let old: SimReport = serde_json::from_str(r#"{"stats":{"trials":10,"clean":7,"detected":3}}"#).unwrap();
+        assert_eq!(old.stats, SimStats { trials: 10, clean: 7, detected: 3, ..SimStats::default() });
+        assert!(old.histogram.is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+// Word-at-a-time scalar encoder: the parity register as u64 words, updated 8 bytes per XOR
+// For each word of the register, split tables give the product of the feedback byte with that
+// word's 8 taps: lo[w][x] packs x * taps[8w..8w + 8], hi[w][x] packs (x << 4) * taps[8w..8w + 8],
+// so one message byte costs two lookups and a shift per word instead of nsym field multiplies.
+// Portable, so it is the encoder RsCodec falls back to when no SIMD kernel is available.
+// Each table set costs 256 bytes per 8 parity bytes (1 KB for nsym = 32), cached per nsym;
+// with `small-tables` RsCodec does not build them and falls back to encode_scalar instead.
+
+use std::sync::OnceLock;
+
+use crate::gf256::GF;
+use crate::opcount::{self, Stage};
+use crate::rs::cached_generator;
+
+/// Split product tables for one generator polynomial
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitTables {
+    nsym: usize,
+    lo: Vec<[u64; 16]>,
+    hi: Vec<[u64; 16]>,
+}
+
+impl SplitTables {
+    /// Tables for `generator` (low order first, monic, degree nsym)
+    pub fn new(generator: &[u8], nsym: usize) -> Self {
+        let words = nsym.div_ceil(8);
+        let mut lo = vec![[0u64; 16]; words];
+        let mut hi = vec![[0u64; 16]; words];
+        for j in 0..nsym {
+            let (w, shift) = (j / 8, 8 * (j % 8));
+            let tap = generator[nsym - 1 - j];
+            for x in 0..16u8 {
+                lo[w][x as usize] |= (GF.mul(tap, x) as u64) << shift;
+                hi[w][x as usize] |= (GF.mul(tap, x << 4) as u64) << shift;
+            }
+        }
+        Self { nsym, lo, hi }
+    }
+
+    pub fn nsym(&self) -> usize {
+        self.nsym
+    }
+
+    /// parity = message(x) * x^nsym mod g(x); parity.len() must be nsym
+    pub fn remainder(&self, message: &[u8], parity: &mut [u8]) {
+        assert_eq!(parity.len(), self.nsym, "parity must be nsym bytes");
+        let _ops = opcount::stage(Stage::Encode);
+        opcount::mul(message.len() * self.nsym);
+        opcount::add(message.len() * self.nsym);
+        let words = self.lo.len();
+        // Little-endian register: byte j of the parity is bits 8(j % 8).. of word j / 8
+        let mut r = [0u64; 32];
+        for &m in message {
+            let fb = m ^ r[0] as u8;
+            let (lo, hi) = ((fb & 0x0f) as usize, (fb >> 4) as usize);
+            for w in 0..words {
+                let next = if w + 1 < words { r[w + 1] } else { 0 };
+                r[w] = (r[w] >> 8 | next << 56) ^ self.lo[w][lo] ^ self.hi[w][hi];
+            }
+        }
+        for (chunk, word) in parity.chunks_mut(8).zip(r) {
+            chunk.copy_from_slice(&word.to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    /// Systematic codeword [message | parity]
+    pub fn encode(&self, message: &[u8]) -> Vec<u8> {
+        let k = message.len();
+        let mut codeword = vec![0u8; k + self.nsym];
+        codeword[..k].copy_from_slice(message);
+        self.remainder(message, &mut codeword[k..]);
+        codeword
+    }
+}
+
+/// SplitTables for cached_generator(nsym), built once per nsym like the generator itself
+pub fn cached_split_tables(nsym: usize) -> &'static SplitTables {
+    static CACHE: [OnceLock<SplitTables>; 255] = [const { OnceLock::new() }; 255];
+    CACHE[nsym].get_or_init(|| SplitTables::new(cached_generator(nsym), nsym))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::encode_scalar;
+    use crate::rs::sim::Rng;
+
+    #[test]
+    fn test_matches_naive_encoder() {
+        let mut rng = Rng::new(370);
+        for nsym in [1, 2, 7, 8, 9, 16, 31, 32, 33] {
+            let tables = cached_split_tables(nsym);
+            for k in 1..=(255 - nsym).min(223) {
+                let message: Vec<u8> = (0..k).map(|_| rng.next_u64() as u8).collect();
+                assert_eq!(tables.encode(&message), encode_scalar(&message, nsym, cached_generator(nsym)), "nsym={} k={}", nsym, k);
+            }
+        }
+        for nsym in [64, 100, 200, 254] {
+            let message: Vec<u8> = (0..255 - nsym).map(|_| rng.next_u64() as u8).collect();
+            assert_eq!(cached_split_tables(nsym).encode(&message), encode_scalar(&message, nsym, cached_generator(nsym)));
+        }
+    }
+}
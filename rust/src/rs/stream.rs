@@ -0,0 +1,449 @@
+// Incremental encode/decode over a stream of arbitrarily sized chunks
+// Both sides buffer less than one block, so memory stays bounded however the input is cut up.
+// The final partial block is a shortened codeword (len + nsym bytes), as in encode_chunked,
+// so the decoder recovers the exact payload length without padding.
+// Pipeline is the multi-threaded decoder for an interleaved link: whole depth-d groups go to a
+// worker pool over a bounded channel and come back through a reorder buffer in stream order.
+
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::rs::interleave::Interleaver;
+use crate::rs::{RsCodec, RsDecoder, RsError};
+
+/// Split `pending ++ data` into `block_len` blocks for `f`, keeping the remainder in `pending`
+fn for_each_block(pending: &mut Vec<u8>, block_len: usize, mut data: &[u8], mut f: impl FnMut(&[u8])) {
+    if !pending.is_empty() {
+        let take = (block_len - pending.len()).min(data.len());
+        pending.extend_from_slice(&data[..take]);
+        data = &data[take..];
+        if pending.len() < block_len {
+            return;
+        }
+        f(pending);
+        pending.clear();
+    }
+    let mut blocks = data.chunks_exact(block_len);
+    for block in &mut blocks {
+        f(block);
+    }
+    pending.extend_from_slice(blocks.remainder());
+}
+
+/// Emits a codeword for every k bytes fed
+#[derive(Debug, Clone)]
+pub struct StreamEncoder {
+    codec: RsCodec,
+    pending: Vec<u8>,
+}
+
+impl StreamEncoder {
+    pub fn new(nsym: usize, nsize: usize) -> Result<Self, RsError> {
+        let codec = RsCodec::new(nsym, nsize)?;
+        Ok(Self { pending: Vec::with_capacity(codec.k()), codec })
+    }
+
+    pub fn codec(&self) -> &RsCodec {
+        &self.codec
+    }
+
+    /// Bytes held back until a block fills (always < k)
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Encoded output for every block completed by `data`
+    pub fn feed(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity((self.pending.len() + data.len()) / self.codec.k() * self.codec.n());
+        let codec = &self.codec;
+        for_each_block(&mut self.pending, codec.k(), data, |block| out.extend(codec.encode(block)));
+        out
+    }
+
+    /// Encode the held-back bytes as a shortened codeword; the encoder can then be reused
+    pub fn flush(&mut self) -> Vec<u8> {
+        let out = self.codec.encode_chunked(&self.pending);
+        self.pending.clear();
+        out
+    }
+}
+
+/// Cumulative StreamDecoder results; unrecoverable blocks are passed through uncorrected
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    pub blocks: u64,
+    pub corrected_symbols: u64,
+    /// Offsets in the decoded payload of blocks that could not be corrected
+    pub failed_offsets: Vec<u64>,
+}
+
+/// Emits the payload of every n bytes fed
+#[derive(Debug, Clone)]
+pub struct StreamDecoder {
+    codec: RsCodec,
+    pending: Vec<u8>,
+    stats: StreamStats,
+    offset: u64,
+}
+
+impl StreamDecoder {
+    pub fn new(nsym: usize, nsize: usize) -> Result<Self, RsError> {
+        let codec = RsCodec::new(nsym, nsize)?;
+        Ok(Self { pending: Vec::with_capacity(codec.n()), codec, stats: StreamStats::default(), offset: 0 })
+    }
+
+    pub fn codec(&self) -> &RsCodec {
+        &self.codec
+    }
+
+    /// Bytes held back until a codeword fills (always < n)
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn stats(&self) -> &StreamStats {
+        &self.stats
+    }
+
+    fn decode_block(codec: &RsCodec, stats: &mut StreamStats, offset: &mut u64, block: &[u8], out: &mut Vec<u8>) {
+        let k = block.len() - codec.nsym();
+        match codec.decode(block) {
+            Ok((message, positions)) => {
+                stats.corrected_symbols += positions.len() as u64;
+                out.extend(message);
+            }
+            Err(_) => {
+                stats.failed_offsets.push(*offset);
+                out.extend_from_slice(&block[..k]);
+            }
+        }
+        stats.blocks += 1;
+        *offset += k as u64;
+    }
+
+    /// Corrected payload for every codeword completed by `data`
+    pub fn feed(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity((self.pending.len() + data.len()) / self.codec.n() * self.codec.k());
+        let Self { codec, pending, stats, offset } = self;
+        for_each_block(pending, codec.n(), data, |block| Self::decode_block(codec, stats, offset, block, &mut out));
+        out
+    }
+
+    /// Decode the held-back bytes as the final, shortened codeword
+    /// Fails with CodewordTooShort (leaving the bytes pending) if nsym bytes or fewer remain,
+    /// which means the stream was truncated.
+    pub fn flush(&mut self) -> Result<Vec<u8>, RsError> {
+        let mut out = vec![];
+        if self.pending.is_empty() {
+            return Ok(out);
+        }
+        if self.pending.len() <= self.codec.nsym() {
+            return Err(RsError::CodewordTooShort);
+        }
+        Self::decode_block(&self.codec, &mut self.stats, &mut self.offset, &self.pending, &mut out);
+        self.pending.clear();
+        Ok(out)
+    }
+}
+
+/// One decoded interleaver group: depth * k payload bytes plus what it took to get them
+struct GroupResult {
+    payload: Vec<u8>,
+    corrected_symbols: u64,
+    /// Indices within the group of codewords that could not be corrected
+    failed: Vec<usize>,
+}
+
+fn decode_group(decoder: &mut RsDecoder, interleaver: &Interleaver, k: usize, group: &[u8]) -> GroupResult {
+    let mut result = GroupResult { payload: Vec::with_capacity(interleaver.depth() * k), corrected_symbols: 0, failed: vec![] };
+    for (i, codeword) in interleaver.deinterleave(group).iter().enumerate() {
+        match decoder.decode_with(codeword) {
+            Ok((message, positions)) => {
+                result.corrected_symbols += positions.len() as u64;
+                result.payload.extend_from_slice(message);
+            }
+            Err(_) => {
+                result.failed.push(i);
+                result.payload.extend_from_slice(&codeword[..k]);
+            }
+        }
+    }
+    result
+}
+
+/// Multi-threaded decoder for a continuous encode_interleaved stream
+/// Bytes are cut into groups of depth codewords (depth * n bytes); each group is deinterleaved
+/// and decoded on one of `threads` workers, and payload comes back out of poll_decoded in stream
+/// order. Memory is bounded: at most `window` groups (twice the thread count) are held between
+/// push and poll_decoded, and push stops accepting bytes once that many are outstanding.
+/// Uncorrectable codewords are passed through and recorded in stats(), as in StreamDecoder.
+pub struct Pipeline {
+    group_len: usize,
+    k: usize,
+    depth: usize,
+    window: usize,
+    pending: Vec<u8>,
+    jobs: Option<SyncSender<(u64, Vec<u8>)>>,
+    results: Receiver<(u64, GroupResult)>,
+    workers: Vec<JoinHandle<()>>,
+    /// Finished groups waiting for the ones before them
+    reorder: BTreeMap<u64, GroupResult>,
+    submitted: u64,
+    emitted: u64,
+    stats: StreamStats,
+}
+
+impl Pipeline {
+    /// Fails with InvalidParameters for a bad (n, nsym) or a depth or thread count of 0
+    pub fn new(n: usize, nsym: usize, depth: usize, threads: usize) -> Result<Self, RsError> {
+        let codec = RsCodec::new(nsym, n)?;
+        if depth == 0 {
+            return Err(RsError::InvalidParameters("depth must be at least 1"));
+        }
+        if threads == 0 {
+            return Err(RsError::InvalidParameters("threads must be at least 1"));
+        }
+        let window = 2 * threads;
+        let (jobs, queue) = mpsc::sync_channel::<(u64, Vec<u8>)>(threads);
+        let (done, results) = mpsc::channel();
+        let queue = Arc::new(Mutex::new(queue));
+        let workers = (0..threads)
+            .map(|i| {
+                let (queue, done) = (queue.clone(), done.clone());
+                let (k, interleaver) = (codec.k(), Interleaver::new(depth));
+                thread::Builder::new()
+                    .name(format!("ecc-pipeline-{}", i))
+                    .spawn(move || {
+                        let mut decoder = RsDecoder::new(nsym, n);
+                        loop {
+                            // A statement of its own, so the lock is released before decoding
+                            let job = queue.lock().unwrap().recv();
+                            let Ok((seq, group)) = job else { return };
+                            if done.send((seq, decode_group(&mut decoder, &interleaver, k, &group))).is_err() {
+                                return;
+                            }
+                        }
+                    })
+                    .expect("could not start pipeline thread")
+            })
+            .collect();
+        Ok(Self {
+            group_len: depth * n,
+            k: codec.k(),
+            depth,
+            window,
+            pending: Vec::with_capacity(depth * n),
+            jobs: Some(jobs),
+            results,
+            workers,
+            reorder: BTreeMap::new(),
+            submitted: 0,
+            emitted: 0,
+            stats: StreamStats::default(),
+        })
+    }
+
+    /// Groups submitted but not yet returned by poll_decoded
+    pub fn in_flight(&self) -> usize {
+        (self.submitted - self.emitted) as usize
+    }
+
+    /// Bytes held back until a group fills (always < depth * n)
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn stats(&self) -> &StreamStats {
+        &self.stats
+    }
+
+    /// Take bytes from the front of `data`, returning how many were accepted
+    /// Accepts everything unless the window of outstanding groups fills; then the rest must be
+    /// pushed again after poll_decoded has drained some output.
+    pub fn push(&mut self, data: &[u8]) -> usize {
+        let mut used = 0;
+        while used < data.len() && self.in_flight() < self.window {
+            let take = (self.group_len - self.pending.len()).min(data.len() - used);
+            self.pending.extend_from_slice(&data[used..used + take]);
+            used += take;
+            if self.pending.len() == self.group_len {
+                let group = std::mem::replace(&mut self.pending, Vec::with_capacity(self.group_len));
+                self.submit(group);
+            }
+        }
+        used
+    }
+
+    fn submit(&mut self, group: Vec<u8>) {
+        let jobs = self.jobs.as_ref().expect("workers run until drop");
+        jobs.send((self.submitted, group)).expect("pipeline worker exited");
+        self.submitted += 1;
+    }
+
+    /// Payload of every group finished in order so far
+    /// Blocks for the oldest outstanding group only when the window is full, so a push /
+    /// poll_decoded loop makes progress without spinning.
+    pub fn poll_decoded(&mut self) -> Vec<u8> {
+        let block = self.in_flight() >= self.window;
+        self.drain(block, false)
+    }
+
+    /// Wait for every submitted group and return the rest of the payload
+    /// Fails with CodewordTooShort, keeping the bytes pending, if the stream stopped part way
+    /// through a group.
+    pub fn finish(&mut self) -> Result<Vec<u8>, RsError> {
+        if !self.pending.is_empty() {
+            return Err(RsError::CodewordTooShort);
+        }
+        Ok(self.drain(true, true))
+    }
+
+    /// Emit finished groups in order; wait for the next one if `block`, and for all if `all`
+    fn drain(&mut self, block: bool, all: bool) -> Vec<u8> {
+        let mut out = vec![];
+        loop {
+            while let Ok((seq, result)) = self.results.try_recv() {
+                self.reorder.insert(seq, result);
+            }
+            while let Some(result) = self.reorder.remove(&self.emitted) {
+                self.emit(result, &mut out);
+            }
+            let waiting = self.in_flight() > 0 && (all || (block && out.is_empty()));
+            if !waiting {
+                return out;
+            }
+            let (seq, result) = self.results.recv().expect("pipeline worker exited");
+            self.reorder.insert(seq, result);
+        }
+    }
+
+    fn emit(&mut self, result: GroupResult, out: &mut Vec<u8>) {
+        let base = self.emitted * (self.depth * self.k) as u64;
+        self.stats.failed_offsets.extend(result.failed.iter().map(|&i| base + (i * self.k) as u64));
+        self.stats.corrected_symbols += result.corrected_symbols;
+        self.stats.blocks += self.depth as u64;
+        out.extend_from_slice(&result.payload);
+        self.emitted += 1;
+    }
+}
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        // Closing the job channel lets each worker finish its group and exit
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_matches_chunked() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let codec = RsCodec::new(8, 60).unwrap();
+        for piece in [1, 13, 52, 60, 999] {
+            let mut encoder = StreamEncoder::new(8, 60).unwrap();
+            let mut encoded: Vec<u8> = data.chunks(piece).flat_map(|c| encoder.feed(c)).collect();
+            assert!(encoder.pending() < 52);
+            encoded.extend(encoder.flush());
+            assert_eq!(encoded, codec.encode_chunked(&data), "piece={}", piece);
+
+            encoded[100] ^= 0x40;
+            encoded[4000] ^= 0x01;
+            let mut decoder = StreamDecoder::new(8, 60).unwrap();
+            let mut decoded: Vec<u8> = encoded.chunks(piece).flat_map(|c| decoder.feed(c)).collect();
+            decoded.extend(decoder.flush().unwrap());
+            assert_eq!(decoded, data);
+            assert_eq!(decoder.stats(), &StreamStats { blocks: 97, corrected_symbols: 2, failed_offsets: vec![] });
+        }
+    }
+
+    #[test]
+    fn test_decoder_failures() {
+        let mut encoder = StreamEncoder::new(4, 20).unwrap();
+        let mut encoded = encoder.feed(&[9u8; 50]);
+        encoded.extend(encoder.flush());
+        for b in &mut encoded[20..23] {
+            *b ^= 0xff;
+        }
+        let mut decoder = StreamDecoder::new(4, 20).unwrap();
+        let mut decoded = decoder.feed(&encoded);
+        decoded.extend(decoder.flush().unwrap());
+        assert_eq!(decoded.len(), 50);
+        assert_eq!(decoder.stats().failed_offsets, vec![16]);
+
+        decoder.feed(&encoded[..44]);
+        assert_eq!(decoder.flush(), Err(RsError::CodewordTooShort));
+        assert_eq!(decoder.pending(), 4);
+    }
+
+    #[test]
+    fn test_pipeline_matches_offline_decode() {
+        use crate::rs::interleave::{decode_interleaved, encode_interleaved};
+        use crate::rs::sim::Rng;
+
+        // 100 MB of stream in optimized builds; debug builds run the same test on less
+        let megabytes = if cfg!(debug_assertions) { 4 } else { 100 };
+        let (n, nsym, depth) = (255, 16, 8);
+        let k = n - nsym;
+        let mut rng = Rng::new(377);
+        let groups = megabytes * (1 << 20) / (depth * n);
+        let data: Vec<u8> = (0..groups * depth * k).map(|_| rng.next_u64() as u8).collect();
+        let mut stream = encode_interleaved(&data, nsym, n, depth).unwrap();
+        // One burst of up to depth * t bytes in every fourth group: at most t symbols per codeword
+        for group in rng.distinct(groups / 4, groups) {
+            let len = 1 + rng.below(depth * nsym / 2);
+            let start = group * depth * n + rng.below(depth * n - len + 1);
+            for b in &mut stream[start..start + len] {
+                *b ^= rng.nonzero_byte();
+            }
+        }
+        let expected = decode_interleaved(&stream, nsym, n, depth).unwrap();
+
+        let mut pipeline = Pipeline::new(n, nsym, depth, 3).unwrap();
+        let mut decoded = Vec::with_capacity(expected.data.len());
+        let mut rest = &stream[..];
+        while !rest.is_empty() {
+            let chunk = (1 + rng.below(3 * depth * n)).min(rest.len());
+            let mut piece = &rest[..chunk];
+            while !piece.is_empty() {
+                let used = pipeline.push(piece);
+                piece = &piece[used..];
+                assert!(pipeline.in_flight() <= 6 && pipeline.pending() < depth * n);
+                decoded.extend(pipeline.poll_decoded());
+            }
+            rest = &rest[chunk..];
+        }
+        decoded.extend(pipeline.finish().unwrap());
+        assert!(decoded == expected.data, "pipeline output differs from decode_interleaved");
+        let stats = pipeline.stats();
+        assert_eq!((stats.blocks, stats.corrected_symbols), ((groups * depth) as u64, (expected.positions.len() + expected.parity_positions.len()) as u64));
+        assert!(stats.failed_offsets.is_empty());
+    }
+
+    #[test]
+    fn test_pipeline_failures_and_truncation() {
+        use crate::rs::interleave::encode_interleaved;
+
+        let (n, nsym, depth) = (20, 4, 2);
+        let mut stream = encode_interleaved(&[7u8; 64], nsym, n, depth).unwrap();
+        for b in &mut stream[40..46] {
+            *b ^= 0xff; // 3 errors in each codeword of group 1
+        }
+        let mut pipeline = Pipeline::new(n, nsym, depth, 2).unwrap();
+        assert_eq!(pipeline.push(&stream[..70]), 70);
+        assert_eq!(pipeline.finish(), Err(RsError::CodewordTooShort));
+        assert_eq!(pipeline.push(&stream[70..]), 10);
+        let mut decoded = pipeline.poll_decoded();
+        decoded.extend(pipeline.finish().unwrap());
+        assert_eq!(decoded.len(), 64);
+        assert_eq!(pipeline.stats().failed_offsets, vec![32, 48]);
+        assert!(Pipeline::new(n, nsym, 0, 1).is_err() && Pipeline::new(n, nsym, 1, 0).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+// Exhaustive verification that a (shortened) RS code corrects every pattern of <= t errors
+// Enumerates every position set of size 1..=t and every assignment of the sampled magnitudes,
+// so for small n it is a proof rather than a Monte Carlo estimate.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::rs::{build_generator, decode, encode, RsError};
+
+/// A pattern of <= t errors the decoder did not repair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample {
+    pub positions: Vec<usize>,
+    pub magnitudes: Vec<u8>,
+    /// What the decoder returned: the wrong message, or the error it reported
+    pub outcome: Result<Vec<u8>, RsError>,
+}
+
+/// Result of `verify_exhaustive`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyReport {
+    pub n: usize,
+    pub nsym: usize,
+    /// Number of error patterns decoded
+    pub patterns_checked: u64,
+    /// Patterns not decoded back to the original message
+    pub failures: u64,
+    /// First failure in enumeration order (fewest errors, then lexicographic positions)
+    pub first_counterexample: Option<Counterexample>,
+}
+
+impl VerifyReport {
+    pub fn passed(&self) -> bool {
+        self.failures == 0
+    }
+}
+
+// Per-position-set partial result: (checked, failures, first failure)
+type Partial = (u64, u64, Option<(usize, Counterexample)>);
+
+/// Check every error pattern of weight 1..=nsym/2 with magnitudes drawn from `magnitude_samples`
+/// Zero magnitudes are ignored. Work is P(n, <=t) * |magnitudes|^t decodes, so keep n and the
+/// sample set small; position sets are spread across threads with the `parallel` feature.
+pub fn verify_exhaustive(n: usize, nsym: usize, magnitude_samples: &[u8]) -> VerifyReport {
+    assert!(nsym < n && n <= 255, "invalid RS parameters");
+    let t = nsym / 2;
+    let k = n - nsym;
+    let mut magnitudes: Vec<u8> = magnitude_samples.iter().copied().filter(|&m| m != 0).collect();
+    magnitudes.sort_unstable();
+    magnitudes.dedup();
+
+    let message: Vec<u8> = (0..k).map(|i| (i as u8).wrapping_mul(37).wrapping_add(1)).collect();
+    let codeword = encode(&message, nsym, &build_generator(nsym));
+
+    let mut position_sets = Vec::new();
+    if !magnitudes.is_empty() {
+        for weight in 1..=t {
+            position_sets.extend(Combinations::new(n, weight));
+        }
+    }
+
+    let check_set = |(index, positions): (usize, &Vec<usize>)| -> Partial {
+        let mut partial: Partial = (0, 0, None);
+        let mut digits = vec![0usize; positions.len()];
+        loop {
+            let mut received = codeword.clone();
+            for (&pos, &d) in positions.iter().zip(&digits) {
+                received[pos] ^= magnitudes[d];
+            }
+            partial.0 += 1;
+            let outcome = decode(&received, nsym).map(|(decoded, _)| decoded);
+            if outcome.as_ref() != Ok(&message) {
+                partial.1 += 1;
+                if partial.2.is_none() {
+                    let magnitudes = digits.iter().map(|&d| magnitudes[d]).collect();
+                    partial.2 = Some((index, Counterexample { positions: positions.clone(), magnitudes, outcome }));
+                }
+            }
+            if !advance(&mut digits, magnitudes.len()) {
+                return partial;
+            }
+        }
+    };
+    let merge = |a: Partial, b: Partial| -> Partial {
+        let first = match (a.2, b.2) {
+            (Some(x), Some(y)) => Some(if x.0 <= y.0 { x } else { y }),
+            (x, y) => x.or(y),
+        };
+        (a.0 + b.0, a.1 + b.1, first)
+    };
+
+    #[cfg(feature = "parallel")]
+    let (patterns_checked, failures, first) =
+        position_sets.par_iter().enumerate().map(check_set).reduce(|| (0, 0, None), merge);
+    #[cfg(not(feature = "parallel"))]
+    let (patterns_checked, failures, first) =
+        position_sets.iter().enumerate().map(check_set).fold((0, 0, None), merge);
+
+    VerifyReport { n, nsym, patterns_checked, failures, first_counterexample: first.map(|(_, c)| c) }
+}
+
+/// All `weight`-subsets of 0..n in lexicographic order
+pub(crate) struct Combinations {
+    n: usize,
+    combo: Option<Vec<usize>>,
+}
+
+impl Combinations {
+    pub(crate) fn new(n: usize, weight: usize) -> Self {
+        Self { n, combo: (weight <= n).then(|| (0..weight).collect()) }
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        let current = self.combo.take()?;
+        let weight = current.len();
+        // Rightmost index that can still move
+        if let Some(i) = (0..weight).rev().find(|&i| current[i] < self.n - weight + i) {
+            let mut next = current.clone();
+            next[i] += 1;
+            for j in i + 1..weight {
+                next[j] = next[j - 1] + 1;
+            }
+            self.combo = Some(next);
+        }
+        Some(current)
+    }
+}
+
+/// Mixed-radix increment; false once every digit has wrapped
+fn advance(digits: &mut [usize], radix: usize) -> bool {
+    for d in digits.iter_mut().rev() {
+        *d += 1;
+        if *d < radix {
+            return true;
+        }
+        *d = 0;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combination_count() {
+        let sets: Vec<_> = Combinations::new(6, 3).collect();
+        assert_eq!(sets.len(), 20);
+        assert_eq!(sets.first(), Some(&vec![0, 1, 2]));
+        assert_eq!(sets.last(), Some(&vec![3, 4, 5]));
+        assert_eq!(Combinations::new(4, 0).count(), 1);
+        assert_eq!(Combinations::new(3, 4).count(), 0);
+    }
+
+    #[test]
+    fn test_small_codes_pass() {
+        let report = verify_exhaustive(15, 4, &[0x01, 0x80, 0xff, 0x00]);
+        assert!(report.passed(), "{:?}", report.first_counterexample);
+        // 15 * 3 single errors + C(15,2) * 3^2 double errors
+        assert_eq!(report.patterns_checked, 15 * 3 + 105 * 9);
+
+        // Shortened code, t = 3
+        let report = verify_exhaustive(32, 6, &[0x01, 0x5a]);
+        assert!(report.passed(), "{:?}", report.first_counterexample);
+        assert_eq!(report.patterns_checked, 32 * 2 + 496 * 4 + 4960 * 8);
+    }
+
+    #[test]
+    #[ignore = "heavy: every magnitude for RS(15,11), ~6.9M decodes"]
+    fn test_exhaustive_15_4_all_magnitudes() {
+        let all: Vec<u8> = (1..=255).collect();
+        let report = verify_exhaustive(15, 4, &all);
+        assert!(report.passed(), "{:?}", report.first_counterexample);
+        assert_eq!(report.patterns_checked, 15 * 255 + 105 * 255 * 255);
+    }
+}
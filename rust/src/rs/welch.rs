@@ -0,0 +1,130 @@
+// Berlekamp-Welch decoding: RS decoding as one linear system, for comparison and teaching
+// The generator-polynomial code is also an evaluation code. Byte p is the coefficient of
+// x^(n-1-p), locator X_p = alpha^(n-1-p), and the codewords are exactly c_p = v_p f(X_p) for the
+// message polynomials f of degree < k, with column multipliers v_p = 1 / prod_(q != p)(X_p - X_q)
+// (the dual of the parity checks sum_p c_p X_p^j = 0, j < nsym). With y_p = r_p / v_p, an error
+// locator E (monic, degree t = nsym / 2, zero at the error locators) and Q = f E satisfy
+//   Q(X_p) = y_p E(X_p)   for every p,
+// n equations linear in the k + t coefficients of Q and the t low coefficients of E. Any
+// solution gives f = Q / E when there are at most t errors: Q - f E has degree < k + t and
+// vanishes on the >= n - t correct positions. Re-evaluating f gives the codeword.
+//
+// Complexity: Gaussian elimination of an n x (k + 2t) system, O(n^3) against the O(n * nsym)
+// of syndromes, Berlekamp-Massey and Chien. No syndromes are computed; the result matches
+// decode's, failures included, as both succeed exactly when a codeword lies within t.
+
+use crate::gf256::matrix::{self, Inconsistent};
+use crate::gf256::{gf_div, gf_mul, poly_div, poly_eval, GF};
+use crate::rs::{validate_params, RsError};
+
+/// decode by solving the Berlekamp-Welch system: (message, corrected positions ascending)
+/// SingularSystem if the system has no solution, TooManyErrors if E does not divide Q; either
+/// way there is no codeword within nsym / 2.
+pub fn decode_bw(codeword: &[u8], nsym: usize) -> Result<(Vec<u8>, Vec<usize>), RsError> {
+    let n = codeword.len();
+    if n < nsym {
+        return Err(RsError::CodewordTooShort);
+    }
+    validate_params(nsym, n)?;
+    let (k, t) = (n - nsym, nsym / 2);
+    let locators: Vec<u8> = (0..n).map(|p| GF.exp[n - 1 - p]).collect();
+    let multipliers: Vec<u8> = (0..n)
+        .map(|p| {
+            let product = (0..n).filter(|&q| q != p).fold(1u8, |acc, q| gf_mul(acc, locators[p] ^ locators[q]));
+            gf_div(1, product)
+        })
+        .collect();
+
+    // Row p: [X_p^0 .. X_p^(k+t-1) | y_p X_p^0 .. y_p X_p^(t-1)] = y_p X_p^t
+    let unknowns = k + 2 * t;
+    let mut a = Vec::with_capacity(n * unknowns);
+    let mut b = Vec::with_capacity(n);
+    for p in 0..n {
+        let y = gf_div(codeword[p], multipliers[p]);
+        let mut power = 1u8;
+        for _ in 0..k + t {
+            a.push(power);
+            power = gf_mul(power, locators[p]);
+        }
+        let mut power = y;
+        for _ in 0..t {
+            a.push(power);
+            power = gf_mul(power, locators[p]);
+        }
+        b.push(power);
+    }
+    let solution = matrix::solve(a, unknowns, b).map_err(|Inconsistent { rank }| RsError::SingularSystem { rank, unknowns })?;
+
+    let q = &solution[..k + t];
+    let mut e = solution[k + t..].to_vec();
+    e.push(1);
+    let (mut f, remainder) = poly_div(q, &e);
+    if remainder.iter().any(|&c| c != 0) {
+        return Err(RsError::TooManyErrors);
+    }
+    f.resize(k, 0);
+    let corrected: Vec<u8> = (0..n).map(|p| gf_mul(multipliers[p], poly_eval(&f, locators[p]))).collect();
+    let positions = (0..n).filter(|&p| corrected[p] != codeword[p]).collect();
+    Ok((corrected[..k].to_vec(), positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+    use crate::rs::{cached_generator, decode, encode};
+
+    #[test]
+    fn test_matches_decode() {
+        // Up to nsym errors, so failures are compared too: both decoders succeed exactly when a
+        // codeword lies within t, and then agree on it
+        let mut rng = Rng::new(411);
+        // The solve is cubic, so full length gets fewer trials
+        for (nsym, n, trials) in [(1, 5, 60), (2, 10, 60), (4, 30, 60), (7, 40, 60), (16, 80, 40), (10, 255, 4)] {
+            for _ in 0..trials {
+                let message: Vec<u8> = (0..n - nsym).map(|_| rng.next_u64() as u8).collect();
+                let mut received = encode(&message, nsym, cached_generator(nsym));
+                let errors = rng.below(nsym + 1);
+                for pos in rng.distinct(errors, n) {
+                    received[pos] ^= rng.nonzero_byte();
+                }
+                let expected = decode(&received, nsym).map(|(message, mut positions)| {
+                    positions.sort_unstable();
+                    (message, positions)
+                });
+                let result = decode_bw(&received, nsym);
+                assert_eq!(result.as_ref().ok(), expected.as_ref().ok(), "nsym={} errors={}", nsym, errors);
+                if errors <= nsym / 2 {
+                    assert_eq!(result.unwrap().0, message);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_codeword_within_t_is_found() {
+        let (nsym, n) = (6, 20);
+        let message: Vec<u8> = (1..=14).collect();
+        let sent = encode(&message, nsym, cached_generator(nsym));
+        assert_eq!(decode_bw(&sent, nsym), Ok((message.clone(), vec![])));
+        let mut received = sent.clone();
+        for (pos, e) in [(0, 0x80), (9, 0x01), (n - 1, 0x3c)] {
+            received[pos] ^= e;
+        }
+        assert_eq!(decode_bw(&received, nsym), Ok((message, vec![0, 9, n - 1])));
+    }
+
+    #[test]
+    fn test_singular_system() {
+        // Two errors against t = 1: the 6 equations in k + 2t = 6 unknowns are square but have
+        // no solution, the coefficient matrix being singular
+        let nsym = 2;
+        let mut received = encode(&[1, 2, 3, 4], nsym, cached_generator(nsym));
+        received[0] ^= 1;
+        received[1] ^= 1;
+        assert_eq!(decode_bw(&received, nsym), Err(RsError::SingularSystem { rank: 5, unknowns: 6 }));
+        assert!(decode(&received, nsym).is_err());
+        assert!(matches!(decode_bw(&received, 6), Err(RsError::InvalidParameters(_))));
+        assert_eq!(decode_bw(&received[..1], nsym), Err(RsError::CodewordTooShort));
+    }
+}
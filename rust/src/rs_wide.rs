@@ -0,0 +1,418 @@
+// Reed-Solomon over GF(2^m) with u16 symbols (m <= 16), for wide-symbol memory codes and the
+// 10- and 12-bit symbol codes of NAND flash controllers
+// Same conventions as rs.rs: systematic [data | parity], position 0 = highest power of x,
+// generator roots alpha^0 .. alpha^(nsym-1)
+// Packed byte layout (pack_bits / unpack_bits): symbols are concatenated into one big-endian
+// bit stream, symbol i in stream bits i*m .. (i+1)*m, most significant bit first; stream bit j
+// is bit 7 - j % 8 of byte j / 8. A final partial byte is filled from its top bit down and its
+// unused low bits are written as zero and ignored on unpacking. So a byte error touches at most
+// two symbols when m >= 8 (up to 8 / m + 1 when m < 8).
+
+use crate::gf2m::Gf2m;
+use crate::rs::batch::map_blocks;
+use crate::rs::{berlekamp_massey_in, RsError};
+
+/// Per-block decode outcome of WideRs::decode_batch, as rs::BlockResult
+pub type WideBlockResult = Result<(Vec<u16>, Vec<usize>), RsError>;
+
+/// RS(n, n - nsym) over GF(2^m)
+pub struct WideRs {
+    field: Gf2m,
+    n: usize,
+    nsym: usize,
+    generator: Vec<u16>, // generator[i] = coefficient of x^i
+}
+
+impl WideRs {
+    /// Returns None unless 1 <= nsym < n <= 2^m - 1 for a supported m
+    pub fn new(m: u32, n: usize, nsym: usize) -> Option<Self> {
+        let field = Gf2m::with_default_poly(m)?;
+        if nsym == 0 || nsym >= n || n > field.order() {
+            return None;
+        }
+        let mut generator = vec![1u16];
+        for i in 0..nsym {
+            let root = field.exp(i);
+            let mut next = vec![0u16; generator.len() + 1];
+            for (d, &coef) in generator.iter().enumerate() {
+                next[d] ^= field.mul(coef, root);
+                next[d + 1] ^= coef;
+            }
+            generator = next;
+        }
+        Some(Self { field, n, nsym, generator })
+    }
+
+    pub fn field(&self) -> &Gf2m {
+        &self.field
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn k(&self) -> usize {
+        self.n - self.nsym
+    }
+
+    pub fn nsym(&self) -> usize {
+        self.nsym
+    }
+
+    /// Systematic encode of k symbols, each below 2^m
+    pub fn encode(&self, message: &[u16]) -> Vec<u16> {
+        assert_eq!(message.len(), self.k(), "message must be k={} symbols", self.k());
+        assert!(message.iter().all(|&s| (s as usize) <= self.field.order()), "symbols must fit in m={} bits", self.field.m());
+        let (k, nsym) = (self.k(), self.nsym);
+        let mut codeword = vec![0u16; self.n];
+        codeword[..k].copy_from_slice(message);
+        for i in 0..k {
+            let coef = codeword[i];
+            if coef != 0 {
+                for j in 1..=nsym {
+                    codeword[i + j] ^= self.field.mul(self.generator[nsym - j], coef);
+                }
+            }
+        }
+        codeword[..k].copy_from_slice(message);
+        codeword
+    }
+
+    /// Syndromes S_j = r(alpha^j), j = 0..nsym-1
+    pub fn syndromes(&self, codeword: &[u16]) -> Vec<u16> {
+        let n = codeword.len();
+        (0..self.nsym)
+            .map(|j| {
+                codeword.iter().enumerate().fold(0u16, |s, (idx, &sym)| {
+                    s ^ self.field.mul(sym, self.field.exp(j * (n - 1 - idx)))
+                })
+            })
+            .collect()
+    }
+
+    /// Decode correcting up to nsym/2 symbol errors
+    pub fn decode(&self, codeword: &[u16]) -> Result<(Vec<u16>, Vec<usize>), RsError> {
+        self.decode_limited(codeword, self.nsym / 2)
+    }
+
+    /// Decode, refusing to apply more than max_errors corrections
+    /// Used for single-symbol-correct / double-symbol-detect operation
+    pub fn decode_limited(&self, codeword: &[u16], max_errors: usize) -> Result<(Vec<u16>, Vec<usize>), RsError> {
+        let fixes = self.errata(codeword, &[], max_errors)?;
+        Ok(self.apply(codeword, &fixes))
+    }
+
+    /// Errors-and-erasures decode: e errors plus h erasures are corrected when 2e + h <= nsym
+    /// Reports the positions that changed, so an erasure whose symbol was right is not listed.
+    pub fn decode_errata(&self, codeword: &[u16], erasures: &[usize]) -> Result<(Vec<u16>, Vec<usize>), RsError> {
+        let fixes = self.errata(codeword, erasures, self.nsym / 2)?;
+        Ok(self.apply(codeword, &fixes))
+    }
+
+    /// (position, error value) of every symbol decode_errata changes, in position order
+    pub fn corrections(&self, codeword: &[u16], erasures: &[usize]) -> Result<Vec<(usize, u16)>, RsError> {
+        self.errata(codeword, erasures, self.nsym / 2)
+    }
+
+    /// Encode every message, on the rayon pool when `parallel` is set (see rs::batch)
+    pub fn encode_batch(&self, messages: &[&[u16]], parallel: bool) -> Vec<Vec<u16>> {
+        map_blocks(messages, parallel, |message| self.encode(message))
+    }
+
+    /// decode_errata on every codeword with the same erasures, in input order
+    pub fn decode_batch(&self, codewords: &[&[u16]], erasures: &[usize], parallel: bool) -> Vec<WideBlockResult> {
+        map_blocks(codewords, parallel, |codeword| self.decode_errata(codeword, erasures))
+    }
+
+    fn apply(&self, codeword: &[u16], fixes: &[(usize, u16)]) -> (Vec<u16>, Vec<usize>) {
+        let mut message = codeword[..self.k()].to_vec();
+        for &(pos, magnitude) in fixes.iter().filter(|&&(pos, _)| pos < self.k()) {
+            message[pos] ^= magnitude;
+        }
+        (message, fixes.iter().map(|&(pos, _)| pos).collect())
+    }
+
+    /// The corrections for up to max_errors errors besides the erasures, as in rs::decode_errata:
+    /// BM runs on the Forney syndromes and Chien/Forney on the errata locator
+    fn errata(&self, codeword: &[u16], erasures: &[usize], max_errors: usize) -> Result<Vec<(usize, u16)>, RsError> {
+        let n = codeword.len();
+        if n != self.n {
+            return Err(RsError::CodewordTooShort);
+        }
+        let mut seen = vec![false; n];
+        for &pos in erasures {
+            if pos >= n || seen[pos] {
+                return Err(RsError::InvalidErasure);
+            }
+            seen[pos] = true;
+        }
+        let h = erasures.len();
+        if h > self.nsym {
+            return Err(RsError::TooManyErrors);
+        }
+        let syndromes = self.syndromes(codeword);
+        if syndromes.iter().all(|&s| s == 0) {
+            return Ok(vec![]);
+        }
+
+        let order = self.field.order();
+        let locator = |pos: usize| self.field.exp((n - 1 - pos) % order);
+        let eval = |poly: &[u16], x: u16| poly.iter().rev().fold(0u16, |acc, &c| self.field.mul(acc, x) ^ c);
+
+        // Erasure locator Gamma(x) = prod (1 + X_i x); coefficients h.. of S(x) * Gamma(x) are
+        // the Forney syndromes, which no longer see the erasures
+        let mut gamma = vec![1u16];
+        for &pos in erasures {
+            let x = locator(pos);
+            gamma.push(0);
+            for j in (1..gamma.len()).rev() {
+                gamma[j] ^= self.field.mul(gamma[j - 1], x);
+            }
+        }
+        let modified: Vec<u16> = (h..self.nsym).map(|i| gamma.iter().enumerate().fold(0u16, |acc, (j, &g)| acc ^ self.field.mul(syndromes[i - j], g))).collect();
+        let sigma = berlekamp_massey_in(&self.field, &modified);
+        let num_errors = sigma.len() - 1;
+        if num_errors == 0 && h == 0 {
+            return Err(RsError::TrivialLocator);
+        }
+        if num_errors > max_errors.min(self.nsym / 2) || 2 * num_errors + h > self.nsym {
+            return Err(RsError::TooManyErrors);
+        }
+
+        // Errata locator psi = sigma * Gamma
+        let mut psi = vec![0u16; sigma.len() + gamma.len() - 1];
+        for (i, &a) in sigma.iter().enumerate() {
+            for (j, &b) in gamma.iter().enumerate() {
+                psi[i + j] ^= self.field.mul(a, b);
+            }
+        }
+
+        // Chien search
+        let positions: Vec<usize> = (0..n).filter(|&pos| eval(&psi, self.field.exp(order - (n - 1 - pos) % order)) == 0).collect();
+        if positions.len() != psi.len() - 1 {
+            return Err(RsError::ChienSearchFailed);
+        }
+
+        // Forney: Omega(x) = S(x) * psi(x) mod x^nsym, e_j = X_j * Omega(X_j^-1) / psi'(X_j^-1)
+        let mut omega = vec![0u16; self.nsym];
+        for (i, o) in omega.iter_mut().enumerate() {
+            for (j, &pj) in psi.iter().enumerate().take(i + 1) {
+                *o ^= self.field.mul(syndromes[i - j], pj);
+            }
+        }
+        let mut psi_prime = vec![0u16; psi.len()];
+        for i in (1..psi.len()).step_by(2) {
+            psi_prime[i - 1] = psi[i];
+        }
+
+        let mut fixes = Vec::with_capacity(positions.len());
+        let mut corrected = codeword.to_vec();
+        for &pos in &positions {
+            let x_exp = (n - 1 - pos) % order;
+            let x_j_inv = self.field.exp(order - x_exp);
+            let denom = eval(&psi_prime, x_j_inv);
+            if denom == 0 {
+                return Err(RsError::VerificationFailed);
+            }
+            let magnitude = self.field.mul(locator(pos), self.field.div(eval(&omega, x_j_inv), denom));
+            corrected[pos] ^= magnitude;
+            if magnitude != 0 {
+                fixes.push((pos, magnitude));
+            }
+        }
+
+        if self.syndromes(&corrected).iter().any(|&s| s != 0) {
+            return Err(RsError::VerificationFailed);
+        }
+        Ok(fixes)
+    }
+}
+
+/// Bytes pack_bits produces for `count` m-bit symbols, ceil(count * m / 8)
+pub fn packed_len(count: usize, m: u32) -> usize {
+    (count * m as usize).div_ceil(8)
+}
+
+/// Pack m-bit symbols into bytes, MSB first (layout at the top of this file)
+/// Panics unless 1 <= m <= 16 and every symbol fits in m bits.
+pub fn pack_bits(symbols: &[u16], m: u32) -> Vec<u8> {
+    assert!((1..=16).contains(&m), "m must be 1..=16");
+    let mut bytes = vec![0u8; packed_len(symbols.len(), m)];
+    let (mut acc, mut bits, mut out) = (0u32, 0u32, 0usize);
+    for &symbol in symbols {
+        assert!(symbol as u32 >> m == 0, "symbol {:#x} does not fit in m={} bits", symbol, m);
+        acc = acc << m | symbol as u32;
+        bits += m;
+        while bits >= 8 {
+            bits -= 8;
+            bytes[out] = (acc >> bits) as u8;
+            out += 1;
+        }
+        acc &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        bytes[out] = (acc << (8 - bits)) as u8;
+    }
+    bytes
+}
+
+/// Unpack the first `count` m-bit symbols of a pack_bits buffer; bits past them are ignored
+/// Panics unless 1 <= m <= 16 and bytes holds at least packed_len(count, m) bytes.
+pub fn unpack_bits(bytes: &[u8], m: u32, count: usize) -> Vec<u16> {
+    assert!((1..=16).contains(&m), "m must be 1..=16");
+    assert!(bytes.len() >= packed_len(count, m), "{} bytes cannot hold {} {}-bit symbols", bytes.len(), count, m);
+    let mut symbols = Vec::with_capacity(count);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    let mut input = bytes.iter();
+    while symbols.len() < count {
+        while bits < m {
+            acc = acc << 8 | *input.next().expect("length checked above") as u32;
+            bits += 8;
+        }
+        bits -= m;
+        symbols.push((acc >> bits) as u16 & ((1u32 << m) - 1) as u16);
+        acc &= (1 << bits) - 1;
+    }
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_gf2_16() {
+        let rs = WideRs::new(16, 36, 4).unwrap();
+        let message: Vec<u16> = (0..32).map(|i| (i * 0x1357 + 0xbeef) as u16).collect();
+        let mut codeword = rs.encode(&message);
+        assert!(rs.syndromes(&codeword).iter().all(|&s| s == 0));
+
+        codeword[0] ^= 0xffff;
+        codeword[35] ^= 0x0001;
+        let (decoded, positions) = rs.decode(&codeword).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(positions, vec![0, 35]);
+
+        assert_eq!(rs.decode_limited(&codeword, 1), Err(RsError::TooManyErrors));
+    }
+
+    #[test]
+    fn test_errata_gf2_16() {
+        // A 4 KB block: 2048 16-bit symbols, 48 of them parity
+        let rs = WideRs::new(16, 2048, 48).unwrap();
+        let mut rng = crate::rs::sim::Rng::new(419);
+        let message: Vec<u16> = (0..rs.k()).map(|_| rng.next_u64() as u16).collect();
+        let codeword = rs.encode(&message);
+        // Every split with 2e + h = nsym, errata near both ends
+        for (errors, erasures) in [(24usize, 0usize), (10, 28), (1, 46), (0, 48)] {
+            let mut received = codeword.clone();
+            let mut hit: Vec<usize> = (0..(errors + erasures).div_ceil(2)).chain(2048 - (errors + erasures) / 2..2048).collect();
+            for &pos in &hit {
+                received[pos] ^= (rng.next_u64() as u16).max(1);
+            }
+            let erased: Vec<usize> = hit.iter().copied().skip(errors).collect();
+            let (decoded, positions) = rs.decode_errata(&received, &erased).unwrap();
+            assert_eq!(decoded, message, "errors={} erasures={}", errors, erasures);
+            hit.sort_unstable();
+            assert_eq!(positions, hit);
+            let fixes = rs.corrections(&received, &erased).unwrap();
+            assert!(fixes.iter().all(|&(pos, magnitude)| received[pos] ^ magnitude == codeword[pos]));
+        }
+        // Erasures that were right are not reported, and a clean word with erasures is clean
+        assert_eq!(rs.decode_errata(&codeword, &[0, 2047]).unwrap(), (message.clone(), vec![]));
+        let mut received = codeword.clone();
+        received[5] ^= 1;
+        assert_eq!(rs.decode_errata(&received, &[5, 6]).unwrap().1, vec![5]);
+        assert_eq!(rs.decode_errata(&codeword, &[1, 1]), Err(RsError::InvalidErasure));
+        assert_eq!(rs.decode_errata(&codeword, &[2048]), Err(RsError::InvalidErasure));
+        assert_eq!(rs.decode_errata(&codeword[1..], &[]), Err(RsError::CodewordTooShort));
+
+        let blocks: Vec<&[u16]> = vec![&message, &message];
+        let encoded = rs.encode_batch(&blocks, true);
+        assert_eq!(encoded, vec![codeword.clone(), codeword.clone()]);
+        let mut bad = codeword.clone();
+        for pos in 0..25 {
+            bad[pos * 81] ^= 0x8000;
+        }
+        let results = rs.decode_batch(&[&codeword, &bad], &[], true);
+        assert_eq!(results[0], Ok((message, vec![])));
+        assert!(results[1].is_err());
+    }
+
+    #[test]
+    fn test_matches_gf256_codec() {
+        // With m = 8 and the same primitive polynomial this is the byte codec
+        let rs = WideRs::new(8, 20, 6).unwrap();
+        let message: Vec<u8> = (0..14).map(|i| i * 17 + 3).collect();
+        let wide: Vec<u16> = message.iter().map(|&b| b as u16).collect();
+        let expected = crate::rs::encode(&message, 6, &crate::rs::build_generator(6));
+        let actual: Vec<u8> = rs.encode(&wide).iter().map(|&s| s as u8).collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_rejects_invalid_parameters() {
+        assert!(WideRs::new(16, 36, 0).is_none());
+        assert!(WideRs::new(16, 36, 36).is_none());
+        assert!(WideRs::new(8, 256, 4).is_none());
+    }
+
+    #[test]
+    fn test_pack_layout() {
+        // 0x3ff, 0x001, 0x2aa as 10-bit symbols: 1111111111 0000000001 1010101010 + 2 pad bits
+        assert_eq!(pack_bits(&[0x3ff, 0x001, 0x2aa], 10), [0xff, 0xc0, 0x1a, 0xa8]);
+        assert_eq!(unpack_bits(&[0xff, 0xc0, 0x1a, 0xab], 10, 3), [0x3ff, 0x001, 0x2aa], "pad bits ignored");
+        assert_eq!(pack_bits(&[0xabc], 12), [0xab, 0xc0]);
+        assert_eq!(pack_bits(&[0x12, 0x34], 8), [0x12, 0x34]);
+        assert_eq!((packed_len(3, 10), packed_len(4, 10), packed_len(2, 12), packed_len(0, 12)), (4, 5, 3, 0));
+
+        let mut rng = crate::rs::sim::Rng::new(404);
+        for m in 1..=16u32 {
+            // Counts on both sides of whole-byte boundaries
+            for count in 0..=17 {
+                let symbols: Vec<u16> = (0..count).map(|_| (rng.next_u64() & ((1 << m) - 1)) as u16).collect();
+                let bytes = pack_bits(&symbols, m);
+                assert_eq!(bytes.len(), packed_len(count, m));
+                if !(count * m as usize).is_multiple_of(8) {
+                    assert_eq!(bytes.last().unwrap() & ((1 << (8 - count * m as usize % 8)) - 1), 0, "pad bits zero, m={}", m);
+                }
+                assert_eq!(unpack_bits(&bytes, m, count), symbols, "m={} count={}", m, count);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn test_pack_rejects_wide_symbols() {
+        pack_bits(&[0x400], 10);
+    }
+
+    #[test]
+    fn test_flash_codes_through_packed_bytes() {
+        // A byte error in the packed page straddles two symbols unless it lines up with one
+        let mut rng = crate::rs::sim::Rng::new(0x404);
+        for (m, n, nsym) in [(10u32, 103, 8), (12, 255, 16)] {
+            let rs = WideRs::new(m, n, nsym).unwrap();
+            let mask = (1u16 << m) - 1;
+            for _ in 0..50 {
+                let message: Vec<u16> = (0..rs.k()).map(|_| rng.next_u64() as u16 & mask).collect();
+                let mut page = pack_bits(&rs.encode(&message), m);
+                // nsym / 4 byte errors: at most 2 symbols each, within t = nsym / 2
+                let mut hit = vec![];
+                for byte in rng.distinct(nsym / 4, page.len() - 1) {
+                    page[byte] ^= rng.nonzero_byte();
+                    hit.extend(byte * 8 / m as usize..=(byte * 8 + 7) / m as usize);
+                }
+                let received = unpack_bits(&page, m, n);
+                let (decoded, positions) = rs.decode(&received).unwrap();
+                assert_eq!(decoded, message);
+                assert!(positions.iter().all(|p| hit.contains(p)), "m={} {:?} {:?}", m, positions, hit);
+            }
+            // A byte straddling symbols 0 and 1 corrupts both
+            let message = vec![0u16; rs.k()];
+            let mut page = pack_bits(&rs.encode(&message), m);
+            page[1] ^= 0xff;
+            assert_eq!(rs.decode(&unpack_bits(&page, m, n)).unwrap(), (message, vec![0, 1]), "m={}", m);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+// SEC-DED (72,64) code for the classic DRAM ECC model
+// Default H-matrix is Hsiao's odd-weight-column construction: 8 unit columns for the check bits,
+// all 56 weight-3 columns plus 8 weight-5 columns for the data bits, with every row of weight 26.
+// Bit positions: 0..=63 are data bits (bit i of the u64), 64..=71 are check bits 0..=7.
+
+const NO_COLUMN: u8 = 0xFF;
+
+/// Outcome of a SEC-DED decode
+/// A decoder cannot see undetected errors: a 3+ bit error that aliases onto a valid
+/// codeword or a single-bit syndrome comes back as NoError or CorrectedBit, and can only
+/// be classified as silent corruption by comparing against the written data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecdedResult {
+    NoError,
+    /// Single-bit error at the given position (0..=71), corrected
+    CorrectedBit(u8),
+    /// Even-weight nonzero syndrome: a double-bit error
+    DetectedDouble,
+    /// Odd-weight syndrome matching no column: a 3+ bit error
+    DetectedUncorrectable,
+}
+
+impl SecdedResult {
+    /// Data word after applying the correction, None if the error was only detected
+    pub fn corrected_data(self, data: u64) -> Option<u64> {
+        match self {
+            SecdedResult::NoError => Some(data),
+            SecdedResult::CorrectedBit(pos) if pos < 64 => Some(data ^ (1u64 << pos)),
+            SecdedResult::CorrectedBit(_) => Some(data), // check bit error, data is intact
+            SecdedResult::DetectedDouble | SecdedResult::DetectedUncorrectable => None,
+        }
+    }
+}
+
+/// Parity-check matrix as 8-bit columns, one per data bit (check-bit columns are the unit vectors)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HMatrix {
+    data_cols: [u8; 64],
+    syndrome_to_pos: [u8; 256],
+}
+
+impl HMatrix {
+    /// Standard Hsiao (72,64) layout
+    pub const fn hsiao() -> Self {
+        let mut data_cols = [0u8; 64];
+        let mut count = 0usize;
+        // All 56 weight-3 columns in increasing order
+        let mut col = 0u32;
+        while col < 256 {
+            if (col as u8).count_ones() == 3 {
+                data_cols[count] = col as u8;
+                count += 1;
+            }
+            col += 1;
+        }
+        // 8 weight-5 columns: rotations of 0b0001_1111 give each row exactly 5 more ones
+        let mut r = 0u32;
+        while r < 8 {
+            data_cols[count] = 0x1Fu8.rotate_left(r);
+            count += 1;
+            r += 1;
+        }
+        Self { data_cols, syndrome_to_pos: build_syndrome_table(&data_cols) }
+    }
+
+    /// Custom layout for modeling alternative H-matrices
+    /// Columns must be nonzero, distinct, and distinct from the check-bit unit columns (the SEC condition)
+    pub fn from_columns(data_cols: [u8; 64]) -> Result<Self, &'static str> {
+        let mut seen = [false; 256];
+        for bit in 0..8 {
+            seen[1usize << bit] = true;
+        }
+        for &col in &data_cols {
+            if col == 0 {
+                return Err("H-matrix column must be nonzero");
+            }
+            if seen[col as usize] {
+                return Err("H-matrix columns must be distinct (including the check-bit unit columns)");
+            }
+            seen[col as usize] = true;
+        }
+        Ok(Self { data_cols, syndrome_to_pos: build_syndrome_table(&data_cols) })
+    }
+
+    /// Column for data bit i
+    pub fn data_column(&self, i: usize) -> u8 {
+        self.data_cols[i]
+    }
+
+    /// Check bits for a data word
+    pub fn check_bits(&self, data: u64) -> u8 {
+        let mut check = 0u8;
+        let mut bits = data;
+        while bits != 0 {
+            let i = bits.trailing_zeros() as usize;
+            check ^= self.data_cols[i];
+            bits &= bits - 1;
+        }
+        check
+    }
+
+    /// Classify a received (data, check) pair
+    pub fn decode(&self, data: u64, check: u8) -> SecdedResult {
+        let syndrome = check ^ self.check_bits(data);
+        if syndrome == 0 {
+            return SecdedResult::NoError;
+        }
+        match self.syndrome_to_pos[syndrome as usize] {
+            NO_COLUMN if syndrome.count_ones().is_multiple_of(2) => SecdedResult::DetectedDouble,
+            NO_COLUMN => SecdedResult::DetectedUncorrectable,
+            pos => SecdedResult::CorrectedBit(pos),
+        }
+    }
+}
+
+impl Default for HMatrix {
+    fn default() -> Self {
+        Self::hsiao()
+    }
+}
+
+const fn build_syndrome_table(data_cols: &[u8; 64]) -> [u8; 256] {
+    let mut table = [NO_COLUMN; 256];
+    let mut i = 0usize;
+    while i < 64 {
+        table[data_cols[i] as usize] = i as u8;
+        i += 1;
+    }
+    let mut bit = 0usize;
+    while bit < 8 {
+        table[1usize << bit] = 64 + bit as u8;
+        bit += 1;
+    }
+    table
+}
+
+// Global default matrix (computed at compile time)
+pub static HSIAO: HMatrix = HMatrix::hsiao();
+
+/// Encode with the default Hsiao matrix: returns (data, check bits)
+pub fn encode64(data: u64) -> (u64, u8) {
+    (data, HSIAO.check_bits(data))
+}
+
+/// Decode with the default Hsiao matrix
+pub fn decode64(data: u64, check: u8) -> SecdedResult {
+    HSIAO.decode(data, check)
+}
+
+/// Flip bit `pos` (0..=71) of a (data, check) pair
+pub fn flip_bit(data: u64, check: u8, pos: u8) -> (u64, u8) {
+    if pos < 64 {
+        (data ^ (1u64 << pos), check)
+    } else {
+        (data, check ^ (1u8 << (pos - 64)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs::sim::Rng;
+
+    #[test]
+    fn test_hsiao_structure() {
+        let h = HMatrix::hsiao();
+        let mut row_weights = [0u32; 8];
+        for i in 0..64 {
+            let col = h.data_column(i);
+            assert_eq!(col.count_ones() % 2, 1, "Hsiao columns are odd weight");
+            for (row, w) in row_weights.iter_mut().enumerate() {
+                *w += ((col >> row) & 1) as u32;
+            }
+        }
+        // 26 data ones per row (21 from weight-3 columns, 5 from weight-5)
+        assert_eq!(row_weights, [26; 8]);
+        assert!(HMatrix::from_columns(h.data_cols).is_ok());
+    }
+
+    #[test]
+    fn test_corrects_every_single_bit_error() {
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..2000 {
+            let data = rng.next_u64();
+            let (data, check) = encode64(data);
+            assert_eq!(decode64(data, check), SecdedResult::NoError);
+            for pos in 0..72u8 {
+                let (d, c) = flip_bit(data, check, pos);
+                let result = decode64(d, c);
+                assert_eq!(result, SecdedResult::CorrectedBit(pos));
+                assert_eq!(result.corrected_data(d), Some(data));
+            }
+        }
+    }
+
+    #[test]
+    fn test_detects_every_double_bit_error() {
+        let mut rng = Rng::new(0xdead_beef);
+        for _ in 0..200_000 {
+            let data = rng.next_u64();
+            let (data, check) = encode64(data);
+            let a = rng.below(72) as u8;
+            let b = (a + 1 + rng.below(71) as u8) % 72;
+            let (d, c) = flip_bit(data, check, a);
+            let (d, c) = flip_bit(d, c, b);
+            assert_eq!(decode64(d, c), SecdedResult::DetectedDouble, "bits {} {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_custom_matrix_validation() {
+        let mut cols = HSIAO.data_cols;
+        cols[5] = cols[6];
+        assert!(HMatrix::from_columns(cols).is_err());
+        cols[5] = 0x01; // collides with check bit 0
+        assert!(HMatrix::from_columns(cols).is_err());
+
+        // An even-weight column is a legal SEC layout but loses DED for some pairs
+        cols[5] = 0x03;
+        let h = HMatrix::from_columns(cols).unwrap();
+        let check = h.check_bits(0);
+        let result = h.decode(0, check ^ 0x01 ^ 0x02);
+        assert_eq!(result, SecdedResult::CorrectedBit(5));
+    }
+}
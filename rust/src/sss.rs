@@ -0,0 +1,215 @@
+// Shamir secret sharing over GF(256) with primitive polynomial 0x11d, one byte at a time
+// Each secret byte is the constant term of its own random polynomial of degree at most k - 1, and
+// share x (1..=n) holds every polynomial's value at x. Any k shares give the secret back by
+// Lagrange interpolation at 0; k - 1 or fewer say nothing about it. Products involving secret
+// bytes (coefficients, share values) go through gf_mul_ct, not the log/exp tables, whose lookups
+// are indexed by their operands and so leak them through cache timing. The x indices are public,
+// so the Lagrange weights use the tables.
+// Not interchangeable with tools over the AES field (0x11b): the same split gives other shares.
+
+use std::fmt;
+
+use crate::gf256::{gf_div, gf_mul, gf_mul_ct};
+use crate::sha256::sha256;
+
+/// One share: the secret's polynomials evaluated at x, tagged with the threshold k
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Share {
+    pub x: u8,
+    pub k: u8,
+    pub y: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SssError {
+    /// k must be in 1..=n
+    Threshold { k: u8, n: usize },
+    /// combine needs k shares
+    TooFewShares { have: usize, need: usize },
+    /// Two shares with one x index
+    DuplicateX(u8),
+    /// Shares of one secret all have its length
+    LengthMismatch { x: u8, len: usize, expected: usize },
+    /// Shares of one secret all carry its threshold
+    ThresholdMismatch { x: u8, k: u8, expected: u8 },
+}
+
+impl fmt::Display for SssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SssError::Threshold { k, n } => write!(f, "threshold k={} must be in 1..={} (n)", k, n),
+            SssError::TooFewShares { have, need } => write!(f, "{} shares, need k={}", have, need),
+            SssError::DuplicateX(x) => write!(f, "two shares with x={}", x),
+            SssError::LengthMismatch { x, len, expected } => write!(f, "share x={} has length {}, expected {}", x, len, expected),
+            SssError::ThresholdMismatch { x, k, expected } => write!(f, "share x={} has threshold k={}, expected {}", x, k, expected),
+        }
+    }
+}
+
+impl std::error::Error for SssError {}
+
+/// Split `secret` into n shares, any k of which recover it, with coefficients from `rng_seed`
+/// The seed is the only randomness: the shares are reproducible from it, and are exactly as
+/// secret as it is (64 bits at most, however long the secret). That suits test vectors and
+/// modeling; for real secrets use split_with and a cryptographic RNG. The seed is expanded by
+/// SHA-256 in counter mode, so the stream is the same on every platform.
+pub fn split(secret: &[u8], k: u8, n: u8, rng_seed: u64) -> Result<Vec<Share>, SssError> {
+    let mut stream = SeededStream { seed: rng_seed, counter: 0, block: [0; 32], used: 32 };
+    split_with(secret, k, n, |buf| stream.fill(buf))
+}
+
+/// split with coefficients from `fill`, which must fill its buffer with uniform random bytes
+/// unknown to anyone who may see fewer than k shares, e.g. from the operating system's CSPRNG.
+/// Anything less (a counter, a seeded simulation Rng) lets shares below the threshold leak.
+pub fn split_with(secret: &[u8], k: u8, n: u8, mut fill: impl FnMut(&mut [u8])) -> Result<Vec<Share>, SssError> {
+    if k == 0 || k > n {
+        return Err(SssError::Threshold { k, n: n as usize });
+    }
+    let mut shares: Vec<Share> = (1..=n).map(|x| Share { x, k, y: Vec::with_capacity(secret.len()) }).collect();
+    // coefficients[0] is the secret byte, the rest fresh randomness for each byte
+    let mut coefficients = vec![0u8; k as usize];
+    for &byte in secret {
+        coefficients[0] = byte;
+        fill(&mut coefficients[1..]);
+        for share in &mut shares {
+            share.y.push(coefficients.iter().rev().fold(0u8, |acc, &c| gf_mul_ct(acc, share.x) ^ c));
+        }
+    }
+    Ok(shares)
+}
+
+/// The secret from k or more shares of it; the first k are interpolated and the rest only checked
+/// for consistent x, k and length. A share altered in its y bytes is not detected: the result is
+/// then simply wrong.
+pub fn combine(shares: &[Share]) -> Result<Vec<u8>, SssError> {
+    let Some(first) = shares.first() else {
+        return Err(SssError::TooFewShares { have: 0, need: 1 });
+    };
+    let (k, len) = (first.k, first.y.len());
+    if k == 0 {
+        return Err(SssError::Threshold { k, n: shares.len() });
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.k != k {
+            return Err(SssError::ThresholdMismatch { x: share.x, k: share.k, expected: k });
+        }
+        if share.y.len() != len {
+            return Err(SssError::LengthMismatch { x: share.x, len: share.y.len(), expected: len });
+        }
+        if shares[..i].iter().any(|s| s.x == share.x) {
+            return Err(SssError::DuplicateX(share.x));
+        }
+    }
+    if shares.len() < k as usize {
+        return Err(SssError::TooFewShares { have: shares.len(), need: k as usize });
+    }
+    let used = &shares[..k as usize];
+    // Lagrange weight of share j at 0: prod over m != j of x_m / (x_m - x_j), subtraction being xor
+    let weights: Vec<u8> = used
+        .iter()
+        .map(|j| used.iter().filter(|m| m.x != j.x).fold(1u8, |acc, m| gf_mul(acc, gf_div(m.x, m.x ^ j.x))))
+        .collect();
+    Ok((0..len).map(|i| used.iter().zip(&weights).fold(0u8, |acc, (share, &w)| acc ^ gf_mul_ct(share.y[i], w))).collect())
+}
+
+/// SHA-256(seed || counter), little-endian, block after block
+struct SeededStream {
+    seed: u64,
+    counter: u64,
+    block: [u8; 32],
+    used: usize,
+}
+
+impl SeededStream {
+    fn fill(&mut self, buf: &mut [u8]) {
+        for byte in buf {
+            if self.used == self.block.len() {
+                let mut input = [0u8; 16];
+                input[..8].copy_from_slice(&self.seed.to_le_bytes());
+                input[8..].copy_from_slice(&self.counter.to_le_bytes());
+                self.block = sha256(&input);
+                self.counter += 1;
+                self.used = 0;
+            }
+            *byte = self.block[self.used];
+            self.used += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let secret = b"correct horse battery staple".to_vec();
+        for (k, n) in [(1, 1), (2, 3), (3, 5), (5, 5), (10, 255)] {
+            let shares = split(&secret, k, n, 421).unwrap();
+            assert_eq!(shares.len(), n as usize);
+            assert!(shares.iter().enumerate().all(|(i, s)| (s.x as usize, s.k, s.y.len()) == (i + 1, k, secret.len())));
+            // Exactly k from either end and in either order, and all n
+            assert_eq!(combine(&shares[..k as usize]).unwrap(), secret);
+            assert_eq!(combine(&shares[(n - k) as usize..]).unwrap(), secret);
+            let mut reversed = shares.clone();
+            reversed.reverse();
+            assert_eq!(combine(&reversed[..k as usize]).unwrap(), secret);
+            assert_eq!(combine(&shares).unwrap(), secret);
+            if k > 1 {
+                assert_eq!(combine(&shares[..k as usize - 1]), Err(SssError::TooFewShares { have: k as usize - 1, need: k as usize }));
+            }
+        }
+        assert_eq!(combine(&split(b"", 2, 3, 1).unwrap()).unwrap(), b"");
+    }
+
+    #[test]
+    fn test_below_threshold_reveals_nothing() {
+        // With k = 2 one share is the secret plus a uniform random multiple of x, so over many
+        // seeds share 1 of secret 0x00 and of secret 0xff takes every byte value
+        let mut seen = [[false; 256]; 2];
+        for seed in 0..4000 {
+            for (i, secret) in [0x00u8, 0xff].into_iter().enumerate() {
+                seen[i][split(&[secret], 2, 3, seed).unwrap()[0].y[0] as usize] = true;
+            }
+        }
+        assert!(seen.iter().all(|s| s.iter().all(|&b| b)));
+    }
+
+    #[test]
+    fn test_known_answer() {
+        // f(x) = 0x53 + 0x10 x + x^2 by hand: no product exceeds 8 bits, so no reduction by 0x11d
+        // f(1) = 0x53 ^ 0x10 ^ 0x01, f(2) = 0x53 ^ 0x20 ^ 0x04, f(3) = 0x53 ^ 0x30 ^ 0x05
+        let share = |x, y| Share { x, k: 3, y: vec![y] };
+        assert_eq!(combine(&[share(1, 0x42), share(2, 0x77), share(3, 0x66)]).unwrap(), [0x53]);
+        assert_eq!(combine(&[share(3, 0x66), share(1, 0x42), share(2, 0x77)]).unwrap(), [0x53]);
+        // split's output for a fixed seed, pinning the seeded stream and the evaluation; with k = 2
+        // byte i of share x is secret[i] ^ a_i x, a_i byte i of SHA-256(421u64 || 0u64) (little-endian)
+        let shares = split(b"ECC", 2, 3, 421).unwrap();
+        let ys: Vec<&[u8]> = shares.iter().map(|s| &s.y[..]).collect();
+        assert_eq!(ys, [&[0x83, 0x50, 0x82][..], &[0xd4, 0x65, 0xdc][..], &[0x12, 0x76, 0x1d][..]]);
+    }
+
+    #[test]
+    fn test_split_with() {
+        // Coefficients 0x10, 0x01 for every byte reproduce the known-answer shares
+        let shares = split_with(&[0x53], 3, 4, |buf| buf.copy_from_slice(&[0x10, 0x01])).unwrap();
+        let ys: Vec<u8> = shares.iter().map(|s| s.y[0]).collect();
+        assert_eq!(ys[..3], [0x42, 0x77, 0x66]);
+        assert_eq!(combine(&shares[1..]).unwrap(), [0x53]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(split(b"x", 0, 3, 1), Err(SssError::Threshold { k: 0, n: 3 }));
+        assert_eq!(split(b"x", 4, 3, 1), Err(SssError::Threshold { k: 4, n: 3 }));
+        let shares = split(b"secret", 3, 5, 7).unwrap();
+        assert_eq!(combine(&[]), Err(SssError::TooFewShares { have: 0, need: 1 }));
+        assert_eq!(combine(&[shares[0].clone(), shares[2].clone(), shares[0].clone()]), Err(SssError::DuplicateX(1)));
+        let mut short = shares.clone();
+        short[1].y.pop();
+        assert_eq!(combine(&short), Err(SssError::LengthMismatch { x: 2, len: 5, expected: 6 }));
+        let other = split(b"secret", 2, 5, 7).unwrap();
+        assert_eq!(combine(&[shares[0].clone(), other[1].clone(), shares[2].clone()]), Err(SssError::ThresholdMismatch { x: 2, k: 2, expected: 3 }));
+        assert_eq!(SssError::TooFewShares { have: 2, need: 3 }.to_string(), "2 shares, need k=3");
+    }
+}
@@ -0,0 +1,481 @@
+// Decode-stage instrumentation in the shape of the tracing crate (`trace` feature)
+// Spans mark the stages of a decode (syndromes, bm, chien, forney, verify) inside a "decode"
+// span, events carry what each stage found, and a failed decode ends with a "decode.failed"
+// event holding the RsError. Like crc and sha256 this is kept in-house, so the feature adds no
+// dependency. Without the feature span! and event! expand to an `if false` block (span! also
+// to a zero-sized guard): the field expressions are type-checked but never evaluated, and the
+// optimiser drops them. With it, a call first loads one atomic counting the installed
+// subscribers that want its level, and builds its fields only when there is one.
+// Levels: Debug for the stage spans, their summaries and the failure; Trace for the
+// per-iteration Berlekamp-Massey state and the full syndrome and magnitude vectors. The
+// inversionless variant's "ibm.iteration" events carry its whole register state (discrepancy,
+// gamma, k and both polynomials), for cycle-by-cycle comparison with an RTL model.
+
+#[cfg(feature = "trace")]
+pub use imp::*;
+
+/// Enter a span until the returned guard drops: `let _span = span!(Debug, "bm", nsym = n);`
+#[cfg(feature = "trace")]
+macro_rules! span {
+    ($level:ident, $name:literal $(, $key:ident = $value:expr)* $(,)?) => {
+        $crate::trace::enter($crate::trace::Level::$level, $name, || vec![$((stringify!($key), $crate::trace::Value::from($value))),*])
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! span {
+    ($level:ident, $name:literal $(, $key:ident = $value:expr)* $(,)?) => {{
+        if false {
+            $(let _ = &$value;)*
+        }
+        $crate::trace::NoSpan
+    }};
+}
+
+/// The guard span! gives without the feature
+#[cfg(not(feature = "trace"))]
+#[doc(hidden)]
+pub struct NoSpan;
+
+/// Emit an event inside the current span: `event!(Trace, "bm.iteration", r = r, l = l);`
+#[cfg(feature = "trace")]
+macro_rules! event {
+    ($level:ident, $name:literal $(, $key:ident = $value:expr)* $(,)?) => {
+        $crate::trace::emit($crate::trace::Level::$level, $name, || vec![$((stringify!($key), $crate::trace::Value::from($value))),*])
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! event {
+    ($level:ident, $name:literal $(, $key:ident = $value:expr)* $(,)?) => {
+        if false {
+            $(let _ = &$value;)*
+        }
+    };
+}
+
+pub(crate) use {event, span};
+
+/// A field element as an event records it: u8 and u16 elements (the crate's fields) and u32 as
+/// their value, any other element type as u32::MAX
+pub(crate) fn elem<T: Copy + 'static>(value: T) -> u32 {
+    let value: &dyn core::any::Any = &value;
+    (value.downcast_ref::<u8>().map(|&v| u32::from(v)))
+        .or_else(|| value.downcast_ref::<u16>().map(|&v| u32::from(v)))
+        .or_else(|| value.downcast_ref::<u32>().copied())
+        .unwrap_or(u32::MAX)
+}
+
+#[cfg(feature = "trace")]
+mod imp {
+    use crate::rs::RsError;
+    use std::cell::RefCell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    /// Verbosity of a span or event; Trace is the more verbose
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum Level {
+        Debug,
+        Trace,
+    }
+
+    /// A span or event field
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Value {
+        U64(u64),
+        I64(i64),
+        Bytes(Vec<u8>),
+        /// Polynomial coefficients over any field, x^0 first
+        Poly(Vec<u32>),
+        Positions(Vec<usize>),
+        Error(RsError),
+    }
+
+    impl From<u64> for Value {
+        fn from(v: u64) -> Self {
+            Value::U64(v)
+        }
+    }
+
+    impl From<u32> for Value {
+        fn from(v: u32) -> Self {
+            Value::U64(v as u64)
+        }
+    }
+
+    impl From<usize> for Value {
+        fn from(v: usize) -> Self {
+            Value::U64(v as u64)
+        }
+    }
+
+    impl From<isize> for Value {
+        fn from(v: isize) -> Self {
+            Value::I64(v as i64)
+        }
+    }
+
+    impl From<Vec<u32>> for Value {
+        fn from(v: Vec<u32>) -> Self {
+            Value::Poly(v)
+        }
+    }
+
+    impl From<&[u8]> for Value {
+        fn from(v: &[u8]) -> Self {
+            Value::Bytes(v.to_vec())
+        }
+    }
+
+    impl From<&[usize]> for Value {
+        fn from(v: &[usize]) -> Self {
+            Value::Positions(v.to_vec())
+        }
+    }
+
+    impl From<RsError> for Value {
+        fn from(v: RsError) -> Self {
+            Value::Error(v)
+        }
+    }
+
+    pub type Fields = [(&'static str, Value)];
+
+    /// Receiver of spans and events; installed per thread by with_default or for every thread by
+    /// set_global_default
+    pub trait Subscriber: Send + Sync {
+        /// Most verbose level wanted; anything above it is never built
+        fn max_level(&self) -> Level;
+        fn enter(&self, level: Level, name: &'static str, fields: &Fields);
+        /// Leaves the innermost span entered on this thread, `name`
+        fn exit(&self, name: &'static str);
+        fn event(&self, level: Level, name: &'static str, fields: &Fields);
+    }
+
+    /// Installed subscribers by level: WANTED[l] counts those whose max_level is l or above
+    static WANTED: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+    static GLOBAL: OnceLock<Arc<dyn Subscriber>> = OnceLock::new();
+
+    thread_local! {
+        static CURRENT: RefCell<Option<Arc<dyn Subscriber>>> = const { RefCell::new(None) };
+    }
+
+    fn count(subscriber: &dyn Subscriber, delta: isize) {
+        for wanted in &WANTED[..=subscriber.max_level() as usize] {
+            if delta > 0 {
+                wanted.fetch_add(1, Ordering::Relaxed);
+            } else {
+                wanted.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Whether any subscriber on any thread wants `level`; the whole cost of a filtered call
+    #[inline]
+    pub fn enabled(level: Level) -> bool {
+        WANTED[level as usize].load(Ordering::Relaxed) > 0
+    }
+
+    /// Subscriber for every thread without its own; fails, handing it back, if one is already set
+    pub fn set_global_default(subscriber: Arc<dyn Subscriber>) -> Result<(), Arc<dyn Subscriber>> {
+        GLOBAL.set(subscriber.clone())?;
+        count(&*subscriber, 1);
+        Ok(())
+    }
+
+    /// Run f with `subscriber` receiving this thread's spans and events
+    pub fn with_default<R>(subscriber: Arc<dyn Subscriber>, f: impl FnOnce() -> R) -> R {
+        /// Restores the previous subscriber, even if f panics
+        struct Restore(Option<Arc<dyn Subscriber>>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                if let Some(current) = CURRENT.with(|c| c.replace(previous)) {
+                    count(&*current, -1);
+                }
+            }
+        }
+
+        count(&*subscriber, 1);
+        let _restore = Restore(CURRENT.with(|c| c.replace(Some(subscriber))));
+        f()
+    }
+
+    /// This thread's subscriber if it wants `level`
+    fn dispatcher(level: Level) -> Option<Arc<dyn Subscriber>> {
+        CURRENT
+            .with(|c| c.borrow().clone())
+            .or_else(|| GLOBAL.get().cloned())
+            .filter(|subscriber| level <= subscriber.max_level())
+    }
+
+    /// Exits its span when dropped
+    pub struct Entered {
+        subscriber: Arc<dyn Subscriber>,
+        name: &'static str,
+    }
+
+    impl Drop for Entered {
+        fn drop(&mut self) {
+            self.subscriber.exit(self.name);
+        }
+    }
+
+    /// What span! expands to: None, without building fields, unless a subscriber wants `level`
+    #[inline]
+    pub fn enter(level: Level, name: &'static str, fields: impl FnOnce() -> Vec<(&'static str, Value)>) -> Option<Entered> {
+        if !enabled(level) {
+            return None;
+        }
+        let subscriber = dispatcher(level)?;
+        subscriber.enter(level, name, &fields());
+        Some(Entered { subscriber, name })
+    }
+
+    /// What event! expands to
+    #[inline]
+    pub fn emit(level: Level, name: &'static str, fields: impl FnOnce() -> Vec<(&'static str, Value)>) {
+        if !enabled(level) {
+            return;
+        }
+        if let Some(subscriber) = dispatcher(level) {
+            subscriber.event(level, name, &fields());
+        }
+    }
+
+    /// A span entered or an event emitted, as a Recorder saw it
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Record {
+        /// Enclosing spans, outermost first
+        pub parents: Vec<&'static str>,
+        pub name: &'static str,
+        pub level: Level,
+        pub is_span: bool,
+        pub fields: Vec<(&'static str, Value)>,
+    }
+
+    impl Record {
+        /// "decode/bm" for the bm span inside decode, "decode/bm/bm.iteration" for its events
+        pub fn path(&self) -> String {
+            let mut path: Vec<&str> = self.parents.clone();
+            path.push(self.name);
+            path.join("/")
+        }
+
+        pub fn field(&self, key: &str) -> Option<&Value> {
+            self.fields.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+        }
+    }
+
+    /// Subscriber that keeps everything up to its level, in order, for tests and offline analysis
+    /// Meant for with_default on one thread: spans from several threads would interleave.
+    #[derive(Debug)]
+    pub struct Recorder {
+        level: Level,
+        state: Mutex<(Vec<&'static str>, Vec<Record>)>,
+    }
+
+    impl Recorder {
+        pub fn new(level: Level) -> Self {
+            Self { level, state: Mutex::new((Vec::new(), Vec::new())) }
+        }
+
+        pub fn records(&self) -> Vec<Record> {
+            self.state.lock().unwrap().1.clone()
+        }
+
+        pub fn clear(&self) {
+            self.state.lock().unwrap().1.clear();
+        }
+
+        fn push(&self, level: Level, name: &'static str, fields: &Fields, is_span: bool) {
+            let mut state = self.state.lock().unwrap();
+            let (stack, records) = &mut *state;
+            records.push(Record { parents: stack.clone(), name, level, is_span, fields: fields.to_vec() });
+            if is_span {
+                stack.push(name);
+            }
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn max_level(&self) -> Level {
+            self.level
+        }
+
+        fn enter(&self, level: Level, name: &'static str, fields: &Fields) {
+            self.push(level, name, fields, true);
+        }
+
+        fn exit(&self, name: &'static str) {
+            let exited = self.state.lock().unwrap().0.pop();
+            debug_assert_eq!(exited, Some(name));
+        }
+
+        fn event(&self, level: Level, name: &'static str, fields: &Fields) {
+            self.push(level, name, fields, false);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use super::*;
+    use crate::rs::decoder::RsDecoder;
+    use crate::gf256::gf_mul;
+    use crate::rs::{cached_generator, decode_errata, encode, BmVariant, RsError};
+    use std::sync::Arc;
+
+    fn record(level: Level, f: impl FnOnce()) -> Vec<Record> {
+        let recorder = Arc::new(Recorder::new(level));
+        with_default(recorder.clone(), f);
+        recorder.records()
+    }
+
+    #[test]
+    fn test_span_hierarchy() {
+        let nsym = 8;
+        let mut received = encode(&[7u8; 20], nsym, cached_generator(nsym));
+        for (pos, e) in [(3, 0x11), (17, 0x5a), (25, 0x80)] {
+            received[pos] ^= e;
+        }
+        let mut decoder = RsDecoder::new(nsym, received.len());
+        let records = record(Level::Trace, || assert!(decoder.decode_with(&received).is_ok()));
+        let spans: Vec<String> = records.iter().filter(|r| r.is_span).map(Record::path).collect();
+        assert_eq!(spans, ["decode", "decode/syndromes", "decode/bm", "decode/chien", "decode/forney", "decode/verify"]);
+        assert_eq!(records[0].field("n"), Some(&Value::U64(28)));
+
+        // One BM iteration per syndrome, inside the bm span, with L reaching the error count
+        let iterations: Vec<&Record> = records.iter().filter(|r| r.name == "bm.iteration").collect();
+        assert_eq!(iterations.len(), nsym);
+        assert!(iterations.iter().all(|r| r.path() == "decode/bm/bm.iteration" && r.level == Level::Trace));
+        let locator = records.iter().find(|r| r.name == "bm.locator").unwrap();
+        assert_eq!(locator.field("errors"), Some(&Value::U64(3)));
+        let roots = records.iter().find(|r| r.name == "chien.roots").unwrap();
+        let Some(Value::Positions(mut positions)) = roots.field("positions").cloned() else { panic!("{:?}", roots) };
+        positions.sort_unstable();
+        assert_eq!(positions, [3, 17, 25]);
+        assert!(records.iter().any(|r| r.path() == "decode/forney/forney.magnitudes"));
+        assert!(records.iter().any(|r| r.path() == "decode/verify/verify" && r.field("clean") == Some(&Value::U64(1))));
+
+        // Debug leaves out the per-iteration and vector events but keeps the stages
+        let records = record(Level::Debug, || assert!(decoder.decode_with(&received).is_ok()));
+        assert!(records.iter().all(|r| r.level == Level::Debug));
+        assert_eq!(records.iter().filter(|r| r.is_span).count(), 6);
+        assert!(!records.iter().any(|r| r.name == "bm.iteration" || r.name == "syndromes" && !r.is_span));
+
+        // The erasure path has the same stages, with the erasures on the decode span
+        let records = record(Level::Debug, || assert!(decode_errata(&received, nsym, &[3, 4]).is_ok()));
+        assert_eq!(records[0].field("erasures"), Some(&Value::Positions(vec![3, 4])));
+        assert_eq!(records.iter().filter(|r| r.is_span).map(Record::path).collect::<Vec<_>>(), spans);
+
+        // Nothing reaches a thread without a subscriber
+        let recorder = Arc::new(Recorder::new(Level::Trace));
+        with_default(recorder.clone(), || std::thread::scope(|s| s.spawn(|| decoder.decode_with(&received).is_ok()).join().unwrap()));
+        assert!(recorder.records().is_empty());
+    }
+
+    #[test]
+    fn test_inversionless_register_state() {
+        // One ibm.iteration per syndrome with the registers each step started from; replaying the
+        // recurrence from the recorded state gives the next step's lambda, and the last step's
+        // update gives the locator the decoder used
+        let nsym = 8;
+        let mut received = encode(&[9u8; 20], nsym, cached_generator(nsym));
+        for (pos, e) in [(1, 0x21), (12, 0x07)] {
+            received[pos] ^= e;
+        }
+        let mut decoder = RsDecoder::new(nsym, received.len()).with_bm(BmVariant::Inversionless);
+        let records = record(Level::Trace, || assert_eq!(decoder.decode_with(&received).unwrap().1, [1, 12]));
+        let iterations: Vec<&Record> = records.iter().filter(|r| r.name == "ibm.iteration").collect();
+        assert_eq!(iterations.len(), nsym);
+        assert!(iterations.iter().all(|r| r.path() == "decode/bm/ibm.iteration"));
+        let state = |r: &Record| {
+            let (Some(&Value::U64(delta)), Some(&Value::U64(gamma)), Some(Value::Poly(lambda)), Some(Value::Poly(b))) =
+                (r.field("discrepancy"), r.field("gamma"), r.field("lambda"), r.field("b"))
+            else {
+                panic!("{:?}", r)
+            };
+            // gamma * lambda + delta * x * b
+            let mut next = vec![0u8; lambda.len().max(b.len() + 1)];
+            for (i, &c) in lambda.iter().enumerate() {
+                next[i] ^= gf_mul(gamma as u8, c as u8);
+            }
+            for (i, &c) in b.iter().enumerate() {
+                next[i + 1] ^= gf_mul(delta as u8, c as u8);
+            }
+            (next, lambda.iter().map(|&c| c as u8).collect::<Vec<u8>>())
+        };
+        for pair in iterations.windows(2) {
+            let (next, _) = state(pair[0]);
+            let (_, lambda) = state(pair[1]);
+            assert_eq!(next[..lambda.len()], lambda[..]);
+            assert!(next[lambda.len()..].iter().all(|&c| c == 0));
+        }
+        assert_eq!(iterations[0].field("k"), Some(&Value::I64(0)));
+        let (mut last, _) = state(iterations[nsym - 1]);
+        while last.last() == Some(&0) {
+            last.pop();
+        }
+        let locator = records.iter().find(|r| r.name == "bm.locator").unwrap();
+        assert_eq!(locator.field("locator"), Some(&Value::Bytes(last)));
+    }
+
+    #[test]
+    fn test_fixed_work_is_data_independent() {
+        // The crate has no operation counter; the stages and per-iteration events stand in for
+        // one. With fixed_work a clean word, a correctable one and two kinds of failure all run
+        // the same stages and nsym BM iterations, where the normal decoder stops at the syndromes
+        // for the clean word
+        let nsym = 8;
+        let sent = encode(&[5u8; 24], nsym, cached_generator(nsym));
+        let mut weights = vec![];
+        for errors in [0, 1, 4, 5, 9] {
+            let mut received = sent.clone();
+            for pos in 0..errors {
+                received[3 * pos] ^= 0x40 | pos as u8;
+            }
+            weights.push(received);
+        }
+        let stages = |fixed_work: bool, received: &[u8]| {
+            let mut decoder = RsDecoder::new(nsym, received.len()).with_fixed_work(fixed_work);
+            let records = record(Level::Trace, || {
+                let _ = decoder.decode_with(received);
+            });
+            records.iter().filter(|r| r.name != "decode.failed").map(|r| r.path()).collect::<Vec<_>>()
+        };
+        let first = stages(true, &weights[0]);
+        assert_eq!(first.iter().filter(|p| p.as_str() == "decode/bm/bm.iteration").count(), nsym);
+        for stage in ["decode/chien", "decode/verify"] {
+            assert!(first.iter().any(|p| p == stage), "{}", stage);
+        }
+        for received in &weights[1..] {
+            assert_eq!(stages(true, received), first);
+        }
+        assert!(!stages(false, &weights[0]).iter().any(|p| p == "decode/bm"));
+        assert_ne!(stages(false, &weights[1]), stages(false, &weights[4]));
+    }
+
+    #[test]
+    fn test_failure_event() {
+        // Five errors against t = 4: the decode fails and says how, inside the decode span
+        let nsym = 8;
+        let mut received = encode(&[0u8; 20], nsym, cached_generator(nsym));
+        for pos in [0, 5, 9, 14, 22] {
+            received[pos] ^= 0x3c;
+        }
+        let mut decoder = RsDecoder::new(nsym, received.len());
+        let mut result = None;
+        let records = record(Level::Debug, || result = Some(decoder.decode_with(&received).map(|_| ()).unwrap_err()));
+        let error = result.unwrap();
+        let failed: Vec<&Record> = records.iter().filter(|r| r.name == "decode.failed").collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!((failed[0].path(), failed[0].field("error")), ("decode/decode.failed".to_string(), Some(&Value::Error(error))));
+        assert!(matches!(error, RsError::TooManyErrors | RsError::ChienSearchFailed), "{:?}", error);
+
+        let records = record(Level::Debug, || assert_eq!(decoder.decode_with(&received[..4]), Err(RsError::CodewordTooShort)));
+        assert_eq!(records.last().unwrap().field("error"), Some(&Value::Error(RsError::CodewordTooShort)));
+    }
+}
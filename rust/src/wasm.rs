@@ -0,0 +1,96 @@
+// JavaScript bindings through wasm-bindgen (`wasm` feature)
+// The same RsCodec the Python module wraps: Uint8Array in and out, and failures thrown as Error
+// carrying the RsError message. Build with
+// `wasm-pack build --target web -- --no-default-features --features wasm`; README.md has the JS side.
+
+use wasm_bindgen::prelude::*;
+
+use crate::rs::{RsCodec, RsError};
+
+fn js_error(e: RsError) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+fn codec(nsym: usize, nsize: usize) -> Result<RsCodec, JsError> {
+    RsCodec::new(nsym, nsize).map_err(js_error)
+}
+
+/// Encode a k = nsize - nsym byte message into an nsize-byte codeword [message | parity]
+///
+/// ```js
+/// const codeword = encode(32, 255, message); // message: Uint8Array(223)
+/// ```
+#[wasm_bindgen]
+pub fn encode(nsym: usize, nsize: usize, message: &[u8]) -> Result<Vec<u8>, JsError> {
+    let codec = codec(nsym, nsize)?;
+    if message.len() != codec.k() {
+        return Err(JsError::new(&format!("message length {} does not match expected k={} for (n={}, nsym={})", message.len(), codec.k(), nsize, nsym)));
+    }
+    Ok(codec.encode(message))
+}
+
+/// decode's result: the k-byte message and the corrected positions, ascending
+#[wasm_bindgen]
+pub struct Decoded {
+    message: Vec<u8>,
+    positions: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl Decoded {
+    /// Uint8Array of the corrected message
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> Vec<u8> {
+        self.message.clone()
+    }
+
+    /// Uint32Array of the codeword positions that were corrected
+    #[wasm_bindgen(getter)]
+    pub fn positions(&self) -> Vec<u32> {
+        self.positions.clone()
+    }
+}
+
+/// Decode an nsize-byte codeword, correcting up to nsym / 2 symbol errors
+/// Throws an Error naming the failure (e.g. "too many errors") when the block cannot be decoded.
+///
+/// ```js
+/// const { message, positions } = decode(32, 255, codeword);
+/// ```
+#[wasm_bindgen]
+pub fn decode(nsym: usize, nsize: usize, codeword: &[u8]) -> Result<Decoded, JsError> {
+    let codec = codec(nsym, nsize)?;
+    if codeword.len() != nsize {
+        return Err(JsError::new(&format!("codeword length {} does not match n={}", codeword.len(), nsize)));
+    }
+    let (message, positions) = codec.decode(codeword).map_err(js_error)?;
+    Ok(Decoded { message, positions: positions.into_iter().map(|p| p as u32).collect() })
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    #[wasm_bindgen_test]
+    fn test_round_trip_with_errors() {
+        let (nsym, nsize) = (32, 255);
+        let message: Vec<u8> = (0..nsize - nsym).map(|i| (i * 37 + 11) as u8).collect();
+        let codeword = encode(nsym, nsize, &message).unwrap();
+        let mut received = codeword.clone();
+        // t = 16 errors, spread over data and parity
+        let errors: Vec<u32> = (0..16).map(|i| i * 15 + 3).collect();
+        for &pos in &errors {
+            received[pos as usize] ^= 0x5a;
+        }
+        let decoded = decode(nsym, nsize, &received).unwrap();
+        assert_eq!(decoded.message(), message);
+        assert_eq!(decoded.positions(), errors);
+
+        received[1] ^= 0xff;
+        assert!(decode(nsym, nsize, &received).is_err());
+        assert!(encode(nsym, nsize, &message[1..]).is_err());
+        assert!(encode(0, nsize, &message).is_err());
+    }
+}
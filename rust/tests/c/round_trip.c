@@ -0,0 +1,58 @@
+/* Round trip through the C API: encode, inject nsym / 2 errors, decode, then one error too many.
+ * Built and run by tests/capi.rs; exits nonzero with a message on the first mismatch. */
+#include <stdio.h>
+#include <string.h>
+
+#include "ecc_rs.h"
+
+#define NSYM 32
+#define NSIZE 255
+#define K (NSIZE - NSYM)
+
+static int fail(const char *what, int32_t status) {
+    fprintf(stderr, "%s: status %d: %s\n", what, (int)status, ecc_rs_last_error_message());
+    return 1;
+}
+
+int main(void) {
+    uint8_t message[K], codeword[NSIZE], decoded[K];
+    size_t positions[NSYM / 2], count = 0, i;
+    int32_t status;
+
+    for (i = 0; i < K; i++) {
+        message[i] = (uint8_t)(i * 37 + 11);
+    }
+    status = ecc_rs_encode(NSYM, NSIZE, message, K, codeword, sizeof codeword);
+    if (status != ECC_RS_STATUS_OK) {
+        return fail("encode", status);
+    }
+
+    /* Errors at 0, 15, ..., 225: every one in the message but the last, which hits parity */
+    for (i = 0; i < NSYM / 2; i++) {
+        codeword[i * 15] ^= (uint8_t)(0xa5 + i);
+    }
+    status = ecc_rs_decode(NSYM, NSIZE, codeword, NSIZE, decoded, sizeof decoded, positions, NSYM / 2, &count);
+    if (status != ECC_RS_STATUS_OK) {
+        return fail("decode", status);
+    }
+    if (memcmp(decoded, message, K) != 0 || count != NSYM / 2) {
+        return fail("decode result", status);
+    }
+    for (i = 0; i < count; i++) {
+        if (positions[i] != i * 15) {
+            return fail("decode positions", status);
+        }
+    }
+
+    codeword[250] ^= 0xff;
+    status = ecc_rs_decode(NSYM, NSIZE, codeword, NSIZE, decoded, sizeof decoded, positions, NSYM / 2, &count);
+    if (status != ECC_RS_STATUS_UNCORRECTABLE || strlen(ecc_rs_last_error_message()) == 0) {
+        return fail("decode past t", status);
+    }
+    status = ecc_rs_encode(NSYM, NSIZE, NULL, K, codeword, sizeof codeword);
+    if (status != ECC_RS_STATUS_NULL_POINTER) {
+        return fail("encode NULL", status);
+    }
+    printf("ok\n");
+    return 0;
+}
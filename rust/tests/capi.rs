@@ -0,0 +1,41 @@
+// Builds the static library with only the `capi` feature, compiles tests/c/round_trip.c against it
+// and include/ecc_rs.h with the platform C compiler (found through cc), and runs the program.
+// The committed header must match the one build.rs generates from src/ffi.rs.
+#![cfg(all(feature = "capi", unix))]
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_c_round_trip() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // A target dir of its own: the outer cargo still holds target/, and no pyo3 to link
+    let target_dir = root.join("target/capi");
+    let build = Command::new(env!("CARGO"))
+        .current_dir(root)
+        .args(["rustc", "--lib", "--no-default-features", "--features", "capi", "--crate-type", "staticlib", "--target-dir"])
+        .arg(&target_dir)
+        .output()
+        .unwrap();
+    assert!(build.status.success(), "building the static library failed: {}", String::from_utf8_lossy(&build.stderr));
+
+    let target = env!("ECC_RS_TARGET");
+    let compiler = cc::Build::new().target(target).host(target).opt_level(0).cargo_metadata(false).get_compiler();
+    let exe = target_dir.join("round_trip");
+    let mut command = compiler.to_command();
+    command.arg(root.join("tests/c/round_trip.c")).arg("-I").arg(root.join("include")).arg("-o").arg(&exe);
+    // The system libraries std needs, per rustc --print native-static-libs
+    command.arg(target_dir.join("debug/lib_rs.a")).args(["-lpthread", "-ldl", "-lm"]);
+    assert!(command.status().unwrap().success(), "compiling round_trip.c failed");
+
+    let output = Command::new(&exe).output().unwrap();
+    assert!(output.status.success(), "round_trip failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
+
+#[test]
+fn test_committed_header_is_current() {
+    let committed = std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("include/ecc_rs.h")).unwrap();
+    let generated = std::fs::read_to_string(env!("ECC_RS_HEADER")).unwrap();
+    assert!(committed == generated, "include/ecc_rs.h is stale; regenerate it with ECC_RS_WRITE_HEADER=1 cargo build --features capi");
+}
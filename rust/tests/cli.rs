@@ -0,0 +1,104 @@
+// rs-tool end to end: encode a file, flip bytes in the output as dd conv=notrunc would, then
+// verify, decode and repair and check the exit codes and --json summaries
+#![cfg(feature = "cli")]
+
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use _rs::rs::decode_file;
+use _rs::rs::container::HEADER_BLOCK_LEN;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ecc_model_cli_{}_{}", std::process::id(), name))
+}
+
+fn rs_tool(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rs-tool")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+/// XOR `mask` into `len` bytes at `offset`, in place
+fn flip(path: &PathBuf, offset: u64, len: usize, mask: u8) {
+    let bytes = fs::read(path).unwrap();
+    let flipped: Vec<u8> = bytes[offset as usize..][..len].iter().map(|b| b ^ mask).collect();
+    let mut file = OpenOptions::new().write(true).open(path).unwrap();
+    file.seek(SeekFrom::Start(offset)).unwrap();
+    file.write_all(&flipped).unwrap();
+}
+
+#[test]
+fn test_round_trip_and_repair() {
+    let (src, enc, dec) = (temp_path("src"), temp_path("enc"), temp_path("dec"));
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+    fs::write(&src, &data).unwrap();
+    let [src_s, enc_s, dec_s] = [&src, &enc, &dec].map(|p| p.to_str().unwrap().to_owned());
+
+    let out = rs_tool(&["encode", "--nsym", "16", "--nsize", "255", "--interleave", "4", "--json", &src_s, &enc_s]);
+    assert!(out.status.success(), "{:?}", out);
+    // ceil(100000 / (4 * 239)) = 105 groups of 4
+    assert_eq!(stdout(&out).trim(), "{\"command\":\"encode\",\"nsym\":16,\"nsize\":255,\"interleave\":4,\"length\":100000,\"blocks\":420}");
+    let out = rs_tool(&["verify", &enc_s]);
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(stdout(&out).trim(), "420 blocks, all clean");
+
+    // A 32-byte burst in group 10 (8 symbols in each of blocks 40..44, t = 8) and one byte in block 100
+    let group = 4 * 255;
+    flip(&enc, (HEADER_BLOCK_LEN + 10 * group + 77) as u64, 32, 0xff);
+    flip(&enc, (HEADER_BLOCK_LEN + 25 * group + 4 * 9) as u64, 1, 0x01);
+    let out = rs_tool(&["verify", "--json", &enc_s]);
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(stdout(&out).trim(), "{\"command\":\"verify\",\"blocks\":420,\"dirty_blocks\":[40,41,42,43,100],\"header_intact\":true}");
+    let out = rs_tool(&["decode", "--json", &enc_s, &dec_s]);
+    assert_eq!(out.status.code(), Some(0), "{:?}", out);
+    assert_eq!(
+        stdout(&out).trim(),
+        "{\"command\":\"decode\",\"length\":100000,\"blocks\":420,\"corrected_blocks\":5,\"corrected_symbols\":33,\"failed_offsets\":[]}"
+    );
+    assert_eq!(fs::read(&dec).unwrap(), data);
+
+    // 36 more bytes in group 20: 9 per codeword, past t, so decode writes them through and fails
+    flip(&enc, (HEADER_BLOCK_LEN + 20 * group) as u64, 36, 0x5a);
+    let out = rs_tool(&["decode", &enc_s, &dec_s]);
+    assert_eq!(out.status.code(), Some(1));
+    let summary = stdout(&out);
+    assert!(summary.starts_with("decoded 100000 bytes from 420 blocks: 5 corrected (33 symbols), 4 unrecoverable"), "{}", summary);
+    assert!(summary.contains("at data offsets 19120,19359,19598,19837"), "{}", summary);
+
+    // The library reads what the tool wrote
+    let report = decode_file(&enc, &dec, None, 1).unwrap();
+    assert_eq!(report.failed_offsets, vec![80 * 239, 81 * 239, 82 * 239, 83 * 239]);
+
+    // A wiped leading header: verify flags it, repair restores it and the correctable blocks
+    flip(&enc, 0, HEADER_BLOCK_LEN, 0xff);
+    let out = rs_tool(&["verify", &enc_s]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stdout(&out).contains("header copies need repair: leading damaged, trailer clean"), "{:?}", out);
+    let out = rs_tool(&["repair", "--json", &enc_s]);
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(
+        stdout(&out).trim(),
+        "{\"command\":\"repair\",\"blocks\":420,\"repaired_blocks\":[40,41,42,43,100],\"unrecoverable_blocks\":[80,81,82,83],\"headers_rewritten\":1}"
+    );
+    let out = rs_tool(&["verify", "--json", &enc_s]);
+    assert_eq!(stdout(&out).trim(), "{\"command\":\"verify\",\"blocks\":420,\"dirty_blocks\":[80,81,82,83],\"header_intact\":true}");
+    for p in [src, enc, dec] {
+        fs::remove_file(p).ok();
+    }
+}
+
+#[test]
+fn test_usage_errors() {
+    for args in [&[][..], &["compress", "a", "b"], &["decode", "a"], &["decode", "--nsym", "8", "a", "b"], &["repair", "a", "b"], &["encode", "--nsym", "x", "a", "b"]] {
+        let out = rs_tool(args);
+        assert_eq!(out.status.code(), Some(2), "{:?}", args);
+        assert!(String::from_utf8_lossy(&out.stderr).contains("usage:"), "{:?}", args);
+    }
+    let out = rs_tool(&["verify", temp_path("missing").to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("rs-tool: I/O error"));
+}
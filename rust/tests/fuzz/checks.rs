@@ -0,0 +1,54 @@
+// Properties the fuzz targets check, shared with tests/fuzz_regressions.rs so every fixture
+// there replays exactly what the fuzzer ran. Each takes the raw fuzz input; any Err from the
+// library is acceptable, a panic or a failed assertion is a bug.
+
+// Each fuzz target compiles this file but calls only one of the checks
+#![allow(dead_code)]
+
+use _rs::gf256::{gf_mul, GF};
+use _rs::rs::{cached_generator, calc_syndromes, decode, decode_errata, encode, validate_params};
+
+/// Positions where the re-encoded message differs from the received codeword
+fn changed(message: &[u8], nsym: usize, codeword: &[u8]) -> Vec<usize> {
+    let corrected = encode(message, nsym, cached_generator(nsym));
+    (0..codeword.len()).filter(|&i| corrected[i] != codeword[i]).collect()
+}
+
+/// nsym, then the codeword; an Ok decode must be a valid codeword within nsym / 2 corrections
+pub fn decode_input(data: &[u8]) {
+    let Some((&nsym, codeword)) = data.split_first() else { return };
+    let nsym = nsym as usize;
+    if let Ok((message, positions)) = decode(codeword, nsym) {
+        assert!(validate_params(nsym, codeword.len()).is_ok());
+        assert!(positions.len() <= nsym / 2);
+        assert_eq!(changed(&message, nsym, codeword), positions);
+    }
+}
+
+/// nsym, an erasure count h, h one-byte erasure positions (so out-of-range and repeated ones
+/// occur), then the codeword
+pub fn decode_errata_input(data: &[u8]) {
+    let [nsym, h, rest @ ..] = data else { return };
+    let (nsym, h) = (*nsym as usize, *h as usize);
+    if rest.len() < h {
+        return;
+    }
+    let (erasures, codeword) = rest.split_at(h);
+    let erasures: Vec<usize> = erasures.iter().map(|&e| e as usize).collect();
+    if let Ok((message, positions)) = decode_errata(codeword, nsym, &erasures) {
+        assert_eq!(changed(&message, nsym, codeword), positions);
+    }
+}
+
+/// nsym as a little-endian u16 below 512, past the vector kernels' 256-row limit, then the
+/// codeword; every syndrome must match Horner's rule at its root
+pub fn calc_syndromes_input(data: &[u8]) {
+    let [lo, hi, codeword @ ..] = data else { return };
+    let nsym = u16::from_le_bytes([*lo, *hi]) as usize % 512;
+    let syndromes = calc_syndromes(codeword, nsym);
+    assert_eq!(syndromes.len(), nsym);
+    for (j, &s) in syndromes.iter().enumerate() {
+        let root = GF.exp[j % 255];
+        assert_eq!(s, codeword.iter().fold(0u8, |acc, &byte| gf_mul(acc, root) ^ byte), "j={}", j);
+    }
+}
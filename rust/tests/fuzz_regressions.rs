@@ -0,0 +1,39 @@
+// Replays the inputs under tests/fuzz/<target>/ through the fuzz targets' checks
+// A crash found by cargo fuzz (rust/fuzz) is fixed and then checked in there, named for what it
+// exercises, so it stays covered by a plain cargo test.
+#![cfg(feature = "std")]
+
+#[path = "fuzz/checks.rs"]
+mod checks;
+
+use std::fs;
+use std::path::Path;
+
+fn replay(target: &str, check: fn(&[u8])) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fuzz").join(target);
+    let mut count = 0;
+    for entry in fs::read_dir(&dir).unwrap_or_else(|e| panic!("{}: {}", dir.display(), e)) {
+        let path = entry.unwrap().path();
+        let data = fs::read(&path).unwrap();
+        if std::panic::catch_unwind(|| check(&data)).is_err() {
+            panic!("{} failed", path.display());
+        }
+        count += 1;
+    }
+    assert!(count > 0, "no fixtures in {}", dir.display());
+}
+
+#[test]
+fn test_decode_fixtures() {
+    replay("decode", checks::decode_input);
+}
+
+#[test]
+fn test_decode_errata_fixtures() {
+    replay("decode_errata", checks::decode_errata_input);
+}
+
+#[test]
+fn test_calc_syndromes_fixtures() {
+    replay("calc_syndromes", checks::calc_syndromes_input);
+}
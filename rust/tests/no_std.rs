@@ -0,0 +1,56 @@
+// Smoke test of the heap-free API from a no_std crate, run by the std test harness
+// Only core is in scope here, so this fails to compile if encode/decode need alloc. The library
+// itself is checked without std by cargo build --no-default-features --target thumbv7em-none-eabihf.
+#![no_std]
+
+use _rs::rs::{decode_in_place, encode_into, generator_into, RsCode, RsError};
+
+fn round_trip<const N: usize, const NSYM: usize, const K: usize>(seed: u8) {
+    let mut message = [0u8; K];
+    for (i, byte) in message.iter_mut().enumerate() {
+        *byte = seed.wrapping_mul(31).wrapping_add(i as u8);
+    }
+    let code = RsCode::<N, NSYM>::new();
+    let mut codeword = code.encode(&message);
+
+    let mut generator = [0u8; 256];
+    generator_into(NSYM, &mut generator);
+    let mut buffer = [0u8; N];
+    encode_into(&message, NSYM, &generator, &mut buffer).unwrap();
+    assert_eq!(buffer, codeword);
+
+    // NSYM / 2 errors spread over the codeword, parity included
+    for e in 0..NSYM / 2 {
+        codeword[(e * 7 + seed as usize) % N] ^= 0x5a ^ e as u8;
+    }
+    let (decoded, positions) = code.decode::<K>(&codeword).unwrap();
+    assert_eq!(decoded, message);
+    assert_eq!(positions.len(), NSYM / 2);
+
+    let mut slots = [0usize; NSYM];
+    let count = decode_in_place(&mut codeword, NSYM, &mut slots).unwrap();
+    assert_eq!(slots[..count], *positions.as_slice());
+    assert_eq!(codeword[..K], message);
+    assert_eq!(codeword, buffer);
+}
+
+#[test]
+fn test_round_trip() {
+    round_trip::<255, 32, 223>(1);
+    round_trip::<36, 4, 32>(2);
+    round_trip::<15, 6, 9>(3);
+}
+
+#[test]
+fn test_too_many_errors() {
+    let code = RsCode::<36, 4>::new();
+    let mut codeword = code.encode(&[9u8; 32]);
+    for pos in [0, 11, 35] {
+        codeword[pos] ^= 1;
+    }
+    let mut slots = [0usize; 2];
+    let received = codeword;
+    assert!(decode_in_place(&mut codeword, 4, &mut slots).is_err());
+    assert_eq!(codeword, received);
+    assert_eq!(decode_in_place(&mut codeword[..3], 4, &mut slots), Err(RsError::CodewordTooShort));
+}
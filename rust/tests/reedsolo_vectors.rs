@@ -0,0 +1,54 @@
+// Checks RsCodec against the reedsolo interop vectors in tests/vectors/reedsolo.txt
+// The vectors cover nsym in {2, 8, 16, 32} with fcr 0 and 1; tests/test_reedsolo_interop.py
+// generates them with reedsolo (the file header names its version) and re-checks them against it.
+#![cfg(feature = "std")]
+
+use _rs::rs::{CodeOptions, RsCodec};
+use std::fs;
+use std::path::Path;
+
+fn hex(field: &str) -> Vec<u8> {
+    (0..field.len()).step_by(2).map(|i| u8::from_str_radix(&field[i..i + 2], 16).unwrap()).collect()
+}
+
+fn numbers(field: &str) -> Vec<usize> {
+    if field == "-" {
+        return Vec::new();
+    }
+    field.split(',').map(|v| v.parse().unwrap()).collect()
+}
+
+fn codec(nsym: usize, fcr: usize, n: usize) -> RsCodec {
+    RsCodec::with_options(nsym, n, CodeOptions { fcr, ..Default::default() }).unwrap()
+}
+
+#[test]
+fn test_reedsolo_vectors() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vectors/reedsolo.txt");
+    let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let (mut encodes, mut decodes) = (0, 0);
+    for (number, line) in text.lines().enumerate().filter(|(_, l)| !l.starts_with('#') && !l.trim().is_empty()) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (nsym, fcr): (usize, usize) = (fields[1].parse().unwrap(), fields[2].parse().unwrap());
+        let data = hex(fields[3]);
+        let at = format!("line {}: nsym={} fcr={}", number + 1, nsym, fcr);
+        match fields[0] {
+            "encode" => {
+                assert_eq!(codec(nsym, fcr, data.len() + nsym).encode(&data), hex(fields[4]), "{}", at);
+                encodes += 1;
+            }
+            "decode" => {
+                let (erasures, positions) = (numbers(fields[4]), numbers(fields[6]));
+                let codec = codec(nsym, fcr, data.len());
+                let result = if erasures.is_empty() { codec.decode(&data) } else { codec.decode_errata(&data, &erasures) };
+                let (message, mut found) = result.unwrap_or_else(|e| panic!("{}: {}", at, e));
+                found.sort_unstable();
+                assert_eq!((message, found), (hex(fields[5]), positions), "{}", at);
+                decodes += 1;
+            }
+            kind => panic!("{}: unknown record {}", at, kind),
+        }
+    }
+    // Every (nsym, fcr, length) cell of the grid has an encode record and six decode records
+    assert_eq!((encodes, decodes), (32, 192));
+}
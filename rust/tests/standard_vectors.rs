@@ -0,0 +1,89 @@
+// Golden codewords from published standards in tests/vectors/standards.json
+// Unlike round trips these catch a conventions bug (field, root offset, symbol order) that
+// encode and decode would share. Each entry carries its code parameters; one still missing its
+// bytes has a "todo" instead, and its family's test stays ignored until it is filled in.
+#![cfg(all(feature = "std", feature = "sim"))]
+
+use _rs::rs::sim::Rng;
+use _rs::rs::{CodeOptions, RsCodec};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+struct Vector {
+    name: String,
+    codec: RsCodec,
+    message: Vec<u8>,
+    codeword: Vec<u8>,
+}
+
+fn hex(field: &str) -> Vec<u8> {
+    (0..field.len()).step_by(2).map(|i| u8::from_str_radix(&field[i..i + 2], 16).unwrap()).collect()
+}
+
+/// The vectors whose name starts with family; panics on an entry still marked todo
+fn load(family: &str) -> Vec<Vector> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vectors/standards.json");
+    let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let json: Value = serde_json::from_str(&text).unwrap();
+    let vectors: Vec<Vector> = json["vectors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|v| v["name"].as_str().unwrap().starts_with(family))
+        .map(|v| {
+            let name = v["name"].as_str().unwrap().to_string();
+            if let Some(todo) = v["todo"].as_str() {
+                panic!("{}: {}", name, todo);
+            }
+            let field = |key: &str| v[key].as_u64().unwrap_or_else(|| panic!("{}: no {}", name, key));
+            let options = CodeOptions { fcr: field("fcr") as usize, generator: field("generator") as u8, prim: field("prim") as u32 };
+            let codec = RsCodec::with_options(field("nsym") as usize, field("nsize") as usize, options).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            Vector { message: hex(v["message"].as_str().unwrap()), codeword: hex(v["codeword"].as_str().unwrap()), name, codec }
+        })
+        .collect();
+    assert!(!vectors.is_empty(), "no {} vectors", family);
+    vectors
+}
+
+/// Encode must reproduce each codeword exactly, and decode must undo seeded corruptions of it:
+/// t errors, nsym erasures, and every mix with 2e + f = nsym
+fn check(family: &str) {
+    for v in load(family) {
+        assert_eq!(v.codec.encode(&v.message), v.codeword, "{}: encode", v.name);
+        assert_eq!(v.codec.decode(&v.codeword).unwrap(), (v.message.clone(), vec![]), "{}: clean decode", v.name);
+        let (n, nsym) = (v.codec.n(), v.codec.nsym());
+        let mut rng = Rng::new(389);
+        for errors in 0..=nsym / 2 {
+            let erasures = nsym - 2 * errors;
+            for _ in 0..20 {
+                let positions = rng.distinct(errors + erasures, n);
+                let mut received = v.codeword.clone();
+                for &p in &positions {
+                    received[p] ^= rng.nonzero_byte();
+                }
+                let (message, mut corrected) = v.codec.decode_errata(&received, &positions[errors..]).unwrap_or_else(|e| panic!("{}: {} errors, {} erasures: {}", v.name, errors, erasures, e));
+                corrected.sort_unstable();
+                let mut expected = positions.clone();
+                expected.sort_unstable();
+                assert_eq!((message, corrected), (v.message.clone(), expected), "{}: {} errors, {} erasures", v.name, errors, erasures);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_qr_vectors() {
+    check("qr-");
+}
+
+#[test]
+fn test_dvb_vectors() {
+    check("dvb-");
+}
+
+#[test]
+#[ignore = "CCSDS frames use the dual basis, which has no preset yet; see the todo in standards.json"]
+fn test_ccsds_vectors() {
+    check("ccsds-");
+}
@@ -0,0 +1,83 @@
+// ecc-sweep end to end: a tiny grid from a spec file plus command-line overrides, checked for
+// schema, consistency with rs::sim, determinism and resumption after an interrupted run
+#![cfg(feature = "sim")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use _rs::rs::sim::{simulate_interleaved, ChannelModel};
+use _rs::rs::RsCodec;
+
+const HEADER: &str = "n,nsym,depth,channel,param,trials,seed,codewords,clean,ce,due,sdc";
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ecc_model_sweep_{}_{}", std::process::id(), name))
+}
+
+fn sweep(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ecc-sweep")).args(args).output().unwrap()
+}
+
+#[test]
+fn test_tiny_grid() {
+    let (spec, out, again) = (temp_path("spec.toml"), temp_path("out.csv"), temp_path("again.csv"));
+    fs::write(&spec, "# two codes, two depths\nn = [32, 64]\nnsym = 8\ndepth = [1, 4]\nser = [0.05]\nburst = [16]\ntrials = 50\n").unwrap();
+    let [spec_s, out_s, again_s] = [&spec, &out, &again].map(|p| p.to_str().unwrap().to_owned());
+    let _ = fs::remove_file(&out);
+    let _ = fs::remove_file(&again);
+
+    let output = sweep(&["--spec", &spec_s, "--seed", "417", "--quiet", &out_s]);
+    assert!(output.status.success(), "{:?}", output);
+    let csv = fs::read_to_string(&out).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], HEADER);
+    // 2 n x 1 nsym x 2 depths x 2 channel points, in grid order
+    assert_eq!(lines.len(), 1 + 8);
+    let keys: Vec<String> = lines[1..].iter().map(|l| l.split(',').take(5).collect::<Vec<_>>().join(",")).collect();
+    assert_eq!(keys[..4], ["32,8,1,ser,0.05", "32,8,1,burst,16", "32,8,4,ser,0.05", "32,8,4,burst,16"]);
+    for line in &lines[1..] {
+        let fields: Vec<&str> = line.split(',').collect();
+        assert_eq!(fields.len(), HEADER.split(',').count());
+        let counts: Vec<u64> = fields[5..].iter().map(|f| f.parse().unwrap()).collect();
+        let [trials, seed, codewords, clean, ce, due, sdc] = counts[..] else { unreachable!() };
+        let depth: u64 = fields[2].parse().unwrap();
+        assert_eq!((trials, seed, codewords), (50, 417, 50 * depth));
+        assert_eq!(clean + ce + due + sdc, codewords);
+    }
+    // Rows are the library's numbers
+    let expected = simulate_interleaved(&RsCodec::new(8, 64).unwrap(), 4, &ChannelModel::Burst(16), 50, 417).stats;
+    let row = format!("64,8,4,burst,16,50,417,{},{},{},{},{}", expected.trials, expected.clean, expected.corrected, expected.detected, expected.miscorrected);
+    assert_eq!(lines[8], row);
+
+    // The same grid from the command line alone gives the same file
+    let output = sweep(&["--n", "32,64", "--nsym", "8", "--depth", "1,4", "--ser", "0.05", "--burst", "16", "--trials", "50", "--seed", "417", &again_s]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(fs::read_to_string(&again).unwrap(), csv);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("8 points, 0 already in"));
+
+    // Interrupted after three rows, partway through the fourth: the torn line is dropped and
+    // only the missing points run
+    let cut = csv.match_indices('\n').nth(3).unwrap().0 + 1;
+    fs::write(&again, format!("{}32,8,4,bu", &csv[..cut])).unwrap();
+    let output = sweep(&["--spec", &spec_s, "--seed", "417", &again_s]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(fs::read_to_string(&again).unwrap(), csv);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("8 points, 3 already in"));
+    // Complete: nothing to run
+    let output = sweep(&["--spec", &spec_s, "--seed", "417", &again_s]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("0 to run"));
+    assert_eq!(fs::read_to_string(&again).unwrap(), csv);
+
+    // Bad specs are usage errors and leave foreign files alone
+    fs::write(&again, "a,b\n1,2\n").unwrap();
+    assert_eq!(sweep(&["--spec", &spec_s, &again_s]).status.code(), Some(2));
+    assert_eq!(fs::read_to_string(&again).unwrap(), "a,b\n1,2\n");
+    assert_eq!(sweep(&["--n", "32", "--nsym", "40", "--ser", "0.1", &again_s]).status.code(), Some(2));
+    assert_eq!(sweep(&["--n", "32", "--nsym", "8", &again_s]).status.code(), Some(2));
+    assert_eq!(sweep(&["--bogus", "1", &again_s]).status.code(), Some(2));
+
+    for path in [spec, out, again] {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,2 +1,9 @@
 ﻿__all__ = []
 __version__ = "0.1.0"
+
+try:
+    from ._rs import ECCError, LengthError, MiscorrectionError, ParameterError, PinnedPositionConflict, UncorrectableError
+except ImportError:  # extension not built
+    pass
+else:
+    __all__ += ["ECCError", "LengthError", "MiscorrectionError", "ParameterError", "PinnedPositionConflict", "UncorrectableError"]
@@ -0,0 +1,134 @@
+"""
+Drop-in replacement for the pure-Python ``reedsolo`` package's ``RSCodec``.
+
+Migrating code only needs its import changed::
+
+    from ecc_model.reedsolo import RSCodec, ReedSolomonError
+
+Chunking, shortening of the last chunk and errata bookkeeping run natively in
+``ecc_model._rs.RSCodec`` (``encode_chunked`` / ``decode_chunked`` / ``check_chunked``).
+"""
+
+from __future__ import annotations
+
+from typing import Dict, List, Optional, Sequence, Tuple, Union
+
+from ._rs import ECCError, ParameterError
+from ._rs import RSCodec as _NativeCodec
+
+__all__ = ["RSCodec", "ReedSolomonError"]
+
+# Every error the extension raises derives from ECCError, so `except ReedSolomonError`
+# still catches decode failures (UncorrectableError) as well as bad input
+ReedSolomonError = ECCError
+
+Data = Union[bytes, bytearray, memoryview, str, Sequence[int]]
+
+
+def _as_bytes(data: Data) -> Union[bytes, bytearray, memoryview]:
+    if isinstance(data, (bytes, bytearray, memoryview)):
+        return data
+    if isinstance(data, str):
+        return data.encode("latin-1")
+    return bytes(data)
+
+
+class RSCodec:
+    """
+    reedsolo-compatible Reed-Solomon codec over GF(2^8).
+
+    Signatures and results follow reedsolo 1.x: ``encode`` auto-chunks messages longer than
+    ``nsize - nsym`` bytes and returns a bytearray; ``decode`` returns
+    ``(decoded_msg, decoded_msgecc, errata_pos)``; ``check`` returns one bool per chunk;
+    ``maxerrata`` returns ``(errors, erasures)``.
+
+    Known differences from reedsolo:
+
+    - Exceptions: ``ReedSolomonError`` is an alias of ``ecc_model.ECCError``. Uncorrectable
+      chunks raise its subclass ``UncorrectableError`` (message prefixed ``"chunk <i>: "``),
+      invalid parameters raise ``ParameterError`` and a trailing chunk no longer than nsym
+      raises ``LengthError``; both of the latter are also ValueErrors.
+    - ``errata_pos`` is a list of offsets into the whole input. reedsolo returns a bytearray
+      of offsets relative to each chunk, which only agrees for single-chunk messages.
+    - Only GF(2^8) is supported: ``c_exp`` must be 8 and ``nsize`` at most 255, where
+      reedsolo would switch to a larger field. ``single_gen`` is accepted and ignored;
+      a per-call ``nsym`` always works.
+    - ``only_erasures`` is accepted but changes nothing: the decoder always corrects e
+      errors plus h erasures with 2e + h <= nsym, so it corrects everything reedsolo's
+      erasures-only mode does, and also unflagged errors where the budget allows.
+    - ``maxerrata`` raises ``ReedSolomonError`` past the Singleton bound, as reedsolo does,
+      but never prints unless ``verbose`` is set.
+    """
+
+    def __init__(
+        self,
+        nsym: int = 10,
+        nsize: int = 255,
+        fcr: int = 0,
+        prim: int = 0x11D,
+        generator: int = 2,
+        c_exp: int = 8,
+        single_gen: bool = True,
+    ) -> None:
+        if c_exp != 8:
+            raise ParameterError(f"only GF(2^8) is supported (c_exp={c_exp})")
+        self.nsym = nsym
+        self.nsize = nsize
+        self.fcr = fcr
+        self.prim = prim
+        self.generator = generator
+        self.c_exp = c_exp
+        self._codecs: Dict[int, _NativeCodec] = {}
+        self._codec(nsym)  # validate now, like reedsolo's table setup
+
+    def _codec(self, nsym: Optional[int]) -> _NativeCodec:
+        nsym = self.nsym if nsym is None else nsym
+        codec = self._codecs.get(nsym)
+        if codec is None:
+            codec = _NativeCodec(nsym, self.nsize, fcr=self.fcr, generator=self.generator, prim=self.prim)
+            self._codecs[nsym] = codec
+        return codec
+
+    def encode(self, data: Data, nsym: Optional[int] = None) -> bytearray:
+        """Encode data of any length; every nsize - nsym bytes become one chunk."""
+        return bytearray(self._codec(nsym).encode_chunked(_as_bytes(data)))
+
+    def decode(
+        self,
+        data: Data,
+        nsym: Optional[int] = None,
+        erase_pos: Optional[Sequence[int]] = None,
+        only_erasures: bool = False,
+    ) -> Tuple[bytearray, bytearray, List[int]]:
+        """Return (decoded_msg, decoded_msgecc, errata_pos); erase_pos are offsets into data."""
+        message, codeword, errata = self._codec(nsym).decode_chunked(_as_bytes(data), erase_pos)
+        return bytearray(message), bytearray(codeword), errata
+
+    def check(self, data: Data, nsym: Optional[int] = None) -> List[bool]:
+        """One bool per chunk: True if the chunk is a valid codeword."""
+        return self._codec(nsym).check_chunked(_as_bytes(data))
+
+    def maxerrata(
+        self,
+        nsym: Optional[int] = None,
+        errors: Optional[int] = None,
+        erasures: Optional[int] = None,
+        verbose: bool = False,
+    ) -> Tuple[int, int]:
+        """Correctable (errors, erasures), optionally given how many of the other to expect."""
+        nsym = self.nsym if nsym is None else nsym
+        max_errors, max_erasures = nsym // 2, nsym
+        if erasures is not None and erasures >= 0:
+            if erasures > max_erasures:
+                raise ReedSolomonError("Specified number of errors or erasures exceeding the Singleton Bound!")
+            max_errors, max_erasures = (nsym - erasures) // 2, erasures
+        if errors is not None and errors >= 0:
+            if errors > max_errors:
+                raise ReedSolomonError("Specified number of errors or erasures exceeding the Singleton Bound!")
+            max_errors, max_erasures = errors, nsym - 2 * errors
+        if verbose:
+            print(f"This codec can correct up to {max_errors} errors and {max_erasures} erasures simultaneously")
+        return max_errors, max_erasures
+
+    def __repr__(self) -> str:
+        return f"RSCodec(nsym={self.nsym}, nsize={self.nsize}, fcr={self.fcr}, prim={self.prim:#x}, generator={self.generator})"
@@ -31,7 +31,7 @@ class RustCodec:
     def decode(self, codeword: bytes) -> Tuple[bytes, List[int]]:
         try:
             decoded, positions = self._rs.decode(self.nsym, self.nsize, codeword)  # type: ignore[misc]
-        except Exception as exc:
+        except RuntimeError as exc:
             raise DecodeError(str(exc)) from exc
         return decoded, list(positions)
 
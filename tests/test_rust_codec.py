@@ -0,0 +1,2505 @@
+from __future__ import annotations
+
+import array
+import asyncio
+import concurrent.futures
+import copy
+import ctypes
+import multiprocessing
+import os
+import pickle
+import random
+import sys
+import sysconfig
+import tempfile
+import threading
+import time
+import unittest
+from concurrent.futures import ThreadPoolExecutor
+
+try:
+    from ecc_model import _rs
+except ImportError:  # extension not built (run `maturin develop`)
+    _rs = None
+
+try:
+    import numpy
+except ImportError:
+    numpy = None
+
+
+# Wall-clock comparisons flake on loaded or single-core machines, so they only run when asked for
+TIMING_TESTS = os.environ.get("ECC_MODEL_TIMING_TESTS") == "1"
+
+
+def _best_of(fn, runs: int = 3) -> float:
+    # Fastest of a few runs, in seconds, so one scheduler hiccup does not decide a comparison
+    times = []
+    for _ in range(runs):
+        start = time.perf_counter()
+        fn()
+        times.append(time.perf_counter() - start)
+    return min(times)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class CrcFramingTests(unittest.TestCase):
+    def test_round_trip_with_error(self) -> None:
+        message = bytes(range(24))
+        codeword = bytearray(_rs.encode_crc(4, 32, message))
+        self.assertEqual(len(codeword), 32)
+        codeword[5] ^= 0x80
+        decoded, positions = _rs.decode_crc(4, 32, bytes(codeword))
+        self.assertEqual(decoded, message)
+        self.assertEqual(positions, [5])
+
+    def test_k_accounts_for_crc(self) -> None:
+        with self.assertRaisesRegex(RuntimeError, r"expected k=26 .*crc=2 bytes"):
+            _rs.encode_crc(4, 32, bytes(24), crc_bits=16)
+        self.assertEqual(len(_rs.encode_crc(4, 32, bytes(26), crc_bits=16)), 32)
+
+    def test_nsize_must_hold_parity_and_crc(self) -> None:
+        # RS(10, 2) has 2 message bytes: no room for a CRC-32, with or without data
+        for crc_bits, nsize in ((32, 10), (32, 11), (16, 9)):
+            with self.assertRaisesRegex(_rs.ParameterError, f"nsize={nsize} has no room"):
+                _rs.encode_crc(8, nsize, b"", crc_bits)
+            with self.assertRaisesRegex(_rs.ParameterError, f"nsize={nsize} has no room"):
+                _rs.decode_crc(8, nsize, bytes(nsize), crc_bits)
+        # Exactly nsym + CRC bytes: an empty message
+        codeword = _rs.encode_crc(8, 12, b"", 32)
+        self.assertEqual(len(codeword), 12)
+        self.assertEqual(_rs.decode_crc(8, 12, codeword, 32), (b"", []))
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class ParameterValidationTests(unittest.TestCase):
+    def test_bad_combinations_raise_value_error(self) -> None:
+        cases = [
+            (0, 10, "nsym must be at least 1"),
+            (4, 256, "nsize must be at most 255"),
+            (4, 300, "nsize must be at most 255"),
+            (255, 255, "nsym must be less than nsize"),
+            (10, 10, "nsym must be less than nsize"),
+            (11, 10, "nsym must be less than nsize"),
+        ]
+        for nsym, nsize, constraint in cases:
+            k = max(nsize - nsym, 0)
+            with self.subTest(nsym=nsym, nsize=nsize):
+                with self.assertRaisesRegex(ValueError, constraint):
+                    _rs.encode(nsym, nsize, bytes(k))
+                with self.assertRaisesRegex(ValueError, constraint):
+                    _rs.decode(nsym, nsize, bytes(nsize))
+
+    def test_boundary_255_is_valid(self) -> None:
+        codeword = _rs.encode(1, 255, bytes(254))
+        self.assertEqual(len(codeword), 255)
+        self.assertEqual(_rs.decode(254, 255, bytes(255)), (bytes(1), []))
+
+    def test_keyword_calling_convention(self) -> None:
+        message = bytes(range(56))
+        legacy = _rs.encode(8, 64, message)
+        self.assertEqual(_rs.encode(message, nsym=8), legacy)
+        self.assertEqual(_rs.encode(message, 8), legacy)
+        self.assertEqual(_rs.encode(message, nsym=8, nsize=64), legacy)
+        self.assertEqual(_rs.encode(nsym=8, nsize=64, message=message), legacy)
+        self.assertEqual(len(_rs.encode(bytes(223), nsym=32)), 255)
+
+        damaged = bytearray(legacy)
+        damaged[9] ^= 0x44
+        expected = (message, [9])
+        self.assertEqual(_rs.decode(8, 64, bytes(damaged)), expected)
+        self.assertEqual(_rs.decode(bytes(damaged), nsym=8), expected)
+        self.assertEqual(_rs.decode(bytes(damaged), 8), expected)
+        self.assertEqual(_rs.decode(damaged, nsym=8, nsize=64), expected)
+        self.assertEqual(_rs.decode(codeword=bytes(damaged), nsym=8), expected)
+        self.assertEqual(_rs.decode(bytes(damaged), nsym=8, erase_pos=[9]), (message, [9], [9]))
+        self.assertEqual(_rs.decode(8, 64, bytes(damaged), [9]), (message, [9], [9]))
+
+    def test_keyword_errors_echo_inferred_values(self) -> None:
+        with self.assertRaisesRegex(ValueError, r"nsize=287 inferred from message length 255"):
+            _rs.encode(bytes(255), nsym=32)
+        with self.assertRaisesRegex(ValueError, r"nsym=40, nsize=30 inferred from codeword length"):
+            _rs.decode(bytes(30), nsym=40)
+        with self.assertRaisesRegex(_rs.LengthError, r"expected n=64 \(nsize=64 given, n=63 inferred"):
+            _rs.decode(bytes(63), nsym=8, nsize=64)
+        with self.assertRaisesRegex(_rs.LengthError, r"message length 255 does not match expected k=223"):
+            _rs.encode(bytes(255), nsym=32, nsize=255)
+        with self.assertRaisesRegex(TypeError, "missing required argument 'nsym'"):
+            _rs.decode(bytes(64))
+        with self.assertRaisesRegex(TypeError, "multiple values for argument 'nsym'"):
+            _rs.encode(bytes(56), 8, nsym=8)
+
+    def test_length_mismatch_is_still_runtime_error(self) -> None:
+        with self.assertRaises(RuntimeError):
+            _rs.encode(4, 32, bytes(27))
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class RSCodecClassTests(unittest.TestCase):
+    def test_properties_and_repr(self) -> None:
+        codec = _rs.RSCodec(nsym=32, nsize=255)
+        self.assertEqual((codec.n, codec.k, codec.nsym, codec.max_errors), (255, 223, 32, 16))
+        self.assertEqual(repr(codec), "RSCodec(nsym=32, nsize=255)")
+        self.assertEqual(repr(_rs.RSCodec(4, 20)), "RSCodec(nsym=4, nsize=20)")
+
+    def test_info_and_capacities(self) -> None:
+        info = _rs.info()
+        for key in ("version", "field", "field_polynomial", "simd_kernels", "active_kernel", "features"):
+            self.assertIn(key, info)
+        self.assertEqual(info["field_polynomial"], 0x11D)
+        self.assertIsInstance(info["simd_kernels"], list)
+        self.assertIn(info["active_kernel"], info["simd_kernels"] + ["scalar"])
+        for nsym, nsize in [(32, 255), (7, 20), (1, 2)]:
+            codec = _rs.RSCodec(nsym, nsize)
+            self.assertEqual(codec.max_errors, nsym // 2)
+            self.assertEqual(codec.max_erasures, nsym)
+            self.assertEqual(codec.k, nsize - nsym)
+
+    def test_construction_errors(self) -> None:
+        for nsym, nsize in [(0, 10), (4, 256), (10, 10)]:
+            with self.subTest(nsym=nsym, nsize=nsize):
+                with self.assertRaises(ValueError):
+                    _rs.RSCodec(nsym=nsym, nsize=nsize)
+
+    def test_round_trip_and_check(self) -> None:
+        codec = _rs.RSCodec(nsym=8, nsize=64)
+        message = bytes(range(56))
+        codeword = codec.encode(message)
+        self.assertTrue(codec.check(codeword))
+        self.assertEqual(codeword, _rs.encode(8, 64, message))
+
+        damaged = bytearray(codeword)
+        damaged[1] ^= 0xFF
+        damaged[60] ^= 0x01
+        self.assertFalse(codec.check(bytes(damaged)))
+        self.assertEqual(codec.decode(bytes(damaged)), (message, [1, 60]))
+        with self.assertRaises(RuntimeError):
+            codec.check(bytes(63))
+
+    def test_clean_and_corrected_decode(self) -> None:
+        # Clean blocks take the copy-free path; both paths return the same shapes and count alike
+        codec = _rs.RSCodec(nsym=8, nsize=64)
+        message = bytes(range(56))
+        codeword = codec.encode(message)
+        for clean in (codeword, bytearray(codeword), memoryview(codeword)):
+            decoded, positions = codec.decode(clean)
+            self.assertEqual((type(decoded), decoded, positions), (bytes, message, []))
+        damaged = bytearray(codeword)
+        damaged[7] ^= 0x10
+        self.assertEqual(codec.decode(damaged), (message, [7]))
+        self.assertEqual(_rs.decode(codeword, 8), (message, []))
+        stats = codec.stats()
+        self.assertEqual((stats["blocks_decoded"], stats["blocks_corrected"]), (4, 1))
+
+    def test_codecs_do_not_interfere(self) -> None:
+        a = _rs.RSCodec(nsym=4, nsize=32)
+        b = _rs.RSCodec(nsym=8, nsize=32)
+        msg_a, msg_b = bytes(28), bytes(range(24))
+        cw_a, cw_b = a.encode(msg_a), b.encode(msg_b)
+        self.assertEqual(a.decode(cw_a), (msg_a, []))
+        self.assertEqual(b.decode(cw_b), (msg_b, []))
+        self.assertEqual(cw_b, _rs.encode(8, 32, msg_b))
+        self.assertEqual((a.k, b.k), (28, 24))
+
+    def test_pickle_and_copy(self) -> None:
+        codec = _rs.RSCodec(nsym=8, nsize=64)
+        for protocol in range(pickle.HIGHEST_PROTOCOL + 1):
+            restored = pickle.loads(pickle.dumps(codec, protocol))
+            self.assertIsInstance(restored, _rs.RSCodec)
+            self.assertEqual(repr(restored), repr(codec))
+            self.assertEqual(restored.generator, codec.generator)
+        self.assertEqual(repr(copy.copy(codec)), repr(codec))
+        self.assertEqual(repr(copy.deepcopy([codec])[0]), repr(codec))
+
+    def test_pickle_through_spawned_worker(self) -> None:
+        codec = _rs.RSCodec(nsym=16, nsize=100)
+        message = bytes(range(84))
+        damaged = bytearray(codec.encode(message))
+        damaged[3] ^= 0x21
+        damaged[90] ^= 0x07
+        with multiprocessing.get_context("spawn").Pool(1) as pool:
+            remote = pool.apply(_pickle_worker, (codec, message, bytes(damaged)))
+        self.assertEqual(remote, (repr(codec), codec.encode(message), codec.decode(bytes(damaged))))
+
+
+def _pickle_worker(codec, message: bytes, damaged: bytes) -> tuple:
+    # Runs in a spawned process: the codec arrives pickled and is rebuilt there
+    return repr(codec), codec.encode(message), codec.decode(damaged)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class BufferProtocolTests(unittest.TestCase):
+    def setUp(self) -> None:
+        self.codec = _rs.RSCodec(nsym=8, nsize=64)
+        self.message = bytes(range(100, 156))
+        self.codeword = self.codec.encode(self.message)
+        damaged = bytearray(self.codeword)
+        damaged[9] ^= 0x33
+        self.damaged = bytes(damaged)
+
+    def test_buffer_types_give_identical_results(self) -> None:
+        expected = self.codec.decode(self.damaged)
+        big = bytearray(1000) + bytearray(self.damaged) + bytearray(1000)
+        inputs = [
+            bytearray(self.damaged),
+            memoryview(self.damaged),
+            memoryview(big)[1000:1064],
+            memoryview(bytes(big))[1000:1064],
+        ]
+        for data in inputs:
+            with self.subTest(kind=type(data).__name__):
+                self.assertEqual(self.codec.decode(data), expected)
+                self.assertEqual(_rs.decode(8, 64, data), expected)
+        self.assertEqual(self.codec.encode(bytearray(self.message)), self.codeword)
+        self.assertEqual(_rs.encode(8, 64, memoryview(self.message)), self.codeword)
+
+    @unittest.skipIf(numpy is None, "numpy not installed")
+    def test_numpy_arrays(self) -> None:
+        arr = numpy.frombuffer(self.damaged, dtype=numpy.uint8)
+        self.assertEqual(self.codec.decode(arr), self.codec.decode(self.damaged))
+        self.assertEqual(self.codec.encode(numpy.array(list(self.message), dtype=numpy.uint8)), self.codeword)
+        with self.assertRaises(TypeError):
+            self.codec.decode(numpy.zeros(64, dtype=numpy.uint16))
+
+    def test_large_buffer_is_borrowed_not_copied(self) -> None:
+        big = bytearray(1 << 20)
+        base = ctypes.addressof((ctypes.c_char * len(big)).from_buffer(big))
+        offset = 12345
+        view = memoryview(big)[offset:offset + 64]
+        self.assertEqual(_rs._buffer_address(view), base + offset)
+        self.assertEqual(_rs._buffer_address(big), base)
+
+    def test_rejects_incompatible_buffers(self) -> None:
+        with self.assertRaisesRegex(TypeError, "contiguous"):
+            self.codec.decode(memoryview(self.damaged * 2)[::2])
+        with self.assertRaisesRegex(TypeError, "uint8"):
+            self.codec.decode(memoryview(array.array("H", range(64))))
+        with self.assertRaises(TypeError):
+            self.codec.decode("not bytes")
+        with self.assertRaisesRegex(TypeError, "iterable of ints, got int"):
+            self.codec.decode(64)
+
+    def test_int_sequences(self) -> None:
+        message = self.message
+        inputs = [list(message), tuple(message), range(100, 156), (b for b in message)]
+        for data in inputs:
+            with self.subTest(kind=type(data).__name__):
+                self.assertEqual(self.codec.encode(data), self.codeword)
+        for data in ([*self.damaged], tuple(self.damaged), (b for b in self.damaged)):
+            with self.subTest(kind=type(data).__name__):
+                self.assertEqual(self.codec.decode(data), self.codec.decode(self.damaged))
+        self.assertEqual(_rs.decode(list(self.damaged), nsym=8), (message, [9]))
+        self.assertEqual(_rs.encode([0x12, 0x34, 0x56], nsym=4), _rs.encode(b"\x12\x34\x56", nsym=4))
+        self.assertEqual(_rs.encode(8, 64, list(message)), self.codeword)
+        self.assertTrue(self.codec.check(list(self.codeword)))
+
+    def test_int_sequence_errors(self) -> None:
+        bad = list(self.message)
+        bad[7] = 256
+        with self.assertRaisesRegex(ValueError, r"element 7 = 256 is out of range 0\.\.=255"):
+            self.codec.encode(bad)
+        bad[7] = -1
+        with self.assertRaisesRegex(ValueError, "element 7 = -1 is out of range"):
+            _rs.encode(bad, nsym=8)
+        bad[7] = 1.0
+        with self.assertRaisesRegex(ValueError, "element 7 must be an int in 0..=255, got float"):
+            self.codec.encode(bad)
+        bad[7] = 1 << 70
+        with self.assertRaisesRegex(_rs.ParameterError, "element 7"):
+            self.codec.encode(bad)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class BatchFunctionTests(unittest.TestCase):
+    def test_ten_thousand_blocks(self) -> None:
+        nsym, nsize = 8, 40
+        messages = [bytes((i + j) & 0xFF for j in range(nsize - nsym)) for i in range(10_000)]
+        codewords = _rs.encode_batch(nsym, nsize, messages)
+        self.assertEqual(len(codewords), len(messages))
+        self.assertEqual(codewords[1234], _rs.encode(nsym, nsize, messages[1234]))
+
+        damaged = [bytearray(c) for c in codewords]
+        for i, c in enumerate(damaged):
+            c[i % nsize] ^= 0xA5
+        results = _rs.decode_batch(nsym, nsize, [bytes(c) for c in damaged])
+        for i, (decoded, positions) in enumerate(results):
+            self.assertEqual(decoded, messages[i])
+            self.assertEqual(positions, [i % nsize])
+
+    def test_errors_name_the_offending_index(self) -> None:
+        with self.assertRaisesRegex(RuntimeError, r"message 2: length 5"):
+            _rs.encode_batch(2, 8, [bytes(6), bytes(6), bytes(5)])
+        with self.assertRaisesRegex(RuntimeError, r"codeword 1: length 7"):
+            _rs.decode_batch(2, 8, [bytes(8), bytes(7)])
+        bad = bytearray(_rs.encode(2, 8, bytes(6)))
+        bad[0] ^= 1
+        bad[1] ^= 1
+        with self.assertRaisesRegex(RuntimeError, r"codeword 1: "):
+            _rs.decode_batch(2, 8, [bytes(8), bytes(bad)])
+
+    def test_parallel_matches_serial(self) -> None:
+        blocks = _corrupted_blocks(500)
+        messages = [bytes((i + j) & 0xFF for j in range(223)) for i in range(50)]
+        self.assertEqual(_rs.encode_batch(32, 255, messages, parallel=True), _rs.encode_batch(32, 255, messages))
+        self.assertEqual(_rs.decode_batch(32, 255, blocks, parallel=True), _rs.decode_batch(32, 255, blocks))
+
+    def test_failing_block_keeps_other_results(self) -> None:
+        blocks = _corrupted_blocks(20, nsym=8, nsize=64)
+        expected = _rs.decode_batch(8, 64, blocks)
+        for failing in ((7,), (0, 13, 19)):
+            for parallel in (False, True):
+                with self.subTest(failing=failing, parallel=parallel):
+                    batch = list(blocks)
+                    for i in failing:
+                        bad = bytearray(_rs.encode(bytes(56), nsym=8))
+                        for p in range(5):  # t = 4
+                            bad[p * 3] ^= 0x3C
+                        batch[i] = bytes(bad)
+                    with self.assertRaises(_rs.UncorrectableError) as ctx:
+                        _rs.decode_batch(8, 64, batch, parallel=parallel)
+                    err = ctx.exception
+                    self.assertTrue(str(err).startswith(f"codeword {failing[0]}: "))
+                    self.assertEqual(sorted(err.errors), list(failing))
+                    self.assertIsInstance(err.errors[failing[-1]], _rs.UncorrectableError)
+                    for i, result in enumerate(err.results):
+                        self.assertEqual(result, None if i in failing else expected[i])
+
+    def test_report_mode_returns_every_entry(self) -> None:
+        messages = [bytes((7 * i + j) & 0xFF for j in range(56)) for i in range(9)]
+        batch = [bytearray(_rs.encode(m, nsym=8)) for m in messages]
+        for i in (1, 4, 7):  # correctable: i // 2 + 1 errors <= t = 4
+            for p in range(i // 2 + 1):
+                batch[i][5 * p] ^= 0x81
+        for i in (2, 5):  # uncorrectable: 5 errors > t
+            for p in range(5):
+                batch[i][3 * p] ^= 0x3C
+        for parallel in (False, True):
+            with self.subTest(parallel=parallel):
+                results = _rs.decode_batch(8, 64, [bytes(c) for c in batch], parallel=parallel, errors="report")
+                self.assertEqual(len(results), 9)
+                for i, entry in enumerate(results):
+                    if i in (2, 5):
+                        self.assertIsInstance(entry, _rs.BlockError)
+                        self.assertEqual(entry.index, i)
+                        self.assertIs(entry.type, _rs.UncorrectableError)
+                        self.assertIsInstance(entry.exception, _rs.UncorrectableError)
+                        self.assertTrue(entry.message.startswith(f"codeword {i}: "))
+                        self.assertEqual(entry.message, str(entry.exception))
+                        self.assertIn(f"BlockError(index={i}, type=UncorrectableError", repr(entry))
+                        continue
+                    decoded, positions = entry
+                    self.assertEqual(decoded, messages[i])
+                    expected = [5 * p for p in range(i // 2 + 1)] if i in (1, 4, 7) else []
+                    self.assertEqual(sorted(positions), expected)
+        with self.assertRaises(_rs.UncorrectableError):
+            _rs.decode_batch(8, 64, [bytes(c) for c in batch], errors="raise")
+
+    def test_report_mode_still_raises_for_bad_arguments(self) -> None:
+        with self.assertRaisesRegex(_rs.LengthError, r"codeword 1: length 7"):
+            _rs.decode_batch(2, 8, [bytes(8), bytes(7)], errors="report")
+        with self.assertRaises(_rs.ParameterError):
+            _rs.decode_batch(0, 8, [bytes(8)], errors="report")
+        with self.assertRaisesRegex(_rs.ParameterError, "errors must be"):
+            _rs.decode_batch(2, 8, [bytes(8)], errors="ignore")
+
+    @unittest.skipIf((os.cpu_count() or 1) < 4, "speedup needs at least 4 cores")
+    def test_parallel_speedup(self) -> None:
+        blocks = _corrupted_blocks(20_000)
+
+        serial = _best_of(lambda: _rs.decode_batch(32, 255, blocks))
+        parallel = _best_of(lambda: _rs.decode_batch(32, 255, blocks, parallel=True))
+        self.assertLess(parallel, serial, f"decode_batch of {len(blocks)} blocks: serial {serial:.3f}s, parallel {parallel:.3f}s")
+
+    @unittest.skipUnless(TIMING_TESTS, "wall-clock comparison; set ECC_MODEL_TIMING_TESTS=1")
+    def test_batch_beats_python_loop(self) -> None:
+        # Tiny blocks so per-call overhead dominates; the message gives the per-block cost of each path
+        messages = [bytes([i & 0xFF, 1]) for i in range(10_000)]
+
+        loop = _best_of(lambda: [_rs.encode(2, 4, m) for m in messages])
+        batch = _best_of(lambda: _rs.encode_batch(2, 4, messages))
+        self.assertLess(batch, loop, f"per-block encode: loop {loop / len(messages) * 1e9:.0f} ns, batch {batch / len(messages) * 1e9:.0f} ns")
+
+
+@unittest.skipIf(_rs is None or numpy is None or not hasattr(_rs, "encode_array"),
+                 "needs numpy and the extension built with the `numpy` feature")
+class NumpyArrayTests(unittest.TestCase):
+    def setUp(self) -> None:
+        rng = numpy.random.default_rng(325)
+        self.nsym = 8
+        self.messages = rng.integers(0, 256, size=(50, 32), dtype=numpy.uint8)
+
+    def test_c_contiguous_round_trip(self) -> None:
+        codewords = _rs.encode_array(self.nsym, self.messages)
+        self.assertEqual(codewords.shape, (50, 40))
+        self.assertEqual(codewords.dtype, numpy.uint8)
+        self.assertEqual(codewords[7].tobytes(), _rs.encode(8, 40, self.messages[7].tobytes()))
+
+        codewords[3, 5] ^= 0x10
+        codewords[4, 0] ^= 0x01
+        codewords[4, 39] ^= 0x02
+        decoded, status = _rs.decode_array(self.nsym, codewords)
+        numpy.testing.assert_array_equal(decoded, self.messages)
+        self.assertEqual(status[3], 1)
+        self.assertEqual(status[4], 2)
+        self.assertEqual(int(status.sum()), 3)
+
+    def test_row_slice_is_accepted_and_strided_view_rejected(self) -> None:
+        codewords = _rs.encode_array(self.nsym, self.messages)
+        decoded, status = _rs.decode_array(self.nsym, codewords[10:20])
+        numpy.testing.assert_array_equal(decoded, self.messages[10:20])
+        with self.assertRaisesRegex(TypeError, "C-contiguous"):
+            _rs.decode_array(self.nsym, codewords[::2])
+        with self.assertRaisesRegex(TypeError, "C-contiguous"):
+            _rs.encode_array(self.nsym, self.messages[:, ::2])
+        with self.assertRaisesRegex(TypeError, "uint8"):
+            _rs.encode_array(self.nsym, self.messages.astype(numpy.int16))
+
+    def test_failed_block_does_not_poison_the_rest(self) -> None:
+        codewords = _rs.encode_array(self.nsym, self.messages)
+        codewords[25, :5] ^= 0xFF  # 5 errors > t = 4
+        decoded, status = _rs.decode_array(self.nsym, codewords)
+        self.assertEqual(status[25], -1)
+        keep = numpy.arange(50) != 25
+        numpy.testing.assert_array_equal(decoded[keep], self.messages[keep])
+        self.assertTrue((status[keep] == 0).all())
+
+
+@unittest.skipIf(_rs is None or numpy is None or not hasattr(_rs, "RSCodec16"),
+                 "needs numpy and the extension built with the `numpy` feature")
+class RSCodec16Tests(unittest.TestCase):
+    def setUp(self) -> None:
+        # A 4 KB block of 16-bit symbols: RS(2048, 2000), t = 24
+        self.codec = _rs.RSCodec16(48, 2048)
+        rng = numpy.random.default_rng(419)
+        self.message = rng.integers(0, 1 << 16, size=2000, dtype=numpy.uint16)
+        self.codeword = self.codec.encode(self.message)
+        self.hit = [0, 1, 7, 1000, 2040, 2046, 2047]
+
+    def damaged(self):
+        received = self.codeword.copy()
+        for i, pos in enumerate(self.hit):
+            received[pos] ^= 0x1111 * (i + 1)
+        return received
+
+    def test_round_trip_with_errors_near_both_ends(self) -> None:
+        self.assertEqual((self.codec.n, self.codec.k, self.codec.nsym), (2048, 2000, 48))
+        self.assertEqual((self.codeword.dtype, self.codeword.shape), (numpy.uint16, (2048,)))
+        numpy.testing.assert_array_equal(self.codeword[:2000], self.message)
+        self.assertTrue(self.codec.check(self.codeword))
+        message, positions = self.codec.decode(self.damaged())
+        numpy.testing.assert_array_equal(message, self.message)
+        self.assertEqual(positions, self.hit)
+        # Erasures: the flagged ones cost half, so 2e + h = nsym still decodes
+        received = self.damaged()
+        extra = list(range(100, 134))
+        received[extra] ^= 0xBEEF
+        message, positions, erased = self.codec.decode(received, erase_pos=extra + [0])
+        numpy.testing.assert_array_equal(message, self.message)
+        self.assertEqual(erased, sorted(extra + [0]))
+
+    def test_bytes_packing(self) -> None:
+        packed = self.message.astype("<u2").tobytes()
+        codeword = self.codec.encode(packed)
+        self.assertIsInstance(codeword, bytes)
+        self.assertEqual(codeword, self.codeword.astype("<u2").tobytes())
+        self.assertEqual(codeword[:2], bytes([int(self.message[0]) & 0xFF, int(self.message[0]) >> 8]))
+        message, positions = self.codec.decode(self.damaged().astype("<u2").tobytes())
+        self.assertEqual(message, packed)
+        self.assertEqual(positions, self.hit)
+        with self.assertRaises(_rs.LengthError):
+            self.codec.decode(codeword[:-1])
+
+    def test_detailed_and_batch(self) -> None:
+        result = self.codec.decode_detailed(self.damaged(), erase_pos=[0, 5])
+        self.assertEqual(result.positions, self.hit)
+        self.assertEqual((result.erasure_positions, result.unused_erasures), ([0], [5]))
+        self.assertEqual(result.parity_positions, [2040, 2046, 2047])
+        numpy.testing.assert_array_equal(result.codeword, self.codeword)
+        numpy.testing.assert_array_equal(result.magnitudes, [0x1111 * (i + 1) for i in range(7)])
+        self.assertIn((1, 1), result.bit_errors)
+
+        messages = numpy.stack([self.message, self.message[::-1]])
+        codewords = self.codec.encode_batch(messages, parallel=True)
+        numpy.testing.assert_array_equal(codewords[0], self.codeword)
+        codewords[1, :25] ^= 1  # 25 errors > t
+        results = self.codec.decode_batch(codewords, errors="report")
+        numpy.testing.assert_array_equal(results[0][0], self.message)
+        self.assertIsInstance(results[1], _rs.BlockError)
+        with self.assertRaises(_rs.UncorrectableError) as ctx:
+            self.codec.decode_batch(codewords)
+        self.assertEqual(list(ctx.exception.errors), [1])
+
+    def test_dtype_and_contiguity_are_type_errors(self) -> None:
+        with self.assertRaisesRegex(TypeError, "uint16"):
+            self.codec.encode(self.message.astype(numpy.uint32))
+        with self.assertRaisesRegex(TypeError, "uint16"):
+            self.codec.decode(self.codeword.astype(">u2"))
+        with self.assertRaisesRegex(TypeError, "C-contiguous"):
+            self.codec.decode(numpy.repeat(self.codeword, 2)[::2])
+        with self.assertRaisesRegex(TypeError, "dimensions"):
+            self.codec.encode_batch(self.message)
+        with self.assertRaises(TypeError):
+            self.codec.encode(list(range(2000)))
+        with self.assertRaises(_rs.ParameterError):
+            _rs.RSCodec16(48, 65536)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class ExceptionHierarchyTests(unittest.TestCase):
+    def test_classes_importable_from_package(self) -> None:
+        import ecc_model
+        from ecc_model import ECCError, LengthError, MiscorrectionError, ParameterError, UncorrectableError
+
+        self.assertIs(ecc_model.ECCError, _rs.ECCError)
+        for cls in (LengthError, MiscorrectionError, ParameterError, UncorrectableError):
+            self.assertTrue(issubclass(cls, ECCError))
+        self.assertTrue(issubclass(ECCError, RuntimeError))
+        self.assertTrue(issubclass(ParameterError, ValueError))
+        self.assertTrue(issubclass(LengthError, ValueError))
+        self.assertFalse(issubclass(UncorrectableError, ValueError))
+
+    def test_parameter_error(self) -> None:
+        with self.assertRaises(_rs.ParameterError):
+            _rs.RSCodec(nsym=0, nsize=10)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.encode_crc(4, 32, bytes(24), crc_bits=8)
+
+    def test_length_error(self) -> None:
+        codec = _rs.RSCodec(nsym=4, nsize=16)
+        with self.assertRaises(_rs.LengthError):
+            codec.encode(bytes(11))
+        with self.assertRaises(_rs.LengthError):
+            codec.decode(bytes(15))
+        with self.assertRaisesRegex(_rs.LengthError, "message 1"):
+            _rs.encode_batch(4, 16, [bytes(12), bytes(13)])
+
+    def test_uncorrectable_error_attributes(self) -> None:
+        codec = _rs.RSCodec(nsym=4, nsize=16)
+        codeword = bytearray(codec.encode(bytes(range(12))))
+        for pos in (0, 1, 2):
+            codeword[pos] ^= 0x40
+        with self.assertRaises(_rs.UncorrectableError) as ctx:
+            codec.decode(bytes(codeword))
+        err = ctx.exception
+        self.assertEqual(err.capacity, 2)
+        self.assertEqual(err.syndrome_weight, 4)
+        self.assertGreater(err.detected_errors, err.capacity)
+        self.assertEqual(err.reason, "too many errors")
+        self.assertEqual(str(err), "too many errors")
+
+        with self.assertRaises(_rs.UncorrectableError) as ctx:
+            _rs.decode_batch(4, 16, [codec.encode(bytes(12)), bytes(codeword)])
+        self.assertTrue(str(ctx.exception).startswith("codeword 1: "))
+        self.assertEqual(ctx.exception.capacity, 2)
+
+    def test_miscorrection_error(self) -> None:
+        # A valid RS codeword whose CRC bytes are stale decodes cleanly but fails the CRC
+        message = bytes(range(24))
+        frame = bytearray(_rs.encode_crc(4, 32, message)[:28])
+        frame[0] ^= 0x01
+        wrong = _rs.encode(4, 32, bytes(frame))
+        with self.assertRaises(_rs.MiscorrectionError):
+            _rs.decode_crc(4, 32, wrong)
+
+
+def _gf_mul(a: int, b: int, prim: int = 0x11D) -> int:
+    # Reference GF(256) multiply, primitive polynomial 0x11d unless given
+    result = 0
+    while b:
+        if b & 1:
+            result ^= a
+        a <<= 1
+        if a & 0x100:
+            a ^= prim
+        b >>= 1
+    return result
+
+
+def _reference_syndromes(codeword: bytes, nsym: int) -> bytes:
+    # S_j = r(alpha^j) with codeword[0] the highest-degree coefficient (Horner)
+    out = []
+    alpha_j = 1
+    for _ in range(nsym):
+        s = 0
+        for byte in codeword:
+            s = _gf_mul(s, alpha_j) ^ byte
+        out.append(s)
+        alpha_j = _gf_mul(alpha_j, 2)
+    return bytes(out)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class SyndromeTests(unittest.TestCase):
+    def test_zero_for_fresh_codeword_nonzero_after_any_flip(self) -> None:
+        codec = _rs.RSCodec(nsym=6, nsize=30)
+        codeword = codec.encode(bytes(range(24)))
+        self.assertEqual(codec.syndromes(codeword), bytes(6))
+        self.assertEqual(_rs.syndromes(6, codeword), bytes(6))
+        self.assertEqual(codec.syndrome_weight(codeword), 0)
+        for pos in range(len(codeword)):
+            damaged = bytearray(codeword)
+            damaged[pos] ^= 0x01
+            self.assertGreater(_rs.syndrome_weight(6, damaged), 0, pos)
+
+    def test_matches_pure_python_reference(self) -> None:
+        codeword = bytearray(_rs.encode(4, 10, b"synd!!"))
+        codeword[2] ^= 0x37
+        codeword = bytes(codeword)
+        expected = _reference_syndromes(codeword, 4)
+        self.assertNotEqual(expected, bytes(4))
+        self.assertEqual(_rs.syndromes(4, codeword), expected)
+        self.assertEqual(_rs.RSCodec(4, 10).syndromes(codeword), expected)
+        self.assertEqual(_rs.syndrome_weight(4, codeword), sum(1 for s in expected if s))
+
+    def test_length_validation(self) -> None:
+        with self.assertRaises(_rs.LengthError):
+            _rs.syndromes(4, bytes(4))
+        with self.assertRaises(_rs.LengthError):
+            _rs.syndromes(4, bytes(256))
+        with self.assertRaises(_rs.LengthError):
+            _rs.RSCodec(4, 10).syndromes(bytes(11))
+        with self.assertRaises(_rs.ParameterError):
+            _rs.syndromes(0, bytes(10))
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class GeneratorPolyTests(unittest.TestCase):
+    def test_degree_and_leading_coefficient(self) -> None:
+        for nsym in (1, 4, 32):
+            gen = _rs.generator_poly(nsym)
+            self.assertEqual(len(gen), nsym + 1)
+            self.assertEqual(gen[-1], 1)
+            self.assertEqual(_rs.RSCodec(nsym=nsym, nsize=255).generator, gen)
+        # (x + 1)(x + 2) = x^2 + 3x + 2, constant term first
+        self.assertEqual(_rs.generator_poly(2), bytes([2, 3, 1]))
+
+    def test_roots_are_consecutive_powers_of_alpha(self) -> None:
+        roots = _rs.generator_roots(8)
+        expected, a = [], 1
+        for _ in range(8):
+            expected.append(a)
+            a = _gf_mul(a, 2)
+        self.assertEqual(roots, expected)
+
+    def test_generator_vanishes_at_each_root(self) -> None:
+        nsym = 16
+        gen = _rs.generator_poly(nsym)
+        # As a codeword the highest-degree coefficient comes first
+        self.assertEqual(_rs.syndromes(nsym, bytes(reversed(gen))), bytes(nsym))
+        for root in _rs.generator_roots(nsym):
+            value = 0
+            for coef in reversed(gen):
+                value = _gf_mul(value, root) ^ coef
+            self.assertEqual(value, 0)
+
+    def test_invalid_nsym(self) -> None:
+        with self.assertRaises(_rs.ParameterError):
+            _rs.generator_poly(0)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.generator_roots(255)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class GeneratorCacheTests(unittest.TestCase):
+    def test_cached_encode_matches_reference_across_threads(self) -> None:
+        # Every nsym, first used from several threads at once, must encode as the reference does
+        def work(offset: int) -> list:
+            out = []
+            for i in range(254):
+                nsym = 1 + (i * 11 + offset * 37) % 254
+                message = bytes((nsym * 3 + j) & 0xFF for j in range(255 - nsym))
+                out.append(_rs.encode(message, nsym=nsym) == _reedsolo_encode(message, nsym))
+            return out
+
+        with ThreadPoolExecutor(max_workers=4) as pool:
+            self.assertTrue(all(all(r) for r in pool.map(work, range(4))))
+
+    @unittest.skipUnless(TIMING_TESTS, "wall-clock comparison; set ECC_MODEL_TIMING_TESTS=1")
+    def test_module_encode_overhead(self) -> None:
+        message = bytes(32)
+        codec = _rs.RSCodec(32, 64)
+        module = _best_of(lambda: [_rs.encode(message, nsym=32) for _ in range(5000)])
+        prebuilt = _best_of(lambda: [codec.encode(message) for _ in range(5000)])
+        # No generator build per call, so the module function costs about what a kept codec does
+        self.assertLess(module, 3 * prebuilt, f"nsym=32 k=32 encode: module {module / 5000 * 1e9:.0f} ns, prebuilt RSCodec {prebuilt / 5000 * 1e9:.0f} ns")
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class ErasureDecodeTests(unittest.TestCase):
+    def setUp(self) -> None:
+        self.codec = _rs.RSCodec(nsym=6, nsize=40)
+        self.message = bytes(range(34))
+        self.codeword = self.codec.encode(self.message)
+
+    def test_exactly_nsym_erasures(self) -> None:
+        damaged = bytearray(self.codeword)
+        erased = [0, 7, 8, 20, 33, 39]
+        for pos in erased:
+            damaged[pos] = 0 if damaged[pos] else 0xFF
+        # Without erasure information six errors are far beyond t = 3
+        with self.assertRaises(_rs.UncorrectableError):
+            self.codec.decode(bytes(damaged))
+        message, positions, erasures = _rs.decode(6, 40, bytes(damaged), erase_pos=erased)
+        self.assertEqual(message, self.message)
+        self.assertEqual(positions, erased)
+        self.assertEqual(erasures, erased)
+
+    def test_mixed_errors_and_erasures(self) -> None:
+        damaged = bytearray(self.codeword)
+        damaged[3] ^= 0x11   # unflagged error
+        damaged[10] ^= 0x22  # unflagged error
+        damaged[25] ^= 0x33  # flagged
+        damaged[30] ^= 0x44  # flagged
+        message, positions, erasures = self.codec.decode(bytes(damaged), erase_pos=iter([30, 25]))
+        self.assertEqual(message, self.message)
+        self.assertEqual(positions, [3, 10, 25, 30])
+        self.assertEqual(erasures, [25, 30])
+
+    def test_erasure_that_was_correct_is_not_reported(self) -> None:
+        damaged = bytearray(self.codeword)
+        damaged[5] ^= 0x01
+        message, positions, erasures = self.codec.decode(bytes(damaged), erase_pos=[5, 6])
+        self.assertEqual((message, positions, erasures), (self.message, [5], [5]))
+        self.assertEqual(self.codec.decode(self.codeword, erase_pos=[]), (self.message, [], []))
+
+    def test_invalid_erasures(self) -> None:
+        for bad in ([40], [-1], [3, 3]):
+            with self.subTest(erase_pos=bad):
+                with self.assertRaises(ValueError):
+                    self.codec.decode(self.codeword, erase_pos=bad)
+        with self.assertRaises(_rs.UncorrectableError):
+            self.codec.decode(self.codeword, erase_pos=range(7))
+        damaged = bytearray(self.codeword)
+        damaged[0] ^= 1
+        damaged[1] ^= 1
+        # 2 errors + 3 erasures needs 7 > nsym
+        with self.assertRaises(_rs.UncorrectableError):
+            self.codec.decode(bytes(damaged), erase_pos=[10, 11, 12])
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class ErasureHintTests(unittest.TestCase):
+    def setUp(self) -> None:
+        # nsym = 9: t = 4 errors, or 4 errors plus 1 erasure
+        self.codec = _rs.RSCodec(nsym=9, nsize=40)
+        self.message = bytes(range(31))
+        damaged = bytearray(self.codec.encode(self.message))
+        self.errors = [2, 9, 17, 26, 38]
+        for pos in self.errors:
+            damaged[pos] ^= 0x6C
+        self.damaged = bytes(damaged)
+
+    def test_callback_supplies_the_extra_position(self) -> None:
+        calls = []
+
+        def hint(codeword: bytes, error: Exception) -> list:
+            calls.append((codeword, error))
+            return [17]
+
+        self.assertEqual(self.codec.decode(self.damaged, on_failure=hint), (self.message, self.errors))
+        self.assertEqual(len(calls), 1)
+        self.assertEqual(calls[0][0], self.damaged)
+        self.assertIsInstance(calls[0][1], _rs.UncorrectableError)
+        self.assertEqual(_rs.decode(self.damaged, nsym=9, on_failure=hint), (self.message, self.errors))
+        # With erase_pos the hints join the given erasures in `erased`
+        self.assertEqual(self.codec.decode(self.damaged, erase_pos=[3], on_failure=lambda c, e: [17, 38]),
+                         (self.message, self.errors, [17, 38]))
+
+    def test_not_called_when_decode_succeeds(self) -> None:
+        codeword = self.codec.encode(self.message)
+        self.assertEqual(self.codec.decode(codeword, on_failure=lambda c, e: self.fail("called")), (self.message, []))
+
+    def test_retries_are_bounded_and_noted(self) -> None:
+        calls = []
+
+        def wrong(codeword: bytes, error: Exception) -> list:
+            calls.append(error)
+            return [30 + len(calls)]
+
+        with self.assertRaises(_rs.UncorrectableError) as ctx:
+            self.codec.decode(self.damaged, on_failure=wrong)
+        self.assertEqual(len(calls), _rs.MAX_HINT_RETRIES)
+        self.assertIs(ctx.exception, calls[0])
+        self.assertRegex(ctx.exception.__notes__[0], r"tried 3 time\(s\), all failed: \[\[31\], \[32\], \[33\]\]")
+
+        with self.assertRaises(_rs.UncorrectableError) as ctx:
+            self.codec.decode(self.damaged, on_failure=lambda c, e: None)
+        self.assertEqual(ctx.exception.__notes__, ["on_failure offered no erasure hints"])
+
+    def test_callback_errors_propagate(self) -> None:
+        def broken(codeword: bytes, error: Exception) -> list:
+            raise KeyError("device map unavailable")
+
+        with self.assertRaises(KeyError):
+            self.codec.decode(self.damaged, on_failure=broken)
+        with self.assertRaises(_rs.ParameterError):
+            self.codec.decode(self.damaged, on_failure=lambda c, e: [40])
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class MaxCorrectTests(unittest.TestCase):
+    def setUp(self) -> None:
+        self.message = bytes(range(32))
+        damaged = bytearray(_rs.encode(self.message, nsym=8))
+        for pos in (4, 20, 35):
+            damaged[pos] ^= 0x5A
+        self.damaged = bytes(damaged)
+
+    def test_three_errors_refused_at_max_correct_two(self) -> None:
+        self.assertEqual(_rs.RSCodec(8, 40).decode(self.damaged), (self.message, [4, 20, 35]))
+        codec = _rs.RSCodec(8, 40, max_correct=2)
+        with self.assertRaisesRegex(_rs.UncorrectableError, r"3 errors located, more than max_correct=2") as ctx:
+            codec.decode(self.damaged)
+        self.assertEqual((ctx.exception.detected_errors, ctx.exception.capacity), (3, 2))
+        with self.assertRaises(_rs.UncorrectableError):
+            _rs.decode(self.damaged, nsym=8, max_correct=2)
+        with self.assertRaises(_rs.UncorrectableError):
+            codec.decode_detailed(self.damaged)
+        # Flagged erasures do not use up the cap
+        self.assertEqual(codec.decode(self.damaged, erase_pos=[20])[1], [4, 20, 35])
+
+    def test_max_correct_at_t_matches_default(self) -> None:
+        default, capped = _rs.RSCodec(8, 40), _rs.RSCodec(8, 40, max_correct=4)
+        codeword = default.encode(self.message)
+        for errors in range(7):
+            damaged = bytearray(codeword)
+            for e in range(errors):
+                damaged[6 * e] ^= 0x11 * (e + 1)
+            with self.subTest(errors=errors):
+                try:
+                    expected = default.decode(bytes(damaged))
+                except _rs.UncorrectableError as exc:
+                    with self.assertRaises(_rs.UncorrectableError) as ctx:
+                        capped.decode(bytes(damaged))
+                    self.assertEqual(ctx.exception.reason, exc.reason)
+                else:
+                    self.assertEqual(capped.decode(bytes(damaged)), expected)
+
+    def test_round_trips_and_validation(self) -> None:
+        codec = _rs.RSCodec(8, 40, max_correct=1)
+        self.assertEqual(codec.max_correct, 1)
+        self.assertIsNone(_rs.RSCodec(8, 40).max_correct)
+        self.assertEqual(repr(codec), "RSCodec(nsym=8, nsize=40, max_correct=1)")
+        self.assertEqual(pickle.loads(pickle.dumps(codec)).max_correct, 1)
+        with self.assertRaisesRegex(_rs.ParameterError, "max_correct must be at most nsym / 2"):
+            _rs.RSCodec(8, 40, max_correct=5)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class TruncatedDecodeTests(unittest.TestCase):
+    def setUp(self) -> None:
+        self.message = bytes(range(1, 33))
+        self.codec = _rs.RSCodec(8, 40)
+        self.codeword = self.codec.encode(self.message)
+
+    def test_lost_tail_recovered(self) -> None:
+        for cut in (1, 8):
+            with self.subTest(cut=cut):
+                received = self.codeword[:-cut]
+                with self.assertRaises(_rs.LengthError):
+                    self.codec.decode(received)
+                message, positions = self.codec.decode(received, allow_truncated=True)
+                self.assertEqual(message, self.message)
+                self.assertTrue(all(p >= 40 - cut for p in positions))
+                self.assertEqual(_rs.decode(received, nsym=8, nsize=40, allow_truncated=True)[0], self.message)
+        # Errors fit in what the lost tail leaves of the budget, and the tail counts as erased
+        damaged = bytearray(self.codeword[:-1])
+        damaged[5] ^= 0x40
+        message, positions, erased = self.codec.decode(bytes(damaged), erase_pos=[], allow_truncated=True)
+        self.assertEqual(message, self.message)
+        self.assertIn(5, positions)
+        self.assertNotIn(5, erased)
+        # A full-length codeword decodes as usual
+        self.assertEqual(self.codec.decode(self.codeword, allow_truncated=True), (self.message, []))
+
+    def test_too_much_missing(self) -> None:
+        with self.assertRaisesRegex(_rs.UncorrectableError, r"9 trailing symbols missing, more than nsym=8") as ctx:
+            self.codec.decode(self.codeword[:-9], allow_truncated=True)
+        self.assertEqual((ctx.exception.detected_errors, ctx.exception.capacity), (9, 8))
+        with self.assertRaises(_rs.UncorrectableError):
+            _rs.decode(self.codeword[:-9], nsym=8, nsize=40, allow_truncated=True)
+        with self.assertRaises(_rs.LengthError):
+            self.codec.decode(self.codeword + b"\0", allow_truncated=True)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class PinnedPositionTests(unittest.TestCase):
+    def setUp(self) -> None:
+        self.codec = _rs.RSCodec(4, 12)
+        self.message = bytes(range(1, 9))
+        # Three errors against t = 2: decode "corrects" positions 6 and 9 onto the wrong codeword
+        received = bytearray(self.codec.encode(self.message))
+        for pos, e in ((0, 5), (1, 70), (2, 0x55)):
+            received[pos] ^= e
+        self.received = bytes(received)
+
+    def test_miscorrection_at_pinned_byte_rejected(self) -> None:
+        message, positions = self.codec.decode(self.received)
+        self.assertNotEqual(message, self.message)
+        self.assertEqual(positions, [6, 9])
+        with self.assertRaises(_rs.PinnedPositionConflict) as ctx:
+            self.codec.decode(self.received, pinned=range(4, 8))
+        self.assertEqual(ctx.exception.positions, [6])
+        self.assertIsInstance(ctx.exception, _rs.MiscorrectionError)
+        with self.assertRaises(_rs.PinnedPositionConflict):
+            _rs.decode(self.received, nsym=4, pinned=[9])
+        # Pins away from the corrections change nothing
+        self.assertEqual(self.codec.decode(self.received, pinned=[0, 1]), (message, positions))
+
+    def test_pinned_with_hints(self) -> None:
+        # A hint that leads to a decode clear of the pinned bytes is accepted
+        fixable = bytearray(self.codec.encode(self.message))
+        fixable[0] ^= 1
+        fixable[1] ^= 2
+        fixable[6] ^= 3
+        self.assertEqual(
+            self.codec.decode(bytes(fixable), pinned=[4, 5], on_failure=lambda cw, e: [0, 1]),
+            (self.message, [0, 1, 6]),
+        )
+        # A retry that conflicts is a failed retry: the callback sees the conflict, and once the
+        # hints run out the original error is raised
+        seen = []
+
+        def hint(codeword: bytes, error: Exception) -> list[int]:
+            seen.append(type(error))
+            return [0, 1]
+
+        with self.assertRaises(_rs.UncorrectableError):
+            self.codec.decode(bytes(fixable), pinned=[6], on_failure=hint)
+        self.assertEqual(seen[:2], [_rs.UncorrectableError, _rs.PinnedPositionConflict])
+
+    def test_pinned_outside_codeword(self) -> None:
+        for pinned in ([12], [-1], [300]):
+            with self.subTest(pinned=pinned), self.assertRaises(_rs.ParameterError):
+                self.codec.decode(self.received, pinned=pinned)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class DecodeResultTests(unittest.TestCase):
+    def setUp(self) -> None:
+        self.codec = _rs.RSCodec(nsym=8, nsize=48)
+        self.message = bytes(range(40))
+        self.codeword = self.codec.encode(self.message)
+
+    def test_clean(self) -> None:
+        result = self.codec.decode_detailed(self.codeword)
+        self.assertEqual(result.message, self.message)
+        self.assertEqual(result.positions, [])
+        self.assertEqual(result.magnitudes, b"")
+        self.assertFalse(result.corrected)
+        self.assertEqual(result.num_corrected, 0)
+        self.assertIsNone(result.codeword)
+        message, positions = result
+        self.assertEqual((message, positions), self.codec.decode(self.codeword))
+
+    def test_corrected(self) -> None:
+        damaged = bytearray(self.codeword)
+        damaged[2] ^= 0x80
+        damaged[17] ^= 0x05
+        result = self.codec.decode_detailed(bytes(damaged))
+        self.assertEqual(result.message, self.message)
+        self.assertEqual(result.positions, [2, 17])
+        self.assertEqual(result.magnitudes, bytes([0x80, 0x05]))
+        self.assertTrue(result.corrected)
+        self.assertEqual(result.num_corrected, 2)
+        self.assertEqual(result.codeword, self.codeword)
+        self.assertEqual(tuple(result), self.codec.decode(bytes(damaged)))
+        self.assertEqual(result, self.codec.decode_detailed(bytes(damaged)))
+        self.assertNotEqual(result, self.codec.decode_detailed(self.codeword))
+        self.assertEqual(
+            repr(result),
+            "DecodeResult(num_corrected=2, positions=[2, 17], magnitudes=[128, 5], "
+            "message=<40 bytes>, codeword=<48 bytes>)",
+        )
+
+    def test_parity_only_error(self) -> None:
+        damaged = bytearray(self.codeword)
+        damaged[45] ^= 0xEE
+        result = self.codec.decode_detailed(bytes(damaged))
+        self.assertEqual(result.message, self.message)
+        self.assertEqual(result.positions, [45])
+        self.assertEqual(result.magnitudes, b"\xee")
+        self.assertTrue(result.corrected)
+        self.assertEqual(result.codeword, self.codeword)
+
+    def test_failure_raises(self) -> None:
+        damaged = bytearray(self.codeword)
+        for pos in range(5):
+            damaged[pos] ^= 0xFF
+        with self.assertRaises(_rs.UncorrectableError):
+            self.codec.decode_detailed(bytes(damaged))
+
+    def test_erasures_versus_errors(self) -> None:
+        damaged = bytearray(self.codeword)
+        damaged[3] ^= 0x11  # hinted
+        damaged[30] ^= 0x22  # hinted
+        damaged[12] ^= 0x33  # not hinted
+        result = self.codec.decode_detailed(bytes(damaged), erase_pos=[30, 3, 44])  # 44 is clean
+        self.assertEqual(result.message, self.message)
+        self.assertEqual(result.positions, [3, 12, 30])
+        self.assertEqual(result.erasure_positions, [3, 30])
+        self.assertEqual(result.error_positions, [12])
+        self.assertEqual(result.unused_erasures, [44])
+        self.assertEqual(sorted(result.erasure_positions + result.error_positions), result.positions)
+        self.assertEqual(result.magnitudes, bytes([0x11, 0x33, 0x22]))
+        self.assertIn("erasure_positions=[3, 30], error_positions=[12], unused_erasures=[44])", repr(result))
+        plain = self.codec.decode_detailed(bytes(damaged))
+        self.assertEqual((plain.error_positions, plain.erasure_positions, plain.unused_erasures), ([3, 12, 30], [], []))
+        with self.assertRaises(ValueError):
+            self.codec.decode_detailed(bytes(damaged), erase_pos=[48])
+
+    def test_bit_errors(self) -> None:
+        damaged = bytearray(self.codeword)
+        damaged[5] ^= 0x40  # single bit
+        damaged[17] ^= 0x0C  # bits 2 and 3
+        damaged[44] ^= 0x81  # parity, bits 0 and 7
+        damaged[8] = 0x00 if self.codeword[8] else 0xFF  # erased: replaced, not bit-listed
+        result = self.codec.decode_detailed(bytes(damaged), erase_pos=[8])
+        self.assertEqual(result.positions, [5, 8, 17, 44])
+        self.assertEqual(result.bit_errors, [(5, 6), (17, 2), (17, 3), (44, 0), (44, 7)])
+        self.assertEqual((result.single_bit_errors, result.multi_bit_errors), (1, 2))
+        self.assertEqual(result.erasure_positions, [8])
+        plain = self.codec.decode_detailed(bytes(damaged))
+        self.assertEqual((plain.single_bit_errors + plain.multi_bit_errors, len(plain.error_positions)), (4, 4))
+
+    def test_locations(self) -> None:
+        # RS(48,40): data 0..40, parity 40..48
+        damaged = bytearray(self.codeword)
+        for p in (2, 39, 40, 47):
+            damaged[p] ^= 0x21
+        result = self.codec.decode_detailed(bytes(damaged))
+        self.assertEqual(result.locations, [(2, False, 2), (39, False, 39), (40, True, None), (47, True, None)])
+        self.assertEqual(result.parity_positions, [40, 47])
+        self.assertEqual(result.positions, [2, 39, 40, 47])
+
+    def test_locations_padded(self) -> None:
+        # A 30-byte message padded to k = 40: padding 30..40, reported with message_index None
+        message = bytes(range(100, 130))
+        codeword = bytearray(self.codec.encode(message, pad=True))
+        for p in (5, 33, 44):
+            codeword[p] ^= 0x08
+        result = self.codec.decode_detailed(bytes(codeword), msg_len=30)
+        self.assertEqual(result.message, message)
+        self.assertEqual(result.locations, [(5, False, 5), (33, False, None), (44, True, None)])
+        self.assertEqual(result.magnitudes, bytes([0x08] * 3))
+        self.assertEqual(result.codeword, self.codec.encode(message, pad=True))
+        with self.assertRaises(ValueError):
+            self.codec.decode_detailed(bytes(codeword), msg_len=41)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class CheckManyTests(unittest.TestCase):
+    def test_ten_megabyte_scan(self) -> None:
+        codec = _rs.RSCodec(nsym=8, nsize=255)
+        block = codec.encode(bytes(range(247)))
+        blocks = (10 * 1024 * 1024) // 255
+        buffer = bytearray(block * blocks)
+        dirty = [0, 17, 4000, 31337, blocks - 1]
+        for i in dirty:
+            buffer[i * 255 + (i % 255)] ^= 0x40
+        self.assertEqual(codec.check_many(bytes(buffer), 255), dirty)
+        self.assertEqual(codec.check_many(buffer, 255), dirty)
+
+    def test_tail_mismatch_raises(self) -> None:
+        codec = _rs.RSCodec(nsym=4, nsize=16)
+        buffer = codec.encode(bytes(12)) * 3
+        self.assertEqual(codec.check_many(buffer, 16), [])
+        with self.assertRaisesRegex(_rs.LengthError, "5 trailing bytes"):
+            codec.check_many(buffer + bytes(5), 16)
+        with self.assertRaises(_rs.ParameterError):
+            codec.check_many(buffer, 4)
+
+
+def _corrupted_blocks(count: int, nsym: int = 32, nsize: int = 255) -> list:
+    blocks = []
+    for i in range(count):
+        message = bytes((i * 7 + j) & 0xFF for j in range(nsize - nsym))
+        codeword = bytearray(_rs.encode(nsym, nsize, message))
+        for e in range(nsym // 2):
+            codeword[(i + 13 * e) % nsize] ^= 0x5A
+        blocks.append(bytes(codeword))
+    return blocks
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class GilReleaseTests(unittest.TestCase):
+    def test_decode_releases_gil(self) -> None:
+        # With a huge switch interval the interpreter never preempts this thread, so the
+        # waiting helper can only run if decode drops the GIL inside the native call
+        blocks = _corrupted_blocks(200)
+        go = threading.Event()
+        ran = []
+        helper = threading.Thread(target=lambda: (go.wait(), ran.append(True)))
+        old_interval = sys.getswitchinterval()
+        helper.start()
+        try:
+            sys.setswitchinterval(1000.0)
+            go.set()
+            for _ in range(20):
+                for block in blocks:
+                    _rs.decode(32, 255, block)
+                if ran:
+                    break
+            observed = bool(ran)
+        finally:
+            sys.setswitchinterval(old_interval)
+            helper.join(timeout=10)
+        self.assertTrue(observed, "helper thread never ran during decode")
+
+    def test_readonly_view_of_mutating_buffer(self) -> None:
+        # A read-only view does not make its bytearray immutable: the decode must work on a
+        # snapshot, and each state the writer leaves (1 or 2 errors) decodes to the message
+        codec = _rs.RSCodec(8, 255)
+        message = bytes(range(247))
+        buffer = bytearray(codec.encode(message))
+        buffer[3] ^= 0x21
+        view = memoryview(buffer).toreadonly()
+        stop = threading.Event()
+
+        def writer() -> None:
+            while not stop.is_set():
+                buffer[200] ^= 0x5A
+
+        thread = threading.Thread(target=writer)
+        old_interval = sys.getswitchinterval()
+        sys.setswitchinterval(1e-6)
+        thread.start()
+        try:
+            for _ in range(2000):
+                self.assertEqual(codec.decode(view)[0], message)
+                self.assertEqual(_rs.decode(8, 255, view)[0], message)
+        finally:
+            stop.set()
+            thread.join()
+            sys.setswitchinterval(old_interval)
+
+    def test_thread_pool_matches_sequential(self) -> None:
+        blocks = _corrupted_blocks(64)
+        sequential = [_rs.decode(32, 255, b) for b in blocks]
+        with ThreadPoolExecutor(max_workers=4) as pool:
+            threaded = list(pool.map(lambda b: _rs.decode(32, 255, b), blocks))
+        self.assertEqual(threaded, sequential)
+        decoded, positions = threaded[0]
+        self.assertIsInstance(decoded, bytes)
+        self.assertIsInstance(positions, list)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class ContiguousBlockTests(unittest.TestCase):
+    def test_matches_list_api(self) -> None:
+        messages = [bytes((i + j) & 0xFF for j in range(56)) for i in range(30)]
+        data = b"".join(messages)
+        for parallel in (False, True):
+            with self.subTest(parallel=parallel):
+                codewords = _rs.encode_blocks(8, 64, memoryview(data), parallel=parallel)
+                self.assertEqual(codewords, b"".join(_rs.encode_batch(8, 64, messages)))
+                damaged = bytearray(codewords)
+                damaged[4 * 64 + 10] ^= 0x20
+                for p in range(5):  # block 9: 5 errors > t = 4
+                    damaged[9 * 64 + 3 * p] ^= 0x3C
+                decoded, status = _rs.decode_blocks(8, 64, bytes(damaged), parallel=parallel)
+                self.assertEqual(len(status), 30)
+                self.assertEqual((status[4].positions, status[4].num_corrected, status[4].ok), ([10], 1, True))
+                self.assertEqual(repr(status[4]), "BlockStatus(index=4, positions=[10])")
+                self.assertFalse(status[9].ok)
+                self.assertIsNotNone(status[9].error)
+                self.assertEqual([s.index for s in status], list(range(30)))
+                self.assertEqual(sum(not s.ok for s in status), 1)
+                self.assertEqual(decoded[9 * 56:10 * 56], bytes(damaged[9 * 64:9 * 64 + 56]))
+                self.assertEqual(decoded[:9 * 56] + decoded[10 * 56:], data[:9 * 56] + data[10 * 56:])
+        self.assertEqual(_rs.encode_blocks(8, 64, b""), b"")
+        self.assertEqual(_rs.decode_blocks(8, 64, b""), (b"", []))
+
+    def test_misaligned_lengths_name_the_multiple(self) -> None:
+        with self.assertRaisesRegex(_rs.LengthError, r"data length 100 is not a multiple of k=56 \(44 trailing bytes\)"):
+            _rs.encode_blocks(8, 64, bytes(100))
+        with self.assertRaisesRegex(_rs.LengthError, r"data length 100 is not a multiple of n=64"):
+            _rs.decode_blocks(8, 64, bytes(100))
+        with self.assertRaises(_rs.ParameterError):
+            _rs.encode_blocks(8, 8, bytes(8))
+
+    @unittest.skipUnless(TIMING_TESTS, "wall-clock comparison; set ECC_MODEL_TIMING_TESTS=1")
+    def test_beats_list_api_for_small_blocks(self) -> None:
+        # 100k tiny blocks so per-object overhead dominates; the list path has to split and join
+        nsym, nsize, count = 2, 8, 100_000
+        k = nsize - nsym
+        data = bytes(i & 0xFF for i in range(count * k))
+
+        codewords = _rs.encode_blocks(nsym, nsize, data)
+        listed = _best_of(lambda: b"".join(_rs.encode_batch(nsym, nsize, [data[i:i + k] for i in range(0, len(data), k)])))
+        contiguous = _best_of(lambda: _rs.encode_blocks(nsym, nsize, data))
+        self.assertLess(contiguous, listed, f"100k-block encode: list {listed:.3f}s, contiguous {contiguous:.3f}s")
+
+        listed = _best_of(lambda: _rs.decode_batch(nsym, nsize, [codewords[i:i + nsize] for i in range(0, len(codewords), nsize)]))
+        contiguous = _best_of(lambda: _rs.decode_blocks(nsym, nsize, codewords))
+        self.assertLess(contiguous, listed, f"100k-block decode: list {listed:.3f}s, contiguous {contiguous:.3f}s")
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class PaddedMessageTests(unittest.TestCase):
+    message = b"ten bytes!"
+
+    def test_short_message_with_damage_in_data_and_padding(self) -> None:
+        codeword = _rs.encode(self.message, nsym=32, nsize=255, pad=True)
+        self.assertEqual(len(codeword), 255)
+        self.assertEqual(codeword[10:223], bytes(213))
+        damaged = bytearray(codeword)
+        data_errors = [0, 4, 9, 240, 250, 254] + list(range(224, 234))  # 16 = t
+        pad_errors = list(range(10, 223, 7))  # 31 more, in the zero padding
+        for p in data_errors + pad_errors:
+            damaged[p] ^= 0xA5
+        decoded, positions = _rs.decode(bytes(damaged), nsym=32, msg_len=10)
+        self.assertEqual(decoded, self.message)
+        self.assertEqual(positions, sorted(data_errors + pad_errors))
+        # Naive padding would have to spend capacity on the padding and give up
+        with self.assertRaises(_rs.UncorrectableError):
+            _rs.decode(bytes(damaged), nsym=32)
+
+    def test_codec_methods_and_legacy_calls(self) -> None:
+        codec = _rs.RSCodec(8, 64)
+        codeword = codec.encode(self.message, pad=True)
+        self.assertEqual(codeword, _rs.encode(8, 64, self.message, pad=True))
+        self.assertEqual(codeword, codec.encode(self.message + bytes(46)))
+        damaged = bytearray(codeword)
+        damaged[2] ^= 1
+        damaged[30] ^= 1
+        self.assertEqual(codec.decode(bytes(damaged), msg_len=10), (self.message, [2, 30]))
+        self.assertEqual(codec.decode(bytes(damaged), erase_pos=[2, 30], msg_len=10), (self.message, [2, 30], [2, 30]))
+        self.assertEqual(_rs.decode(8, 64, _rs.encode(8, 64, b"", pad=True), msg_len=0), (b"", []))
+        self.assertEqual(codec.decode(codeword, msg_len=56)[0], self.message + bytes(46))
+
+    def test_lengths_are_validated(self) -> None:
+        codec = _rs.RSCodec(8, 64)
+        with self.assertRaisesRegex(_rs.LengthError, "pass pad=True"):
+            codec.encode(self.message)
+        with self.assertRaises(_rs.LengthError):
+            codec.encode(bytes(57), pad=True)
+        codeword = codec.encode(self.message, pad=True)
+        for msg_len in (-1, 57):
+            with self.subTest(msg_len=msg_len), self.assertRaisesRegex(_rs.ParameterError, r"msg_len=-?\d+ must be in 0..=56"):
+                codec.decode(codeword, msg_len=msg_len)
+
+# encode_file's container: a 32-byte RS-protected header before the codewords and a copy after
+HEADER_BLOCK = 32
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class FileCodecTests(unittest.TestCase):
+    def setUp(self) -> None:
+        self.tmp = tempfile.TemporaryDirectory()
+        self.addCleanup(self.tmp.cleanup)
+
+    def _path(self, name: str) -> str:
+        return os.path.join(self.tmp.name, name)
+
+    def _round_trip(self, data: bytes, nsym: int = 32, nsize: int = 255) -> dict:
+        src, enc, dec = self._path("src"), self._path("enc"), self._path("dec")
+        with open(src, "wb") as f:
+            f.write(data)
+        blocks = _rs.encode_file(src, enc, nsym, nsize)
+        self.assertEqual(blocks, -(-len(data) // (nsize - nsym)))
+        self.assertEqual(os.path.getsize(enc), 2 * HEADER_BLOCK + blocks * nsize)
+        report = _rs.decode_file(enc, dec)
+        with open(dec, "rb") as f:
+            self.assertEqual(f.read(), data)
+        self.assertEqual(report["blocks"], blocks)
+        self.assertEqual(report["length"], len(data))
+        return report
+
+    def test_empty_and_one_byte(self) -> None:
+        self.assertEqual(self._round_trip(b"")["blocks"], 0)
+        self.assertEqual(self._round_trip(b"\x5a")["blocks"], 1)
+
+    def test_multi_megabyte_with_damage(self) -> None:
+        rng = random.Random(332)
+        data = rng.randbytes(3 * 1024 * 1024 + 17)
+        src, enc, dec = self._path("src"), self._path("enc"), self._path("dec")
+        with open(src, "wb") as f:
+            f.write(data)
+        calls = []
+        blocks = _rs.encode_file(src, enc, 32, 255, progress=lambda d, t: calls.append((d, t)), progress_every=1000)
+        self.assertEqual(calls[-1], (blocks, blocks))
+        self.assertEqual(len(calls), blocks // 1000 + 1)
+
+        with open(enc, "r+b") as f:
+            for block in (0, 5, 9000):  # 10 errors each: correctable
+                for i in range(10):
+                    f.seek(HEADER_BLOCK + block * 255 + i * 20)
+                    f.write(b"\xff")
+            for i in range(17):  # one past t = 16
+                f.seek(HEADER_BLOCK + 42 * 255 + i)
+                f.write(bytes([(data[42 * 223 + i] ^ 0x33)]))
+        report = _rs.decode_file(enc, dec, progress=lambda d, t: None, progress_every=4096)
+        self.assertEqual(report["failed_offsets"], [42 * 223])
+        self.assertGreaterEqual(report["corrected_symbols"], 27)
+        with open(dec, "rb") as f:
+            out = f.read()
+        self.assertEqual(len(out), len(data))
+        self.assertEqual(out[: 42 * 223], data[: 42 * 223])
+        self.assertEqual(out[43 * 223 :], data[43 * 223 :])
+
+    def test_repair_in_place(self) -> None:
+        data = random.Random(336).randbytes(200_000)
+        src, enc, dec = self._path("src"), self._path("enc"), self._path("dec")
+        with open(src, "wb") as f:
+            f.write(data)
+        _rs.encode_file(src, enc, 16, 255)
+        with open(enc, "rb") as f:
+            pristine = f.read()
+
+        damaged = bytearray(pristine)
+        for block, count in ((3, 2), (100, 8), (500, 1)):
+            for i in range(count):
+                damaged[HEADER_BLOCK + block * 255 + 7 * i] ^= 0x5A
+        for i in range(9):  # one past t = 8
+            damaged[HEADER_BLOCK + 250 * 255 + i] ^= 0xC3
+        with open(enc, "wb") as f:
+            f.write(damaged)
+
+        with self.assertRaisesRegex(_rs.ParameterError, "encoded with \\(nsym=16, nsize=255\\)"):
+            _rs.repair_file(enc, 32, 255)
+        report = _rs.repair_file(enc, 16, 255)
+        self.assertIsInstance(report, _rs.RepairReport)
+        self.assertEqual(report.corrected, {3: 2, 100: 8, 500: 1})
+        self.assertEqual(report.total_corrected, 11)
+        self.assertEqual(report.unrecoverable, [250])
+        self.assertEqual(report.blocks, -(-len(data) // 239))
+
+        with open(enc, "rb") as f:
+            repaired = f.read()
+        bad = slice(HEADER_BLOCK + 250 * 255, HEADER_BLOCK + 251 * 255)
+        self.assertEqual(repaired[bad], bytes(damaged[bad]))
+        self.assertEqual(repaired[: bad.start], pristine[: bad.start])
+        self.assertEqual(repaired[bad.stop :], pristine[bad.stop :])
+
+        # Restore the unrecoverable block and the file round-trips; a second pass is a no-op
+        with open(enc, "r+b") as f:
+            f.seek(bad.start)
+            f.write(pristine[bad])
+        self.assertEqual(_rs.repair_file(enc).corrected, {})
+        _rs.decode_file(enc, dec)
+        with open(dec, "rb") as f:
+            self.assertEqual(f.read(), data)
+
+    def test_interleaved_burst(self) -> None:
+        data = random.Random(383).randbytes(50_000)
+        src, enc, dec = self._path("src"), self._path("enc"), self._path("dec")
+        with open(src, "wb") as f:
+            f.write(data)
+        blocks = _rs.encode_file(src, enc, 16, 255, interleave=8)
+        self.assertEqual(blocks, -(-len(data) // (8 * 239)) * 8)
+        self.assertEqual(os.path.getsize(enc), 2 * HEADER_BLOCK + blocks * 255)
+        with open(enc, "r+b") as f:  # 64-byte burst: 8 symbols per codeword of group 2, t = 8
+            f.seek(HEADER_BLOCK + 2 * 8 * 255 + 500)
+            burst = f.read(64)
+            f.seek(-64, os.SEEK_CUR)
+            f.write(bytes(b ^ 0xA5 for b in burst))
+        report = _rs.decode_file(enc, dec)
+        self.assertEqual((report["corrected_blocks"], report["corrected_symbols"]), (8, 64))
+        self.assertEqual(report["failed_offsets"], [])
+        with open(dec, "rb") as f:
+            self.assertEqual(f.read(), data)
+        self.assertEqual(len(_rs.repair_file(enc).corrected), 8)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.encode_file(src, enc, 16, 255, interleave=0)
+
+    def test_damaged_header(self) -> None:
+        data = random.Random(386).randbytes(20_000)
+        src, enc, dec = self._path("src"), self._path("enc"), self._path("dec")
+        with open(src, "wb") as f:
+            f.write(data)
+        _rs.encode_file(src, enc, 16, 255)
+        with open(enc, "rb") as f:
+            pristine = f.read()
+        with open(enc, "r+b") as f:  # the leading header copy is lost; the trailer still describes the file
+            f.write(b"\xff" * HEADER_BLOCK)
+        report = _rs.decode_file(enc, dec)
+        self.assertEqual((report["length"], report["failed_offsets"]), (len(data), []))
+        with open(dec, "rb") as f:
+            self.assertEqual(f.read(), data)
+        self.assertEqual(_rs.repair_file(enc).headers_rewritten, 1)
+        with open(enc, "rb") as f:
+            self.assertEqual(f.read(), pristine)
+
+        with open(enc, "r+b") as f:  # both copies gone
+            f.write(b"\xff" * HEADER_BLOCK)
+            f.seek(-HEADER_BLOCK, os.SEEK_END)
+            f.write(b"\xff" * HEADER_BLOCK)
+        with self.assertRaisesRegex(_rs.ECCError, "bad magic"):
+            _rs.decode_file(enc, dec)
+        with open(enc, "wb") as f:  # truncated inside the codewords
+            f.write(pristine[: HEADER_BLOCK + 40 * 255 + 9])
+        with self.assertRaisesRegex(_rs.ECCError, "truncated"):
+            _rs.decode_file(enc, dec)
+
+    def test_callback_exception_stops_job(self) -> None:
+        src, enc = self._path("src"), self._path("enc")
+        with open(src, "wb") as f:
+            f.write(bytes(10_000))
+
+        def boom(done: int, total: int) -> None:
+            raise KeyError(done)
+
+        with self.assertRaises(KeyError):
+            _rs.encode_file(src, enc, 8, 64, progress=boom, progress_every=10)
+
+    def test_bad_inputs(self) -> None:
+        src, enc = self._path("src"), self._path("enc")
+        with open(src, "wb") as f:
+            f.write(b"not an encoded file at all")
+        with self.assertRaisesRegex(_rs.ECCError, "bad magic"):
+            _rs.decode_file(src, enc)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.encode_file(src, enc, 0, 255)
+        with self.assertRaises(FileNotFoundError):
+            _rs.encode_file(self._path("missing"), enc, 32, 255)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class InterleaverTests(unittest.TestCase):
+    def test_interleave_round_trip(self) -> None:
+        il = _rs.Interleaver(3)
+        blocks = [b"abcd", b"efgh", b"ijkl"]
+        self.assertEqual(il.interleave(blocks), b"aeibfjcgkdhl")
+        self.assertEqual(_rs.interleave(blocks), b"aeibfjcgkdhl")
+        self.assertEqual(il.deinterleave(b"aeibfjcgkdhl"), blocks)
+        self.assertEqual(_rs.deinterleave(b"aeibfjcgkdhl", 4, 3), blocks)
+        with self.assertRaises(_rs.LengthError):
+            il.interleave([b"ab", b"cd", b"e"])
+        with self.assertRaises(_rs.ParameterError):
+            _rs.Interleaver(0)
+
+    def test_burst_recovery(self) -> None:
+        data = random.Random(337).randbytes(5000)
+        # A 40-byte burst at depth 8 puts 5 errors in each of 8 codewords. With nsym=8 (t=4)
+        # that is one too many, so 40 bytes needs nsym=10; nsym=8 survives 32-byte bursts.
+        for nsym, burst_len in ((10, 40), (8, 32)):
+            with self.subTest(nsym=nsym, burst=burst_len):
+                stream = bytearray(_rs.encode_interleaved(data, nsym, 255, 8))
+                self.assertEqual(len(stream) % (8 * 255), 0)
+                start = 3000
+                for i in range(start, start + burst_len):
+                    stream[i] ^= 0xFF
+                decoded, positions, parity_positions = _rs.decode_interleaved(bytes(stream), nsym, 255, 8, length=len(data))
+                self.assertEqual(decoded, data)
+                # Offset r into a group of 8 * 255 bytes is byte r // 8 of the group's codeword r % 8,
+                # here always a data byte
+                k = 255 - nsym
+                expected = [(s // 2040 * 8 + s % 2040 % 8) * k + s % 2040 // 8 for s in range(start, start + burst_len)]
+                self.assertEqual(positions, sorted(expected))
+                self.assertEqual(parity_positions, [])
+
+        stream = bytearray(_rs.encode_interleaved(data, 8, 255, 8))
+        for i in range(100, 140):
+            stream[i] ^= 0xFF
+        with self.assertRaisesRegex(_rs.UncorrectableError, "codeword [0-7]: "):
+            _rs.decode_interleaved(bytes(stream), 8, 255, 8)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class EncodeIntoTests(unittest.TestCase):
+    message = bytes(range(100, 156))
+
+    def test_bytearray_and_offset(self) -> None:
+        expected = _rs.encode(self.message, nsym=8)
+        out = bytearray(64)
+        self.assertEqual(_rs.encode_into(8, self.message, out), 64)
+        self.assertEqual(bytes(out), expected)
+
+        frame = bytearray(b"\xee" * 100)
+        self.assertEqual(_rs.encode_into(8, self.message, frame, offset=20), 64)
+        self.assertEqual(frame[20:84], expected)
+        self.assertEqual(frame[:20] + frame[84:], b"\xee" * 36)
+
+    def test_memoryview_slice_of_frame(self) -> None:
+        frame = bytearray(300)
+        view = memoryview(frame)[100:200]
+        _rs.encode_into(8, self.message, view, offset=10)
+        self.assertEqual(frame[110:174], _rs.encode(self.message, nsym=8))
+        self.assertEqual(frame[:110] + frame[174:], bytes(236))
+
+    def test_overlapping_message_is_supported(self) -> None:
+        # Message already sits in the frame where the codeword goes: encode in place
+        frame = bytearray(10) + bytearray(self.message) + bytearray(8 + 10)
+        _rs.encode_into(8, memoryview(frame)[10:66], frame, offset=10)
+        self.assertEqual(frame[10:74], _rs.encode(self.message, nsym=8))
+        # Shifted overlap: the message is read completely before the output is written
+        frame = bytearray(self.message) + bytearray(30)
+        _rs.encode_into(8, memoryview(frame)[:56], frame, offset=20)
+        self.assertEqual(frame[20:84], _rs.encode(self.message, nsym=8))
+
+    @unittest.skipIf(numpy is None, "numpy not installed")
+    def test_numpy_output(self) -> None:
+        out = numpy.zeros(80, dtype=numpy.uint8)
+        _rs.encode_into(8, self.message, out, offset=16)
+        self.assertEqual(out[16:].tobytes(), _rs.encode(self.message, nsym=8))
+
+    def test_rejects_bad_output(self) -> None:
+        with self.assertRaisesRegex(TypeError, "read-only"):
+            _rs.encode_into(8, self.message, bytes(64))
+        with self.assertRaisesRegex(_rs.LengthError, "no room for n=64 bytes at offset 1"):
+            _rs.encode_into(8, self.message, bytearray(64), offset=1)
+        with self.assertRaises(_rs.LengthError):
+            _rs.encode_into(8, self.message, bytearray(64), offset=1000)
+        with self.assertRaises(TypeError):
+            _rs.encode_into(8, self.message, memoryview(bytearray(128))[::2])
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class DecodeInplaceTests(unittest.TestCase):
+    message = bytes(range(100, 156))
+
+    def test_bytearray_is_corrected_in_place(self) -> None:
+        codeword = _rs.encode(self.message, nsym=8)
+        buf = bytearray(codeword)
+        buf[3] ^= 0x11
+        buf[60] ^= 0x80
+        self.assertEqual(sorted(_rs.decode_inplace(8, buf)), [3, 60])
+        self.assertEqual(bytes(buf), codeword)
+        self.assertEqual(_rs.decode_inplace(8, buf), [])
+
+    @unittest.skipIf(numpy is None, "numpy not installed")
+    def test_writable_numpy_view(self) -> None:
+        codeword = _rs.encode(self.message, nsym=8)
+        frame = numpy.zeros(200, dtype=numpy.uint8)
+        frame[50:114] = numpy.frombuffer(codeword, dtype=numpy.uint8)
+        frame[52] ^= 0xFF
+        view = frame[50:114]
+        self.assertEqual(_rs.decode_inplace(8, view), [2])
+        self.assertEqual(frame[50:114].tobytes(), codeword)
+
+    def test_block_run_with_offsets_into_the_buffer(self) -> None:
+        blocks = [_rs.encode(bytes([i]) * 56, nsym=8) for i in range(20)]
+        buf = bytearray(b"".join(blocks))
+        shared = memoryview(buf)
+        buf[5 * 64 + 1] ^= 1
+        buf[17 * 64 + 63] ^= 2
+        self.assertEqual(_rs.decode_inplace(8, shared, block_len=64), [5 * 64 + 1, 17 * 64 + 63])
+        self.assertEqual(bytes(buf), b"".join(blocks))
+
+    def test_failure_leaves_the_buffer_untouched(self) -> None:
+        blocks = [_rs.encode(bytes([i]) * 56, nsym=8) for i in range(4)]
+        buf = bytearray(b"".join(blocks))
+        buf[0] ^= 1  # correctable, but must not be applied
+        for p in range(5):  # 5 errors > t = 4
+            buf[2 * 64 + 3 * p] ^= 0x3C
+        before = bytes(buf)
+        with self.assertRaisesRegex(_rs.UncorrectableError, "codeword 2: "):
+            _rs.decode_inplace(8, buf, block_len=64)
+        self.assertEqual(bytes(buf), before)
+        single = bytearray(buf[128:192])
+        with self.assertRaises(_rs.UncorrectableError):
+            _rs.decode_inplace(8, single)
+        self.assertEqual(bytes(single), before[128:192])
+
+    def test_rejects_bad_buffers(self) -> None:
+        with self.assertRaisesRegex(TypeError, "read-only"):
+            _rs.decode_inplace(8, _rs.encode(self.message, nsym=8))
+        with self.assertRaisesRegex(_rs.LengthError, "must be in nsym\\+1..=255"):
+            _rs.decode_inplace(8, bytearray(300))
+        with self.assertRaisesRegex(_rs.LengthError, "10 trailing bytes"):
+            _rs.decode_inplace(8, bytearray(138), block_len=64)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.decode_inplace(0, bytearray(64))
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class RepairDetachedTests(unittest.TestCase):
+    def setUp(self) -> None:
+        self.page = bytes((i * 37 + 5) & 0xFF for i in range(200))
+        codeword = _rs.encode(self.page, nsym=16)
+        self.parity = codeword[200:]
+
+    def test_data_only(self) -> None:
+        data = bytearray(self.page)
+        for pos in (0, 77, 199):
+            data[pos] ^= 0x3C
+        info = _rs.repair_detached(16, data, self.parity)
+        self.assertEqual(bytes(data), self.page)
+        self.assertEqual((info.data_positions, info.parity_positions), ([0, 77, 199], []))
+        self.assertFalse(info.parity_corrected)
+        self.assertIsNone(info.parity)
+        self.assertEqual(info.num_corrected, 3)
+
+    def test_parity_only(self) -> None:
+        data = bytearray(self.page)
+        damaged = bytearray(self.parity)
+        damaged[0] ^= 1
+        damaged[15] ^= 0xFF
+        info = _rs.repair_detached(16, data, bytes(damaged), return_parity=True)
+        self.assertEqual(bytes(data), self.page)
+        self.assertEqual((info.data_positions, info.parity_positions), ([], [0, 15]))
+        self.assertTrue(info.parity_corrected)
+        self.assertEqual(info.parity, self.parity)
+
+    def test_data_and_parity(self) -> None:
+        data = bytearray(self.page)
+        data[10] ^= 0x80
+        data[150] ^= 0x01
+        damaged = bytearray(self.parity)
+        damaged[3] ^= 0x42
+        info = _rs.repair_detached(16, data, damaged, return_parity=True)
+        self.assertEqual(bytes(data), self.page)
+        self.assertEqual((info.data_positions, info.parity_positions), ([10, 150], [3]))
+        self.assertEqual(info.parity, self.parity)
+        self.assertEqual(damaged[3], self.parity[3] ^ 0x42)  # the parity buffer itself is not written
+        self.assertEqual(_rs.repair_detached(16, data, self.parity).num_corrected, 0)
+
+    def test_uncorrectable_leaves_data_untouched(self) -> None:
+        data = bytearray(self.page)
+        for pos in range(0, 180, 20):
+            data[pos] ^= 0x55
+        before = bytes(data)
+        with self.assertRaises(_rs.UncorrectableError) as ctx:
+            _rs.repair_detached(16, data, self.parity)
+        self.assertEqual(bytes(data), before)
+        self.assertEqual(ctx.exception.capacity, 8)
+        joined = _rs.syndrome_weight(16, before + self.parity)
+        self.assertEqual(ctx.exception.syndrome_weight, joined)
+
+    def test_rejects_bad_buffers(self) -> None:
+        with self.assertRaisesRegex(TypeError, "read-only"):
+            _rs.repair_detached(16, self.page, self.parity)
+        with self.assertRaisesRegex(_rs.LengthError, "parity length 15"):
+            _rs.repair_detached(16, bytearray(self.page), self.parity[:15])
+        with self.assertRaisesRegex(_rs.LengthError, "data length 240"):
+            _rs.repair_detached(16, bytearray(240), self.parity)
+
+
+@unittest.skipIf(_rs is None or not hasattr(_rs, "corrupt"), "needs the extension built with the `sim` feature")
+class CorruptTests(unittest.TestCase):
+    codeword = bytes(range(64))
+
+    def test_deterministic_for_seed(self) -> None:
+        a = _rs.corrupt(self.codeword, n_errors=5, burst_len=4, seed=1234)
+        self.assertEqual(a, _rs.corrupt(self.codeword, n_errors=5, burst_len=4, seed=1234))
+        self.assertNotEqual(a, _rs.corrupt(self.codeword, n_errors=5, burst_len=4, seed=1235))
+        # Pinned so the stream cannot drift between platforms or releases
+        self.assertEqual(_rs.corrupt(self.codeword, n_errors=3, seed=0)[1], [2, 27, 62])
+
+    def test_positions_are_exactly_the_changed_bytes(self) -> None:
+        for seed in range(20):
+            corrupted, positions, erased = _rs.corrupt(self.codeword, 3, 4, 5, seed=seed)
+            changed = [i for i in range(64) if corrupted[i] != self.codeword[i]]
+            self.assertEqual(positions, changed)
+            self.assertEqual(len(positions), 12)
+            self.assertEqual(len(erased), 4)
+            self.assertTrue(set(erased) <= set(positions))
+
+    def test_round_trip_through_decoder(self) -> None:
+        message = bytes(range(48))
+        codeword = _rs.encode(message, nsym=16)
+        corrupted, positions, erased = _rs.corrupt(codeword, n_errors=3, n_erasures=6, seed=9)
+        self.assertEqual(_rs.decode(corrupted, nsym=16, erase_pos=erased), (message, positions, sorted(erased)))
+        corrupted, positions = _rs.corrupt(codeword, n_errors=8, seed=None)
+        self.assertEqual(_rs.decode(corrupted, nsym=16), (message, positions))
+
+    def test_too_many_positions(self) -> None:
+        with self.assertRaises(_rs.ParameterError):
+            _rs.corrupt(bytes(10), n_errors=6, burst_len=5)
+
+
+@unittest.skipIf(_rs is None or not hasattr(_rs, "simulate"), "needs the extension built with the `sim` feature")
+class SimulateTests(unittest.TestCase):
+    def test_flip_first_byte_is_deterministic(self) -> None:
+        def flip_first(codeword: bytearray, rng_seed: int) -> None:
+            codeword[0] ^= 0xFF
+
+        stats = _rs.simulate(8, 40, 500, channel=flip_first, seed=1)
+        self.assertEqual(stats, {"trials": 500, "clean": 0, "corrected": 500, "detected": 0, "miscorrected": 0,
+                                 "block_failure_rate": 0.0, "histogram": [0, 500] + [0] * 7})
+        self.assertEqual(_rs.simulate(8, 40, 500, channel=flip_first, seed=2), stats)
+
+    def test_seeded_python_channel_is_reproducible(self) -> None:
+        seen = []
+
+        def noisy(codeword: bytearray, rng_seed: int) -> None:
+            seen.append(rng_seed)
+            rng = random.Random(rng_seed)
+            for pos in rng.sample(range(len(codeword)), rng.randrange(7)):
+                codeword[pos] ^= rng.randrange(1, 256)
+
+        a = _rs.simulate(8, 40, 300, channel=noisy, seed=360)
+        first_seeds = list(seen)
+        self.assertEqual(_rs.simulate(8, 40, 300, channel=noisy, seed=360), a)
+        self.assertEqual(seen[300:], first_seeds)
+        self.assertEqual(len(set(first_seeds)), 300)
+        self.assertEqual(a["clean"] + a["corrected"] + a["detected"] + a["miscorrected"], 300)
+        self.assertEqual(sum(a["histogram"]), 300 - a["detected"])
+        self.assertGreater(a["detected"], 0)  # 5 and 6 errors exceed t = 4
+
+    def test_native_channels(self) -> None:
+        stats = _rs.simulate(32, 255, 2000, "symbol_errors", param=16, seed=3)
+        self.assertEqual((stats["corrected"], stats["histogram"][16]), (2000, 2000))
+        self.assertEqual(_rs.simulate(32, 255, 2000, "symbol_errors", param=16, seed=3), stats)
+        beyond = _rs.simulate(8, 40, 1000, "symbol_errors", param=5)
+        self.assertEqual(beyond["detected"] + beyond["miscorrected"], 1000)
+        self.assertEqual(beyond["block_failure_rate"], 1.0)
+        for name, param in (("symbol_error_rate", 0.05), ("burst", 4), ("erasures", 2), ("bit_flips", 3), ("bit_error_rate", 0.01)):
+            with self.subTest(channel=name):
+                stats = _rs.simulate(8, 40, 200, name, param=param)
+                self.assertEqual(stats["trials"], 200)
+
+    def test_invalid_channels(self) -> None:
+        with self.assertRaises(_rs.ParameterError):
+            _rs.simulate(8, 40, 10, "gaussian", param=1)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.simulate(8, 40, 10, "symbol_errors")
+        with self.assertRaises(_rs.ParameterError):
+            _rs.simulate(8, 40, 10, "symbol_errors", param=41)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.simulate(8, 40, 10, "symbol_error_rate", param=1.5)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.simulate(8, 40, 10, "bit_error_rate", param=-0.1)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.simulate(8, 40, 10, 42)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.simulate(8, 40, 10, lambda cw, seed: None, param=3)
+        with self.assertRaises(_rs.LengthError):
+            _rs.simulate(8, 40, 10, lambda cw, seed: cw.append(0))
+
+        calls = []
+
+        def fails_on_third(codeword: bytearray, rng_seed: int) -> None:
+            calls.append(rng_seed)
+            if len(calls) == 3:
+                raise OSError("channel trace ended")
+
+        with self.assertRaisesRegex(OSError, "trace ended"):
+            _rs.simulate(8, 40, 10, fails_on_third)
+        self.assertEqual(len(calls), 3)
+
+
+def _reedsolo_encode(message: bytes, nsym: int, fcr: int = 0, generator: int = 2, prim: int = 0x11D) -> bytes:
+    # Port of reedsolo's rs_generator_poly + rs_simple_encode_msg (polynomials highest degree first):
+    # g(x) = prod (x - generator^(fcr + i)), then the remainder of msg(x) * x^nsym by g(x)
+    def gf_pow(x: int, power: int) -> int:
+        result = 1
+        for _ in range(power):
+            result = _gf_mul(result, x, prim)
+        return result
+
+    gen = [1]
+    for i in range(nsym):
+        root = gf_pow(generator, i + fcr)
+        gen = [a ^ _gf_mul(b, root, prim) for a, b in zip(gen + [0], [0] + gen)]
+    out = list(message) + [0] * nsym
+    for i in range(len(message)):
+        coef = out[i]
+        if coef:
+            for j in range(1, len(gen)):
+                out[i + j] ^= _gf_mul(gen[j], coef, prim)
+    return bytes(message) + bytes(out[len(message):])
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class CodeOptionsTests(unittest.TestCase):
+    def test_defaults_unchanged(self) -> None:
+        # Output shown in the reedsolo README for RSCodec(10).encode(b"hello world")
+        readme = b"hello world\xed%T\xc4\xfd\xfd\x89\xf3\xa8\xaa"
+        codec = _rs.RSCodec(10, 21)
+        self.assertEqual(codec.encode(b"hello world"), readme)
+        self.assertEqual(_rs.RSCodec(10, 21, fcr=0, generator=2, prim=0x11D).encode(b"hello world"), readme)
+        self.assertEqual(repr(codec), "RSCodec(nsym=10, nsize=21)")
+        self.assertEqual((codec.fcr, codec.generator_element, codec.prim), (0, 2, 0x11D))
+
+    def test_reedsolo_fcr1_vector(self) -> None:
+        message = b"interop with reedsolo, fcr=1"
+        vector = _reedsolo_encode(message, 12, fcr=1)
+        codec = _rs.RSCodec(12, len(vector), fcr=1, prim=0x11D)
+        self.assertEqual(codec.encode(message), vector)
+        self.assertNotEqual(vector, _rs.encode(message, nsym=12))
+        damaged = bytearray(vector)
+        for pos in (0, 9, 20, 31, 39):
+            damaged[pos] ^= 0x6B
+        self.assertEqual(codec.decode(bytes(damaged)), (message, [0, 9, 20, 31, 39]))
+        erased = bytearray(vector)
+        for pos in range(12):
+            erased[pos * 3] = 0
+        self.assertEqual(codec.decode(bytes(erased), erase_pos=[p * 3 for p in range(12)])[0], message)
+        self.assertEqual(codec.syndromes(vector), bytes(12))
+
+    def test_other_fields_and_generators(self) -> None:
+        message = bytes(range(30))
+        for fcr, generator, prim in ((1, 2, 0x12D), (120, 0x80, 0x11D), (3, 2, 0x187)):
+            with self.subTest(fcr=fcr, generator=generator, prim=prim):
+                codec = _rs.RSCodec(8, 38, fcr=fcr, generator=generator, prim=prim)
+                vector = _reedsolo_encode(message, 8, fcr, generator, prim)
+                self.assertEqual(codec.encode(message), vector)
+                damaged = bytearray(vector)
+                damaged[5] ^= 1
+                damaged[37] ^= 0xFF
+                self.assertEqual(codec.decode(bytes(damaged)), (message, [5, 37]))
+                self.assertEqual(repr(pickle.loads(pickle.dumps(codec))), repr(codec))
+                self.assertEqual(pickle.loads(pickle.dumps(codec)).encode(message), vector)
+        self.assertEqual(
+            repr(_rs.RSCodec(8, 38, fcr=1, prim=0x12D)), "RSCodec(nsym=8, nsize=38, fcr=1, generator=2, prim=0x12d)"
+        )
+
+    def test_invalid_combinations(self) -> None:
+        cases = [
+            (dict(prim=0x11B), "prim must be a primitive polynomial"),  # irreducible, x has order 51
+            (dict(prim=0x100), "prim must be a primitive polynomial"),
+            (dict(prim=0x1011D), "prim must be a primitive polynomial"),
+            (dict(generator=8), "generator must be a primitive element"),  # alpha^3, order 85
+            (dict(generator=0), "generator must be a primitive element"),
+            (dict(generator=256), "not an element of GF\\(256\\)"),
+            (dict(fcr=255), "fcr must be less than 255"),
+        ]
+        for kwargs, pattern in cases:
+            with self.subTest(**kwargs):
+                with self.assertRaisesRegex(ValueError, pattern):
+                    _rs.RSCodec(8, 38, **kwargs)
+        with self.assertRaises(_rs.UncorrectableError) as ctx:
+            codec = _rs.RSCodec(4, 20, fcr=1)
+            damaged = bytearray(codec.encode(bytes(16)))
+            for pos in range(5):
+                damaged[pos] ^= 0x11
+            codec.decode(bytes(damaged))
+        self.assertEqual(ctx.exception.syndrome_weight, sum(1 for s in codec.syndromes(bytes(damaged)) if s))
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class ReedsoloShimTests(unittest.TestCase):
+    def test_readme_examples(self) -> None:
+        # reedsolo's README snippets, with only the import changed
+        from ecc_model.reedsolo import RSCodec, ReedSolomonError
+
+        rsc = RSCodec(10)  # 10 ecc symbols
+        self.assertEqual(rsc.encode([1, 2, 3, 4]), b"\x01\x02\x03\x04,\x9d\x1c+=\xf8h\xfa\x98M")
+        self.assertEqual(rsc.encode(bytearray([1, 2, 3, 4])), bytearray(b"\x01\x02\x03\x04,\x9d\x1c+=\xf8h\xfa\x98M"))
+        self.assertEqual(rsc.encode(b"hello world"), b"hello world\xed%T\xc4\xfd\xfd\x89\xf3\xa8\xaa")
+
+        tampered_msg = b"heXlo worXd\xed%T\xc4\xfdX\x89\xf3\xa8\xaa"
+        self.assertEqual(rsc.decode(tampered_msg)[0], b"hello world")
+        with self.assertRaises(ReedSolomonError):
+            rsc.decode(b"hXXXo worXd\xed%T\xc4\xfdXX\xf3\xa8\xaa")  # 6 errors - fail
+        self.assertEqual(rsc.decode(b"hXXXo worXd\xed%T\xc4\xfdXX\xf3\xa8\xaa", erase_pos=[1, 2, 3])[0], b"hello world")
+
+        self.assertEqual(rsc.check(b"hello worXd\xed%T\xc4\xfdX\x89\xf3\xa8\xaa"), [False])
+        rmes, rmesecc, errata_pos = rsc.decode(tampered_msg)
+        self.assertEqual(rsc.check(rmesecc), [True])
+        self.assertEqual(rmesecc, b"hello world\xed%T\xc4\xfd\xfd\x89\xf3\xa8\xaa")
+        self.assertEqual(errata_pos, [2, 9, 16])
+
+        rsc = RSCodec(12)  # using 2 more ecc symbols (to correct max 6 errors or 12 erasures)
+        self.assertEqual(rsc.encode(b"hello world"), b"hello world?Ay\xb2\xbc\xdc\x01q\xb9\xe3\xe2=")
+        self.assertEqual(rsc.decode(b"hello worXXXXy\xb2XX\x01q\xb9\xe3\xe2=")[0], b"hello world")  # 6 errors - ok
+        self.assertEqual(
+            rsc.decode(b"helXXXXXXXXXXy\xb2XX\x01q\xb9\xe3\xe2=", erase_pos=[3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 15, 16])[0],
+            b"hello world",
+        )  # 12 erasures - ok
+        self.assertEqual(rsc.maxerrata(), (6, 12))
+        self.assertEqual(rsc.maxerrata(erasures=6), (3, 6))
+        self.assertEqual(rsc.maxerrata(errors=5), (5, 2))
+        with self.assertRaises(ReedSolomonError):
+            rsc.maxerrata(errors=7)
+
+    def test_chunking(self) -> None:
+        from ecc_model.reedsolo import RSCodec
+
+        rsc = RSCodec(10, nsize=40)
+        data = bytes(random.Random(342).randrange(256) for _ in range(1000))
+        encoded = rsc.encode(data)
+        self.assertIsInstance(encoded, bytearray)
+        # 33 full chunks of 30 bytes plus a 10-byte tail
+        self.assertEqual(len(encoded), 33 * 40 + 20)
+        self.assertEqual(encoded[40:80], _rs.RSCodec(10, 40).encode(data[30:60]))
+        self.assertEqual(rsc.check(encoded), [True] * 34)
+        damaged = bytearray(encoded)
+        for pos in (0, 1, 500, 1339):
+            damaged[pos] ^= 0xFF
+        decoded, full, errata = rsc.decode(damaged)
+        self.assertEqual((decoded, full, errata), (data, encoded, [0, 1, 500, 1339]))
+        self.assertEqual(rsc.decode(damaged, erase_pos=[1339])[0], data)
+        self.assertEqual(rsc.encode("h\xe9llo"), rsc.encode(b"h\xe9llo"))
+        self.assertEqual(rsc.encode(b""), b"")
+        # Per-call nsym, as reedsolo allows
+        self.assertEqual(len(rsc.encode(b"abc", nsym=4)), 7)
+        self.assertEqual(rsc.decode(rsc.encode(b"abc", nsym=4), nsym=4)[0], b"abc")
+
+    def test_failures(self) -> None:
+        from ecc_model.reedsolo import RSCodec, ReedSolomonError
+
+        rsc = RSCodec(4, nsize=20)
+        encoded = bytearray(rsc.encode(bytes(40)))
+        with self.assertRaises(_rs.LengthError):
+            rsc.decode(encoded[:43])  # 3-byte tail chunk, not longer than nsym
+        encoded[25:28] = b"xyz"
+        with self.assertRaisesRegex(_rs.UncorrectableError, r"^chunk 1: "):
+            rsc.decode(encoded)
+        with self.assertRaises(ValueError):
+            rsc.decode(encoded, erase_pos=[len(encoded)])
+        self.assertEqual(rsc.check(encoded[:43]), [True, False, False])
+        for kwargs in (dict(nsize=256), dict(c_exp=12), dict(prim=0x11B)):
+            with self.subTest(**kwargs):
+                with self.assertRaises(ReedSolomonError):
+                    RSCodec(4, **kwargs)
+        fcr1 = RSCodec(8, nsize=40, fcr=1)
+        self.assertEqual(fcr1.encode(b"abc"), _reedsolo_encode(b"abc", 8, fcr=1))
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class GfModuleTests(unittest.TestCase):
+    def test_scalar_ops(self) -> None:
+        from ecc_model._rs import gf
+
+        self.assertEqual(gf.mul(3, 7), 9)  # (x + 1)(x^2 + x + 1) = x^3 + 1, no reduction
+        self.assertEqual(gf.mul(2, 0x80), 0x1D)  # x^8 = x^4 + x^3 + x^2 + 1
+        self.assertEqual(gf.div(9, 3), 7)
+        self.assertEqual(gf.inv(2), 0x8E)  # 2 * 0x8e = 0x11c, reduced by 0x11d gives 1
+        self.assertEqual((gf.exp(8), gf.log(0x1D), gf.exp(255), gf.exp(-1)), (0x1D, 8, 1, 0x8E))
+        self.assertEqual((gf.pow(2, 8), gf.pow(2, -1), gf.pow(0, 0), gf.pow(0, 3)), (0x1D, 0x8E, 1, 0))
+        self.assertEqual(gf.PRIM_POLY, 0x11D)
+        rng = random.Random(344)
+        for _ in range(200):
+            a, b = rng.randrange(256), rng.randrange(1, 256)
+            self.assertEqual(gf.mul(a, b), _gf_mul(a, b))
+            self.assertEqual(gf.mul(gf.div(a, b), b), a)
+            self.assertEqual(gf.exp(gf.log(b)), b)
+
+    def test_polynomials(self) -> None:
+        from ecc_model._rs import gf
+
+        self.assertEqual(gf.poly_mul([1, 1], [1, 1]), b"\x01\x00\x01")  # (x + 1)^2 = x^2 + 1
+        self.assertEqual(gf.poly_eval([5, 3], 2), 3)  # 5 + 3 * 2
+        self.assertEqual(gf.poly_eval(b"\x01\x00\x01", 1), 0)
+        self.assertEqual(gf.poly_div([1, 0, 1], [1, 1]), (b"\x01\x01", b"\x00"))
+        self.assertEqual(gf.poly_div([7], [1, 1]), (b"", b"\x07"))
+        generator = _rs.generator_poly(6)
+        for i in range(6):
+            self.assertEqual(gf.poly_eval(generator, gf.exp(i)), 0)
+        # A codeword read as a polynomial (highest power first) is a multiple of the generator
+        codeword = _rs.encode(bytes(range(20)), nsym=6)
+        self.assertEqual(gf.poly_div(codeword[::-1], generator)[1], bytes(6))
+
+    def test_errors(self) -> None:
+        import ecc_model._rs.gf as gf
+
+        with self.assertRaises(ZeroDivisionError):
+            gf.div(5, 0)
+        with self.assertRaises(ZeroDivisionError):
+            gf.inv(0)
+        with self.assertRaises(ZeroDivisionError):
+            gf.pow(0, -1)
+        with self.assertRaises(ZeroDivisionError):
+            gf.poly_div([1, 2], [0, 0])
+        with self.assertRaisesRegex(ValueError, r"log\(0\) is undefined"):
+            gf.log(0)
+        with self.assertRaisesRegex(ValueError, "b=256 is not an element"):
+            gf.mul(1, 256)
+        with self.assertRaisesRegex(ValueError, "element 1 = 300"):
+            gf.poly_eval([1, 300], 2)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class DecoderStageTests(unittest.TestCase):
+    def test_stages_compose_to_decode(self) -> None:
+        nsym = 16
+        message = bytes(range(80, 180))
+        codeword = _rs.encode(message, nsym=nsym)
+        n = len(codeword)
+        rng = random.Random(345)
+        for n_errors in range(nsym // 2 + 1):
+            received = bytearray(codeword)
+            for pos in rng.sample(range(n), n_errors):
+                received[pos] ^= rng.randrange(1, 256)
+            damaged = bytes(received)
+            with self.subTest(n_errors=n_errors):
+                syndromes = _rs.syndromes(nsym, received)
+                sigma = _rs.berlekamp_massey(syndromes)
+                self.assertEqual(len(sigma) - 1, n_errors)
+                positions = _rs.chien_search(sigma, n)
+                magnitudes = _rs.forney(syndromes, sigma, positions, n)
+                for pos, magnitude in zip(positions, magnitudes):
+                    received[pos] ^= magnitude
+                self.assertEqual(_rs.syndromes(nsym, received), bytes(nsym))
+                self.assertEqual(received, codeword)
+                self.assertEqual(_rs.decode(nsym, n, damaged), (bytes(received[:-nsym]), positions))
+
+    def test_stage_input_validation(self) -> None:
+        with self.assertRaisesRegex(_rs.LengthError, "constant term"):
+            _rs.chien_search(b"", 10)
+        with self.assertRaisesRegex(ValueError, r"sigma\[0\] must be 1"):
+            _rs.chien_search(b"\x00\x01", 10)
+        with self.assertRaisesRegex(ValueError, "n must be in 1..=255"):
+            _rs.chien_search(b"\x01\x02", 256)
+        with self.assertRaisesRegex(ValueError, "n must be in 1..=255"):
+            _rs.forney(b"\x01\x02", b"\x01\x02", [], 0)
+        with self.assertRaisesRegex(ValueError, "position 10 out of range for n=10"):
+            _rs.forney(b"\x01\x02", b"\x01\x02", [10], 10)
+        self.assertEqual(_rs.berlekamp_massey(b""), b"\x01")
+        # sigma longer than 256 bytes folds x^299 onto x^44 instead of overrunning a buffer
+        long_sigma = bytes([1] + [0] * 298 + [5])
+        self.assertEqual(_rs.chien_search(long_sigma, 255), _rs.chien_search(bytes([1] + [0] * 43 + [5]), 255))
+        _rs.forney(b"\x01\x02", long_sigma, [0], 255)
+        self.assertEqual(_rs.berlekamp_massey(bytes(8)), b"\x01")
+        self.assertEqual(_rs.forney(b"", b"\x01", [], 5), b"")
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class StreamCodecTests(unittest.TestCase):
+    nsym, nsize = 16, 255
+
+    def setUp(self) -> None:
+        self.data = random.Random(346).randbytes(2 * 1024 * 1024 + 1000)  # not a multiple of k
+        k = self.nsize - self.nsym
+        blocks = [self.data[i:i + k] for i in range(0, len(self.data), k)]
+        # Batch path: whole blocks through encode_batch, the tail as a shortened codeword
+        self.encoded = b"".join(_rs.encode_batch(self.nsym, self.nsize, blocks[:-1])) + _rs.encode(blocks[-1], nsym=self.nsym)
+
+    def _pipe(self, stage, stream: bytes, piece: int) -> bytes:
+        out = [stage.feed(stream[i:i + piece]) for i in range(0, len(stream), piece)]
+        out.append(stage.flush())
+        return b"".join(out)
+
+    def test_chunk_sizes_match_batch(self) -> None:
+        damaged = bytearray(self.encoded)
+        for pos in range(0, len(damaged), 1000):
+            damaged[pos] ^= 0x5A
+        for piece in (1, 64 * 1024):
+            with self.subTest(piece=piece):
+                encoder = _rs.StreamEncoder(self.nsym, self.nsize)
+                self.assertEqual(self._pipe(encoder, self.data, piece), self.encoded)
+                decoder = _rs.StreamDecoder(self.nsym, self.nsize)
+                self.assertEqual(self._pipe(decoder, bytes(damaged), piece), self.data)
+                self.assertEqual(decoder.blocks, -(-len(self.data) // (self.nsize - self.nsym)))
+                self.assertEqual(decoder.corrected_symbols, len(range(0, len(damaged), 1000)))
+                self.assertEqual(decoder.failed_offsets, [])
+                self.assertEqual(decoder.pending, 0)
+
+    def test_irregular_chunks_and_failures(self) -> None:
+        rng = random.Random(3460)
+        encoder = _rs.StreamEncoder(4, 20)
+        data = rng.randbytes(1000)
+        pieces, i = [], 0
+        while i < len(data):
+            step = rng.choice([0, 1, 7, 16, 17, 100])
+            pieces.append(data[i:i + step])
+            i += step
+        encoded = b"".join(encoder.feed(p) for p in pieces) + encoder.flush()
+        self.assertEqual(encoded, _rs.RSCodec(4, 20).encode_chunked(data))
+        self.assertEqual(encoder.feed(b"abc"), b"")
+        self.assertEqual(encoder.pending, 3)
+        self.assertEqual(repr(encoder), "StreamEncoder(nsym=4, nsize=20, pending=3)")
+
+        damaged = bytearray(encoded)
+        damaged[40:43] = b"\xff\xff\xff"  # block 2: 3 errors > t = 2
+        decoder = _rs.StreamDecoder(4, 20)
+        decoded = decoder.feed(bytes(damaged[:50])) + decoder.feed(bytes(damaged[50:])) + decoder.flush()
+        self.assertEqual(len(decoded), 1000)
+        self.assertEqual(decoded[:32] + decoded[48:], data[:32] + data[48:])
+        self.assertEqual(decoder.failed_offsets, [32])
+        self.assertIn("failed=1", repr(decoder))
+        decoder.feed(encoded[:23])
+        with self.assertRaisesRegex(_rs.LengthError, "stream ends with 3 bytes"):
+            decoder.flush()
+        with self.assertRaises(ValueError):
+            _rs.StreamDecoder(20, 20)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class ShardCodecTests(unittest.TestCase):
+    def test_lose_m_shards_of_ten_megabytes(self) -> None:
+        codec = _rs.ShardCodec(10, 4)
+        data = random.Random(348).randbytes(10 * 1024 * 1024 + 3)
+        shards = codec.split(data)
+        self.assertEqual(len(shards), 14)
+        self.assertEqual({len(s) for s in shards}, {codec.shard_len(len(data))})
+        self.assertEqual(b"".join(shards[:10])[8:8 + len(data)], data)  # systematic after the length prefix
+        rng = random.Random(3480)
+        for trial in range(2):
+            lost = set(rng.sample(range(14), 4))
+            with self.subTest(lost=sorted(lost)):
+                survivors = {i: s for i, s in enumerate(shards) if i not in lost}
+                self.assertEqual(codec.join(survivors), data)
+                self.assertEqual(codec.reconstruct([None if i in lost else s for i, s in enumerate(shards)]), shards)
+
+    def test_reconstruct_range(self) -> None:
+        codec = _rs.ShardCodec(4, 2)
+        shards = codec.split(random.Random(405).randbytes(5000))
+        survivors = {i: shards[i] for i in (0, 2, 3, 5)}
+        for index in (1, 4):
+            for offset, length in ((0, 0), (0, 1), (777, 100), (len(shards[0]) - 3, 3)):
+                self.assertEqual(codec.reconstruct_range(survivors, index, offset, length), shards[index][offset:offset + length])
+        with self.assertRaisesRegex(_rs.LengthError, "the window ends at"):
+            codec.reconstruct_range(survivors, 1, len(shards[0]), 1)
+        with self.assertRaisesRegex(ValueError, "shard index 6 out of range"):
+            codec.reconstruct_range(survivors, 6, 0, 1)
+
+    def test_too_few_shards(self) -> None:
+        codec = _rs.ShardCodec(10, 4)
+        shards = codec.split(b"x" * 1000)
+        survivors = {i: shards[i] for i in range(14) if i not in (0, 3, 7, 11, 13)}
+        with self.assertRaisesRegex(_rs.UncorrectableError, "only 9 shards present, need 10: 1 more required") as ctx:
+            codec.join(survivors)
+        self.assertEqual((ctx.exception.detected_errors, ctx.exception.capacity), (5, 4))
+        with self.assertRaisesRegex(_rs.UncorrectableError, "need 10: 10 more"):
+            codec.reconstruct({})
+
+    def test_small_inputs_and_errors(self) -> None:
+        codec = _rs.ShardCodec(3, 2)
+        self.assertEqual(repr(codec), "ShardCodec(k=3, m=2)")
+        self.assertEqual((codec.k, codec.m, codec.n), (3, 2, 5))
+        for data in (b"", b"a", bytes(range(256))):
+            shards = codec.split(data)
+            self.assertEqual(codec.join({3: shards[3], 4: shards[4], 1: bytearray(shards[1])}), data)
+        shards = codec.split(b"hello")
+        with self.assertRaisesRegex(_rs.LengthError, "shard 2 has length 1"):
+            codec.join({0: shards[0], 1: shards[1], 2: b"x"})
+        with self.assertRaisesRegex(ValueError, "shard index 5 out of range"):
+            codec.join({5: shards[0]})
+        with self.assertRaisesRegex(_rs.LengthError, "expected 5 shard slots, got 2"):
+            codec.reconstruct(shards[:2])
+        for k, m in ((0, 2), (2, 0), (250, 6)):
+            with self.assertRaises(_rs.ParameterError):
+                _rs.ShardCodec(k, m)
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class PlanTests(unittest.TestCase):
+    def test_more_parity_never_increases_failure_rate(self) -> None:
+        for nsize, p, burst_len, depth in ((255, 1e-2, None, 1), (255, 1e-4, None, 1), (64, 0.05, None, 1), (255, 1e-3, 6, 2)):
+            with self.subTest(nsize=nsize, p=p, burst_len=burst_len, depth=depth):
+                rates = [_rs.block_failure_rate(nsize, nsym, p, burst_len, depth) for nsym in range(1, nsize)]
+                self.assertTrue(all(b <= a for a, b in zip(rates, rates[1:])))
+                self.assertTrue(all(0.0 <= r <= 1.0 for r in rates))
+
+    def test_smallest_nsym_meeting_target(self) -> None:
+        result = _rs.plan(255, 1e-2, 1e-9)
+        self.assertEqual((result.nsize, result.nsym, result.t), (255, 34, 17))
+        self.assertLessEqual(result.block_failure_rate, 1e-9)
+        self.assertEqual([nsym for nsym, _ in result.candidates], list(range(1, 35)))
+        self.assertGreater(result.candidates[-2][1], 1e-9)
+        self.assertEqual(result.candidates[-1][1], result.block_failure_rate)
+        self.assertAlmostEqual(result.code_rate, 221 / 255)
+        self.assertAlmostEqual(result.overhead, 34 / 221)
+        self.assertIn("nsym=34", repr(result))
+        # t = 16 at p = 1e-2: the exact tail is 1.400228694454406e-09
+        self.assertAlmostEqual(_rs.block_failure_rate(255, 32, 1e-2) / 1.400228694454406e-09, 1.0, places=10)
+
+    def test_burst_length_and_interleaving(self) -> None:
+        plain = _rs.plan(255, 1e-3, 1e-12).nsym
+        self.assertGreater(_rs.plan(255, 1e-3, 1e-12, burst_len=8).nsym, plain)
+        self.assertEqual(_rs.plan(255, 1e-3, 1e-12, burst_len=8, depth=8).nsym, plain)
+
+    def test_invalid_arguments(self) -> None:
+        for args, kwargs in (((255, 1.5, 1e-3), {}), ((255, 1e-3, 0.0), {}), ((256, 1e-3, 1e-3), {}),
+                             ((255, 0.9, 1e-6), {}), ((255, 1e-3, 1e-3), {"depth": 0})):
+            with self.subTest(args=args, kwargs=kwargs), self.assertRaises(_rs.ParameterError):
+                _rs.plan(*args, **kwargs)
+        with self.assertRaisesRegex(_rs.ParameterError, "no nsym < nsize reaches"):
+            _rs.plan(255, 0.9, 1e-6)
+        with self.assertRaises(_rs.ParameterError):
+            _rs.block_failure_rate(255, 0, 1e-3)
+
+
+@unittest.skipIf(_rs is None or not hasattr(_rs, "MemoryModel"), "needs the extension built with the `sim` feature")
+class MemoryModelTests(unittest.TestCase):
+    MIX = {"bit": 0.01, "double_bit": 0.004, "device": 0.004, "row": 0.002}
+
+    def test_million_access_sweep_is_deterministic(self) -> None:
+        expected = {
+            "secded72": {"ce": 13587, "due": 6619, "sdc": 5},
+            "chipkill_x4": {"ce": 14139, "due": 6072, "sdc": 0},
+            "rs(36,32)": {"ce": 18114, "due": 2097, "sdc": 0},
+        }
+        for scheme, outcome in expected.items():
+            with self.subTest(scheme=scheme):
+                model = _rs.MemoryModel(scheme)
+                counters = model.sweep(10**6, self.MIX, seed=350)
+                self.assertEqual(counters, {"writes": 10**6, "reads": 10**6, "faults": 20211,
+                                            "clean": 979789, **outcome})
+                self.assertEqual(model.counters(), counters)
+                self.assertEqual(_rs.MemoryModel(scheme, seed=99).sweep(10**6, self.MIX, seed=350), counters)
+
+    def test_single_accesses(self) -> None:
+        line = bytes(range(64))
+        model = _rs.MemoryModel("secded72")
+        self.assertEqual((model.beats, model.bus_bits, model.devices, model.device_width, model.line_bits),
+                         (8, 72, 9, 8, 576))
+        model.write(0x40, line)
+        self.assertEqual(model.read(0x7F), (line, "clean"))
+        model.inject_bit(0x40, 72 + 3)
+        self.assertEqual(model.read(0x40), (line, "ce"))
+        model.inject_bit(0x40, 72 + 5)
+        self.assertEqual(model.read(0x40)[1], "due")
+        self.assertEqual(model.read(0x1000), (bytes(64), "clean"))
+        self.assertEqual(model.counters(), {"writes": 1, "reads": 4, "faults": 2, "clean": 2, "ce": 1, "due": 1, "sdc": 0})
+        model.reset_counters()
+        self.assertEqual(model.counters()["reads"], 0)
+
+        chipkill = _rs.MemoryModel("chipkill_x4", seed=1)
+        chipkill.write(0, line)
+        chipkill.inject_device(0, 35)
+        self.assertEqual(chipkill.read(0), (line, "ce"))
+        chipkill.inject_row_burst(0, beat=2, start=4, length=8)
+        self.assertEqual(chipkill.read(0)[1], "due")
+
+        rs = _rs.MemoryModel("RS(36, 32)")
+        self.assertEqual((rs.scheme, rs.beats, rs.devices), ("rs(36,32)", 2, 36))
+        rs.write(0, line)
+        rs.inject_device(0, 7, pattern=0xFFFF)
+        self.assertEqual(rs.read(0), (line, "ce"))
+        self.assertIn("rs(36,32)", repr(rs))
+
+    def test_invalid_arguments(self) -> None:
+        for scheme in ("secded64", "rs(34,30)", "rs(300,64)"):
+            with self.subTest(scheme=scheme), self.assertRaises(_rs.ParameterError):
+                _rs.MemoryModel(scheme)
+        model = _rs.MemoryModel("secded72")
+        with self.assertRaises(_rs.LengthError):
+            model.write(0, bytes(63))
+        with self.assertRaisesRegex(_rs.ParameterError, "bit 576 out of range"):
+            model.inject_bit(0, 576)
+        with self.assertRaisesRegex(_rs.ParameterError, "device 9 out of range"):
+            model.inject_device(0, 9)
+        with self.assertRaises(_rs.ParameterError):
+            model.inject_row_burst(0, 8)
+        with self.assertRaisesRegex(_rs.ParameterError, "sum to at most 1"):
+            model.sweep(10, {"bit": 0.7, "row": 0.7})
+        with self.assertRaisesRegex(_rs.ParameterError, "unknown fault kind"):
+            model.sweep(10, {"bits": 0.1})
+        self.assertEqual(model.counters()["faults"], 0)
+
+
+@unittest.skipIf(_rs is None or numpy is None or not hasattr(_rs, "raid"),
+                 "needs numpy and the extension built with the `numpy` feature")
+class RaidTests(unittest.TestCase):
+    def setUp(self) -> None:
+        rng = numpy.random.default_rng(351)
+        self.blocks = rng.integers(0, 256, size=(12, 1 << 20), dtype=numpy.uint8)
+        self.p, self.q = _rs.raid.compute_pq(self.blocks)
+
+    def test_two_lost_megabyte_blocks_are_rebuilt(self) -> None:
+        self.assertEqual((self.p.shape, self.q.shape, self.p.dtype), ((1 << 20,), (1 << 20,), numpy.uint8))
+        numpy.testing.assert_array_equal(self.p, numpy.bitwise_xor.reduce(self.blocks, axis=0))
+        blocks = [self.blocks[i] for i in range(12)]
+        blocks[3] = blocks[9] = None
+        rebuilt = _rs.raid.reconstruct(blocks, self.p, self.q)
+        self.assertEqual(len(rebuilt), 12)
+        self.assertIs(rebuilt[0], blocks[0])
+        for i in (3, 9):
+            self.assertEqual(rebuilt[i].tobytes(), self.blocks[i].tobytes())
+
+        # One data block and P lost: Q alone recovers it
+        blocks = [self.blocks[i] for i in range(12)]
+        blocks[11] = None
+        numpy.testing.assert_array_equal(_rs.raid.reconstruct(blocks, None, self.q)[11], self.blocks[11])
+        blocks[0] = None
+        with self.assertRaises(_rs.UncorrectableError):
+            _rs.raid.reconstruct(blocks, None, self.q)
+
+    def test_update_pq_matches_recompute(self) -> None:
+        p, q = self.p.copy(), self.q.copy()
+        new = numpy.random.default_rng(7).integers(0, 256, size=1 << 20, dtype=numpy.uint8)
+        _rs.raid.update_pq(p, q, 5, self.blocks[5], new)
+        self.blocks[5] = new
+        expected_p, expected_q = _rs.raid.compute_pq(self.blocks)
+        numpy.testing.assert_array_equal(p, expected_p)
+        numpy.testing.assert_array_equal(q, expected_q)
+
+    def test_shape_and_dtype_are_validated(self) -> None:
+        with self.assertRaisesRegex(ValueError, "blocks has 1 dimensions"):
+            _rs.raid.compute_pq(self.blocks[0])
+        with self.assertRaisesRegex(ValueError, "dtype int16"):
+            _rs.raid.compute_pq(self.blocks[:, :8].astype(numpy.int16))
+        blocks = [self.blocks[i] for i in range(12)]
+        blocks[2] = None
+        blocks[4] = self.blocks[4][:100]
+        with self.assertRaisesRegex(ValueError, r"blocks\[4\] has length 100, expected blocklen=1048576"):
+            _rs.raid.reconstruct(blocks, self.p, self.q)
+        with self.assertRaisesRegex(ValueError, r"blocks\[0\] has length 8"):
+            _rs.raid.update_pq(self.p, self.q, 0, self.blocks[0], self.blocks[0][:8])
+        with self.assertRaisesRegex(ValueError, "not writeable"):
+            self.p.flags.writeable = False
+            _rs.raid.update_pq(self.p, self.q, 0, self.blocks[0], self.blocks[1])
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class CodecStatsTests(unittest.TestCase):
+    ZERO = {"blocks_encoded": 0, "blocks_decoded": 0, "blocks_corrected": 0, "symbols_corrected": 0,
+            "decode_failures": 0}
+
+    def test_mixed_workload(self) -> None:
+        codec = _rs.RSCodec(8, 40)
+        self.assertEqual(codec.stats(), self.ZERO)
+        codewords = [codec.encode(bytes([i]) * 32) for i in range(10)]
+        for i, codeword in enumerate(codewords):
+            damaged = bytearray(codeword)
+            for e in range(i % 4):
+                damaged[3 * e] ^= 0x40
+            codec.decode(bytes(damaged))
+        unfixable = bytearray(codewords[0])
+        for e in range(5):
+            unfixable[e] ^= 0xFF
+        with self.assertRaises(_rs.UncorrectableError):
+            codec.decode(bytes(unfixable))
+        result = codec.decode_detailed(bytes(unfixable), erase_pos=range(5))
+        self.assertEqual(result.num_corrected, 5)
+        with self.assertRaises(_rs.LengthError):
+            codec.decode(bytes(39))  # rejected before decoding: not counted
+        # 10 blocks with 0, 1, 2, 3, 0, 1, ... errors, one failure, one 5-erasure fix
+        self.assertEqual(codec.stats(), {"blocks_encoded": 10, "blocks_decoded": 12, "blocks_corrected": 8,
+                                         "symbols_corrected": 1 + 2 + 3 + 1 + 2 + 3 + 1 + 5,
+                                         "decode_failures": 1})
+        self.assertEqual(pickle.loads(pickle.dumps(codec)).stats(), self.ZERO)
+        # Copies are separate instances with their own counters
+        before = codec.stats()
+        for duplicate in (copy.copy(codec), copy.deepcopy(codec)):
+            self.assertIsNot(duplicate, codec)
+            self.assertEqual(duplicate.stats(), self.ZERO)
+            duplicate.encode(bytes(32))
+            duplicate.reset_stats()
+        self.assertEqual(codec.stats(), before)
+        codec.reset_stats()
+        self.assertEqual(codec.stats(), self.ZERO)
+
+    def test_chunked_calls_count_every_chunk(self) -> None:
+        codec = _rs.RSCodec(8, 40)
+        stream = bytearray(codec.encode_chunked(bytes(range(80))))  # chunks of 32, 32, 16
+        self.assertEqual(codec.stats()["blocks_encoded"], 3)
+        stream[1] ^= 1
+        stream[45] ^= 2
+        stream[46] ^= 3
+        codec.decode_chunked(bytes(stream))
+        self.assertEqual(codec.stats(), {"blocks_encoded": 3, "blocks_decoded": 3, "blocks_corrected": 2,
+                                         "symbols_corrected": 3, "decode_failures": 0})
+        for pos in range(40, 45):
+            stream[pos] ^= 0xFF
+        with self.assertRaises(_rs.UncorrectableError):
+            codec.decode_chunked(bytes(stream))
+        # The first chunk decodes, the second fails and the third is never reached
+        self.assertEqual(codec.stats()["blocks_decoded"], 5)
+        self.assertEqual(codec.stats()["decode_failures"], 1)
+
+    def test_threads_lose_no_counts(self) -> None:
+        codec = _rs.RSCodec(32, 255)
+        blocks = _corrupted_blocks(64)
+        with ThreadPoolExecutor(max_workers=8) as pool:
+            list(pool.map(lambda _: [codec.decode(b) for b in blocks], range(16)))
+            list(pool.map(lambda b: codec.encode(b[:223]), blocks * 8))
+        self.assertEqual(codec.stats(), {"blocks_encoded": 512, "blocks_decoded": 1024, "blocks_corrected": 1024,
+                                         "symbols_corrected": 1024 * 16, "decode_failures": 0})
+
+
+@unittest.skipIf(_rs is None, "Rust extension ecc_model._rs is not built")
+class PoolTests(unittest.TestCase):
+    def test_overlapping_jobs_collected_in_any_order(self) -> None:
+        blocks = _corrupted_blocks(64)
+        messages = [b[:223] for b in blocks]
+        with _rs.Pool(threads=4) as pool:
+            jobs = []
+            for i in range(12):
+                batch = blocks[i:] + blocks[:i]
+                jobs.append((pool.submit_decode(32, 255, batch), _rs.decode_batch(32, 255, batch)))
+                jobs.append((pool.submit_encode(32, 255, messages[i:]), _rs.encode_batch(32, 255, messages[i:])))
+            random.Random(358).shuffle(jobs)
+            # Results are picked up from other Python threads, in an order unrelated to submission
+            with ThreadPoolExecutor(max_workers=6) as executor:
+                results = list(executor.map(lambda job: job[0].result(timeout=60), jobs))
+        for (job, expected), result in zip(jobs, results):
+            self.assertTrue(job.done())
+            self.assertEqual(result, expected)
+            self.assertEqual(job.result(), expected)
+
+    def test_failing_job_does_not_poison_the_pool(self) -> None:
+        blocks = _corrupted_blocks(20, nsym=8, nsize=64)
+        broken = list(blocks)
+        broken[5] = bytes(b ^ 0xFF for b in broken[5][:10]) + broken[5][10:]
+        with _rs.Pool(threads=2) as pool:
+            failing = pool.submit_decode(8, 64, broken)
+            reported = pool.submit_decode(8, 64, broken, errors="report")
+            after = [pool.submit_decode(8, 64, blocks) for _ in range(4)]
+            with self.assertRaisesRegex(_rs.UncorrectableError, r"codeword 5: ") as ctx:
+                failing.result()
+            self.assertIsNone(ctx.exception.results[5])
+            self.assertEqual(sorted(ctx.exception.errors), [5])
+            with self.assertRaises(_rs.UncorrectableError):
+                failing.result()  # every call re-raises
+            self.assertIsInstance(reported.result()[5], _rs.BlockError)
+            expected = _rs.decode_batch(8, 64, blocks)
+            for job in after + [pool.submit_decode(8, 64, blocks)]:
+                self.assertEqual(job.result(), expected)
+
+    def test_cancel_and_timeout(self) -> None:
+        blocks = _corrupted_blocks(2000)
+        with _rs.Pool(threads=1) as pool:
+            busy = pool.submit_decode(32, 255, blocks)
+            queued = pool.submit_decode(32, 255, blocks[:10])
+            waiting = pool.submit_encode(32, 255, [bytes(223)])
+            with self.assertRaises(TimeoutError):
+                waiting.result(timeout=0)
+            self.assertTrue(queued.cancel())
+            self.assertTrue(queued.cancelled() and queued.done())
+            with self.assertRaises(concurrent.futures.CancelledError):
+                queued.result()
+            self.assertEqual(len(busy.result()), 2000)
+            self.assertFalse(busy.cancel())
+            self.assertFalse(busy.cancelled())
+            self.assertEqual(waiting.result(timeout=60), [_rs.encode(32, 255, bytes(223))])
+        with self.assertRaises(_rs.ECCError):
+            pool.submit_encode(32, 255, [bytes(223)])
+
+    def test_event_loop_stays_responsive(self) -> None:
+        blocks = _corrupted_blocks(2000)
+
+        async def main() -> tuple:
+            ticks = 0
+            job = pool.submit_decode(32, 255, blocks)
+            waiter = asyncio.get_running_loop().run_in_executor(None, job.result)
+            while not waiter.done():
+                ticks += 1
+                await asyncio.sleep(0.001)
+            return ticks, await waiter
+
+        with _rs.Pool(threads=2) as pool:
+            ticks, results = asyncio.run(main())
+        self.assertEqual(len(results), 2000)
+        self.assertGreater(ticks, 5, f"event loop ticked {ticks} times during a 2000-block pool decode")
+
+    def test_arguments_checked_at_submit(self) -> None:
+        with self.assertRaises(_rs.ParameterError):
+            _rs.Pool(threads=0)
+        with _rs.Pool(threads=1) as pool:
+            self.assertEqual(pool.threads, 1)
+            with self.assertRaisesRegex(_rs.LengthError, r"message 1: length 5"):
+                pool.submit_encode(2, 8, [bytes(6), bytes(5)])
+            with self.assertRaisesRegex(_rs.LengthError, r"codeword 0: length 7"):
+                pool.submit_decode(2, 8, [bytes(7)])
+            with self.assertRaises(_rs.ParameterError):
+                pool.submit_decode(2, 8, [bytes(8)], errors="ignore")
+            with self.assertRaises(_rs.ParameterError):
+                pool.submit_encode(2, 8, []).result(timeout=-1)
+        self.assertGreaterEqual(_rs.Pool().threads, 1)
+
+
+@unittest.skipIf(_rs is None or not sysconfig.get_config_var("Py_GIL_DISABLED"),
+                 "needs the extension on a free-threaded (3.13t) interpreter")
+class FreeThreadingTests(unittest.TestCase):
+    def test_import_keeps_the_gil_disabled(self) -> None:
+        self.assertFalse(sys._is_gil_enabled())
+
+    def test_one_codec_hammered_from_eight_threads(self) -> None:
+        codec = _rs.RSCodec(32, 255)
+        blocks = _corrupted_blocks(64)
+        expected = [_rs.decode(32, 255, b) for b in blocks]
+        barrier = threading.Barrier(8)
+
+        def work(seed: int) -> bool:
+            barrier.wait()
+            ok = True
+            for round_ in range(50):
+                i = (seed * 31 + round_) % len(blocks)
+                message, positions = codec.decode(blocks[i])
+                ok &= (message, positions) == expected[i]
+                ok &= codec.encode(message) == _rs.encode(32, 255, message)
+                ok &= codec.decode_detailed(blocks[i]).num_corrected == 16
+            return ok
+
+        with ThreadPoolExecutor(max_workers=8) as pool:
+            self.assertTrue(all(pool.map(work, range(8))))
+        self.assertEqual(codec.stats()["blocks_decoded"], 8 * 50 * 2)
+        self.assertEqual(codec.stats()["blocks_encoded"], 8 * 50)
+
+
+if __name__ == "__main__":
+    unittest.main()